@@ -0,0 +1,53 @@
+/*!
+Measures cloning and collecting large owned strings.
+
+Cloning a zero-terminated string must find its length before it can copy it; these compare that against `Slice`, which stores its length, and against collecting the same units from an iterator.
+
+This requires a nightly compiler.
+*/
+#![cfg(feature="nightly")]
+#![cfg_attr(feature="nightly", feature(test))]
+extern crate strffi;
+extern crate test;
+
+use strffi::ZWCString;
+use strffi::alloc::Malloc;
+use strffi::encoding::{Wide, WUnit};
+use strffi::sea::SeaString;
+use strffi::structure::{Slice, ZeroTerm};
+use test::Bencher;
+
+/**
+Returns 1 Mi wide units of text.
+*/
+fn sample() -> Vec<WUnit> {
+    (0..1 << 20).map(|i| WUnit((0x61 + i % 26) as _)).collect()
+}
+
+#[bench]
+fn bench_clone_zwcstring(b: &mut Bencher) {
+    let s = ZWCString::new(&sample()).unwrap();
+    b.bytes = (s.as_units().len() * ::std::mem::size_of::<WUnit>()) as u64;
+    b.iter(|| s.clone());
+}
+
+#[bench]
+fn bench_clone_slice(b: &mut Bencher) {
+    let s = SeaString::<Slice, Wide, Malloc>::new(&sample()).unwrap();
+    b.bytes = (s.as_units().len() * ::std::mem::size_of::<WUnit>()) as u64;
+    b.iter(|| s.clone());
+}
+
+#[bench]
+fn bench_collect_zero_term(b: &mut Bencher) {
+    let units = sample();
+    b.bytes = (units.len() * ::std::mem::size_of::<WUnit>()) as u64;
+    b.iter(|| units.iter().cloned().collect::<SeaString<ZeroTerm, Wide, Malloc>>());
+}
+
+#[bench]
+fn bench_collect_slice(b: &mut Bencher) {
+    let units = sample();
+    b.bytes = (units.len() * ::std::mem::size_of::<WUnit>()) as u64;
+    b.iter(|| units.iter().cloned().collect::<SeaString<Slice, Wide, Malloc>>());
+}
@@ -0,0 +1,57 @@
+/*!
+Measures searching roughly 1 MiB of text for a unit which only occurs at the very end.
+
+`find_unit` takes the `memchr` path for single byte units; `find_unit_position` is the obvious loop, for comparison.
+
+This requires a nightly compiler.
+*/
+#![cfg(feature="nightly")]
+#![cfg_attr(feature="nightly", feature(test))]
+extern crate strffi;
+extern crate test;
+
+use strffi::encoding::{Utf8, Utf8Unit, Utf16, Utf16Unit};
+use strffi::sea::SeStr;
+use strffi::structure::Slice;
+use test::Bencher;
+
+const LEN: usize = 1 << 20;
+
+fn utf8() -> Vec<Utf8Unit> {
+    let mut units: Vec<_> = (0..LEN).map(|i| Utf8Unit(b'a' + (i % 26) as u8)).collect();
+    units[LEN - 1] = Utf8Unit(b'=');
+    units
+}
+
+#[bench]
+fn find_unit(b: &mut Bencher) {
+    let units = utf8();
+    let s = SeStr::<Slice, Utf8>::new(&units);
+    b.bytes = LEN as u64;
+    b.iter(|| s.find(Utf8Unit(b'=')).unwrap());
+}
+
+#[bench]
+fn find_unit_position(b: &mut Bencher) {
+    let units = utf8();
+    let s = SeStr::<Slice, Utf8>::new(&units);
+    b.bytes = LEN as u64;
+    b.iter(|| s.as_units().iter().position(|&u| u == Utf8Unit(b'=')).unwrap());
+}
+
+#[bench]
+fn find_slice(b: &mut Bencher) {
+    let units = utf8();
+    let s = SeStr::<Slice, Utf8>::new(&units);
+    let needle = [units[LEN - 2], Utf8Unit(b'=')];
+    b.bytes = LEN as u64;
+    b.iter(|| s.find(&needle[..]).unwrap());
+}
+
+#[bench]
+fn find_unit_utf16(b: &mut Bencher) {
+    let units: Vec<_> = utf8().into_iter().map(|u| Utf16Unit(u.0 as u16)).collect();
+    let s = SeStr::<Slice, Utf16>::new(&units);
+    b.bytes = (LEN * 2) as u64;
+    b.iter(|| s.find(Utf16Unit(0x3d)).unwrap());
+}
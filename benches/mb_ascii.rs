@@ -0,0 +1,54 @@
+/*!
+Measures multibyte to wide conversion of ASCII text, which skips `mbrtowc` in ASCII transparent locales.
+
+This runs under `C.UTF-8`, so it requires Linux, and a nightly compiler.
+*/
+#![cfg(all(target_os="linux", feature="nightly"))]
+#![cfg_attr(feature="nightly", feature(test))]
+extern crate libc;
+extern crate strffi;
+extern crate test;
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{MultiByte, MbUnit, Wide};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+use test::Bencher;
+
+fn set_utf8() {
+    unsafe {
+        let r = libc::setlocale(libc::LC_ALL, b"C.UTF-8\0".as_ptr() as *const _);
+        assert!(!r.is_null());
+    }
+}
+
+/**
+Returns roughly 1 MiB of text built from `line`.
+*/
+fn sample(line: &str) -> Vec<MbUnit> {
+    line.repeat((1 << 20) / line.len()).bytes().map(|b| MbUnit(b as _)).collect()
+}
+
+#[bench]
+fn bench_ascii(b: &mut Bencher) {
+    set_utf8();
+    let units = sample("The quick brown fox jumps over the lazy dog.\n");
+    let s = SeStr::<Slice, MultiByte>::new(&units);
+    b.bytes = units.len() as u64;
+    b.iter(|| {
+        let r: SeaString<ZeroTerm, Wide, Malloc> = s.transcode_to().unwrap();
+        r
+    });
+}
+
+#[bench]
+fn bench_mixed(b: &mut Bencher) {
+    set_utf8();
+    let units = sample("The quick brown fox jumps over the lazy dog; garçon, naïve, 😀.\n");
+    let s = SeStr::<Slice, MultiByte>::new(&units);
+    b.bytes = units.len() as u64;
+    b.iter(|| {
+        let r: SeaString<ZeroTerm, Wide, Malloc> = s.transcode_to().unwrap();
+        r
+    });
+}
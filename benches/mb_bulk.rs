@@ -0,0 +1,70 @@
+/*!
+Compares converting a whole zero-terminated multibyte string with `mbsrtowcs` against converting it one character at a time.
+
+This runs under `C.UTF-8`, so it requires Linux, and a nightly compiler.
+*/
+#![cfg(all(target_os="linux", feature="nightly"))]
+#![cfg_attr(feature="nightly", feature(test))]
+extern crate libc;
+extern crate strffi;
+extern crate test;
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{MultiByte, Wide};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+use test::Bencher;
+
+fn set_utf8() {
+    unsafe {
+        let r = libc::setlocale(libc::LC_ALL, b"C.UTF-8\0".as_ptr() as *const _);
+        assert!(!r.is_null());
+    }
+}
+
+/**
+Returns roughly 1 MiB of mixed text.
+*/
+fn sample() -> SeaString<ZeroTerm, MultiByte, Malloc> {
+    let line = "The quick brown fox jumps over the lazy dog; garçon, naïve, 日本語, 😀.\n";
+    let text = line.repeat((1 << 20) / line.len());
+    SeaString::new(SeStr::<Slice, MultiByte>::from_bytes(text.as_bytes()).as_units()).unwrap()
+}
+
+#[bench]
+fn bench_into_string_iter(b: &mut Bencher) {
+    set_utf8();
+    let s = sample();
+    b.bytes = s.as_units().len() as u64;
+    b.iter(|| s.into_string().unwrap());
+}
+
+#[bench]
+fn bench_into_string_bulk(b: &mut Bencher) {
+    set_utf8();
+    let s = sample();
+    b.bytes = s.as_units().len() as u64;
+    b.iter(|| s.into_string_bulk().unwrap());
+}
+
+#[bench]
+fn bench_to_wide_iter(b: &mut Bencher) {
+    set_utf8();
+    let s = sample();
+    b.bytes = s.as_units().len() as u64;
+    b.iter(|| {
+        let r: SeaString<ZeroTerm, Wide, Malloc> = s.transcode_to().unwrap();
+        r
+    });
+}
+
+#[bench]
+fn bench_to_wide_bulk(b: &mut Bencher) {
+    set_utf8();
+    let s = sample();
+    b.bytes = s.as_units().len() as u64;
+    b.iter(|| {
+        let r: SeaString<ZeroTerm, Wide, Malloc> = s.transcode_to_wide_bulk().unwrap();
+        r
+    });
+}
@@ -0,0 +1,57 @@
+/*!
+Times building a string one unit at a time.
+
+`Slice` strings grow geometrically, so the time per unit should stay roughly constant as the string gets longer.  `ZeroTerm` strings reallocate on every push, so they are quadratic; they are included (at smaller sizes) for comparison.
+
+This requires a nightly compiler.
+*/
+#![cfg(feature="nightly")]
+#![cfg_attr(feature="nightly", feature(test))]
+extern crate strffi;
+extern crate test;
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{Utf8, Utf8Unit};
+use strffi::sea::SeaString;
+use strffi::structure::{Slice, ZeroTerm};
+use test::Bencher;
+
+fn push_slice(n: usize) -> SeaString<Slice, Utf8, Malloc> {
+    let mut s = SeaString::new(&[]).unwrap();
+    for i in 0..n {
+        s.push_unit(Utf8Unit(b'a' + (i % 26) as u8)).unwrap();
+    }
+    s
+}
+
+fn push_zero_term(n: usize) -> SeaString<ZeroTerm, Utf8, Malloc> {
+    let mut s = SeaString::new(&[]).unwrap();
+    for i in 0..n {
+        s.push_unit(Utf8Unit(b'a' + (i % 26) as u8)).unwrap();
+    }
+    s
+}
+
+#[bench]
+fn bench_slice_4k(b: &mut Bencher) {
+    b.bytes = 4 << 10;
+    b.iter(|| push_slice(4 << 10));
+}
+
+#[bench]
+fn bench_slice_64k(b: &mut Bencher) {
+    b.bytes = 64 << 10;
+    b.iter(|| push_slice(64 << 10));
+}
+
+#[bench]
+fn bench_zero_term_1k(b: &mut Bencher) {
+    b.bytes = 1 << 10;
+    b.iter(|| push_zero_term(1 << 10));
+}
+
+#[bench]
+fn bench_zero_term_4k(b: &mut Bencher) {
+    b.bytes = 4 << 10;
+    b.iter(|| push_zero_term(4 << 10));
+}
@@ -0,0 +1,106 @@
+/*!
+Counts the reallocations made while transcoding a large string.
+
+Each benchmark prints the number of reallocations for a single conversion before timing it.  With accurate `size_hint`s, `collect` can allocate close to the final size up front, rather than growing the buffer one doubling at a time.
+
+`transcode_to` avoids the buffer altogether, by counting its output before allocating the result; `bench_utf8_to_utf16_via_vec` keeps the old collect-then-copy approach for comparison.
+
+This requires a nightly compiler.
+*/
+#![cfg(feature="nightly")]
+#![cfg_attr(feature="nightly", feature(test))]
+extern crate strffi;
+extern crate test;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use strffi::alloc::Malloc;
+use strffi::encoding::{Utf8, Utf8Unit, Utf16, Utf16Unit};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+use test::Bencher;
+
+struct CountingAlloc;
+
+static REALLOCS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        REALLOCS.fetch_add(1, Ordering::SeqCst);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+/**
+Returns roughly 1 MiB of mostly-ASCII text.
+*/
+fn sample() -> String {
+    let line = "The quick brown fox jumps over the lazy dog; garçon, naïve, 😀.\n";
+    line.repeat((1 << 20) / line.len())
+}
+
+/**
+Runs `f` once, and reports how many reallocations it made.
+*/
+fn report<F, R>(name: &str, f: F) where F: FnOnce() -> R {
+    let before = REALLOCS.load(Ordering::SeqCst);
+    let r = f();
+    let after = REALLOCS.load(Ordering::SeqCst);
+    drop(r);
+    println!("{}: {} reallocations", name, after - before);
+}
+
+#[bench]
+fn bench_utf8_to_utf16(b: &mut Bencher) {
+    let units: Vec<_> = sample().bytes().map(Utf8Unit).collect();
+    let s = SeStr::<Slice, Utf8>::new(&units);
+    let transcode = || -> SeaString<ZeroTerm, Utf16, Malloc> { s.transcode_to().unwrap() };
+    report("utf8 -> utf16", &transcode);
+    b.bytes = units.len() as u64;
+    b.iter(&transcode);
+}
+
+#[bench]
+fn bench_utf8_to_utf16_via_vec(b: &mut Bencher) {
+    // This is how `transcode_to` used to work: collect into a temporary, then copy into the destination.
+    let units: Vec<_> = sample().bytes().map(Utf8Unit).collect();
+    let s = SeStr::<Slice, Utf8>::new(&units);
+    let transcode = || -> SeaString<ZeroTerm, Utf16, Malloc> {
+        let tmp: Vec<Utf16Unit> = s.transcode_to_iter::<Utf16>().map(Result::unwrap).collect();
+        SeaString::new(&tmp).unwrap()
+    };
+    report("utf8 -> utf16 via Vec", &transcode);
+    b.bytes = units.len() as u64;
+    b.iter(&transcode);
+}
+
+#[bench]
+fn bench_utf16_into_string(b: &mut Bencher) {
+    let units: Vec<_> = sample().encode_utf16().map(Utf16Unit).collect();
+    let s = SeStr::<Slice, Utf16>::new(&units);
+    let into_string = || s.into_string().unwrap();
+    report("utf16 -> String", &into_string);
+    b.bytes = (units.len() * 2) as u64;
+    b.iter(&into_string);
+}
+
+#[bench]
+fn bench_zero_term_utf16_into_string(b: &mut Bencher) {
+    // Zero-terminated strings don't know their length up front, so their unit iterators can't give a useful `size_hint`.
+    let s = SeaString::<ZeroTerm, Utf16, Malloc>::from_str(&sample()).unwrap();
+    let into_string = || s.into_string().unwrap();
+    report("zero-terminated utf16 -> String", &into_string);
+    b.bytes = (s.as_units().len() * 2) as u64;
+    b.iter(&into_string);
+}
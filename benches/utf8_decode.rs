@@ -0,0 +1,80 @@
+/*!
+Measures UTF-8 validation and decoding over ASCII, Cyrillic, and emoji text.
+
+`naive` decodes one character at a time with `DecodeUnits`, for comparison with the `Utf8` to `CheckedUnicode` transcoder and with `is_valid_utf8`.
+
+This requires a nightly compiler.
+*/
+#![cfg(feature="nightly")]
+#![cfg_attr(feature="nightly", feature(test))]
+extern crate strffi;
+extern crate test;
+
+use strffi::encoding::{DecodeUnits, Utf8, Utf8Unit};
+use strffi::sea::SeStr;
+use strffi::structure::Slice;
+use test::Bencher;
+
+/**
+Returns roughly 1 MiB of text built from `line`.
+*/
+fn sample(line: &str) -> Vec<Utf8Unit> {
+    line.repeat((1 << 20) / line.len()).bytes().map(Utf8Unit).collect()
+}
+
+fn ascii() -> Vec<Utf8Unit> {
+    sample("The quick brown fox jumps over the lazy dog.\n")
+}
+
+fn cyrillic() -> Vec<Utf8Unit> {
+    sample("Съешь же ещё этих мягких французских булок, да выпей чаю.\n")
+}
+
+fn emoji() -> Vec<Utf8Unit> {
+    sample("😀😁😂🤣😃😄😅😆😉😊😋😎😍😘🥰😗\n")
+}
+
+fn naive(units: &[Utf8Unit]) -> usize {
+    let mut at = 0;
+    let mut n = 0;
+    while let Some(r) = Utf8::decode_next(units, &mut at) {
+        r.unwrap();
+        n += 1;
+    }
+    n
+}
+
+macro_rules! benches {
+    ($($sample:ident: $naive:ident, $into_string:ident, $is_valid:ident;)*) => {
+        $(
+            #[bench]
+            fn $naive(b: &mut Bencher) {
+                let units = $sample();
+                b.bytes = units.len() as u64;
+                b.iter(|| naive(&units));
+            }
+
+            #[bench]
+            fn $into_string(b: &mut Bencher) {
+                let units = $sample();
+                let s = SeStr::<Slice, Utf8>::new(&units);
+                b.bytes = units.len() as u64;
+                b.iter(|| s.into_string().unwrap());
+            }
+
+            #[bench]
+            fn $is_valid(b: &mut Bencher) {
+                let units = $sample();
+                let s = SeStr::<Slice, Utf8>::new(&units);
+                b.bytes = units.len() as u64;
+                b.iter(|| s.is_valid_utf8());
+            }
+        )*
+    };
+}
+
+benches! {
+    ascii: bench_ascii_naive, bench_ascii_into_string, bench_ascii_is_valid;
+    cyrillic: bench_cyrillic_naive, bench_cyrillic_into_string, bench_cyrillic_is_valid;
+    emoji: bench_emoji_naive, bench_emoji_into_string, bench_emoji_is_valid;
+}
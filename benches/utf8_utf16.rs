@@ -0,0 +1,46 @@
+/*!
+Compares direct `Utf8` to `Utf16` transcoding against the composed path through `CheckedUnicode`.
+
+This requires a nightly compiler.
+*/
+#![cfg(feature="nightly")]
+#![cfg_attr(feature="nightly", feature(test))]
+extern crate strffi;
+extern crate test;
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{CheckedUnicode, Utf8, Utf8Unit, Utf16};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+use test::Bencher;
+
+/**
+Returns roughly 1 MiB of mostly-ASCII UTF-8.
+*/
+fn sample() -> Vec<Utf8Unit> {
+    let line = "The quick brown fox jumps over the lazy dog; garçon, naïve, 😀.\n";
+    line.repeat((1 << 20) / line.len()).bytes().map(Utf8Unit).collect()
+}
+
+#[bench]
+fn bench_direct(b: &mut Bencher) {
+    let units = sample();
+    let s = SeStr::<Slice, Utf8>::new(&units);
+    b.bytes = units.len() as u64;
+    b.iter(|| {
+        let r: SeaString<ZeroTerm, Utf16, Malloc> = s.transcode_to().unwrap();
+        r
+    });
+}
+
+#[bench]
+fn bench_composed(b: &mut Bencher) {
+    let units = sample();
+    let s = SeStr::<Slice, Utf8>::new(&units);
+    b.bytes = units.len() as u64;
+    b.iter(|| {
+        let chars: Vec<char> = s.transcode_to_iter::<CheckedUnicode>().map(Result::unwrap).collect();
+        let r: SeaString<ZeroTerm, Utf16, Malloc> = SeStr::<Slice, CheckedUnicode>::new(&chars).transcode_to().unwrap();
+        r
+    });
+}
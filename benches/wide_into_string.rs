@@ -0,0 +1,65 @@
+/*!
+Measures `into_string` from the wide and UTF-16/UTF-32 encodings over roughly 1 MiB of mixed text.
+
+`generic` collects the `CheckedUnicode` transcoder directly, for comparison with the specialised paths taken by `into_string`.
+
+This requires a nightly compiler.
+*/
+#![cfg(feature="nightly")]
+#![cfg_attr(feature="nightly", feature(test))]
+extern crate strffi;
+extern crate test;
+
+use strffi::encoding::{CheckedUnicode, Utf16, Utf16Unit, Utf32, Utf32Unit, Wide, WUnit};
+use strffi::sea::SeStr;
+use strffi::structure::Slice;
+use test::Bencher;
+
+fn sample() -> String {
+    let line = "The quick brown fox. Съешь же ещё этих булок. 😀😁😂\n";
+    line.repeat((1 << 20) / line.len())
+}
+
+fn wide() -> Vec<WUnit> {
+    if strffi::wide_is_utf16!() {
+        sample().encode_utf16().map(|u| WUnit(u as _)).collect()
+    } else {
+        sample().chars().map(|c| WUnit(c as _)).collect()
+    }
+}
+
+fn utf16() -> Vec<Utf16Unit> {
+    sample().encode_utf16().map(Utf16Unit).collect()
+}
+
+fn utf32() -> Vec<Utf32Unit> {
+    sample().chars().map(|c| Utf32Unit(c as u32)).collect()
+}
+
+macro_rules! benches {
+    ($($enc:ident: $units:ident, $into_string:ident, $generic:ident;)*) => {
+        $(
+            #[bench]
+            fn $into_string(b: &mut Bencher) {
+                let units = $units();
+                let s = SeStr::<Slice, $enc>::new(&units);
+                b.bytes = sample().len() as u64;
+                b.iter(|| s.into_string().unwrap());
+            }
+
+            #[bench]
+            fn $generic(b: &mut Bencher) {
+                let units = $units();
+                let s = SeStr::<Slice, $enc>::new(&units);
+                b.bytes = sample().len() as u64;
+                b.iter(|| s.transcode_to_iter::<CheckedUnicode>().collect::<Result<String, _>>().unwrap());
+            }
+        )*
+    };
+}
+
+benches! {
+    Wide: wide, wide_into_string, wide_generic;
+    Utf16: utf16, utf16_into_string, utf16_generic;
+    Utf32: utf32, utf32_into_string, utf32_generic;
+}
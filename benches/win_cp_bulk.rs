@@ -0,0 +1,91 @@
+/*!
+Compares the per-character code page conversions against the bulk ones, which make a single call to `MultiByteToWideChar` or `WideCharToMultiByte`.
+
+This requires Windows and a nightly compiler.
+*/
+#![cfg(all(windows, feature="nightly"))]
+#![cfg_attr(feature="nightly", feature(test))]
+#[macro_use] extern crate strffi;
+extern crate test;
+
+use strffi::alloc::Malloc;
+use strffi::encoding::Wide;
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+use test::Bencher;
+
+declare_code_page! {
+    /// Windows-1252.
+    pub enum Cp1252;
+    unit Cp1252Unit;
+    prefix "Cp1252";
+    code_page 1252;
+}
+
+/**
+Returns roughly 1 MiB of Windows-1252.
+*/
+fn sample() -> Vec<Cp1252Unit> {
+    let line = b"The quick brown fox jumps over the lazy dog; gar\xe7on, na\xefve, \x80.\n";
+    line.repeat((1 << 20) / line.len()).into_iter().map(Cp1252Unit).collect()
+}
+
+#[bench]
+fn bench_transcode_to(b: &mut Bencher) {
+    let units = sample();
+    let s = SeStr::<Slice, Cp1252>::new(&units);
+    b.bytes = units.len() as u64;
+    b.iter(|| {
+        let r: SeaString<ZeroTerm, Wide, Malloc> = s.transcode_to().unwrap();
+        r
+    });
+}
+
+#[bench]
+fn bench_transcode_to_wide_bulk(b: &mut Bencher) {
+    let units = sample();
+    let s = SeStr::<Slice, Cp1252>::new(&units);
+    b.bytes = units.len() as u64;
+    b.iter(|| {
+        let r: SeaString<ZeroTerm, Wide, Malloc> = s.transcode_to_wide_bulk().unwrap();
+        r
+    });
+}
+
+#[bench]
+fn bench_into_string(b: &mut Bencher) {
+    let units = sample();
+    let s = SeStr::<Slice, Cp1252>::new(&units);
+    b.bytes = units.len() as u64;
+    b.iter(|| s.into_string().unwrap());
+}
+
+#[bench]
+fn bench_into_string_bulk(b: &mut Bencher) {
+    let units = sample();
+    let s = SeStr::<Slice, Cp1252>::new(&units);
+    b.bytes = units.len() as u64;
+    b.iter(|| s.into_string_bulk().unwrap());
+}
+
+#[bench]
+fn bench_from_wide(b: &mut Bencher) {
+    let units = sample();
+    let ws: SeaString<Slice, Wide, Malloc> = SeStr::<Slice, Cp1252>::new(&units).transcode_to().unwrap();
+    b.bytes = units.len() as u64;
+    b.iter(|| {
+        let r: SeaString<Slice, Cp1252, Malloc> = ws.transcode_to().unwrap();
+        r
+    });
+}
+
+#[bench]
+fn bench_from_wide_bulk(b: &mut Bencher) {
+    let units = sample();
+    let ws: SeaString<Slice, Wide, Malloc> = SeStr::<Slice, Cp1252>::new(&units).transcode_to().unwrap();
+    b.bytes = units.len() as u64;
+    b.iter(|| {
+        let r: SeaString<Slice, Cp1252, Malloc> = ws.transcode_to_code_page_bulk().unwrap();
+        r
+    });
+}
@@ -1,6 +1,9 @@
 /*!
 Allocation types and traits.
 */
+use std::alloc::{alloc_zeroed, dealloc, Layout};
+use std::cell::RefCell;
+use std::cmp;
 use std::error::Error as StdError;
 use std::fmt::{self, Display};
 use std::mem;
@@ -8,6 +11,8 @@ pub use self::rust::Rust;
 
 use libc::{self, c_void};
 
+#[cfg(feature="alloc-stats")] pub mod stats;
+
 /**
 Abstracts over different memory allocators.
 
@@ -38,6 +43,17 @@ pub trait Allocator {
     */
     unsafe fn free(ptr: Self::Pointer, align: usize);
 
+    /**
+    Returns the number of bytes actually reserved by a call to `alloc_bytes` with the given arguments.
+
+    This includes any headers or padding the allocator adds, but not the bookkeeping overhead of any underlying heap, which generally cannot be known.  It is intended for memory profiling, and is only a best-effort figure.
+
+    The default implementation assumes there is no overhead.
+    */
+    fn allocated_bytes(bytes: usize, _align: usize) -> usize {
+        bytes
+    }
+
     /**
     Returns a string which can be used to uniquely identify this allocator in debug output.
 
@@ -48,6 +64,17 @@ pub trait Allocator {
     fn debug_prefix() -> &'static str;
 }
 
+/**
+Indicates that memory from this allocator may be freed on a different thread from the one which allocated it, and accessed from several threads at once.
+
+Owned strings are only `Send` and `Sync` when their allocator implements this trait; see `SeaString`.
+
+# Safety
+
+Implementing this trait asserts that `free` may be called on any thread, for any live allocation, and that allocations remain valid regardless of which threads are running.  This is not implemented for `Arena`, whose memory belongs to the thread which created it, and is reclaimed when that thread leaves the arena's scope.
+*/
+pub unsafe trait ThreadSafeAllocator: Allocator {}
+
 /**
 This trait defines the required interface for allocation errors.
 */
@@ -58,6 +85,13 @@ pub trait AllocatorError: StdError {
     This exists to allow string structures to safely indicate that the size of an allocation exceeded some intrinsic limit.
     */
     fn overflow() -> Self;
+
+    /**
+    Construct an error indicating that the contents of a zero-terminated string contained a zero unit at the given offset, other than as the last unit.
+
+    This exists to allow zero-terminated structures to refuse contents which would be silently truncated.
+    */
+    fn interior_zero(at: usize) -> Self;
 }
 
 /**
@@ -68,17 +102,26 @@ pub enum AllocError {
     Failed,
     CannotAlign,
     SizeOverflow,
+    NoArena,
+    InteriorZeroAt(usize),
 }
 
 impl AllocatorError for AllocError {
     fn overflow() -> Self {
         AllocError::SizeOverflow
     }
+
+    fn interior_zero(at: usize) -> Self {
+        AllocError::InteriorZeroAt(at)
+    }
 }
 
 impl Display for AllocError {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        write!(fmt, "{}", self.description())
+        match *self {
+            AllocError::InteriorZeroAt(at) => write!(fmt, "interior zero at offset {}", at),
+            _ => write!(fmt, "{}", self.description()),
+        }
     }
 }
 
@@ -88,15 +131,33 @@ impl StdError for AllocError {
             AllocError::Failed => "failed to allocate memory",
             AllocError::CannotAlign => "cannot satisfy requested alignment",
             AllocError::SizeOverflow => "overflow while computing size",
+            AllocError::NoArena => "no arena is active on this thread",
+            AllocError::InteriorZeroAt(_) => "interior zero",
         }
     }
 }
 
 /**
 Represents the C runtime heap allocator.
+
+Allocations with an alignment no greater than that of a pointer are passed directly to `calloc`.  Larger alignments are satisfied by over-allocating, and storing the pointer returned by `calloc` immediately before the aligned pointer.
+
+# Warning
+
+An over-aligned allocation does not start at the pointer `calloc` returned, so it must **not** be released with the C runtime's `free`, whether directly or by foreign code which takes ownership of the string.  Doing so corrupts the heap.  Only allocations with an alignment no greater than that of a pointer, which includes those of every unit type in this crate, can be handed to foreign code to free.
 */
 pub enum Malloc {}
 
+impl Malloc {
+    /**
+    Determines whether an allocation with the given alignment requires over-allocation.
+    */
+    fn needs_align(align: usize) -> bool {
+        // A conservative guess.
+        align > mem::align_of::<usize>()
+    }
+}
+
 impl Allocator for Malloc {
     type AllocError = AllocError;
     type Pointer = *mut ();
@@ -104,36 +165,269 @@ impl Allocator for Malloc {
     fn alloc_bytes(bytes: usize, align: usize) -> Result<*mut (), AllocError> {
         // println!("-- Malloc::alloc_bytes({:?}, {:?})", bytes, _align);
         unsafe {
-            // A conservative guess.
-            if align > mem::align_of::<usize>() {
+            if !Malloc::needs_align(align) {
+                let ptr = libc::calloc(bytes, 1);
+                return if ptr.is_null() {
+                    Err(AllocError::Failed)
+                } else {
+                    Ok(ptr as *mut ())
+                };
+            }
+
+            if !align.is_power_of_two() {
                 return Err(AllocError::CannotAlign);
             }
 
-            let ptr = libc::calloc(bytes, 1);
-            if ptr.is_null() {
-                Err(AllocError::Failed)
-            } else {
-                Ok(ptr as *mut ())
+            // Leave room for the original pointer, plus enough slack to align the result.
+            let extra = align.checked_add(mem::size_of::<*mut c_void>())
+                .ok_or(AllocError::SizeOverflow)?;
+            let total = bytes.checked_add(extra).ok_or(AllocError::SizeOverflow)?;
+
+            let base = libc::calloc(total, 1);
+            if base.is_null() {
+                return Err(AllocError::Failed);
             }
+
+            let start = base as usize + mem::size_of::<*mut c_void>();
+            let aligned = (start + align - 1) & !(align - 1);
+            let ptr = aligned as *mut *mut c_void;
+
+            // Save the original pointer for later.
+            *ptr.offset(-1) = base;
+
+            Ok(ptr as *mut ())
         }
     }
 
-    unsafe fn free(ptr: *mut (), _align: usize) {
+    unsafe fn free(ptr: *mut (), align: usize) {
         // println!("-- Malloc::free(_, {:?})", _align);
         if !ptr.is_null() {
-            libc::free(ptr as *mut c_void);
+            if Malloc::needs_align(align) {
+                let base = *(ptr as *mut *mut c_void).offset(-1);
+                libc::free(base);
+            } else {
+                libc::free(ptr as *mut c_void);
+            }
+        }
+    }
+
+    fn allocated_bytes(bytes: usize, align: usize) -> usize {
+        if Malloc::needs_align(align) {
+            bytes.saturating_add(align).saturating_add(mem::size_of::<*mut c_void>())
+        } else {
+            bytes
         }
     }
 
     fn debug_prefix() -> &'static str { "C" }
 }
 
+// `free` is backed by the C runtime heap, which is shared by all threads.
+unsafe impl ThreadSafeAllocator for Malloc {}
+
+/**
+Represents the Rust global heap allocator, as exposed by `std::alloc`.
+
+Unlike `Rust`, this allocator does not require a nightly compiler.  Allocations are routed through whichever allocator has been registered with `#[global_allocator]`, if any.
+
+The size of each allocation is stored immediately before the returned pointer, so that the layout can be reconstructed on deallocation.
+*/
+pub enum Global {}
+
+impl Global {
+    /**
+    Returns the alignment to actually allocate with, and the number of bytes reserved in front of the returned pointer.
+    */
+    fn layout_params(align: usize) -> (usize, usize) {
+        let align = cmp::max(align, mem::align_of::<usize>());
+        let header = cmp::max(align, mem::size_of::<usize>());
+        (align, header)
+    }
+}
+
+impl Allocator for Global {
+    type AllocError = AllocError;
+    type Pointer = *mut ();
+
+    fn alloc_bytes(bytes: usize, align: usize) -> Result<*mut (), AllocError> {
+        let (align, header) = Global::layout_params(align);
+        let total = bytes.checked_add(header).ok_or(AllocError::SizeOverflow)?;
+        let layout = Layout::from_size_align(total, align)
+            .map_err(|_| AllocError::CannotAlign)?;
+
+        unsafe {
+            let ptr = alloc_zeroed(layout);
+            if ptr.is_null() {
+                return Err(AllocError::Failed);
+            }
+
+            // Save the size for later.
+            let ptr = ptr.offset(header as isize);
+            *(ptr as *mut usize).offset(-1) = total;
+
+            Ok(ptr as *mut ())
+        }
+    }
+
+    unsafe fn free(ptr: *mut (), align: usize) {
+        if !ptr.is_null() {
+            let (align, header) = Global::layout_params(align);
+            let total = *(ptr as *mut usize).offset(-1);
+            let ptr = (ptr as *mut u8).offset(-(header as isize));
+            dealloc(ptr, Layout::from_size_align_unchecked(total, align));
+        }
+    }
+
+    fn allocated_bytes(bytes: usize, align: usize) -> usize {
+        let (_, header) = Global::layout_params(align);
+        bytes.saturating_add(header)
+    }
+
+    fn debug_prefix() -> &'static str { "G" }
+}
+
+// The global allocator must be usable from any thread, as `Box` and `Vec` are `Send`.
+unsafe impl ThreadSafeAllocator for Global {}
+
+/**
+Represents a thread-local bump allocator.
+
+Allocations are carved out of large, zeroed chunks of memory belonging to the innermost active arena on the current thread.  Freeing an individual allocation does nothing; instead, all memory belonging to an arena is reclaimed at once when its scope ends.  This makes allocating large numbers of short-lived strings considerably cheaper.
+
+An arena is made active with `Arena::scope`.  Attempting to allocate when no arena is active will fail with `AllocError::NoArena`.
+*/
+pub enum Arena {}
+
+thread_local! {
+    static ARENAS: RefCell<Vec<ArenaState>> = RefCell::new(Vec::new());
+}
+
+impl Arena {
+    /**
+    The default size of each chunk of memory allocated by an arena, in bytes.
+    */
+    pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+    /**
+    Runs `f` with a new arena active on the current thread.
+
+    Arenas may be nested; allocations are always made from the innermost active arena.  The arena is deactivated, and its memory reclaimed, when `f` returns or panics.
+
+    # Safety
+
+    Any string allocated by the arena *must not* be used after this method returns.  In particular, `f` must not return, or otherwise smuggle out, any such string.  The only thing that may be done with such a string once the scope has ended is to drop it.
+    */
+    pub unsafe fn scope<F, R>(f: F) -> R where F: FnOnce() -> R {
+        Arena::scope_with_chunk_size(Arena::DEFAULT_CHUNK_SIZE, f)
+    }
+
+    /**
+    Runs `f` with a new arena active on the current thread, using the given chunk size.
+
+    Allocations larger than `chunk_size` are given a dedicated chunk.
+
+    # Safety
+
+    See `scope`.
+    */
+    pub unsafe fn scope_with_chunk_size<F, R>(chunk_size: usize, f: F) -> R where F: FnOnce() -> R {
+        struct PopOnDrop;
+
+        impl Drop for PopOnDrop {
+            fn drop(&mut self) {
+                ARENAS.with(|arenas| { arenas.borrow_mut().pop(); });
+            }
+        }
+
+        ARENAS.with(|arenas| arenas.borrow_mut().push(ArenaState::new(chunk_size)));
+        let _pop = PopOnDrop;
+        f()
+    }
+
+    /**
+    Returns `true` if an arena is active on the current thread.
+    */
+    pub fn is_active() -> bool {
+        ARENAS.with(|arenas| !arenas.borrow().is_empty())
+    }
+}
+
+impl Allocator for Arena {
+    type AllocError = AllocError;
+    type Pointer = *mut ();
+
+    fn alloc_bytes(bytes: usize, align: usize) -> Result<*mut (), AllocError> {
+        ARENAS.with(|arenas| {
+            match arenas.borrow_mut().last_mut() {
+                Some(arena) => arena.alloc_bytes(bytes, align),
+                None => Err(AllocError::NoArena),
+            }
+        })
+    }
+
+    unsafe fn free(_ptr: *mut (), _align: usize) {
+        // Memory is reclaimed when the arena's scope ends.
+    }
+
+    fn debug_prefix() -> &'static str { "Ar" }
+}
+
+struct ArenaState {
+    chunks: Vec<Vec<u8>>,
+    used: usize,
+    chunk_size: usize,
+}
+
+impl ArenaState {
+    fn new(chunk_size: usize) -> Self {
+        ArenaState {
+            chunks: vec![],
+            used: 0,
+            chunk_size: chunk_size,
+        }
+    }
+
+    fn alloc_bytes(&mut self, bytes: usize, align: usize) -> Result<*mut (), AllocError> {
+        if !align.is_power_of_two() {
+            return Err(AllocError::CannotAlign);
+        }
+
+        if let Some(ptr) = self.bump(bytes, align) {
+            return Ok(ptr);
+        }
+
+        // Doesn't fit; start a new chunk with enough slack to align the allocation.
+        let min_size = bytes.checked_add(align).ok_or(AllocError::SizeOverflow)?;
+        self.chunks.push(vec![0; cmp::max(self.chunk_size, min_size)]);
+        self.used = 0;
+
+        self.bump(bytes, align).ok_or(AllocError::Failed)
+    }
+
+    fn bump(&mut self, bytes: usize, align: usize) -> Option<*mut ()> {
+        let chunk = match self.chunks.last_mut() {
+            Some(chunk) => chunk,
+            None => return None,
+        };
+
+        let base = chunk.as_mut_ptr() as usize;
+        let start = base.checked_add(self.used)?.checked_add(align - 1)? & !(align - 1);
+        let end = start.checked_add(bytes)?;
+        if end > base + chunk.len() {
+            return None;
+        }
+
+        self.used = end - base;
+        Some(start as *mut ())
+    }
+}
+
 #[cfg(all(feature="nightly", feature="nightly-alloc"))]
 mod rust {
     use std::cmp;
     use std::mem;
     use rust_alloc::heap;
-    use super::{Allocator, AllocError};
+    use super::{Allocator, AllocError, ThreadSafeAllocator};
 
     /**
     Represents the Rust runtime heap allocator.
@@ -175,13 +469,20 @@ mod rust {
             }
         }
 
+        fn allocated_bytes(bytes: usize, align: usize) -> usize {
+            bytes.saturating_add(cmp::min(mem::align_of::<usize>(), align))
+        }
+
         fn debug_prefix() -> &'static str { "R" }
     }
+
+    // Backed by the Rust heap, which is shared by all threads.
+    unsafe impl ThreadSafeAllocator for Rust {}
 }
 
 #[cfg(not(all(feature="nightly", feature="nightly-alloc")))]
 mod rust {
-    use super::{Allocator, AllocError};
+    use super::{Allocator, AllocError, ThreadSafeAllocator};
 
     /**
     Represents the Rust runtime heap allocator.
@@ -219,6 +520,14 @@ mod rust {
             }
         }
 
+        fn allocated_bytes(bytes: usize, _align: usize) -> usize {
+            // One word for the length, rounded up to a whole number of words.
+            (bytes.saturating_add(15) / 8).saturating_mul(8)
+        }
+
         fn debug_prefix() -> &'static str { "R" }
     }
+
+    // Backed by the Rust heap, which is shared by all threads.
+    unsafe impl ThreadSafeAllocator for Rust {}
 }
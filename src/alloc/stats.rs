@@ -0,0 +1,182 @@
+/*!
+Allocation statistics, for memory profiling.
+
+With the `alloc-stats` feature enabled, every `SeaString` reports the memory it takes ownership of, and the memory it gives up, to a hook installed with `set_stats_hook`.  Byte counts are those given by `SeaString::allocated_bytes`, so an allocation, less any shrinking reported along the way, always matches its eventual free.
+
+Adopting a foreign string with `SeaString::from_ptr` is reported as an allocation, and handing one back with `SeaString::into_ptr` as a free, since those are the points at which the memory becomes, and stops being, the string's responsibility.
+
+`counting_hook` is a ready-made hook which simply keeps running totals.
+*/
+use std::mem;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/**
+Whether an event gained or released memory.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AllocEventKind {
+    /**
+    Memory was allocated, or adopted from foreign code.
+    */
+    Alloc,
+
+    /**
+    Memory was freed, or handed over to foreign code.
+    */
+    Free,
+
+    /**
+    A string was shortened in place, and no longer accounts for some of its memory.
+
+    This is not a separate allocation; it only changes the size that the string's eventual `Free` will report.
+    */
+    Shrink,
+}
+
+/**
+Describes a single allocation or free.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AllocEvent {
+    /**
+    Whether memory was gained or released.
+    */
+    pub kind: AllocEventKind,
+
+    /**
+    The allocator responsible, as given by `Allocator::debug_prefix`.
+    */
+    pub allocator: &'static str,
+
+    /**
+    The number of bytes involved, including any overhead which the allocator is known to add.
+    */
+    pub bytes: usize,
+}
+
+/**
+The type of a statistics hook.
+
+Hooks may be called from any thread, and must not themselves create or drop any `SeaString`.
+*/
+pub type StatsHook = fn(&AllocEvent);
+
+/**
+The installed hook, stored as a function pointer; zero means no hook is installed.
+*/
+static HOOK: AtomicUsize = AtomicUsize::new(0);
+
+/**
+Installs a statistics hook, returning the previously installed hook.
+
+Passing `None` removes the hook.
+*/
+pub fn set_stats_hook(hook: Option<StatsHook>) -> Option<StatsHook> {
+    let new = hook.map(|f| f as usize).unwrap_or(0);
+    match HOOK.swap(new, Ordering::SeqCst) {
+        0 => None,
+        old => Some(unsafe { mem::transmute::<usize, StatsHook>(old) }),
+    }
+}
+
+/**
+Passes an event to the installed hook, if there is one.
+*/
+pub(crate) fn report(event: AllocEvent) {
+    match HOOK.load(Ordering::SeqCst) {
+        0 => (),
+        hook => unsafe { mem::transmute::<usize, StatsHook>(hook)(&event) },
+    }
+}
+
+static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+static FREES: AtomicUsize = AtomicUsize::new(0);
+static ALLOC_BYTES: AtomicUsize = AtomicUsize::new(0);
+static FREE_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/**
+Running totals kept by `counting_hook`.
+*/
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AllocCounts {
+    /**
+    The number of allocations.
+    */
+    pub allocs: usize,
+
+    /**
+    The number of frees.
+    */
+    pub frees: usize,
+
+    /**
+    The total number of bytes allocated.
+    */
+    pub alloc_bytes: usize,
+
+    /**
+    The total number of bytes freed, including those released by shrinking a string in place.
+    */
+    pub free_bytes: usize,
+}
+
+impl AllocCounts {
+    /**
+    Returns the number of allocations which have not yet been freed.
+    */
+    pub fn live(&self) -> usize {
+        self.allocs.wrapping_sub(self.frees)
+    }
+
+    /**
+    Returns the number of bytes which have not yet been freed.
+    */
+    pub fn live_bytes(&self) -> usize {
+        self.alloc_bytes.wrapping_sub(self.free_bytes)
+    }
+}
+
+/**
+A statistics hook which keeps process-wide running totals of every event, regardless of allocator.
+
+Install it with `set_stats_hook(Some(counting_hook))`, and read the totals with `counts`.
+*/
+pub fn counting_hook(event: &AllocEvent) {
+    match event.kind {
+        AllocEventKind::Alloc => {
+            ALLOCS.fetch_add(1, Ordering::SeqCst);
+            ALLOC_BYTES.fetch_add(event.bytes, Ordering::SeqCst);
+        },
+        AllocEventKind::Free => {
+            FREES.fetch_add(1, Ordering::SeqCst);
+            FREE_BYTES.fetch_add(event.bytes, Ordering::SeqCst);
+        },
+        AllocEventKind::Shrink => {
+            FREE_BYTES.fetch_add(event.bytes, Ordering::SeqCst);
+        },
+    }
+}
+
+/**
+Returns the totals kept by `counting_hook`.
+
+The totals are read one at a time, so they may be inconsistent with one another if strings are being allocated or freed concurrently.
+*/
+pub fn counts() -> AllocCounts {
+    AllocCounts {
+        allocs: ALLOCS.load(Ordering::SeqCst),
+        frees: FREES.load(Ordering::SeqCst),
+        alloc_bytes: ALLOC_BYTES.load(Ordering::SeqCst),
+        free_bytes: FREE_BYTES.load(Ordering::SeqCst),
+    }
+}
+
+/**
+Resets the totals kept by `counting_hook` to zero.
+*/
+pub fn reset_counts() {
+    ALLOCS.store(0, Ordering::SeqCst);
+    FREES.store(0, Ordering::SeqCst);
+    ALLOC_BYTES.store(0, Ordering::SeqCst);
+    FREE_BYTES.store(0, Ordering::SeqCst);
+}
@@ -25,6 +25,9 @@ See the `encoding` module.
 | Prefix  | Name        | Encoding |
 | ------- | ----------- | -------- |
 | `A`     | `Ascii`     | 7-bit ASCII. |
+| `C16`   | `Char16`    | C11 `char16_t` encoding.  Only UTF-16 if `__STDC_UTF_16__` is defined. |
+| `C32`   | `Char32`    | C11 `char32_t` encoding.  Only UTF-32 if `__STDC_UTF_32__` is defined. |
+| `CUtf8` | `CheckedUtf8` | Guaranteed valid UTF-8. |
 | `Jni`   | `JniMtf8`   | JNI-style "modified" UTF-8. |
 | `L`     | `Latin1`    | 8-bit Latin-1. |
 | `Mb`    | `MultiByte` | Current thread-local C runtime multibyte encoding. |
@@ -32,11 +35,19 @@ See the `encoding` module.
 | `Raw16` | `Raw16`     | Raw 16-bit data. |
 | `Utf8`  | `Utf8`      | Possibly invalid UTF-8. |
 | `Utf16` | `Utf16`     | Possibly invalid UTF-16. |
+| `Utf16Be` | `Utf16Be` | Possibly invalid big-endian UTF-16. |
+| `Utf16Le` | `Utf16Le` | Possibly invalid little-endian UTF-16. |
 | `Utf32` | `Utf32`     | Possibly invalid UTF-32. |
+| `Utf32Be` | `Utf32Be` | Possibly invalid big-endian UTF-32. |
+| `Utf32Le` | `Utf32Le` | Possibly invalid little-endian UTF-32. |
 | `U`     | `CheckedUnicode` | Guaranteed valid Unicode.  Should **not** be used for FFI. |
 | `W`     | `Wide`      | Current thread-local C runtime wide character encoding. |
-| `Wa`    | `WinAnsi`   | Current thread-local Windows ANSI code page. |
+| `W16`   | `Wide16`    | Possibly invalid 16-bit wide encoding (UTF-16).  Uses the `W` prefix on Windows. |
+| `W32`   | `Wide32`    | Possibly invalid 32-bit wide encoding (UTF-32).  Uses the `W` prefix elsewhere. |
+| `Wa`    | `WinAnsi`   | Windows ANSI code page, as used by the `A`-suffixed Windows API functions. |
+| `Wo`    | `WinOem`    | Windows OEM code page, as used by console applications. |
 | `Ww`    | `WinUnicode`| Equivalent to `Utf16`, assuming non-pathological compiler settings. |
+| `Wtf8`  | `Wtf8`      | Possibly invalid WTF-8; UTF-8 extended to hold unpaired surrogates. |
 
 ## Allocators
 
@@ -44,7 +55,9 @@ See the `alloc` module.
 
 | Prefix | Name         | Allocator |
 | ------ | ------------ | --------- |
+| `Ar`   | `Arena`      | Thread-local bump allocator; memory is reclaimed at the end of an `Arena::scope`. |
 | `C`    | `Malloc`     | C runtime heap allocator (*i.e.* `malloc`/`free`) |
+| `G`    | `Global`     | Rust global allocator, via `std::alloc`. |
 | `R`    | `Rust`       | Rust heap allocator. |
 | `Wsa`  | `WinSysAlloc` | Windows API `SysAlloc*` allocator.  Requires the `Bstr` structure. |
 
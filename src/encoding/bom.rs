@@ -0,0 +1,106 @@
+/*!
+Detecting byte order marks.
+
+A byte order mark (BOM) is the character U+FEFF placed at the very start of a string, where it identifies the encoding and byte order of what follows.  It is metadata, not content: a string which begins with a BOM should usually have it removed before being used.
+
+See `SeStr::detect_bom` and `SeStr::strip_bom`, and the `skip_bom` methods on the UTF-16 and UTF-32 decoders.
+*/
+
+/**
+The byte order of a multi-byte encoding.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Endianness {
+    /**
+    The least significant byte comes first.
+    */
+    Little,
+
+    /**
+    The most significant byte comes first.
+    */
+    Big,
+}
+
+impl Endianness {
+    /**
+    Returns the byte order of the host.
+    */
+    #[cfg(target_endian="little")]
+    pub fn native() -> Endianness {
+        Endianness::Little
+    }
+
+    /**
+    Returns the byte order of the host.
+    */
+    #[cfg(target_endian="big")]
+    pub fn native() -> Endianness {
+        Endianness::Big
+    }
+
+    /**
+    Returns the opposite byte order.
+    */
+    pub fn swap(self) -> Endianness {
+        match self {
+            Endianness::Little => Endianness::Big,
+            Endianness::Big => Endianness::Little,
+        }
+    }
+}
+
+/**
+A byte order mark found at the start of a byte string.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Bom {
+    /**
+    The UTF-8 BOM, `EF BB BF`.
+    */
+    Utf8,
+
+    /**
+    A UTF-16 BOM: `FF FE` for little-endian, `FE FF` for big-endian.
+    */
+    Utf16(Endianness),
+
+    /**
+    A UTF-32 BOM: `FF FE 00 00` for little-endian, `00 00 FE FF` for big-endian.
+    */
+    Utf32(Endianness),
+}
+
+impl Bom {
+    /**
+    Identifies the byte order mark at the start of `bytes`, if there is one.
+
+    A little-endian UTF-32 BOM begins with the little-endian UTF-16 BOM, so `FF FE 00 00` is always taken to be UTF-32.  This is only wrong for a UTF-16 string whose first character is U+0000.
+    */
+    pub fn detect(bytes: &[u8]) -> Option<Bom> {
+        if bytes.starts_with(&[0xff, 0xfe, 0x00, 0x00]) {
+            Some(Bom::Utf32(Endianness::Little))
+        } else if bytes.starts_with(&[0x00, 0x00, 0xfe, 0xff]) {
+            Some(Bom::Utf32(Endianness::Big))
+        } else if bytes.starts_with(&[0xef, 0xbb, 0xbf]) {
+            Some(Bom::Utf8)
+        } else if bytes.starts_with(&[0xff, 0xfe]) {
+            Some(Bom::Utf16(Endianness::Little))
+        } else if bytes.starts_with(&[0xfe, 0xff]) {
+            Some(Bom::Utf16(Endianness::Big))
+        } else {
+            None
+        }
+    }
+
+    /**
+    Returns the length of this byte order mark, in bytes.
+    */
+    pub fn len(&self) -> usize {
+        match *self {
+            Bom::Utf8 => 3,
+            Bom::Utf16(_) => 2,
+            Bom::Utf32(_) => 4,
+        }
+    }
+}
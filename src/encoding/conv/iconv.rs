@@ -0,0 +1,467 @@
+/*!
+Conversions between `CheckedUnicode` and any character set supported by the system `iconv`.
+
+Unlike `MultiByte`, these do not depend on the C runtime locale: each encoding names its character set explicitly, so strings in several different encodings can be converted side by side without calling `setlocale`.  Since encodings are uninhabited marker types, the character set is usually given by the `IconvEncoding` trait rather than a value; encodings for a specific character set can be declared with `declare_iconv_encoding!`.  Where the character set is only known at runtime, an `IconvCharset` can be used to transcode units of any byte encoding instead.
+
+Each transcoder opens its own conversion descriptor when it is created, and closes it when it is dropped, whether or not it ran to completion.
+
+This is only available on Unix platforms, with the `iconv` feature enabled.
+*/
+use std::char;
+use std::ffi::CString;
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use std::ptr;
+use libc::{c_char, c_int, size_t, E2BIG, EILSEQ, EINVAL};
+use encoding::{ByteEncoding, TranscodeTo, Unit, UnitIter, CheckedUnicode, Raw8, Raw8Unit};
+use encoding::conv::ErrorOffset;
+use ffi::iconv::{iconv_t, iconv_open, iconv, iconv_close};
+
+/**
+Implemented by encodings which correspond to a character set supported by `iconv`.
+
+Any encoding implementing this can be transcoded to and from `CheckedUnicode`.  Encodings for a specific character set can be declared with `declare_iconv_encoding!`.
+*/
+pub trait IconvEncoding: ByteEncoding {
+    /**
+    Returns the name `iconv_open` should use for the character set, such as `"SHIFT_JIS"`.
+
+    Which names are available depends on the platform's `iconv` implementation.  If the name is not supported, transcoding fails with `UnsupportedEncoding`.
+    */
+    fn iconv_name() -> &'static str;
+}
+
+/**
+A character set supported by `iconv`, chosen at runtime.
+
+This is the runtime counterpart to `IconvEncoding`: rather than being fixed by the encoding type, the character set is given by name when the transcoder is created.  The units themselves can be of any `ByteEncoding`; `decode` and `encode` use `Raw8`, since the units have no meaning of their own until the character set is known.
+
+```ignore
+let charset = IconvCharset("SHIFT_JIS");
+let s: Result<String, _> = charset.decode(units.iter().cloned()).collect();
+```
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IconvCharset(pub &'static str);
+
+impl IconvCharset {
+    /**
+    Returns the name `iconv_open` should use for the character set.
+    */
+    pub fn name(self) -> &'static str {
+        self.0
+    }
+
+    /**
+    Decodes raw bytes in this character set into `CheckedUnicode`.
+    */
+    pub fn decode<It>(self, iter: It) -> IconvToUniIter<Raw8, It::IntoIter>
+    where It: IntoIterator<Item=Raw8Unit> {
+        IconvToUniIter::with_charset(iter.into_iter(), self)
+    }
+
+    /**
+    Encodes characters into raw bytes in this character set.
+    */
+    pub fn encode<It>(self, iter: It) -> UniToIconvIter<Raw8, It::IntoIter>
+    where It: IntoIterator<Item=char> {
+        UniToIconvIter::with_charset(iter.into_iter(), self)
+    }
+}
+
+impl<E, It> TranscodeTo<CheckedUnicode> for UnitIter<E, It> where E: IconvEncoding, It: Iterator<Item=E::Unit> {
+    type Iter = IconvToUniIter<E, It>;
+    type Error = IconvToUniError;
+
+    fn transcode(self) -> Self::Iter {
+        IconvToUniIter::new(self.into_iter())
+    }
+}
+
+impl<E, It> TranscodeTo<E> for UnitIter<CheckedUnicode, It> where E: IconvEncoding, It: Iterator<Item=char> {
+    type Iter = UniToIconvIter<E, It>;
+    type Error = UniToIconvError;
+
+    fn transcode(self) -> Self::Iter {
+        UniToIconvIter::new(self.into_iter())
+    }
+}
+
+/**
+The `iconv` name for UTF-32 in native byte order.  Naming the byte order explicitly stops `iconv` from writing a byte order mark.
+*/
+#[cfg(target_endian="little")]
+const UTF32_NATIVE: &'static str = "UTF-32LE";
+#[cfg(target_endian="big")]
+const UTF32_NATIVE: &'static str = "UTF-32BE";
+
+/**
+The number of units (or characters) passed to `iconv` at a time.
+*/
+const CHUNK_UNITS: usize = 64;
+
+/**
+An open conversion descriptor, which is closed on drop.
+*/
+struct Descriptor(iconv_t);
+
+impl Descriptor {
+    /**
+    Opens a descriptor converting from `from` to `to`.  Returns `None` if either name contains a zero, or is not supported.
+    */
+    fn open(to: &str, from: &str) -> Option<Descriptor> {
+        let to = CString::new(to).ok()?;
+        let from = CString::new(from).ok()?;
+        let cd = unsafe { iconv_open(to.as_ptr(), from.as_ptr()) };
+        if cd as isize == -1 {
+            None
+        } else {
+            Some(Descriptor(cd))
+        }
+    }
+
+    /**
+    Converts as much of `input` as possible, appending the result to `out`, which is grown as needed.  If `input` is `None`, this writes whatever is needed to return to the initial shift state.
+
+    Returns the number of bytes consumed, and the `errno` value which stopped the conversion, if it did not consume everything.
+    */
+    fn convert(&mut self, input: Option<&[u8]>, out: &mut Vec<u8>) -> (usize, Result<(), c_int>) {
+        let in_len = input.map_or(0, |input| input.len());
+        let mut in_ptr = input.map_or(ptr::null_mut(), |input| input.as_ptr() as *mut c_char);
+        let mut in_left: size_t = in_len;
+        let mut room = CHUNK_UNITS * 4;
+        loop {
+            let start = out.len();
+            out.resize(start + room, 0);
+            let mut out_ptr = out[start..].as_mut_ptr() as *mut c_char;
+            let mut out_left: size_t = room;
+            let r = unsafe {
+                let (in_ptr, in_left) = match input {
+                    Some(_) => (&mut in_ptr as *mut _, &mut in_left as *mut _),
+                    None => (ptr::null_mut(), ptr::null_mut()),
+                };
+                iconv(self.0, in_ptr, in_left, &mut out_ptr, &mut out_left)
+            };
+            let written = room - out_left;
+            out.truncate(start + written);
+
+            if r != -1isize as size_t {
+                return (in_len - in_left, Ok(()));
+            }
+            match io::Error::last_os_error().raw_os_error().unwrap_or(0) {
+                E2BIG => {
+                    // The output didn't fit; if nothing fit at all, try again with more room.
+                    if written == 0 {
+                        room *= 2;
+                    }
+                },
+                errno => return (in_len - in_left, Err(errno)),
+            }
+        }
+    }
+}
+
+impl Drop for Descriptor {
+    fn drop(&mut self) {
+        unsafe {
+            iconv_close(self.0);
+        }
+    }
+}
+
+pub struct IconvToUniIter<E, It> {
+    iter: Option<It>,
+    cd: Option<Descriptor>,
+    // The offset of the first unit in `pending`.
+    at: usize,
+    pending: Vec<u8>,
+    // Converted UTF-32, in native byte order.
+    out: Vec<u8>,
+    out_at: usize,
+    // An error to report once everything converted before it has been returned.
+    fail: Option<IconvToUniError>,
+    _marker: PhantomData<E>,
+}
+
+impl<E, It> IconvToUniIter<E, It> where E: IconvEncoding {
+    pub fn new(iter: It) -> Self {
+        IconvToUniIter::with_charset(iter, IconvCharset(E::iconv_name()))
+    }
+}
+
+impl<E, It> IconvToUniIter<E, It> where E: ByteEncoding {
+    /**
+    Creates a decoder for units in `charset`, regardless of what `E` says about them.
+    */
+    pub fn with_charset(iter: It, charset: IconvCharset) -> Self {
+        IconvToUniIter {
+            iter: Some(iter),
+            cd: Descriptor::open(UTF32_NATIVE, charset.name()),
+            at: 0,
+            pending: vec![],
+            out: vec![],
+            out_at: 0,
+            fail: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<E, It> Iterator for IconvToUniIter<E, It> where E: ByteEncoding, It: Iterator<Item=E::Unit> {
+    type Item = Result<char, IconvToUniError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.out_at + 4 <= self.out.len() {
+                let mut cp = [0u8; 4];
+                cp.copy_from_slice(&self.out[self.out_at..self.out_at + 4]);
+                self.out_at += 4;
+                let c = char::from_u32(u32::from_ne_bytes(cp))
+                    .expect("iconv produced an invalid code point");
+                return Some(Ok(c));
+            }
+            self.out.clear();
+            self.out_at = 0;
+
+            if let Some(err) = self.fail.take() {
+                self.iter = None;
+                return Some(Err(err));
+            }
+
+            let err = {
+                let iter = match self.iter.as_mut() {
+                    Some(iter) => iter,
+                    None => return None,
+                };
+
+                let cd = match self.cd.as_mut() {
+                    Some(cd) => cd,
+                    None => {
+                        self.iter = None;
+                        return Some(Err(IconvToUniError::UnsupportedEncoding));
+                    },
+                };
+
+                let pending_len = self.pending.len();
+                self.pending.extend(iter.take(CHUNK_UNITS).map(|u| u.to_raw() as u8));
+                if self.pending.len() == pending_len {
+                    self.iter = None;
+                    return if self.pending.is_empty() {
+                        None
+                    } else {
+                        Some(Err(IconvToUniError::Incomplete))
+                    };
+                }
+
+                let (consumed, r) = cd.convert(Some(&self.pending), &mut self.out);
+                self.pending.drain(..consumed);
+                self.at += consumed;
+                r
+            };
+
+            match err {
+                // `EINVAL` means the chunk ended part-way through a character, which stays pending until more units arrive.
+                Ok(()) | Err(EINVAL) => (),
+                Err(_) => self.fail = Some(IconvToUniError::InvalidAt(self.at)),
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every unit produces at most one character.
+        let buffered = (self.out.len() - self.out_at) / 4;
+        match (self.iter.as_ref(), self.fail) {
+            (Some(iter), None) => {
+                let (_, hi) = iter.size_hint();
+                (buffered, hi.and_then(|hi| hi.checked_add(self.pending.len() + buffered + 1)))
+            },
+            (_, Some(_)) => (buffered + 1, Some(buffered + 1)),
+            (None, None) => (buffered, Some(buffered)),
+        }
+    }
+}
+
+pub struct UniToIconvIter<E, It> {
+    iter: Option<It>,
+    cd: Option<Descriptor>,
+    // The number of characters converted so far.
+    at: usize,
+    pending: Vec<u8>,
+    out: Vec<u8>,
+    out_at: usize,
+    fail: Option<UniToIconvError>,
+    _marker: PhantomData<E>,
+}
+
+impl<E, It> UniToIconvIter<E, It> where E: IconvEncoding {
+    pub fn new(iter: It) -> Self {
+        UniToIconvIter::with_charset(iter, IconvCharset(E::iconv_name()))
+    }
+}
+
+impl<E, It> UniToIconvIter<E, It> where E: ByteEncoding {
+    /**
+    Creates an encoder producing units in `charset`, regardless of what `E` says about them.
+    */
+    pub fn with_charset(iter: It, charset: IconvCharset) -> Self {
+        UniToIconvIter {
+            iter: Some(iter),
+            cd: Descriptor::open(charset.name(), UTF32_NATIVE),
+            at: 0,
+            pending: vec![],
+            out: vec![],
+            out_at: 0,
+            fail: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<E, It> Iterator for UniToIconvIter<E, It> where E: ByteEncoding, It: Iterator<Item=char> {
+    type Item = Result<E::Unit, UniToIconvError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.out_at < self.out.len() {
+                let b = self.out[self.out_at];
+                self.out_at += 1;
+                return Some(Ok(E::Unit::from_raw(b as u32)
+                    .expect("byte encoding rejected a byte")));
+            }
+            self.out.clear();
+            self.out_at = 0;
+
+            if let Some(err) = self.fail.take() {
+                self.iter = None;
+                return Some(Err(err));
+            }
+
+            let iter = match self.iter.as_mut() {
+                Some(iter) => iter,
+                None => return None,
+            };
+
+            let cd = match self.cd.as_mut() {
+                Some(cd) => cd,
+                None => {
+                    self.iter = None;
+                    return Some(Err(UniToIconvError::UnsupportedEncoding));
+                },
+            };
+
+            self.pending.clear();
+            for c in iter.take(CHUNK_UNITS) {
+                self.pending.extend_from_slice(&(c as u32).to_ne_bytes());
+            }
+
+            if self.pending.is_empty() {
+                // Stateful encodings may need to shift back to their initial state at the end.
+                self.iter = None;
+                if cd.convert(None, &mut self.out).1.is_err() {
+                    self.fail = Some(UniToIconvError::InvalidAt(self.at));
+                }
+                continue;
+            }
+
+            let (consumed, r) = cd.convert(Some(&self.pending), &mut self.out);
+            self.at += consumed / 4;
+            if r.is_err() {
+                self.fail = Some(UniToIconvError::InvalidAt(self.at));
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every character produces at least one unit, but there is no useful upper bound.  Encoding stops at the first error, so only one more item is certain.
+        let buffered = self.out.len() - self.out_at;
+        match (self.iter.as_ref(), self.fail) {
+            (Some(iter), None) => {
+                let (lo, _) = iter.size_hint();
+                (::std::cmp::min(lo, 1).saturating_add(buffered), None)
+            },
+            (_, Some(_)) => (buffered + 1, Some(buffered + 1)),
+            (None, None) => (buffered, Some(buffered)),
+        }
+    }
+}
+
+/**
+An error decoding a string with `iconv`.
+
+`InvalidAt` gives the offset of the first unit of a sequence which `iconv` rejected with `EILSEQ`.  `Incomplete` corresponds to `EINVAL` at the end of the string.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IconvToUniError {
+    InvalidAt(usize),
+    Incomplete,
+    UnsupportedEncoding,
+}
+
+impl fmt::Display for IconvToUniError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IconvToUniError::InvalidAt(at) => write!(fmt, "invalid unit at offset {}", at),
+            IconvToUniError::Incomplete => write!(fmt, "incomplete unit"),
+            IconvToUniError::UnsupportedEncoding => write!(fmt, "encoding not supported by iconv"),
+        }
+    }
+}
+
+impl ::std::error::Error for IconvToUniError {
+    fn description(&self) -> &str {
+        match *self {
+            IconvToUniError::InvalidAt(_) => "invalid unit",
+            IconvToUniError::Incomplete => "incomplete unit",
+            IconvToUniError::UnsupportedEncoding => "encoding not supported by iconv",
+        }
+    }
+}
+
+impl ErrorOffset for IconvToUniError {
+    fn offset(&self) -> Option<usize> {
+        match *self {
+            IconvToUniError::InvalidAt(at) => Some(at),
+            IconvToUniError::Incomplete => None,
+            IconvToUniError::UnsupportedEncoding => None,
+        }
+    }
+}
+
+/**
+An error encoding a string with `iconv`.
+
+`InvalidAt` gives the offset of a character which cannot be represented in the target encoding.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum UniToIconvError {
+    InvalidAt(usize),
+    UnsupportedEncoding,
+}
+
+impl fmt::Display for UniToIconvError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            UniToIconvError::InvalidAt(at) => write!(fmt, "invalid unit at offset {}", at),
+            UniToIconvError::UnsupportedEncoding => write!(fmt, "encoding not supported by iconv"),
+        }
+    }
+}
+
+impl ::std::error::Error for UniToIconvError {
+    fn description(&self) -> &str {
+        match *self {
+            UniToIconvError::InvalidAt(_) => "invalid unit",
+            UniToIconvError::UnsupportedEncoding => "encoding not supported by iconv",
+        }
+    }
+}
+
+impl ErrorOffset for UniToIconvError {
+    fn offset(&self) -> Option<usize> {
+        match *self {
+            UniToIconvError::InvalidAt(at) => Some(at),
+            UniToIconvError::UnsupportedEncoding => None,
+        }
+    }
+}
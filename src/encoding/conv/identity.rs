@@ -0,0 +1,89 @@
+/*!
+Identity "transcoders", from each encoding to itself.
+
+These exist so that generic code which transcodes from `E` into some fixed encoding continues to work when `E` *is* that encoding.  A blanket implementation over all encodings would overlap with the generic code page conversions, so each encoding gets its own implementation instead.
+*/
+use encoding::{TranscodeTo, Transparent, UnitIter, CheckedUnicode, CheckedUtf8, Char16, Char32, JniMtf8, MultiByte, Raw8,
+    Utf8, Utf16, Utf16Be, Utf16Le, Utf32, Utf32Be, Utf32Le, Wide16, Wide32, WinUnicode, Wtf8};
+#[cfg(windows)] use encoding::{WinAnsi, WinOem};
+use encoding::conv::NoError;
+
+macro_rules! identity_impl {
+    ($($(#[$attr:meta])* $enc:ident),* $(,)*) => {
+        $(
+            $(#[$attr])*
+            impl<It> TranscodeTo<$enc> for UnitIter<$enc, It> where It: Iterator<Item=<$enc as ::encoding::Encoding>::Unit> {
+                type Iter = IdentityIter<It>;
+                type Error = NoError;
+
+                fn transcode(self) -> Self::Iter {
+                    IdentityIter::new(self.into_iter())
+                }
+
+                fn transparent() -> Option<Transparent<Self, $enc>> {
+                    Some(Transparent::new())
+                }
+            }
+        )*
+    };
+}
+
+identity_impl! {
+    CheckedUnicode,
+    CheckedUtf8,
+    Char16,
+    Char32,
+    JniMtf8,
+    MultiByte,
+    Raw8,
+    Utf8,
+    Utf16,
+    Utf16Be,
+    Utf16Le,
+    Utf32,
+    Utf32Be,
+    Utf32Le,
+    Wide16,
+    Wide32,
+    #[cfg(windows)] WinAnsi,
+    #[cfg(windows)] WinOem,
+    WinUnicode,
+    Wtf8,
+}
+
+/**
+Passes units through unchanged.
+
+This is the transcoding iterator for every conversion from an encoding to itself.
+*/
+pub struct IdentityIter<It> {
+    iter: It,
+}
+
+impl<It> IdentityIter<It> {
+    pub fn new(iter: It) -> Self {
+        IdentityIter {
+            iter: iter,
+        }
+    }
+}
+
+impl<It> Iterator for IdentityIter<It> where It: Iterator {
+    type Item = Result<It::Item, NoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(Ok)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<It> DoubleEndedIterator for IdentityIter<It> where It: DoubleEndedIterator {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(Ok)
+    }
+}
+
+impl<It> ExactSizeIterator for IdentityIter<It> where It: ExactSizeIterator {}
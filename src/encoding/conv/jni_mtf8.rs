@@ -0,0 +1,522 @@
+use std::char;
+use std::fmt;
+use encoding::{TranscodeTo, UnitIter, CheckedUnicode, JniMtf8, Mtf8Unit, Utf8, Utf8Unit};
+use encoding::conv::{ErrorOffset, NoError};
+
+impl<It> TranscodeTo<CheckedUnicode> for UnitIter<JniMtf8, It> where It: Iterator<Item=Mtf8Unit> {
+    type Iter = Mtf8ToUniIter<It>;
+    type Error = Mtf8ToUniError;
+
+    fn transcode(self) -> Self::Iter {
+        Mtf8ToUniIter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<JniMtf8> for UnitIter<CheckedUnicode, It> where It: Iterator<Item=char> {
+    type Iter = UniToMtf8Iter<It>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        UniToMtf8Iter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<Utf8> for UnitIter<JniMtf8, It> where It: Iterator<Item=Mtf8Unit> {
+    type Iter = Mtf8ToUtf8Iter<It>;
+    type Error = Mtf8ToUniError;
+
+    fn transcode(self) -> Self::Iter {
+        Mtf8ToUtf8Iter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<JniMtf8> for UnitIter<Utf8, It> where It: Iterator<Item=Utf8Unit> {
+    type Iter = Utf8ToMtf8Iter<It>;
+    type Error = Utf8ToMtf8Error;
+
+    fn transcode(self) -> Self::Iter {
+        Utf8ToMtf8Iter::new(self.into_iter())
+    }
+}
+
+pub struct Mtf8ToUniIter<It> {
+    at: usize,
+    iter: Option<It>,
+}
+
+impl<It> Mtf8ToUniIter<It> {
+    pub fn new(iter: It) -> Self {
+        Mtf8ToUniIter {
+            at: 0,
+            iter: Some(iter),
+        }
+    }
+}
+
+impl<It> Iterator for Mtf8ToUniIter<It> where It: Iterator<Item=Mtf8Unit> {
+    type Item = Result<char, Mtf8ToUniError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let r = {
+            let iter = match self.iter.as_mut() {
+                Some(iter) => iter,
+                None => return None,
+            };
+
+            let b0 = match iter.next() {
+                Some(b0) => b0.0,
+                None => return None,
+            };
+
+            decode_mtf8(b0, &mut iter.map(|u| u.0))
+        };
+
+        match r {
+            Ok((ch, len)) => {
+                self.at += len;
+                Some(Ok(ch))
+            },
+            Err(err) => {
+                self.iter = None;
+                Some(Err(err.at(self.at)))
+            },
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every code point takes between one and six units.  Decoding stops at the first error, so only one more item is certain.
+        match self.iter {
+            Some(ref iter) => {
+                let (lo, hi) = iter.size_hint();
+                (::std::cmp::min((lo + 5) / 6, 1), hi)
+            },
+            None => (0, Some(0)),
+        }
+    }
+}
+
+pub struct UniToMtf8Iter<It> {
+    buf: UnitBuf,
+    iter: Option<It>,
+}
+
+impl<It> UniToMtf8Iter<It> {
+    pub fn new(iter: It) -> Self {
+        UniToMtf8Iter {
+            buf: UnitBuf::new(),
+            iter: Some(iter),
+        }
+    }
+}
+
+impl<It> Iterator for UniToMtf8Iter<It> where It: Iterator<Item=char> {
+    type Item = Result<Mtf8Unit, NoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(b) = self.buf.pop() {
+            return Some(Ok(Mtf8Unit(b)));
+        }
+
+        let ch = match self.iter.as_mut().and_then(|iter| iter.next()) {
+            Some(ch) => ch,
+            None => {
+                self.iter = None;
+                return None;
+            },
+        };
+
+        self.buf.fill(|buf| encode_mtf8(ch, buf));
+        self.buf.pop().map(|b| Ok(Mtf8Unit(b)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every code point produces between one and six units.
+        let buffered = self.buf.remaining();
+        match self.iter {
+            Some(ref iter) => {
+                let (lo, hi) = iter.size_hint();
+                (lo.saturating_add(buffered), hi.and_then(|hi| hi.checked_mul(6)).and_then(|hi| hi.checked_add(buffered)))
+            },
+            None => (buffered, Some(buffered)),
+        }
+    }
+}
+
+pub struct Mtf8ToUtf8Iter<It> {
+    buf: UnitBuf,
+    iter: Mtf8ToUniIter<It>,
+}
+
+impl<It> Mtf8ToUtf8Iter<It> {
+    pub fn new(iter: It) -> Self {
+        Mtf8ToUtf8Iter {
+            buf: UnitBuf::new(),
+            iter: Mtf8ToUniIter::new(iter),
+        }
+    }
+}
+
+impl<It> Iterator for Mtf8ToUtf8Iter<It> where It: Iterator<Item=Mtf8Unit> {
+    type Item = Result<Utf8Unit, Mtf8ToUniError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(b) = self.buf.pop() {
+            return Some(Ok(Utf8Unit(b)));
+        }
+
+        let ch = match self.iter.next() {
+            Some(Ok(ch)) => ch,
+            Some(Err(err)) => return Some(Err(err)),
+            None => return None,
+        };
+
+        self.buf.fill(|buf| ch.encode_utf8(buf).len());
+        self.buf.pop().map(|b| Ok(Utf8Unit(b)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Re-encoding never grows the string, and at most halves it (for U+0000).  Decoding stops at the first error, so only one more item is certain.
+        let buffered = self.buf.remaining();
+        match self.iter.iter {
+            Some(ref iter) => {
+                let (lo, hi) = iter.size_hint();
+                (::std::cmp::min((lo + 1) / 2, 1).saturating_add(buffered), hi.and_then(|hi| hi.checked_add(buffered)))
+            },
+            None => (buffered, Some(buffered)),
+        }
+    }
+}
+
+pub struct Utf8ToMtf8Iter<It> {
+    at: usize,
+    buf: UnitBuf,
+    iter: Option<It>,
+}
+
+impl<It> Utf8ToMtf8Iter<It> {
+    pub fn new(iter: It) -> Self {
+        Utf8ToMtf8Iter {
+            at: 0,
+            buf: UnitBuf::new(),
+            iter: Some(iter),
+        }
+    }
+}
+
+impl<It> Iterator for Utf8ToMtf8Iter<It> where It: Iterator<Item=Utf8Unit> {
+    type Item = Result<Mtf8Unit, Utf8ToMtf8Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(b) = self.buf.pop() {
+            return Some(Ok(Mtf8Unit(b)));
+        }
+
+        let r = {
+            let iter = match self.iter.as_mut() {
+                Some(iter) => iter,
+                None => return None,
+            };
+
+            let b0 = match iter.next() {
+                Some(b0) => b0.0,
+                None => return None,
+            };
+
+            decode_utf8(b0, &mut iter.map(|u| u.0))
+        };
+
+        match r {
+            Ok((ch, len)) => {
+                self.at += len;
+                self.buf.fill(|buf| encode_mtf8(ch, buf));
+                self.buf.pop().map(|b| Ok(Mtf8Unit(b)))
+            },
+            Err(err) => {
+                self.iter = None;
+                Some(Err(err.at(self.at)))
+            },
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Re-encoding never shrinks the string, and at most doubles it (for U+0000).  Decoding stops at the first error, so only one more item is certain.
+        let buffered = self.buf.remaining();
+        match self.iter {
+            Some(ref iter) => {
+                let (lo, hi) = iter.size_hint();
+                (::std::cmp::min(lo, 1).saturating_add(buffered), hi.and_then(|hi| hi.checked_mul(2)).and_then(|hi| hi.checked_add(buffered)))
+            },
+            None => (buffered, Some(buffered)),
+        }
+    }
+}
+
+/**
+A small buffer of pending output bytes.  Six bytes is enough for a supplementary character in modified UTF-8.
+*/
+struct UnitBuf {
+    buf: [u8; 6],
+    at: u8,
+    len: u8,
+}
+
+impl UnitBuf {
+    fn new() -> Self {
+        UnitBuf {
+            buf: [0; 6],
+            at: 0,
+            len: 0,
+        }
+    }
+
+    fn fill<F>(&mut self, f: F) where F: FnOnce(&mut [u8]) -> usize {
+        self.len = f(&mut self.buf[..]) as u8;
+        self.at = 0;
+    }
+
+    fn pop(&mut self) -> Option<u8> {
+        if self.at < self.len {
+            let b = self.buf[self.at as usize];
+            self.at += 1;
+            Some(b)
+        } else {
+            None
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        (self.len - self.at) as usize
+    }
+}
+
+/**
+Errors which can occur while decoding a single sequence.  These are turned into the public error types once the offset is known.
+*/
+enum SeqError {
+    Invalid,
+    Incomplete,
+}
+
+impl SeqError {
+    fn at<E>(self, at: usize) -> E where E: From<(SeqError, usize)> {
+        E::from((self, at))
+    }
+}
+
+/**
+Reads `n` continuation units, accumulating them onto `v`.
+
+This does *not* check for overlong forms or surrogates; that's the caller's job.
+*/
+fn read_cont<It>(iter: &mut It, mut v: u32, n: usize) -> Result<u32, SeqError>
+where It: Iterator<Item=u8> {
+    for _ in 0..n {
+        let b = match iter.next() {
+            Some(b) => b,
+            None => return Err(SeqError::Incomplete),
+        };
+        if b & 0xc0 != 0x80 {
+            return Err(SeqError::Invalid);
+        }
+        v = (v << 6) | (b & 0x3f) as u32;
+    }
+    Ok(v)
+}
+
+/**
+Decodes a single character from modified UTF-8, given the first unit.  Returns the character and the number of units consumed.
+*/
+fn decode_mtf8<It>(b0: u8, iter: &mut It) -> Result<(char, usize), SeqError>
+where It: Iterator<Item=u8> {
+    let (v, len) = match b0 {
+        // Strictly, this shouldn't appear, but we need to support embedded terminators.
+        0x00 ... 0x7f => (b0 as u32, 1),
+        0xc0 ... 0xdf => {
+            let v = read_cont(iter, (b0 & 0x1f) as u32, 1)?;
+            // `C0 80` is the only permitted overlong form.
+            if v != 0 && v < 0x80 {
+                return Err(SeqError::Invalid);
+            }
+            (v, 2)
+        },
+        0xe0 ... 0xef => {
+            let v = read_cont(iter, (b0 & 0x0f) as u32, 2)?;
+            match v {
+                0x0000 ... 0x07ff => return Err(SeqError::Invalid),
+                0xd800 ... 0xdbff => {
+                    let b3 = match iter.next() {
+                        Some(b3) => b3,
+                        None => return Err(SeqError::Incomplete),
+                    };
+                    if b3 & 0xf0 != 0xe0 {
+                        return Err(SeqError::Invalid);
+                    }
+                    let lo = read_cont(iter, (b3 & 0x0f) as u32, 2)?;
+                    if !(0xdc00 <= lo && lo <= 0xdfff) {
+                        return Err(SeqError::Invalid);
+                    }
+                    (0x10000 + (((v - 0xd800) << 10) | (lo - 0xdc00)), 6)
+                },
+                0xdc00 ... 0xdfff => return Err(SeqError::Invalid),
+                v => (v, 3),
+            }
+        },
+        _ => return Err(SeqError::Invalid),
+    };
+
+    char::from_u32(v).map(|ch| (ch, len)).ok_or(SeqError::Invalid)
+}
+
+/**
+Decodes a single character from (strict) UTF-8, given the first unit.  Returns the character and the number of units consumed.
+*/
+fn decode_utf8<It>(b0: u8, iter: &mut It) -> Result<(char, usize), SeqError>
+where It: Iterator<Item=u8> {
+    let (v, len) = match b0 {
+        0x00 ... 0x7f => (b0 as u32, 1),
+        0xc2 ... 0xdf => (read_cont(iter, (b0 & 0x1f) as u32, 1)?, 2),
+        0xe0 ... 0xef => {
+            let v = read_cont(iter, (b0 & 0x0f) as u32, 2)?;
+            if v < 0x800 {
+                return Err(SeqError::Invalid);
+            }
+            (v, 3)
+        },
+        0xf0 ... 0xf4 => {
+            let v = read_cont(iter, (b0 & 0x07) as u32, 3)?;
+            if v < 0x10000 {
+                return Err(SeqError::Invalid);
+            }
+            (v, 4)
+        },
+        _ => return Err(SeqError::Invalid),
+    };
+
+    // This rejects surrogates and anything above U+10FFFF.
+    char::from_u32(v).map(|ch| (ch, len)).ok_or(SeqError::Invalid)
+}
+
+/**
+Encodes a single character into modified UTF-8, returning the number of units written.
+
+`buf` must be at least six units long.
+*/
+pub fn encode_mtf8(ch: char, buf: &mut [u8]) -> usize {
+    fn enc3(v: u32, buf: &mut [u8]) {
+        buf[0] = 0xe0 | (v >> 12) as u8;
+        buf[1] = 0x80 | ((v >> 6) & 0x3f) as u8;
+        buf[2] = 0x80 | (v & 0x3f) as u8;
+    }
+
+    let v = ch as u32;
+    match v {
+        0x0000 => {
+            buf[0] = 0xc0;
+            buf[1] = 0x80;
+            2
+        },
+        0x0001 ... 0x007f => {
+            buf[0] = v as u8;
+            1
+        },
+        0x0080 ... 0x07ff => {
+            buf[0] = 0xc0 | (v >> 6) as u8;
+            buf[1] = 0x80 | (v & 0x3f) as u8;
+            2
+        },
+        0x0800 ... 0xffff => {
+            enc3(v, buf);
+            3
+        },
+        _ => {
+            let v = v - 0x10000;
+            enc3(0xd800 | (v >> 10), &mut buf[..3]);
+            enc3(0xdc00 | (v & 0x3ff), &mut buf[3..]);
+            6
+        },
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Mtf8ToUniError {
+    InvalidAt(usize),
+    Incomplete,
+}
+
+impl From<(SeqError, usize)> for Mtf8ToUniError {
+    fn from((err, at): (SeqError, usize)) -> Self {
+        match err {
+            SeqError::Invalid => Mtf8ToUniError::InvalidAt(at),
+            SeqError::Incomplete => Mtf8ToUniError::Incomplete,
+        }
+    }
+}
+
+impl fmt::Display for Mtf8ToUniError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Mtf8ToUniError::InvalidAt(at) => write!(fmt, "invalid unit at offset {}", at),
+            Mtf8ToUniError::Incomplete => write!(fmt, "incomplete unit"),
+        }
+    }
+}
+
+impl ::std::error::Error for Mtf8ToUniError {
+    fn description(&self) -> &str {
+        match *self {
+            Mtf8ToUniError::InvalidAt(_) => "invalid unit",
+            Mtf8ToUniError::Incomplete => "incomplete unit",
+        }
+    }
+}
+
+impl ErrorOffset for Mtf8ToUniError {
+    fn offset(&self) -> Option<usize> {
+        match *self {
+            Mtf8ToUniError::InvalidAt(at) => Some(at),
+            Mtf8ToUniError::Incomplete => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Utf8ToMtf8Error {
+    InvalidAt(usize),
+    Incomplete,
+}
+
+impl From<(SeqError, usize)> for Utf8ToMtf8Error {
+    fn from((err, at): (SeqError, usize)) -> Self {
+        match err {
+            SeqError::Invalid => Utf8ToMtf8Error::InvalidAt(at),
+            SeqError::Incomplete => Utf8ToMtf8Error::Incomplete,
+        }
+    }
+}
+
+impl fmt::Display for Utf8ToMtf8Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Utf8ToMtf8Error::InvalidAt(at) => write!(fmt, "invalid unit at offset {}", at),
+            Utf8ToMtf8Error::Incomplete => write!(fmt, "incomplete unit"),
+        }
+    }
+}
+
+impl ::std::error::Error for Utf8ToMtf8Error {
+    fn description(&self) -> &str {
+        match *self {
+            Utf8ToMtf8Error::InvalidAt(_) => "invalid unit",
+            Utf8ToMtf8Error::Incomplete => "incomplete unit",
+        }
+    }
+}
+
+impl ErrorOffset for Utf8ToMtf8Error {
+    fn offset(&self) -> Option<usize> {
+        match *self {
+            Utf8ToMtf8Error::InvalidAt(at) => Some(at),
+            Utf8ToMtf8Error::Incomplete => None,
+        }
+    }
+}
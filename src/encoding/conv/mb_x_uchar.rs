@@ -0,0 +1,377 @@
+/*!
+Transcoders between the C runtime multi-byte encoding and the C11 `char16_t` and `char32_t` encodings.
+
+These are implemented on top of the `mbrtoc16`, `c16rtomb`, `mbrtoc32`, and `c32rtomb` functions from `uchar.h`, and as such depend on the current C runtime locale.
+*/
+use std::fmt;
+use std::mem;
+use libc::{c_char, size_t};
+use encoding::{Encoding, TranscodeTo, Unit, UnitIter, MultiByte, MbUnit, Char16, C16Unit, Char32, C32Unit};
+use ffi::{MbConvResult, mbrtoc16, c16rtomb, mbrtoc32, c32rtomb, mbstate_t};
+use encoding::conv::ErrorOffset;
+
+impl<It> TranscodeTo<Char16> for UnitIter<MultiByte, It> where It: Iterator<Item=MbUnit> {
+    type Iter = MbsToUcIter<It, C16Unit>;
+    type Error = MbsToUcError;
+
+    fn transcode(self) -> Self::Iter {
+        MbsToUcIter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<MultiByte> for UnitIter<Char16, It> where It: Iterator<Item=C16Unit> {
+    type Iter = UcsToMbIter<It>;
+    type Error = UcsToMbError;
+
+    fn transcode(self) -> Self::Iter {
+        UcsToMbIter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<Char32> for UnitIter<MultiByte, It> where It: Iterator<Item=MbUnit> {
+    type Iter = MbsToUcIter<It, C32Unit>;
+    type Error = MbsToUcError;
+
+    fn transcode(self) -> Self::Iter {
+        MbsToUcIter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<MultiByte> for UnitIter<Char32, It> where It: Iterator<Item=C32Unit> {
+    type Iter = UcsToMbIter<It>;
+    type Error = UcsToMbError;
+
+    fn transcode(self) -> Self::Iter {
+        UcsToMbIter::new(self.into_iter())
+    }
+}
+
+/**
+Abstracts over the `char16_t` and `char32_t` unit types, and the C runtime functions which convert them.
+*/
+pub trait UcharUnit: Unit + fmt::Debug {
+    /**
+    Converts a single multi-byte character.  This has the same contract as `mbrtoc16` and `mbrtoc32`.
+    */
+    unsafe fn mbrtoc(dest: *mut Self, src: *const c_char, n: size_t, mbs: *mut mbstate_t) -> size_t;
+
+    /**
+    Converts a single unit to multi-byte units.  This has the same contract as `c16rtomb` and `c32rtomb`.
+    */
+    unsafe fn crtomb(dest: *mut c_char, src: Self, mbs: *mut mbstate_t) -> size_t;
+}
+
+impl UcharUnit for C16Unit {
+    #[inline]
+    unsafe fn mbrtoc(dest: *mut Self, src: *const c_char, n: size_t, mbs: *mut mbstate_t) -> size_t {
+        mbrtoc16(&mut (*dest).0, src, n, mbs)
+    }
+
+    #[inline]
+    unsafe fn crtomb(dest: *mut c_char, src: Self, mbs: *mut mbstate_t) -> size_t {
+        c16rtomb(dest, src.0, mbs)
+    }
+}
+
+impl UcharUnit for C32Unit {
+    #[inline]
+    unsafe fn mbrtoc(dest: *mut Self, src: *const c_char, n: size_t, mbs: *mut mbstate_t) -> size_t {
+        mbrtoc32(&mut (*dest).0, src, n, mbs)
+    }
+
+    #[inline]
+    unsafe fn crtomb(dest: *mut c_char, src: Self, mbs: *mut mbstate_t) -> size_t {
+        c32rtomb(dest, src.0, mbs)
+    }
+}
+
+pub struct MbsToUcIter<It, U> {
+    iter: Option<It>,
+    at: usize,
+    stored: Option<U>,
+    state: mbstate_t,
+}
+
+impl<It, U> MbsToUcIter<It, U> {
+    pub fn new(iter: It) -> Self {
+        MbsToUcIter {
+            iter: Some(iter),
+            at: 0,
+            stored: None,
+            state: unsafe { mem::zeroed() },
+        }
+    }
+}
+
+pub struct UcsToMbIter<It> {
+    iter: Option<It>,
+    at: usize,
+    buf: [MbUnit; <MultiByte as Encoding>::MAX_UNITS_PER_CHAR],
+    buf_at: u8,
+    buf_len: u8,
+    partial: bool,
+    state: mbstate_t,
+}
+
+impl<It> UcsToMbIter<It> {
+    pub fn new(iter: It) -> Self {
+        UcsToMbIter {
+            iter: Some(iter),
+            at: 0,
+            buf: [MbUnit(0); <MultiByte as Encoding>::MAX_UNITS_PER_CHAR],
+            buf_at: 0,
+            buf_len: 0,
+            partial: false,
+            state: unsafe { mem::zeroed() },
+        }
+    }
+}
+
+impl<It, U> Iterator for MbsToUcIter<It, U> where It: Iterator<Item=MbUnit>, U: UcharUnit {
+    type Item = Result<U, MbsToUcError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(uc) = self.stored.take() {
+            return Some(Ok(uc));
+        }
+
+        let err;
+
+        {
+            let mut buf = [0; <MultiByte as Encoding>::MAX_UNITS_PER_CHAR];
+            let mut buf_len = 0;
+
+            let iter = match self.iter.as_mut() {
+                Some(iter) => iter,
+                None => return None,
+            };
+
+            loop {
+                if buf_len == buf.len() {
+                    err = MbsToUcError::OutOfBufferAt(self.at);
+                    break;
+                }
+
+                buf[buf_len] = match iter.next() {
+                    Some(mbu) => mbu.0,
+                    None => {
+                        if buf_len == 0 {
+                            return None;
+                        } else {
+                            err = MbsToUcError::Incomplete;
+                            break;
+                        }
+                    },
+                };
+                buf_len += 1;
+
+                let mut uc = U::zero();
+                let mut state_new = self.state;
+
+                match unsafe {
+                    let r = U::mbrtoc(&mut uc,
+                        buf.as_ptr() as *const c_char,
+                        buf_len as usize,
+                        &mut state_new);
+                    MbConvResult::from_raw(r)
+                } {
+                    MbConvResult::Illegal => {
+                        err = MbsToUcError::InvalidAt(self.at);
+                        break;
+                    },
+
+                    MbConvResult::Incomplete => {
+                        // We have to keep pulling new units in until we run out or exhaust the buffer.
+                        continue;
+                    },
+
+                    MbConvResult::StoredOnly => {
+                        // Stored units are always drained below, before any more input is fed in.
+                        panic!("mbrtoc returned a stored unit after consuming input");
+                    },
+
+                    MbConvResult::Consumed(_) => (),
+                }
+
+                self.at += buf_len as usize;
+                self.state = state_new;
+
+                /*
+                A single multi-byte character may produce more than one unit (*e.g.* a surrogate pair).  The remaining unit is returned by the next call *without* consuming any input, which we can only detect by asking.  This is done on a copy of the state so that a "no" answer cannot disturb it.
+                */
+                let mut stored = U::zero();
+                let mut state_peek = self.state;
+                if let MbConvResult::StoredOnly = unsafe {
+                    MbConvResult::from_raw(U::mbrtoc(&mut stored, buf.as_ptr() as *const c_char, 0, &mut state_peek))
+                } {
+                    self.state = state_peek;
+                    self.stored = Some(stored);
+                }
+
+                return Some(Ok(uc));
+            }
+        }
+
+        self.iter = None;
+        Some(Err(err))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every character takes at least one unit, and produces at most two.  Conversion stops at the first error, so only one more item is certain.
+        let stored = self.stored.is_some() as usize;
+        match self.iter {
+            Some(ref iter) => {
+                let max = <MultiByte as Encoding>::MAX_UNITS_PER_CHAR;
+                let (lo, hi) = iter.size_hint();
+                (::std::cmp::min((lo + max - 1) / max, 1).saturating_add(stored), hi.and_then(|hi| hi.checked_mul(2)).and_then(|hi| hi.checked_add(stored)))
+            },
+            None => (stored, Some(stored)),
+        }
+    }
+}
+
+impl<It> Iterator for UcsToMbIter<It> where It: Iterator, It::Item: UcharUnit {
+    type Item = Result<MbUnit, UcsToMbError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buf_at < self.buf_len {
+            let mbu = self.buf[self.buf_at as usize];
+            self.buf_at += 1;
+            return Some(Ok(mbu));
+        }
+
+        // Refresh buffer
+        self.buf_at = 0;
+        self.buf_len = 0;
+
+        loop {
+            let ucu = match self.iter.as_mut().map(|iter| iter.next()) {
+                None => return None,
+                Some(Some(ucu)) => ucu,
+                Some(None) => {
+                    self.iter = None;
+                    if self.partial {
+                        return Some(Err(UcsToMbError::Incomplete));
+                    }
+                    return None;
+                },
+            };
+
+            match unsafe {
+                MbConvResult::from_raw(It::Item::crtomb(
+                    self.buf[..].as_mut_ptr() as *mut c_char,
+                    ucu,
+                    &mut self.state))
+            } {
+                MbConvResult::Illegal => {
+                    self.iter = None;
+                    return Some(Err(UcsToMbError::InvalidAt(self.at)));
+                },
+                MbConvResult::Incomplete | MbConvResult::StoredOnly => {
+                    // `c16rtomb` and `c32rtomb` aren't allowed to return these.
+                    panic!("crtomb returned an invalid result for {:?}", ucu);
+                },
+                MbConvResult::Consumed(0) => {
+                    // The unit was stored in the conversion state, pending the rest of the character.
+                    self.at += 1;
+                    self.partial = true;
+                    continue;
+                },
+                MbConvResult::Consumed(len) if len > <MultiByte as Encoding>::MAX_UNITS_PER_CHAR => {
+                    // We can *probably* assume memory corruption.
+                    panic!("crtomb has corrupted memory");
+                },
+                MbConvResult::Consumed(len) => {
+                    self.at += 1;
+                    self.partial = false;
+                    self.buf_at = 1;
+                    self.buf_len = len as u8;
+                    return Some(Ok(self.buf[0]));
+                },
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // A surrogate pair produces a single character, which takes at least one unit.  Conversion stops at the first error, so only one more item is certain.
+        let buffered = (self.buf_len - self.buf_at) as usize;
+        match self.iter {
+            Some(ref iter) => {
+                let max = <MultiByte as Encoding>::MAX_UNITS_PER_CHAR;
+                let (lo, hi) = iter.size_hint();
+                (::std::cmp::min((lo + 1) / 2, 1).saturating_add(buffered), hi.and_then(|hi| hi.checked_mul(max)).and_then(|hi| hi.checked_add(buffered)))
+            },
+            None => (buffered, Some(buffered)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MbsToUcError {
+    InvalidAt(usize),
+    Incomplete,
+    OutOfBufferAt(usize),
+}
+
+impl fmt::Display for MbsToUcError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MbsToUcError::InvalidAt(at) => write!(fmt, "invalid unit at offset {}", at),
+            MbsToUcError::Incomplete => write!(fmt, "incomplete unit"),
+            MbsToUcError::OutOfBufferAt(at) => write!(fmt, "character too large to transcode at offset {}", at),
+        }
+    }
+}
+
+impl ::std::error::Error for MbsToUcError {
+    fn description(&self) -> &str {
+        match *self {
+            MbsToUcError::InvalidAt(_) => "invalid unit",
+            MbsToUcError::Incomplete => "incomplete unit",
+            MbsToUcError::OutOfBufferAt(_) => "character too large to transcode",
+        }
+    }
+}
+
+impl ErrorOffset for MbsToUcError {
+    fn offset(&self) -> Option<usize> {
+        match *self {
+            MbsToUcError::InvalidAt(at) => Some(at),
+            MbsToUcError::Incomplete => None,
+            MbsToUcError::OutOfBufferAt(at) => Some(at),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum UcsToMbError {
+    InvalidAt(usize),
+    Incomplete,
+}
+
+impl fmt::Display for UcsToMbError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            UcsToMbError::InvalidAt(at) => write!(fmt, "invalid unit at offset {}", at),
+            UcsToMbError::Incomplete => write!(fmt, "incomplete unit"),
+        }
+    }
+}
+
+impl ::std::error::Error for UcsToMbError {
+    fn description(&self) -> &str {
+        match *self {
+            UcsToMbError::InvalidAt(_) => "invalid unit",
+            UcsToMbError::Incomplete => "incomplete unit",
+        }
+    }
+}
+
+impl ErrorOffset for UcsToMbError {
+    fn offset(&self) -> Option<usize> {
+        match *self {
+            UcsToMbError::InvalidAt(at) => Some(at),
+            UcsToMbError::Incomplete => None,
+        }
+    }
+}
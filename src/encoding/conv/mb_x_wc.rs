@@ -1,12 +1,13 @@
 use std::fmt;
 use std::iter;
 use std::mem;
-use libc::{c_char};
-use encoding::{TranscodeTo, UnitIter, CheckedUnicode, MultiByte, Wide, MbUnit, WUnit};
-use encoding::conv::NoError;
-use encoding::conv::os::{WcToUniIter, WcToUniError, UniToWcIter};
-use ffi::{MB_LEN_MAX, mbrtowc, wcrtomb, mbstate_t};
-use util::{LiftErrIter, LiftTrapErrIter, LiftErrExt};
+use std::ptr;
+use libc::{c_char, wchar_t};
+use encoding::{Encoding, Recoverable, TranscodeSource, TranscodeTo, UnitIter, CheckedUnicode, MultiByte, Wide, MbUnit, WUnit};
+use encoding::conv::{ErrorOffset, NoError};
+use encoding::conv::wide::{WcToUniIter, WcToUniError, UniToWcIter};
+use ffi::{MbConvResult, mb_is_ascii_transparent, mbrtowc, mbsrtowcs, wcrtomb, wcsrtombs, mbstate_t};
+use util::{LiftErrIter, LiftTrapErrIter, LiftErrExt, MapOffsetIter, OffsetLog, SourceOffset, TrackOffsetIter};
 
 impl<It> TranscodeTo<Wide> for UnitIter<MultiByte, It> where It: Iterator<Item=MbUnit> {
     type Iter = MbsToWcIter<It>;
@@ -15,6 +16,13 @@ impl<It> TranscodeTo<Wide> for UnitIter<MultiByte, It> where It: Iterator<Item=M
     fn transcode(self) -> Self::Iter {
         MbsToWcIter::new(self.into_iter())
     }
+
+    fn transcode_terminated(units: &[<Self as TranscodeSource>::Unit], out: Option<&mut [WUnit]>) -> Option<usize> {
+        match out {
+            None => mbs_to_wcs_bulk_len(units),
+            Some(out) => if mbs_to_wcs_bulk_into(units, out) { Some(out.len()) } else { None },
+        }
+    }
 }
 
 impl<It> TranscodeTo<MultiByte> for UnitIter<Wide, It> where It: Iterator<Item=WUnit> {
@@ -29,10 +37,13 @@ impl<It> TranscodeTo<MultiByte> for UnitIter<Wide, It> where It: Iterator<Item=W
 impl<It> TranscodeTo<CheckedUnicode> for UnitIter<MultiByte, It> where It: Iterator<Item=MbUnit> {
     type Iter = LiftErrIter<
         iter::Map<
-            WcToUniIter<
-                LiftTrapErrIter<
-                    MbsToWcIter<It>,
-                    MbsToWcError,
+            MapOffsetIter<
+                WcToUniIter<
+                    Wide,
+                    LiftTrapErrIter<
+                        TrackOffsetIter<MbsToWcIter<It>>,
+                        MbsToWcError,
+                    >
                 >
             >,
             fn(Result<char, WcToUniError>) -> Result<char, MbsToUniError>,
@@ -42,8 +53,10 @@ impl<It> TranscodeTo<CheckedUnicode> for UnitIter<MultiByte, It> where It: Itera
     type Error = MbsToUniError;
 
     fn transcode(self) -> Self::Iter {
-        MbsToWcIter::new(self.into_iter())
-            .lift_err(|over| WcToUniIter::new(over)
+        // `WcToUniIter` counts wide units; the log lets its errors report offsets into the source instead.
+        let log = OffsetLog::new();
+        TrackOffsetIter::new(MbsToWcIter::new(self.into_iter()), log.clone())
+            .lift_err(|over| MapOffsetIter::new(WcToUniIter::new(over), log)
                 .map(map_err as fn(_) -> _))
     }
 }
@@ -53,7 +66,7 @@ impl<It> TranscodeTo<MultiByte> for UnitIter<CheckedUnicode, It> where It: Itera
         iter::Map<
             WcsToMbIter<
                 LiftTrapErrIter<
-                    UniToWcIter<It>,
+                    UniToWcIter<Wide, It>,
                     NoError,
                 >
             >,
@@ -71,12 +84,23 @@ impl<It> TranscodeTo<MultiByte> for UnitIter<CheckedUnicode, It> where It: Itera
     }
 }
 
+/**
+Converts multibyte units to wide units, according to the current C runtime locale.
+
+After an invalid sequence, this resets the conversion state and resumes with the unit following the one at which the error was reported.  Units which were read ahead while looking for the end of the sequence are converted again.
+
+When the input runs out, whether cleanly or part-way through a sequence, the conversion state is returned to the initial shift state.
+*/
 pub struct MbsToWcIter<It> {
     iter: Option<It>,
     at: usize,
-    // buf: [c_char; MB_LEN_MAX],
-    // buf_len: u8,
+    // Units taken from `iter` which still need converting, after recovering from an invalid sequence.
+    pending: [c_char; <MultiByte as Encoding>::MAX_UNITS_PER_CHAR],
+    pending_at: u8,
+    pending_len: u8,
     state: mbstate_t,
+    // Whether units below `0x80` can be converted without calling `mbrtowc`.
+    ascii_fast: bool,
 }
 
 impl<It> MbsToWcIter<It> {
@@ -84,15 +108,26 @@ impl<It> MbsToWcIter<It> {
         MbsToWcIter {
             iter: Some(iter),
             at: 0,
+            pending: [0; <MultiByte as Encoding>::MAX_UNITS_PER_CHAR],
+            pending_at: 0,
+            pending_len: 0,
             state: unsafe { mem::zeroed() },
+            ascii_fast: mb_is_ascii_transparent(),
         }
     }
 }
 
+/**
+Converts wide units to multibyte units, according to the current C runtime locale.
+
+A wide unit which cannot be represented in the locale produces a single error in its place.  The conversion state is then reset, and conversion resumes with the next wide unit.
+
+At the end of the input, this emits whatever sequence is needed to return to the initial shift state, so the output can always be decoded from a fresh state.
+*/
 pub struct WcsToMbIter<It> {
     iter: Option<It>,
     at: usize,
-    buf: [MbUnit; MB_LEN_MAX],
+    buf: [MbUnit; <MultiByte as Encoding>::MAX_UNITS_PER_CHAR],
     buf_at: u8,
     buf_len: u8,
     state: mbstate_t,
@@ -103,7 +138,7 @@ impl<It> WcsToMbIter<It> {
         WcsToMbIter {
             iter: Some(iter),
             at: 0,
-            buf: [MbUnit(0); MB_LEN_MAX],
+            buf: [MbUnit(0); <MultiByte as Encoding>::MAX_UNITS_PER_CHAR],
             buf_at: 0,
             buf_len: 0,
             state: unsafe { mem::zeroed() },
@@ -111,79 +146,123 @@ impl<It> WcsToMbIter<It> {
     }
 }
 
+impl<It> MbsToWcIter<It> {
+    /**
+    Ends the conversion, returning the state to the initial shift state.
+
+    Decoding never needs to produce anything for this, but the terminal call lets the C runtime discard any partial sequence or shift state it is holding.
+    */
+    fn finish(&mut self) {
+        if self.iter.take().is_some() {
+            unsafe {
+                mbrtowc(ptr::null_mut(), ptr::null(), 0, &mut self.state);
+            }
+        }
+        self.state = unsafe { mem::zeroed() };
+    }
+}
+
+impl<It> SourceOffset for MbsToWcIter<It> {
+    fn source_offset(&self) -> usize {
+        self.at
+    }
+}
+
 impl<It> Iterator for MbsToWcIter<It> where It: Iterator<Item=MbUnit> {
     type Item = Result<WUnit, MbsToWcError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let err;
-
-        {
-            let mut buf = [0; MB_LEN_MAX];
-            let mut buf_len = 0;
-
-            let iter = match self.iter.as_mut() {
-                Some(iter) => iter,
-                None => return None,
-            };
+        let mut buf = [0; <MultiByte as Encoding>::MAX_UNITS_PER_CHAR];
+        let mut buf_len = 0;
 
-            loop {
-                if buf_len == buf.len() {
-                    err = MbsToWcError::OutOfBufferAt(self.at);
-                    break;
-                }
+        let err = loop {
+            if buf_len == buf.len() {
+                break MbsToWcError::OutOfBufferAt(self.at);
+            }
 
-                buf[buf_len] = match {
-                    let e = iter.next();
-                    e
-                } {
+            buf[buf_len] = if self.pending_at < self.pending_len {
+                let mbu = self.pending[self.pending_at as usize];
+                self.pending_at += 1;
+                mbu
+            } else {
+                match self.iter.as_mut().and_then(|iter| iter.next()) {
                     Some(mbu) => mbu.0,
                     None => {
-                        if buf_len == 0 {
+                        let was_running = self.iter.is_some();
+                        self.finish();
+                        if buf_len == 0 || !was_running {
                             return None;
                         } else {
-                            err = MbsToWcError::Incomplete;
-                            break;
+                            // Nothing can follow the incomplete sequence, so there is nothing to recover.
+                            return Some(Err(MbsToWcError::Incomplete));
                         }
                     },
-                };
-                buf_len += 1;
-
-                const ILLEGAL: usize = -1isize as usize;
-                const INCOMPLETE: usize = -2isize as usize;
-
-                let mut wc = 0;
-                let mut state_new = self.state;
-
-                match unsafe {
-                    let r = mbrtowc(&mut wc,
-                        buf.as_ptr() as *const c_char,
-                        buf_len as usize,
-                        &mut state_new);
-                    r
-                } {
-                    ILLEGAL => {
-                        err = MbsToWcError::InvalidAt(self.at);
-                        break;
-                    },
-
-                    INCOMPLETE => {
-                        // We have to keep pulling new units in until we run out or exhaust the buffer.
-                        continue;
-                    },
-
-                    _ => (),
                 }
+            };
+            buf_len += 1;
 
-                self.at += buf_len as usize;
-                self.state = state_new;
+            // An ASCII transparent encoding is stateless, so an ASCII unit at the start of a sequence can only be that character.
+            if buf_len == 1 && self.ascii_fast && (buf[0] as u8) < 0x80 {
+                self.at += 1;
+                return Some(Ok(WUnit(buf[0] as _)));
+            }
 
-                return Some(Ok(WUnit(wc)));
+            let mut wc = 0;
+            let mut state_new = self.state;
+
+            match unsafe {
+                let r = mbrtowc(&mut wc,
+                    buf.as_ptr(),
+                    buf_len as usize,
+                    &mut state_new);
+                MbConvResult::from_raw(r)
+            } {
+                MbConvResult::Illegal => {
+                    break MbsToWcError::InvalidAt(self.at);
+                },
+
+                MbConvResult::Incomplete => {
+                    // We have to keep pulling new units in until we run out or exhaust the buffer.
+                    continue;
+                },
+
+                _ => (),
             }
-        }
 
-        self.iter = None;
+            self.at += buf_len as usize;
+            self.state = state_new;
+
+            return Some(Ok(WUnit(wc as _)));
+        };
+
+        // Skip the first unit of the sequence, and go back over the rest.  Everything in `buf` was taken from the front of the pending units, or after them, so they still fit.
+        let rest = &buf[1..buf_len];
+        let mut pending = [0; <MultiByte as Encoding>::MAX_UNITS_PER_CHAR];
+        let remaining = &self.pending[self.pending_at as usize..self.pending_len as usize];
+        pending[..rest.len()].copy_from_slice(rest);
+        pending[rest.len()..rest.len() + remaining.len()].copy_from_slice(remaining);
+        self.pending_len = (rest.len() + remaining.len()) as u8;
+        self.pending_at = 0;
+        self.pending = pending;
+
+        self.at += 1;
+        self.state = unsafe { mem::zeroed() };
         Some(Err(err))
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every character, and every error, takes at least one unit.
+        match self.iter {
+            Some(ref iter) => {
+                let max = <MultiByte as Encoding>::MAX_UNITS_PER_CHAR;
+                let pending = (self.pending_len - self.pending_at) as usize;
+                let (lo, hi) = iter.size_hint();
+                let lo = lo.saturating_add(pending);
+                (lo / max + (lo % max != 0) as usize, hi.and_then(|hi| hi.checked_add(pending)))
+            },
+            None => (0, Some(0)),
+        }
+    }
 }
 
 impl<It> Iterator for WcsToMbIter<It> where It: Iterator<Item=WUnit> {
@@ -206,29 +285,53 @@ impl<It> Iterator for WcsToMbIter<It> where It: Iterator<Item=WUnit> {
                 None => return None,
             }
         } {
-            None => return None,
+            None => {
+                // Source is exhausted.  For stateful encodings, we need to emit whatever sequence returns to the initial shift state.
+                // Converting a null wide character does exactly this, followed by a null unit which we drop.
+                self.iter = None;
+                unsafe {
+                    match MbConvResult::from_raw(wcrtomb(
+                        self.buf[..].as_mut_ptr() as *mut c_char,
+                        0,
+                        &mut self.state))
+                    {
+                        MbConvResult::Consumed(len) if 1 < len && len <= <MultiByte as Encoding>::MAX_UNITS_PER_CHAR => {
+                            self.buf_at = 1;
+                            self.buf_len = (len - 1) as u8;
+                            return Some(Ok(self.buf[0]));
+                        },
+                        _ => return None,
+                    }
+                }
+            },
             Some(wcu) => {
                 unsafe {
-                    const ILLEGAL: usize = -1isize as usize;
                     match {
-                        wcrtomb(
+                        MbConvResult::from_raw(wcrtomb(
                             self.buf[..].as_mut_ptr() as *mut c_char,
-                            wcu.0,
-                            &mut self.state)
+                            wcu.0 as wchar_t,
+                            &mut self.state))
                     } {
-                        ILLEGAL => {
-                            self.iter = None;
-                            return Some(Err(WcsToMbError::InvalidAt(self.at)));
+                        MbConvResult::Illegal => {
+                            // Nothing was written, but `wcrtomb` may have left the state part-way through a sequence, so start again from the initial state.
+                            let at = self.at;
+                            self.at += 1;
+                            self.state = mem::zeroed();
+                            return Some(Err(WcsToMbError::InvalidAt(at)));
+                        },
+                        MbConvResult::Incomplete | MbConvResult::StoredOnly => {
+                            // `wcrtomb` isn't allowed to return these.
+                            panic!("wcrtomb returned an invalid result for {:?}", wcu);
                         },
-                        0 => {
+                        MbConvResult::Consumed(0) => {
                             // This... *shouldn't happen.*
                             panic!("wcrtomb wrote no multibyte units for {:?}", wcu);
                         },
-                        len if len > MB_LEN_MAX => {
+                        MbConvResult::Consumed(len) if len > <MultiByte as Encoding>::MAX_UNITS_PER_CHAR => {
                             // We can *probably* assume memory corruption.
                             panic!("wcrtomb has corrupted memory");
                         },
-                        len => {
+                        MbConvResult::Consumed(len) => {
                             self.at += 1;
                             self.buf_at = 1;
                             self.buf_len = len as u8;
@@ -239,6 +342,154 @@ impl<It> Iterator for WcsToMbIter<It> where It: Iterator<Item=WUnit> {
             },
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every unit produces at least one multibyte unit.  The shift state reset at the end can add up to one more character's worth.
+        let buffered = (self.buf_len - self.buf_at) as usize;
+        match self.iter {
+            Some(ref iter) => {
+                let max = <MultiByte as Encoding>::MAX_UNITS_PER_CHAR;
+                let (lo, hi) = iter.size_hint();
+                (lo.saturating_add(buffered), hi.and_then(|hi| hi.checked_add(1)).and_then(|hi| hi.checked_mul(max)).and_then(|hi| hi.checked_add(buffered)))
+            },
+            None => (buffered, Some(buffered)),
+        }
+    }
+}
+
+impl<It> Recoverable for MbsToWcIter<It> {}
+
+impl<It> Recoverable for WcsToMbIter<It> {}
+
+/*
+The layered conversions resume wherever `MbsToWcIter` and `WcsToMbIter` do, as `LiftErrIter` doesn't end at a lifted error.
+*/
+
+impl<It> Recoverable for LiftErrIter<
+    iter::Map<
+        MapOffsetIter<
+            WcToUniIter<
+                Wide,
+                LiftTrapErrIter<
+                    TrackOffsetIter<MbsToWcIter<It>>,
+                    MbsToWcError,
+                >
+            >
+        >,
+        fn(Result<char, WcToUniError>) -> Result<char, MbsToUniError>,
+    >,
+    MbsToWcError,
+> where It: Iterator<Item=MbUnit> {}
+
+impl<It> Recoverable for LiftErrIter<
+    iter::Map<
+        WcsToMbIter<
+            LiftTrapErrIter<
+                UniToWcIter<Wide, It>,
+                NoError,
+            >
+        >,
+        fn(Result<MbUnit, WcsToMbError>) -> Result<MbUnit, WcsToMbError>,
+    >,
+    NoError,
+> where It: Iterator<Item=char> {}
+
+/**
+Converts a whole zero-terminated multibyte string to wide units, with a single call to `mbsrtowcs`.
+
+`src` must end with its terminator, and contain no other zero units.  The result does not include the terminator.
+
+This produces the same result as transcoding the string with `MbsToWcIter`, but avoids a call into the C runtime for every character.  The output buffer is sized from the input, as every wide unit is decoded from at least one multibyte unit.
+
+Returns `None` if the conversion failed.  As `mbsrtowcs` does not report *where*, the caller should fall back to the transcoding iterators to find out.
+*/
+pub fn mbs_to_wcs_bulk(src: &[MbUnit]) -> Option<Vec<WUnit>> {
+    debug_assert!(src.last().map(|u| u.0 == 0).unwrap_or(false), "string must end with its terminator");
+
+    unsafe {
+        let mut state: mbstate_t = mem::zeroed();
+        let mut src_ptr = src.as_ptr() as *const c_char;
+        let mut out: Vec<WUnit> = Vec::with_capacity(src.len());
+        // `WUnit` is a `repr(C)` wrapper around `wchar_t`.
+        let len = mbsrtowcs(out.as_mut_ptr() as *mut wchar_t, &mut src_ptr, src.len(), &mut state);
+        match MbConvResult::from_raw(len) {
+            // `mbsrtowcs` only clears the source pointer once it has converted the terminator.
+            MbConvResult::Consumed(len) if src_ptr.is_null() => {
+                out.set_len(len);
+                Some(out)
+            },
+            _ => None,
+        }
+    }
+}
+
+/**
+Returns the number of wide units a whole zero-terminated multibyte string converts to, with a single call to `mbsrtowcs`.
+
+This has the same requirements and failure behaviour as `mbs_to_wcs_bulk`.  Nothing is written; use `mbs_to_wcs_bulk_into` to convert the string into a buffer of exactly this length.
+*/
+pub fn mbs_to_wcs_bulk_len(src: &[MbUnit]) -> Option<usize> {
+    debug_assert!(src.last().map(|u| u.0 == 0).unwrap_or(false), "string must end with its terminator");
+
+    unsafe {
+        let mut state: mbstate_t = mem::zeroed();
+        let mut src_ptr = src.as_ptr() as *const c_char;
+        match MbConvResult::from_raw(mbsrtowcs(ptr::null_mut(), &mut src_ptr, 0, &mut state)) {
+            MbConvResult::Consumed(len) => Some(len),
+            _ => None,
+        }
+    }
+}
+
+/**
+Converts a whole zero-terminated multibyte string into `out`, with a single call to `mbsrtowcs`.
+
+`out` must be exactly as long as `mbs_to_wcs_bulk_len` says; the terminator is not written.  Returns `false` if the conversion failed, or did not fill `out` exactly, in which case the contents of `out` are unspecified.
+*/
+pub fn mbs_to_wcs_bulk_into(src: &[MbUnit], out: &mut [WUnit]) -> bool {
+    debug_assert!(src.last().map(|u| u.0 == 0).unwrap_or(false), "string must end with its terminator");
+
+    unsafe {
+        let mut state: mbstate_t = mem::zeroed();
+        let mut src_ptr = src.as_ptr() as *const c_char;
+        // `WUnit` is a `repr(C)` wrapper around `wchar_t`.
+        let len = mbsrtowcs(out.as_mut_ptr() as *mut wchar_t, &mut src_ptr, out.len(), &mut state);
+        // With no room for the terminator, `mbsrtowcs` stops just short of it, and leaves the source pointer there.
+        match MbConvResult::from_raw(len) {
+            MbConvResult::Consumed(len) => len == out.len() && src_ptr == src.as_ptr().offset(src.len() as isize - 1) as *const c_char,
+            _ => false,
+        }
+    }
+}
+
+/**
+Converts a whole zero-terminated wide string to multibyte units, with a single call to `wcsrtombs`.
+
+This is the reverse of `mbs_to_wcs_bulk`, and has the same requirements and failure behaviour.  The output is sized exactly, by asking `wcsrtombs` for its length before converting.
+*/
+pub fn wcs_to_mbs_bulk(src: &[WUnit]) -> Option<Vec<MbUnit>> {
+    debug_assert!(src.last().map(|u| u.0 == 0).unwrap_or(false), "string must end with its terminator");
+
+    unsafe {
+        let mut state: mbstate_t = mem::zeroed();
+        let mut src_ptr = src.as_ptr() as *const wchar_t;
+        let len = match MbConvResult::from_raw(wcsrtombs(ptr::null_mut(), &mut src_ptr, 0, &mut state)) {
+            MbConvResult::Consumed(len) => len,
+            _ => return None,
+        };
+
+        let mut state: mbstate_t = mem::zeroed();
+        let mut src_ptr = src.as_ptr() as *const wchar_t;
+        let mut out: Vec<MbUnit> = Vec::with_capacity(len + 1);
+        let written = wcsrtombs(out.as_mut_ptr() as *mut c_char, &mut src_ptr, len + 1, &mut state);
+        match MbConvResult::from_raw(written) {
+            MbConvResult::Consumed(written) if written == len && src_ptr.is_null() => {
+                out.set_len(written);
+                Some(out)
+            },
+            _ => None,
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -268,6 +519,16 @@ impl ::std::error::Error for MbsToWcError {
     }
 }
 
+impl ErrorOffset for MbsToWcError {
+    fn offset(&self) -> Option<usize> {
+        match *self {
+            MbsToWcError::InvalidAt(at) => Some(at),
+            MbsToWcError::Incomplete => None,
+            MbsToWcError::OutOfBufferAt(at) => Some(at),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum WcsToMbError {
     InvalidAt(usize),
@@ -289,6 +550,14 @@ impl ::std::error::Error for WcsToMbError {
     }
 }
 
+impl ErrorOffset for WcsToMbError {
+    fn offset(&self) -> Option<usize> {
+        match *self {
+            WcsToMbError::InvalidAt(at) => Some(at),
+        }
+    }
+}
+
 impl From<NoError> for WcsToMbError {
     fn from(v: NoError) -> Self {
         match v {}
@@ -344,3 +613,13 @@ impl ::std::error::Error for MbsToUniError {
         }
     }
 }
+
+impl ErrorOffset for MbsToUniError {
+    fn offset(&self) -> Option<usize> {
+        match *self {
+            MbsToUniError::InvalidAt(at) => Some(at),
+            MbsToUniError::Incomplete => None,
+            MbsToUniError::OutOfBufferAt(at) => Some(at),
+        }
+    }
+}
@@ -2,21 +2,41 @@
 Encoding conversion support.
 */
 use std::fmt;
+use util::MapOffset;
 
+#[cfg(all(unix, feature="iconv"))]
+pub mod iconv;
+pub mod identity;
+pub mod jni_mtf8;
+pub mod mb_x_uchar;
 pub mod mb_x_wc;
+pub mod utf16;
+pub mod utf32;
+pub mod utf8;
+pub mod wide;
+#[cfg(windows)]
+pub mod win_cp;
+pub mod wtf8;
 
-#[cfg(target_os="linux")]
-pub mod linux;
+/**
+Implemented by transcoding errors which can report where in the source string they occurred.
 
-#[cfg(target_os="linux")]
-pub use self::linux as os;
+This is what allows `SeStr::transcode_with` to describe each substitution it makes.
+*/
+pub trait ErrorOffset {
+    /**
+    Returns the offset, in source units, of the first unit which could not be transcoded.
 
-#[cfg(target_os="windows")]
-pub mod windows;
+    Returns `None` if the source ended part-way through a sequence.
+    */
+    fn offset(&self) -> Option<usize>;
+}
 
-#[cfg(target_os="windows")]
-pub use self::windows as os;
+/**
+The error produced when decoding `Wide` or `WinUnicode` units into Unicode.
 
+As with the other decoders, `InvalidAt` gives the offset of the unit which begins the ill-formed sequence.  For a high surrogate which is not followed by a low surrogate, that is the high surrogate.
+*/
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum WcToUniError {
     InvalidAt(usize),
@@ -41,6 +61,24 @@ impl ::std::error::Error for WcToUniError {
     }
 }
 
+impl MapOffset for WcToUniError {
+    fn map_offset<F>(self, f: F) -> Self where F: FnOnce(usize) -> usize {
+        match self {
+            WcToUniError::InvalidAt(at) => WcToUniError::InvalidAt(f(at)),
+            WcToUniError::Incomplete => WcToUniError::Incomplete,
+        }
+    }
+}
+
+impl ErrorOffset for WcToUniError {
+    fn offset(&self) -> Option<usize> {
+        match *self {
+            WcToUniError::InvalidAt(at) => Some(at),
+            WcToUniError::Incomplete => None,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum NoError {}
 
@@ -61,3 +99,9 @@ impl ::std::error::Error for NoError {
         match *self {}
     }
 }
+
+impl ErrorOffset for NoError {
+    fn offset(&self) -> Option<usize> {
+        match *self {}
+    }
+}
@@ -0,0 +1,545 @@
+/*!
+Transcoders for the UTF-16 family of encodings.
+
+The decoding and encoding iterators are generic over the unit type, by way of `Utf16CodeUnit`, so that the same logic serves both native and explicit byte order encodings.
+*/
+use std::char;
+use std::fmt;
+use std::marker::PhantomData;
+use encoding::{TranscodeSource, TranscodeTo, Transparent, TransparentAs, Recoverable, Unit, UnitIter, CheckedUnicode, Utf16, Utf16Unit,
+    Utf16Le, Utf16LeUnit, Utf16Be, Utf16BeUnit, W16Unit, WinUnicode, WwUnit};
+use encoding::conv::{ErrorOffset, NoError};
+use encoding::conv::utf32::MEASURE_CHUNK_UNITS;
+use util::write_utf8;
+
+impl<It> TranscodeTo<CheckedUnicode> for UnitIter<Utf16, It> where It: Iterator<Item=Utf16Unit> {
+    type Iter = Utf16ToUniIter<It>;
+    type Error = Utf16ToUniError;
+
+    fn transcode(self) -> Self::Iter {
+        Utf16ToUniIter::new(self.into_iter())
+    }
+
+    fn decode_to_string(units: &[<Self as TranscodeSource>::Unit]) -> Option<String> {
+        utf16_to_string(units)
+    }
+}
+
+impl<It> TranscodeTo<Utf16> for UnitIter<CheckedUnicode, It> where It: Iterator<Item=char> {
+    type Iter = UniToUtf16Iter<It, Utf16Unit>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        UniToUtf16Iter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<CheckedUnicode> for UnitIter<WinUnicode, It> where It: Iterator<Item=WwUnit> {
+    type Iter = Utf16ToUniIter<It>;
+    type Error = Utf16ToUniError;
+
+    fn transcode(self) -> Self::Iter {
+        Utf16ToUniIter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<WinUnicode> for UnitIter<CheckedUnicode, It> where It: Iterator<Item=char> {
+    type Iter = UniToUtf16Iter<It, WwUnit>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        UniToUtf16Iter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<CheckedUnicode> for UnitIter<Utf16Le, It> where It: Iterator<Item=Utf16LeUnit> {
+    type Iter = Utf16ToUniIter<It>;
+    type Error = Utf16ToUniError;
+
+    fn transcode(self) -> Self::Iter {
+        Utf16ToUniIter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<Utf16Le> for UnitIter<CheckedUnicode, It> where It: Iterator<Item=char> {
+    type Iter = UniToUtf16Iter<It, Utf16LeUnit>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        UniToUtf16Iter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<CheckedUnicode> for UnitIter<Utf16Be, It> where It: Iterator<Item=Utf16BeUnit> {
+    type Iter = Utf16ToUniIter<It>;
+    type Error = Utf16ToUniError;
+
+    fn transcode(self) -> Self::Iter {
+        Utf16ToUniIter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<Utf16Be> for UnitIter<CheckedUnicode, It> where It: Iterator<Item=char> {
+    type Iter = UniToUtf16Iter<It, Utf16BeUnit>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        UniToUtf16Iter::new(self.into_iter())
+    }
+}
+
+/*
+Byte-order conversions to and from the native order change nothing at all, so those encodings can be borrowed as one another.
+*/
+#[cfg(target_endian="little")]
+unsafe impl TransparentAs<Utf16> for Utf16Le {}
+#[cfg(target_endian="little")]
+unsafe impl TransparentAs<Utf16Le> for Utf16 {}
+#[cfg(target_endian="big")]
+unsafe impl TransparentAs<Utf16> for Utf16Be {}
+#[cfg(target_endian="big")]
+unsafe impl TransparentAs<Utf16Be> for Utf16 {}
+
+impl<It> TranscodeTo<Utf16> for UnitIter<Utf16Le, It> where It: Iterator<Item=Utf16LeUnit> {
+    type Iter = Utf16SwapIter<It, Utf16Unit>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        Utf16SwapIter::new(self.into_iter())
+    }
+
+    #[cfg(target_endian="little")]
+    fn transparent() -> Option<Transparent<Self, Utf16>> {
+        Some(Transparent::new())
+    }
+}
+
+impl<It> TranscodeTo<Utf16Le> for UnitIter<Utf16, It> where It: Iterator<Item=Utf16Unit> {
+    type Iter = Utf16SwapIter<It, Utf16LeUnit>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        Utf16SwapIter::new(self.into_iter())
+    }
+
+    #[cfg(target_endian="little")]
+    fn transparent() -> Option<Transparent<Self, Utf16Le>> {
+        Some(Transparent::new())
+    }
+}
+
+impl<It> TranscodeTo<Utf16> for UnitIter<Utf16Be, It> where It: Iterator<Item=Utf16BeUnit> {
+    type Iter = Utf16SwapIter<It, Utf16Unit>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        Utf16SwapIter::new(self.into_iter())
+    }
+
+    #[cfg(target_endian="big")]
+    fn transparent() -> Option<Transparent<Self, Utf16>> {
+        Some(Transparent::new())
+    }
+}
+
+impl<It> TranscodeTo<Utf16Be> for UnitIter<Utf16, It> where It: Iterator<Item=Utf16Unit> {
+    type Iter = Utf16SwapIter<It, Utf16BeUnit>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        Utf16SwapIter::new(self.into_iter())
+    }
+
+    #[cfg(target_endian="big")]
+    fn transparent() -> Option<Transparent<Self, Utf16Be>> {
+        Some(Transparent::new())
+    }
+}
+
+/**
+Abstracts over unit types which hold UTF-16 code units, differing only in their storage byte order or encoding.
+
+The logical value of a unit is read with `Unit::to_raw`.
+*/
+pub trait Utf16CodeUnit: Unit {
+    /**
+    Constructs a unit from a logical code unit value, given in native byte order.
+    */
+    fn from_code_unit(cu: u16) -> Self;
+}
+
+impl Utf16CodeUnit for Utf16Unit {
+    #[inline]
+    fn from_code_unit(cu: u16) -> Self { Utf16Unit(cu) }
+}
+
+impl Utf16CodeUnit for W16Unit {
+    #[inline]
+    fn from_code_unit(cu: u16) -> Self { W16Unit(cu) }
+}
+
+impl Utf16CodeUnit for WwUnit {
+    #[inline]
+    fn from_code_unit(cu: u16) -> Self { WwUnit(cu) }
+}
+
+impl Utf16CodeUnit for Utf16LeUnit {
+    #[inline]
+    fn from_code_unit(cu: u16) -> Self { Utf16LeUnit::new(cu) }
+}
+
+impl Utf16CodeUnit for Utf16BeUnit {
+    #[inline]
+    fn from_code_unit(cu: u16) -> Self { Utf16BeUnit::new(cu) }
+}
+
+/**
+Decodes the next code point from an iterator of UTF-16 units.
+
+This is shared by the decoders which stop at the first error; see `next_utf16_code_point_resuming` for those which do not.  `at` is the offset of the next unit, and is advanced past each decoded code point.  On error, `iter` is set to `None`, so that decoding stops.
+*/
+pub fn next_utf16_code_point<It>(iter: &mut Option<It>, at: &mut usize) -> Option<Result<char, Utf16ToUniError>>
+where It: Iterator, It::Item: Unit {
+    let cu0 = match iter.as_mut().and_then(|iter| iter.next()) {
+        Some(cu0) => cu0.to_raw(),
+        None => return None,
+    };
+
+    Some(finish_utf16_code_point(cu0, iter, at))
+}
+
+/**
+Decodes a code point from an iterator of UTF-16 units, given its first unit, which has already been taken from `iter`.
+
+This is `next_utf16_code_point` for decoders which need to inspect the first unit themselves.
+
+Errors are reported at the unit which begins the ill-formed sequence: a lone low surrogate at its own offset, and a high surrogate which is not followed by a low surrogate at the offset of the high surrogate.  `at` only advances past a character once it has been decoded, so it is always the offset of the first unit being examined.
+*/
+pub fn finish_utf16_code_point<It>(cu0: u32, iter: &mut Option<It>, at: &mut usize) -> Result<char, Utf16ToUniError>
+where It: Iterator, It::Item: Unit {
+    let cp = match cu0 {
+        0x0000 ... 0xd7ff | 0xe000 ... 0xffff => {
+            *at += 1;
+            cu0
+        },
+        0xdc00 ... 0xdfff => {
+            *iter = None;
+            return Err(Utf16ToUniError::InvalidAt(*at));
+        },
+        0xd800 ... 0xdbff => {
+            let cu1 = match iter.as_mut().and_then(|iter| iter.next()) {
+                Some(cu1) => cu1.to_raw(),
+                None => {
+                    *iter = None;
+                    return Err(Utf16ToUniError::Incomplete);
+                },
+            };
+
+            if !(0xdc00 <= cu1 && cu1 <= 0xdfff) {
+                *iter = None;
+                return Err(Utf16ToUniError::InvalidAt(*at));
+            }
+
+            *at += 2;
+
+            let hi = cu0 & 0x3ff;
+            let lo = cu1 & 0x3ff;
+            0x10000 + ((hi << 10) | lo)
+        },
+        _ => {
+            // Not a 16-bit unit at all.
+            *iter = None;
+            return Err(Utf16ToUniError::InvalidAt(*at));
+        },
+    };
+
+    // Surrogates were excluded above, so this cannot fail.
+    Ok(char::from_u32(cp).expect("decoded invalid code point"))
+}
+
+/**
+Decodes the next code point from an iterator of UTF-16 units, continuing past errors.
+
+Errors are reported at the same offsets as by `next_utf16_code_point`, but each unpaired surrogate (or unit which is not 16 bits at all) produces a single error, after which decoding resumes with the next unit.  A high surrogate followed by something other than a low surrogate does not consume that unit; it is kept in `pending` and decoded next.
+*/
+pub fn next_utf16_code_point_resuming<It>(iter: &mut It, pending: &mut Option<u32>, at: &mut usize) -> Option<Result<char, Utf16ToUniError>>
+where It: Iterator, It::Item: Unit {
+    let cu0 = match pending.take().or_else(|| iter.next().map(|cu| cu.to_raw())) {
+        Some(cu0) => cu0,
+        None => return None,
+    };
+
+    let start = *at;
+    *at += 1;
+
+    let cp = match cu0 {
+        0x0000 ... 0xd7ff | 0xe000 ... 0xffff => cu0,
+        0xd800 ... 0xdbff => {
+            let cu1 = match iter.next() {
+                Some(cu1) => cu1.to_raw(),
+                None => return Some(Err(Utf16ToUniError::Incomplete)),
+            };
+
+            if !(0xdc00 <= cu1 && cu1 <= 0xdfff) {
+                // This unit might start the next code point.
+                *pending = Some(cu1);
+                return Some(Err(Utf16ToUniError::InvalidAt(start)));
+            }
+
+            *at += 1;
+            0x10000 + (((cu0 & 0x3ff) << 10) | (cu1 & 0x3ff))
+        },
+        // A lone low surrogate, or not a 16-bit unit at all.
+        _ => return Some(Err(Utf16ToUniError::InvalidAt(start))),
+    };
+
+    // Surrogates were excluded above, so this cannot fail.
+    Some(Ok(char::from_u32(cp).expect("decoded invalid code point")))
+}
+
+/**
+Decodes a whole slice of UTF-16 units into a `String`.
+
+This measures the result before decoding anything, so that the string is allocated exactly once.  It returns `None` if the units are not valid UTF-16; the caller should decode them again with an iterator to find out where.
+*/
+pub fn utf16_to_string<U>(units: &[U]) -> Option<String> where U: Unit {
+    let is_high = |cu: u32| (cu & 0xfc00 == 0xd800) as u32;
+    let is_low = |cu: u32| (cu & 0xfc00 == 0xdc00) as u32;
+
+    match (units.first(), units.last()) {
+        (Some(first), Some(last)) => if is_low(first.to_raw()) | is_high(last.to_raw()) != 0 {
+            return None;
+        },
+        _ => return Some(String::new()),
+    }
+
+    // As in `utf32_to_string`, this is written to be vectorised.  Every unit of a surrogate pair counts as two bytes, and every high surrogate must be followed by a low surrogate, and *vice versa*.
+    let mut bytes = units.len();
+    for chunk in units.chunks(MEASURE_CHUNK_UNITS) {
+        let mut extra = 0u32;
+        let mut invalid = 0u32;
+        for cu in chunk {
+            let cu = cu.to_raw();
+            extra += (cu >= 0x80) as u32 + (cu >= 0x800) as u32 - (cu & 0xf800 == 0xd800) as u32;
+            invalid |= (cu > 0xffff) as u32;
+        }
+        if invalid != 0 {
+            return None;
+        }
+        bytes += extra as usize;
+    }
+    let mut invalid = 0u32;
+    for pair in units.windows(2) {
+        invalid |= is_high(pair[0].to_raw()) ^ is_low(pair[1].to_raw());
+    }
+    if invalid != 0 {
+        return None;
+    }
+
+    let mut buf = Vec::<u8>::with_capacity(bytes);
+    unsafe {
+        let dst = buf.as_mut_ptr();
+        let mut at = 0;
+        let mut units = units.iter();
+        while let Some(cu0) = units.next() {
+            let mut cp = cu0.to_raw();
+            if is_high(cp) != 0 {
+                // Pairing was checked above, so there is always a low surrogate next.
+                let cu1 = units.next().map(Unit::to_raw).unwrap_or(0xdc00);
+                cp = 0x10000 + (((cp & 0x3ff) << 10) | (cu1 & 0x3ff));
+            }
+            at += write_utf8(dst.offset(at as isize), cp);
+        }
+        debug_assert_eq!(at, bytes);
+        buf.set_len(bytes);
+        Some(String::from_utf8_unchecked(buf))
+    }
+}
+
+
+/**
+Returns the `size_hint` for a decoder built on `next_utf16_code_point_resuming`, with `pending` units already taken from `iter`.
+
+Every code point or error takes either one or two units.
+*/
+pub fn utf16_decode_size_hint<It>(iter: &It, pending: bool) -> (usize, Option<usize>)
+where It: Iterator {
+    let pending = pending as usize;
+    let (lo, hi) = iter.size_hint();
+    ((lo.saturating_add(pending).saturating_add(1)) / 2, hi.and_then(|hi| hi.checked_add(pending)))
+}
+
+/**
+Returns the `size_hint` for an encoder producing UTF-16 units from an iterator of code points, with `buffered` units already waiting to be yielded.
+
+Every code point produces either one or two units.
+*/
+pub fn utf16_encode_size_hint<It>(iter: &Option<It>, buffered: usize) -> (usize, Option<usize>)
+where It: Iterator {
+    match *iter {
+        Some(ref iter) => {
+            let (lo, hi) = iter.size_hint();
+            (lo.saturating_add(buffered), hi.and_then(|hi| hi.checked_mul(2)).and_then(|hi| hi.checked_add(buffered)))
+        },
+        None => (buffered, Some(buffered)),
+    }
+}
+
+/**
+Decodes a string which may contain invalid UTF-16.
+
+Each unpaired surrogate produces a single error, after which decoding resumes with the next unit; see `next_utf16_code_point_resuming`.
+*/
+pub struct Utf16ToUniIter<It> {
+    at: usize,
+    iter: It,
+    pending: Option<u32>,
+    skip_bom: bool,
+}
+
+impl<It> Utf16ToUniIter<It> {
+    pub fn new(iter: It) -> Self {
+        Utf16ToUniIter {
+            at: 0,
+            iter: iter,
+            pending: None,
+            skip_bom: false,
+        }
+    }
+
+    /**
+    Treats a leading U+FEFF as a byte order mark, rather than content, and skips over it.
+
+    Error offsets still count the byte order mark.  Only a mark in native byte order is recognised; see `SeStr::detect_bom`.
+    */
+    pub fn skip_bom(mut self) -> Self {
+        self.skip_bom = self.at == 0;
+        self
+    }
+}
+
+impl<It> Iterator for Utf16ToUniIter<It> where It: Iterator, It::Item: Utf16CodeUnit {
+    type Item = Result<char, Utf16ToUniError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.skip_bom {
+            self.skip_bom = false;
+            match next_utf16_code_point_resuming(&mut self.iter, &mut self.pending, &mut self.at) {
+                Some(Ok('\u{feff}')) => (),
+                other => return other,
+            }
+        }
+        next_utf16_code_point_resuming(&mut self.iter, &mut self.pending, &mut self.at)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lo, hi) = utf16_decode_size_hint(&self.iter, self.pending.is_some());
+        (lo.saturating_sub(self.skip_bom as usize), hi)
+    }
+}
+
+impl<It> Recoverable for Utf16ToUniIter<It> {}
+
+pub struct UniToUtf16Iter<It, U> {
+    buf: Option<U>,
+    iter: Option<It>,
+}
+
+impl<It, U> UniToUtf16Iter<It, U> {
+    pub fn new(iter: It) -> Self {
+        UniToUtf16Iter {
+            buf: None,
+            iter: Some(iter),
+        }
+    }
+}
+
+impl<It, U> Iterator for UniToUtf16Iter<It, U> where It: Iterator<Item=char>, U: Utf16CodeUnit {
+    type Item = Result<U, NoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(cu) = self.buf.take() {
+            return Some(Ok(cu));
+        }
+
+        let ch = match self.iter.as_mut().and_then(|iter| iter.next()) {
+            Some(ch) => ch,
+            None => return None,
+        };
+
+        let mut utf16 = [0; 2];
+        let utf16 = ch.encode_utf16(&mut utf16[..]);
+        self.buf = utf16.get(1).map(|&cu| U::from_code_unit(cu));
+        Some(Ok(U::from_code_unit(utf16[0])))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        utf16_encode_size_hint(&self.iter, self.buf.is_some() as usize)
+    }
+}
+
+/**
+Converts between UTF-16 unit types of differing byte order or encoding.
+
+This does not validate the contents; unpaired surrogates are passed through untouched.
+*/
+pub struct Utf16SwapIter<It, U> {
+    iter: It,
+    _marker: PhantomData<U>,
+}
+
+impl<It, U> Utf16SwapIter<It, U> {
+    pub fn new(iter: It) -> Self {
+        Utf16SwapIter {
+            iter: iter,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<It, U> Iterator for Utf16SwapIter<It, U> where It: Iterator, It::Item: Utf16CodeUnit, U: Utf16CodeUnit {
+    type Item = Result<U, NoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|cu| Ok(U::from_code_unit(cu.to_raw() as u16)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<It, U> ExactSizeIterator for Utf16SwapIter<It, U> where It: ExactSizeIterator, It::Item: Utf16CodeUnit, U: Utf16CodeUnit {}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Utf16ToUniError {
+    InvalidAt(usize),
+    Incomplete,
+}
+
+impl fmt::Display for Utf16ToUniError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Utf16ToUniError::InvalidAt(at) => write!(fmt, "invalid unit at offset {}", at),
+            Utf16ToUniError::Incomplete => write!(fmt, "incomplete unit"),
+        }
+    }
+}
+
+impl ::std::error::Error for Utf16ToUniError {
+    fn description(&self) -> &str {
+        match *self {
+            Utf16ToUniError::InvalidAt(_) => "invalid unit",
+            Utf16ToUniError::Incomplete => "incomplete unit",
+        }
+    }
+}
+
+impl ErrorOffset for Utf16ToUniError {
+    fn offset(&self) -> Option<usize> {
+        match *self {
+            Utf16ToUniError::InvalidAt(at) => Some(at),
+            Utf16ToUniError::Incomplete => None,
+        }
+    }
+}
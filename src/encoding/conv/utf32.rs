@@ -0,0 +1,438 @@
+/*!
+Transcoders for the UTF-32 family of encodings.
+
+The decoding and encoding iterators are generic over the unit type, by way of `Utf32CodeUnit`, so that the same logic serves both native and explicit byte order encodings.
+*/
+use std::char;
+use std::fmt;
+use std::marker::PhantomData;
+use encoding::{TranscodeSource, TranscodeTo, Transparent, TransparentAs, Recoverable, Unit, UnitIter, CheckedUnicode, Utf32, Utf32Unit,
+    Utf32Le, Utf32LeUnit, Utf32Be, Utf32BeUnit};
+use encoding::conv::{ErrorOffset, NoError};
+use util::write_utf8;
+
+impl<It> TranscodeTo<CheckedUnicode> for UnitIter<Utf32, It> where It: Iterator<Item=Utf32Unit> {
+    type Iter = RecoverableUtf32ToUniIter<It>;
+    type Error = Utf32ToUniError;
+
+    fn transcode(self) -> Self::Iter {
+        RecoverableUtf32ToUniIter::new(self.into_iter())
+    }
+
+    fn decode_to_string(units: &[<Self as TranscodeSource>::Unit]) -> Option<String> {
+        utf32_to_string(units)
+    }
+}
+
+impl<It> TranscodeTo<Utf32> for UnitIter<CheckedUnicode, It> where It: Iterator<Item=char> {
+    type Iter = UniToUtf32Iter<It, Utf32Unit>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        UniToUtf32Iter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<CheckedUnicode> for UnitIter<Utf32Le, It> where It: Iterator<Item=Utf32LeUnit> {
+    type Iter = Utf32ToUniIter<It>;
+    type Error = Utf32ToUniError;
+
+    fn transcode(self) -> Self::Iter {
+        Utf32ToUniIter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<Utf32Le> for UnitIter<CheckedUnicode, It> where It: Iterator<Item=char> {
+    type Iter = UniToUtf32Iter<It, Utf32LeUnit>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        UniToUtf32Iter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<CheckedUnicode> for UnitIter<Utf32Be, It> where It: Iterator<Item=Utf32BeUnit> {
+    type Iter = Utf32ToUniIter<It>;
+    type Error = Utf32ToUniError;
+
+    fn transcode(self) -> Self::Iter {
+        Utf32ToUniIter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<Utf32Be> for UnitIter<CheckedUnicode, It> where It: Iterator<Item=char> {
+    type Iter = UniToUtf32Iter<It, Utf32BeUnit>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        UniToUtf32Iter::new(self.into_iter())
+    }
+}
+
+/*
+As with UTF-16, the native byte order variant is stored exactly like `Utf32`.
+*/
+#[cfg(target_endian="little")]
+unsafe impl TransparentAs<Utf32> for Utf32Le {}
+#[cfg(target_endian="little")]
+unsafe impl TransparentAs<Utf32Le> for Utf32 {}
+#[cfg(target_endian="big")]
+unsafe impl TransparentAs<Utf32> for Utf32Be {}
+#[cfg(target_endian="big")]
+unsafe impl TransparentAs<Utf32Be> for Utf32 {}
+
+impl<It> TranscodeTo<Utf32> for UnitIter<Utf32Le, It> where It: Iterator<Item=Utf32LeUnit> {
+    type Iter = Utf32SwapIter<It, Utf32Unit>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        Utf32SwapIter::new(self.into_iter())
+    }
+
+    #[cfg(target_endian="little")]
+    fn transparent() -> Option<Transparent<Self, Utf32>> {
+        Some(Transparent::new())
+    }
+}
+
+impl<It> TranscodeTo<Utf32Le> for UnitIter<Utf32, It> where It: Iterator<Item=Utf32Unit> {
+    type Iter = Utf32SwapIter<It, Utf32LeUnit>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        Utf32SwapIter::new(self.into_iter())
+    }
+
+    #[cfg(target_endian="little")]
+    fn transparent() -> Option<Transparent<Self, Utf32Le>> {
+        Some(Transparent::new())
+    }
+}
+
+impl<It> TranscodeTo<Utf32> for UnitIter<Utf32Be, It> where It: Iterator<Item=Utf32BeUnit> {
+    type Iter = Utf32SwapIter<It, Utf32Unit>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        Utf32SwapIter::new(self.into_iter())
+    }
+
+    #[cfg(target_endian="big")]
+    fn transparent() -> Option<Transparent<Self, Utf32>> {
+        Some(Transparent::new())
+    }
+}
+
+impl<It> TranscodeTo<Utf32Be> for UnitIter<Utf32, It> where It: Iterator<Item=Utf32Unit> {
+    type Iter = Utf32SwapIter<It, Utf32BeUnit>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        Utf32SwapIter::new(self.into_iter())
+    }
+
+    #[cfg(target_endian="big")]
+    fn transparent() -> Option<Transparent<Self, Utf32Be>> {
+        Some(Transparent::new())
+    }
+}
+
+/**
+Abstracts over UTF-32 unit types which differ only in their storage byte order.
+
+The logical value of a unit is read with `Unit::to_raw`.
+*/
+pub trait Utf32CodeUnit: Unit {
+    /**
+    Constructs a unit from a logical code unit value, given in native byte order.
+    */
+    fn from_code_unit(cu: u32) -> Self;
+}
+
+impl Utf32CodeUnit for Utf32Unit {
+    #[inline]
+    fn from_code_unit(cu: u32) -> Self { Utf32Unit(cu) }
+}
+
+impl Utf32CodeUnit for Utf32LeUnit {
+    #[inline]
+    fn from_code_unit(cu: u32) -> Self { Utf32LeUnit::new(cu) }
+}
+
+impl Utf32CodeUnit for Utf32BeUnit {
+    #[inline]
+    fn from_code_unit(cu: u32) -> Self { Utf32BeUnit::new(cu) }
+}
+
+/**
+Decodes the next code point from an iterator of UTF-32 units.
+
+This is shared by every decoder for a UTF-32-based encoding.  `at` is the offset of the next unit, and is advanced past each decoded code point.  On error, `iter` is set to `None`, so that decoding stops.
+*/
+pub fn next_utf32_code_point<It>(iter: &mut Option<It>, at: &mut usize) -> Option<Result<char, Utf32ToUniError>>
+where It: Iterator, It::Item: Unit {
+    let cp = match iter.as_mut().and_then(|iter| iter.next()) {
+        Some(cu) => cu.to_raw(),
+        None => return None,
+    };
+
+    // `from_u32` rejects both surrogates and anything above U+10FFFF.
+    match char::from_u32(cp) {
+        Some(c) => {
+            *at += 1;
+            Some(Ok(c))
+        },
+        None => {
+            *iter = None;
+            Some(Err(Utf32ToUniError::InvalidAt(*at)))
+        },
+    }
+}
+
+/**
+Decodes a whole slice of UTF-32 units into a `String`.
+
+This measures the result before decoding anything, so that the string is allocated exactly once.  It returns `None` if the units are not valid UTF-32; the caller should decode them again with an iterator to find out where.
+*/
+pub fn utf32_to_string<U>(units: &[U]) -> Option<String> where U: Unit {
+    let mut bytes = units.len();
+    // The measuring loop is written without branches, and with 32-bit counters, so that it can be vectorised.  Chunking keeps the counters from overflowing.
+    for chunk in units.chunks(MEASURE_CHUNK_UNITS) {
+        let mut extra = 0u32;
+        let mut invalid = 0u32;
+        for u in chunk {
+            let cp = u.to_raw();
+            extra += (cp >= 0x80) as u32 + (cp >= 0x800) as u32 + (cp >= 0x10000) as u32;
+            invalid |= ((cp.wrapping_sub(0xd800) < 0x800) | (cp > 0x10ffff)) as u32;
+        }
+        if invalid != 0 {
+            return None;
+        }
+        bytes += extra as usize;
+    }
+
+    let mut buf = Vec::<u8>::with_capacity(bytes);
+    unsafe {
+        let dst = buf.as_mut_ptr();
+        let mut at = 0;
+        for u in units {
+            at += write_utf8(dst.offset(at as isize), u.to_raw());
+        }
+        debug_assert_eq!(at, bytes);
+        buf.set_len(bytes);
+        Some(String::from_utf8_unchecked(buf))
+    }
+}
+
+/**
+The number of units measured at a time by `utf32_to_string` and `utf16_to_string`.
+*/
+pub const MEASURE_CHUNK_UNITS: usize = 4096;
+
+
+pub struct Utf32ToUniIter<It> {
+    at: usize,
+    iter: Option<It>,
+    skip_bom: bool,
+}
+
+impl<It> Utf32ToUniIter<It> {
+    pub fn new(iter: It) -> Self {
+        Utf32ToUniIter {
+            at: 0,
+            iter: Some(iter),
+            skip_bom: false,
+        }
+    }
+
+    /**
+    Treats a leading U+FEFF as a byte order mark, rather than content, and skips over it.
+
+    Error offsets still count the byte order mark.  Only a mark in native byte order is recognised; see `SeStr::detect_bom`.
+    */
+    pub fn skip_bom(mut self) -> Self {
+        self.skip_bom = self.at == 0;
+        self
+    }
+}
+
+impl<It> Iterator for Utf32ToUniIter<It> where It: Iterator, It::Item: Utf32CodeUnit {
+    type Item = Result<char, Utf32ToUniError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.skip_bom {
+            self.skip_bom = false;
+            match next_utf32_code_point(&mut self.iter, &mut self.at) {
+                Some(Ok('\u{feff}')) => (),
+                other => return other,
+            }
+        }
+        next_utf32_code_point(&mut self.iter, &mut self.at)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.iter {
+            Some(ref iter) => {
+                let (lo, hi) = iter.size_hint();
+                // An invalid unit ends decoding, so only one more item is certain.
+                (::std::cmp::min(lo.saturating_sub(self.skip_bom as usize), 1), hi)
+            },
+            None => (0, Some(0)),
+        }
+    }
+}
+
+/**
+Decodes UTF-32 units, continuing past invalid ones.
+
+Every unit is decoded independently, so an invalid unit (a surrogate, or a value above U+10FFFF) produces an error in its place, and decoding resumes with the next unit.
+*/
+pub struct RecoverableUtf32ToUniIter<It> {
+    at: usize,
+    iter: It,
+    first: Option<Result<char, Utf32ToUniError>>,
+}
+
+impl<It> RecoverableUtf32ToUniIter<It> {
+    pub fn new(iter: It) -> Self {
+        RecoverableUtf32ToUniIter {
+            at: 0,
+            iter: iter,
+            first: None,
+        }
+    }
+}
+
+impl<It> RecoverableUtf32ToUniIter<It> where It: Iterator, It::Item: Utf32CodeUnit {
+    /**
+    Treats a leading U+FEFF as a byte order mark, rather than content, and skips over it.
+
+    Error offsets still count the byte order mark.  Only a mark in native byte order is recognised; see `SeStr::detect_bom`.
+
+    So that the length stays exact, the first unit is decoded immediately.
+    */
+    pub fn skip_bom(mut self) -> Self {
+        if self.at == 0 {
+            match self.next() {
+                Some(Ok('\u{feff}')) => (),
+                other => self.first = other,
+            }
+        }
+        self
+    }
+}
+
+impl<It> Iterator for RecoverableUtf32ToUniIter<It> where It: Iterator, It::Item: Utf32CodeUnit {
+    type Item = Result<char, Utf32ToUniError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(first) = self.first.take() {
+            return Some(first);
+        }
+        self.iter.next().map(|cu| {
+            let at = self.at;
+            self.at += 1;
+            char::from_u32(cu.to_raw()).ok_or(Utf32ToUniError::InvalidAt(at))
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let first = self.first.is_some() as usize;
+        let (lo, hi) = self.iter.size_hint();
+        (lo.saturating_add(first), hi.and_then(|hi| hi.checked_add(first)))
+    }
+}
+
+impl<It> ExactSizeIterator for RecoverableUtf32ToUniIter<It> where It: ExactSizeIterator, It::Item: Utf32CodeUnit {}
+
+impl<It> Recoverable for RecoverableUtf32ToUniIter<It> {}
+
+pub struct UniToUtf32Iter<It, U> {
+    iter: It,
+    _marker: PhantomData<U>,
+}
+
+impl<It, U> UniToUtf32Iter<It, U> {
+    pub fn new(iter: It) -> Self {
+        UniToUtf32Iter {
+            iter: iter,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<It, U> Iterator for UniToUtf32Iter<It, U> where It: Iterator<Item=char>, U: Utf32CodeUnit {
+    type Item = Result<U, NoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|c| Ok(U::from_code_unit(c as u32)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<It, U> ExactSizeIterator for UniToUtf32Iter<It, U> where It: ExactSizeIterator<Item=char>, U: Utf32CodeUnit {}
+
+/**
+Converts between UTF-32 unit types of differing byte order.
+
+This does not validate the contents; invalid code points are passed through untouched.
+*/
+pub struct Utf32SwapIter<It, U> {
+    iter: It,
+    _marker: PhantomData<U>,
+}
+
+impl<It, U> Utf32SwapIter<It, U> {
+    pub fn new(iter: It) -> Self {
+        Utf32SwapIter {
+            iter: iter,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<It, U> Iterator for Utf32SwapIter<It, U> where It: Iterator, It::Item: Utf32CodeUnit, U: Utf32CodeUnit {
+    type Item = Result<U, NoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|cu| Ok(U::from_code_unit(cu.to_raw())))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<It, U> ExactSizeIterator for Utf32SwapIter<It, U> where It: ExactSizeIterator, It::Item: Utf32CodeUnit, U: Utf32CodeUnit {}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Utf32ToUniError {
+    InvalidAt(usize),
+}
+
+impl fmt::Display for Utf32ToUniError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Utf32ToUniError::InvalidAt(at) => write!(fmt, "invalid unit at offset {}", at),
+        }
+    }
+}
+
+impl ::std::error::Error for Utf32ToUniError {
+    fn description(&self) -> &str {
+        match *self {
+            Utf32ToUniError::InvalidAt(_) => "invalid unit",
+        }
+    }
+}
+
+impl ErrorOffset for Utf32ToUniError {
+    fn offset(&self) -> Option<usize> {
+        match *self {
+            Utf32ToUniError::InvalidAt(at) => Some(at),
+        }
+    }
+}
@@ -0,0 +1,542 @@
+/*!
+Transcoders for the UTF-8 family of encodings.
+*/
+use std::char;
+use std::fmt;
+use std::str;
+use encoding::{TranscodeSource, TranscodeTo, Recoverable, UnitIter, CheckedUnicode, CheckedUtf8, CheckedUtf8Unit, Utf8, Utf8Unit, Utf16, Utf16Unit};
+use encoding::conv::{ErrorOffset, NoError};
+use encoding::conv::utf16::{finish_utf16_code_point, Utf16ToUniError};
+use util::{Utf8EncodeExt, Utf8EncodeIter};
+
+impl<It> TranscodeTo<CheckedUnicode> for UnitIter<CheckedUtf8, It> where It: Iterator<Item=CheckedUtf8Unit> {
+    type Iter = CheckedUtf8ToUniIter<It>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        CheckedUtf8ToUniIter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<CheckedUtf8> for UnitIter<CheckedUnicode, It> where It: Iterator<Item=char> {
+    type Iter = UniToCheckedUtf8Iter<It>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        UniToCheckedUtf8Iter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<CheckedUnicode> for UnitIter<Utf8, It> where It: Iterator<Item=Utf8Unit> {
+    type Iter = Utf8ToUniIter<It>;
+    type Error = Utf8ToUniError;
+
+    fn transcode(self) -> Self::Iter {
+        Utf8ToUniIter::new(self.into_iter())
+    }
+
+    fn is_valid_units(units: &[<Self as TranscodeSource>::Unit]) -> Option<bool> {
+        Some(validate_utf8(units).is_ok())
+    }
+}
+
+impl<It> TranscodeTo<Utf8> for UnitIter<CheckedUnicode, It> where It: Iterator<Item=char> {
+    type Iter = UniToUtf8Iter<It>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        UniToUtf8Iter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<Utf16> for UnitIter<Utf8, It> where It: Iterator<Item=Utf8Unit> {
+    type Iter = Utf8ToUtf16Iter<It>;
+    type Error = Utf8Utf16Error;
+
+    fn transcode(self) -> Self::Iter {
+        Utf8ToUtf16Iter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<Utf8> for UnitIter<Utf16, It> where It: Iterator<Item=Utf16Unit> {
+    type Iter = Utf16ToUtf8Iter<It>;
+    type Error = Utf8Utf16Error;
+
+    fn transcode(self) -> Self::Iter {
+        Utf16ToUtf8Iter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<Utf8> for UnitIter<CheckedUtf8, It> where It: Iterator<Item=CheckedUtf8Unit> {
+    type Iter = CheckedUtf8ToUtf8Iter<It>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        CheckedUtf8ToUtf8Iter::new(self.into_iter())
+    }
+}
+
+/**
+Validates that a sequence of units is well-formed UTF-8.
+
+This rejects everything `str::from_utf8` does, including overlong encodings, encoded surrogates, and truncated sequences.
+*/
+pub fn validate_utf8(units: &[Utf8Unit]) -> Result<(), Utf8ValidationError> {
+    // `Utf8Unit` is a `repr(C)` wrapper around `u8`.
+    let bytes = unsafe { &*(units as *const [Utf8Unit] as *const [u8]) };
+    match str::from_utf8(bytes) {
+        Ok(_) => Ok(()),
+        Err(err) => match err.error_len() {
+            Some(_) => Err(Utf8ValidationError::InvalidAt(err.valid_up_to())),
+            None => Err(Utf8ValidationError::Incomplete),
+        },
+    }
+}
+
+/**
+Decodes a string which is already known to be valid.
+
+Should the input somehow be invalid, each malformed sequence decodes to U+FFFD REPLACEMENT CHARACTER.
+*/
+pub struct CheckedUtf8ToUniIter<It> {
+    iter: It,
+}
+
+impl<It> CheckedUtf8ToUniIter<It> {
+    pub fn new(iter: It) -> Self {
+        CheckedUtf8ToUniIter {
+            iter: iter,
+        }
+    }
+}
+
+impl<It> Iterator for CheckedUtf8ToUniIter<It> where It: Iterator<Item=CheckedUtf8Unit> {
+    type Item = Result<char, NoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cu0 = match self.iter.next() {
+            Some(cu0) => cu0.0 as u32,
+            None => return None,
+        };
+
+        let (len, init) = match cu0 {
+            0x00 ... 0x7f => return Some(Ok(cu0 as u8 as char)),
+            0xc0 ... 0xdf => (1, cu0 & 0x1f),
+            0xe0 ... 0xef => (2, cu0 & 0x0f),
+            _ => (3, cu0 & 0x07),
+        };
+
+        let mut cp = init;
+        for _ in 0..len {
+            match self.iter.next() {
+                Some(cu) => cp = (cp << 6) | (cu.0 as u32 & 0x3f),
+                None => return Some(Ok('\u{fffd}')),
+            }
+        }
+
+        Some(Ok(char::from_u32(cp).unwrap_or('\u{fffd}')))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lo, hi) = self.iter.size_hint();
+        ((lo + 3) / 4, hi)
+    }
+}
+
+pub struct UniToCheckedUtf8Iter<It> {
+    iter: It,
+    buf: [u8; 4],
+    off: u8,
+    len: u8,
+}
+
+impl<It> UniToCheckedUtf8Iter<It> {
+    pub fn new(iter: It) -> Self {
+        UniToCheckedUtf8Iter {
+            iter: iter,
+            buf: [0; 4],
+            off: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<It> Iterator for UniToCheckedUtf8Iter<It> where It: Iterator<Item=char> {
+    type Item = Result<CheckedUtf8Unit, NoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.off == self.len {
+            let c = match self.iter.next() {
+                Some(c) => c,
+                None => return None,
+            };
+            self.off = 0;
+            self.len = c.encode_utf8(&mut self.buf[..]).len() as u8;
+        }
+
+        let cu = self.buf[self.off as usize];
+        self.off += 1;
+        Some(Ok(CheckedUtf8Unit(cu)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every code point produces between one and four units.
+        let buffered = (self.len - self.off) as usize;
+        let (lo, hi) = self.iter.size_hint();
+        (lo.saturating_add(buffered), hi.and_then(|hi| hi.checked_mul(4)).and_then(|hi| hi.checked_add(buffered)))
+    }
+}
+
+/**
+Decodes a string which may contain invalid UTF-8.
+
+Each invalid sequence produces a single error, after which decoding resumes with the next unit which could not have been part of that sequence.  Sequences are rejected as soon as they cannot be completed validly, so overlong encodings, encoded surrogates, and code points beyond U+10FFFF are all reported at the offset of their first unit.
+*/
+pub struct Utf8ToUniIter<It> {
+    iter: It,
+    at: usize,
+    pending: Option<u8>,
+}
+
+impl<It> Utf8ToUniIter<It> {
+    pub fn new(iter: It) -> Self {
+        Utf8ToUniIter {
+            iter: iter,
+            at: 0,
+            pending: None,
+        }
+    }
+}
+
+impl<It> Iterator for Utf8ToUniIter<It> where It: Iterator<Item=Utf8Unit> {
+    type Item = Result<char, Utf8ToUniError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cu0 = match self.pending.take().or_else(|| self.iter.next().map(|cu| cu.0)) {
+            Some(cu0) => cu0,
+            None => return None,
+        };
+
+        let start = self.at;
+        self.at += 1;
+
+        // The number of continuation units, and the valid range of the *first* continuation unit.
+        let (len, lo, hi) = match cu0 {
+            // ASCII costs one comparison.  Reading ahead to test a word at a time was measured to be slower, as every unit still has to come through `iter` one by one.
+            0x00 ... 0x7f => return Some(Ok(cu0 as char)),
+            0xc2 ... 0xdf => (1, 0x80, 0xbf),
+            0xe0 => (2, 0xa0, 0xbf),
+            0xed => (2, 0x80, 0x9f),
+            0xe1 ... 0xef => (2, 0x80, 0xbf),
+            0xf0 => (3, 0x90, 0xbf),
+            0xf1 ... 0xf3 => (3, 0x80, 0xbf),
+            0xf4 => (3, 0x80, 0x8f),
+            _ => return Some(Err(Utf8ToUniError::InvalidAt(start))),
+        };
+
+        let mut cp = (cu0 as u32) & (0x7f >> (len + 1));
+        let (mut lo, mut hi) = (lo, hi);
+        for _ in 0..len {
+            let cu = match self.iter.next() {
+                Some(cu) => cu.0,
+                None => return Some(Err(Utf8ToUniError::Incomplete)),
+            };
+            if cu < lo || hi < cu {
+                // This unit might start the next sequence.
+                self.pending = Some(cu);
+                return Some(Err(Utf8ToUniError::InvalidAt(start)));
+            }
+            cp = (cp << 6) | (cu as u32 & 0x3f);
+            self.at += 1;
+            lo = 0x80;
+            hi = 0xbf;
+        }
+
+        Some(Ok(char::from_u32(cp).expect("decoded invalid code point from UTF-8")))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = self.pending.is_some() as usize;
+        let (lo, hi) = self.iter.size_hint();
+        ((lo + pending + 3) / 4, hi.and_then(|hi| hi.checked_add(pending)))
+    }
+}
+
+impl<It> Recoverable for Utf8ToUniIter<It> {}
+
+pub struct UniToUtf8Iter<It> where It: Iterator<Item=char> {
+    iter: Utf8EncodeIter<It>,
+}
+
+impl<It> UniToUtf8Iter<It> where It: Iterator<Item=char> {
+    pub fn new(iter: It) -> Self {
+        UniToUtf8Iter {
+            iter: iter.encode_utf8(),
+        }
+    }
+}
+
+impl<It> Iterator for UniToUtf8Iter<It> where It: Iterator<Item=char> {
+    type Item = Result<Utf8Unit, NoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|cu| Ok(Utf8Unit(cu)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/**
+Transcodes UTF-8 directly to UTF-16.
+
+ASCII units are passed straight through; everything else is decoded exactly as by `Utf8ToUniIter`, including recovery after errors, which are reported at their offset in the UTF-8 input.
+*/
+pub struct Utf8ToUtf16Iter<It> {
+    iter: Utf8ToUniIter<It>,
+    lo: Option<u16>,
+}
+
+impl<It> Utf8ToUtf16Iter<It> {
+    pub fn new(iter: It) -> Self {
+        Utf8ToUtf16Iter {
+            iter: Utf8ToUniIter::new(iter),
+            lo: None,
+        }
+    }
+}
+
+impl<It> Iterator for Utf8ToUtf16Iter<It> where It: Iterator<Item=Utf8Unit> {
+    type Item = Result<Utf16Unit, Utf8Utf16Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(lo) = self.lo.take() {
+            return Some(Ok(Utf16Unit(lo)));
+        }
+
+        // Fast path for ASCII, which needs no decoding at all.
+        if self.iter.pending.is_none() {
+            match self.iter.iter.next() {
+                Some(Utf8Unit(cu)) if cu < 0x80 => {
+                    self.iter.at += 1;
+                    return Some(Ok(Utf16Unit(cu as u16)));
+                },
+                Some(cu) => self.iter.pending = Some(cu.0),
+                None => return None,
+            }
+        }
+
+        self.iter.next().map(|r| match r {
+            Ok(c) => {
+                let cp = c as u32;
+                if cp < 0x10000 {
+                    Ok(Utf16Unit(cp as u16))
+                } else {
+                    let cp = cp - 0x10000;
+                    self.lo = Some(0xdc00 | (cp & 0x3ff) as u16);
+                    Ok(Utf16Unit(0xd800 | (cp >> 10) as u16))
+                }
+            },
+            Err(Utf8ToUniError::InvalidAt(at)) => Err(Utf8Utf16Error::InvalidAt(at)),
+            Err(Utf8ToUniError::Incomplete) => Err(Utf8Utf16Error::Incomplete),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every UTF-8 unit produces at most one UTF-16 unit, and every four at least one.
+        let buffered = self.lo.is_some() as usize;
+        let pending = self.iter.pending.is_some() as usize;
+        let (lo, hi) = self.iter.iter.size_hint();
+        ((lo + pending + 3) / 4 + buffered, hi.and_then(|hi| hi.checked_add(pending + buffered)))
+    }
+}
+
+impl<It> Recoverable for Utf8ToUtf16Iter<It> {}
+
+/**
+Transcodes UTF-16 directly to UTF-8.
+
+ASCII units are passed straight through; everything else is decoded by `finish_utf16_code_point`.  Unlike `Utf16ToUniIter`, this stops at the first unpaired surrogate, which is reported at its offset in the UTF-16 input.
+*/
+pub struct Utf16ToUtf8Iter<It> {
+    iter: Option<It>,
+    at: usize,
+    buf: [u8; 4],
+    off: u8,
+    len: u8,
+}
+
+impl<It> Utf16ToUtf8Iter<It> {
+    pub fn new(iter: It) -> Self {
+        Utf16ToUtf8Iter {
+            iter: Some(iter),
+            at: 0,
+            buf: [0; 4],
+            off: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<It> Iterator for Utf16ToUtf8Iter<It> where It: Iterator<Item=Utf16Unit> {
+    type Item = Result<Utf8Unit, Utf8Utf16Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.off < self.len {
+            let cu = self.buf[self.off as usize];
+            self.off += 1;
+            return Some(Ok(Utf8Unit(cu)));
+        }
+
+        // Fast path for ASCII, which needs no decoding at all.
+        let cu0 = match self.iter.as_mut().and_then(|iter| iter.next()) {
+            Some(Utf16Unit(cu)) if cu < 0x80 => {
+                self.at += 1;
+                return Some(Ok(Utf8Unit(cu as u8)));
+            },
+            Some(cu) => cu,
+            None => return None,
+        };
+
+        let r = finish_utf16_code_point(cu0.0 as u32, &mut self.iter, &mut self.at);
+
+        Some(match r {
+            Ok(c) => {
+                self.off = 1;
+                self.len = c.encode_utf8(&mut self.buf[..]).len() as u8;
+                Ok(Utf8Unit(self.buf[0]))
+            },
+            Err(Utf16ToUniError::InvalidAt(at)) => Err(Utf8Utf16Error::InvalidAt(at)),
+            Err(Utf16ToUniError::Incomplete) => Err(Utf8Utf16Error::Incomplete),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every UTF-16 unit produces at least one UTF-8 unit, and at most three.  Decoding stops at the first error, so only one more item is certain.
+        let buffered = (self.len - self.off) as usize;
+        match self.iter {
+            Some(ref iter) => {
+                let (lo, hi) = iter.size_hint();
+                (::std::cmp::min(lo, 1).saturating_add(buffered), hi.and_then(|hi| hi.checked_mul(3)).and_then(|hi| hi.checked_add(buffered)))
+            },
+            None => (buffered, Some(buffered)),
+        }
+    }
+}
+
+pub struct CheckedUtf8ToUtf8Iter<It> {
+    iter: It,
+}
+
+impl<It> CheckedUtf8ToUtf8Iter<It> {
+    pub fn new(iter: It) -> Self {
+        CheckedUtf8ToUtf8Iter {
+            iter: iter,
+        }
+    }
+}
+
+impl<It> Iterator for CheckedUtf8ToUtf8Iter<It> where It: Iterator<Item=CheckedUtf8Unit> {
+    type Item = Result<Utf8Unit, NoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|cu| Ok(Utf8Unit(cu.0)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<It> ExactSizeIterator for CheckedUtf8ToUtf8Iter<It> where It: ExactSizeIterator<Item=CheckedUtf8Unit> {}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Utf8ValidationError {
+    InvalidAt(usize),
+    Incomplete,
+}
+
+impl fmt::Display for Utf8ValidationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Utf8ValidationError::InvalidAt(at) => write!(fmt, "invalid unit at offset {}", at),
+            Utf8ValidationError::Incomplete => write!(fmt, "incomplete unit"),
+        }
+    }
+}
+
+impl ::std::error::Error for Utf8ValidationError {
+    fn description(&self) -> &str {
+        match *self {
+            Utf8ValidationError::InvalidAt(_) => "invalid unit",
+            Utf8ValidationError::Incomplete => "incomplete unit",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Utf8ToUniError {
+    InvalidAt(usize),
+    Incomplete,
+}
+
+impl fmt::Display for Utf8ToUniError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Utf8ToUniError::InvalidAt(at) => write!(fmt, "invalid unit at offset {}", at),
+            Utf8ToUniError::Incomplete => write!(fmt, "incomplete unit"),
+        }
+    }
+}
+
+impl ::std::error::Error for Utf8ToUniError {
+    fn description(&self) -> &str {
+        match *self {
+            Utf8ToUniError::InvalidAt(_) => "invalid unit",
+            Utf8ToUniError::Incomplete => "incomplete unit",
+        }
+    }
+}
+
+impl ErrorOffset for Utf8ToUniError {
+    fn offset(&self) -> Option<usize> {
+        match *self {
+            Utf8ToUniError::InvalidAt(at) => Some(at),
+            Utf8ToUniError::Incomplete => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Utf8Utf16Error {
+    InvalidAt(usize),
+    Incomplete,
+}
+
+impl fmt::Display for Utf8Utf16Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Utf8Utf16Error::InvalidAt(at) => write!(fmt, "invalid unit at offset {}", at),
+            Utf8Utf16Error::Incomplete => write!(fmt, "incomplete unit"),
+        }
+    }
+}
+
+impl ::std::error::Error for Utf8Utf16Error {
+    fn description(&self) -> &str {
+        match *self {
+            Utf8Utf16Error::InvalidAt(_) => "invalid unit",
+            Utf8Utf16Error::Incomplete => "incomplete unit",
+        }
+    }
+}
+
+impl ErrorOffset for Utf8Utf16Error {
+    fn offset(&self) -> Option<usize> {
+        match *self {
+            Utf8Utf16Error::InvalidAt(at) => Some(at),
+            Utf8Utf16Error::Incomplete => None,
+        }
+    }
+}
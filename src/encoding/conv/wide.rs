@@ -0,0 +1,313 @@
+/*!
+Transcoders for the fixed-width wide encodings.
+
+`WcToUniIter` and `UniToWcIter` are parameterised by the wide encoding marker, so that each of `Wide16` and `Wide32` gets its own implementation, and `Wide` picks up whichever one it aliases.
+
+Because `Wide` is an alias rather than a distinct encoding, there is never more than one transcoder to choose from.  Units of one width cannot be passed off as the other:
+
+```compile_fail
+extern crate strffi;
+use strffi::encoding::{TranscodeTo, UnitIter, CheckedUnicode, Wide16, W32Unit};
+
+fn main() {
+    let units = vec![W32Unit(0x61)];
+    let _ = TranscodeTo::<CheckedUnicode>::transcode(UnitIter::<Wide16, _>::new(units.into_iter()));
+}
+```
+*/
+use std::char;
+use std::marker::PhantomData;
+use encoding::{TranscodeSource, TranscodeTo, Transparent, TransparentAs, Recoverable, Unit, UnitIter, CheckedUnicode, Wide16, W16Unit, Wide32, W32Unit, WinUnicode, WwUnit};
+use encoding::conv::NoError;
+use encoding::conv::utf16::{next_utf16_code_point_resuming, utf16_decode_size_hint, utf16_encode_size_hint, utf16_to_string, Utf16SwapIter, Utf16ToUniError};
+use encoding::conv::utf32::utf32_to_string;
+pub use super::WcToUniError;
+
+impl<It> TranscodeTo<CheckedUnicode> for UnitIter<Wide16, It> where It: Iterator<Item=W16Unit> {
+    type Iter = WcToUniIter<Wide16, It>;
+    type Error = WcToUniError;
+
+    fn transcode(self) -> Self::Iter {
+        WcToUniIter::new(self.into_iter())
+    }
+
+    fn decode_to_string(units: &[<Self as TranscodeSource>::Unit]) -> Option<String> {
+        utf16_to_string(units)
+    }
+}
+
+impl<It> TranscodeTo<Wide16> for UnitIter<CheckedUnicode, It> where It: Iterator<Item=char> {
+    type Iter = UniToWcIter<Wide16, It>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        UniToWcIter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<CheckedUnicode> for UnitIter<Wide32, It> where It: Iterator<Item=W32Unit> {
+    type Iter = WcToUniIter<Wide32, It>;
+    type Error = WcToUniError;
+
+    fn transcode(self) -> Self::Iter {
+        WcToUniIter::new(self.into_iter())
+    }
+
+    fn decode_to_string(units: &[<Self as TranscodeSource>::Unit]) -> Option<String> {
+        utf32_to_string(units)
+    }
+}
+
+impl<It> TranscodeTo<Wide32> for UnitIter<CheckedUnicode, It> where It: Iterator<Item=char> {
+    type Iter = UniToWcIter<Wide32, It>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        UniToWcIter::new(self.into_iter())
+    }
+}
+
+/*
+`Wide16` and `WinUnicode` are the same encoding, so these are just unit-for-unit copies.
+*/
+unsafe impl TransparentAs<WinUnicode> for Wide16 {}
+unsafe impl TransparentAs<Wide16> for WinUnicode {}
+
+impl<It> TranscodeTo<WinUnicode> for UnitIter<Wide16, It> where It: Iterator<Item=W16Unit> {
+    type Iter = Utf16SwapIter<It, WwUnit>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        Utf16SwapIter::new(self.into_iter())
+    }
+
+    fn transparent() -> Option<Transparent<Self, WinUnicode>> {
+        Some(Transparent::new())
+    }
+}
+
+impl<It> TranscodeTo<Wide16> for UnitIter<WinUnicode, It> where It: Iterator<Item=WwUnit> {
+    type Iter = Utf16SwapIter<It, W16Unit>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        Utf16SwapIter::new(self.into_iter())
+    }
+
+    fn transparent() -> Option<Transparent<Self, Wide16>> {
+        Some(Transparent::new())
+    }
+}
+
+impl<It> TranscodeTo<WinUnicode> for UnitIter<Wide32, It> where It: Iterator<Item=W32Unit> {
+    type Iter = WcToWwIter<It>;
+    type Error = WcToUniError;
+
+    fn transcode(self) -> Self::Iter {
+        WcToWwIter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<Wide32> for UnitIter<WinUnicode, It> where It: Iterator<Item=WwUnit> {
+    type Iter = WwToWcIter<It>;
+    type Error = WcToUniError;
+
+    fn transcode(self) -> Self::Iter {
+        WwToWcIter::new(self.into_iter())
+    }
+}
+
+/**
+Decodes a wide string which may contain invalid units.
+
+Each invalid unit, or unpaired surrogate for `Wide16`, produces a single error, after which decoding resumes with the next unit.
+*/
+pub struct WcToUniIter<E, It> {
+    at: usize,
+    iter: It,
+    pending: Option<u32>,
+    _marker: PhantomData<E>,
+}
+
+impl<E, It> WcToUniIter<E, It> {
+    pub fn new(iter: It) -> Self {
+        WcToUniIter {
+            at: 0,
+            iter: iter,
+            pending: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+pub struct UniToWcIter<E, It> {
+    buf: Option<u16>,
+    iter: Option<It>,
+    _marker: PhantomData<E>,
+}
+
+impl<E, It> UniToWcIter<E, It> {
+    pub fn new(iter: It) -> Self {
+        UniToWcIter {
+            buf: None,
+            iter: Some(iter),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<It> Iterator for WcToUniIter<Wide16, It> where It: Iterator<Item=W16Unit> {
+    type Item = Result<char, WcToUniError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        next_utf16_code_point_resuming(&mut self.iter, &mut self.pending, &mut self.at)
+            .map(|r| r.map_err(|err| match err {
+                Utf16ToUniError::InvalidAt(at) => WcToUniError::InvalidAt(at),
+                Utf16ToUniError::Incomplete => WcToUniError::Incomplete,
+            }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        utf16_decode_size_hint(&self.iter, self.pending.is_some())
+    }
+}
+
+impl<It> Iterator for WcToUniIter<Wide32, It> where It: Iterator<Item=W32Unit> {
+    type Item = Result<char, WcToUniError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Every unit is decoded independently, so there is never anything pending.
+        self.iter.next().map(|cu| {
+            let at = self.at;
+            self.at += 1;
+            // `from_u32` rejects both surrogates and anything above U+10FFFF.
+            char::from_u32(cu.to_raw()).ok_or(WcToUniError::InvalidAt(at))
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<E, It> Recoverable for WcToUniIter<E, It> {}
+
+impl<It> Iterator for UniToWcIter<Wide16, It> where It: Iterator<Item=char> {
+    type Item = Result<W16Unit, NoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(cu) = self.buf.take() {
+            return Some(Ok(W16Unit(cu)));
+        }
+
+        let ch = match self.iter.as_mut().and_then(|iter| iter.next()) {
+            Some(ch) => ch,
+            None => {
+                self.iter = None;
+                return None
+            },
+        };
+
+        let mut utf16 = [0; 2];
+        let utf16 = ch.encode_utf16(&mut utf16[..]);
+        self.buf = utf16.get(1).cloned();
+        Some(Ok(W16Unit(utf16[0])))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        utf16_encode_size_hint(&self.iter, self.buf.is_some() as usize)
+    }
+}
+
+impl<It> Iterator for UniToWcIter<Wide32, It> where It: Iterator<Item=char> {
+    type Item = Result<W32Unit, NoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.iter.as_mut().and_then(|iter| iter.next()) {
+            None => None,
+            Some(cp) => {
+                // Now, pay attention, because this is *very* complicated.
+                Some(Ok(W32Unit(cp as u32 as _)))
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.iter {
+            Some(ref iter) => iter.size_hint(),
+            None => (0, Some(0)),
+        }
+    }
+}
+
+impl<It> ExactSizeIterator for UniToWcIter<Wide32, It> where It: ExactSizeIterator<Item=char> {}
+
+/*
+`Wide32` is UTF-32, so these have to actually re-encode to and from UTF-16.
+*/
+pub struct WcToWwIter<It> {
+    buf: Option<WwUnit>,
+    iter: WcToUniIter<Wide32, It>,
+}
+
+impl<It> WcToWwIter<It> {
+    pub fn new(iter: It) -> Self {
+        WcToWwIter {
+            buf: None,
+            iter: WcToUniIter::new(iter),
+        }
+    }
+}
+
+impl<It> Iterator for WcToWwIter<It> where It: Iterator<Item=W32Unit> {
+    type Item = Result<WwUnit, WcToUniError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(wwunit) = self.buf.take() {
+            return Some(Ok(wwunit));
+        }
+
+        let ch = match self.iter.next() {
+            Some(Ok(ch)) => ch,
+            Some(Err(err)) => return Some(Err(err)),
+            None => return None,
+        };
+
+        let mut utf16 = [0; 2];
+        let utf16 = ch.encode_utf16(&mut utf16[..]);
+        self.buf = utf16.get(1).map(|&u| WwUnit(u));
+        Some(Ok(WwUnit(utf16[0])))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every code point produces either one or two units.
+        let buffered = self.buf.is_some() as usize;
+        let (lo, hi) = self.iter.size_hint();
+        (lo.saturating_add(buffered), hi.and_then(|hi| hi.checked_mul(2)).and_then(|hi| hi.checked_add(buffered)))
+    }
+}
+
+pub struct WwToWcIter<It> {
+    iter: WcToUniIter<Wide16, ::std::iter::Map<It, fn(WwUnit) -> W16Unit>>,
+}
+
+impl<It> WwToWcIter<It> where It: Iterator<Item=WwUnit> {
+    pub fn new(iter: It) -> Self {
+        fn ww_to_w16(u: WwUnit) -> W16Unit { W16Unit(u.0) }
+        WwToWcIter {
+            iter: WcToUniIter::new(iter.map(ww_to_w16 as fn(_) -> _)),
+        }
+    }
+}
+
+impl<It> Iterator for WwToWcIter<It> where It: Iterator<Item=WwUnit> {
+    type Item = Result<W32Unit, WcToUniError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|r| r.map(|c| W32Unit(c as u32 as _)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
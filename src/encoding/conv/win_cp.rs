@@ -0,0 +1,613 @@
+/*!
+Conversions between the Windows code page encodings and `Wide`.
+
+These use `MultiByteToWideChar` and `WideCharToMultiByte`, one character at a time.  Neither function keeps any state between calls, so the iterators here work out how many units make up each character themselves: for double-byte code pages, this is determined by `IsDBCSLeadByteEx`; for UTF-8, by the lead byte.
+*/
+use std::fmt;
+use std::iter;
+use std::marker::PhantomData;
+use std::ptr;
+use libc::{c_char, c_int};
+use encoding::{Encoding, TranscodeTo, Unit, UnitIter, ByteEncoding, CheckedUnicode, Raw8, Raw8Unit, Wide16, W16Unit, WinAnsi, WinOem};
+use encoding::conv::{ErrorOffset, NoError};
+use encoding::conv::wide::{WcToUniIter, WcToUniError, UniToWcIter};
+use ffi::win::{CP_UTF8, MB_ERR_INVALID_CHARS, WC_ERR_INVALID_CHARS, WC_NO_BEST_FIT_CHARS,
+    GetACP, GetOEMCP, IsDBCSLeadByteEx, MultiByteToWideChar, WideCharToMultiByte};
+use util::{LiftErrIter, LiftTrapErrIter, LiftErrExt, MapOffsetIter, OffsetLog, SourceOffset, TrackOffsetIter};
+
+/**
+The largest number of units a code page can need to represent a single character.
+
+This is four, to allow for `CP_UTF8`.
+*/
+const CP_MAX_UNITS: usize = 4;
+
+/**
+Implemented by encodings which correspond to a Windows code page.
+
+Any encoding implementing this can be transcoded to and from `Wide` and `CheckedUnicode`.  Encodings for a specific, fixed code page can be declared with `declare_code_page!`.
+
+Single-byte and double-byte code pages are supported, as is UTF-8.  Stateful code pages, such as the ISO 2022 family and UTF-7, and GB 18030, whose four-byte sequences cannot be detected from the lead byte, are *not* supported.
+*/
+pub trait CodePage: ByteEncoding {
+    /**
+    Returns the identifier of the code page.
+
+    This must be a concrete identifier, *not* a pseudo identifier such as `CP_OEMCP`, so that it can be compared against `CP_UTF8`.
+    */
+    fn code_page() -> u32;
+}
+
+impl CodePage for WinAnsi {
+    #[inline]
+    fn code_page() -> u32 {
+        unsafe { GetACP() }
+    }
+}
+
+impl CodePage for WinOem {
+    #[inline]
+    fn code_page() -> u32 {
+        unsafe { GetOEMCP() }
+    }
+}
+
+impl<E, It> TranscodeTo<Wide16> for UnitIter<E, It> where E: CodePage, It: Iterator<Item=E::Unit> {
+    type Iter = CpToWcIter<E, It>;
+    type Error = CpToWcError;
+
+    fn transcode(self) -> Self::Iter {
+        CpToWcIter::new(self.into_iter())
+    }
+}
+
+impl<E, It> TranscodeTo<E> for UnitIter<Wide16, It> where E: CodePage, It: Iterator<Item=W16Unit> {
+    type Iter = WcToCpIter<E, It>;
+    type Error = WcToCpError;
+
+    fn transcode(self) -> Self::Iter {
+        WcToCpIter::new(self.into_iter())
+    }
+}
+
+impl<E, It> TranscodeTo<CheckedUnicode> for UnitIter<E, It> where E: CodePage, It: Iterator<Item=E::Unit> {
+    type Iter = LiftErrIter<
+        iter::Map<
+            MapOffsetIter<
+                WcToUniIter<
+                    Wide16,
+                    LiftTrapErrIter<
+                        TrackOffsetIter<CpToWcIter<E, It>>,
+                        CpToWcError,
+                    >
+                >
+            >,
+            fn(Result<char, WcToUniError>) -> Result<char, CpToUniError>,
+        >,
+        CpToWcError,
+    >;
+    type Error = CpToUniError;
+
+    fn transcode(self) -> Self::Iter {
+        // `WcToUniIter` counts wide units; the log lets its errors report offsets into the source instead.
+        let log = OffsetLog::new();
+        TrackOffsetIter::new(CpToWcIter::new(self.into_iter()), log.clone())
+            .lift_err(|over| MapOffsetIter::new(WcToUniIter::new(over), log)
+                .map(map_err as fn(_) -> _))
+    }
+}
+
+impl<E, It> TranscodeTo<E> for UnitIter<CheckedUnicode, It> where E: CodePage, It: Iterator<Item=char> {
+    type Iter = LiftErrIter<
+        iter::Map<
+            WcToCpIter<
+                E,
+                LiftTrapErrIter<
+                    UniToWcIter<Wide16, It>,
+                    NoError,
+                >
+            >,
+            fn(Result<E::Unit, WcToCpError>) -> Result<E::Unit, WcToCpError>,
+        >,
+        NoError,
+    >;
+    type Error = WcToCpError;
+
+    fn transcode(self) -> Self::Iter {
+        UniToWcIter::new(self.into_iter())
+            .lift_err(|over| WcToCpIter::new(over)
+                .map(::util::id as fn(_) -> _))
+    }
+}
+
+/**
+Converts a whole string from the given code page to UTF-16, with a single call to `MultiByteToWideChar`.
+
+This produces the same result as transcoding a `CodePage` string to `Wide16`, but is much faster for long strings than converting one character at a time, as the transcoding iterators must.  The output is sized exactly, by asking `MultiByteToWideChar` for its length before converting.
+
+# Failure
+
+Invalid sequences are rejected using `MB_ERR_INVALID_CHARS`, rather than replaced.  As that does not say *where* the problem is, the string is then decoded one character at a time to find it, so errors are reported exactly as the transcoding iterators would report them.
+*/
+pub fn cp_to_wide_bulk(cp: u32, src: &[u8]) -> Result<Vec<W16Unit>, CpToWcError> {
+    let per_char = || CpToWcIter::<Raw8, _>::with_code_page(src.iter().map(|&b| Raw8Unit(b)), cp).collect();
+    if src.is_empty() {
+        return Ok(vec![]);
+    }
+    if src.len() > c_int::max_value() as usize {
+        return per_char();
+    }
+
+    unsafe {
+        let src_ptr = src.as_ptr() as *const c_char;
+        let src_len = src.len() as c_int;
+        let len = MultiByteToWideChar(cp, MB_ERR_INVALID_CHARS, src_ptr, src_len, ptr::null_mut(), 0);
+        if len <= 0 {
+            return per_char();
+        }
+
+        let mut out: Vec<W16Unit> = Vec::with_capacity(len as usize);
+        // `W16Unit` is a `repr(C)` wrapper around `u16`.
+        let written = MultiByteToWideChar(cp, MB_ERR_INVALID_CHARS, src_ptr, src_len,
+            out.as_mut_ptr() as *mut u16, len);
+        if written <= 0 {
+            return per_char();
+        }
+        out.set_len(written as usize);
+        Ok(out)
+    }
+}
+
+/**
+Converts a whole UTF-16 string to the given code page, with a single call to `WideCharToMultiByte`.
+
+This is the reverse of `cp_to_wide_bulk`, and produces the same result as transcoding a `Wide16` string to a `CodePage` encoding.
+
+# Failure
+
+Characters which cannot be represented in the code page are rejected, rather than replaced with a default or best-fit character.  As with `cp_to_wide_bulk`, the string is then encoded one character at a time to find where the problem is.
+*/
+pub fn wide_to_cp_bulk(cp: u32, src: &[W16Unit]) -> Result<Vec<u8>, WcToCpError> {
+    let per_char = || WcToCpIter::<Raw8, _>::with_code_page(src.iter().cloned(), cp)
+        .map(|r| r.map(|u| u.0))
+        .collect();
+    if src.is_empty() {
+        return Ok(vec![]);
+    }
+    if src.len() > c_int::max_value() as usize {
+        return per_char();
+    }
+
+    unsafe {
+        let src_ptr = src.as_ptr() as *const u16;
+        let src_len = src.len() as c_int;
+        // See `WcToCpIter` for why the flags differ for `CP_UTF8`.
+        let flags = if cp == CP_UTF8 { WC_ERR_INVALID_CHARS } else { WC_NO_BEST_FIT_CHARS };
+        let mut used_default = 0;
+        let used_default_ptr = if cp == CP_UTF8 { ptr::null_mut() } else { &mut used_default as *mut i32 };
+
+        let len = WideCharToMultiByte(cp, flags, src_ptr, src_len,
+            ptr::null_mut(), 0, ptr::null(), used_default_ptr);
+        if len <= 0 || used_default != 0 {
+            return per_char();
+        }
+
+        let mut out: Vec<u8> = Vec::with_capacity(len as usize);
+        let written = WideCharToMultiByte(cp, flags, src_ptr, src_len,
+            out.as_mut_ptr() as *mut c_char, len, ptr::null(), used_default_ptr);
+        if written <= 0 || used_default != 0 {
+            return per_char();
+        }
+        out.set_len(written as usize);
+        Ok(out)
+    }
+}
+
+/**
+Returns the length of the UTF-8 sequence introduced by `lead`, or `None` if it cannot begin a sequence.
+*/
+fn utf8_seq_len(lead: u8) -> Option<usize> {
+    match lead {
+        0x00...0x7f => Some(1),
+        0xc2...0xdf => Some(2),
+        0xe0...0xef => Some(3),
+        0xf0...0xf4 => Some(4),
+        _ => None,
+    }
+}
+
+/**
+Returns the number of units in the character introduced by `lead` in the given code page, or `None` if it cannot begin a character.
+*/
+fn cp_seq_len(cp: u32, lead: u8) -> Option<usize> {
+    if cp == CP_UTF8 {
+        utf8_seq_len(lead)
+    } else if unsafe { IsDBCSLeadByteEx(cp, lead) } != 0 {
+        Some(2)
+    } else {
+        Some(1)
+    }
+}
+
+pub struct CpToWcIter<E, It> {
+    iter: Option<It>,
+    cp: u32,
+    at: usize,
+    // Where the character in `buf` started.
+    buf_from: usize,
+    buf: [W16Unit; 2],
+    buf_at: u8,
+    buf_len: u8,
+    _marker: PhantomData<E>,
+}
+
+impl<E, It> CpToWcIter<E, It> where E: CodePage {
+    pub fn new(iter: It) -> Self {
+        CpToWcIter::with_code_page(iter, E::code_page())
+    }
+}
+
+impl<E, It> CpToWcIter<E, It> {
+    /**
+    Creates an iterator which decodes using the given code page, rather than the one belonging to `E`.
+    */
+    pub fn with_code_page(iter: It, cp: u32) -> Self {
+        CpToWcIter {
+            iter: Some(iter),
+            cp: cp,
+            at: 0,
+            buf_from: 0,
+            buf: [W16Unit(0); 2],
+            buf_at: 0,
+            buf_len: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<E, It> SourceOffset for CpToWcIter<E, It> {
+    fn source_offset(&self) -> usize {
+        if self.buf_at < self.buf_len {
+            self.buf_from
+        } else {
+            self.at
+        }
+    }
+}
+
+impl<E, It> Iterator for CpToWcIter<E, It> where E: ByteEncoding, It: Iterator<Item=E::Unit> {
+    type Item = Result<W16Unit, CpToWcError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buf_at < self.buf_len {
+            let wcu = self.buf[self.buf_at as usize];
+            self.buf_at += 1;
+            return Some(Ok(wcu));
+        }
+
+        let err;
+
+        {
+            let iter = match self.iter.as_mut() {
+                Some(iter) => iter,
+                None => return None,
+            };
+
+            let mut mb = [0u8; CP_MAX_UNITS];
+            mb[0] = match iter.next() {
+                Some(u) => u.to_raw() as u8,
+                None => return None,
+            };
+
+            loop {
+                let len = match cp_seq_len(self.cp, mb[0]) {
+                    Some(len) => len,
+                    None => {
+                        err = CpToWcError::InvalidAt(self.at);
+                        break;
+                    },
+                };
+
+                let mut incomplete = false;
+                for i in 1..len {
+                    mb[i] = match iter.next() {
+                        Some(u) => u.to_raw() as u8,
+                        None => {
+                            incomplete = true;
+                            break;
+                        },
+                    };
+                }
+                if incomplete {
+                    err = CpToWcError::Incomplete;
+                    break;
+                }
+
+                let mut wc = [0u16; 2];
+                let wc_len = unsafe {
+                    MultiByteToWideChar(self.cp, MB_ERR_INVALID_CHARS,
+                        mb.as_ptr() as *const c_char, len as c_int,
+                        wc.as_mut_ptr(), wc.len() as c_int)
+                };
+                if wc_len <= 0 {
+                    err = CpToWcError::InvalidAt(self.at);
+                    break;
+                }
+
+                self.buf_from = self.at;
+                self.at += len;
+                self.buf = [W16Unit(wc[0]), W16Unit(wc[1])];
+                self.buf_at = 1;
+                self.buf_len = wc_len as u8;
+                return Some(Ok(self.buf[0]));
+            }
+        }
+
+        self.iter = None;
+        Some(Err(err))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every character takes at least one unit for every wide unit it produces.  Decoding stops at the first error, so only one more item is certain.
+        let buffered = (self.buf_len - self.buf_at) as usize;
+        match self.iter {
+            Some(ref iter) => {
+                let (lo, hi) = iter.size_hint();
+                (::std::cmp::min((lo + CP_MAX_UNITS - 1) / CP_MAX_UNITS, 1).saturating_add(buffered), hi.and_then(|hi| hi.checked_add(buffered)))
+            },
+            None => (buffered, Some(buffered)),
+        }
+    }
+}
+
+pub struct WcToCpIter<E, It> where E: Encoding {
+    iter: Option<It>,
+    cp: u32,
+    at: usize,
+    buf: [E::Unit; CP_MAX_UNITS],
+    buf_at: u8,
+    buf_len: u8,
+}
+
+impl<E, It> WcToCpIter<E, It> where E: CodePage {
+    pub fn new(iter: It) -> Self {
+        WcToCpIter::with_code_page(iter, E::code_page())
+    }
+}
+
+impl<E, It> WcToCpIter<E, It> where E: Encoding {
+    /**
+    Creates an iterator which encodes using the given code page, rather than the one belonging to `E`.
+    */
+    pub fn with_code_page(iter: It, cp: u32) -> Self {
+        WcToCpIter {
+            iter: Some(iter),
+            cp: cp,
+            at: 0,
+            buf: [E::Unit::zero(); CP_MAX_UNITS],
+            buf_at: 0,
+            buf_len: 0,
+        }
+    }
+}
+
+impl<E, It> Iterator for WcToCpIter<E, It> where E: ByteEncoding, It: Iterator<Item=W16Unit> {
+    type Item = Result<E::Unit, WcToCpError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buf_at < self.buf_len {
+            let u = self.buf[self.buf_at as usize];
+            self.buf_at += 1;
+            return Some(Ok(u));
+        }
+
+        let err;
+
+        {
+            let iter = match self.iter.as_mut() {
+                Some(iter) => iter,
+                None => return None,
+            };
+
+            let mut wc = [0u16; 2];
+            wc[0] = match iter.next() {
+                Some(wcu) => wcu.0,
+                None => return None,
+            };
+
+            loop {
+                // Surrogates have to be converted as a pair, or not at all.
+                let wc_len = match wc[0] {
+                    0xd800...0xdbff => match iter.next() {
+                        Some(W16Unit(lo @ 0xdc00...0xdfff)) => {
+                            wc[1] = lo;
+                            2
+                        },
+                        _ => {
+                            err = WcToCpError::InvalidAt(self.at);
+                            break;
+                        },
+                    },
+                    0xdc00...0xdfff => {
+                        err = WcToCpError::InvalidAt(self.at);
+                        break;
+                    },
+                    _ => 1,
+                };
+
+                let mut mb = [0u8; CP_MAX_UNITS];
+                let mut used_default = 0;
+                let mb_len = unsafe {
+                    // `CP_UTF8` doesn't support best-fit mapping or default characters, but it *does* support rejecting invalid input.
+                    let (flags, used_default_ptr) = if self.cp == CP_UTF8 {
+                        (WC_ERR_INVALID_CHARS, ptr::null_mut())
+                    } else {
+                        (WC_NO_BEST_FIT_CHARS, &mut used_default as *mut i32)
+                    };
+                    WideCharToMultiByte(self.cp, flags,
+                        wc.as_ptr(), wc_len,
+                        mb.as_mut_ptr() as *mut c_char, mb.len() as c_int,
+                        ptr::null(), used_default_ptr)
+                };
+                if mb_len <= 0 || used_default != 0 {
+                    err = WcToCpError::InvalidAt(self.at);
+                    break;
+                }
+
+                let mb_len = mb_len as usize;
+                for (dst, &src) in self.buf.iter_mut().zip(&mb[..mb_len]) {
+                    *dst = E::Unit::from_raw(src as u32)
+                        .expect("byte encoding rejected a byte");
+                }
+
+                self.at += wc_len as usize;
+                self.buf_at = 1;
+                self.buf_len = mb_len as u8;
+                return Some(Ok(self.buf[0]));
+            }
+        }
+
+        self.iter = None;
+        Some(Err(err))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every character takes one or two wide units, and produces at least one unit.  Encoding stops at the first error, so only one more item is certain.
+        let buffered = (self.buf_len - self.buf_at) as usize;
+        match self.iter {
+            Some(ref iter) => {
+                let (lo, hi) = iter.size_hint();
+                (::std::cmp::min((lo + 1) / 2, 1).saturating_add(buffered), hi.and_then(|hi| hi.checked_mul(CP_MAX_UNITS)).and_then(|hi| hi.checked_add(buffered)))
+            },
+            None => (buffered, Some(buffered)),
+        }
+    }
+}
+
+/**
+An error decoding a code page string.
+
+`InvalidAt` gives the offset of the first unit of a sequence which `MultiByteToWideChar` rejected with `ERROR_NO_UNICODE_TRANSLATION`, or which could not begin a sequence at all.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CpToWcError {
+    InvalidAt(usize),
+    Incomplete,
+}
+
+impl fmt::Display for CpToWcError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CpToWcError::InvalidAt(at) => write!(fmt, "invalid unit at offset {}", at),
+            CpToWcError::Incomplete => write!(fmt, "incomplete unit"),
+        }
+    }
+}
+
+impl ::std::error::Error for CpToWcError {
+    fn description(&self) -> &str {
+        match *self {
+            CpToWcError::InvalidAt(_) => "invalid unit",
+            CpToWcError::Incomplete => "incomplete unit",
+        }
+    }
+}
+
+impl ErrorOffset for CpToWcError {
+    fn offset(&self) -> Option<usize> {
+        match *self {
+            CpToWcError::InvalidAt(at) => Some(at),
+            CpToWcError::Incomplete => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WcToCpError {
+    InvalidAt(usize),
+}
+
+impl fmt::Display for WcToCpError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            WcToCpError::InvalidAt(at) => write!(fmt, "invalid unit at offset {}", at),
+        }
+    }
+}
+
+impl ::std::error::Error for WcToCpError {
+    fn description(&self) -> &str {
+        match *self {
+            WcToCpError::InvalidAt(_) => "invalid unit",
+        }
+    }
+}
+
+impl ErrorOffset for WcToCpError {
+    fn offset(&self) -> Option<usize> {
+        match *self {
+            WcToCpError::InvalidAt(at) => Some(at),
+        }
+    }
+}
+
+impl From<NoError> for WcToCpError {
+    fn from(v: NoError) -> Self {
+        match v {}
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CpToUniError {
+    InvalidAt(usize),
+    Incomplete,
+}
+
+impl From<CpToWcError> for CpToUniError {
+    fn from(v: CpToWcError) -> Self {
+        match v {
+            CpToWcError::InvalidAt(at) => CpToUniError::InvalidAt(at),
+            CpToWcError::Incomplete => CpToUniError::Incomplete,
+        }
+    }
+}
+
+impl From<WcToUniError> for CpToUniError {
+    fn from(v: WcToUniError) -> Self {
+        match v {
+            WcToUniError::InvalidAt(at) => CpToUniError::InvalidAt(at),
+            WcToUniError::Incomplete => CpToUniError::Incomplete,
+        }
+    }
+}
+
+fn map_err<T, E, F>(v: Result<T, E>) -> Result<T, F> where E: Into<F> {
+    v.map_err(Into::into)
+}
+
+impl fmt::Display for CpToUniError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CpToUniError::InvalidAt(at) => write!(fmt, "invalid unit at offset {}", at),
+            CpToUniError::Incomplete => write!(fmt, "incomplete unit"),
+        }
+    }
+}
+
+impl ::std::error::Error for CpToUniError {
+    fn description(&self) -> &str {
+        match *self {
+            CpToUniError::InvalidAt(_) => "invalid unit",
+            CpToUniError::Incomplete => "incomplete unit",
+        }
+    }
+}
+
+impl ErrorOffset for CpToUniError {
+    fn offset(&self) -> Option<usize> {
+        match *self {
+            CpToUniError::InvalidAt(at) => Some(at),
+            CpToUniError::Incomplete => None,
+        }
+    }
+}
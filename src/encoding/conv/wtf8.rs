@@ -0,0 +1,493 @@
+/*!
+Transcoders for the WTF-8 encoding.
+
+Transcoding from the UTF-16 family into `Wtf8` cannot fail: unpaired surrogates are encoded as they are, rather than rejected.  Transcoding back reproduces the original units exactly.
+*/
+use std::char;
+use std::fmt;
+use encoding::{TranscodeTo, Unit, UnitIter, CheckedUnicode, Utf16, Utf16Unit, Wide16, W16Unit, WinUnicode, WwUnit, Wtf8, Wtf8Unit};
+use encoding::conv::{ErrorOffset, NoError};
+use encoding::conv::utf16::Utf16CodeUnit;
+
+macro_rules! utf16_wtf8_impl {
+    ($($enc:ident: $unit:ident),* $(,)*) => {
+        $(
+            impl<It> TranscodeTo<Wtf8> for UnitIter<$enc, It> where It: Iterator<Item=$unit> {
+                type Iter = Utf16ToWtf8Iter<It>;
+                type Error = NoError;
+
+                fn transcode(self) -> Self::Iter {
+                    Utf16ToWtf8Iter::new(self.into_iter())
+                }
+            }
+
+            impl<It> TranscodeTo<$enc> for UnitIter<Wtf8, It> where It: Iterator<Item=Wtf8Unit> {
+                type Iter = Wtf8ToUtf16Iter<It, $unit>;
+                type Error = Wtf8ToUtf16Error;
+
+                fn transcode(self) -> Self::Iter {
+                    Wtf8ToUtf16Iter::new(self.into_iter())
+                }
+            }
+        )*
+    };
+}
+
+utf16_wtf8_impl! {
+    Utf16: Utf16Unit,
+    Wide16: W16Unit,
+    WinUnicode: WwUnit,
+}
+
+impl<It> TranscodeTo<CheckedUnicode> for UnitIter<Wtf8, It> where It: Iterator<Item=Wtf8Unit> {
+    type Iter = Wtf8ToUniIter<It>;
+    type Error = Wtf8ToUniError;
+
+    fn transcode(self) -> Self::Iter {
+        Wtf8ToUniIter::new(self.into_iter())
+    }
+}
+
+impl<It> TranscodeTo<Wtf8> for UnitIter<CheckedUnicode, It> where It: Iterator<Item=char> {
+    type Iter = UniToWtf8Iter<It>;
+    type Error = NoError;
+
+    fn transcode(self) -> Self::Iter {
+        UniToWtf8Iter::new(self.into_iter())
+    }
+}
+
+/**
+Encodes UTF-16 units as WTF-8.
+
+Surrogate pairs are encoded as the supplementary character they represent; any other surrogate is encoded on its own.
+*/
+pub struct Utf16ToWtf8Iter<It> {
+    buf: UnitBuf,
+    // A unit read while looking for the second half of a surrogate pair, which turned out not to be one.
+    pending: Option<u32>,
+    iter: It,
+}
+
+impl<It> Utf16ToWtf8Iter<It> {
+    pub fn new(iter: It) -> Self {
+        Utf16ToWtf8Iter {
+            buf: UnitBuf::new(),
+            pending: None,
+            iter: iter,
+        }
+    }
+}
+
+impl<It> Iterator for Utf16ToWtf8Iter<It> where It: Iterator, It::Item: Utf16CodeUnit {
+    type Item = Result<Wtf8Unit, NoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(b) = self.buf.pop() {
+            return Some(Ok(Wtf8Unit(b)));
+        }
+
+        let cu0 = match self.pending.take().or_else(|| self.iter.next().map(|cu| cu.to_raw())) {
+            Some(cu0) => cu0,
+            None => return None,
+        };
+
+        let cp = match cu0 {
+            0xd800 ... 0xdbff => match self.iter.next().map(|cu| cu.to_raw()) {
+                Some(cu1 @ 0xdc00 ... 0xdfff) => 0x10000 + (((cu0 & 0x3ff) << 10) | (cu1 & 0x3ff)),
+                cu1 => {
+                    self.pending = cu1;
+                    cu0
+                },
+            },
+            cu0 => cu0,
+        };
+
+        self.buf.fill(|buf| encode_wtf8(cp, buf));
+        self.buf.pop().map(|b| Ok(Wtf8Unit(b)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every unit produces between one and three units; a pair produces four.
+        let buffered = self.buf.remaining();
+        let pending = self.pending.is_some() as usize;
+        let (lo, hi) = self.iter.size_hint();
+        let lo = lo.saturating_add(pending);
+        (lo.saturating_add(buffered), hi.and_then(|hi| hi.checked_add(pending)).and_then(|hi| hi.checked_mul(3)).and_then(|hi| hi.checked_add(buffered)))
+    }
+}
+
+/**
+Decodes WTF-8 into UTF-16 units.
+*/
+pub struct Wtf8ToUtf16Iter<It, U> {
+    at: usize,
+    buf: Option<U>,
+    after_high: bool,
+    iter: Option<It>,
+}
+
+impl<It, U> Wtf8ToUtf16Iter<It, U> {
+    pub fn new(iter: It) -> Self {
+        Wtf8ToUtf16Iter {
+            at: 0,
+            buf: None,
+            after_high: false,
+            iter: Some(iter),
+        }
+    }
+}
+
+impl<It, U> Iterator for Wtf8ToUtf16Iter<It, U> where It: Iterator<Item=Wtf8Unit>, U: Utf16CodeUnit {
+    type Item = Result<U, Wtf8ToUtf16Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(cu) = self.buf.take() {
+            return Some(Ok(cu));
+        }
+
+        let r = match self.iter.as_mut() {
+            Some(iter) => match iter.next() {
+                Some(b0) => decode_wtf8(b0.0, &mut iter.map(|u| u.0)),
+                None => return None,
+            },
+            None => return None,
+        };
+
+        let (cp, len) = match r {
+            // An encoded surrogate pair should have been a single supplementary character.
+            Ok((0xdc00 ... 0xdfff, _)) if self.after_high => {
+                self.iter = None;
+                return Some(Err(Wtf8ToUtf16Error::InvalidAt(self.at)));
+            },
+            Ok(r) => r,
+            Err(err) => {
+                self.iter = None;
+                return Some(Err(err.at(self.at)));
+            },
+        };
+
+        self.at += len;
+        self.after_high = 0xd800 <= cp && cp <= 0xdbff;
+
+        if cp < 0x10000 {
+            Some(Ok(U::from_code_unit(cp as u16)))
+        } else {
+            let cp = cp - 0x10000;
+            self.buf = Some(U::from_code_unit(0xdc00 | (cp & 0x3ff) as u16));
+            Some(Ok(U::from_code_unit(0xd800 | (cp >> 10) as u16)))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every code point takes between one and four units, and produces one or two units.  Decoding stops at the first error, so only one more item is certain.
+        let buffered = self.buf.is_some() as usize;
+        match self.iter {
+            Some(ref iter) => {
+                let (lo, hi) = iter.size_hint();
+                (::std::cmp::min((lo + 2) / 3, 1).saturating_add(buffered), hi.and_then(|hi| hi.checked_add(buffered)))
+            },
+            None => (buffered, Some(buffered)),
+        }
+    }
+}
+
+/**
+Decodes WTF-8 into Unicode.  Unpaired surrogates cannot be represented, so they are errors.
+*/
+pub struct Wtf8ToUniIter<It> {
+    at: usize,
+    iter: Option<It>,
+}
+
+impl<It> Wtf8ToUniIter<It> {
+    pub fn new(iter: It) -> Self {
+        Wtf8ToUniIter {
+            at: 0,
+            iter: Some(iter),
+        }
+    }
+}
+
+impl<It> Iterator for Wtf8ToUniIter<It> where It: Iterator<Item=Wtf8Unit> {
+    type Item = Result<char, Wtf8ToUniError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let r = match self.iter.as_mut() {
+            Some(iter) => match iter.next() {
+                Some(b0) => decode_wtf8(b0.0, &mut iter.map(|u| u.0)),
+                None => return None,
+            },
+            None => return None,
+        };
+
+        match r.and_then(|(cp, len)| char::from_u32(cp).map(|ch| (ch, len)).ok_or(SeqError::Invalid)) {
+            Ok((ch, len)) => {
+                self.at += len;
+                Some(Ok(ch))
+            },
+            Err(err) => {
+                self.iter = None;
+                Some(Err(err.at(self.at)))
+            },
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every code point takes between one and four units.  Decoding stops at the first error, so only one more item is certain.
+        match self.iter {
+            Some(ref iter) => {
+                let (lo, hi) = iter.size_hint();
+                (::std::cmp::min((lo + 3) / 4, 1), hi)
+            },
+            None => (0, Some(0)),
+        }
+    }
+}
+
+/**
+Encodes Unicode as WTF-8, which for valid characters is exactly UTF-8.
+*/
+pub struct UniToWtf8Iter<It> {
+    buf: UnitBuf,
+    iter: It,
+}
+
+impl<It> UniToWtf8Iter<It> {
+    pub fn new(iter: It) -> Self {
+        UniToWtf8Iter {
+            buf: UnitBuf::new(),
+            iter: iter,
+        }
+    }
+}
+
+impl<It> Iterator for UniToWtf8Iter<It> where It: Iterator<Item=char> {
+    type Item = Result<Wtf8Unit, NoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(b) = self.buf.pop() {
+            return Some(Ok(Wtf8Unit(b)));
+        }
+
+        let ch = match self.iter.next() {
+            Some(ch) => ch,
+            None => return None,
+        };
+
+        self.buf.fill(|buf| ch.encode_utf8(buf).len());
+        self.buf.pop().map(|b| Ok(Wtf8Unit(b)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every code point produces between one and four units.
+        let buffered = self.buf.remaining();
+        let (lo, hi) = self.iter.size_hint();
+        (lo.saturating_add(buffered), hi.and_then(|hi| hi.checked_mul(4)).and_then(|hi| hi.checked_add(buffered)))
+    }
+}
+
+/**
+A small buffer of pending output bytes.  Four bytes is enough for any code point.
+*/
+struct UnitBuf {
+    buf: [u8; 4],
+    at: u8,
+    len: u8,
+}
+
+impl UnitBuf {
+    fn new() -> Self {
+        UnitBuf {
+            buf: [0; 4],
+            at: 0,
+            len: 0,
+        }
+    }
+
+    fn fill<F>(&mut self, f: F) where F: FnOnce(&mut [u8]) -> usize {
+        self.len = f(&mut self.buf[..]) as u8;
+        self.at = 0;
+    }
+
+    fn pop(&mut self) -> Option<u8> {
+        if self.at < self.len {
+            let b = self.buf[self.at as usize];
+            self.at += 1;
+            Some(b)
+        } else {
+            None
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        (self.len - self.at) as usize
+    }
+}
+
+/**
+Errors which can occur while decoding a single sequence.  These are turned into the public error types once the offset is known.
+*/
+enum SeqError {
+    Invalid,
+    Incomplete,
+}
+
+impl SeqError {
+    fn at<E>(self, at: usize) -> E where E: From<(SeqError, usize)> {
+        E::from((self, at))
+    }
+}
+
+/**
+Decodes a single code point from WTF-8, given the first unit.  Returns the code point, which may be a surrogate, and the number of units consumed.
+
+This is UTF-8 decoding, except that surrogates are allowed.  Whether a surrogate is acceptable in context is the caller's problem.
+*/
+fn decode_wtf8<It>(b0: u8, iter: &mut It) -> Result<(u32, usize), SeqError>
+where It: Iterator<Item=u8> {
+    // The number of continuation units, and the valid range of the *first* continuation unit.
+    let (len, lo, hi) = match b0 {
+        0x00 ... 0x7f => return Ok((b0 as u32, 1)),
+        0xc2 ... 0xdf => (1, 0x80, 0xbf),
+        0xe0 => (2, 0xa0, 0xbf),
+        0xe1 ... 0xef => (2, 0x80, 0xbf),
+        0xf0 => (3, 0x90, 0xbf),
+        0xf1 ... 0xf3 => (3, 0x80, 0xbf),
+        0xf4 => (3, 0x80, 0x8f),
+        _ => return Err(SeqError::Invalid),
+    };
+
+    let mut cp = (b0 as u32) & (0x7f >> (len + 1));
+    let (mut lo, mut hi) = (lo, hi);
+    for _ in 0..len {
+        let b = match iter.next() {
+            Some(b) => b,
+            None => return Err(SeqError::Incomplete),
+        };
+        if b < lo || hi < b {
+            return Err(SeqError::Invalid);
+        }
+        cp = (cp << 6) | (b & 0x3f) as u32;
+        lo = 0x80;
+        hi = 0xbf;
+    }
+
+    Ok((cp, len + 1))
+}
+
+/**
+Encodes a single code point, which may be a surrogate, into WTF-8, returning the number of units written.
+
+`buf` must be at least four units long.
+*/
+fn encode_wtf8(cp: u32, buf: &mut [u8]) -> usize {
+    match cp {
+        0x0000 ... 0x007f => {
+            buf[0] = cp as u8;
+            1
+        },
+        0x0080 ... 0x07ff => {
+            buf[0] = 0xc0 | (cp >> 6) as u8;
+            buf[1] = 0x80 | (cp & 0x3f) as u8;
+            2
+        },
+        0x0800 ... 0xffff => {
+            buf[0] = 0xe0 | (cp >> 12) as u8;
+            buf[1] = 0x80 | ((cp >> 6) & 0x3f) as u8;
+            buf[2] = 0x80 | (cp & 0x3f) as u8;
+            3
+        },
+        _ => {
+            buf[0] = 0xf0 | (cp >> 18) as u8;
+            buf[1] = 0x80 | ((cp >> 12) & 0x3f) as u8;
+            buf[2] = 0x80 | ((cp >> 6) & 0x3f) as u8;
+            buf[3] = 0x80 | (cp & 0x3f) as u8;
+            4
+        },
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Wtf8ToUtf16Error {
+    InvalidAt(usize),
+    Incomplete,
+}
+
+impl From<(SeqError, usize)> for Wtf8ToUtf16Error {
+    fn from((err, at): (SeqError, usize)) -> Self {
+        match err {
+            SeqError::Invalid => Wtf8ToUtf16Error::InvalidAt(at),
+            SeqError::Incomplete => Wtf8ToUtf16Error::Incomplete,
+        }
+    }
+}
+
+impl fmt::Display for Wtf8ToUtf16Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Wtf8ToUtf16Error::InvalidAt(at) => write!(fmt, "invalid unit at offset {}", at),
+            Wtf8ToUtf16Error::Incomplete => write!(fmt, "incomplete unit"),
+        }
+    }
+}
+
+impl ::std::error::Error for Wtf8ToUtf16Error {
+    fn description(&self) -> &str {
+        match *self {
+            Wtf8ToUtf16Error::InvalidAt(_) => "invalid unit",
+            Wtf8ToUtf16Error::Incomplete => "incomplete unit",
+        }
+    }
+}
+
+impl ErrorOffset for Wtf8ToUtf16Error {
+    fn offset(&self) -> Option<usize> {
+        match *self {
+            Wtf8ToUtf16Error::InvalidAt(at) => Some(at),
+            Wtf8ToUtf16Error::Incomplete => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Wtf8ToUniError {
+    InvalidAt(usize),
+    Incomplete,
+}
+
+impl From<(SeqError, usize)> for Wtf8ToUniError {
+    fn from((err, at): (SeqError, usize)) -> Self {
+        match err {
+            SeqError::Invalid => Wtf8ToUniError::InvalidAt(at),
+            SeqError::Incomplete => Wtf8ToUniError::Incomplete,
+        }
+    }
+}
+
+impl fmt::Display for Wtf8ToUniError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Wtf8ToUniError::InvalidAt(at) => write!(fmt, "invalid unit at offset {}", at),
+            Wtf8ToUniError::Incomplete => write!(fmt, "incomplete unit"),
+        }
+    }
+}
+
+impl ::std::error::Error for Wtf8ToUniError {
+    fn description(&self) -> &str {
+        match *self {
+            Wtf8ToUniError::InvalidAt(_) => "invalid unit",
+            Wtf8ToUniError::Incomplete => "incomplete unit",
+        }
+    }
+}
+
+impl ErrorOffset for Wtf8ToUniError {
+    fn offset(&self) -> Option<usize> {
+        match *self {
+            Wtf8ToUniError::InvalidAt(at) => Some(at),
+            Wtf8ToUniError::Incomplete => None,
+        }
+    }
+}
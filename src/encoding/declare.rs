@@ -0,0 +1,293 @@
+/*!
+Support for encodings defined outside this crate.
+*/
+
+/**
+Declares a new encoding, along with its unit type.
+
+This produces an uninhabited marker type implementing `Encoding`, and a `repr(C)` newtype unit implementing `Unit`, `UnitDebug`, `Debug`, `Ord`, and the other traits required of units.  For example:
+
+```
+#[macro_use] extern crate strffi;
+
+declare_encoding! {
+    /// The GSM 03.38 7-bit default alphabet, one character per unit.
+    pub enum Gsm7;
+    unit Gsm7Unit(u8);
+    prefix "Gsm7";
+}
+
+# fn main() {
+use strffi::alloc::Malloc;
+use strffi::sea::SeaString;
+use strffi::structure::ZeroTerm;
+
+let s = SeaString::<ZeroTerm, Gsm7, Malloc>::new(&[Gsm7Unit(0x48), Gsm7Unit(0x69)]).unwrap();
+assert_eq!(s.as_units().len(), 2);
+# }
+```
+
+The unit's underlying type must be one of `u8`, `u16`, or `u32`, and is also used as the encoding's `FfiUnit`.  Debug output assumes the encoding is a superset of ASCII: printable ASCII units are written as-is, and everything else as a hex escape sized to the unit.
+
+`MAX_UNITS_PER_CHAR` defaults to `1`.  For multi-unit encodings, give it explicitly after the prefix, *e.g.* `max_units_per_char 3;`.
+
+If the meaning of the encoding does not depend on the C runtime locale, or anything else which can differ between threads, finish with `locale_independent;` to implement `LocaleIndependent`, which allows owned strings in it to be sent between threads.  This is left off by default, as getting it wrong is unsafe.  Without it, owned strings stay on the thread which made them:
+
+```compile_fail
+#[macro_use] extern crate strffi;
+
+declare_encoding! {
+    pub enum Local;
+    unit LocalUnit(u8);
+    prefix "Local";
+}
+
+fn assert_send<T: Send>() {}
+
+fn main() {
+    assert_send::<strffi::sea::SeaString<strffi::structure::ZeroTerm, Local, strffi::alloc::Malloc>>();
+}
+```
+
+`static_zeroes` is provided automatically, as is the identity conversion from the encoding to itself.  The encoding is assumed to accept any sequence of units, and so implements `MutableUnits`.  Other transcoding support must still be implemented by hand, as must any of the optional encoding traits, such as `AsciiCompatible`.
+*/
+#[macro_export]
+macro_rules! declare_encoding {
+    (@locale_independent locale_independent $name:ident) => {
+        impl $crate::encoding::LocaleIndependent for $name {}
+    };
+
+    (
+        $(#[$attr:meta])*
+        $vis:vis enum $name:ident;
+        $(#[$unit_attr:meta])*
+        unit $unit:ident($repr:ident);
+        prefix $prefix:expr;
+        max_units_per_char $max:expr;
+        $($li:ident;)*
+    ) => {
+        $(#[$attr])*
+        $vis enum $name {}
+
+        impl $crate::encoding::Encoding for $name {
+            type Unit = $unit;
+            type FfiUnit = $repr;
+            const MAX_UNITS_PER_CHAR: usize = $max;
+
+            #[inline]
+            fn debug_prefix() -> &'static str { $prefix }
+
+            #[inline]
+            fn static_zeroes() -> &'static [Self::Unit] {
+                const ZEROES: &'static [$unit] = &[$unit(0), $unit(0)];
+                ZEROES
+            }
+        }
+
+        $(declare_encoding! { @locale_independent $li $name })*
+        unsafe impl $crate::encoding::MutableUnits for $name {}
+
+        impl<It> $crate::encoding::TranscodeTo<$name> for $crate::encoding::UnitIter<$name, It>
+        where It: Iterator<Item=$unit> {
+            type Iter = $crate::encoding::conv::identity::IdentityIter<It>;
+            type Error = $crate::encoding::conv::NoError;
+
+            fn transcode(self) -> Self::Iter {
+                $crate::encoding::conv::identity::IdentityIter::new(self.into_iter())
+            }
+
+            fn transparent() -> Option<$crate::encoding::Transparent<Self, $name>> {
+                Some($crate::encoding::Transparent::new())
+            }
+        }
+
+        $(#[$unit_attr])*
+        #[derive(Copy, Clone, PartialEq, Eq, Hash)]
+        #[repr(C)]
+        $vis struct $unit(pub $repr);
+
+        impl $crate::encoding::Unit for $unit {
+            #[inline]
+            fn zero() -> Self {
+                $unit(0)
+            }
+
+            #[inline]
+            fn is_zero(&self) -> bool {
+                self.0 == 0
+            }
+
+            const BITS: u32 = (::std::mem::size_of::<$repr>() * 8) as u32;
+
+            #[inline]
+            fn to_raw(&self) -> u32 {
+                self.0 as u32
+            }
+
+            #[inline]
+            fn from_raw(v: u32) -> Option<Self> {
+                if v <= $repr::max_value() as u32 {
+                    Some($unit(v as $repr))
+                } else {
+                    None
+                }
+            }
+        }
+
+        impl $crate::encoding::UnitDebug for $unit {
+            fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                let v = self.0 as u32;
+                if 0x20 <= v && v <= 0x7e {
+                    ::std::fmt::Display::fmt(&(v as u8 as char), fmt)
+                } else {
+                    match <$unit as $crate::encoding::Unit>::BITS {
+                        8 => write!(fmt, "\\x{:02x}", v),
+                        16 => write!(fmt, "\\u{:04x}", v),
+                        _ => write!(fmt, "\\U{:08x}", v),
+                    }
+                }
+            }
+        }
+
+        impl ::std::fmt::Debug for $unit {
+            fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                write!(fmt, "'")?;
+                $crate::encoding::UnitDebug::fmt(self, fmt)?;
+                write!(fmt, "'")
+            }
+        }
+
+        impl ::std::cmp::Ord for $unit {
+            fn cmp(&self, other: &Self) -> ::std::cmp::Ordering {
+                self.0.cmp(&other.0)
+            }
+        }
+
+        impl ::std::cmp::PartialOrd for $unit {
+            fn partial_cmp(&self, other: &Self) -> Option<::std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+    };
+
+    (
+        $(#[$attr:meta])*
+        $vis:vis enum $name:ident;
+        $(#[$unit_attr:meta])*
+        unit $unit:ident($repr:ident);
+        prefix $prefix:expr;
+        $($li:ident;)*
+    ) => {
+        declare_encoding! {
+            $(#[$attr])*
+            $vis enum $name;
+            $(#[$unit_attr])*
+            unit $unit($repr);
+            prefix $prefix;
+            max_units_per_char 1;
+            $($li;)*
+        }
+    };
+}
+
+/**
+Declares a new encoding for a fixed Windows code page.
+
+This is `declare_encoding!` for an encoding with `u8` units, which additionally implements `ByteEncoding` and `CodePage`, and so can be transcoded to and from `Wide` and `CheckedUnicode`, regardless of the current ANSI code page or C runtime locale.  For example:
+
+```ignore
+#[macro_use] extern crate strffi;
+
+declare_code_page! {
+    /// Shift JIS, as used by Windows.
+    pub enum Cp932;
+    unit Cp932Unit;
+    prefix "Cp932";
+    code_page 932;
+}
+```
+
+See `CodePage` for the code pages which are supported.
+
+This is only available on Windows.
+*/
+#[cfg(windows)]
+#[macro_export]
+macro_rules! declare_code_page {
+    (
+        $(#[$attr:meta])*
+        $vis:vis enum $name:ident;
+        $(#[$unit_attr:meta])*
+        unit $unit:ident;
+        prefix $prefix:expr;
+        code_page $cp:expr;
+    ) => {
+        declare_encoding! {
+            $(#[$attr])*
+            $vis enum $name;
+            $(#[$unit_attr])*
+            unit $unit(u8);
+            prefix $prefix;
+            max_units_per_char 4;
+            locale_independent;
+        }
+
+        unsafe impl $crate::encoding::ByteEncoding for $name {}
+
+        impl $crate::encoding::conv::win_cp::CodePage for $name {
+            #[inline]
+            fn code_page() -> u32 { $cp }
+        }
+    };
+}
+
+/**
+Declares a new encoding for a character set supported by `iconv`.
+
+This is `declare_encoding!` for an encoding with `u8` units, which additionally implements `ByteEncoding` and `IconvEncoding`, and so can be transcoded to and from `CheckedUnicode`, regardless of the C runtime locale.  For example:
+
+```ignore
+#[macro_use] extern crate strffi;
+
+declare_iconv_encoding! {
+    /// Shift JIS.
+    pub enum ShiftJis;
+    unit ShiftJisUnit;
+    prefix "Sjis";
+    iconv_name "SHIFT_JIS";
+}
+```
+
+`MAX_UNITS_PER_CHAR` is `ffi::MB_LEN_MAX`, since the longest sequence in the character set cannot be known in advance.
+
+This is only available on Unix platforms, with the `iconv` feature enabled.
+*/
+#[cfg(all(unix, feature="iconv"))]
+#[macro_export]
+macro_rules! declare_iconv_encoding {
+    (
+        $(#[$attr:meta])*
+        $vis:vis enum $name:ident;
+        $(#[$unit_attr:meta])*
+        unit $unit:ident;
+        prefix $prefix:expr;
+        iconv_name $iconv_name:expr;
+    ) => {
+        declare_encoding! {
+            $(#[$attr])*
+            $vis enum $name;
+            $(#[$unit_attr])*
+            unit $unit(u8);
+            prefix $prefix;
+            max_units_per_char $crate::ffi::MB_LEN_MAX;
+            locale_independent;
+        }
+
+        unsafe impl $crate::encoding::ByteEncoding for $name {}
+
+        impl $crate::encoding::conv::iconv::IconvEncoding for $name {
+            #[inline]
+            fn iconv_name() -> &'static str { $iconv_name }
+        }
+    };
+}
@@ -0,0 +1,158 @@
+/*!
+Decoding characters directly from a slice of units.
+
+Unlike transcoding, decoding does not stop at the first error: each invalid sequence is reported where it occurs, and decoding resumes immediately after it.
+*/
+use std::char;
+use std::cmp;
+use std::fmt;
+use std::str;
+use encoding::{Encoding, Unit, CheckedUnicode, CheckedUtf8, Utf8, Utf16, Utf16Le, Utf16Be,
+    Utf32, Utf32Le, Utf32Be, Wide16, Wide32, WinUnicode};
+
+/**
+Implemented by encodings whose units can be decoded into characters without reference to any external state.
+
+See `SeStr::char_indices`.
+*/
+pub trait DecodeUnits: Encoding {
+    /**
+    Decodes the character starting at offset `*at` in `units`.
+
+    Returns `None` if `*at` is at the end of `units`.  Otherwise, `*at` is advanced past the decoded character, or past the invalid sequence, ready for the next call.  An invalid sequence always occupies at least one unit, so repeated calls will always reach the end.
+    */
+    fn decode_next(units: &[Self::Unit], at: &mut usize) -> Option<Result<char, DecodeError>>;
+}
+
+/**
+The error produced when a character cannot be decoded.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /**
+    The units at the given offset do not form a valid sequence.
+    */
+    InvalidAt(usize),
+
+    /**
+    The string ends in the middle of a sequence starting at the given offset.
+    */
+    IncompleteAt(usize),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DecodeError::InvalidAt(at) => write!(fmt, "invalid unit at offset {}", at),
+            DecodeError::IncompleteAt(at) => write!(fmt, "incomplete unit at offset {}", at),
+        }
+    }
+}
+
+impl ::std::error::Error for DecodeError {
+    fn description(&self) -> &str {
+        match *self {
+            DecodeError::InvalidAt(_) => "invalid unit",
+            DecodeError::IncompleteAt(_) => "incomplete unit",
+        }
+    }
+}
+
+fn decode_utf8(bytes: &[u8], at: &mut usize) -> Option<Result<char, DecodeError>> {
+    let start = *at;
+    if start >= bytes.len() {
+        return None;
+    }
+
+    // No character is longer than four bytes, so there's no point looking any further.
+    let end = cmp::min(start + 4, bytes.len());
+    let valid = match str::from_utf8(&bytes[start..end]) {
+        Ok(s) => s,
+        Err(err) if err.valid_up_to() > 0 => unsafe { str::from_utf8_unchecked(&bytes[start..start + err.valid_up_to()]) },
+        Err(err) => {
+            return match err.error_len() {
+                Some(len) => {
+                    *at += len;
+                    Some(Err(DecodeError::InvalidAt(start)))
+                },
+                None => {
+                    *at = bytes.len();
+                    Some(Err(DecodeError::IncompleteAt(start)))
+                },
+            };
+        },
+    };
+
+    let c = valid.chars().next().expect("decoded empty UTF-8 sequence");
+    *at += c.len_utf8();
+    Some(Ok(c))
+}
+
+impl DecodeUnits for Utf8 {
+    fn decode_next(units: &[Self::Unit], at: &mut usize) -> Option<Result<char, DecodeError>> {
+        // `Utf8Unit` is a `repr(C)` wrapper around `u8`.
+        decode_utf8(unsafe { &*(units as *const [<Utf8 as Encoding>::Unit] as *const [u8]) }, at)
+    }
+}
+
+impl DecodeUnits for CheckedUtf8 {
+    fn decode_next(units: &[Self::Unit], at: &mut usize) -> Option<Result<char, DecodeError>> {
+        // `CheckedUtf8Unit` is a `repr(C)` wrapper around `u8`.
+        decode_utf8(unsafe { &*(units as *const [<CheckedUtf8 as Encoding>::Unit] as *const [u8]) }, at)
+    }
+}
+
+impl DecodeUnits for CheckedUnicode {
+    fn decode_next(units: &[Self::Unit], at: &mut usize) -> Option<Result<char, DecodeError>> {
+        units.get(*at).map(|&c| {
+            *at += 1;
+            Ok(c)
+        })
+    }
+}
+
+fn decode_utf16<U>(units: &[U], at: &mut usize) -> Option<Result<char, DecodeError>> where U: Unit {
+    let start = *at;
+    let cu0 = match units.get(start) {
+        Some(cu0) => cu0.to_raw(),
+        None => return None,
+    };
+    *at += 1;
+
+    match cu0 {
+        0xd800 ... 0xdbff => match units.get(start + 1).map(Unit::to_raw) {
+            Some(cu1 @ 0xdc00 ... 0xdfff) => {
+                *at += 1;
+                let cp = 0x10000 + (((cu0 & 0x3ff) << 10) | (cu1 & 0x3ff));
+                Some(Ok(char::from_u32(cp).expect("decoded invalid code point")))
+            },
+            // Leave the following unit to be decoded on its own.
+            Some(_) => Some(Err(DecodeError::InvalidAt(start))),
+            None => Some(Err(DecodeError::IncompleteAt(start))),
+        },
+        cp => Some(char::from_u32(cp).ok_or(DecodeError::InvalidAt(start))),
+    }
+}
+
+fn decode_utf32<U>(units: &[U], at: &mut usize) -> Option<Result<char, DecodeError>> where U: Unit {
+    let start = *at;
+    units.get(start).map(|cu| {
+        *at += 1;
+        char::from_u32(cu.to_raw()).ok_or(DecodeError::InvalidAt(start))
+    })
+}
+
+macro_rules! decode_with {
+    ($f:ident: $($enc:ty),*) => {
+        $(
+            impl DecodeUnits for $enc {
+                fn decode_next(units: &[Self::Unit], at: &mut usize) -> Option<Result<char, DecodeError>> {
+                    $f(units, at)
+                }
+            }
+        )*
+    };
+}
+
+decode_with! { decode_utf16: Utf16, Utf16Le, Utf16Be, Wide16, WinUnicode }
+decode_with! { decode_utf32: Utf32, Utf32Le, Utf32Be, Wide32 }
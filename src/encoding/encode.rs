@@ -0,0 +1,184 @@
+/*!
+Encoding individual characters directly into a buffer of units.
+
+This is the counterpart to the `decode` module: it allows generic code to produce the units for a character without transcoding, or allocating.
+*/
+use std::fmt;
+use encoding::{Encoding, Unit, CheckedUnicode, CheckedUtf8, JniMtf8, Utf8, Utf16, Utf16Le, Utf16Be,
+    Utf32, Utf32Le, Utf32Be, Wide16, Wide32, WinUnicode};
+use encoding::conv::jni_mtf8::encode_mtf8;
+
+/**
+Implemented by encodings which can encode a character without reference to any external state.
+
+See `SeaString::push_char`.
+*/
+pub trait EncodeChar: Encoding {
+    /**
+    Encodes `c` into the start of `buf`, returning the units written.
+
+    A buffer of `MAX_UNITS_PER_CHAR` units is always large enough.
+
+    # Failure
+
+    This method will fail if `c` cannot be represented in this encoding, or if `buf` is too small to hold its encoding.  In either case, the contents of `buf` are unspecified.
+    */
+    fn encode(c: char, buf: &mut [Self::Unit]) -> Result<&[Self::Unit], EncodeError>;
+}
+
+/**
+The error produced when a character cannot be encoded.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EncodeError {
+    /**
+    The character cannot be represented in the encoding.
+    */
+    Unrepresentable(char),
+
+    /**
+    The buffer is too small to hold the encoded character.
+    */
+    BufferTooSmall(char),
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EncodeError::Unrepresentable(c) => write!(fmt, "character {:?} cannot be represented", c),
+            EncodeError::BufferTooSmall(c) => write!(fmt, "buffer too small to encode character {:?}", c),
+        }
+    }
+}
+
+impl ::std::error::Error for EncodeError {
+    fn description(&self) -> &str {
+        match *self {
+            EncodeError::Unrepresentable(_) => "unrepresentable character",
+            EncodeError::BufferTooSmall(_) => "buffer too small",
+        }
+    }
+}
+
+/**
+Encodes `c` onto the end of `out`, making room for `E::MAX_UNITS_PER_CHAR` units first.
+
+On failure, `out` is left as it was.
+*/
+pub(crate) fn encode_onto<E>(c: char, out: &mut Vec<E::Unit>) -> Result<(), EncodeError> where E: EncodeChar {
+    let end = out.len();
+    out.resize(end + E::MAX_UNITS_PER_CHAR, E::Unit::zero());
+    match E::encode(c, &mut out[end..]).map(|encoded| encoded.len()) {
+        Ok(len) => {
+            out.truncate(end + len);
+            Ok(())
+        },
+        Err(err) => {
+            out.truncate(end);
+            Err(err)
+        },
+    }
+}
+
+/**
+Encodes `c`, and passes the units to `f`.
+
+The units are encoded on the stack if `E::MAX_UNITS_PER_CHAR` is small enough, as it is for every encoding in this crate; otherwise, a buffer of that size is allocated.
+*/
+pub(crate) fn with_encoded<E, F, R>(c: char, f: F) -> Result<R, EncodeError>
+where E: EncodeChar, F: FnOnce(&[E::Unit]) -> R {
+    const STACK_UNITS: usize = 8;
+    if E::MAX_UNITS_PER_CHAR <= STACK_UNITS {
+        let mut buf = [E::Unit::zero(); STACK_UNITS];
+        E::encode(c, &mut buf[..E::MAX_UNITS_PER_CHAR]).map(f)
+    } else {
+        let mut buf = vec![E::Unit::zero(); E::MAX_UNITS_PER_CHAR];
+        E::encode(c, &mut buf).map(f)
+    }
+}
+
+/**
+Writes raw unit values into `buf`, failing if any of them are out of range for `U`.
+*/
+fn write_raw<'a, U>(c: char, raw: &[u32], buf: &'a mut [U]) -> Result<&'a [U], EncodeError> where U: Unit {
+    if buf.len() < raw.len() {
+        return Err(EncodeError::BufferTooSmall(c));
+    }
+    for (unit, &v) in buf.iter_mut().zip(raw) {
+        *unit = U::from_raw(v).ok_or(EncodeError::Unrepresentable(c))?;
+    }
+    Ok(&buf[..raw.len()])
+}
+
+fn encode_utf8(c: char, buf: &mut [u8]) -> Result<&[u8], EncodeError> {
+    if buf.len() < c.len_utf8() {
+        return Err(EncodeError::BufferTooSmall(c));
+    }
+    Ok(c.encode_utf8(buf).as_bytes())
+}
+
+impl EncodeChar for Utf8 {
+    fn encode(c: char, buf: &mut [Self::Unit]) -> Result<&[Self::Unit], EncodeError> {
+        // `Utf8Unit` is a `repr(C)` wrapper around `u8`.
+        let bytes = unsafe { &mut *(buf as *mut [<Utf8 as Encoding>::Unit] as *mut [u8]) };
+        let len = encode_utf8(c, bytes)?.len();
+        Ok(&buf[..len])
+    }
+}
+
+impl EncodeChar for CheckedUtf8 {
+    fn encode(c: char, buf: &mut [Self::Unit]) -> Result<&[Self::Unit], EncodeError> {
+        // `CheckedUtf8Unit` is a `repr(C)` wrapper around `u8`.
+        let bytes = unsafe { &mut *(buf as *mut [<CheckedUtf8 as Encoding>::Unit] as *mut [u8]) };
+        let len = encode_utf8(c, bytes)?.len();
+        Ok(&buf[..len])
+    }
+}
+
+impl EncodeChar for JniMtf8 {
+    fn encode(c: char, buf: &mut [Self::Unit]) -> Result<&[Self::Unit], EncodeError> {
+        let mut bytes = [0u8; 6];
+        let len = encode_mtf8(c, &mut bytes);
+        let mut raw = [0u32; 6];
+        for (r, &b) in raw.iter_mut().zip(&bytes[..len]) {
+            *r = b as u32;
+        }
+        write_raw(c, &raw[..len], buf)
+    }
+}
+
+impl EncodeChar for CheckedUnicode {
+    fn encode(c: char, buf: &mut [Self::Unit]) -> Result<&[Self::Unit], EncodeError> {
+        match buf.first_mut() {
+            Some(unit) => *unit = c,
+            None => return Err(EncodeError::BufferTooSmall(c)),
+        }
+        Ok(&buf[..1])
+    }
+}
+
+fn encode_utf16<U>(c: char, buf: &mut [U]) -> Result<&[U], EncodeError> where U: Unit {
+    let mut utf16 = [0u16; 2];
+    let utf16 = c.encode_utf16(&mut utf16);
+    let raw = [utf16[0] as u32, utf16.get(1).map(|&cu| cu as u32).unwrap_or(0)];
+    write_raw(c, &raw[..utf16.len()], buf)
+}
+
+fn encode_utf32<U>(c: char, buf: &mut [U]) -> Result<&[U], EncodeError> where U: Unit {
+    write_raw(c, &[c as u32], buf)
+}
+
+macro_rules! encode_with {
+    ($f:ident: $($enc:ty),*) => {
+        $(
+            impl EncodeChar for $enc {
+                fn encode(c: char, buf: &mut [Self::Unit]) -> Result<&[Self::Unit], EncodeError> {
+                    $f(c, buf)
+                }
+            }
+        )*
+    };
+}
+
+encode_with! { encode_utf16: Utf16, Utf16Le, Utf16Be, Wide16, WinUnicode }
+encode_with! { encode_utf32: Utf32, Utf32Le, Utf32Be, Wide32 }
@@ -1,14 +1,29 @@
 /*!
 Encoding types and traits.
 */
+pub mod bom;
 pub mod conv;
-
+pub mod decode;
+mod declare;
+pub mod encode;
+pub mod stream;
+pub mod validate;
+
+pub use self::bom::{Bom, Endianness};
+pub use self::decode::{DecodeUnits, DecodeError};
+pub use self::encode::{EncodeChar, EncodeError};
+pub use self::stream::{StreamTranscode, StreamTranscoder, TranscodeError};
+pub use self::validate::{ValidateEncoding, EncodingError};
+
+use std::char;
 use std::cmp::Ordering;
 use std::fmt::{self, Debug, Display};
 use std::hash::Hash;
 use std::marker::PhantomData;
 use std::mem;
-use libc::{c_char, wchar_t};
+use libc::c_char;
+#[cfg(not(windows))] use libc::wchar_t;
+use ffi::MB_LEN_MAX;
 
 /**
 This trait abstracts over different encoding schemes for strings used in foreign code.
@@ -36,6 +51,13 @@ pub trait Encoding {
     */
     type FfiUnit;
 
+    /**
+    The largest number of units this encoding can need to represent a single character.
+
+    This is intended for sizing fixed buffers, and for computing upper bounds on the length of transcoded strings.  For encodings whose width depends on the C runtime locale, this is the bound for *any* supported locale, not just the current one.
+    */
+    const MAX_UNITS_PER_CHAR: usize;
+
     /**
     Returns a string which can be used to uniquely identify this encoding in debug output.
 
@@ -72,6 +94,25 @@ pub trait Unit: Copy + PartialEq + Eq + PartialOrd + Ord + Hash + UnitDebug + 's
     Determines if a given unit is equal to the zero unit.
     */
     fn is_zero(&self) -> bool;
+
+    /**
+    The number of significant bits in the raw value of a unit.  All values returned by `to_raw` are less than `1 << BITS`.
+    */
+    const BITS: u32;
+
+    /**
+    Returns the raw value of this unit.
+
+    For units with an explicit byte order, this is the logical value, in native byte order.  Units based on signed types are zero-extended, so that a `c_char` of `-1` becomes `0xff`.
+    */
+    fn to_raw(&self) -> u32;
+
+    /**
+    Constructs a unit from a raw value, as returned by `to_raw`.
+
+    Returns `None` if the value is out of range for this unit type.  Unit types which cannot be validated in isolation, such as `CheckedUtf8Unit`, always return `None`.
+    */
+    fn from_raw(v: u32) -> Option<Self>;
 }
 
 /**
@@ -89,6 +130,35 @@ pub trait UnitDebug {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result;
 }
 
+/**
+Selects the escaping rules used by `SeStr::escape_units_as`.
+
+In all styles, printable ASCII units are written directly, and quotes, backslashes, tabs, carriage returns, and line feeds use their usual two-character escapes.  The styles differ in how all other units are written.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EscapeStyle {
+    /**
+    Rust string literal syntax.  NUL is written as `\0`, 8-bit units as `\xHH`, and wider units as `\u{H}`.
+
+    Note that Rust only accepts `\xHH` escapes above `\x7f` in byte string literals, and only accepts `\u{H}` escapes for valid code points.
+    */
+    RustLike,
+
+    /**
+    C string literal syntax.  8-bit units are written as three-digit octal escapes (`\ooo`), and wider units as `\xH` escapes, suitable for wide string literals.
+
+    Because a C hex escape consumes *every* hex digit which follows it, a hex escape followed by a literal hex digit is split into two adjacent literals (*e.g.* `"\x263a""a"`).
+    */
+    CLike,
+
+    /**
+    JSON string syntax.  Units are written as `\uHHHH` escapes, with units above U+FFFF written as UTF-16 surrogate pairs.
+
+    JSON has no way of representing raw bytes, so 8-bit units are written as the code point of the same value.  Units above U+10FFFF cannot be represented at all, and are written as `\ufffd`.
+    */
+    Json,
+}
+
 /**
 Implementations of this trait define conversions from the implementing encoding to a given destination encoding.
 
@@ -137,6 +207,45 @@ pub trait TranscodeTo<Dst>: Sized where Dst: Encoding {
     Begin transcoding from the `Self` encoding to the `Dst` encoding.
     */
     fn transcode(self) -> Self::Iter;
+
+    /**
+    Returns proof that transcoding is unnecessary, if the source encoding is `TransparentAs` the target.
+
+    This lets `SeStr::transcode_to_cow` borrow the source string instead of copying it.  The default returns `None`, which is always safe.
+    */
+    fn transparent() -> Option<Transparent<Self, Dst>> {
+        None
+    }
+
+    /**
+    Decodes a whole slice of source units into a `String`, if this transcoder has a faster way of doing so than going one character at a time.
+
+    This is only consulted by `SeStr::into_string`, where `Dst` is `CheckedUnicode` and `units` are the string's own units.  The default returns `None`, which falls back to `transcode`.  Implementations should also return `None` for invalid input, so that the error is reported by `transcode`, exactly as it would have been otherwise.
+    */
+    fn decode_to_string(units: &[<Self as TranscodeSource>::Unit]) -> Option<String> where Self: TranscodeSource {
+        let _ = units;
+        None
+    }
+
+    /**
+    Checks whether a whole slice of source units would transcode without error, if this transcoder has a faster way of doing so than transcoding them.
+
+    This is only consulted by `SeStr::is_valid`, where `Dst` is `CheckedUnicode` and `units` are the string's own units.  The default returns `None`, which falls back to `transcode`.  Implementations must accept exactly what `transcode` does.
+    */
+    fn is_valid_units(units: &[<Self as TranscodeSource>::Unit]) -> Option<bool> where Self: TranscodeSource {
+        let _ = units;
+        None
+    }
+
+    /**
+    Transcodes a whole zero-terminated slice of source units at once, if this transcoder has a faster way of doing so than going one unit at a time.
+
+    This is only consulted by `SeStr::transcode_to`, where `units` are the string's own units, *including* the terminator.  Without `out`, this returns the number of units the result needs.  With `out`, which is exactly that long, it fills `out` and returns the same number again.  The default returns `None`, which falls back to `transcode`.  Implementations should also return `None` for invalid input, so that the error is reported by `transcode`, exactly as it would have been otherwise.
+    */
+    fn transcode_terminated(units: &[<Self as TranscodeSource>::Unit], out: Option<&mut [Dst::Unit]>) -> Option<usize> where Self: TranscodeSource {
+        let _ = (units, out);
+        None
+    }
 }
 
 /**
@@ -159,6 +268,23 @@ where
     _marker: PhantomData<E>,
 }
 
+/**
+Names the units a transcoder reads.
+
+This ties the slice-based hooks on `TranscodeTo`, such as `decode_to_string`, to the source encoding's own unit type.
+*/
+pub trait TranscodeSource {
+    type Unit: Unit;
+}
+
+impl<E, It> TranscodeSource for UnitIter<E, It>
+where
+    It: Iterator<Item=E::Unit>,
+    E: Encoding,
+{
+    type Unit = E::Unit;
+}
+
 impl<E, It> UnitIter<E, It>
 where
     It: Iterator<Item=E::Unit>,
@@ -178,12 +304,92 @@ where
 
 /**
 If implemented on an iterator, indicates that it can recover from transcoding errors.
+
+A recoverable transcoder produces an `Err` in place of each invalid or inconvertible sequence, and resumes transcoding immediately afterward, rather than ending.  See `SeStr::into_string_recover`.
 */
-// TODO: add support to string types.
 pub trait Recoverable {}
 
+/**
+Indicates that strings in this encoding can be reinterpreted, unchanged, as strings in `F`.
+
+Every encoding is transparent as itself.  Other pairs are distinct encodings which happen to share a representation, such as `Wide16` and `WinUnicode`, or `Utf16` and `Utf16Le` on little-endian machines.
+
+# Safety
+
+`Self::Unit` must have the same size and alignment as `F::Unit`, and every sequence of `Self` units must also be a valid sequence of `F` units with the same meaning.  In particular, nothing may be transparent as `CheckedUnicode` or `CheckedUtf8` unless it guarantees their validity invariants.
+*/
+pub unsafe trait TransparentAs<F>: Encoding where F: Encoding {}
+
+unsafe impl<E> TransparentAs<E> for E where E: Encoding {}
+
+/**
+Proof that the source encoding of the transcoder `T` is `TransparentAs` `F`.
+
+This can only be created where that bound holds, so a `TranscodeTo` implementation cannot claim to be transparent without it.  See `TranscodeTo::transparent`.
+*/
+pub struct Transparent<T, F> {
+    _marker: PhantomData<(T, F)>,
+}
+
+impl<E, It, F> Transparent<UnitIter<E, It>, F>
+where
+    It: Iterator<Item=E::Unit>,
+    E: TransparentAs<F>,
+    F: Encoding,
+{
+    pub fn new() -> Self {
+        Transparent {
+            _marker: PhantomData,
+        }
+    }
+}
+
+/**
+Indicates that an encoding is a superset of ASCII at the level of individual units.
+
+Implementing this trait asserts that every unit with a raw value in `0x00...0x7f` represents the corresponding ASCII character, and that such units *never* appear as part of a larger multi-unit sequence.  As a result, ASCII letters can be case-folded unit-by-unit without disturbing anything else in the string.
+
+This is deliberately *not* implemented for `MultiByte`: some C runtime locales (such as Shift JIS) use bytes in the ASCII range as the trailing bytes of multi-byte characters.
+*/
+pub trait AsciiCompatible: Encoding {}
+
+/**
+Indicates that an encoding's units are plain bytes.
+
+# Safety
+
+Implementing this trait asserts that `Self::Unit` has exactly the same layout as `u8`, and that *every* byte value is a valid unit.  This allows strings in the encoding to be reinterpreted as byte slices, and vice versa, without copying.
+
+This is not implemented for `CheckedUtf8`, since not every byte sequence is valid in that encoding.
+*/
+pub unsafe trait ByteEncoding: Encoding {}
+
+/**
+Indicates that *any* sequence of units is a valid string in this encoding.
+
+# Safety
+
+Implementing this trait allows safe code to overwrite, remove, and insert individual units (*e.g.* with `SeStr::as_units_mut` or `SeaString::retain`).  It must not be implemented for an encoding with a validity invariant that such changes could break.
+
+This is not implemented for `CheckedUtf8`, since changing a single unit can leave a malformed or truncated sequence behind.
+*/
+pub unsafe trait MutableUnits: Encoding {}
+
+/**
+Indicates that the meaning of a string in this encoding does not depend on the thread which interprets it.
+
+Owned strings are only `Send` and `Sync` when their encoding implements this trait; see `SeaString`.
+
+This is deliberately *not* implemented for `MultiByte`, `Wide16`, or `Wide32`.  Their meaning depends on the C runtime locale, which can differ from one thread to the next (*e.g.* via `uselocale`), and which can be changed by `setlocale` at any time.  A multibyte string produced on one thread may be garbage, or outright invalid, when decoded on another.  The same goes for `Char16` and `Char32`, unless the `stdc-utf` feature asserts that they are always UTF-16 and UTF-32.
+*/
+pub trait LocaleIndependent: Encoding {}
+
 macro_rules! naive_unit_impl {
-    ($ty_name:ident) => {
+    ($ty_name:ident: $raw_ty:ident) => {
+        naive_unit_impl! { $ty_name: $raw_ty, from_raw: |v| Some($ty_name(v as $raw_ty as _)) }
+    };
+
+    ($ty_name:ident: $raw_ty:ident, from_raw: $from_raw:expr) => {
         impl Unit for $ty_name {
             #[inline]
             fn zero() -> Self {
@@ -194,6 +400,22 @@ macro_rules! naive_unit_impl {
             fn is_zero(&self) -> bool {
                 self.0 == 0
             }
+
+            const BITS: u32 = (mem::size_of::<$raw_ty>() * 8) as u32;
+
+            #[inline]
+            fn to_raw(&self) -> u32 {
+                self.0 as $raw_ty as u32
+            }
+
+            #[inline]
+            fn from_raw(v: u32) -> Option<Self> {
+                if v <= $raw_ty::max_value() as u32 {
+                    ($from_raw)(v)
+                } else {
+                    None
+                }
+            }
         }
 
         impl Debug for $ty_name {
@@ -232,16 +454,100 @@ macro_rules! ascii_ext_unit_impl {
     };
 }
 
+macro_rules! byte_order_unit_impl {
+    ($ty_name:ident {int: $int:ident, to: $to:ident, from: $from:ident, format: $format:expr}) => {
+        impl $ty_name {
+            /**
+            Constructs a unit from a logical code unit value, given in native byte order.
+            */
+            #[inline]
+            pub fn new(v: $int) -> Self {
+                $ty_name($int::$to(v))
+            }
+
+            /**
+            Returns the logical code unit value, in native byte order.
+            */
+            #[inline]
+            pub fn get(self) -> $int {
+                $int::$from(self.0)
+            }
+        }
+
+        impl Unit for $ty_name {
+            #[inline]
+            fn zero() -> Self {
+                $ty_name(0)
+            }
+
+            #[inline]
+            fn is_zero(&self) -> bool {
+                self.0 == 0
+            }
+
+            const BITS: u32 = (mem::size_of::<$int>() * 8) as u32;
+
+            #[inline]
+            fn to_raw(&self) -> u32 {
+                self.get() as u32
+            }
+
+            #[inline]
+            fn from_raw(v: u32) -> Option<Self> {
+                if v <= $int::max_value() as u32 {
+                    Some($ty_name::new(v as $int))
+                } else {
+                    None
+                }
+            }
+        }
+
+        impl Debug for $ty_name {
+            fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+                write!(fmt, "'")?;
+                UnitDebug::fmt(self, fmt)?;
+                write!(fmt, "'")
+            }
+        }
+
+        impl Ord for $ty_name {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.get().cmp(&other.get())
+            }
+        }
+
+        impl PartialOrd for $ty_name {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl UnitDebug for $ty_name {
+            fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+                let v = self.get();
+                if 0x20 <= v && v <= 0x7e {
+                    Display::fmt(&(v as u8 as char), fmt)
+                } else {
+                    write!(fmt, $format, v)
+                }
+            }
+        }
+    };
+}
+
 /**
 Represents the current, thread-specific C runtime multi-byte encoding.
 
 This depends on the current locale as controlled by the `setlocale` function.
+
+For tests which must not depend on the locales installed on the machine, the `mb-test-codec` feature allows conversions to be redirected to a fixed, built-in codec; see `ffi::test_codec`.
 */
 pub enum MultiByte {}
 
 impl Encoding for MultiByte {
     type Unit = MbUnit;
     type FfiUnit = c_char;
+    const MAX_UNITS_PER_CHAR: usize = MB_LEN_MAX;
 
     #[inline]
     fn debug_prefix() -> &'static str { "Mb" }
@@ -253,6 +559,9 @@ impl Encoding for MultiByte {
     }
 }
 
+unsafe impl ByteEncoding for MultiByte {}
+unsafe impl MutableUnits for MultiByte {}
+
 /**
 A string unit encoded in the current, thread-specific C runtime multi-byte encoding.
 */
@@ -260,128 +569,705 @@ A string unit encoded in the current, thread-specific C runtime multi-byte encod
 #[repr(C)]
 pub struct MbUnit(pub c_char);
 
-naive_unit_impl! { MbUnit }
+naive_unit_impl! { MbUnit: u8 }
 ascii_ext_unit_impl! { MbUnit { format: "\\x{:02x}", unit_ty: u8 }}
 
 /**
 Represents the C runtime wide encoding.
+
+This is an alias for whichever of `Wide16` or `Wide32` matches the width of `wchar_t` on the target platform: `Wide16` on Windows, and `Wide32` elsewhere.  Code which stores wide units in files or protocols should use one of the fixed-width encodings directly.
 */
-pub enum Wide {}
+#[cfg(windows)]
+pub use self::Wide16 as Wide;
 
-impl Encoding for Wide {
-    type Unit = WUnit;
-    type FfiUnit = wchar_t;
+/**
+Represents the C runtime wide encoding.
 
-    #[inline]
-    fn debug_prefix() -> &'static str { "W" }
+This is an alias for whichever of `Wide16` or `Wide32` matches the width of `wchar_t` on the target platform: `Wide16` on Windows, and `Wide32` elsewhere.  Code which stores wide units in files or protocols should use one of the fixed-width encodings directly.
+*/
+#[cfg(not(windows))]
+pub use self::Wide32 as Wide;
 
-    #[inline]
-    fn static_zeroes() -> &'static [Self::Unit] {
-        const ZEROES: &'static [WUnit] = &[WUnit(0), WUnit(0)];
-        ZEROES
-    }
+/**
+Evaluates to `true` if `Wide` is `Wide16`, *i.e.* if `wchar_t` holds UTF-16.
+
+This is a constant expression, so it can be used to select between platform-specific values without `cfg` attributes.  See also `Wide16::WIDTH` and `Wide32::WIDTH`.
+*/
+#[macro_export]
+macro_rules! wide_is_utf16 {
+    () => { ($crate::encoding::Wide::WIDTH == 16) };
+}
+
+/**
+Evaluates to `true` if `Wide` is `Wide32`, *i.e.* if `wchar_t` holds UTF-32.
+
+This is a constant expression, so it can be used to select between platform-specific values without `cfg` attributes.  See also `Wide16::WIDTH` and `Wide32::WIDTH`.
+*/
+#[macro_export]
+macro_rules! wide_is_utf32 {
+    () => { ($crate::encoding::Wide::WIDTH == 32) };
 }
 
 /**
 A string unit encoded in the C runtime wide encoding.
+
+This is an alias for either `W16Unit` or `W32Unit`; see `Wide`.
 */
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
-#[repr(C)]
-pub struct WUnit(pub wchar_t);
+#[cfg(windows)]
+pub use self::W16Unit as WUnit;
 
-naive_unit_impl! { WUnit }
+/**
+A string unit encoded in the C runtime wide encoding.
 
-impl UnitDebug for WUnit {
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        if 0x20 <= self.0 && self.0 <= 0x7e {
-            Display::fmt(&(self.0 as u8 as char), fmt)
-        } else {
-            use ::util::Unsigned;
-            let mut v = self.0.unsigned();
-            for _ in 0..mem::size_of::<wchar_t>() {
-                let b = (v & 0xff) as u8;
-                write!(fmt, "\\x{:02x}", b)?;
-                v >>= 8;
-            }
-            Ok(())
-        }
-    }
-}
+This is an alias for either `W16Unit` or `W32Unit`; see `Wide`.
+*/
+#[cfg(not(windows))]
+pub use self::W32Unit as WUnit;
 
 /**
-Represents the UTF-8 encoding.
+Represents a wide encoding with 16-bit units, interpreted as UTF-16.
+
+This is the C runtime wide encoding on Windows.
 
 Note that this encoding is *not* assumed to be valid; strings in this encoding *may* contain invalid sequences, or decode to invalid code points.
 */
-pub enum Utf8 {}
+pub enum Wide16 {}
 
-impl Encoding for Utf8 {
-    type Unit = Utf8Unit;
-    type FfiUnit = u8;
+impl Wide16 {
+    /**
+    The width of a unit, in bits.  Through the `Wide` alias, this gives the width of `wchar_t`.
+    */
+    pub const WIDTH: u32 = 16;
+}
+
+impl Encoding for Wide16 {
+    type Unit = W16Unit;
+    type FfiUnit = u16;
+    const MAX_UNITS_PER_CHAR: usize = 2;
 
     #[inline]
-    fn debug_prefix() -> &'static str { "Utf8" }
+    fn debug_prefix() -> &'static str {
+        if cfg!(windows) { "W" } else { "W16" }
+    }
 
     #[inline]
     fn static_zeroes() -> &'static [Self::Unit] {
-        const ZEROES: &'static [Utf8Unit] = &[Utf8Unit(0), Utf8Unit(0)];
+        const ZEROES: &'static [W16Unit] = &[W16Unit(0), W16Unit(0)];
         ZEROES
     }
 }
 
+unsafe impl MutableUnits for Wide16 {}
+
 /**
-A string unit encoded in the UTF-8 encoding.
+A string unit encoded in a 16-bit wide encoding.
 */
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
 #[repr(C)]
-pub struct Utf8Unit(pub u8);
+pub struct W16Unit(pub u16);
 
-naive_unit_impl! { Utf8Unit }
-ascii_ext_unit_impl! { Utf8Unit { format: "\\x{:02x}", unit_ty: u8 }}
+naive_unit_impl! { W16Unit: u16 }
+ascii_ext_unit_impl! { W16Unit { format: "\\u{:04x}", unit_ty: u16 }}
 
 /**
-Represents the UTF-16 encoding.
+Represents a wide encoding with 32-bit units, interpreted as UTF-32.
 
-Note that this encoding is *not* assumed to be valid; strings in this encoding *may* contain invalid sequences, or decode to invalid code points.
+This is the C runtime wide encoding on platforms other than Windows.  On those platforms, the FFI unit is `wchar_t`, so that pointers can be passed to and from C directly.
+
+Note that this encoding is *not* assumed to be valid; strings in this encoding *may* contain invalid code points.
 */
-pub enum Utf16 {}
+pub enum Wide32 {}
 
-impl Encoding for Utf16 {
-    type Unit = Utf16Unit;
-    type FfiUnit = u16;
+impl Wide32 {
+    /**
+    The width of a unit, in bits.  Through the `Wide` alias, this gives the width of `wchar_t`.
+    */
+    pub const WIDTH: u32 = 32;
+}
+
+impl Encoding for Wide32 {
+    type Unit = W32Unit;
+    #[cfg(windows)]
+    type FfiUnit = u32;
+    #[cfg(not(windows))]
+    type FfiUnit = wchar_t;
+    const MAX_UNITS_PER_CHAR: usize = 1;
 
     #[inline]
-    fn debug_prefix() -> &'static str { "Utf16" }
+    fn debug_prefix() -> &'static str {
+        if cfg!(windows) { "W32" } else { "W" }
+    }
 
     #[inline]
     fn static_zeroes() -> &'static [Self::Unit] {
-        const ZEROES: &'static [Utf16Unit] = &[Utf16Unit(0), Utf16Unit(0)];
+        const ZEROES: &'static [W32Unit] = &[W32Unit(0), W32Unit(0)];
         ZEROES
     }
 }
 
+unsafe impl MutableUnits for Wide32 {}
+
 /**
-A string unit encoded in the UTF-16 encoding.
+A string unit encoded in a 32-bit wide encoding.
+
+On platforms other than Windows, the field is `wchar_t`, so that this has exactly the layout of the C type, and units can be built with `WUnit(c as wchar_t)` wherever `wchar_t` is 32 bits wide.  Its signedness varies between platforms; `Unit::to_raw` always gives the unsigned value.
 */
+#[cfg(not(windows))]
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
 #[repr(C)]
-pub struct Utf16Unit(pub u16);
-
-naive_unit_impl! { Utf16Unit }
-ascii_ext_unit_impl! { Utf16Unit { format: "\\u{:04x}", unit_ty: u16 }}
+pub struct W32Unit(pub wchar_t);
 
 /**
-Represents the UTF-32 encoding.
+A string unit encoded in a 32-bit wide encoding.
 
-Note that this encoding is *not* assumed to be valid; strings in this encoding *may* contain invalid code points.
+Windows has no 32-bit `wchar_t`, so here the field is `u32`.
 */
-pub enum Utf32 {}
+#[cfg(windows)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct W32Unit(pub u32);
 
-impl Encoding for Utf32 {
-    type Unit = Utf32Unit;
-    type FfiUnit = u32;
+naive_unit_impl! { W32Unit: u32 }
+ascii_ext_unit_impl! { W32Unit { format: "\\U{:08x}", unit_ty: u32 }}
 
-    #[inline]
+/**
+Represents the C runtime `char16_t` encoding, as used by `mbrtoc16` and `c16rtomb`.
+
+C does *not* require this to be UTF-16, or even Unicode; it is only guaranteed to be UTF-16 where the implementation defines `__STDC_UTF_16__`.  As such, this encoding can only be transcoded to and from `MultiByte`, using the current C runtime locale.  Where `__STDC_UTF_16__` is known to hold, the `stdc-utf` feature allows strings in this encoding to be reinterpreted as `Utf16` strings; see `SeStr::as_utf16`.
+*/
+pub enum Char16 {}
+
+impl Encoding for Char16 {
+    type Unit = C16Unit;
+    type FfiUnit = u16;
+    const MAX_UNITS_PER_CHAR: usize = 2;
+
+    #[inline]
+    fn debug_prefix() -> &'static str { "C16" }
+
+    #[inline]
+    fn static_zeroes() -> &'static [Self::Unit] {
+        const ZEROES: &'static [C16Unit] = &[C16Unit(0), C16Unit(0)];
+        ZEROES
+    }
+}
+
+// Only fixed where the C runtime promises UTF-16; see above.
+#[cfg(feature="stdc-utf")]
+impl LocaleIndependent for Char16 {}
+unsafe impl MutableUnits for Char16 {}
+
+/**
+A string unit encoded in the C runtime `char16_t` encoding.
+*/
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct C16Unit(pub u16);
+
+naive_unit_impl! { C16Unit: u16 }
+ascii_ext_unit_impl! { C16Unit { format: "\\u{:04x}", unit_ty: u16 }}
+
+/**
+Represents the C runtime `char32_t` encoding, as used by `mbrtoc32` and `c32rtomb`.
+
+C does *not* require this to be UTF-32, or even Unicode; it is only guaranteed to be UTF-32 where the implementation defines `__STDC_UTF_32__`.  As such, this encoding can only be transcoded to and from `MultiByte`, using the current C runtime locale.  Where `__STDC_UTF_32__` is known to hold, the `stdc-utf` feature allows strings in this encoding to be reinterpreted as `Utf32` strings; see `SeStr::as_utf32`.
+*/
+pub enum Char32 {}
+
+impl Encoding for Char32 {
+    type Unit = C32Unit;
+    type FfiUnit = u32;
+    const MAX_UNITS_PER_CHAR: usize = 1;
+
+    #[inline]
+    fn debug_prefix() -> &'static str { "C32" }
+
+    #[inline]
+    fn static_zeroes() -> &'static [Self::Unit] {
+        const ZEROES: &'static [C32Unit] = &[C32Unit(0), C32Unit(0)];
+        ZEROES
+    }
+}
+
+// Only fixed where the C runtime promises UTF-32; see above.
+#[cfg(feature="stdc-utf")]
+impl LocaleIndependent for Char32 {}
+unsafe impl MutableUnits for Char32 {}
+
+/**
+A string unit encoded in the C runtime `char32_t` encoding.
+*/
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct C32Unit(pub u32);
+
+naive_unit_impl! { C32Unit: u32 }
+ascii_ext_unit_impl! { C32Unit { format: "\\U{:08x}", unit_ty: u32 }}
+
+/**
+Represents the Windows "Unicode" encoding, as used by the `W`-suffixed Windows API functions.
+
+This is equivalent to `Utf16`, assuming non-pathological compiler settings.  On Windows, strings in this encoding can be reinterpreted as `Wide` strings (and vice versa) without copying; see `SeStr::as_win_unicode` and `SeStr::as_wide`.
+
+Note that this encoding is *not* assumed to be valid; strings in this encoding *may* contain invalid sequences, or decode to invalid code points.
+*/
+pub enum WinUnicode {}
+
+impl Encoding for WinUnicode {
+    type Unit = WwUnit;
+    type FfiUnit = u16;
+    const MAX_UNITS_PER_CHAR: usize = 2;
+
+    #[inline]
+    fn debug_prefix() -> &'static str { "Ww" }
+
+    #[inline]
+    fn static_zeroes() -> &'static [Self::Unit] {
+        const ZEROES: &'static [WwUnit] = &[WwUnit(0), WwUnit(0)];
+        ZEROES
+    }
+}
+
+impl LocaleIndependent for WinUnicode {}
+unsafe impl MutableUnits for WinUnicode {}
+
+/**
+A string unit encoded in the Windows "Unicode" encoding.
+*/
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct WwUnit(pub u16);
+
+naive_unit_impl! { WwUnit: u16 }
+ascii_ext_unit_impl! { WwUnit { format: "\\u{:04x}", unit_ty: u16 }}
+
+/**
+Represents the Windows ANSI code page, as returned by `GetACP`.
+
+This is the encoding used by the `A`-suffixed Windows API functions, such as `GetWindowTextA`.  It is distinct from the C runtime `MultiByte` encoding, which follows the current C locale instead.  The ANSI code page may be a double-byte code page, in which case a single character may span two units.
+
+Note that this encoding is *not* assumed to be valid; strings in this encoding *may* contain invalid sequences.
+*/
+#[cfg(windows)]
+pub enum WinAnsi {}
+
+#[cfg(windows)]
+impl Encoding for WinAnsi {
+    type Unit = AnsiUnit;
+    type FfiUnit = c_char;
+    // The ANSI code page can be set to UTF-8.
+    const MAX_UNITS_PER_CHAR: usize = 4;
+
+    #[inline]
+    fn debug_prefix() -> &'static str { "Wa" }
+
+    #[inline]
+    fn static_zeroes() -> &'static [Self::Unit] {
+        const ZEROES: &'static [AnsiUnit] = &[AnsiUnit(0), AnsiUnit(0)];
+        ZEROES
+    }
+}
+
+#[cfg(windows)]
+unsafe impl ByteEncoding for WinAnsi {}
+#[cfg(windows)]
+impl LocaleIndependent for WinAnsi {}
+#[cfg(windows)]
+unsafe impl MutableUnits for WinAnsi {}
+
+/**
+A string unit encoded in the Windows ANSI code page.
+*/
+#[cfg(windows)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct AnsiUnit(pub u8);
+
+#[cfg(windows)]
+naive_unit_impl! { AnsiUnit: u8 }
+#[cfg(windows)]
+ascii_ext_unit_impl! { AnsiUnit { format: "\\x{:02x}", unit_ty: u8 }}
+
+/**
+Represents the Windows OEM code page, as returned by `GetOEMCP`.
+
+This is the encoding used by console applications for text passed through pipes and files, such as the output of `cmd.exe` built-ins.  It is distinct from both the ANSI code page and the C runtime `MultiByte` encoding.  The OEM code page is fixed for the lifetime of the system, but may be a double-byte code page, in which case a single character may span two units.
+
+Note that this encoding is *not* assumed to be valid; strings in this encoding *may* contain invalid sequences.
+*/
+#[cfg(windows)]
+pub enum WinOem {}
+
+#[cfg(windows)]
+impl Encoding for WinOem {
+    type Unit = OemUnit;
+    type FfiUnit = c_char;
+    // The OEM code page can be set to UTF-8.
+    const MAX_UNITS_PER_CHAR: usize = 4;
+
+    #[inline]
+    fn debug_prefix() -> &'static str { "Wo" }
+
+    #[inline]
+    fn static_zeroes() -> &'static [Self::Unit] {
+        const ZEROES: &'static [OemUnit] = &[OemUnit(0), OemUnit(0)];
+        ZEROES
+    }
+}
+
+#[cfg(windows)]
+unsafe impl ByteEncoding for WinOem {}
+#[cfg(windows)]
+impl LocaleIndependent for WinOem {}
+#[cfg(windows)]
+unsafe impl MutableUnits for WinOem {}
+
+/**
+A string unit encoded in the Windows OEM code page.
+*/
+#[cfg(windows)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct OemUnit(pub u8);
+
+#[cfg(windows)]
+naive_unit_impl! { OemUnit: u8 }
+#[cfg(windows)]
+ascii_ext_unit_impl! { OemUnit { format: "\\x{:02x}", unit_ty: u8 }}
+
+/**
+Represents the "modified" UTF-8 encoding used by the Java Native Interface.
+
+This differs from UTF-8 in two ways: U+0000 is encoded as the overlong sequence `C0 80` (so that encoded strings never contain zero units), and supplementary characters are encoded as a pair of three-unit surrogates (as in CESU-8).
+
+Note that this encoding is *not* assumed to be valid; strings in this encoding *may* contain invalid sequences, or decode to invalid code points.
+*/
+pub enum JniMtf8 {}
+
+impl Encoding for JniMtf8 {
+    type Unit = Mtf8Unit;
+    type FfiUnit = c_char;
+    const MAX_UNITS_PER_CHAR: usize = 6;
+
+    #[inline]
+    fn debug_prefix() -> &'static str { "Jni" }
+
+    #[inline]
+    fn static_zeroes() -> &'static [Self::Unit] {
+        const ZEROES: &'static [Mtf8Unit] = &[Mtf8Unit(0), Mtf8Unit(0)];
+        ZEROES
+    }
+}
+
+impl AsciiCompatible for JniMtf8 {}
+unsafe impl ByteEncoding for JniMtf8 {}
+impl LocaleIndependent for JniMtf8 {}
+unsafe impl MutableUnits for JniMtf8 {}
+
+/**
+A string unit encoded in the JNI "modified" UTF-8 encoding.
+*/
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Mtf8Unit(pub u8);
+
+naive_unit_impl! { Mtf8Unit: u8 }
+ascii_ext_unit_impl! { Mtf8Unit { format: "\\x{:02x}", unit_ty: u8 }}
+
+/**
+Represents raw 8-bit data, with no particular interpretation.
+
+This is primarily useful as a starting point for reinterpreting byte buffers as some other encoding; see `SeStr::as_utf16le` and `SeStr::as_utf16be`.
+*/
+pub enum Raw8 {}
+
+impl Encoding for Raw8 {
+    type Unit = Raw8Unit;
+    type FfiUnit = u8;
+    const MAX_UNITS_PER_CHAR: usize = 1;
+
+    #[inline]
+    fn debug_prefix() -> &'static str { "Raw8" }
+
+    #[inline]
+    fn static_zeroes() -> &'static [Self::Unit] {
+        const ZEROES: &'static [Raw8Unit] = &[Raw8Unit(0), Raw8Unit(0)];
+        ZEROES
+    }
+}
+
+impl AsciiCompatible for Raw8 {}
+unsafe impl ByteEncoding for Raw8 {}
+impl LocaleIndependent for Raw8 {}
+unsafe impl MutableUnits for Raw8 {}
+
+/**
+A unit of raw 8-bit data.
+*/
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Raw8Unit(pub u8);
+
+naive_unit_impl! { Raw8Unit: u8 }
+
+impl UnitDebug for Raw8Unit {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "\\x{:02x}", self.0)
+    }
+}
+
+/**
+Represents the UTF-8 encoding.
+
+Note that this encoding is *not* assumed to be valid; strings in this encoding *may* contain invalid sequences, or decode to invalid code points.
+*/
+pub enum Utf8 {}
+
+impl Encoding for Utf8 {
+    type Unit = Utf8Unit;
+    type FfiUnit = u8;
+    const MAX_UNITS_PER_CHAR: usize = 4;
+
+    #[inline]
+    fn debug_prefix() -> &'static str { "Utf8" }
+
+    #[inline]
+    fn static_zeroes() -> &'static [Self::Unit] {
+        const ZEROES: &'static [Utf8Unit] = &[Utf8Unit(0), Utf8Unit(0)];
+        ZEROES
+    }
+}
+
+impl AsciiCompatible for Utf8 {}
+unsafe impl ByteEncoding for Utf8 {}
+impl LocaleIndependent for Utf8 {}
+unsafe impl MutableUnits for Utf8 {}
+
+/**
+A string unit encoded in the UTF-8 encoding.
+*/
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Utf8Unit(pub u8);
+
+naive_unit_impl! { Utf8Unit: u8 }
+ascii_ext_unit_impl! { Utf8Unit { format: "\\x{:02x}", unit_ty: u8 }}
+
+/**
+Represents the WTF-8 encoding.
+
+This is UTF-8, extended so that it can also hold unpaired surrogates, each encoded as its own three-unit sequence.  As a result, *any* sequence of UTF-16 units, valid or not, can be transcoded into WTF-8 and back without loss.  This is what the standard library uses to store Windows `OsString`s.
+
+Surrogates which *do* form a pair are always encoded as the supplementary character they represent; a string containing an encoded high surrogate immediately followed by an encoded low surrogate is invalid.
+
+Note that this encoding is *not* assumed to be valid; strings in this encoding *may* contain invalid sequences.
+*/
+pub enum Wtf8 {}
+
+impl Encoding for Wtf8 {
+    type Unit = Wtf8Unit;
+    type FfiUnit = u8;
+    const MAX_UNITS_PER_CHAR: usize = 4;
+
+    #[inline]
+    fn debug_prefix() -> &'static str { "Wtf8" }
+
+    #[inline]
+    fn static_zeroes() -> &'static [Self::Unit] {
+        const ZEROES: &'static [Wtf8Unit] = &[Wtf8Unit(0), Wtf8Unit(0)];
+        ZEROES
+    }
+}
+
+impl AsciiCompatible for Wtf8 {}
+unsafe impl ByteEncoding for Wtf8 {}
+impl LocaleIndependent for Wtf8 {}
+unsafe impl MutableUnits for Wtf8 {}
+
+/**
+A string unit encoded in the WTF-8 encoding.
+*/
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Wtf8Unit(pub u8);
+
+naive_unit_impl! { Wtf8Unit: u8 }
+ascii_ext_unit_impl! { Wtf8Unit { format: "\\x{:02x}", unit_ty: u8 }}
+
+/**
+Represents the UTF-8 encoding, where the contents are guaranteed to be valid.
+
+Unlike `Utf8`, strings in this encoding *cannot* contain invalid sequences, making them equivalent to Rust's `str`.  Because of this, `CheckedUtf8Unit` cannot be constructed from an arbitrary byte.  Strings in this encoding can only be created by validating a `Utf8` string (see `SeStr::validate_utf8`), or by transcoding from `CheckedUnicode`.
+
+The one exception is re-borrowing a foreign pointer (*e.g.* with `SeStr::from_ptr`); in that case, ensuring that the foreign string is valid UTF-8 is part of the caller's safety obligations.
+
+Units copied out of a valid string can still be sliced or reassembled without regard to character boundaries, so nothing relies on the contents being valid for memory safety; `SeStr::as_str` checks them before handing out a `str`.  Unit-level mutation, such as `SeStr::as_units_mut`, is not available at all; see `MutableUnits`.
+*/
+pub enum CheckedUtf8 {}
+
+impl Encoding for CheckedUtf8 {
+    type Unit = CheckedUtf8Unit;
+    type FfiUnit = u8;
+    const MAX_UNITS_PER_CHAR: usize = 4;
+
+    #[inline]
+    fn debug_prefix() -> &'static str { "CUtf8" }
+
+    #[inline]
+    fn static_zeroes() -> &'static [Self::Unit] {
+        const ZEROES: &'static [CheckedUtf8Unit] = &[CheckedUtf8Unit(0), CheckedUtf8Unit(0)];
+        ZEROES
+    }
+}
+
+impl LocaleIndependent for CheckedUtf8 {}
+
+/**
+A string unit encoded in the UTF-8 encoding, as part of a string known to be valid.
+
+See `CheckedUtf8`.
+*/
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct CheckedUtf8Unit(u8);
+
+impl CheckedUtf8Unit {
+    /**
+    Returns the value of this unit.
+    */
+    #[inline]
+    pub fn get(self) -> u8 {
+        self.0
+    }
+}
+
+naive_unit_impl! { CheckedUtf8Unit: u8, from_raw: |_| None }
+ascii_ext_unit_impl! { CheckedUtf8Unit { format: "\\x{:02x}", unit_ty: u8 }}
+
+/**
+Represents the UTF-16 encoding.
+
+Note that this encoding is *not* assumed to be valid; strings in this encoding *may* contain invalid sequences, or decode to invalid code points.
+*/
+pub enum Utf16 {}
+
+impl Encoding for Utf16 {
+    type Unit = Utf16Unit;
+    type FfiUnit = u16;
+    const MAX_UNITS_PER_CHAR: usize = 2;
+
+    #[inline]
+    fn debug_prefix() -> &'static str { "Utf16" }
+
+    #[inline]
+    fn static_zeroes() -> &'static [Self::Unit] {
+        const ZEROES: &'static [Utf16Unit] = &[Utf16Unit(0), Utf16Unit(0)];
+        ZEROES
+    }
+}
+
+impl LocaleIndependent for Utf16 {}
+unsafe impl MutableUnits for Utf16 {}
+
+/**
+A string unit encoded in the UTF-16 encoding.
+*/
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Utf16Unit(pub u16);
+
+naive_unit_impl! { Utf16Unit: u16 }
+ascii_ext_unit_impl! { Utf16Unit { format: "\\u{:04x}", unit_ty: u16 }}
+
+/**
+Represents the little-endian UTF-16 encoding.
+
+Units are stored in little-endian byte order, regardless of the byte order of the host.  Use `Utf16LeUnit::new` and `Utf16LeUnit::get` to convert to and from logical code unit values.
+
+Note that this encoding is *not* assumed to be valid; strings in this encoding *may* contain invalid sequences, or decode to invalid code points.
+*/
+pub enum Utf16Le {}
+
+impl Encoding for Utf16Le {
+    type Unit = Utf16LeUnit;
+    type FfiUnit = u16;
+    const MAX_UNITS_PER_CHAR: usize = 2;
+
+    #[inline]
+    fn debug_prefix() -> &'static str { "Utf16Le" }
+
+    #[inline]
+    fn static_zeroes() -> &'static [Self::Unit] {
+        const ZEROES: &'static [Utf16LeUnit] = &[Utf16LeUnit(0), Utf16LeUnit(0)];
+        ZEROES
+    }
+}
+
+impl LocaleIndependent for Utf16Le {}
+unsafe impl MutableUnits for Utf16Le {}
+
+/**
+A string unit encoded in the little-endian UTF-16 encoding.
+
+The contained value is in little-endian byte order.
+*/
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Utf16LeUnit(pub u16);
+
+byte_order_unit_impl! { Utf16LeUnit { int: u16, to: to_le, from: from_le, format: "\\u{:04x}" }}
+
+/**
+Represents the big-endian UTF-16 encoding.
+
+Units are stored in big-endian byte order, regardless of the byte order of the host.  Use `Utf16BeUnit::new` and `Utf16BeUnit::get` to convert to and from logical code unit values.
+
+Note that this encoding is *not* assumed to be valid; strings in this encoding *may* contain invalid sequences, or decode to invalid code points.
+*/
+pub enum Utf16Be {}
+
+impl Encoding for Utf16Be {
+    type Unit = Utf16BeUnit;
+    type FfiUnit = u16;
+    const MAX_UNITS_PER_CHAR: usize = 2;
+
+    #[inline]
+    fn debug_prefix() -> &'static str { "Utf16Be" }
+
+    #[inline]
+    fn static_zeroes() -> &'static [Self::Unit] {
+        const ZEROES: &'static [Utf16BeUnit] = &[Utf16BeUnit(0), Utf16BeUnit(0)];
+        ZEROES
+    }
+}
+
+impl LocaleIndependent for Utf16Be {}
+unsafe impl MutableUnits for Utf16Be {}
+
+/**
+A string unit encoded in the big-endian UTF-16 encoding.
+
+The contained value is in big-endian byte order.
+*/
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Utf16BeUnit(pub u16);
+
+byte_order_unit_impl! { Utf16BeUnit { int: u16, to: to_be, from: from_be, format: "\\u{:04x}" }}
+
+/**
+Represents the UTF-32 encoding.
+
+Note that this encoding is *not* assumed to be valid; strings in this encoding *may* contain invalid code points.
+*/
+pub enum Utf32 {}
+
+impl Encoding for Utf32 {
+    type Unit = Utf32Unit;
+    type FfiUnit = u32;
+    const MAX_UNITS_PER_CHAR: usize = 1;
+
+    #[inline]
     fn debug_prefix() -> &'static str { "Utf32" }
 
     #[inline]
@@ -391,6 +1277,9 @@ impl Encoding for Utf32 {
     }
 }
 
+impl LocaleIndependent for Utf32 {}
+unsafe impl MutableUnits for Utf32 {}
+
 /**
 A string unit encoded in the UTF-32 encoding.
 */
@@ -398,9 +1287,85 @@ A string unit encoded in the UTF-32 encoding.
 #[repr(C)]
 pub struct Utf32Unit(pub u32);
 
-naive_unit_impl! { Utf32Unit }
+naive_unit_impl! { Utf32Unit: u32 }
 ascii_ext_unit_impl! { Utf32Unit { format: "\\U{:08x}", unit_ty: u32 }}
 
+/**
+Represents the little-endian UTF-32 encoding.
+
+Units are stored in little-endian byte order, regardless of the byte order of the host.  Use `Utf32LeUnit::new` and `Utf32LeUnit::get` to convert to and from logical code unit values.
+
+Note that this encoding is *not* assumed to be valid; strings in this encoding *may* contain invalid code points.
+*/
+pub enum Utf32Le {}
+
+impl Encoding for Utf32Le {
+    type Unit = Utf32LeUnit;
+    type FfiUnit = u32;
+    const MAX_UNITS_PER_CHAR: usize = 1;
+
+    #[inline]
+    fn debug_prefix() -> &'static str { "Utf32Le" }
+
+    #[inline]
+    fn static_zeroes() -> &'static [Self::Unit] {
+        const ZEROES: &'static [Utf32LeUnit] = &[Utf32LeUnit(0), Utf32LeUnit(0)];
+        ZEROES
+    }
+}
+
+impl LocaleIndependent for Utf32Le {}
+unsafe impl MutableUnits for Utf32Le {}
+
+/**
+A string unit encoded in the little-endian UTF-32 encoding.
+
+The contained value is in little-endian byte order.
+*/
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Utf32LeUnit(pub u32);
+
+byte_order_unit_impl! { Utf32LeUnit { int: u32, to: to_le, from: from_le, format: "\\U{:08x}" }}
+
+/**
+Represents the big-endian UTF-32 encoding.
+
+Units are stored in big-endian byte order, regardless of the byte order of the host.  Use `Utf32BeUnit::new` and `Utf32BeUnit::get` to convert to and from logical code unit values.
+
+Note that this encoding is *not* assumed to be valid; strings in this encoding *may* contain invalid code points.
+*/
+pub enum Utf32Be {}
+
+impl Encoding for Utf32Be {
+    type Unit = Utf32BeUnit;
+    type FfiUnit = u32;
+    const MAX_UNITS_PER_CHAR: usize = 1;
+
+    #[inline]
+    fn debug_prefix() -> &'static str { "Utf32Be" }
+
+    #[inline]
+    fn static_zeroes() -> &'static [Self::Unit] {
+        const ZEROES: &'static [Utf32BeUnit] = &[Utf32BeUnit(0), Utf32BeUnit(0)];
+        ZEROES
+    }
+}
+
+impl LocaleIndependent for Utf32Be {}
+unsafe impl MutableUnits for Utf32Be {}
+
+/**
+A string unit encoded in the big-endian UTF-32 encoding.
+
+The contained value is in big-endian byte order.
+*/
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Utf32BeUnit(pub u32);
+
+byte_order_unit_impl! { Utf32BeUnit { int: u32, to: to_be, from: from_be, format: "\\U{:08x}" }}
+
 /**
 Represents the UTF-32 encoding.
 
@@ -411,6 +1376,7 @@ pub enum CheckedUnicode {}
 impl Encoding for CheckedUnicode {
     type Unit = char;
     type FfiUnit = char;
+    const MAX_UNITS_PER_CHAR: usize = 1;
 
     #[inline]
     fn debug_prefix() -> &'static str { "U" }
@@ -422,6 +1388,9 @@ impl Encoding for CheckedUnicode {
     }
 }
 
+impl LocaleIndependent for CheckedUnicode {}
+unsafe impl MutableUnits for CheckedUnicode {}
+
 impl Unit for char {
     fn zero() -> Self {
         '\u{0}'
@@ -430,6 +1399,16 @@ impl Unit for char {
     fn is_zero(&self) -> bool {
         *self == '\u{0}'
     }
+
+    const BITS: u32 = 21;
+
+    fn to_raw(&self) -> u32 {
+        *self as u32
+    }
+
+    fn from_raw(v: u32) -> Option<Self> {
+        char::from_u32(v)
+    }
 }
 
 impl UnitDebug for char {
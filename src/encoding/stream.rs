@@ -0,0 +1,319 @@
+/*!
+Transcoding text which arrives in pieces.
+
+The `TranscodeTo` iterators assume they can see the whole string at once: a character split across the end of their input is an error.  A `StreamTranscoder` instead holds back an incomplete character at the end of each chunk, along with any conversion state, until the next chunk arrives.
+*/
+use std::cmp;
+use std::fmt;
+use std::mem;
+use libc::c_char;
+use encoding::{Encoding, CheckedUnicode, DecodeError, DecodeUnits, EncodeChar, EncodeError, MultiByte, Wide, WUnit};
+use encoding::conv::ErrorOffset;
+use encoding::encode::encode_onto;
+use ffi::{MbConvResult, mbrtowc, mbstate_t};
+
+/**
+Implemented by encodings which can be transcoded into `Dst` a chunk at a time.
+
+Any encoding which implements `DecodeUnits` can be streamed into any encoding which implements `EncodeChar`.  `MultiByte` can be streamed into `Wide` and `CheckedUnicode`.
+*/
+pub trait StreamTranscode<Dst>: Encoding where Dst: Encoding {
+    /**
+    The conversion state carried from one chunk to the next.
+    */
+    type State;
+
+    /**
+    Returns the state at the start of a stream.
+    */
+    fn initial_state() -> Self::State;
+
+    /**
+    Transcodes as many complete characters from the start of `units` as possible, appending them to `out`.
+
+    Returns the number of units consumed.  This is less than the length of `units` only if they end part-way through a character; the remaining units will be passed again, with more after them, on the next call.
+
+    # Failure
+
+    This method will fail if `units` contains an invalid sequence, with an offset relative to the start of `units`.  Anything transcoded before the error may or may not have been appended to `out`.
+    */
+    fn transcode_chunk(state: &mut Self::State, units: &[Self::Unit], out: &mut Vec<Dst::Unit>) -> Result<usize, TranscodeError>;
+}
+
+/**
+Transcodes a string from `Src` to `Dst` as it arrives, one chunk at a time.
+
+Characters may be split across chunks in any way.  Units at the end of a chunk which do not form a complete character are kept, along with the conversion state, until the next call to `feed`.
+
+```
+# use strffi::encoding::{Utf8, Utf8Unit, Utf16};
+# use strffi::encoding::stream::StreamTranscoder;
+let mut tc = StreamTranscoder::<Utf8, Utf16>::new();
+let mut out = vec![];
+tc.feed(&[Utf8Unit(b'g'), Utf8Unit(0xc2)], &mut out).unwrap();
+tc.feed(&[Utf8Unit(0xaa)], &mut out).unwrap();
+tc.finish(&mut out).unwrap();
+assert_eq!(out.len(), 2);
+```
+*/
+pub struct StreamTranscoder<Src, Dst> where Src: StreamTranscode<Dst>, Dst: Encoding {
+    state: Src::State,
+    pending: Vec<Src::Unit>,
+    at: usize,
+}
+
+impl<Src, Dst> StreamTranscoder<Src, Dst> where Src: StreamTranscode<Dst>, Dst: Encoding {
+    /**
+    Creates a transcoder at the start of a new stream.
+    */
+    pub fn new() -> Self {
+        StreamTranscoder {
+            state: Src::initial_state(),
+            pending: vec![],
+            at: 0,
+        }
+    }
+
+    /**
+    Transcodes the next chunk of the stream, appending the result to `out`.
+
+    Error offsets are relative to the start of the stream, not the start of `units`.
+
+    # Failure
+
+    This method will fail if the stream contains an invalid sequence.  After an error, the transcoder starts again as if at the beginning of a new stream.
+    */
+    pub fn feed(&mut self, mut units: &[Src::Unit], out: &mut Vec<Dst::Unit>) -> Result<(), TranscodeError> {
+        if !self.pending.is_empty() {
+            // Finish the held-back character first, borrowing no more of `units` than it could possibly need.
+            let held = self.pending.len();
+            let take = cmp::min(units.len(), Src::MAX_UNITS_PER_CHAR);
+            let mut pending = mem::replace(&mut self.pending, vec![]);
+            pending.extend_from_slice(&units[..take]);
+            let consumed = self.transcode(&pending, out)?;
+            if consumed == 0 {
+                // Still incomplete, so everything was taken.
+                self.pending = pending;
+                return Ok(());
+            }
+            units = &units[consumed - held..];
+            pending.clear();
+            self.pending = pending;
+        }
+
+        let consumed = self.transcode(units, out)?;
+        self.pending.extend_from_slice(&units[consumed..]);
+        Ok(())
+    }
+
+    /**
+    Ends the stream.
+
+    Afterwards, the transcoder can be used for a new stream.
+
+    # Failure
+
+    This method will fail if the stream ended part-way through a character.
+    */
+    pub fn finish(&mut self, out: &mut Vec<Dst::Unit>) -> Result<(), TranscodeError> {
+        // None of the supported conversions need to write anything at the end, but stateful targets would do so here.
+        let _ = out;
+        let incomplete = !self.pending.is_empty();
+        self.reset();
+        if incomplete {
+            Err(TranscodeError::Incomplete)
+        } else {
+            Ok(())
+        }
+    }
+
+    /**
+    Returns the number of source units held back, waiting for the rest of their character.
+    */
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /**
+    Transcodes the complete characters at the start of `units`, returning how many units that took.  On failure, the transcoder is reset.
+    */
+    fn transcode(&mut self, units: &[Src::Unit], out: &mut Vec<Dst::Unit>) -> Result<usize, TranscodeError> {
+        let consumed = match Src::transcode_chunk(&mut self.state, units, out) {
+            Ok(consumed) => consumed,
+            Err(err) => {
+                let at = self.at;
+                self.reset();
+                return Err(err.map_offset(|off| at + off));
+            },
+        };
+
+        let rest = units.len() - consumed;
+        if rest >= Src::MAX_UNITS_PER_CHAR {
+            let at = self.at + consumed;
+            self.reset();
+            return Err(TranscodeError::OutOfBufferAt(at));
+        }
+
+        self.at += consumed;
+        Ok(consumed)
+    }
+
+    fn reset(&mut self) {
+        self.state = Src::initial_state();
+        self.pending.clear();
+        self.at = 0;
+    }
+}
+
+impl<Src, Dst> Default for StreamTranscoder<Src, Dst> where Src: StreamTranscode<Dst>, Dst: Encoding {
+    fn default() -> Self {
+        StreamTranscoder::new()
+    }
+}
+
+impl<Src, Dst> StreamTranscode<Dst> for Src where Src: DecodeUnits, Dst: EncodeChar {
+    type State = ();
+
+    fn initial_state() -> () {}
+
+    fn transcode_chunk(_: &mut (), units: &[Src::Unit], out: &mut Vec<Dst::Unit>) -> Result<usize, TranscodeError> {
+        let mut at = 0;
+        loop {
+            let start = at;
+            match Src::decode_next(units, &mut at) {
+                None => return Ok(at),
+                Some(Ok(c)) => match encode_onto::<Dst>(c, out) {
+                    Ok(()) => (),
+                    Err(EncodeError::Unrepresentable(_)) => return Err(TranscodeError::InvalidAt(start)),
+                    Err(EncodeError::BufferTooSmall(_)) => return Err(TranscodeError::OutOfBufferAt(start)),
+                },
+                Some(Err(DecodeError::InvalidAt(at))) => return Err(TranscodeError::InvalidAt(at)),
+                Some(Err(DecodeError::IncompleteAt(at))) => return Ok(at),
+            }
+        }
+    }
+}
+
+impl StreamTranscode<Wide> for MultiByte {
+    type State = mbstate_t;
+
+    fn initial_state() -> mbstate_t {
+        unsafe { mem::zeroed() }
+    }
+
+    fn transcode_chunk(state: &mut mbstate_t, units: &[Self::Unit], out: &mut Vec<WUnit>) -> Result<usize, TranscodeError> {
+        mb_chunk(state, units, |_, wc| {
+            out.push(WUnit(wc as _));
+            Ok(())
+        })
+    }
+}
+
+impl StreamTranscode<CheckedUnicode> for MultiByte {
+    type State = mbstate_t;
+
+    fn initial_state() -> mbstate_t {
+        unsafe { mem::zeroed() }
+    }
+
+    fn transcode_chunk(state: &mut mbstate_t, units: &[Self::Unit], out: &mut Vec<char>) -> Result<usize, TranscodeError> {
+        mb_chunk(state, units, |at, wc| {
+            // `mbrtowc` produces one wide unit per call, so a surrogate here can never be completed.
+            let c = ::std::char::from_u32(wc as u32).ok_or(TranscodeError::InvalidAt(at))?;
+            out.push(c);
+            Ok(())
+        })
+    }
+}
+
+/**
+Converts the complete characters at the start of `units` with `mbrtowc`, passing each wide character and its offset to `f`.
+*/
+fn mb_chunk<F>(state: &mut mbstate_t, units: &[<MultiByte as Encoding>::Unit], mut f: F) -> Result<usize, TranscodeError>
+where F: FnMut(usize, ::libc::wchar_t) -> Result<(), TranscodeError> {
+    let mut at = 0;
+    while at < units.len() {
+        let len = cmp::min(units.len() - at, MultiByte::MAX_UNITS_PER_CHAR);
+        let mut wc = 0;
+        // `mbrtowc` updates the state even for an incomplete sequence, so it is only kept once a character is finished.
+        let mut state_new = *state;
+        match unsafe {
+            MbConvResult::from_raw(mbrtowc(&mut wc,
+                units[at..].as_ptr() as *const c_char,
+                len,
+                &mut state_new))
+        } {
+            MbConvResult::Illegal => return Err(TranscodeError::InvalidAt(at)),
+            MbConvResult::Incomplete => return Ok(at),
+            MbConvResult::StoredOnly => panic!("mbrtowc returned an invalid result"),
+            MbConvResult::Consumed(n) => {
+                f(at, wc)?;
+                // A zero character is reported as consuming nothing; in practice, it is always a single unit.
+                at += cmp::max(n, 1);
+                *state = state_new;
+            },
+        }
+    }
+    Ok(at)
+}
+
+/**
+The error produced by a `StreamTranscoder`.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TranscodeError {
+    /**
+    The unit at the given offset in the stream does not begin a valid sequence.
+    */
+    InvalidAt(usize),
+
+    /**
+    The stream ended part-way through a character.
+    */
+    Incomplete,
+
+    /**
+    The character at the given offset in the stream is longer than any character in the source encoding, or than its encoding says any character in the target could be.
+    */
+    OutOfBufferAt(usize),
+}
+
+impl TranscodeError {
+    fn map_offset<F>(self, f: F) -> Self where F: FnOnce(usize) -> usize {
+        match self {
+            TranscodeError::InvalidAt(at) => TranscodeError::InvalidAt(f(at)),
+            TranscodeError::Incomplete => TranscodeError::Incomplete,
+            TranscodeError::OutOfBufferAt(at) => TranscodeError::OutOfBufferAt(f(at)),
+        }
+    }
+}
+
+impl fmt::Display for TranscodeError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TranscodeError::InvalidAt(at) => write!(fmt, "invalid unit at offset {}", at),
+            TranscodeError::Incomplete => write!(fmt, "incomplete unit"),
+            TranscodeError::OutOfBufferAt(at) => write!(fmt, "character too large to transcode at offset {}", at),
+        }
+    }
+}
+
+impl ::std::error::Error for TranscodeError {
+    fn description(&self) -> &str {
+        match *self {
+            TranscodeError::InvalidAt(_) => "invalid unit",
+            TranscodeError::Incomplete => "incomplete unit",
+            TranscodeError::OutOfBufferAt(_) => "character too large to transcode",
+        }
+    }
+}
+
+impl ErrorOffset for TranscodeError {
+    fn offset(&self) -> Option<usize> {
+        match *self {
+            TranscodeError::InvalidAt(at) => Some(at),
+            TranscodeError::Incomplete => None,
+            TranscodeError::OutOfBufferAt(at) => Some(at),
+        }
+    }
+}
@@ -0,0 +1,204 @@
+/*!
+Checking that strings are well-formed in their declared encoding, without transcoding them.
+*/
+use std::fmt;
+use std::mem;
+use std::str;
+use libc::{c_char, wchar_t};
+use encoding::{Encoding, Unit, CheckedUnicode, CheckedUtf8, MultiByte, Raw8, Utf8, Utf16, Utf16Le, Utf16Be,
+    Utf32, Utf32Le, Utf32Be, Wide16, Wide32, WinUnicode};
+use encoding::conv::utf16::{next_utf16_code_point, Utf16ToUniError};
+use encoding::conv::utf32::{next_utf32_code_point, Utf32ToUniError};
+use ffi::{MbConvResult, mbrtowc, mbstate_t};
+
+/**
+Implemented by encodings whose strings can be checked for well-formedness.
+
+See `SeStr::validate_encoding`.
+*/
+pub trait ValidateEncoding: Encoding {
+    /**
+    Checks that `units` is a well-formed sequence in this encoding.
+
+    # Failure
+
+    On failure, the error describes the first problem found; see `EncodingError`.
+    */
+    fn validate(units: &[Self::Unit]) -> Result<(), EncodingError>;
+}
+
+/**
+The error produced when a string is not well-formed in its declared encoding.
+
+This mirrors `std::str::Utf8Error`.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EncodingError {
+    valid_up_to: usize,
+    error_len: Option<usize>,
+}
+
+impl EncodingError {
+    /**
+    Constructs a new error.
+    */
+    pub fn new(valid_up_to: usize, error_len: Option<usize>) -> Self {
+        EncodingError {
+            valid_up_to: valid_up_to,
+            error_len: error_len,
+        }
+    }
+
+    /**
+    Returns the number of units, from the start of the string, which are well-formed.
+    */
+    pub fn valid_up_to(&self) -> usize {
+        self.valid_up_to
+    }
+
+    /**
+    Returns the length, in units, of the invalid sequence which follows the well-formed prefix.
+
+    Returns `None` if the string ended in the middle of an otherwise valid sequence.
+    */
+    pub fn error_len(&self) -> Option<usize> {
+        self.error_len
+    }
+}
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self.error_len {
+            Some(len) => write!(fmt, "invalid sequence of {} units at offset {}", len, self.valid_up_to),
+            None => write!(fmt, "incomplete sequence at offset {}", self.valid_up_to),
+        }
+    }
+}
+
+impl ::std::error::Error for EncodingError {
+    fn description(&self) -> &str {
+        match self.error_len {
+            Some(_) => "invalid sequence",
+            None => "incomplete sequence",
+        }
+    }
+}
+
+impl From<str::Utf8Error> for EncodingError {
+    fn from(v: str::Utf8Error) -> Self {
+        EncodingError::new(v.valid_up_to(), v.error_len())
+    }
+}
+
+/**
+Encodings in which every sequence of units is well-formed.
+*/
+macro_rules! always_valid {
+    ($($enc:ty),*) => {
+        $(
+            impl ValidateEncoding for $enc {
+                fn validate(_: &[Self::Unit]) -> Result<(), EncodingError> {
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+always_valid! { Raw8, CheckedUnicode }
+
+impl ValidateEncoding for Utf8 {
+    fn validate(units: &[Self::Unit]) -> Result<(), EncodingError> {
+        let bytes = unsafe {
+            // `Utf8Unit` is a `repr(C)` wrapper around `u8`.
+            &*(units as *const [<Utf8 as Encoding>::Unit] as *const [u8])
+        };
+        str::from_utf8(bytes)?;
+        Ok(())
+    }
+}
+
+/**
+`CheckedUtf8` strings are meant to be valid, but can still be sliced, or assembled from individual units, without regard to character boundaries, so they are checked like any other UTF-8.
+*/
+impl ValidateEncoding for CheckedUtf8 {
+    fn validate(units: &[Self::Unit]) -> Result<(), EncodingError> {
+        let bytes = unsafe {
+            // `CheckedUtf8Unit` is a `repr(C)` wrapper around `u8`.
+            &*(units as *const [<CheckedUtf8 as Encoding>::Unit] as *const [u8])
+        };
+        str::from_utf8(bytes)?;
+        Ok(())
+    }
+}
+
+fn validate_utf16<U>(units: &[U]) -> Result<(), EncodingError> where U: Unit {
+    let mut iter = Some(units.iter().cloned());
+    let mut at = 0;
+    while let Some(r) = next_utf16_code_point(&mut iter, &mut at) {
+        match r {
+            Ok(_) => (),
+            Err(Utf16ToUniError::InvalidAt(at)) => return Err(EncodingError::new(at, Some(1))),
+            Err(Utf16ToUniError::Incomplete) => return Err(EncodingError::new(at, None)),
+        }
+    }
+    Ok(())
+}
+
+fn validate_utf32<U>(units: &[U]) -> Result<(), EncodingError> where U: Unit {
+    let mut iter = Some(units.iter().cloned());
+    let mut at = 0;
+    while let Some(r) = next_utf32_code_point(&mut iter, &mut at) {
+        match r {
+            Ok(_) => (),
+            Err(Utf32ToUniError::InvalidAt(at)) => return Err(EncodingError::new(at, Some(1))),
+        }
+    }
+    Ok(())
+}
+
+macro_rules! validate_with {
+    ($f:ident: $($enc:ty),*) => {
+        $(
+            impl ValidateEncoding for $enc {
+                fn validate(units: &[Self::Unit]) -> Result<(), EncodingError> {
+                    $f(units)
+                }
+            }
+        )*
+    };
+}
+
+validate_with! { validate_utf16: Utf16, Utf16Le, Utf16Be, Wide16, WinUnicode }
+validate_with! { validate_utf32: Utf32, Utf32Le, Utf32Be, Wide32 }
+
+/**
+Validation uses the current C runtime locale.
+
+The C runtime does not report how long an invalid sequence is, so `error_len` is always `1` for invalid sequences.
+*/
+impl ValidateEncoding for MultiByte {
+    fn validate(units: &[Self::Unit]) -> Result<(), EncodingError> {
+        let mut state: mbstate_t = unsafe { mem::zeroed() };
+        let mut at = 0;
+
+        while at < units.len() {
+            let mut wc: wchar_t = 0;
+            let rest = &units[at..];
+            let r = unsafe {
+                MbConvResult::from_raw(mbrtowc(&mut wc, rest.as_ptr() as *const c_char, rest.len(), &mut state))
+            };
+
+            match r {
+                MbConvResult::Illegal => return Err(EncodingError::new(at, Some(1))),
+                MbConvResult::Incomplete => return Err(EncodingError::new(at, None)),
+                // `mbrtowc` doesn't say how many units an embedded zero took up.  In every supported locale, it's one.
+                MbConvResult::Consumed(0) => at += 1,
+                MbConvResult::Consumed(n) => at += n,
+                MbConvResult::StoredOnly => panic!("mbrtowc returned a stored unit"),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,22 @@
+/*!
+Bindings to the POSIX `iconv` character set conversion interface.
+
+These are only available on Unix platforms, with the `iconv` feature enabled.  They are used to implement the conversions in `encoding::conv::iconv`.
+*/
+use libc::{c_char, c_int, c_void, size_t};
+
+/**
+A conversion descriptor, as returned by `iconv_open`.
+
+`iconv_open` signals failure by returning `(iconv_t)-1`.
+*/
+#[allow(non_camel_case_types)]
+pub type iconv_t = *mut c_void;
+
+// On macOS, `iconv` lives in its own library, rather than the C runtime.
+#[cfg_attr(target_os="macos", link(name="iconv"))]
+extern "C" {
+    pub fn iconv_open(tocode: *const c_char, fromcode: *const c_char) -> iconv_t;
+    pub fn iconv(cd: iconv_t, inbuf: *mut *mut c_char, inbytesleft: *mut size_t, outbuf: *mut *mut c_char, outbytesleft: *mut size_t) -> size_t;
+    pub fn iconv_close(cd: iconv_t) -> c_int;
+}
@@ -0,0 +1,212 @@
+/*!
+Low-level C runtime bindings.
+
+These are used internally to implement conversions between the C runtime encodings, and, on Windows, the code page encodings (see the `win` module).  They are exposed so that code making its own calls into the C runtime can share the same definitions.
+
+With the `mb-test-codec` feature enabled, the conversion functions are replaced by versions which can be redirected to a fixed, built-in codec.  See the `test_codec` module.
+*/
+use libc::size_t;
+
+// TODO: move into libc
+
+/*
+We have no way of knowing what encodings we'll have to deal with, so 16 was chosen as a (hopefully) excessive upper bound.
+
+Keep in mind that there are serious encodings in existence (though probably *not* being used as the C MB encoding) that can require up to *12 bytes* for a single character.
+
+Normally, you would get this from `limits.h`, except it's not even necessarily a compile-time constant.  Bah!
+*/
+pub const MB_LEN_MAX: usize = 16;
+
+/**
+The decoded result of a C runtime conversion function, such as `mbrtowc` or `wcrtomb`.
+
+These functions signal failure by returning small negative values cast to `size_t`.  Use `MbConvResult::from_raw` to decode them, rather than comparing against magic constants.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MbConvResult {
+    /**
+    The conversion succeeded, consuming (or, for `wcrtomb`, producing) the given number of multibyte units.
+
+    Note that `mbrtowc` returns zero when it converts a zero character, regardless of how many units were consumed.
+    */
+    Consumed(usize),
+
+    /**
+    The input was not a valid sequence.  This is the `(size_t)-1` result, and corresponds to `errno` being set to `EILSEQ`.
+    */
+    Illegal,
+
+    /**
+    The input was a valid, but incomplete, sequence.  This is the `(size_t)-2` result.
+    */
+    Incomplete,
+
+    /**
+    A unit was stored from a previous call without consuming any input.  This is the `(size_t)-3` result, used by `mbrtoc16` to emit the second half of a surrogate pair.
+    */
+    StoredOnly,
+}
+
+impl MbConvResult {
+    /**
+    Decodes a raw `size_t` result.
+    */
+    pub fn from_raw(r: size_t) -> MbConvResult {
+        const ILLEGAL: size_t = -1isize as size_t;
+        const INCOMPLETE: size_t = -2isize as size_t;
+        const STORED_ONLY: size_t = -3isize as size_t;
+
+        match r {
+            ILLEGAL => MbConvResult::Illegal,
+            INCOMPLETE => MbConvResult::Incomplete,
+            STORED_ONLY => MbConvResult::StoredOnly,
+            n => MbConvResult::Consumed(n as usize),
+        }
+    }
+}
+
+/**
+The C11 `char16_t` type, from `uchar.h`.
+*/
+#[allow(non_camel_case_types)]
+pub type char16_t = u16;
+
+/**
+The C11 `char32_t` type, from `uchar.h`.
+*/
+#[allow(non_camel_case_types)]
+pub type char32_t = u32;
+
+#[cfg(not(feature="mb-test-codec"))]
+pub use self::crt::{mbrtowc, wcrtomb, mbsrtowcs, wcsrtombs, mbrtoc16, c16rtomb, mbrtoc32, c32rtomb};
+#[cfg(feature="mb-test-codec")]
+pub use self::test_codec::{mbrtowc, wcrtomb, mbsrtowcs, wcsrtombs, mbrtoc16, c16rtomb, mbrtoc32, c32rtomb};
+
+/**
+Returns `true` if the current C runtime multibyte encoding is known to be ASCII transparent: that is, stateless, with every unit below `0x80` standing for the corresponding ASCII character on its own.
+
+This lets conversions skip calling `mbrtowc` for ASCII text.  A result of `false` only means that the encoding is not *known* to be transparent; shift-based encodings like ISO-2022-JP, and those like Shift JIS which remap parts of the ASCII range, are never reported as transparent.
+*/
+pub fn mb_is_ascii_transparent() -> bool {
+    #[cfg(feature="mb-test-codec")]
+    {
+        // Both built-in codecs are ASCII transparent.
+        if test_codec::test_codec().is_some() {
+            return true;
+        }
+    }
+    crt::mb_is_ascii_transparent()
+}
+
+#[cfg(feature="mb-test-codec")]
+pub mod test_codec;
+
+#[cfg(all(unix, feature="iconv"))]
+pub mod iconv;
+
+#[cfg(windows)]
+pub mod win;
+
+mod crt {
+    use libc::{c_char, size_t, wchar_t};
+    use super::{char16_t, char32_t, mbstate_t};
+
+    extern "C" {
+        pub fn mbrtowc(dest: *mut wchar_t, src: *const c_char, n: size_t, mbs: *mut mbstate_t) -> size_t;
+        pub fn wcrtomb(dest: *mut c_char, src: wchar_t, mbs: *mut mbstate_t) -> size_t;
+        pub fn mbsrtowcs(dest: *mut wchar_t, src: *mut *const c_char, len: size_t, mbs: *mut mbstate_t) -> size_t;
+        pub fn wcsrtombs(dest: *mut c_char, src: *mut *const wchar_t, len: size_t, mbs: *mut mbstate_t) -> size_t;
+        pub fn mbrtoc16(dest: *mut char16_t, src: *const c_char, n: size_t, mbs: *mut mbstate_t) -> size_t;
+        pub fn c16rtomb(dest: *mut c_char, src: char16_t, mbs: *mut mbstate_t) -> size_t;
+        pub fn mbrtoc32(dest: *mut char32_t, src: *const c_char, n: size_t, mbs: *mut mbstate_t) -> size_t;
+        pub fn c32rtomb(dest: *mut c_char, src: char32_t, mbs: *mut mbstate_t) -> size_t;
+    }
+
+    #[cfg(unix)]
+    pub fn mb_is_ascii_transparent() -> bool {
+        use std::ffi::CStr;
+        use libc::{CODESET, nl_langinfo};
+
+        let codeset = unsafe { nl_langinfo(CODESET) };
+        if codeset.is_null() {
+            return false;
+        }
+        let codeset = unsafe { CStr::from_ptr(codeset) }.to_bytes();
+
+        // Names vary between C runtimes ("UTF-8", "utf8", "ISO8859-1", "ISO-8859-1"), so compare them without case or punctuation.
+        let name: Vec<u8> = codeset.iter()
+            .filter(|b| b.is_ascii_alphanumeric())
+            .map(|b| b.to_ascii_uppercase())
+            .collect();
+        match &name[..] {
+            b"UTF8" | b"ASCII" | b"USASCII" | b"ANSIX341968" => true,
+            name => name.starts_with(b"ISO8859")
+                || name.starts_with(b"KOI8")
+                || name.starts_with(b"EUC")
+                || name.starts_with(b"CP125")
+                || name.starts_with(b"WINDOWS125"),
+        }
+    }
+
+    #[cfg(windows)]
+    pub fn mb_is_ascii_transparent() -> bool {
+        // The CRT only accepts ANSI and OEM code pages (and UTF-8) for its locales, all of which are stateless and agree with ASCII below `0x80`.
+        true
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    pub fn mb_is_ascii_transparent() -> bool {
+        false
+    }
+}
+
+/*
+The contents of `mbstate_t` are private to the C runtime; all that matters is that it is big enough, and aligned enough, and that all zeroes is the initial state.
+*/
+
+// glibc and musl: a count plus a four byte buffer, on every architecture.
+#[cfg(all(target_os="linux", any(target_env="gnu", target_env="musl")))]
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct mbstate_t {
+    _data: [u32; 2]
+}
+
+// MinGW: a plain `int`.
+#[cfg(all(target_os="windows", target_env="gnu"))]
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct mbstate_t {
+    _data: [u32; 1]
+}
+
+// MSVC: an `unsigned long` and two `unsigned short`s, on every architecture.
+#[cfg(all(target_os="windows", target_env="msvc"))]
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct mbstate_t {
+    _data: [u32; 2]
+}
+
+// Apple and the BSDs: a union of `char[128]` and `long long`.
+#[cfg(any(target_os="macos", target_os="ios", target_os="freebsd", target_os="dragonfly", target_os="netbsd", target_os="openbsd"))]
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct mbstate_t {
+    _data: [u64; 16]
+}
+
+/*
+Anything else gets an opaque state which should be larger, and at least as aligned, as any real one.  Wasting a little space is much better than refusing to build.
+*/
+#[cfg(not(any(
+    all(target_os="linux", any(target_env="gnu", target_env="musl")),
+    all(target_os="windows", any(target_env="gnu", target_env="msvc")),
+    target_os="macos", target_os="ios", target_os="freebsd", target_os="dragonfly", target_os="netbsd", target_os="openbsd",
+)))]
+#[derive(Copy, Clone)]
+#[repr(C, align(16))]
+pub struct mbstate_t {
+    _data: [u64; 16]
+}
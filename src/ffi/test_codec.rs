@@ -0,0 +1,438 @@
+/*!
+A fixed, built-in replacement for the C runtime multi-byte encoding, **for tests only**.
+
+Normally, every `MultiByte` conversion goes through the C runtime, and so depends on the process locale.  That makes tests of conversion logic depend on which locales happen to be installed on the machine running them.
+
+With the `mb-test-codec` feature enabled, the conversion functions in `ffi` check for a codec selected with `set_test_codec` or `with_test_codec`.  If one is selected, the conversion is done by this module instead of the C runtime; otherwise, the C runtime is used as usual.
+
+The selected codec is *per-thread*, so that tests running in parallel cannot interfere with one another.  Conversions on other threads are not affected.
+
+This feature cannot be enabled in release builds.
+
+# Limitations
+
+These functions implement only as much of the C contract as this crate relies upon.  In particular, an incomplete sequence is *not* remembered in the conversion state; the caller is expected to retry with the same input plus more units, starting from the original state.
+*/
+use std::cell::Cell;
+use std::char;
+use std::cmp;
+use std::mem;
+use std::ptr;
+use std::slice;
+use libc::{c_char, size_t, wchar_t};
+use super::{char16_t, char32_t, mbstate_t, crt};
+
+/**
+A built-in codec which can stand in for the C runtime multi-byte encoding.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TestCodec {
+    /**
+    UTF-8, as used by `C.UTF-8` and similar locales.
+    */
+    Utf8,
+
+    /**
+    Windows code page 1252.  The five bytes which the code page leaves undefined are treated as invalid.
+    */
+    Windows1252,
+}
+
+thread_local! {
+    static CODEC: Cell<Option<TestCodec>> = Cell::new(None);
+}
+
+/**
+Returns the codec selected for the current thread, if any.
+*/
+pub fn test_codec() -> Option<TestCodec> {
+    CODEC.with(|c| c.get())
+}
+
+/**
+Selects the codec used for multi-byte conversions on the current thread, returning the previous selection.
+
+Passing `None` restores the use of the C runtime.
+*/
+pub fn set_test_codec(codec: Option<TestCodec>) -> Option<TestCodec> {
+    CODEC.with(|c| c.replace(codec))
+}
+
+/**
+Runs `f` with `codec` selected for the current thread.
+
+The previous selection is restored afterward, even if `f` panics.
+*/
+pub fn with_test_codec<F, R>(codec: TestCodec, f: F) -> R where F: FnOnce() -> R {
+    struct Restore(Option<TestCodec>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            set_test_codec(self.0);
+        }
+    }
+
+    let _restore = Restore(set_test_codec(Some(codec)));
+    f()
+}
+
+const ILLEGAL: size_t = -1isize as size_t;
+const INCOMPLETE: size_t = -2isize as size_t;
+const STORED_ONLY: size_t = -3isize as size_t;
+
+/**
+Code points for bytes `0x80...0x9f` in code page 1252; `0` marks an undefined byte.
+*/
+const CP1252_HIGH: [u16; 32] = [
+    0x20ac, 0, 0x201a, 0x0192, 0x201e, 0x2026, 0x2020, 0x2021,
+    0x02c6, 0x2030, 0x0160, 0x2039, 0x0152, 0, 0x017d, 0,
+    0, 0x2018, 0x2019, 0x201c, 0x201d, 0x2022, 0x2013, 0x2014,
+    0x02dc, 0x2122, 0x0161, 0x203a, 0x0153, 0, 0x017e, 0x0178,
+];
+
+enum Decoded {
+    Char(char, usize),
+    Incomplete,
+    Illegal,
+}
+
+fn decode(codec: TestCodec, bytes: &[u8]) -> Decoded {
+    match codec {
+        TestCodec::Utf8 => decode_utf8(bytes),
+        TestCodec::Windows1252 => match bytes.first() {
+            None => Decoded::Incomplete,
+            Some(&b @ 0x80 ... 0x9f) => match CP1252_HIGH[(b - 0x80) as usize] {
+                0 => Decoded::Illegal,
+                cp => Decoded::Char(char::from_u32(cp as u32).expect("invalid code page 1252 table entry"), 1),
+            },
+            Some(&b) => Decoded::Char(b as char, 1),
+        },
+    }
+}
+
+fn decode_utf8(bytes: &[u8]) -> Decoded {
+    let lead = match bytes.first() {
+        Some(&b) => b,
+        None => return Decoded::Incomplete,
+    };
+
+    let (len, second) = match lead {
+        0x00 ... 0x7f => return Decoded::Char(lead as char, 1),
+        0xc2 ... 0xdf => (2, (0x80, 0xbf)),
+        0xe0 => (3, (0xa0, 0xbf)),
+        0xed => (3, (0x80, 0x9f)),
+        0xe1 ... 0xef => (3, (0x80, 0xbf)),
+        0xf0 => (4, (0x90, 0xbf)),
+        0xf4 => (4, (0x80, 0x8f)),
+        0xf1 ... 0xf3 => (4, (0x80, 0xbf)),
+        _ => return Decoded::Illegal,
+    };
+
+    // Reject a bad sequence as soon as possible, rather than waiting for it to be complete.
+    for (i, &b) in bytes.iter().enumerate().take(len).skip(1) {
+        let (lo, hi) = if i == 1 { second } else { (0x80, 0xbf) };
+        if b < lo || hi < b {
+            return Decoded::Illegal;
+        }
+    }
+
+    if bytes.len() < len {
+        return Decoded::Incomplete;
+    }
+
+    let mut cp = (lead as u32) & (0x7f >> len);
+    for &b in &bytes[1..len] {
+        cp = (cp << 6) | (b as u32 & 0x3f);
+    }
+    match char::from_u32(cp) {
+        Some(c) => Decoded::Char(c, len),
+        None => Decoded::Illegal,
+    }
+}
+
+fn encode(codec: TestCodec, c: char, buf: &mut [u8]) -> Option<usize> {
+    match codec {
+        TestCodec::Utf8 => Some(c.encode_utf8(buf).len()),
+        TestCodec::Windows1252 => {
+            let b = match c as u32 {
+                cp @ 0x00 ... 0x7f | cp @ 0xa0 ... 0xff => cp as u8,
+                cp => match CP1252_HIGH.iter().position(|&hcp| hcp != 0 && hcp as u32 == cp) {
+                    Some(i) => 0x80 + i as u8,
+                    None => return None,
+                },
+            };
+            buf[0] = b;
+            Some(1)
+        },
+    }
+}
+
+/**
+Returns the slot in the conversion state used to hold a pending surrogate.
+
+Every `mbstate_t` layout is at least as large, and as aligned, as a `u32`.
+*/
+unsafe fn pending<'a>(mbs: *mut mbstate_t) -> &'a mut u32 {
+    &mut *(mbs as *mut u32)
+}
+
+unsafe fn mb_to_char(codec: TestCodec, src: *const c_char, n: size_t) -> Result<(char, size_t), size_t> {
+    let bytes = slice::from_raw_parts(src as *const u8, n);
+    match decode(codec, bytes) {
+        Decoded::Char(c, len) => Ok((c, if c == '\0' { 0 } else { len })),
+        Decoded::Incomplete => Err(INCOMPLETE),
+        Decoded::Illegal => Err(ILLEGAL),
+    }
+}
+
+unsafe fn char_to_mb(codec: TestCodec, dest: *mut c_char, c: char) -> size_t {
+    let buf = slice::from_raw_parts_mut(dest as *mut u8, super::MB_LEN_MAX);
+    match encode(codec, c, buf) {
+        Some(len) => len,
+        None => ILLEGAL,
+    }
+}
+
+/**
+Replacement for the C runtime `mbrtowc`.
+*/
+pub unsafe fn mbrtowc(dest: *mut wchar_t, src: *const c_char, n: size_t, mbs: *mut mbstate_t) -> size_t {
+    let codec = match test_codec() {
+        Some(codec) => codec,
+        None => return crt::mbrtowc(dest, src, n, mbs),
+    };
+
+    if src.is_null() {
+        *mbs = mem::zeroed();
+        return 0;
+    }
+
+    match mb_to_char(codec, src, n) {
+        Ok((c, len)) => {
+            if mem::size_of::<wchar_t>() < 4 && (c as u32) > 0xffff {
+                // A 16-bit `wchar_t` cannot represent the character.
+                return ILLEGAL;
+            }
+            if !dest.is_null() {
+                *dest = c as u32 as wchar_t;
+            }
+            len
+        },
+        Err(r) => r,
+    }
+}
+
+/**
+Replacement for the C runtime `wcrtomb`.
+*/
+pub unsafe fn wcrtomb(dest: *mut c_char, src: wchar_t, mbs: *mut mbstate_t) -> size_t {
+    let codec = match test_codec() {
+        Some(codec) => codec,
+        None => return crt::wcrtomb(dest, src, mbs),
+    };
+
+    if dest.is_null() {
+        *mbs = mem::zeroed();
+        return 1;
+    }
+
+    match char::from_u32(src as u32) {
+        Some(c) => char_to_mb(codec, dest, c),
+        None => ILLEGAL,
+    }
+}
+
+/**
+Replacement for the C runtime `mbsrtowcs`.
+*/
+pub unsafe fn mbsrtowcs(dest: *mut wchar_t, src: *mut *const c_char, len: size_t, mbs: *mut mbstate_t) -> size_t {
+    if test_codec().is_none() {
+        return crt::mbsrtowcs(dest, src, len, mbs);
+    }
+
+    let mut count = 0;
+    let mut cur = *src;
+    while dest.is_null() || count < len {
+        // Never look past the terminator.
+        let n = cmp::min(::libc::strlen(cur) + 1, super::MB_LEN_MAX);
+        let mut wc = 0;
+        match mbrtowc(&mut wc, cur, n, mbs) {
+            0 => {
+                if !dest.is_null() {
+                    *dest.offset(count as isize) = 0;
+                    *src = ptr::null();
+                }
+                return count;
+            },
+            ILLEGAL | INCOMPLETE | STORED_ONLY => {
+                if !dest.is_null() {
+                    *src = cur;
+                }
+                return ILLEGAL;
+            },
+            n => {
+                if !dest.is_null() {
+                    *dest.offset(count as isize) = wc;
+                }
+                cur = cur.offset(n as isize);
+                count += 1;
+            },
+        }
+    }
+    *src = cur;
+    count
+}
+
+/**
+Replacement for the C runtime `wcsrtombs`.
+*/
+pub unsafe fn wcsrtombs(dest: *mut c_char, src: *mut *const wchar_t, len: size_t, mbs: *mut mbstate_t) -> size_t {
+    if test_codec().is_none() {
+        return crt::wcsrtombs(dest, src, len, mbs);
+    }
+
+    let mut count = 0;
+    let mut cur = *src;
+    let mut buf = [0 as c_char; super::MB_LEN_MAX];
+    loop {
+        let wc = *cur;
+        let n = wcrtomb(buf.as_mut_ptr(), wc, mbs);
+        if n == ILLEGAL {
+            if !dest.is_null() {
+                *src = cur;
+            }
+            return ILLEGAL;
+        }
+        if !dest.is_null() {
+            if count + n > len {
+                *src = cur;
+                return count;
+            }
+            ptr::copy_nonoverlapping(buf.as_ptr(), dest.offset(count as isize), n);
+        }
+        if wc == 0 {
+            if !dest.is_null() {
+                *src = ptr::null();
+            }
+            // The terminator is not counted.
+            return count + n - 1;
+        }
+        count += n;
+        cur = cur.offset(1);
+    }
+}
+
+/**
+Replacement for the C runtime `mbrtoc16`.
+*/
+pub unsafe fn mbrtoc16(dest: *mut char16_t, src: *const c_char, n: size_t, mbs: *mut mbstate_t) -> size_t {
+    let codec = match test_codec() {
+        Some(codec) => codec,
+        None => return crt::mbrtoc16(dest, src, n, mbs),
+    };
+
+    // Emit the second half of a surrogate pair left over from the previous call.
+    let low = mem::replace(pending(mbs), 0);
+    if low != 0 {
+        if !dest.is_null() {
+            *dest = low as char16_t;
+        }
+        return STORED_ONLY;
+    }
+
+    if src.is_null() {
+        *mbs = mem::zeroed();
+        return 0;
+    }
+
+    match mb_to_char(codec, src, n) {
+        Ok((c, len)) => {
+            let mut utf16 = [0; 2];
+            let utf16 = c.encode_utf16(&mut utf16);
+            if !dest.is_null() {
+                *dest = utf16[0];
+            }
+            if let Some(&low) = utf16.get(1) {
+                *pending(mbs) = low as u32;
+            }
+            len
+        },
+        Err(r) => r,
+    }
+}
+
+/**
+Replacement for the C runtime `c16rtomb`.
+*/
+pub unsafe fn c16rtomb(dest: *mut c_char, src: char16_t, mbs: *mut mbstate_t) -> size_t {
+    let codec = match test_codec() {
+        Some(codec) => codec,
+        None => return crt::c16rtomb(dest, src, mbs),
+    };
+
+    if dest.is_null() {
+        *mbs = mem::zeroed();
+        return 1;
+    }
+
+    let high = mem::replace(pending(mbs), 0);
+    match (high, src) {
+        (0, 0xd800 ... 0xdbff) => {
+            // Wait for the second half of the pair.
+            *pending(mbs) = src as u32;
+            0
+        },
+        (0, 0xdc00 ... 0xdfff) => ILLEGAL,
+        (0, _) => char_to_mb(codec, dest, char::from_u32(src as u32).expect("unexpected surrogate")),
+        (_, 0xdc00 ... 0xdfff) => {
+            let cp = 0x10000 + (((high & 0x3ff) << 10) | (src as u32 & 0x3ff));
+            char_to_mb(codec, dest, char::from_u32(cp).expect("invalid surrogate pair"))
+        },
+        (_, _) => ILLEGAL,
+    }
+}
+
+/**
+Replacement for the C runtime `mbrtoc32`.
+*/
+pub unsafe fn mbrtoc32(dest: *mut char32_t, src: *const c_char, n: size_t, mbs: *mut mbstate_t) -> size_t {
+    let codec = match test_codec() {
+        Some(codec) => codec,
+        None => return crt::mbrtoc32(dest, src, n, mbs),
+    };
+
+    if src.is_null() {
+        *mbs = mem::zeroed();
+        return 0;
+    }
+
+    match mb_to_char(codec, src, n) {
+        Ok((c, len)) => {
+            if !dest.is_null() {
+                *dest = c as char32_t;
+            }
+            len
+        },
+        Err(r) => r,
+    }
+}
+
+/**
+Replacement for the C runtime `c32rtomb`.
+*/
+pub unsafe fn c32rtomb(dest: *mut c_char, src: char32_t, mbs: *mut mbstate_t) -> size_t {
+    let codec = match test_codec() {
+        Some(codec) => codec,
+        None => return crt::c32rtomb(dest, src, mbs),
+    };
+
+    if dest.is_null() {
+        *mbs = mem::zeroed();
+        return 1;
+    }
+
+    match char::from_u32(src) {
+        Some(c) => char_to_mb(codec, dest, c),
+        None => ILLEGAL,
+    }
+}
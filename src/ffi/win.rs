@@ -0,0 +1,51 @@
+/*!
+Windows code page bindings, from `winnls.h`.
+
+These are used to implement conversions between the Windows code page encodings and `Wide`.
+*/
+#![allow(non_snake_case)]
+
+use libc::{c_char, c_int};
+
+/**
+The pseudo code page identifier for the system ANSI code page.
+*/
+pub const CP_ACP: u32 = 0;
+
+/**
+The pseudo code page identifier for the system OEM code page.
+*/
+pub const CP_OEMCP: u32 = 1;
+
+/**
+The code page identifier for UTF-8.
+*/
+pub const CP_UTF8: u32 = 65001;
+
+/**
+Makes `MultiByteToWideChar` fail on invalid input, rather than substituting a default character.
+*/
+pub const MB_ERR_INVALID_CHARS: u32 = 0x0000_0008;
+
+/**
+Makes `WideCharToMultiByte` fail on lone surrogates.  Only valid for `CP_UTF8`.
+*/
+pub const WC_ERR_INVALID_CHARS: u32 = 0x0000_0080;
+
+/**
+Stops `WideCharToMultiByte` from replacing unrepresentable characters with a "best fit" lookalike.
+*/
+pub const WC_NO_BEST_FIT_CHARS: u32 = 0x0000_0400;
+
+extern "system" {
+    pub fn GetACP() -> u32;
+    pub fn GetOEMCP() -> u32;
+    pub fn IsDBCSLeadByteEx(code_page: u32, test_char: u8) -> i32;
+    pub fn MultiByteToWideChar(code_page: u32, flags: u32,
+        multi_byte_str: *const c_char, multi_byte: c_int,
+        wide_char_str: *mut u16, wide_char: c_int) -> c_int;
+    pub fn WideCharToMultiByte(code_page: u32, flags: u32,
+        wide_char_str: *const u16, wide_char: c_int,
+        multi_byte_str: *mut c_char, multi_byte: c_int,
+        default_char: *const c_char, used_default_char: *mut i32) -> c_int;
+}
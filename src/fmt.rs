@@ -0,0 +1,134 @@
+/*!
+Formatting helpers for collections of strings.
+*/
+use std::char;
+use std::fmt::{self, Debug, Display};
+use std::marker::PhantomData;
+use encoding::{DecodeUnits, Encoding};
+use sea::{fmt_debug_units, SeStr};
+use structure::Structure;
+
+/**
+Wraps a sequence of strings so that they can be formatted as a list.
+
+`iter` can be anything which yields references to strings, or owned strings, such as `&Vec<ZWCString>`.  It is iterated once each time the list is formatted.
+
+See `List` for the output formats.
+*/
+pub fn list<'a, I, S, E>(iter: I) -> List<'a, I::IntoIter, S, E>
+where
+    I: IntoIterator,
+    I::IntoIter: Clone,
+    I::Item: AsRef<SeStr<S, E>>,
+    S: Structure<E>,
+    E: Encoding,
+{
+    List {
+        iter: iter.into_iter(),
+        separator: ", ",
+        max_items: None,
+        _marker: PhantomData,
+    }
+}
+
+/**
+A list of strings which can be formatted with `Display` or `Debug`.
+
+`Display` writes each string decoded to Unicode, with U+FFFD REPLACEMENT CHARACTER in place of any invalid sequences, separated by the separator.  This is only available for encodings which implement `DecodeUnits`.
+
+`Debug` writes the structure and encoding prefix once, followed by the units of each string in square brackets, *e.g.* `ZW["abc", "def"]`.  Each string is subject to `debug_limit`, as usual.
+
+In both cases, if there are more than `max_items` strings, the remainder are replaced by a count.
+
+Use `list` to create a `List`.
+*/
+pub struct List<'a, I, S, E> {
+    iter: I,
+    separator: &'a str,
+    max_items: Option<usize>,
+    _marker: PhantomData<(S, E)>,
+}
+
+impl<'a, I, S, E> List<'a, I, S, E>
+where
+    I: Iterator + Clone,
+    I::Item: AsRef<SeStr<S, E>>,
+    S: Structure<E>,
+    E: Encoding,
+{
+    /**
+    Sets the separator written between strings by `Display`.  The default is `", "`.
+    */
+    pub fn separator(self, separator: &'a str) -> Self {
+        List {
+            separator: separator,
+            ..self
+        }
+    }
+
+    /**
+    Sets the maximum number of strings to write, or `None` to write them all.  The default is `None`.
+    */
+    pub fn max_items(self, max_items: Option<usize>) -> Self {
+        List {
+            max_items: max_items,
+            ..self
+        }
+    }
+
+    /**
+    Writes the items of the list, followed by a count of any which were left out.
+    */
+    fn fmt_items<F>(&self, fmt: &mut fmt::Formatter, separator: &str, mut fmt_item: F) -> fmt::Result
+    where F: FnMut(&SeStr<S, E>, &mut fmt::Formatter) -> fmt::Result {
+        let mut iter = self.iter.clone();
+        let shown = self.max_items.unwrap_or(!0);
+
+        for (i, item) in iter.by_ref().take(shown).enumerate() {
+            if i > 0 {
+                fmt.write_str(separator)?;
+            }
+            fmt_item(item.as_ref(), fmt)?;
+        }
+
+        let rest = iter.count();
+        if rest > 0 {
+            if shown > 0 {
+                fmt.write_str(separator)?;
+            }
+            write!(fmt, "... ({} more)", rest)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, I, S, E> Display for List<'a, I, S, E>
+where
+    I: Iterator + Clone,
+    I::Item: AsRef<SeStr<S, E>>,
+    S: Structure<E>,
+    E: DecodeUnits,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_items(fmt, self.separator, |s, fmt| {
+            for (_, c) in s.char_indices() {
+                fmt::Write::write_char(fmt, c.unwrap_or(char::REPLACEMENT_CHARACTER))?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl<'a, I, S, E> Debug for List<'a, I, S, E>
+where
+    I: Iterator + Clone,
+    I::Item: AsRef<SeStr<S, E>>,
+    S: Structure<E>,
+    E: Encoding,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}{}[", S::debug_prefix(), E::debug_prefix())?;
+        self.fmt_items(fmt, ", ", |s, fmt| fmt_debug_units(s.as_units(), fmt))?;
+        write!(fmt, "]")
+    }
+}
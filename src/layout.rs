@@ -0,0 +1,131 @@
+/*!
+Compile-time checks for the layout assumptions behind the crate's pointer conversions.
+
+Borrowed strings are converted to and from FFI pointers by transmuting, and the wrapper types are converted to and from their inner types the same way.  None of that is sound unless the types involved really do have the same size and alignment, so every structure and encoding the crate ships is checked here.
+
+Each assertion is an array whose length is only `1` if the condition holds; a failing assertion is a type mismatch at compile time.
+*/
+#![allow(dead_code)]
+
+use std::mem::{align_of, size_of};
+use alloc::Malloc;
+use encoding::{Encoding, MultiByte, Wide16, Wide32, Char16, Char32, WinUnicode, JniMtf8, Raw8, Utf8, Wtf8, CheckedUtf8, Utf16, Utf16Le, Utf16Be,
+    Utf32, Utf32Le, Utf32Be, CheckedUnicode};
+use sea::{SeStr, SeaString};
+use structure::{Structure, Slice, ZeroTerm};
+use wrapper::{ZMbStr, ZMbCString};
+use encoding::Wide;
+#[cfg(windows)]
+use encoding::{WinAnsi, WinOem};
+
+macro_rules! const_assert {
+    ($name:ident: $cond:expr) => {
+        const $name: [(); 1] = [(); ($cond) as usize];
+    };
+}
+
+macro_rules! same_layout {
+    ($a:ty, $b:ty) => {
+        (size_of::<$a>() == size_of::<$b>()) & (align_of::<$a>() == align_of::<$b>())
+    };
+}
+
+macro_rules! check_encoding {
+    ($($name:ident: $enc:ty;)*) => {
+        $(
+            mod $name {
+                use super::*;
+
+                type Z = ZeroTerm;
+                type S = Slice;
+
+                // Units are read and written through pointers to `FfiUnit`.
+                const_assert!(UNIT: same_layout!(<$enc as Encoding>::Unit, <$enc as Encoding>::FfiUnit));
+
+                // `SeStr::from_ptr` and `SeStr::as_ptr`.
+                const_assert!(Z_FFI_PTR: same_layout!(<Z as Structure<$enc>>::FfiPtr, &<Z as Structure<$enc>>::RefTarget));
+                const_assert!(Z_FFI_MUT_PTR: same_layout!(<Z as Structure<$enc>>::FfiMutPtr, &mut <Z as Structure<$enc>>::RefTarget));
+                const_assert!(S_FFI_PTR: same_layout!(<S as Structure<$enc>>::FfiPtr, &<S as Structure<$enc>>::RefTarget));
+                const_assert!(S_FFI_MUT_PTR: same_layout!(<S as Structure<$enc>>::FfiMutPtr, &mut <S as Structure<$enc>>::RefTarget));
+
+                // `SeStr` pointers are transmuted from pointers to the structure's target.
+                const_assert!(Z_SESTR: same_layout!(&SeStr<Z, $enc>, &<Z as Structure<$enc>>::RefTarget));
+                const_assert!(S_SESTR: same_layout!(&SeStr<S, $enc>, &<S as Structure<$enc>>::RefTarget));
+
+                // Null pointers must map to `None` without growing the pointer.
+                const_assert!(Z_OPTION: same_layout!(Option<&SeStr<Z, $enc>>, <Z as Structure<$enc>>::FfiPtr));
+                const_assert!(S_OPTION: same_layout!(Option<&SeStr<S, $enc>>, <S as Structure<$enc>>::FfiPtr));
+
+                // `SeaString` is nothing more than the structure's owned handle.
+                const_assert!(Z_SEASTRING: same_layout!(SeaString<Z, $enc, Malloc>, <Z as Structure<$enc>>::Owned));
+                const_assert!(S_SEASTRING: same_layout!(SeaString<S, $enc, Malloc>, <S as Structure<$enc>>::Owned));
+            }
+        )*
+    };
+}
+
+check_encoding! {
+    multi_byte: MultiByte;
+    wide16: Wide16;
+    wide32: Wide32;
+    char16: Char16;
+    char32: Char32;
+    win_unicode: WinUnicode;
+    jni_mtf8: JniMtf8;
+    raw8: Raw8;
+    utf8: Utf8;
+    wtf8: Wtf8;
+    checked_utf8: CheckedUtf8;
+    utf16: Utf16;
+    utf16_le: Utf16Le;
+    utf16_be: Utf16Be;
+    utf32: Utf32;
+    utf32_le: Utf32Le;
+    utf32_be: Utf32Be;
+    checked_unicode: CheckedUnicode;
+}
+
+#[cfg(windows)]
+check_encoding! {
+    win_ansi: WinAnsi;
+    win_oem: WinOem;
+}
+
+// The wrapper types are converted to and from their inner types by transmuting.
+const_assert!(ZMBSTR: same_layout!(ZMbStr, SeStr<ZeroTerm, MultiByte>));
+const_assert!(ZMBSTR_REF: same_layout!(&ZMbStr, &SeStr<ZeroTerm, MultiByte>));
+const_assert!(ZMBSTR_OPTION: same_layout!(Option<&ZMbStr>, *const ::libc::c_char));
+const_assert!(ZMBCSTRING: same_layout!(ZMbCString, SeaString<ZeroTerm, MultiByte, Malloc>));
+
+// On Windows, `Wide` and `WinUnicode` strings are reinterpreted as one another.
+#[cfg(windows)]
+const_assert!(WIDE_IS_WIN_UNICODE: same_layout!(<Wide as Encoding>::Unit, <WinUnicode as Encoding>::Unit));
+
+// `Wide` strings are reinterpreted as `Utf16` strings on Windows, and as `Utf32` strings elsewhere.
+#[cfg(windows)]
+const_assert!(WIDE_IS_UTF16: same_layout!(<Wide as Encoding>::Unit, <Utf16 as Encoding>::Unit));
+#[cfg(not(windows))]
+const_assert!(WIDE_IS_UTF32: same_layout!(<Wide as Encoding>::Unit, <Utf32 as Encoding>::Unit));
+
+// With `stdc-utf`, `Char16` and `Char32` strings are reinterpreted as `Utf16` and `Utf32` strings.
+#[cfg(feature="stdc-utf")]
+const_assert!(CHAR16_IS_UTF16: same_layout!(<Char16 as Encoding>::Unit, <Utf16 as Encoding>::Unit));
+#[cfg(feature="stdc-utf")]
+const_assert!(CHAR32_IS_UTF32: same_layout!(<Char32 as Encoding>::Unit, <Utf32 as Encoding>::Unit));
+
+// `ByteEncoding` strings are reinterpreted as byte slices.
+const_assert!(MB_UNIT_IS_BYTE: same_layout!(<MultiByte as Encoding>::Unit, u8));
+const_assert!(MTF8_UNIT_IS_BYTE: same_layout!(<JniMtf8 as Encoding>::Unit, u8));
+const_assert!(RAW8_UNIT_IS_BYTE: same_layout!(<Raw8 as Encoding>::Unit, u8));
+const_assert!(UTF8_UNIT_IS_BYTE: same_layout!(<Utf8 as Encoding>::Unit, u8));
+const_assert!(WTF8_UNIT_IS_BYTE: same_layout!(<Wtf8 as Encoding>::Unit, u8));
+#[cfg(windows)]
+const_assert!(ANSI_UNIT_IS_BYTE: same_layout!(<WinAnsi as Encoding>::Unit, u8));
+#[cfg(windows)]
+const_assert!(OEM_UNIT_IS_BYTE: same_layout!(<WinOem as Encoding>::Unit, u8));
+
+// `Utf8` and `CheckedUtf8` strings are reinterpreted as one another.
+const_assert!(UTF8_IS_CHECKED_UTF8: same_layout!(<Utf8 as Encoding>::Unit, <CheckedUtf8 as Encoding>::Unit));
+
+// The test codec keeps its pending surrogate in the first four bytes of an `mbstate_t`.
+const_assert!(MBSTATE_HOLDS_U32: (size_of::<::ffi::mbstate_t>() >= size_of::<u32>()) & (align_of::<::ffi::mbstate_t>() >= align_of::<u32>()));
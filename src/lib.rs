@@ -24,7 +24,11 @@ This table does not remove the need to understand how this library represents st
 */
 #![cfg_attr(all(feature="nightly", feature="nightly-alloc"), feature(alloc, heap_api))]
 
+#[cfg(all(feature="mb-test-codec", not(debug_assertions)))]
+compile_error!("the `mb-test-codec` feature is for tests only, and cannot be enabled in release builds");
+
 extern crate libc;
+#[cfg(feature="proptest")] extern crate proptest;
 
 #[cfg(all(feature="nightly", feature="nightly-alloc"))]
 extern crate alloc as rust_alloc;
@@ -34,10 +38,14 @@ macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
 pub mod alloc;
 #[doc(hidden)] pub mod doc;
 pub mod encoding;
+pub mod ffi;
+pub mod fmt;
+#[cfg(any(unix, windows))] pub mod locale;
 pub mod structure;
 pub mod sea;
+#[cfg(feature="proptest")] pub mod strategy;
 
-mod ffi;
+mod layout;
 mod util;
 mod wrapper;
 
@@ -0,0 +1,209 @@
+/*!
+Running conversions under an explicitly chosen C runtime locale.
+
+`MultiByte` strings are interpreted according to whichever locale is active on the current thread at the moment they are *converted*, not the one under which they were produced.  If the locale changes in between, whether through `setlocale` on any thread or `uselocale` on this one, the conversion silently produces the wrong text, or fails outright.
+
+A `Locale` pins this down.  Capture the locale a string was produced under with `Locale::current`, or name one with `Locale::new`, then convert under it with `SeStr::into_string_in_locale`, or run arbitrary code under it with `Locale::with`.  Only the character type category (`LC_CTYPE`) is affected.
+
+On POSIX platforms, this uses `newlocale` and `uselocale`, and so only affects the calling thread.  On Windows, it switches the thread to a per-thread locale with `_configthreadlocale`, then sets it with `setlocale`; the previous locale and mode are restored afterward.
+*/
+use std::error::Error as StdError;
+use std::ffi::CString;
+use std::fmt::{self, Display};
+
+/**
+A C runtime locale, used to convert `MultiByte` strings independently of the current thread's locale.
+
+See the module documentation.
+*/
+pub struct Locale {
+    raw: sys::Raw,
+}
+
+impl Locale {
+    /**
+    Loads the locale with the given name, such as `"C.UTF-8"` or `"ja_JP.eucJP"`.
+
+    Only the character type category is taken from `name`.  On POSIX platforms, the other categories are copied from the current thread's locale, so that running code under the result does not change them.
+
+    # Failure
+
+    This will fail if the locale does not exist, or if `name` contains a zero byte.
+    */
+    pub fn new(name: &str) -> Result<Locale, LocaleError> {
+        let name = CString::new(name).map_err(|_| LocaleError::Unavailable)?;
+        sys::new(name)
+            .map(|raw| Locale { raw: raw })
+            .ok_or(LocaleError::Unavailable)
+    }
+
+    /**
+    Captures the locale currently active on this thread.
+
+    Later changes to the thread's locale do not affect the result.
+
+    # Failure
+
+    This will fail if the C runtime cannot copy the locale, which generally means it has run out of memory.
+    */
+    pub fn current() -> Result<Locale, LocaleError> {
+        sys::current()
+            .map(|raw| Locale { raw: raw })
+            .ok_or(LocaleError::Unavailable)
+    }
+
+    /**
+    Runs `f` with this locale active on the current thread.
+
+    The thread's previous locale is restored when `f` returns or panics.
+    */
+    pub fn with<F, R>(&self, f: F) -> R where F: FnOnce() -> R {
+        let _restore = sys::enter(&self.raw);
+        f()
+    }
+}
+
+impl fmt::Debug for Locale {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Locale(..)")
+    }
+}
+
+/**
+The error produced when a `Locale` cannot be created.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LocaleError {
+    /**
+    The locale does not exist, or could not be loaded.
+    */
+    Unavailable,
+}
+
+impl Display for LocaleError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LocaleError::Unavailable => write!(fmt, "locale is not available"),
+        }
+    }
+}
+
+impl StdError for LocaleError {
+    fn description(&self) -> &str {
+        match *self {
+            LocaleError::Unavailable => "locale is not available",
+        }
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::ffi::CString;
+    use std::mem;
+    use std::ptr;
+    use libc::{duplocale, freelocale, locale_t, newlocale, uselocale, LC_CTYPE_MASK};
+
+    pub struct Raw(locale_t);
+
+    impl Drop for Raw {
+        fn drop(&mut self) {
+            unsafe { freelocale(self.0) }
+        }
+    }
+
+    pub fn new(name: CString) -> Option<Raw> {
+        // Only `LC_CTYPE` comes from `name`; every other category is copied from the thread's current locale, rather than defaulting to "C".
+        let base = current()?;
+        let raw = unsafe { newlocale(LC_CTYPE_MASK, name.as_ptr(), base.0) };
+        if raw.is_null() {
+            // On failure, `base` is left alone, so it is freed as usual.
+            return None;
+        }
+        // On success, `base` has been reused or freed by `newlocale`.
+        mem::forget(base);
+        Some(Raw(raw))
+    }
+
+    pub fn current() -> Option<Raw> {
+        // `uselocale` with a null argument only queries; `duplocale` also accepts `LC_GLOBAL_LOCALE`, which it may return.
+        let raw = unsafe { duplocale(uselocale(ptr::null_mut())) };
+        if raw.is_null() { None } else { Some(Raw(raw)) }
+    }
+
+    pub struct Restore(locale_t);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            unsafe { uselocale(self.0); }
+        }
+    }
+
+    pub fn enter(raw: &Raw) -> Restore {
+        Restore(unsafe { uselocale(raw.0) })
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::ffi::{CStr, CString};
+    use std::ptr;
+    use libc::{c_char, c_int, c_void, setlocale, LC_CTYPE};
+
+    const _ENABLE_PER_THREAD_LOCALE: c_int = 1;
+
+    extern "C" {
+        fn _configthreadlocale(per_thread_locale_type: c_int) -> c_int;
+        fn _create_locale(category: c_int, locale: *const c_char) -> *mut c_void;
+        fn _free_locale(locale: *mut c_void);
+    }
+
+    /*
+    The CRT's conversion functions only follow the thread's locale, not a `_locale_t`, so the locale is kept by name and set with `setlocale` when it is entered.
+    */
+    pub struct Raw(CString);
+
+    pub fn new(name: CString) -> Option<Raw> {
+        // Check that the locale exists without disturbing the current one.
+        let loc = unsafe { _create_locale(LC_CTYPE, name.as_ptr()) };
+        if loc.is_null() {
+            return None;
+        }
+        unsafe { _free_locale(loc) };
+        Some(Raw(name))
+    }
+
+    pub fn current() -> Option<Raw> {
+        let name = unsafe { setlocale(LC_CTYPE, ptr::null()) };
+        if name.is_null() {
+            return None;
+        }
+        Some(Raw(unsafe { CStr::from_ptr(name) }.to_owned()))
+    }
+
+    pub struct Restore {
+        mode: c_int,
+        name: Option<Raw>,
+    }
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            unsafe {
+                if let Some(ref name) = self.name {
+                    setlocale(LC_CTYPE, (name.0).as_ptr());
+                }
+                _configthreadlocale(self.mode);
+            }
+        }
+    }
+
+    pub fn enter(raw: &Raw) -> Restore {
+        // Switching to a per-thread locale starts it as a copy of the global one, so the name must be read afterward.
+        let mode = unsafe { _configthreadlocale(_ENABLE_PER_THREAD_LOCALE) };
+        let restore = Restore {
+            mode: mode,
+            name: current(),
+        };
+        unsafe { setlocale(LC_CTYPE, (raw.0).as_ptr()) };
+        restore
+    }
+}
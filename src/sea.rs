@@ -2,20 +2,36 @@
 Generalised FFI strings.
 */
 use std::borrow::{Borrow, BorrowMut, ToOwned};
-use std::cmp::Ordering;
-use std::convert::{AsRef, AsMut};
+use std::cell::{Cell, RefCell};
+use std::cmp::{self, Ordering};
+use std::convert::{AsRef, AsMut, TryFrom};
 use std::error::Error as StdError;
 use std::fmt::{self, Debug};
 use std::hash::{Hash, Hasher};
 use std::iter::FromIterator;
 use std::marker::PhantomData;
 use std::mem;
-use std::ops::{Deref, DerefMut, Index, IndexMut, RangeFull};
-
-use alloc::{Allocator, Malloc};
-use encoding::{Encoding, TranscodeTo, UnitDebug, UnitIter, CheckedUnicode};
-use structure::{Structure, StructureAlloc, StructureDefault, StructureIter, MutationSafe, OwnershipTransfer, ZeroTerminated, Slice};
-use util::{TrapErrExt, Utf8EncodeExt};
+use std::ops::{Add, Deref, DerefMut, Index, IndexMut, RangeFull};
+use std::rc::Rc;
+use std::slice;
+use std::str;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::usize;
+
+use alloc::{Allocator, AllocatorError, AllocError, Global, Malloc, Rust, ThreadSafeAllocator};
+#[cfg(feature="alloc-stats")] use alloc::stats;
+use encoding::{AsciiCompatible, Bom, ByteEncoding, DecodeError, DecodeUnits, EncodeChar, Encoding, Endianness, EncodingError, EscapeStyle, LocaleIndependent, MutableUnits, Recoverable, TranscodeTo, Unit, UnitDebug, UnitIter, CheckedUnicode, CheckedUtf8, CheckedUtf8Unit, MultiByte, Raw8, Raw8Unit, Utf8, Utf8Unit, Utf16, Utf16Le, Utf16Be, Utf32, ValidateEncoding, Wide};
+use encoding::conv::ErrorOffset;
+use encoding::encode::{encode_onto, with_encoded};
+use encoding::conv::mb_x_wc::{mbs_to_wcs_bulk, wcs_to_mbs_bulk};
+use encoding::conv::utf8::{validate_utf8, Utf8ValidationError};
+#[cfg(any(unix, windows))] use locale::Locale;
+#[cfg(windows)] use encoding::WinUnicode;
+#[cfg(windows)] use encoding::conv::win_cp::{CodePage, cp_to_wide_bulk, wide_to_cp_bulk};
+#[cfg(feature="stdc-utf")] use encoding::{Char16, Char32};
+use structure::{Structure, StructureAlloc, StructureDefault, StructureIter, KnownLength, MutationSafe, OwnershipTransfer, ZeroTerminated, Slice, ZeroTerm};
+use util::{ReplaceErrExt, TrapErrExt, Utf8EncodeExt};
 
 /**
 Represents a borrowed foreign string.
@@ -28,13 +44,19 @@ Note that this type *never* transfers ownership.  Passing a `SeStr` to a foreign
 
 This type *may* be used in FFI signatures and types, but we nonetheless recommend not doing so, and explicitly using the `from_ptr` and `as_ptr` methods instead.
 
+# Implicit operations
+
+The trait implementations which run implicitly (`Deref`, `Borrow`, `AsRef`, `PartialEq`/`Eq`, `PartialOrd`/`Ord`, `Hash`, and `Debug`) never panic, and do work bounded by the length of the string, provided the string was validly constructed.  In particular, a zero-terminated string obtained from `from_ptr` must actually *be* terminated; no operation can recover from a missing terminator.
+
+`Debug` output is additionally capped at `debug_limit()` units, so that accidentally logging an enormous string does not produce enormous output.  Truncated output is followed by an ellipsis and the total length of the string, in units.  See `set_debug_limit`.
+
 # Parameters
 
 `S` defines the structure of the string data.  *e.g.* `ZeroTerm` for zero-terminated strings, and `Slice` for Rust-style fat pointers.
 
 `E` defines the encoding of the string data.  *e.g.* `MultiByte` for the current C runtime multibyte encoding, and `Wide` for C wide strings.
 */
-#[repr(C)]
+#[repr(transparent)]
 pub struct SeStr<S, E> where S: Structure<E>, E: Encoding {
     data: S::RefTarget,
 }
@@ -52,6 +74,31 @@ impl<E> SeStr<Slice, E> where E: Encoding {
         }
     }
 
+    /**
+    Creates a `SeStr<Slice, E>` pointer from a static slice.
+
+    This behaves exactly like `new`, but can be used in constant and static initialisers, such as to build tables of strings to pass to foreign code:
+
+    ```
+    # use strffi::encoding::{Utf8, Utf8Unit};
+    # use strffi::sea::SeStr;
+    # use strffi::structure::Slice;
+    static YES_NO: [&'static SeStr<Slice, Utf8>; 2] = [
+        SeStr::from_static(&[Utf8Unit(b'y'), Utf8Unit(b'e'), Utf8Unit(b's')]),
+        SeStr::from_static(&[Utf8Unit(b'n'), Utf8Unit(b'o')]),
+    ];
+    # assert_eq!(YES_NO[1].as_units(), &[Utf8Unit(b'n'), Utf8Unit(b'o')]);
+    ```
+    */
+    pub const fn from_static(units: &'static [E::Unit]) -> &'static Self {
+        // `SeStr` is transparent over its `RefTarget`, which for `Slice` is `[E::Unit]`, so the two references have the same layout.  `transmute` can't see this through the associated type, hence the union.
+        union Pun<E> where E: 'static + Encoding {
+            units: &'static [E::Unit],
+            s: &'static SeStr<Slice, E>,
+        }
+        unsafe { Pun::<E> { units: units }.s }
+    }
+
     /**
     Creates a mutable `SeStr<Slice, E>` pointer from a slice.
     */
@@ -62,6 +109,281 @@ impl<E> SeStr<Slice, E> where E: Encoding {
     }
 }
 
+/**
+This implementation is for zero-terminated strings whose length is already known, allowing them to be viewed with the `Slice` structure.
+*/
+impl<E> SeStr<ZeroTerm, E> where E: Encoding {
+    /**
+    Re-borrows a zero-terminated foreign string as a `Slice` string of `len` units.
+
+    This is for foreign interfaces which provide both a pointer and a length for a zero-terminated string.  Unlike `from_ptr`, the result has an *O*(1) length, so the string is not re-scanned for its terminator each time it is used.  `len` does *not* include the terminator.
+
+    If `ptr` is null, or is not suitably aligned for `E::Unit`, this method will return `None`.
+
+    # Safety
+
+    The caller asserts that `ptr` points to a valid zero-terminated string of *exactly* `len` units, followed by its terminator.  This is not checked, except by a debug assertion on the terminator.  If `len` is too long, the result will include memory past the end of the string; if it is too short, the string will be silently truncated.
+
+    All of the caveats of `from_ptr` regarding the lifetime of the result also apply.
+    */
+    pub unsafe fn from_ptr_unchecked_len<'a>(ptr: *const E::FfiUnit, len: usize) -> Option<&'a SeStr<Slice, E>> {
+        <ZeroTerm as Structure<E>>::borrow_from_ffi_ptr(ptr).map(|unit| {
+            let ptr = unit as *const E::Unit;
+            debug_assert!((*ptr.offset(len as isize)).is_zero(), "string is not terminated at the given length");
+            SeStr::new(slice::from_raw_parts(ptr, len))
+        })
+    }
+}
+
+/**
+This implementation allows foreign strings to be compared against known units without first being borrowed.
+
+These read the foreign string one unit at a time, and stop at the first unit which differs, or the terminator, whichever comes first.  As such, they are safe to use on a string which is much longer than `expected`.
+*/
+impl<E> SeStr<ZeroTerm, E> where E: Encoding {
+    /**
+    Determines whether the zero-terminated foreign string at `ptr` consists of exactly the units in `expected`.
+
+    At most `expected.len() + 1` units are read.  If `ptr` is null, or is not suitably aligned for `E::Unit`, this returns `false`.  If `expected` contains a zero unit, this always returns `false`, since the foreign string must end there.
+
+    # Safety
+
+    The caller asserts that `ptr` is either null, or points to a valid zero-terminated string.
+    */
+    pub unsafe fn ptr_eq_units(ptr: *const E::FfiUnit, expected: &[E::Unit]) -> bool {
+        match ptr_common_prefix::<E>(ptr, expected) {
+            Some(ptr) => (*ptr.offset(expected.len() as isize)).is_zero(),
+            None => false,
+        }
+    }
+
+    /**
+    Determines whether the zero-terminated foreign string at `ptr` begins with the units in `expected`.
+
+    At most `expected.len()` units are read.  If `ptr` is null, or is not suitably aligned for `E::Unit`, this returns `false`.  If `expected` contains a zero unit, this always returns `false`, since the foreign string must end there.
+
+    # Safety
+
+    The caller asserts that `ptr` is either null, or points to a valid zero-terminated string.
+    */
+    pub unsafe fn ptr_starts_with(ptr: *const E::FfiUnit, expected: &[E::Unit]) -> bool {
+        ptr_common_prefix::<E>(ptr, expected).is_some()
+    }
+
+    /**
+    Returns the rest of this string after `prefix`, or `None` if it does not begin with `prefix`.
+
+    Unlike `strip_prefix`, the result is still zero-terminated, so it can be passed back to foreign code as-is.  Only the units of `prefix` are read; the length of the string is never computed.  If `prefix` contains a zero unit, this always returns `None`, since the string must end there.
+    */
+    pub fn strip_prefix_terminated<P>(&self, prefix: P) -> Option<&SeStr<ZeroTerm, E>> where P: UnitPattern<E> {
+        let prefix = prefix.pattern_units();
+        unsafe {
+            ptr_common_prefix::<E>(self.as_ptr(), prefix)
+                .map(|ptr| mem::transmute::<&E::Unit, &SeStr<ZeroTerm, E>>(&*ptr.offset(prefix.len() as isize)))
+        }
+    }
+}
+
+/**
+Implements `SeStr::ptr_eq_units` and `SeStr::ptr_starts_with`.
+
+Returns the string as a unit pointer if it begins with `expected`, without reading past the first mismatch or terminator.
+*/
+unsafe fn ptr_common_prefix<E>(ptr: *const E::FfiUnit, expected: &[E::Unit]) -> Option<*const E::Unit> where E: Encoding {
+    let ptr = match <ZeroTerm as Structure<E>>::borrow_from_ffi_ptr(ptr) {
+        Some(unit) => unit as *const E::Unit,
+        None => return None,
+    };
+    for (i, &exp) in expected.iter().enumerate() {
+        let unit = *ptr.offset(i as isize);
+        // A zero unit is the terminator, even if `expected` also has one here.
+        if unit != exp || unit.is_zero() {
+            return None;
+        }
+    }
+    Some(ptr)
+}
+
+/**
+This implementation allows raw byte buffers to be reinterpreted as strings with a fixed byte order.
+*/
+impl SeStr<Slice, Raw8> {
+    /**
+    Re-borrows this buffer as a little-endian UTF-16 string, without copying.
+
+    Returns `None` if the buffer is not suitably aligned for `u16`, or does not contain an even number of bytes.
+    */
+    pub fn as_utf16le(&self) -> Option<&SeStr<Slice, Utf16Le>> {
+        reinterpret_units(self.as_units()).map(SeStr::new)
+    }
+
+    /**
+    Re-borrows this buffer as a big-endian UTF-16 string, without copying.
+
+    Returns `None` if the buffer is not suitably aligned for `u16`, or does not contain an even number of bytes.
+    */
+    pub fn as_utf16be(&self) -> Option<&SeStr<Slice, Utf16Be>> {
+        reinterpret_units(self.as_units()).map(SeStr::new)
+    }
+}
+
+/**
+This implementation allows byte order marks to be detected in, and removed from, raw byte buffers.
+*/
+impl<S> SeStr<S, Raw8> where S: Structure<Raw8> {
+    /**
+    Identifies the byte order mark at the start of this buffer, if there is one.
+
+    See `Bom::detect` for how ambiguous marks are resolved.
+    */
+    pub fn detect_bom(&self) -> Option<Bom> {
+        Bom::detect(self.as_raw_bytes())
+    }
+
+    /**
+    Re-borrows this buffer without its leading byte order mark, without copying.
+
+    If there is no byte order mark, this returns the whole buffer.
+    */
+    pub fn strip_bom(&self) -> &SeStr<Slice, Raw8> {
+        let len = self.detect_bom().map(|bom| bom.len()).unwrap_or(0);
+        SeStr::new(&self.as_units()[len..])
+    }
+}
+
+/**
+This implementation allows byte order marks to be detected in, and removed from, `Utf16` strings.
+*/
+impl<S> SeStr<S, Utf16> where S: Structure<Utf16> {
+    /**
+    Returns the byte order indicated by the byte order mark at the start of this string, if there is one.
+
+    A mark read as U+FEFF means the string is already in native byte order.  One read as U+FFFE means the string was written in the opposite byte order, and its units need to be swapped.
+    */
+    pub fn detect_bom(&self) -> Option<Endianness> {
+        match self.as_units().first() {
+            Some(unit) if unit.0 == 0xfeff => Some(Endianness::native()),
+            Some(unit) if unit.0 == 0xfffe => Some(Endianness::native().swap()),
+            _ => None,
+        }
+    }
+
+    /**
+    Re-borrows this string without its leading byte order mark, without copying.
+
+    If there is no byte order mark, this returns the whole string.
+    */
+    pub fn strip_bom(&self) -> &SeStr<Slice, Utf16> {
+        let len = self.detect_bom().map(|_| 1).unwrap_or(0);
+        SeStr::new(&self.as_units()[len..])
+    }
+}
+
+/**
+This implementation allows byte order marks to be detected in, and removed from, `Utf32` strings.
+*/
+impl<S> SeStr<S, Utf32> where S: Structure<Utf32> {
+    /**
+    Returns the byte order indicated by the byte order mark at the start of this string, if there is one.
+
+    A mark read as U+FEFF means the string is already in native byte order.  One read as `0xfffe0000` means the string was written in the opposite byte order, and its units need to be swapped.
+    */
+    pub fn detect_bom(&self) -> Option<Endianness> {
+        match self.as_units().first() {
+            Some(unit) if unit.0 == 0x0000feff => Some(Endianness::native()),
+            Some(unit) if unit.0 == 0xfffe0000 => Some(Endianness::native().swap()),
+            _ => None,
+        }
+    }
+
+    /**
+    Re-borrows this string without its leading byte order mark, without copying.
+
+    If there is no byte order mark, this returns the whole string.
+    */
+    pub fn strip_bom(&self) -> &SeStr<Slice, Utf32> {
+        let len = self.detect_bom().map(|_| 1).unwrap_or(0);
+        SeStr::new(&self.as_units()[len..])
+    }
+}
+
+/**
+This implementation allows byte slices to be used directly as strings, for encodings whose units are plain bytes.
+*/
+impl<E> SeStr<Slice, E> where E: ByteEncoding {
+    /**
+    Creates a `SeStr<Slice, E>` pointer from a byte slice, without copying.
+    */
+    pub fn from_bytes(bytes: &[u8]) -> &Self {
+        unsafe {
+            SeStr::new(slice::from_raw_parts(bytes.as_ptr() as *const E::Unit, bytes.len()))
+        }
+    }
+}
+
+/**
+This implementation allows strings whose units are plain bytes to be viewed as byte slices.
+*/
+impl<S, E> SeStr<S, E> where S: Structure<E>, E: ByteEncoding {
+    /**
+    Returns the units comprising the content of this string as a byte slice, without copying.  This *does not* include any structural data (including terminating units).
+    */
+    pub fn as_raw_bytes(&self) -> &[u8] {
+        let units = self.as_units();
+        unsafe {
+            slice::from_raw_parts(units.as_ptr() as *const u8, units.len())
+        }
+    }
+
+    /**
+    Returns the content of this string as a byte slice, without copying; this is the same as `as_raw_bytes`.
+    */
+    pub fn as_bytes(&self) -> &[u8] {
+        self.as_raw_bytes()
+    }
+}
+
+/**
+This implementation allows strings whose units are plain bytes to be mutated as byte slices, for structures which are safe to mutate.
+*/
+impl<S, E> SeStr<S, E> where S: Structure<E> + MutationSafe, E: ByteEncoding + MutableUnits {
+    /**
+    Returns the units comprising the content of this string as a mutable byte slice, without copying.  This *does not* include any structural data (including terminating units).
+
+    Since every byte is a valid unit in a byte encoding, any bytes may be written.
+    */
+    pub fn as_raw_bytes_mut(&mut self) -> &mut [u8] {
+        let units = self.as_units_mut();
+        unsafe {
+            slice::from_raw_parts_mut(units.as_mut_ptr() as *mut u8, units.len())
+        }
+    }
+
+    /**
+    Returns the content of this string as a mutable byte slice, without copying; this is the same as `as_raw_bytes_mut`.
+    */
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        self.as_raw_bytes_mut()
+    }
+}
+
+/**
+Reinterprets a slice of bytes as a slice of `U`.
+
+`U` *must* be a plain-old-data type for which every bit pattern is valid.
+*/
+fn reinterpret_units<U>(bytes: &[Raw8Unit]) -> Option<&[U]> {
+    let size = mem::size_of::<U>();
+    let addr = bytes.as_ptr() as usize;
+    if addr % mem::align_of::<U>() != 0 || bytes.len() % size != 0 {
+        return None;
+    }
+
+    unsafe {
+        Some(slice::from_raw_parts(bytes.as_ptr() as *const U, bytes.len() / size))
+    }
+}
+
 /**
 General implementation.
 */
@@ -73,6 +395,8 @@ impl<S, E> SeStr<S, E> where S: Structure<E>, E: Encoding {
 
     If `ptr` is null, the result is dependent on the string's structure.  If null is not a valid string pointer value, this method will return `None`; otherwise it will return a valid `SeStr` pointer.
 
+    If `ptr` is not suitably aligned for `E::Unit`, this method will return `None`.  Strings in packed or byte-oriented buffers can be read with the structure's `iter_unaligned` method instead.
+
     # Safety
 
     If the `ptr` is not a valid pointer to a structurally compatible foreign string, then the result of this method is invalid, and may result in a memory protection failure on use.
@@ -85,6 +409,19 @@ impl<S, E> SeStr<S, E> where S: Structure<E>, E: Encoding {
         mem::transmute::<Option<&S::RefTarget>, _>(S::borrow_from_ffi_ptr(ptr))
     }
 
+    /**
+    Re-borrows a `SeStr` from a foreign string pointer, tagging the result as foreign.
+
+    This behaves exactly like `from_ptr`, except that the result is wrapped in `Foreign`.  See `Foreign` for why you might want this.
+
+    # Safety
+
+    See `from_ptr`.
+    */
+    pub unsafe fn from_ptr_tagged<'a>(ptr: S::FfiPtr) -> Option<Foreign<'a, S, E>> {
+        Self::from_ptr(ptr).map(|s| Foreign { s: s })
+    }
+
     /**
     Mutably re-borrows a `SeStr` from a foreign string pointer.
 
@@ -92,6 +429,8 @@ impl<S, E> SeStr<S, E> where S: Structure<E>, E: Encoding {
 
     If `ptr` is null, the result is dependent on the string's structure.  If null is not a valid string pointer value, this method will return `None`; otherwise it will return a valid `SeStr` pointer.
 
+    If `ptr` is not suitably aligned for `E::Unit`, this method will return `None`.  Strings in packed or byte-oriented buffers can be read with the structure's `iter_unaligned` method instead.
+
     # Safety
 
     If the `ptr` is not a valid pointer to a structurally compatible foreign string, then the result of this method is invalid, and may result in a memory protection failure on use.
@@ -117,6 +456,28 @@ impl<S, E> SeStr<S, E> where S: Structure<E>, E: Encoding {
         S::slice_units(&self.data)
     }
 
+    /**
+    Returns the length of this string, in units.  This *does not* include any structural data (including terminating units).
+
+    # Efficiency
+
+    This is `as_units().len()`, and so may require a complete traversal of the underlying memory.  Where `S` implements `KnownLength`, `len` is guaranteed to be *O*(1), and should be preferred.
+    */
+    pub fn unit_len(&self) -> usize {
+        self.as_units().len()
+    }
+
+    /**
+    Determines whether this string has no contents.
+
+    # Efficiency
+
+    This is *O*(1) for every structure in this crate, including `ZeroTerm`, where it only inspects the first unit.
+    */
+    pub fn is_empty(&self) -> bool {
+        S::is_empty(&self.data)
+    }
+
     /**
     Returns the units comprising the content of this string as a contiguous slice.  This *does not* include any structural data (including terminating units).
 
@@ -132,6 +493,8 @@ impl<S, E> SeStr<S, E> where S: Structure<E>, E: Encoding {
 
     Because this method excludes structural and terminating elements, it is not possible to accidentally corrupt the string.  However, it *is* possible to introduce interior terminators into the string, altering its apparent length with some representations.  Any such modification is permanent, and cannot be undone.
 
+    For encodings which do not implement `MutableUnits`, such as `CheckedUtf8`, the caller must also ensure that the contents remain valid in the encoding.
+
     See also: `as_units_mut`.
     */
     pub unsafe fn as_units_mut_unsafe(&mut self) -> &mut [E::Unit] {
@@ -181,11 +544,13 @@ impl<S, E> SeStr<S, E> where S: Structure<E>, E: Encoding {
     /**
     Returns an iterator over the units of this string.
 
+    Unlike `as_units`, this does not need to know the length of the string before it starts.  For zero-terminated strings, units are read one at a time, stopping at the terminator, so a search which stops early never reads the rest of the string.
+
     # Efficiency
 
     This method is *O*(1).  For structures without an explicit length, the returned iterator computes the length lazily.
     */
-    pub fn units<'a>(&'a self) -> S::Iter
+    pub fn units<'a>(&'a self) -> Units<'a, S, E>
     where S: StructureIter<'a, E> {
         S::iter(&self.data)
     }
@@ -206,271 +571,2327 @@ impl<S, E> SeStr<S, E> where S: Structure<E>, E: Encoding {
     }
 
     /**
-    Converts the contents of this string into a normal Rust string.
+    Returns `true` if this string begins with the units of `prefix`.
 
-    # Failure
+    `prefix` can be another string in the same encoding, or a slice of units; see `UnitPattern`.  The comparison is performed on raw units; no transcoding is done.  An empty `prefix` always matches.
+    */
+    pub fn starts_with<P>(&self, prefix: P) -> bool where P: UnitPattern<E> {
+        self.as_units().starts_with(prefix.pattern_units())
+    }
 
-    This conversion will fail if the string contains any units which cannot be translated into Unicode.
+    /**
+    Returns `true` if this string ends with the units of `suffix`.
+
+    `suffix` can be another string in the same encoding, or a slice of units; see `UnitPattern`.  The comparison is performed on raw units; no transcoding is done.  An empty `suffix` always matches.
     */
-    pub fn into_string<'a>(&'a self) -> Result<String, Box<StdError>>
-    where
-        S: StructureIter<'a, E>,
-        UnitIter<E, S::Iter>: TranscodeTo<CheckedUnicode>,
-    {
-        let mut err = Ok(());
-        let units: Vec<_> = self
-            .transcode_to_iter::<CheckedUnicode>()
-            .trap_err(&mut err)
-            .encode_utf8()
-            .collect();
-        let () = err?;
-        let s = unsafe { String::from_utf8_unchecked(units) };
-        Ok(s)
+    pub fn ends_with<P>(&self, suffix: P) -> bool where P: UnitPattern<E> {
+        self.as_units().ends_with(suffix.pattern_units())
     }
 
     /**
-    Transcodes the contents of this string into a different encoding.
+    Returns the rest of this string after `prefix`, or `None` if it does not begin with `prefix`.
 
-    Note that this can also be used to copy the string contents into a string with a different structure.
+    The result borrows from this string; nothing is copied.  See `starts_with` for how `prefix` is matched.  For zero-terminated strings, see also `strip_prefix_terminated`, which keeps the terminator.
+    */
+    pub fn strip_prefix<P>(&self, prefix: P) -> Option<&SeStr<Slice, E>> where P: UnitPattern<E> {
+        let units = self.as_units();
+        let prefix = prefix.pattern_units();
+        if units.starts_with(prefix) {
+            Some(SeStr::new(&units[prefix.len()..]))
+        } else {
+            None
+        }
+    }
 
-    # Failure
+    /**
+    Returns the part of this string before `suffix`, or `None` if it does not end with `suffix`.
 
-    This conversion will fail if the string contains any units which cannot be translated into the target encoding, or if allocation fails.
+    The result borrows from this string; nothing is copied.  See `ends_with` for how `suffix` is matched.
     */
-    pub fn transcode_to<'a, T, F, A>(&'a self) -> Result<SeaString<T, F, A>, Box<StdError>>
-    where
-        S: StructureIter<'a, E>,
-        T: Structure<F> + StructureAlloc<F, A>,
-        F: Encoding,
-        A: Allocator,
-        UnitIter<E, S::Iter>: TranscodeTo<F>,
-    {
-        let units: Result<Vec<_>, _> = self.transcode_to_iter::<F>().collect();
-        let units = units?;
-        Ok(SeaString::new(&units[..])?)
+    pub fn strip_suffix<P>(&self, suffix: P) -> Option<&SeStr<Slice, E>> where P: UnitPattern<E> {
+        let units = self.as_units();
+        let suffix = suffix.pattern_units();
+        if units.ends_with(suffix) {
+            Some(SeStr::new(&units[..units.len() - suffix.len()]))
+        } else {
+            None
+        }
     }
 
     /**
-    Transcodes the contents of this string into a different encoding.
+    Returns `true` if `needle` occurs anywhere within this string.
 
-    The transcoded string contents are returned as an iterator.
+    `needle` can be a single unit, a slice of units, or another string in the same encoding; see `UnitPattern`.  The comparison is performed on raw units; no transcoding is done.  An empty `needle` always matches.
+    */
+    pub fn contains<P>(&self, needle: P) -> bool where P: UnitPattern<E> {
+        self.find(needle).is_some()
+    }
 
-    # Failure
+    /**
+    Returns the offset, in units, of the first occurrence of `needle` within this string.
 
-    This conversion will fail if the string contains any units which cannot be translated into the target encoding.
+    `needle` can be a single unit, a slice of units, or another string in the same encoding; see `UnitPattern`.  The comparison is performed on raw units; no transcoding is done.  An empty `needle` is found at offset `0`.
     */
-    pub fn transcode_to_iter<'a, F>(&'a self) -> <UnitIter<E, S::Iter> as TranscodeTo<F>>::Iter
-    where
-        S: StructureIter<'a, E>,
-        F: Encoding,
-        UnitIter<E, S::Iter>: TranscodeTo<F>,
-    {
-        UnitIter::new(S::iter(&self.data)).transcode()
+    pub fn find<P>(&self, needle: P) -> Option<usize> where P: UnitPattern<E> {
+        needle.find_in(self.as_units())
     }
 
-}
+    /**
+    Returns the offset, in units, of the last occurrence of `needle` within this string.
+
+    `needle` can be a single unit, a slice of units, or another string in the same encoding; see `UnitPattern`.  The comparison is performed on raw units; no transcoding is done.  An empty `needle` is found at the end of the string; that is, at an offset equal to its length.
+    */
+    pub fn rfind<P>(&self, needle: P) -> Option<usize> where P: UnitPattern<E> {
+        needle.rfind_in(self.as_units())
+    }
 
-/**
-This implementation only applies to string structures which are safe to mutate without the risk of truncation or corruption.
-*/
-impl<S, E> SeStr<S, E> where S: Structure<E> + MutationSafe, E: Encoding {
     /**
-    Returns the units comprising the content of this string as a contiguous slice.  This *does not* include any structural data (including terminating units).
+    Returns an iterator over the sub-strings of this string which are separated by `sep`.
 
-    # Efficiency
+    The comparison is performed on raw units; no transcoding is done.  As with `str::split`, a leading or trailing separator produces an empty sub-string at that end, and consecutive separators produce empty sub-strings between them.
+    */
+    pub fn split_unit(&self, sep: E::Unit) -> SplitUnit<S, E> {
+        SplitUnit {
+            rest: Some(self.as_units()),
+            sep: sep,
+            _marker: PhantomData,
+        }
+    }
 
-    For structures where the length of the string is not stored directly, this may require a complete traversal of the underlying memory.  You should avoid calling this method repeatedly.
+    /**
+    Returns the contents of this string as a quoted, escaped literal, using the given escaping rules.
 
-    This method is guaranteed to be *O*(1) if `S` implements the `KnownLength` trait.
+    Unlike `Debug`, which is intended only for human consumption, the result can be pasted into source code or data in the chosen syntax.  Units are escaped individually, based on their raw values; no transcoding is done.
     */
-    pub fn as_units_mut(&mut self) -> &mut [E::Unit] {
-        unsafe { self.as_units_mut_unsafe() }
+    pub fn escape_units_as(&self, style: EscapeStyle) -> String {
+        escape_units(self.as_units(), style)
     }
 
     /**
-    Mutably re-borrows this string as a `SeStr<Slice, E>`.  This can be used to normalise string representations, or to "pre-compute" the length of a foreign string before further processing.
+    Returns a sub-string with all leading and trailing occurrences of `unit` removed.
+
+    The comparison is performed on raw units; no transcoding is done.
     */
-    pub fn as_slice_mut(&mut self) -> &mut SeStr<Slice, E> {
-        unsafe { self.as_slice_mut_unsafe() }
+    pub fn trim_matches(&self, unit: E::Unit) -> &SeStr<Slice, E> {
+        self.trim_start_matches(unit).trim_end_matches(unit)
     }
-}
 
-/**
-This implementation only applies to string structures that end with a zero terminator.
+    /**
+    Returns a sub-string with all leading occurrences of `unit` removed.
+
+    The comparison is performed on raw units; no transcoding is done.
+    */
+    pub fn trim_start_matches(&self, unit: E::Unit) -> &SeStr<Slice, E> {
+        let units = self.as_units();
+        let start = units.iter().position(|&u| u != unit).unwrap_or(units.len());
+        SeStr::new(&units[start..])
+    }
+
+    /**
+    Returns a sub-string with all trailing occurrences of `unit` removed.
+
+    The comparison is performed on raw units; no transcoding is done.
+    */
+    pub fn trim_end_matches(&self, unit: E::Unit) -> &SeStr<Slice, E> {
+        let units = self.as_units();
+        let end = units.iter().rposition(|&u| u != unit).map(|at| at + 1).unwrap_or(0);
+        SeStr::new(&units[..end])
+    }
+
+    /**
+    Converts the contents of this string into a normal Rust string.
+
+    # Failure
+
+    This conversion will fail if the string contains any units which cannot be translated into Unicode.
+    */
+    pub fn into_string<'a>(&'a self) -> Result<String, Box<StdError>>
+    where
+        S: StructureIter<'a, E>,
+        UnitIter<E, S::Iter>: TranscodeTo<CheckedUnicode>,
+    {
+        if let Some(s) = <UnitIter<E, S::Iter> as TranscodeTo<CheckedUnicode>>::decode_to_string(self.as_units()) {
+            return Ok(s);
+        }
+
+        let mut err = Ok(());
+        let mut units = Vec::with_capacity(self.min_transcoded_len());
+        units.extend(self
+            .transcode_to_iter::<CheckedUnicode>()
+            .trap_err(&mut err)
+            .encode_utf8());
+        let () = err?;
+        let s = unsafe { String::from_utf8_unchecked(units) };
+        Ok(s)
+    }
+
+    /**
+    Converts the contents of this string into a normal Rust string, carrying on past anything which cannot be translated into Unicode.
+
+    Each invalid sequence is replaced with U+FFFD, and its offset, in units, is recorded.  The offsets are returned in order, alongside the string; an empty list means the conversion was exact.  An error at the very end of the string, such as a truncated sequence, is recorded at the length of the string.
+
+    This is only available where the transcoder to `CheckedUnicode` implements `Recoverable`, as those which cannot resume after an error would silently lose the rest of the string.  At present, that means `Utf8`, `Utf16`, `Utf16Le`, `Utf16Be`, `WinUnicode`, `Utf32`, `Wide` and `MultiByte` strings; see the implementations of `Recoverable`.  Others, such as `Utf32Le`, `Utf32Be`, `Wtf8`, `JniMtf8` and the Windows code pages, stop at the first error and so are not supported; use `into_string` for those.
+    */
+    pub fn into_string_recover<'a>(&'a self) -> (String, Vec<usize>)
+    where
+        S: StructureIter<'a, E>,
+        UnitIter<E, S::Iter>: TranscodeTo<CheckedUnicode>,
+        <UnitIter<E, S::Iter> as TranscodeTo<CheckedUnicode>>::Iter: Recoverable,
+        <UnitIter<E, S::Iter> as TranscodeTo<CheckedUnicode>>::Error: ErrorOffset,
+    {
+        let len = self.as_units().len();
+        let mut offsets = vec![];
+        let mut s = String::with_capacity(self.min_transcoded_len());
+        for r in self.transcode_to_iter::<CheckedUnicode>() {
+            match r {
+                Ok(c) => s.push(c),
+                Err(err) => {
+                    offsets.push(err.offset().unwrap_or(len));
+                    s.push('\u{fffd}');
+                },
+            }
+        }
+        (s, offsets)
+    }
+
+    /**
+    Returns `true` if this string contains nothing which cannot be translated into Unicode.
+
+    This accepts exactly what `into_string` does, but nothing is allocated, and checking stops at the first error.  `Utf8` strings are checked exactly as `is_valid_utf8` does.
+
+    See also: `validation_errors`, to find out where the problems are.
+    */
+    pub fn is_valid<'a>(&'a self) -> bool
+    where
+        S: StructureIter<'a, E>,
+        UnitIter<E, S::Iter>: TranscodeTo<CheckedUnicode>,
+    {
+        if let Some(valid) = <UnitIter<E, S::Iter> as TranscodeTo<CheckedUnicode>>::is_valid_units(self.as_units()) {
+            return valid;
+        }
+        self.transcode_to_iter::<CheckedUnicode>().all(|r| r.is_ok())
+    }
+
+    /**
+    Returns the offset, in units, of every sequence in this string which cannot be translated into Unicode.
+
+    The offsets are the same as those reported by `into_string_recover`, but no string is built.  An empty list means the string is valid.
+
+    This is only available for the same strings as `into_string_recover`, as transcoders which cannot resume after an error would only ever report the first.
+    */
+    pub fn validation_errors<'a>(&'a self) -> Vec<usize>
+    where
+        S: StructureIter<'a, E>,
+        UnitIter<E, S::Iter>: TranscodeTo<CheckedUnicode>,
+        <UnitIter<E, S::Iter> as TranscodeTo<CheckedUnicode>>::Iter: Recoverable,
+        <UnitIter<E, S::Iter> as TranscodeTo<CheckedUnicode>>::Error: ErrorOffset,
+    {
+        self.transcode_to_iter::<CheckedUnicode>()
+            .filter_map(Result::err)
+            .map(|err| err.offset().unwrap_or_else(|| self.as_units().len()))
+            .collect()
+    }
+
+    /**
+    Returns `true` if this string decodes to exactly the characters of `s`.
+
+    Unlike `==`, which compares raw bytes, this compares characters, so it works for any encoding which can be transcoded into Unicode.  Nothing is allocated, and the comparison stops at the first difference.  A string which contains invalid units is never equal to `s`.
+    */
+    pub fn eq_str<'a>(&'a self, s: &str) -> bool
+    where
+        S: StructureIter<'a, E>,
+        UnitIter<E, S::Iter>: TranscodeTo<CheckedUnicode>,
+    {
+        let mut iter = self.transcode_to_iter::<CheckedUnicode>();
+        for c in s.chars() {
+            match iter.next() {
+                Some(Ok(d)) if d == c => (),
+                _ => return false,
+            }
+        }
+        iter.next().is_none()
+    }
+
+    /**
+    Transcodes the contents of this string into a different encoding.
+
+    Note that this can also be used to copy the string contents into a string with a different structure or allocator: every encoding can be "transcoded" into itself, in which case the units are copied unchanged, and invalid sequences are preserved.
+
+    # Failure
+
+    This conversion will fail if the string contains any units which cannot be translated into the target encoding, or if allocation fails.
+    */
+    pub fn transcode_to<'a, T, F, A>(&'a self) -> Result<SeaString<T, F, A>, Box<StdError>>
+    where
+        S: StructureIter<'a, E>,
+        T: Structure<F> + StructureAlloc<F, A>,
+        F: Encoding,
+        A: Allocator,
+        UnitIter<E, S::Iter>: TranscodeTo<F>,
+    {
+        // Zero-terminated strings may be able to skip the iterators entirely, and be transcoded straight into the result.
+        if let Some(units) = S::slice_units_terminated(&self.data) {
+            if let Some(len) = <UnitIter<E, S::Iter> as TranscodeTo<F>>::transcode_terminated(units, None) {
+                let owned = T::alloc_owned_fill(len, |out| <UnitIter<E, S::Iter> as TranscodeTo<F>>::transcode_terminated(units, Some(out)) == Some(len))?;
+                if let Some(owned) = owned {
+                    let s = SeaString {
+                        owned: owned,
+                        _marker: PhantomData,
+                    };
+                    s.report_alloc();
+                    return Ok(s);
+                }
+            }
+        }
+
+        // To allocate the result exactly once, its length has to be known up front.  Unless the transcoder can say, this means transcoding twice: once to count (and validate), and once to fill.
+        let len = match self.transcode_to_iter::<F>().size_hint() {
+            (lo, Some(hi)) if lo == hi => lo,
+            _ => {
+                let mut err = Ok(());
+                let len = self.transcode_to_iter::<F>().trap_err(&mut err).count();
+                let () = err?;
+                len
+            },
+        };
+
+        let mut err = Ok(());
+        let s = SeaString {
+            owned: T::alloc_owned_from_iter(len, self.transcode_to_iter::<F>().trap_err(&mut err))?,
+            _marker: PhantomData,
+        };
+        s.report_alloc();
+        let () = err?;
+        Ok(s)
+    }
+
+    /**
+    Transcodes the contents of this string into a different encoding, borrowing rather than copying where no conversion is needed.
+
+    If the source encoding is `TransparentAs` the target, such as when they are the same encoding, the result borrows this string's units and nothing is allocated.  Otherwise, this is the same as `transcode_to`.
+
+    # Failure
+
+    This conversion will fail if the string contains any units which cannot be translated into the target encoding, or if allocation fails.
+    */
+    pub fn transcode_to_cow<'a, F>(&'a self) -> Result<SeaCow<'a, Slice, F>, Box<StdError>>
+    where
+        S: StructureIter<'a, E>,
+        F: Encoding,
+        UnitIter<E, S::Iter>: TranscodeTo<F>,
+    {
+        match <UnitIter<E, S::Iter> as TranscodeTo<F>>::transparent() {
+            Some(_) => {
+                let units = self.as_units();
+                // `Transparent` can only exist if `E: TransparentAs<F>`, which guarantees that this is sound.
+                let units = unsafe { slice::from_raw_parts(units.as_ptr() as *const F::Unit, units.len()) };
+                Ok(SeaCow::Borrowed(SeStr::new(units)))
+            },
+            None => Ok(SeaCow::Owned(self.transcode_to()?)),
+        }
+    }
+
+    /**
+    Like `into_string`, except that the error includes a snapshot of this string.
+
+    See `ContextError`.
+    */
+    pub fn into_string_ctx<'a>(&'a self) -> Result<String, ContextError>
+    where
+        S: StructureIter<'a, E>,
+        UnitIter<E, S::Iter>: TranscodeTo<CheckedUnicode>,
+    {
+        self.into_string().map_err(|err| ContextError::new(self, err))
+    }
+
+    /**
+    Transcodes the contents of this string into a caller-provided buffer, returning the transcoded units.
+
+    `out` is cleared first, but its allocation is kept, so the same buffer can be reused across many conversions without reallocating each time.  No terminating zero is added.
+
+    # Failure
+
+    This conversion will fail if the string contains any units which cannot be translated into the target encoding.  On failure, `out` is left empty.
+    */
+    pub fn transcode_into<'a, 'b, F>(&'a self, out: &'b mut Vec<F::Unit>) -> Result<&'b [F::Unit], Box<StdError>>
+    where
+        S: StructureIter<'a, E>,
+        F: Encoding,
+        UnitIter<E, S::Iter>: TranscodeTo<F>,
+    {
+        out.clear();
+        out.reserve(self.min_transcoded_len());
+        let mut err = Ok(());
+        out.extend(self.transcode_to_iter::<F>().trap_err(&mut err));
+        if let Err(err) = err {
+            out.clear();
+            return Err(err.into());
+        }
+        Ok(&out[..])
+    }
+
+    /**
+    Transcodes the contents of this string into a fixed-size, caller-provided buffer.
+
+    As much of the string as will fit is written to the start of `out`; the result reports how many units were written, and whether anything was left out.  If the string is truncated, it is truncated at a character boundary: a character is never only partly written.  No terminating zero is added; see `transcode_into_slice_z`.
+
+    # Failure
+
+    This conversion will fail if the part of the string which fits in `out` contains any units which cannot be translated into the target encoding.  The rest of the string is not examined.  On failure, the contents of `out` are unspecified.
+    */
+    pub fn transcode_into_slice<'a, F>(&'a self, out: &mut [F::Unit]) -> Result<TranscodeInto, Box<StdError>>
+    where
+        S: StructureIter<'a, E>,
+        F: Encoding,
+        UnitIter<E, S::Iter>: TranscodeTo<CheckedUnicode>,
+        UnitIter<CheckedUnicode, CharBoundaries<<UnitIter<E, S::Iter> as TranscodeTo<CheckedUnicode>>::Iter>>: TranscodeTo<F>,
+    {
+        // The string is decoded and re-encoded a character at a time, so that the encoder can be watched for where each character starts.
+        let (chars, counts) = CharBoundaries::new(self.transcode_to_iter::<CheckedUnicode>());
+        let mut iter = UnitIter::<CheckedUnicode, _>::new(chars).transcode();
+        let mut written = 0;
+        while written < out.len() {
+            match iter.next() {
+                Some(unit) => {
+                    out[written] = unit?;
+                    written += 1;
+                    counts.written.set(written);
+                },
+                None => break,
+            }
+        }
+        if let Some(err) = counts.err.borrow_mut().take() {
+            return Err(err);
+        }
+
+        // Anything found past the end of `out` just means the string didn't fit.
+        if iter.next().is_none() && counts.err.borrow().is_none() {
+            return Ok(TranscodeInto {
+                units_written: written,
+                truncated: false,
+            });
+        }
+
+        // Cut at the last character boundary seen, which drops any partly-written character.
+        Ok(TranscodeInto {
+            units_written: counts.boundary.get(),
+            truncated: true,
+        })
+    }
+
+    /**
+    Like `transcode_into_slice`, except that a terminating zero is always written after the transcoded units.
+
+    Room is always left for the terminator, so at most `out.len() - 1` units of the string are written.
+
+    # Failure
+
+    In addition to the failures of `transcode_into_slice`, this conversion will fail if the transcoded units which fit contain a zero unit, which would be mistaken for the terminator.
+
+    # Panics
+
+    This method will panic if `out` is empty, since there is no room for the terminator.
+    */
+    pub fn transcode_into_slice_z<'a, F>(&'a self, out: &mut [F::Unit]) -> Result<TranscodeInto, Box<StdError>>
+    where
+        S: StructureIter<'a, E>,
+        F: Encoding,
+        UnitIter<E, S::Iter>: TranscodeTo<CheckedUnicode>,
+        UnitIter<CheckedUnicode, CharBoundaries<<UnitIter<E, S::Iter> as TranscodeTo<CheckedUnicode>>::Iter>>: TranscodeTo<F>,
+    {
+        assert!(!out.is_empty(), "no room for terminator in empty buffer");
+        let end = out.len() - 1;
+        let r = self.transcode_into_slice::<F>(&mut out[..end])?;
+        let zero = <F::Unit as Unit>::zero();
+        if let Some(at) = out[..r.units_written].iter().position(|&u| u == zero) {
+            return Err(AllocError::InteriorZeroAt(at).into());
+        }
+        out[r.units_written] = zero;
+        Ok(r)
+    }
+
+    /**
+    Like `transcode_to`, except that the error includes a snapshot of this string.
+
+    See `ContextError`.
+    */
+    pub fn transcode_to_ctx<'a, T, F, A>(&'a self) -> Result<SeaString<T, F, A>, ContextError>
+    where
+        S: StructureIter<'a, E>,
+        T: Structure<F> + StructureAlloc<F, A>,
+        F: Encoding,
+        A: Allocator,
+        UnitIter<E, S::Iter>: TranscodeTo<F>,
+    {
+        self.transcode_to().map_err(|err| ContextError::new(self, err))
+    }
+
+    /**
+    Transcodes the contents of this string into a different encoding, substituting `replacement` for anything which cannot be transcoded.
+
+    This is useful for targets which cannot represent U+FFFD, where something like `?` is the usual substitute.  For replacements which take more than one unit, or to find out where substitutions were made, use `transcode_with`.
+
+    `replacement` is substituted for each invalid or inconvertible sequence, and transcoding carries on afterward.  As such, this is only available where the transcoder implements `Recoverable`; use `transcode_with` for the others.
+
+    # Failure
+
+    This conversion will fail if allocation fails, or if the result is incompatible with the structure, such as a zero `replacement` in a zero-terminated string.
+    */
+    pub fn transcode_to_lossy<'a, T, F, A>(&'a self, replacement: F::Unit) -> Result<SeaString<T, F, A>, A::AllocError>
+    where
+        S: StructureIter<'a, E>,
+        T: Structure<F> + StructureAlloc<F, A>,
+        F: Encoding,
+        A: Allocator,
+        UnitIter<E, S::Iter>: TranscodeTo<F>,
+        <UnitIter<E, S::Iter> as TranscodeTo<F>>::Iter: Recoverable,
+    {
+        // As with `transcode_to`, count first so that the result is allocated only once.
+        let len = self.transcode_to_iter::<F>().replace_err(replacement).count();
+        let s = SeaString {
+            owned: T::alloc_owned_from_iter(len, self.transcode_to_iter::<F>().replace_err(replacement))?,
+            _marker: PhantomData,
+        };
+        s.report_alloc();
+        Ok(s)
+    }
+
+    /**
+    Transcodes the contents of this string into a different encoding, dealing with invalid units according to `policy`.
+
+    Alongside the transcoded string, this returns a list of every place in the source where `policy` was applied, in order.  An empty list means the conversion was exact.
+
+    Not all transcoders can resume after an error.  Those that cannot will stop at the first invalid unit, which is treated as the end of the string after `policy` has been applied.
+
+    # Failure
+
+    This conversion will fail if the replacement character cannot be represented in the target encoding, or if allocation fails.
+    */
+    pub fn transcode_with<'a, T, F, A>(&'a self, policy: ReplacementPolicy) -> Result<(SeaString<T, F, A>, Vec<TranscodeIssue>), Box<StdError>>
+    where
+        S: StructureIter<'a, E>,
+        T: Structure<F> + StructureAlloc<F, A>,
+        F: EncodeChar,
+        A: Allocator,
+        UnitIter<E, S::Iter>: TranscodeTo<F>,
+        <UnitIter<E, S::Iter> as TranscodeTo<F>>::Error: ErrorOffset,
+    {
+        // Left empty for policies which don't replace anything.
+        let mut replacement = vec![];
+        match policy {
+            ReplacementPolicy::Unicode => encode_onto::<F>('\u{fffd}', &mut replacement)?,
+            ReplacementPolicy::Char(c) => encode_onto::<F>(c, &mut replacement)?,
+            ReplacementPolicy::Skip | ReplacementPolicy::StopAtError => (),
+        }
+
+        let iter = self.transcode_to_iter::<F>();
+        let mut units = Vec::with_capacity(cmp::max(iter.size_hint().0, self.min_transcoded_len()));
+        let mut issues = vec![];
+        for unit in iter {
+            match unit {
+                Ok(unit) => units.push(unit),
+                Err(err) => {
+                    issues.push(TranscodeIssue {
+                        at: err.offset(),
+                        err: Box::new(err),
+                    });
+                    if policy == ReplacementPolicy::StopAtError {
+                        break;
+                    }
+                    units.extend_from_slice(&replacement);
+                }
+            }
+        }
+        Ok((SeaString::new(&units[..])?, issues))
+    }
+
+    /**
+    Transcodes the contents of this string into a different encoding.
+
+    The transcoded string contents are returned as an iterator.
+
+    # Failure
+
+    This conversion will fail if the string contains any units which cannot be translated into the target encoding.
+    */
+    pub fn transcode_to_iter<'a, F>(&'a self) -> <UnitIter<E, S::Iter> as TranscodeTo<F>>::Iter
+    where
+        S: StructureIter<'a, E>,
+        F: Encoding,
+        UnitIter<E, S::Iter>: TranscodeTo<F>,
+    {
+        UnitIter::new(S::iter(&self.data)).transcode()
+    }
+
+    /**
+    Returns an upper bound on the number of units this string would occupy if transcoded into `F`.
+
+    Every character takes at least one unit in any encoding, so this is just the length of the string multiplied by `F::MAX_UNITS_PER_CHAR`.  It does *not* include a terminating zero.
+
+    Returns `None` if the bound cannot be represented as a `usize`.
+    */
+    pub fn max_transcoded_len<F>(&self) -> Option<usize> where F: Encoding {
+        self.as_units().len().checked_mul(F::MAX_UNITS_PER_CHAR)
+    }
+
+    /**
+    Returns a lower bound on the number of units this string would occupy if transcoded into any encoding, assuming it transcodes successfully.
+
+    Every character takes at most `E::MAX_UNITS_PER_CHAR` units in this string, and at least one unit once transcoded.  It does *not* include a terminating zero.
+    */
+    pub fn min_transcoded_len(&self) -> usize {
+        let len = self.as_units().len();
+        len / E::MAX_UNITS_PER_CHAR + if len % E::MAX_UNITS_PER_CHAR != 0 { 1 } else { 0 }
+    }
+
+    /**
+    Transcodes the contents of this string into an owned string type.
+
+    Unlike `transcode_to`, the structure, encoding, and allocator are all determined by a single target type, so the result can be inferred from context:
+
+    ```
+    # use strffi::alloc::Malloc;
+    # use strffi::encoding::{Utf8, Utf16};
+    # use strffi::sea::{SeStr, SeaString};
+    # use strffi::structure::{Slice, ZeroTerm};
+    # fn main() { run().unwrap() }
+    # fn run() -> Result<(), strffi::Error> {
+    let utf8 = SeStr::<Slice, Utf8>::from_bytes(b"gar\xc3\xa7on");
+    let utf16: SeaString<ZeroTerm, Utf16, Malloc> = utf8.transcode()?;
+    assert_eq!(utf16.into_string()?, "gar\u{e7}on");
+    # Ok(())
+    # }
+    ```
+
+    # Failure
+
+    This conversion will fail if the string contains any units which cannot be translated into the target encoding, or if allocation fails.
+    */
+    pub fn transcode<'a, T>(&'a self) -> Result<T, Box<StdError>>
+    where
+        S: StructureIter<'a, E>,
+        T: TranscodeTarget,
+        UnitIter<E, S::Iter>: TranscodeTo<T::Encoding>,
+    {
+        self.transcode_to::<T::Structure, T::Encoding, T::Allocator>()
+            .map(T::from_sea_string)
+    }
+
+    /**
+    Transcodes the contents of this string into a zero-terminated wide C string.
+
+    # Failure
+
+    This conversion will fail if the string contains any units which cannot be translated into the `Wide` encoding, or if allocation fails.
+    */
+    pub fn to_wide_z<'a>(&'a self) -> Result<SeaString<ZeroTerm, Wide, Malloc>, Box<StdError>>
+    where
+        S: StructureIter<'a, E>,
+        UnitIter<E, S::Iter>: TranscodeTo<Wide>,
+    {
+        self.transcode()
+    }
+
+    /**
+    Transcodes the contents of this string into a zero-terminated UTF-8 string.
+
+    # Failure
+
+    This conversion will fail if the string contains any units which cannot be translated into the `Utf8` encoding, or if allocation fails.
+    */
+    pub fn to_utf8_z<'a>(&'a self) -> Result<SeaString<ZeroTerm, Utf8, Malloc>, Box<StdError>>
+    where
+        S: StructureIter<'a, E>,
+        UnitIter<E, S::Iter>: TranscodeTo<Utf8>,
+    {
+        self.transcode()
+    }
+
+    /**
+    Returns an iterator over the characters of this string, substituting U+FFFD REPLACEMENT CHARACTER for any units which cannot be translated into Unicode.
+
+    Unless the underlying transcoder is recoverable, iteration stops after the first replacement character.
+    */
+    pub fn chars_lossy<'a>(&'a self) -> CharsLossy<'a, S, E>
+    where
+        S: StructureIter<'a, E>,
+        UnitIter<E, S::Iter>: TranscodeTo<CheckedUnicode>,
+    {
+        CharsLossy {
+            iter: self.transcode_to_iter::<CheckedUnicode>(),
+            _marker: PhantomData,
+        }
+    }
+
+    /**
+    Feeds the *decoded* contents of this string into the given hasher.
+
+    Unlike the `Hash` implementation, which hashes units, this hashes characters, so two strings in different encodings which decode to the same text hash equally.  This is what a map keyed on strings of *several* encodings needs; the `Hash` implementation is only consistent with equality *within* a single encoding.
+
+    The characters are hashed one at a time, followed by a single `0xff` byte.  The same hash can be computed for a Rust string `s` with:
+
+    ```
+    # use std::collections::hash_map::DefaultHasher;
+    # use std::hash::{Hash, Hasher};
+    # use strffi::encoding::{Utf16, Utf16Unit};
+    # use strffi::sea::SeStr;
+    # use strffi::structure::Slice;
+    # let s = "gar\u{e7}on";
+    # let state = &mut DefaultHasher::new();
+    for c in s.chars() { c.hash(state); }
+    state.write_u8(0xff);
+    # let units: Vec<_> = s.encode_utf16().map(Utf16Unit).collect();
+    # let mut other = DefaultHasher::new();
+    # SeStr::<Slice, Utf16>::new(&units).hash_decoded(&mut other);
+    # assert_eq!(state.finish(), other.finish());
+    ```
+
+    Units which cannot be decoded are hashed as U+FFFD REPLACEMENT CHARACTER, as for `chars_lossy`.
+    */
+    pub fn hash_decoded<'a, H>(&'a self, state: &mut H)
+    where
+        S: StructureIter<'a, E>,
+        UnitIter<E, S::Iter>: TranscodeTo<CheckedUnicode>,
+        H: Hasher,
+    {
+        for c in self.chars_lossy() {
+            c.hash(state);
+        }
+        state.write_u8(0xff);
+    }
+}
+
+/**
+On Windows, `Wide` and `WinUnicode` strings have identical representations, and can be freely reinterpreted as one another.
+
+On other platforms, use `transcode_to` or `transcode_to_iter` instead.
+*/
+#[cfg(windows)]
+impl<S> SeStr<S, Wide> where S: Structure<Wide> + Structure<WinUnicode> {
+    /**
+    Re-borrows this string as a `WinUnicode` string, without copying.
+    */
+    pub fn as_win_unicode(&self) -> &SeStr<S, WinUnicode> {
+        unsafe {
+            mem::transmute_copy::<&SeStr<S, Wide>, &SeStr<S, WinUnicode>>(&self)
+        }
+    }
+}
+
+/**
+See the `Wide` implementation.
+*/
+#[cfg(windows)]
+impl<S> SeStr<S, WinUnicode> where S: Structure<WinUnicode> + Structure<Wide> {
+    /**
+    Re-borrows this string as a `Wide` string, without copying.
+    */
+    pub fn as_wide(&self) -> &SeStr<S, Wide> {
+        unsafe {
+            mem::transmute_copy::<&SeStr<S, WinUnicode>, &SeStr<S, Wide>>(&self)
+        }
+    }
+}
+
+/**
+On Windows, `wchar_t` is 16 bits wide and holds UTF-16, so `Wide` and `Utf16` strings have identical representations, and can be freely reinterpreted as one another.
+
+This implementation does not exist on other platforms, so code which assumes a 16-bit `wchar_t` will fail to compile there.  To write code which works on every platform, use `wide_is_utf16!` or `Wide::WIDTH` to select between this and the `Utf32` equivalent.
+*/
+#[cfg(windows)]
+impl<S> SeStr<S, Wide> where S: Structure<Wide> + Structure<Utf16> {
+    /**
+    Re-borrows this string as a `Utf16` string, without copying.
+    */
+    pub fn as_utf16(&self) -> &SeStr<S, Utf16> {
+        unsafe {
+            mem::transmute_copy::<&SeStr<S, Wide>, &SeStr<S, Utf16>>(&self)
+        }
+    }
+}
+
+/**
+See the `Wide` implementation.
+*/
+#[cfg(windows)]
+impl<S> SeStr<S, Utf16> where S: Structure<Utf16> + Structure<Wide> {
+    /**
+    Re-borrows this string as a `Wide` string, without copying.
+    */
+    pub fn as_wide(&self) -> &SeStr<S, Wide> {
+        unsafe {
+            mem::transmute_copy::<&SeStr<S, Utf16>, &SeStr<S, Wide>>(&self)
+        }
+    }
+}
+
+/**
+This implementation provides bulk conversions for code page strings, which convert the whole string with a single call to `MultiByteToWideChar`, rather than one call per character.
+
+These give the same results as `transcode_to` and `into_string`, and are much faster for long strings.  The trade-off is that they are not lazy: the whole string is converted up front, into a temporary buffer.
+
+`MultiByte` is not covered, as it follows the C runtime's locale rather than a Windows code page; zero-terminated `MultiByte` strings have bulk conversions of their own.  Anything which needs to be lazy should use the transcoding iterators.
+*/
+#[cfg(windows)]
+impl<S, E> SeStr<S, E> where S: Structure<E>, E: CodePage {
+    /**
+    Transcodes this string into `Wide`, all at once.
+
+    # Failure
+
+    This conversion will fail if the string contains any invalid sequences, or if allocation fails.
+    */
+    pub fn transcode_to_wide_bulk<T, A>(&self) -> Result<SeaString<T, Wide, A>, Box<StdError>>
+    where
+        T: Structure<Wide> + StructureAlloc<Wide, A>,
+        A: Allocator,
+    {
+        let units = cp_to_wide_bulk(E::code_page(), self.as_raw_bytes())?;
+        Ok(SeaString::new(&units)?)
+    }
+
+    /**
+    Transcodes this string into a `String`, all at once.
+
+    # Failure
+
+    This conversion will fail if the string contains any invalid sequences.
+    */
+    pub fn into_string_bulk(&self) -> Result<String, Box<StdError>> {
+        let units = cp_to_wide_bulk(E::code_page(), self.as_raw_bytes())?;
+        // `MultiByteToWideChar` only produces valid UTF-16 when it is rejecting invalid input, so this cannot fail in practice.
+        Ok(::std::char::decode_utf16(units.iter().map(|u| u.0)).collect::<Result<String, _>>()?)
+    }
+}
+
+/**
+This implementation provides bulk conversions from `Wide` into code page strings, which convert the whole string with a single call to `WideCharToMultiByte`.
+
+See the `CodePage` implementation.
+*/
+#[cfg(windows)]
+impl<S> SeStr<S, Wide> where S: Structure<Wide> {
+    /**
+    Transcodes this string into a code page encoding, all at once.
+
+    # Failure
+
+    This conversion will fail if the string contains any characters which cannot be represented in the code page, or if allocation fails.
+    */
+    pub fn transcode_to_code_page_bulk<T, E, A>(&self) -> Result<SeaString<T, E, A>, Box<StdError>>
+    where
+        T: Structure<E> + StructureAlloc<E, A>,
+        E: CodePage,
+        A: Allocator,
+    {
+        let bytes = wide_to_cp_bulk(E::code_page(), self.as_units())?;
+        Ok(SeaString::new(SeStr::<Slice, E>::from_bytes(&bytes).as_units())?)
+    }
+}
+
+/**
+This implementation provides bulk conversions from zero-terminated `MultiByte` strings, which convert the whole string with a single call to `mbsrtowcs`, rather than one call to `mbrtowc` per character.
+
+If the bulk conversion fails, the string is converted again with the transcoding iterators, so that errors are reported exactly as `transcode_to` and `into_string` would report them.
+*/
+impl SeStr<ZeroTerm, MultiByte> {
+    /**
+    Transcodes this string into `Wide`, all at once.
+
+    This is the same as `transcode_to`, which uses the bulk conversion for zero-terminated `MultiByte` strings anyway; the result is converted directly into its own allocation.
+
+    # Failure
+
+    This conversion will fail if the string contains any invalid sequences, or if allocation fails.
+    */
+    pub fn transcode_to_wide_bulk<T, A>(&self) -> Result<SeaString<T, Wide, A>, Box<StdError>>
+    where
+        T: Structure<Wide> + StructureAlloc<Wide, A>,
+        A: Allocator,
+    {
+        self.transcode_to()
+    }
+
+    /**
+    Transcodes this string into a `String`, all at once.
+
+    # Failure
+
+    This conversion will fail if the string contains any units which cannot be translated into Unicode.
+    */
+    pub fn into_string_bulk(&self) -> Result<String, Box<StdError>> {
+        match mbs_to_wcs_bulk(self.as_units_with_term()) {
+            Some(units) => match SeStr::<Slice, Wide>::new(&units).into_string() {
+                Ok(s) => Ok(s),
+                // The wide units were not valid Unicode, so let the iterators say where.
+                Err(_) => self.into_string(),
+            },
+            None => self.into_string(),
+        }
+    }
+}
+
+/**
+This implementation provides conversions from zero-terminated `MultiByte` strings under an explicitly chosen C runtime locale, rather than whichever one happens to be active on the current thread.
+
+See the `locale` module.
+*/
+#[cfg(any(unix, windows))]
+impl SeStr<ZeroTerm, MultiByte> {
+    /**
+    Converts the contents of this string into a normal Rust string, interpreting it according to `locale`.
+
+    The conversion is unaffected by the current thread's locale, and by any calls to `setlocale` since the string was produced.  To decode a string under the locale it was produced in, capture that locale with `Locale::current` at the time.
+
+    # Failure
+
+    This conversion will fail if the string contains any units which cannot be translated into Unicode.
+    */
+    pub fn into_string_in_locale(&self, locale: &Locale) -> Result<String, Box<StdError>> {
+        locale.with(|| self.into_string_bulk())
+    }
+}
+
+/**
+This implementation provides bulk conversions from zero-terminated `Wide` strings into `MultiByte`, which convert the whole string with a single call to `wcsrtombs`.
+
+See the `MultiByte` implementation.
+*/
+impl SeStr<ZeroTerm, Wide> {
+    /**
+    Transcodes this string into `MultiByte`, all at once.
+
+    # Failure
+
+    This conversion will fail if the string contains any characters which cannot be represented in the current locale, or if allocation fails.
+    */
+    pub fn transcode_to_multibyte_bulk<T, A>(&self) -> Result<SeaString<T, MultiByte, A>, Box<StdError>>
+    where
+        T: Structure<MultiByte> + StructureAlloc<MultiByte, A>,
+        A: Allocator,
+    {
+        match wcs_to_mbs_bulk(self.as_units_with_term()) {
+            Some(units) => Ok(SeaString::new(&units)?),
+            None => self.transcode_to(),
+        }
+    }
+}
+
+/**
+On platforms other than Windows, `wchar_t` is 32 bits wide and holds UTF-32, so `Wide` and `Utf32` strings have identical representations, and can be freely reinterpreted as one another.
+
+This implementation does not exist on Windows, so code which assumes a 32-bit `wchar_t` will fail to compile there.  To write code which works on every platform, use `wide_is_utf32!` or `Wide::WIDTH` to select between this and the `Utf16` equivalent.
+*/
+#[cfg(not(windows))]
+impl<S> SeStr<S, Wide> where S: Structure<Wide> + Structure<Utf32> {
+    /**
+    Re-borrows this string as a `Utf32` string, without copying.
+    */
+    pub fn as_utf32(&self) -> &SeStr<S, Utf32> {
+        unsafe {
+            mem::transmute_copy::<&SeStr<S, Wide>, &SeStr<S, Utf32>>(&self)
+        }
+    }
+}
+
+/**
+See the `Wide` implementation.
+*/
+#[cfg(not(windows))]
+impl<S> SeStr<S, Utf32> where S: Structure<Utf32> + Structure<Wide> {
+    /**
+    Re-borrows this string as a `Wide` string, without copying.
+    */
+    pub fn as_wide(&self) -> &SeStr<S, Wide> {
+        unsafe {
+            mem::transmute_copy::<&SeStr<S, Utf32>, &SeStr<S, Wide>>(&self)
+        }
+    }
+}
+
+/**
+Where the C implementation defines `__STDC_UTF_16__`, `Char16` strings are UTF-16, and can be reinterpreted as `Utf16` strings.
+
+This cannot be detected from Rust, so it must be asserted by enabling the `stdc-utf` feature.
+*/
+#[cfg(feature="stdc-utf")]
+impl<S> SeStr<S, Char16> where S: Structure<Char16> + Structure<Utf16> {
+    /**
+    Re-borrows this string as a `Utf16` string, without copying.
+    */
+    pub fn as_utf16(&self) -> &SeStr<S, Utf16> {
+        unsafe {
+            mem::transmute_copy::<&SeStr<S, Char16>, &SeStr<S, Utf16>>(&self)
+        }
+    }
+}
+
+/**
+Where the C implementation defines `__STDC_UTF_32__`, `Char32` strings are UTF-32, and can be reinterpreted as `Utf32` strings.
+
+This cannot be detected from Rust, so it must be asserted by enabling the `stdc-utf` feature.
+*/
+#[cfg(feature="stdc-utf")]
+impl<S> SeStr<S, Char32> where S: Structure<Char32> + Structure<Utf32> {
+    /**
+    Re-borrows this string as a `Utf32` string, without copying.
+    */
+    pub fn as_utf32(&self) -> &SeStr<S, Utf32> {
+        unsafe {
+            mem::transmute_copy::<&SeStr<S, Char32>, &SeStr<S, Utf32>>(&self)
+        }
+    }
+}
+
+/**
+This implementation allows `Utf8` strings to be validated, producing a `CheckedUtf8` string.
+*/
+impl<S> SeStr<S, Utf8> where S: Structure<Utf8> + Structure<CheckedUtf8> {
+    /**
+    Validates that this string contains well-formed UTF-8, and re-borrows it as a `CheckedUtf8` string, without copying.
+
+    # Failure
+
+    This method will fail if the string contains any invalid, overlong, or truncated sequences, or any encoded surrogates.
+    */
+    pub fn validate_utf8(&self) -> Result<&SeStr<S, CheckedUtf8>, Utf8ValidationError> {
+        validate_utf8(self.as_units())?;
+        unsafe {
+            Ok(mem::transmute_copy::<&SeStr<S, Utf8>, &SeStr<S, CheckedUtf8>>(&self))
+        }
+    }
+
+    /**
+    Re-borrows this string as a Rust string slice, without copying.
+
+    This is equivalent to `validate_utf8` followed by `as_str`, but reports failure using the standard library's error type.
+
+    # Failure
+
+    This method will fail if the string is not well-formed UTF-8.
+    */
+    pub fn to_str(&self) -> Result<&str, str::Utf8Error> {
+        str::from_utf8(self.as_raw_bytes())
+    }
+
+    /**
+    Returns `true` if this string contains well-formed UTF-8.
+
+    This accepts exactly what `validate_utf8` does, and is as fast, but does not say where the problem is.
+    */
+    pub fn is_valid_utf8(&self) -> bool {
+        validate_utf8(self.as_units()).is_ok()
+    }
+}
+
+/**
+Strings in the `CheckedUtf8` encoding are expected to be valid, so they can be converted to Rust strings without transcoding.
+*/
+impl<S> SeStr<S, CheckedUtf8> where S: Structure<CheckedUtf8> + Structure<Utf8> {
+    /**
+    Re-borrows this string as a Rust string slice, without copying.
+
+    The contents are still checked, since a `CheckedUtf8` string can be sliced, or assembled from individual units, without regard to character boundaries.
+
+    # Panics
+
+    This method will panic if the string is not well-formed UTF-8.
+    */
+    pub fn as_str(&self) -> &str {
+        let units = self.as_units();
+        // `CheckedUtf8Unit` is a `repr(C)` wrapper around `u8`.
+        let bytes = unsafe { &*(units as *const [CheckedUtf8Unit] as *const [u8]) };
+        str::from_utf8(bytes).expect("CheckedUtf8 string is not valid UTF-8")
+    }
+
+    /**
+    Re-borrows this string as a `Utf8` string, without copying.
+    */
+    pub fn as_utf8(&self) -> &SeStr<S, Utf8> {
+        unsafe {
+            mem::transmute_copy::<&SeStr<S, CheckedUtf8>, &SeStr<S, Utf8>>(&self)
+        }
+    }
+}
+
+impl<'a, S> From<&'a SeStr<S, CheckedUtf8>> for String where S: Structure<CheckedUtf8> + Structure<Utf8> {
+    fn from(v: &'a SeStr<S, CheckedUtf8>) -> Self {
+        v.as_str().to_owned()
+    }
+}
+
+/**
+This implementation applies to encodings which can be decoded directly from their units.
+*/
+impl<S, E> SeStr<S, E> where S: Structure<E>, E: DecodeUnits {
+    /**
+    Returns an iterator over the characters of this string.
+
+    Decoding is performed lazily, and embedded zeroes are decoded like any other character.  Encodings which cannot be decoded without outside state, such as `MultiByte`, have no `chars`; use `chars_lossy` or `transcode_to_iter` for those.
+
+    # Failure
+
+    An `Err` is produced in place of each invalid or incomplete sequence, and decoding resumes immediately after it.
+    */
+    pub fn chars(&self) -> Chars<E> {
+        Chars {
+            iter: self.char_indices(),
+        }
+    }
+
+    /**
+    Returns an iterator over the characters of this string, and the offsets, in units, at which they start.
+
+    Decoding is performed lazily, and embedded zeroes are decoded like any other character.
+
+    # Failure
+
+    As with `chars`, an `Err` is produced in place of each invalid or incomplete sequence, and decoding resumes immediately after it.
+    */
+    pub fn char_indices(&self) -> CharIndices<E> {
+        CharIndices {
+            units: self.as_units(),
+            at: 0,
+        }
+    }
+}
+
+/**
+This implementation applies to encodings which can be checked for well-formedness.
+*/
+impl<S, E> SeStr<S, E> where S: Structure<E>, E: ValidateEncoding {
+    /**
+    Checks that this string is well-formed in its encoding, without transcoding it.
+
+    # Failure
+
+    On failure, the error gives the length of the well-formed prefix of the string, and the length of the invalid sequence which follows it.
+    */
+    pub fn validate_encoding(&self) -> Result<(), EncodingError> {
+        E::validate(self.as_units())
+    }
+}
+
+/**
+This implementation applies to encodings which are supersets of ASCII, allowing ASCII letters to be case-folded without transcoding.
+
+Only units in the range `A`–`Z` and `a`–`z` are affected; all other units, including everything at or above `0x80`, are left untouched.
+*/
+impl<S, E> SeStr<S, E> where S: Structure<E>, E: AsciiCompatible {
+    /**
+    Returns `true` if this string and `other` are equal, ignoring the case of ASCII letters.
+    */
+    pub fn eq_ignore_ascii_case<T>(&self, other: &SeStr<T, E>) -> bool where T: Structure<E> {
+        let (a, b) = (self.as_units(), other.as_units());
+        a.len() == b.len()
+            && a.iter().zip(b).all(|(a, b)| fold_ascii_lower(a.to_raw()) == fold_ascii_lower(b.to_raw()))
+    }
+
+    /**
+    Returns a copy of this string with all ASCII letters converted to lowercase.
+
+    # Failure
+
+    This method can fail if the allocator is unable to allocate sufficient memory.
+    */
+    pub fn to_ascii_lowercase<A>(&self) -> Result<SeaString<S, E, A>, A::AllocError>
+    where
+        S: StructureAlloc<E, A>,
+        A: Allocator,
+    {
+        let units: Vec<_> = self.as_units().iter().map(|&u| unit_to_ascii_lowercase(u)).collect();
+        SeaString::new(&units)
+    }
+
+    /**
+    Returns a copy of this string with all ASCII letters converted to uppercase.
+
+    # Failure
+
+    This method can fail if the allocator is unable to allocate sufficient memory.
+    */
+    pub fn to_ascii_uppercase<A>(&self) -> Result<SeaString<S, E, A>, A::AllocError>
+    where
+        S: StructureAlloc<E, A>,
+        A: Allocator,
+    {
+        let units: Vec<_> = self.as_units().iter().map(|&u| unit_to_ascii_uppercase(u)).collect();
+        SeaString::new(&units)
+    }
+}
+
+/**
+This implementation allows ASCII letters to be case-folded in place.
+*/
+impl<S, E> SeStr<S, E> where S: Structure<E> + MutationSafe, E: AsciiCompatible + MutableUnits {
+    /**
+    Converts all ASCII letters in this string to lowercase, in place.
+    */
+    pub fn make_ascii_lowercase(&mut self) {
+        for unit in self.as_units_mut() {
+            *unit = unit_to_ascii_lowercase(*unit);
+        }
+    }
+
+    /**
+    Converts all ASCII letters in this string to uppercase, in place.
+    */
+    pub fn make_ascii_uppercase(&mut self) {
+        for unit in self.as_units_mut() {
+            *unit = unit_to_ascii_uppercase(*unit);
+        }
+    }
+}
+
+fn fold_ascii_lower(v: u32) -> u32 {
+    match v {
+        0x41 ... 0x5a => v + 0x20,
+        v => v,
+    }
+}
+
+fn fold_ascii_upper(v: u32) -> u32 {
+    match v {
+        0x61 ... 0x7a => v - 0x20,
+        v => v,
+    }
+}
+
+fn unit_to_ascii_lowercase<U>(unit: U) -> U where U: Unit {
+    // Folding an ASCII letter always produces another ASCII letter, which every `AsciiCompatible` unit can represent.
+    U::from_raw(fold_ascii_lower(unit.to_raw())).unwrap_or(unit)
+}
+
+fn unit_to_ascii_uppercase<U>(unit: U) -> U where U: Unit {
+    U::from_raw(fold_ascii_upper(unit.to_raw())).unwrap_or(unit)
+}
+
+/**
+This implementation provides methods for structures which store their length directly.
+*/
+impl<S, E> SeStr<S, E> where S: Structure<E> + KnownLength, E: Encoding {
+    /**
+    Returns the length of this string, in units.  This *does not* include any structural data (including terminating units).
+
+    # Efficiency
+
+    This method is guaranteed to be *O*(1).
+    */
+    pub fn len(&self) -> usize {
+        self.unit_len()
+    }
+}
+
+/**
+This implementation only applies to string structures which are safe to mutate without the risk of truncation or corruption, in encodings where any units may be written.
+*/
+impl<S, E> SeStr<S, E> where S: Structure<E> + MutationSafe, E: MutableUnits {
+    /**
+    Returns the units comprising the content of this string as a contiguous slice.  This *does not* include any structural data (including terminating units).
+
+    # Efficiency
+
+    For structures where the length of the string is not stored directly, this may require a complete traversal of the underlying memory.  You should avoid calling this method repeatedly.
+
+    This method is guaranteed to be *O*(1) if `S` implements the `KnownLength` trait.
+    */
+    pub fn as_units_mut(&mut self) -> &mut [E::Unit] {
+        unsafe { self.as_units_mut_unsafe() }
+    }
+
+    /**
+    Mutably re-borrows this string as a `SeStr<Slice, E>`.  This can be used to normalise string representations, or to "pre-compute" the length of a foreign string before further processing.
+    */
+    pub fn as_slice_mut(&mut self) -> &mut SeStr<Slice, E> {
+        unsafe { self.as_slice_mut_unsafe() }
+    }
+}
+
+/**
+This implementation only applies to string structures that end with a zero terminator.
+*/
+impl<S, E> SeStr<S, E> where S: ZeroTerminated<E>, E: Encoding {
+    pub fn as_units_with_term(&self) -> &[E::Unit] {
+        S::slice_units_with_term(&self.data)
+    }
+}
+
+impl<S, E> AsMut<Self> for SeStr<S, E> where S: Structure<E>, E: Encoding {
+    fn as_mut(&mut self) -> &mut Self {
+        self
+    }
+}
+
+impl<S, E> AsRef<Self> for SeStr<S, E> where S: Structure<E>, E: Encoding {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl<S, E> Debug for SeStr<S, E> where S: Structure<E>, E: Encoding {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}{}", S::debug_prefix(), E::debug_prefix())?;
+        fmt_debug_units(self.as_units(), fmt)
+    }
+}
+
+impl<'a, S, E> Default for &'a SeStr<S, E> where S: Structure<E> + StructureDefault<E>, E: Encoding {
+    fn default() -> Self {
+        unsafe { mem::transmute::<&S::RefTarget, &SeStr<_, _>>(S::default()) }
+    }
+}
+
+impl<S, E> Eq for SeStr<S, E> where S: Structure<E>, E: Encoding {}
+
+/**
+Strings are hashed by their units, so strings of the same encoding hash equally if, and only if, they compare equally, regardless of structure.
+
+Strings in *different* encodings will generally hash differently, even if they represent the same text; use `hash_decoded` for that.
+*/
+impl<S, E> Hash for SeStr<S, E> where S: Structure<E>, E: Encoding {
+    fn hash<H>(&self, state: &mut H) where H: Hasher {
+        Hash::hash_slice(self.as_units(), state)
+    }
+}
+
+impl<S, E> Ord for SeStr<S, E>
+where
+    S: Structure<E>,
+    E: Encoding,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_units().cmp(other.as_units())
+    }
+}
+
+impl<S, E, T> PartialOrd<SeStr<T, E>> for SeStr<S, E>
+where
+    S: Structure<E>,
+    E: Encoding,
+    T: Structure<E>,
+{
+    fn partial_cmp(&self, other: &SeStr<T, E>) -> Option<Ordering> {
+        self.as_units().partial_cmp(other.as_units())
+    }
+}
+
+impl<S, E, T> PartialEq<SeStr<T, E>> for SeStr<S, E>
+where
+    S: Structure<E>,
+    E: Encoding,
+    T: Structure<E>,
+{
+    fn eq(&self, other: &SeStr<T, E>) -> bool {
+        self.as_units().eq(other.as_units())
+    }
+}
+
+impl<S, E> PartialEq<[u8]> for SeStr<S, E> where S: Structure<E>, E: ByteEncoding {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.as_raw_bytes() == other
+    }
+}
+
+impl<'a, S, E> PartialEq<&'a [u8]> for SeStr<S, E> where S: Structure<E>, E: ByteEncoding {
+    fn eq(&self, other: &&'a [u8]) -> bool {
+        self.as_raw_bytes() == *other
+    }
+}
+
+/**
+Compares the raw bytes of the string with the UTF-8 bytes of `other`.
+
+This is exact for `Utf8`.  For other ASCII-compatible encodings, it is a byte-wise comparison, *not* a comparison of characters: non-ASCII characters will generally not compare equal even where they are the same character.  Use `SeStr::eq_str` to compare decoded characters.
+*/
+impl<S, E> PartialEq<str> for SeStr<S, E> where S: Structure<E>, E: ByteEncoding + AsciiCompatible {
+    fn eq(&self, other: &str) -> bool {
+        self.as_raw_bytes() == other.as_bytes()
+    }
+}
+
+impl<'a, S, E> PartialEq<&'a str> for SeStr<S, E> where S: Structure<E>, E: ByteEncoding + AsciiCompatible {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_raw_bytes() == other.as_bytes()
+    }
+}
+
+impl<S, E> ToOwned for SeStr<S, E>
+where
+    S: Structure<E> + StructureAlloc<E, Malloc>,
+    E: Encoding,
+{
+    type Owned = SeaString<S, E, Malloc>;
+
+    fn to_owned(&self) -> SeaString<S, E, Malloc> {
+        self.to_owned_by().expect("could not allocate SeaString")
+    }
+}
+
+/**
+The default value of `debug_limit`.
+*/
+pub const DEFAULT_DEBUG_LIMIT: usize = 1024;
+
+/*
+`usize::MAX` stands in for "no limit"; no string can be that many units long anyway.
+*/
+static DEBUG_LIMIT: AtomicUsize = AtomicUsize::new(DEFAULT_DEBUG_LIMIT);
+
+/**
+Returns the maximum number of units written by the `Debug` implementations of `SeStr` and `SeaString`, or `None` if output is not limited.
+
+This defaults to `DEFAULT_DEBUG_LIMIT`.
+*/
+pub fn debug_limit() -> Option<usize> {
+    match DEBUG_LIMIT.load(AtomicOrdering::Relaxed) {
+        usize::MAX => None,
+        limit => Some(limit),
+    }
+}
+
+/**
+Sets the maximum number of units written by the `Debug` implementations of `SeStr` and `SeaString`.  Passing `None` removes the limit entirely.
+
+This setting is global to the process.
+*/
+pub fn set_debug_limit(limit: Option<usize>) {
+    DEBUG_LIMIT.store(limit.unwrap_or(usize::MAX), AtomicOrdering::Relaxed);
+}
+
+/**
+The default value of `context_limit`.
+*/
+pub const DEFAULT_CONTEXT_LIMIT: usize = 32;
+
+static CONTEXT_LIMIT: AtomicUsize = AtomicUsize::new(DEFAULT_CONTEXT_LIMIT);
+
+/**
+Returns the maximum number of units captured in the snapshot of a `ContextError`.
+
+This defaults to `DEFAULT_CONTEXT_LIMIT`.  Unlike `debug_limit`, there is always a limit.
+*/
+pub fn context_limit() -> usize {
+    CONTEXT_LIMIT.load(AtomicOrdering::Relaxed)
+}
+
+/**
+Sets the maximum number of units captured in the snapshot of a `ContextError`.
+
+This setting is global to the process.
+*/
+pub fn set_context_limit(limit: usize) {
+    CONTEXT_LIMIT.store(limit, AtomicOrdering::Relaxed);
+}
+
+/**
+An error which carries a snapshot of the string it came from.
+
+This is returned by the `_ctx` variants of the conversion methods, such as `SeStr::into_string_ctx`.  The snapshot is the string's debug representation, truncated to `context_limit` units, and is captured when the error occurs, so it remains available after the string itself is gone.
+
+Note that the snapshot contains the string's contents.  Code which handles sensitive data should use the plain conversion methods instead.
+*/
+#[derive(Debug)]
+pub struct ContextError {
+    snapshot: String,
+    err: Box<StdError>,
+}
+
+impl ContextError {
+    fn new<S, E>(s: &SeStr<S, E>, err: Box<StdError>) -> Self where S: Structure<E>, E: Encoding {
+        struct Snapshot<'a, U: 'a>(&'a [U], usize);
+
+        impl<'a, U> fmt::Display for Snapshot<'a, U> where U: UnitDebug {
+            fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+                fmt_debug_units_limit(self.0, Some(self.1), fmt)
+            }
+        }
+
+        let snapshot = format!("{}{}{}", S::debug_prefix(), E::debug_prefix(),
+            Snapshot(s.as_units(), context_limit()));
+        ContextError {
+            snapshot: snapshot,
+            err: err,
+        }
+    }
+
+    /**
+    Returns the snapshot of the string which caused the error.
+    */
+    pub fn snapshot(&self) -> &str {
+        &self.snapshot
+    }
+
+    /**
+    Returns the underlying error.
+    */
+    pub fn into_inner(self) -> Box<StdError> {
+        self.err
+    }
+}
+
+impl fmt::Display for ContextError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{} in {}", self.err, self.snapshot)
+    }
+}
+
+impl StdError for ContextError {
+    fn description(&self) -> &str {
+        self.err.description()
+    }
+
+    fn cause(&self) -> Option<&StdError> {
+        Some(&*self.err)
+    }
+}
+
+/**
+The outcome of transcoding into a fixed-size buffer.
+
+See `SeStr::transcode_into_slice`.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TranscodeInto {
+    /**
+    The number of units written to the start of the buffer, not counting any terminator.
+    */
+    pub units_written: usize,
+
+    /**
+    Whether any of the string was left out because the buffer was too small.
+    */
+    pub truncated: bool,
+}
+
+/**
+Feeds decoded characters to the encoder used by `SeStr::transcode_into_slice`, noting where in the output each one starts.
+
+Every time the encoder asks for another character, the number of units written so far is recorded as a boundary.  Encoders finish producing the units for one character before asking for the next, so each boundary falls between two characters of the output, and the last one seen is where a truncated string can safely be cut.  A decoding error is set aside, and ends the characters.  This is not normally used directly.
+*/
+pub struct CharBoundaries<It> {
+    iter: It,
+    counts: Rc<BoundaryCounts>,
+}
+
+/**
+The state shared between `CharBoundaries` and the code writing out the encoded units.
+*/
+struct BoundaryCounts {
+    written: Cell<usize>,
+    boundary: Cell<usize>,
+    err: RefCell<Option<Box<StdError>>>,
+}
+
+impl<It> CharBoundaries<It> {
+    fn new(iter: It) -> (Self, Rc<BoundaryCounts>) {
+        let counts = Rc::new(BoundaryCounts {
+            written: Cell::new(0),
+            boundary: Cell::new(0),
+            err: RefCell::new(None),
+        });
+        (CharBoundaries { iter: iter, counts: counts.clone() }, counts)
+    }
+}
+
+impl<It, Err> Iterator for CharBoundaries<It> where It: Iterator<Item=Result<char, Err>>, Err: StdError + 'static {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        self.counts.boundary.set(self.counts.written.get());
+        if self.counts.err.borrow().is_some() {
+            return None;
+        }
+        match self.iter.next() {
+            Some(Ok(c)) => Some(c),
+            Some(Err(err)) => {
+                *self.counts.err.borrow_mut() = Some(Box::new(err));
+                None
+            },
+            None => None,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // An error can end the characters at any point.
+        (0, self.iter.size_hint().1)
+    }
+}
+
+/**
+Determines what `SeStr::transcode_with` does when it encounters units which cannot be transcoded.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReplacementPolicy {
+    /**
+    Replace each invalid sequence with U+FFFD REPLACEMENT CHARACTER.
+    */
+    Unicode,
+
+    /**
+    Replace each invalid sequence with the given character.
+    */
+    Char(char),
+
+    /**
+    Drop invalid sequences entirely.
+    */
+    Skip,
+
+    /**
+    Stop at the first invalid sequence, keeping everything transcoded before it.
+    */
+    StopAtError,
+}
+
+/**
+Describes a single place where `SeStr::transcode_with` applied its `ReplacementPolicy`.
+*/
+#[derive(Debug)]
+pub struct TranscodeIssue {
+    at: Option<usize>,
+    err: Box<StdError>,
+}
+
+impl TranscodeIssue {
+    /**
+    Returns the offset, in source units, of the first unit which could not be transcoded.
+
+    This is `None` if the source string ended part-way through a sequence.
+    */
+    pub fn at(&self) -> Option<usize> {
+        self.at
+    }
+
+    /**
+    Returns the underlying error.
+    */
+    pub fn error(&self) -> &StdError {
+        &*self.err
+    }
+
+    /**
+    Returns the underlying error.
+    */
+    pub fn into_inner(self) -> Box<StdError> {
+        self.err
+    }
+}
+
+impl fmt::Display for TranscodeIssue {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.err, fmt)
+    }
+}
+
+/**
+Writes the quoted debug representation of `units`, truncated according to `debug_limit`.
+*/
+pub(crate) fn fmt_debug_units<U>(units: &[U], fmt: &mut fmt::Formatter) -> fmt::Result where U: UnitDebug {
+    fmt_debug_units_limit(units, debug_limit(), fmt)
+}
+
+/**
+Writes the quoted debug representation of `units`, truncated to `limit` units.
+*/
+fn fmt_debug_units_limit<U>(units: &[U], limit: Option<usize>, fmt: &mut fmt::Formatter) -> fmt::Result where U: UnitDebug {
+    let shown = match limit {
+        Some(limit) if limit < units.len() => limit,
+        _ => units.len(),
+    };
+
+    write!(fmt, "\"")?;
+    for unit in &units[..shown] {
+        UnitDebug::fmt(unit, fmt)?;
+    }
+    write!(fmt, "\"")?;
+
+    if shown < units.len() {
+        write!(fmt, "... ({} units)", units.len())?;
+    }
+    Ok(())
+}
+
+/**
+Implements `SeStr::escape_units_as`.
+*/
+fn escape_units<U>(units: &[U], style: EscapeStyle) -> String where U: Unit {
+    use std::fmt::Write;
+
+    let mut out = String::with_capacity(units.len() + 2);
+    out.push('"');
+
+    // Set when the last thing written was a C hex escape, which would absorb any following hex digits.
+    let mut open_hex = false;
+
+    for unit in units {
+        let v = unit.to_raw();
+
+        let named = match v {
+            0x09 => Some("\\t"),
+            0x0a => Some("\\n"),
+            0x0d => Some("\\r"),
+            0x22 => Some("\\\""),
+            0x5c => Some("\\\\"),
+            _ => None,
+        };
+        if let Some(esc) = named {
+            out.push_str(esc);
+            open_hex = false;
+            continue;
+        }
+
+        if 0x20 <= v && v <= 0x7e {
+            let c = v as u8 as char;
+            if open_hex && c.is_digit(16) {
+                out.push_str("\"\"");
+            }
+            out.push(c);
+            open_hex = false;
+            continue;
+        }
+
+        open_hex = false;
+
+        // Writing to a `String` cannot fail.
+        let _ = match style {
+            EscapeStyle::RustLike if v == 0 => write!(out, "\\0"),
+            EscapeStyle::RustLike if U::BITS <= 8 => write!(out, "\\x{:02x}", v),
+            EscapeStyle::RustLike => write!(out, "\\u{{{:x}}}", v),
+
+            EscapeStyle::CLike if U::BITS <= 8 => write!(out, "\\{:03o}", v),
+            EscapeStyle::CLike => {
+                open_hex = true;
+                write!(out, "\\x{:x}", v)
+            },
+
+            EscapeStyle::Json if v <= 0xffff => write!(out, "\\u{:04x}", v),
+            EscapeStyle::Json if v <= 0x10ffff => {
+                let v = v - 0x10000;
+                write!(out, "\\u{:04x}\\u{:04x}", 0xd800 + (v >> 10), 0xdc00 + (v & 0x3ff))
+            },
+            EscapeStyle::Json => write!(out, "\\ufffd"),
+        };
+    }
+
+    out.push('"');
+    out
+}
+
+/**
+A borrowed string which came directly from foreign code.
+
+This is returned by `SeStr::from_ptr_tagged`.  It dereferences to `SeStr`, so it can be used just like the result of `from_ptr`, but its type records that its contents have not been checked in any way.
+
+# Auditing
+
+The intended workflow is to use `from_ptr_tagged` in place of `from_ptr` wherever untrusted strings enter a program, and to keep the `Foreign` wrapper for as long as the string is untrusted.  The points at which trust is established are then marked in the code by one of:
+
+- `assume_checked`, which removes the tag once the contents have been checked by some means, or
+- `to_owned_by`, or `SeStr::to_owned`, which copy the string into memory owned by Rust.
+
+Reviewers can then search for these, and for `from_ptr` itself, to find every point at which foreign data crosses into the program.  Function signatures which accept `Foreign` make it clear that they are expected to cope with arbitrary contents.
 */
-impl<S, E> SeStr<S, E> where S: ZeroTerminated<E>, E: Encoding {
-    pub fn as_units_with_term(&self) -> &[E::Unit] {
-        S::slice_units_with_term(&self.data)
+pub struct Foreign<'a, S, E> where S: 'a + Structure<E>, E: 'a + Encoding {
+    s: &'a SeStr<S, E>,
+}
+
+impl<'a, S, E> Foreign<'a, S, E> where S: Structure<E>, E: Encoding {
+    /**
+    Removes the tag, asserting that the string's contents are trusted.
+
+    This does not check anything; it exists so that the point at which a foreign string is accepted is visible in the code.
+    */
+    pub fn assume_checked(self) -> &'a SeStr<S, E> {
+        self.s
+    }
+
+    /**
+    Copies the string into a new `SeaString`, which is no longer tagged as foreign.
+    */
+    pub fn to_owned_by<A>(&self) -> Result<SeaString<S, E, A>, A::AllocError>
+    where
+        S: StructureAlloc<E, A>,
+        A: Allocator,
+    {
+        self.s.to_owned_by()
     }
 }
 
-impl<S, E> AsMut<Self> for SeStr<S, E> where S: Structure<E>, E: Encoding {
-    fn as_mut(&mut self) -> &mut Self {
-        self
+impl<'a, S, E> AsRef<SeStr<S, E>> for Foreign<'a, S, E> where S: Structure<E>, E: Encoding {
+    fn as_ref(&self) -> &SeStr<S, E> {
+        self.s
     }
 }
 
-impl<S, E> AsRef<Self> for SeStr<S, E> where S: Structure<E>, E: Encoding {
-    fn as_ref(&self) -> &Self {
-        self
+impl<'a, S, E> Clone for Foreign<'a, S, E> where S: Structure<E>, E: Encoding {
+    fn clone(&self) -> Self {
+        *self
     }
 }
 
-impl<S, E> Debug for SeStr<S, E> where S: Structure<E>, E: Encoding {
+impl<'a, S, E> Copy for Foreign<'a, S, E> where S: Structure<E>, E: Encoding {}
+
+impl<'a, S, E> Debug for Foreign<'a, S, E> where S: Structure<E>, E: Encoding {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        write!(fmt, "{}{}\"", S::debug_prefix(), E::debug_prefix())?;
-        for unit in self.as_units() {
-            UnitDebug::fmt(unit, fmt)?;
-        }
-        write!(fmt, "\"")
+        write!(fmt, "Foreign(")?;
+        Debug::fmt(self.s, fmt)?;
+        write!(fmt, ")")
     }
 }
 
-impl<'a, S, E> Default for &'a SeStr<S, E> where S: Structure<E> + StructureDefault<E>, E: Encoding {
-    fn default() -> Self {
-        unsafe { mem::transmute::<&S::RefTarget, &SeStr<_, _>>(S::default()) }
+impl<'a, S, E> Deref for Foreign<'a, S, E> where S: Structure<E>, E: Encoding {
+    type Target = SeStr<S, E>;
+
+    fn deref(&self) -> &SeStr<S, E> {
+        self.s
     }
 }
 
-impl<S, E> Eq for SeStr<S, E> where S: Structure<E>, E: Encoding {}
+/**
+A string which is either borrowed, or owned and allocated with `Malloc`.
 
-impl<S, E> Hash for SeStr<S, E> where S: Structure<E>, E: Encoding {
-    fn hash<H>(&self, state: &mut H) where H: Hasher {
-        Hash::hash_slice(self.as_units(), state)
+This is returned by `SeStr::transcode_to_cow`, and dereferences to `SeStr` either way.
+*/
+pub enum SeaCow<'a, S, E>
+where
+    S: 'a + Structure<E> + StructureAlloc<E, Malloc>,
+    E: 'a + Encoding,
+{
+    /**
+    A string borrowed without conversion.
+    */
+    Borrowed(&'a SeStr<S, E>),
+
+    /**
+    A newly allocated string.
+    */
+    Owned(SeaString<S, E, Malloc>),
+}
+
+impl<'a, S, E> SeaCow<'a, S, E>
+where
+    S: Structure<E> + StructureAlloc<E, Malloc>,
+    E: Encoding,
+{
+    /**
+    Returns `true` if this string is borrowed.
+    */
+    pub fn is_borrowed(&self) -> bool {
+        match *self {
+            SeaCow::Borrowed(_) => true,
+            SeaCow::Owned(_) => false,
+        }
+    }
+
+    /**
+    Returns an owned string, copying the contents if they are borrowed.
+
+    # Failure
+
+    This method can fail if the string is borrowed, and allocating the copy fails.
+    */
+    pub fn into_owned(self) -> Result<SeaString<S, E, Malloc>, AllocError> {
+        match self {
+            SeaCow::Borrowed(s) => s.to_owned_by(),
+            SeaCow::Owned(s) => Ok(s),
+        }
     }
 }
 
-impl<S, E> Ord for SeStr<S, E>
+impl<'a, S, E> Deref for SeaCow<'a, S, E>
 where
-    S: Structure<E>,
+    S: Structure<E> + StructureAlloc<E, Malloc>,
     E: Encoding,
 {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.as_units().cmp(other.as_units())
+    type Target = SeStr<S, E>;
+
+    fn deref(&self) -> &SeStr<S, E> {
+        match *self {
+            SeaCow::Borrowed(s) => s,
+            SeaCow::Owned(ref s) => s,
+        }
+    }
+}
+
+impl<'a, S, E> Debug for SeaCow<'a, S, E>
+where
+    S: Structure<E> + StructureAlloc<E, Malloc>,
+    E: Encoding,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SeaCow::Borrowed(s) => {
+                write!(fmt, "Borrowed(")?;
+                Debug::fmt(s, fmt)?;
+            },
+            SeaCow::Owned(ref s) => {
+                write!(fmt, "Owned(")?;
+                Debug::fmt(s, fmt)?;
+            },
+        }
+        write!(fmt, ")")
+    }
+}
+
+/**
+Represents an owned foreign string.
+
+`SeaString`s can be constructed either from slices of units, by transcoding a `SeStr`, by using `to_owned_as` on a `SeStr`, or by taking ownership from a raw FFI pointer type.
+
+Note that this type *always* transfers ownership.  Passing a `SeaString` to a foreign interface expecting a *borrowed* string will result in a memory leak.  Taking ownership of a borrowed string from a foreign interface will likely result in double-free or heap errors.
+
+`SeaString`s can be converted trivially into a corresponding `SeStr` type, via `AsRef`/`AsMut`, `Borrow`/`BorrowMut`, or dereferencing.  Although mutation is supported, not all structures permit *safe* mutation; see `SeStr` for available methods.
+
+This type *may* be used in FFI signatures and types, but we nonetheless recommend not doing so, and explicitly using the `from_ptr` and `into_ptr` methods instead.
+
+# Parameters
+
+`S` defines the structure of the string data.  *e.g.* `ZeroTerm` for zero-terminated strings, and `Slice` for Rust-style fat pointers.
+
+`E` defines the encoding of the string data.  *e.g.* `MultiByte` for the current C runtime multibyte encoding, and `Wide` for C wide strings.
+
+`A` defines the allocator which manages the string data.  *e.g.* `Malloc` for the C runtime heap allocator, and `Rust` for the Rust heap allocator.
+
+# Threads
+
+A `SeaString` is `Send` and `Sync` only if its encoding is `LocaleIndependent` and its allocator is `ThreadSafeAllocator`.  In particular, strings in `MultiByte` or `Wide` *cannot* be sent between threads, and neither can strings allocated from an `Arena`.
+
+This is a correctness guard rather than a memory safety one.  The meaning of a `MultiByte` (or `Wide`) string depends on the C runtime locale, which may be set per-thread, and which `setlocale` can change underneath any thread at any time.  A string produced under one locale is meaningless under another: decoding it may produce the wrong text, or fail outright.  Transcode such strings to a fixed encoding, such as `Utf8` or `Utf16`, before handing them to another thread.
+
+Note that this *cannot* be enforced for borrowed strings: a `&SeStr` in any encoding can still be shared with a scoped thread.
+
+None of the following compile:
+
+```compile_fail
+# use strffi::alloc::Malloc; use strffi::encoding::MultiByte; use strffi::sea::SeaString; use strffi::structure::{Slice, ZeroTerm};
+fn assert_send<T: Send>() {}
+assert_send::<SeaString<ZeroTerm, MultiByte, Malloc>>();
+```
+
+```compile_fail
+# use strffi::alloc::Malloc; use strffi::encoding::MultiByte; use strffi::sea::SeaString; use strffi::structure::{Slice, ZeroTerm};
+fn assert_sync<T: Sync>() {}
+assert_sync::<SeaString<ZeroTerm, MultiByte, Malloc>>();
+```
+
+```compile_fail
+# use strffi::alloc::Malloc; use strffi::encoding::Wide; use strffi::sea::SeaString; use strffi::structure::{Slice, ZeroTerm};
+fn assert_send<T: Send>() {}
+assert_send::<SeaString<Slice, Wide, Malloc>>();
+```
+
+```compile_fail
+# use strffi::alloc::Malloc; use strffi::encoding::Wide; use strffi::sea::SeaString; use strffi::structure::{Slice, ZeroTerm};
+fn assert_sync<T: Sync>() {}
+assert_sync::<SeaString<Slice, Wide, Malloc>>();
+```
+
+```compile_fail
+# use strffi::alloc::Arena; use strffi::encoding::Utf8; use strffi::sea::SeaString; use strffi::structure::{Slice, ZeroTerm};
+fn assert_send<T: Send>() {}
+assert_send::<SeaString<Slice, Utf8, Arena>>();
+```
+
+```compile_fail
+# use strffi::alloc::Arena; use strffi::encoding::Utf8; use strffi::sea::SeaString; use strffi::structure::{Slice, ZeroTerm};
+fn assert_sync<T: Sync>() {}
+assert_sync::<SeaString<Slice, Utf8, Arena>>();
+```
+*/
+#[repr(transparent)]
+pub struct SeaString<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: Encoding,
+    A: Allocator,
+{
+    owned: S::Owned,
+    _marker: PhantomData<A>,
+}
+
+/*
+The owned pointer is unique, so the only thread-dependent parts of a `SeaString` are its allocator, which must allow freeing from another thread, and the interpretation of its contents.
+*/
+unsafe impl<E, A> Send for SeaString<ZeroTerm, E, A> where E: LocaleIndependent, A: ThreadSafeAllocator<Pointer=*mut ()> {}
+unsafe impl<E, A> Sync for SeaString<ZeroTerm, E, A> where E: LocaleIndependent, A: ThreadSafeAllocator<Pointer=*mut ()> {}
+unsafe impl<E, A> Send for SeaString<Slice, E, A> where E: LocaleIndependent, A: ThreadSafeAllocator<Pointer=*mut ()> {}
+unsafe impl<E, A> Sync for SeaString<Slice, E, A> where E: LocaleIndependent, A: ThreadSafeAllocator<Pointer=*mut ()> {}
+
+/**
+Implemented by owned string types which can be the target of `SeStr::transcode`.
+
+This allows a single type, such as `ZWCString` or `ZMbCString`, to stand in for a structure, encoding, and allocator triple.
+*/
+pub trait TranscodeTarget: Sized {
+    /**
+    The structure of the owned string.
+    */
+    type Structure: Structure<Self::Encoding> + StructureAlloc<Self::Encoding, Self::Allocator>;
+
+    /**
+    The encoding of the owned string.
+    */
+    type Encoding: Encoding;
+
+    /**
+    The allocator which manages the owned string.
+    */
+    type Allocator: Allocator;
+
+    /**
+    Wraps a transcoded string.
+    */
+    fn from_sea_string(s: SeaString<Self::Structure, Self::Encoding, Self::Allocator>) -> Self;
+}
+
+impl<S, E, A> TranscodeTarget for SeaString<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: Encoding,
+    A: Allocator,
+{
+    type Structure = S;
+    type Encoding = E;
+    type Allocator = A;
+
+    fn from_sea_string(s: Self) -> Self {
+        s
     }
 }
 
-impl<S, E, T> PartialOrd<SeStr<T, E>> for SeStr<S, E>
-where
-    S: Structure<E>,
-    E: Encoding,
-    T: Structure<E>,
-{
-    fn partial_cmp(&self, other: &SeStr<T, E>) -> Option<Ordering> {
-        self.as_units().partial_cmp(other.as_units())
+/*impl<S, E, A> SeaString<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: Encoding,
+    A: Allocator,
+{
+}*/
+
+/**
+General methods.
+*/
+impl<S, E, A> SeaString<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: Encoding,
+    A: Allocator,
+{
+    /**
+    Construct a `SeaString` from a slice of units.
+
+    # Failure
+
+    This method will fail if allocating memory fails.
+
+    Construction can also fail if the string contents provided are incompatible with the structure.  For example, it is invalid to construct a zero-terminated string with zero units in anywhere *other* than at the end; this is reported using `AllocatorError::interior_zero`.
+    */
+    pub fn new(units: &[E::Unit]) -> Result<Self, A::AllocError> {
+        let s = SeaString {
+            owned: S::alloc_owned(units)?,
+            _marker: PhantomData,
+        };
+        s.report_alloc();
+        Ok(s)
+    }
+
+    /**
+    Constructs a string containing `count` copies of `unit`.
+
+    This makes a single allocation, sized for the contents and any terminator.
+
+    # Failure
+
+    This method will fail if the size of the string overflows, or if allocating memory fails.
+
+    As with `new`, repeating a zero unit more than once will fail for zero-terminated strings.
+    */
+    pub fn repeat(unit: E::Unit, count: usize) -> Result<Self, A::AllocError> {
+        let s = SeaString {
+            owned: S::alloc_owned_repeat(unit, count)?,
+            _marker: PhantomData,
+        };
+        s.report_alloc();
+        Ok(s)
+    }
+
+    /**
+    Construct a `SeaString` from a Rust string.
+
+    # Failure
+
+    This method will fail if allocating memory fails.
+
+    Construction can also fail if the string contents provided are incompatible with the structure.  For example, a zero-terminated string cannot contain U+0000 anywhere *other* than at the end; rather than truncating the string, this is reported using `AllocatorError::interior_zero`.
+
+    An error will also be returned if the contents of the input string cannot be transcoded to the given encoding.
+
+    This works for any encoding which can be transcoded to from `CheckedUnicode`.  The same conversion is available as `TryFrom<&str>`.
+    */
+    pub fn from_str<'a>(s: &'a str) -> Result<Self, Box<StdError>>
+    where
+        UnitIter<CheckedUnicode, ::std::str::Chars<'a>>: TranscodeTo<E>,
+    {
+        let mut tc_err = Ok(());
+        let units: Vec<_> = UnitIter::new(s.chars())
+            .transcode()
+            .trap_err(&mut tc_err)
+            .collect();
+        let () = tc_err?;
+        let seas = SeaString::new(&units)?;
+        Ok(seas)
+    }
+
+    /**
+    Shortens this string to its first `new_len` units.
+
+    If `new_len` is not less than the current length, this has no effect.  Structures which store their length out-of-band just have it updated.  Zero-terminated structures are shortened in place by writing a new terminator at `new_len`, so `truncate_unsafe(0)` leaves an empty string.
+
+    # Safety
+
+    For encodings which implement `MutableUnits`, this method is not memory-unsafe; here, `unsafe` is used as a check against questionable behaviour.  For those which do not, such as `CheckedUtf8`, the caller must ensure that `new_len` falls on a character boundary, since cutting a sequence in half would leave the string invalid.
+
+    For structures which are not `MutationSafe`, this alters the structure of the string: the unit at `new_len` is overwritten, and the units after it can no longer be recovered.  For structures which are, use `truncate` instead.
+
+    # Panics
+
+    This method will panic if the structure cannot shorten the string in place, and allocating a replacement fails.
+    */
+    pub unsafe fn truncate_unsafe(&mut self, new_len: usize) {
+        if new_len >= self.as_units().len() {
+            return;
+        }
+
+        self.shrink_owned(new_len);
+    }
+
+    /**
+    Appends a Rust string to the end of this string, transcoding it as necessary.
+
+    # Failure
+
+    This method will fail if the contents of `s` cannot be transcoded to this string's encoding, or if allocating memory fails.  In either case, the string is left unchanged.
+    */
+    pub fn push_str<'a>(&mut self, s: &'a str) -> Result<(), Box<StdError>>
+    where
+        UnitIter<CheckedUnicode, ::std::str::Chars<'a>>: TranscodeTo<E>,
+    {
+        let mut tc_err = Ok(());
+        let units: Vec<_> = UnitIter::new(s.chars())
+            .transcode()
+            .trap_err(&mut tc_err)
+            .collect();
+        let () = tc_err?;
+        self.append_units(&units)?;
+        Ok(())
+    }
+
+    /**
+    Returns the number of bytes of memory owned by this string.
+
+    This includes the string's contents, any terminator, and any header or padding the allocator is known to add.  It does *not* include the bookkeeping overhead of the underlying heap, so it is a best-effort lower bound, intended for memory profiling.
+    */
+    pub fn allocated_bytes(&self) -> usize {
+        Self::owned_allocated_bytes(&self.owned)
     }
-}
 
-impl<S, E, T> PartialEq<SeStr<T, E>> for SeStr<S, E>
-where
-    S: Structure<E>,
-    E: Encoding,
-    T: Structure<E>,
-{
-    fn eq(&self, other: &SeStr<T, E>) -> bool {
-        self.as_units().eq(other.as_units())
+    fn owned_allocated_bytes(owned: &S::Owned) -> usize {
+        A::allocated_bytes(S::owned_bytes(owned), mem::align_of::<E::Unit>())
     }
-}
 
-impl<S, E> ToOwned for SeStr<S, E>
-where
-    S: Structure<E> + StructureAlloc<E, Malloc>,
-    E: Encoding,
-{
-    type Owned = SeaString<S, E, Malloc>;
+    #[cfg(feature="alloc-stats")]
+    fn report_alloc(&self) {
+        stats::report(stats::AllocEvent {
+            kind: stats::AllocEventKind::Alloc,
+            allocator: A::debug_prefix(),
+            bytes: self.allocated_bytes(),
+        });
+    }
 
-    fn to_owned(&self) -> SeaString<S, E, Malloc> {
-        self.to_owned_by().expect("could not allocate SeaString")
+    fn report_free(&self) {
+        Self::report_free_owned(&self.owned)
     }
-}
 
-/**
-Represents an owned foreign string.
+    #[cfg(feature="alloc-stats")]
+    fn report_free_owned(owned: &S::Owned) {
+        stats::report(stats::AllocEvent {
+            kind: stats::AllocEventKind::Free,
+            allocator: A::debug_prefix(),
+            bytes: Self::owned_allocated_bytes(owned),
+        });
+    }
 
-`SeaString`s can be constructed either from slices of units, by transcoding a `SeStr`, by using `to_owned_as` on a `SeStr`, or by taking ownership from a raw FFI pointer type.
+    #[cfg(feature="alloc-stats")]
+    fn report_shrink(&self, before: usize) {
+        let after = self.allocated_bytes();
+        if after < before {
+            stats::report(stats::AllocEvent {
+                kind: stats::AllocEventKind::Shrink,
+                allocator: A::debug_prefix(),
+                bytes: before - after,
+            });
+        }
+    }
 
-Note that this type *always* transfers ownership.  Passing a `SeaString` to a foreign interface expecting a *borrowed* string will result in a memory leak.  Taking ownership of a borrowed string from a foreign interface will likely result in double-free or heap errors.
+    #[cfg(not(feature="alloc-stats"))]
+    fn report_alloc(&self) {}
 
-`SeaString`s can be converted trivially into a corresponding `SeStr` type, via `AsRef`/`AsMut`, `Borrow`/`BorrowMut`, or dereferencing.  Although mutation is supported, not all structures permit *safe* mutation; see `SeStr` for available methods.
+    #[cfg(not(feature="alloc-stats"))]
+    fn report_free_owned(_: &S::Owned) {}
 
-This type *may* be used in FFI signatures and types, but we nonetheless recommend not doing so, and explicitly using the `from_ptr` and `into_ptr` methods instead.
+    /**
+    Shortens this string to its first `new_len` units, which must be less than its current length.
 
-# Parameters
+    A string shortened in place only reports the memory it no longer accounts for; one moved to a new allocation reports both allocations.
+    */
+    fn shrink_owned(&mut self, new_len: usize) {
+        #[cfg(feature="alloc-stats")]
+        let before = self.allocated_bytes();
+
+        match S::truncate_owned(&mut self.owned, new_len).expect("could not allocate SeaString") {
+            Some(mut old) => {
+                Self::report_free_owned(&old);
+                S::free_owned(&mut old);
+                self.report_alloc();
+            },
+            None => {
+                #[cfg(feature="alloc-stats")]
+                self.report_shrink(before);
+            },
+        }
+    }
 
-`S` defines the structure of the string data.  *e.g.* `ZeroTerm` for zero-terminated strings, and `Slice` for Rust-style fat pointers.
+    /**
+    Appends units to the end of this string, in any encoding; see `push_units`.
+    */
+    fn append_units(&mut self, units: &[E::Unit]) -> Result<(), A::AllocError> {
+        if units.is_empty() {
+            return Ok(());
+        }
 
-`E` defines the encoding of the string data.  *e.g.* `MultiByte` for the current C runtime multibyte encoding, and `Wide` for C wide strings.
+        if let Some(mut old) = S::push_owned(&mut self.owned, units)? {
+            Self::report_free_owned(&old);
+            S::free_owned(&mut old);
+            self.report_alloc();
+        }
+        Ok(())
+    }
+}
 
-`A` defines the allocator which manages the string data.  *e.g.* `Malloc` for the C runtime heap allocator, and `Rust` for the Rust heap allocator.
+/**
+Methods for encodings where any units may be appended.
 */
-#[repr(C)]
-pub struct SeaString<S, E, A>
+impl<S, E, A> SeaString<S, E, A>
 where
     S: Structure<E> + StructureAlloc<E, A>,
-    E: Encoding,
+    E: MutableUnits,
     A: Allocator,
 {
-    owned: S::Owned,
-    _marker: PhantomData<A>,
-}
+    /**
+    Appends units to the end of this string.
 
-/*impl<S, E, A> SeaString<S, E, A>
-where
-    S: Structure<E> + StructureAlloc<E, A>,
-    E: Encoding,
-    A: Allocator,
-{
-}*/
+    For most structures, this allocates a new buffer holding both the existing contents and `units`, then frees the old one.  Structures which track spare capacity (such as `Slice`) append in place where there is room, and otherwise grow geometrically, so that repeated appends take amortised linear time.  Any terminator required by the structure is preserved.
+
+    # Failure
+
+    This method will fail if allocating memory fails, in which case the string is left unchanged.
+
+    As with `new`, appending to a zero-terminated string will fail if `units` contains a zero unit anywhere other than at the end.
+    */
+    pub fn push_units(&mut self, units: &[E::Unit]) -> Result<(), A::AllocError> {
+        self.append_units(units)
+    }
+
+    /**
+    Appends a single unit to the end of this string.
+
+    See `push_units`.
+    */
+    pub fn push_unit(&mut self, unit: E::Unit) -> Result<(), A::AllocError> {
+        self.push_units(&[unit])
+    }
+}
 
 /**
-General methods.
+Methods for structures which are safe to mutate, in encodings where any units may be written.
 */
 impl<S, E, A> SeaString<S, E, A>
 where
-    S: Structure<E> + StructureAlloc<E, A>,
-    E: Encoding,
+    S: Structure<E> + StructureAlloc<E, A> + MutationSafe,
+    E: MutableUnits,
     A: Allocator,
 {
     /**
-    Construct a `SeaString` from a slice of units.
+    Retains only the units for which `f` returns `true`, preserving their order.
 
-    # Failure
+    If any units are removed, the string is shortened in place where the structure allows it; otherwise, its contents are moved to a new, smaller allocation.
 
-    This method will fail if allocating memory fails.
+    # Panics
 
-    Construction can also fail if the string contents provided are incompatible with the structure.  For example, it is invalid to construct a zero-terminated string with zero units in anywhere *other* than at the end.
+    This method will panic if a new allocation is needed and cannot be made.
     */
-    // TODO: what about interior zeroes?
-    pub fn new(units: &[E::Unit]) -> Result<Self, A::AllocError> {
-        Ok(SeaString {
-            owned: S::alloc_owned(units)?,
-            _marker: PhantomData,
-        })
+    pub fn retain<F>(&mut self, mut f: F) where F: FnMut(E::Unit) -> bool {
+        let (len, kept) = {
+            let units = self.as_units_mut();
+            let mut kept = 0;
+            for i in 0..units.len() {
+                let unit = units[i];
+                if f(unit) {
+                    units[kept] = unit;
+                    kept += 1;
+                }
+            }
+            (units.len(), kept)
+        };
+
+        if kept < len {
+            self.shrink_owned(kept);
+        }
     }
 
     /**
-    Construct a `SeaString` from a Rust string.
+    Shortens this string to its first `new_len` units.
 
-    # Failure
+    If `new_len` is not less than the current length, this has no effect.  Zero-terminated strings can be shortened with `truncate_unsafe`.
 
-    This method will fail if allocating memory fails.
+    # Panics
 
-    Construction can also fail if the string contents provided are incompatible with the structure.  For example, it is invalid to construct a zero-terminated string with zero units in anywhere *other* than at the end.
+    This method will panic if the structure cannot shorten the string in place, and allocating a replacement fails.
+    */
+    pub fn truncate(&mut self, new_len: usize) {
+        unsafe { self.truncate_unsafe(new_len) }
+    }
 
-    An error will also be returned if the contents of the input string cannot be transcoded to the given encoding.
+    /**
+    Replaces every occurrence of the unit `from` with `to`.
     */
-    pub fn from_str<'a>(s: &'a str) -> Result<Self, Box<StdError>>
-    where
-        UnitIter<CheckedUnicode, ::std::str::Chars<'a>>: TranscodeTo<E>,
-    {
-        let mut tc_err = Ok(());
-        let units: Vec<_> = UnitIter::new(s.chars())
-            .transcode()
-            .trap_err(&mut tc_err)
-            .collect();
-        let () = tc_err?;
-        let seas = SeaString::new(&units)?;
-        Ok(seas)
+    pub fn replace_unit(&mut self, from: E::Unit, to: E::Unit) {
+        for unit in self.as_units_mut() {
+            if *unit == from {
+                *unit = to;
+            }
+        }
+    }
+}
+
+/**
+Methods for encodings which can encode characters directly.
+*/
+impl<S, E, A> SeaString<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: EncodeChar,
+    A: Allocator,
+{
+    /**
+    Appends a single character to the end of this string.
+
+    # Failure
+
+    This method will fail if `c` cannot be represented in this string's encoding, or if allocating memory fails.  In either case, the string is left unchanged.
+    */
+    pub fn push_char(&mut self, c: char) -> Result<(), Box<StdError>> {
+        with_encoded::<E, _, _>(c, |units| self.append_units(units))??;
+        Ok(())
     }
 }
 
@@ -497,21 +2918,71 @@ where
     This method must *not* be called more than once on the same pointer.  The only hypothetical exception would be strings which use shared ownership.
     */
     pub unsafe fn from_ptr(ptr: S::OwnedFfiPtr) -> Option<Self> {
-        Some(SeaString {
+        let s = SeaString {
             owned: match S::owned_from_ffi_ptr(ptr) {
                 Some(owned) => owned,
                 None => return None,
             },
             _marker: PhantomData,
-        })
+        };
+        s.report_alloc();
+        Some(s)
+    }
+
+    /**
+    Constructs a `SeaString` by taking ownership of a foreign string pointer, then validates its contents.
+
+    The contents are validated by transcoding them to Unicode.  If this fails, the string is deallocated *before* the error is returned, so ownership of `ptr` is always taken, regardless of the result.
+
+    If `ptr` is null, the result is dependent on the string's structure.  If null is not a valid string pointer value, this method will return `Ok(None)`.
+
+    # Failure
+
+    This method will fail if the string contains any units which cannot be translated into Unicode.  The error is the one produced by the transcoder to `CheckedUnicode`, such as `WcToUniError` for `Wide` strings.
+
+    # Safety
+
+    If the `ptr` is not a valid pointer to a structurally compatible foreign string, then the result of this method is invalid, and may result in a memory protection failure on use.
+
+    This method must *not* be called more than once on the same pointer.  Note that this includes the case where this method returns an error: the pointer will have been freed.
+    */
+    pub unsafe fn from_ptr_take_validated<Error>(ptr: S::OwnedFfiPtr) -> Result<Option<Self>, Error>
+    where
+        S: for<'a> StructureIter<'a, E>,
+        for<'a> UnitIter<E, <S as StructureIter<'a, E>>::Iter>: TranscodeTo<CheckedUnicode, Error=Error>,
+    {
+        let owned = match SeaString::from_ptr(ptr) {
+            Some(owned) => owned,
+            None => return Ok(None),
+        };
+
+        for unit in owned.transcode_to_iter::<CheckedUnicode>() {
+            // On failure, `owned` is dropped (and thus freed) exactly once.
+            let _ = unit?;
+        }
+
+        Ok(Some(owned))
     }
 
     /**
     Relinquishes ownership of this string and returns a pointer.
 
     This pointer can be turned back into a `SeaString` by `from_ptr`, or sent to foreign code, which is then responsible for deallocating it.
+
+    A string which adopted its buffer from a `Box` or `Vec` is first copied into memory allocated by `A`, since foreign code could not otherwise free it.
+
+    # Panics
+
+    This method will panic if that copy cannot be allocated.
     */
     pub fn into_ptr(mut self) -> S::OwnedFfiPtr {
+        if let Some(mut old) = S::rehome_owned(&mut self.owned).expect("could not allocate SeaString") {
+            Self::report_free_owned(&old);
+            S::free_owned(&mut old);
+            self.report_alloc();
+        }
+
+        self.report_free();
         unsafe {
             let ptr = S::into_ffi_ptr(&mut self.owned);
             mem::forget(self);
@@ -520,6 +2991,326 @@ where
     }
 }
 
+/**
+Capacity management for `Slice` strings.
+
+Owned `Slice` strings track their capacity separately from their length, much like `Vec`.  The capacity is not part of the foreign representation, and is discarded by `into_ptr`.
+*/
+impl<E, A> SeaString<Slice, E, A> where E: Encoding, A: Allocator<Pointer=*mut ()> {
+    /**
+    Constructs an empty string with room for at least `cap` units.
+
+    # Failure
+
+    This method will fail if allocating memory fails.
+    */
+    pub fn with_capacity(cap: usize) -> Result<Self, A::AllocError> {
+        let s = SeaString {
+            owned: Slice::alloc_owned_concat_cap::<E, A>(&[], &[], cap)?,
+            _marker: PhantomData,
+        };
+        s.report_alloc();
+        Ok(s)
+    }
+
+    /**
+    Returns the number of units this string can hold without reallocating.
+    */
+    pub fn capacity(&self) -> usize {
+        self.owned.2
+    }
+
+    /**
+    Reserves room for at least `extra` more units than the string currently holds.
+
+    As with `Vec::reserve`, more room may be reserved, so that repeated calls take amortised linear time.  Nothing is done if there is already enough room.
+
+    # Failure
+
+    This method will fail if allocating memory fails, in which case the string is left unchanged.
+    */
+    pub fn reserve(&mut self, extra: usize) -> Result<(), A::AllocError> {
+        let (_, len, cap, _) = self.owned;
+        let needed = len.checked_add(extra).ok_or_else(A::AllocError::overflow)?;
+        if needed <= cap {
+            return Ok(());
+        }
+
+        let new_cap = ::std::cmp::max(needed, cap.saturating_mul(2));
+        let mut owned = Slice::alloc_owned_concat_cap::<E, A>(self.as_units(), &[], new_cap)?;
+        self.report_free();
+        mem::swap(&mut self.owned, &mut owned);
+        <Slice as StructureAlloc<E, A>>::free_owned(&mut owned);
+        self.report_alloc();
+        Ok(())
+    }
+}
+
+/**
+Raw pointer decomposition for `Slice` strings.
+
+These are the same as `into_ptr` and `from_ptr`, without the tuple.  The pointer and length say nothing about which allocator owns the memory: that is only recorded in the type, as `A`.
+*/
+impl<E, A> SeaString<Slice, E, A> where E: Encoding, A: Allocator<Pointer=*mut ()> {
+    /**
+    Relinquishes ownership of this string, and returns a pointer to its contents and its length in units.
+
+    The memory must eventually be freed by `A`, either by passing the parts back to `from_raw_parts` with the same allocator and dropping the result, or by foreign code which frees memory the same way `A` does.  For example, a string using `Malloc` may be freed with `free`, but a string using `Rust` can *only* be freed by `Rust`.
+
+    Any spare capacity is forgotten; the length is all that is needed to free the memory.
+    */
+    pub fn into_raw_parts(self) -> (*mut E::FfiUnit, usize) {
+        self.into_ptr()
+    }
+
+    /**
+    Constructs a string by taking ownership of a pointer and length, such as those returned by `into_raw_parts`.
+
+    # Safety
+
+    `ptr` must point to `len` valid units, in memory allocated by `A`.  This is *not* checked: if the memory came from a different allocator, it will be freed incorrectly when the string is dropped.
+
+    This method must *not* be called more than once on the same pointer.
+
+    # Panics
+
+    This method will panic if `ptr` is null.
+    */
+    pub unsafe fn from_raw_parts(ptr: *mut E::FfiUnit, len: usize) -> Self {
+        SeaString::from_ptr((ptr, len)).expect("SeaString::from_raw_parts called with a null pointer")
+    }
+}
+
+/*
+Conversions between `Slice` strings and boxed slices of units.
+
+`Rust` and `Global` strings can own memory from the same heap as `Box`, so a boxed slice's buffer is adopted rather than copied, and handed back again by `into_boxed_units`.  The string records that its buffer was adopted, and frees it as a `Box` would.  Only buffers which were adopted in the first place can be handed back: anything allocated by the string itself sits behind the allocator's size header, and so must be copied out.
+*/
+macro_rules! boxed_units_impls {
+    ($($alloc:ident),*) => {
+        $(
+            impl<E> SeaString<Slice, E, $alloc> where E: Encoding {
+                /**
+                Constructs a string from a boxed slice of units.
+
+                The box's buffer is taken over as-is, without copying, unless it is empty.
+
+                # Failure
+
+                This method will fail if allocating memory fails.
+                */
+                pub fn from_boxed_units(units: Box<[E::Unit]>) -> Result<Self, AllocError> {
+                    if units.is_empty() {
+                        return SeaString::new(&[]);
+                    }
+
+                    let len = units.len();
+                    let ptr = Box::into_raw(units) as *mut E::Unit;
+                    let s = SeaString {
+                        owned: (ptr as *mut (), len, len, true),
+                        _marker: PhantomData,
+                    };
+                    s.report_alloc();
+                    Ok(s)
+                }
+
+                /**
+                Converts this string into a boxed slice of units.
+
+                If the string's buffer came from `from_boxed_units`, it is handed back without copying, although it may be shrunk to fit.  Otherwise, the contents are copied.
+                */
+                pub fn into_boxed_units(self) -> Box<[E::Unit]> {
+                    let (ptr, len, cap, adopted) = self.owned;
+                    if !adopted {
+                        return self.as_units().to_vec().into_boxed_slice();
+                    }
+
+                    self.report_free();
+                    mem::forget(self);
+                    unsafe {
+                        Vec::from_raw_parts(ptr as *mut E::Unit, len, cap).into_boxed_slice()
+                    }
+                }
+            }
+        )*
+    };
+}
+
+boxed_units_impls! { Rust, Global }
+
+/**
+Copying constructors from reference-counted slices.
+*/
+impl<S, E, A> SeaString<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: Encoding,
+    A: Allocator,
+{
+    /**
+    Constructs a string by copying the contents of a reference-counted slice of units.
+
+    # Failure
+
+    This method will fail if allocating memory fails.
+    */
+    pub fn from_rc_units(units: &Rc<[E::Unit]>) -> Result<Self, A::AllocError> {
+        SeaString::new(&units[..])
+    }
+
+    /**
+    Constructs a string by copying the contents of an atomically reference-counted slice of units.
+
+    # Failure
+
+    This method will fail if allocating memory fails.
+    */
+    pub fn from_arc_units(units: &Arc<[E::Unit]>) -> Result<Self, A::AllocError> {
+        SeaString::new(&units[..])
+    }
+}
+
+/*
+Conversions between zero-terminated, byte-oriented strings and `Vec<u8>`.
+
+As with `from_boxed_units`, a `Rust`-allocated string adopts the vector's buffer rather than copying it, records its capacity, and can hand it back again; the terminator is pushed onto the vector if it does not already end with one.  Strings using any other allocator, or whose buffer was allocated by the string itself, must copy, using the explicitly named methods.
+*/
+macro_rules! byte_vec_impls {
+    ($($enc:ident, $unit:ident;)*) => {
+        $(
+            impl<A> SeaString<ZeroTerm, $enc, A> where A: Allocator<Pointer=*mut ()> {
+                /**
+                Constructs a zero-terminated string by copying the given bytes.
+
+                A single trailing zero is permitted, and will be used as the terminator.  Otherwise, a terminator is appended.
+
+                # Failure
+
+                This method will fail if `bytes` contains a zero anywhere other than at the end, or if allocating memory fails.
+                */
+                pub fn from_bytes_copied(bytes: &[u8]) -> Result<Self, FromBytesError<A::AllocError>> {
+                    check_interior_zero(bytes)?;
+
+                    // `$unit` is a `repr(C)` wrapper around `u8`.
+                    let units = unsafe {
+                        slice::from_raw_parts(bytes.as_ptr() as *const $unit, bytes.len())
+                    };
+                    SeaString::new(units).map_err(FromBytesError::Alloc)
+                }
+
+                /**
+                Copies the contents of this string into a byte vector.  The terminator is not included.
+                */
+                pub fn to_byte_vec(&self) -> Vec<u8> {
+                    self.as_units().iter().map(|u| u.0).collect()
+                }
+            }
+
+            impl TryFrom<Vec<u8>> for SeaString<ZeroTerm, $enc, Rust> {
+                type Error = FromBytesError;
+
+                fn try_from(mut bytes: Vec<u8>) -> Result<Self, Self::Error> {
+                    check_interior_zero(&bytes)?;
+                    if bytes.last() != Some(&0) {
+                        bytes.push(0);
+                    }
+
+                    let cap = bytes.capacity();
+                    let ptr = bytes.as_mut_ptr();
+                    mem::forget(bytes);
+                    let s = SeaString {
+                        owned: (ptr as *mut (), cap),
+                        _marker: PhantomData,
+                    };
+                    s.report_alloc();
+                    Ok(s)
+                }
+            }
+
+            impl From<SeaString<ZeroTerm, $enc, Rust>> for Vec<u8> {
+                fn from(s: SeaString<ZeroTerm, $enc, Rust>) -> Self {
+                    let (ptr, cap) = s.owned;
+                    if cap == 0 {
+                        return s.to_byte_vec();
+                    }
+
+                    let len = s.as_units().len();
+                    s.report_free();
+                    mem::forget(s);
+                    // The terminator is left behind as spare capacity.
+                    unsafe { Vec::from_raw_parts(ptr as *mut u8, len, cap) }
+                }
+            }
+        )*
+    };
+}
+
+byte_vec_impls! {
+    Utf8, Utf8Unit;
+    Raw8, Raw8Unit;
+}
+
+/**
+Checks that the only zero in `bytes`, if any, is the last one.
+*/
+fn check_interior_zero<E>(bytes: &[u8]) -> Result<(), FromBytesError<E>> {
+    match bytes.iter().position(|&b| b == 0) {
+        Some(at) if at + 1 != bytes.len() => Err(FromBytesError::InteriorZeroAt(at)),
+        _ => Ok(()),
+    }
+}
+
+/**
+The error produced when constructing a zero-terminated string from bytes.
+
+`InteriorZeroAt` means the bytes contain a zero before the end, which would truncate the string.  `Alloc` wraps the allocator's error, should allocating memory fail.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FromBytesError<E=AllocError> {
+    InteriorZeroAt(usize),
+    Alloc(E),
+}
+
+impl<E> fmt::Display for FromBytesError<E> where E: fmt::Display {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FromBytesError::InteriorZeroAt(at) => write!(fmt, "interior zero at offset {}", at),
+            FromBytesError::Alloc(ref err) => err.fmt(fmt),
+        }
+    }
+}
+
+impl<E> StdError for FromBytesError<E> where E: StdError {
+    fn description(&self) -> &str {
+        match *self {
+            FromBytesError::InteriorZeroAt(_) => "interior zero",
+            FromBytesError::Alloc(ref err) => err.description(),
+        }
+    }
+
+    fn cause(&self) -> Option<&StdError> {
+        match *self {
+            FromBytesError::InteriorZeroAt(_) => None,
+            FromBytesError::Alloc(ref err) => Some(err),
+        }
+    }
+}
+
+impl<'a, S, E, A, T> Add<&'a SeStr<T, E>> for SeaString<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: Encoding,
+    A: Allocator,
+    T: Structure<E>,
+{
+    type Output = SeaString<S, E, A>;
+
+    fn add(mut self, other: &'a SeStr<T, E>) -> Self::Output {
+        self.append_units(other.as_units()).expect("could not allocate SeaString");
+        self
+    }
+}
+
 impl<S, E, A> AsMut<SeStr<S, E>> for SeaString<S, E, A>
 where
     S: Structure<E> + StructureAlloc<E, A>,
@@ -568,6 +3359,30 @@ where
     }
 }
 
+/**
+Allows a string to be used with `write!`.
+
+Errors cannot be described by `fmt::Error`, so if a character cannot be encoded or memory cannot be allocated, nothing more is known than that writing failed.  Each call to `write_str` appends its contents all at once, or not at all.
+*/
+impl<S, E, A> fmt::Write for SeaString<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: EncodeChar,
+    A: Allocator,
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut units = Vec::with_capacity(s.len());
+        for c in s.chars() {
+            encode_onto::<E>(c, &mut units).map_err(|_| fmt::Error)?;
+        }
+        self.append_units(&units).map_err(|_| fmt::Error)
+    }
+
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.push_char(c).map_err(|_| fmt::Error)
+    }
+}
+
 impl<S, E, A> Clone for SeaString<S, E, A>
 where
     S: Structure<E> + StructureAlloc<E, A>,
@@ -575,7 +3390,14 @@ where
     A: Allocator,
 {
     fn clone(&self) -> Self {
-        SeaString::new(self.as_units()).expect("could not allocate SeaString")
+        // Copying the units as they are checked saves a pass over zero-terminated strings, compared with `new`.
+        let units = self.as_units();
+        let s = SeaString {
+            owned: S::alloc_owned_from_iter(units.len(), units.iter().cloned()).expect("could not allocate SeaString"),
+            _marker: PhantomData,
+        };
+        s.report_alloc();
+        s
     }
 }
 
@@ -586,11 +3408,8 @@ where
     A: Allocator,
 {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        write!(fmt, "{}{}{}\"", S::debug_prefix(), E::debug_prefix(), A::debug_prefix())?;
-        for unit in self.as_units() {
-            UnitDebug::fmt(unit, fmt)?;
-        }
-        write!(fmt, "\"")
+        write!(fmt, "{}{}{}", S::debug_prefix(), E::debug_prefix(), A::debug_prefix())?;
+        fmt_debug_units(self.as_units(), fmt)
     }
 }
 
@@ -640,24 +3459,28 @@ where
     A: Allocator,
 {
     fn drop(&mut self) {
+        self.report_free();
         S::free_owned(&mut self.owned);
     }
 }
 
-// impl<'a, S, E, A> From<&'a [E::Unit]> for SeaString<S, E, A>
-// where
-//     S: Structure<E> + StructureAlloc<E, A>,
-//     E: Encoding,
-//     A: Allocator,
-// {
-//     fn from(value: &'a [E::Unit]) -> Self {
-//         let owned = ;
-//         SeaString {
-//             owned: S::alloc_owned::<A>(value)?,
-//             _marker: PhantomData,
-//         }
-//     }
-// }
+/**
+Constructs a string from a slice of units; this is the same as `SeaString::new`.
+
+The error reports both allocation failure and contents which are incompatible with the structure: for `ZeroTerm`, an interior zero unit is reported using `AllocatorError::interior_zero`.  For `Slice`, only allocation can fail.
+*/
+impl<'a, S, E, A> TryFrom<&'a [E::Unit]> for SeaString<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: Encoding,
+    A: Allocator,
+{
+    type Error = A::AllocError;
+
+    fn try_from(units: &'a [E::Unit]) -> Result<Self, Self::Error> {
+        SeaString::new(units)
+    }
+}
 
 impl<S, E, A> Eq for SeaString<S, E, A>
 where
@@ -673,8 +3496,32 @@ where
     A: Allocator,
 {
     fn from_iter<T>(iter: T) -> Self where T: IntoIterator<Item=E::Unit> {
-        let units: Vec<_> = iter.into_iter().collect();
-        SeaString::new(&units[..]).expect("could not allocate SeaString")
+        // Fused, so that a short string below can only mean the structure stopped at a terminator.
+        let mut iter = iter.into_iter().fuse();
+        match iter.size_hint() {
+            // The length is known, so the units can be written straight into the string.
+            (lo, Some(hi)) if lo == hi => {
+                let mut s = SeaString {
+                    owned: S::alloc_owned_from_iter(lo, iter.by_ref()).expect("could not allocate SeaString"),
+                    _marker: PhantomData,
+                };
+                s.report_alloc();
+                // `size_hint` is only a hint; don't lose anything past it.
+                let rest: Vec<_> = iter.collect();
+                if !rest.is_empty() {
+                    if s.as_units().len() < lo {
+                        // A zero was taken as the terminator, but it turned out not to be the last unit.
+                        Err::<(), _>(A::AllocError::interior_zero(s.as_units().len())).expect("could not allocate SeaString");
+                    }
+                    s.append_units(&rest).expect("could not allocate SeaString");
+                }
+                s
+            },
+            _ => {
+                let units: Vec<_> = iter.collect();
+                SeaString::new(&units[..]).expect("could not allocate SeaString")
+            },
+        }
     }
 }
 
@@ -702,6 +3549,43 @@ where
     }
 }
 
+impl<S, E, A> IntoIterator for SeaString<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: Encoding,
+    A: Allocator,
+{
+    type Item = E::Unit;
+    type IntoIter = IntoUnits<S, E, A>;
+
+    fn into_iter(self) -> IntoUnits<S, E, A> {
+        let (ptr, len) = {
+            let units = self.as_units();
+            (units.as_ptr(), units.len())
+        };
+        IntoUnits {
+            ptr: ptr,
+            front: 0,
+            back: len,
+            _string: self,
+        }
+    }
+}
+
+impl<'a, S, E, A> IntoIterator for &'a SeaString<S, E, A>
+where
+    S: StructureIter<'a, E> + StructureAlloc<E, A>,
+    E: Encoding,
+    A: Allocator,
+{
+    type Item = E::Unit;
+    type IntoIter = Units<'a, S, E>;
+
+    fn into_iter(self) -> Units<'a, S, E> {
+        self.units()
+    }
+}
+
 impl<S, E, A, T, B> PartialEq<SeaString<T, E, B>> for SeaString<S, E, A>
 where
     S: Structure<E> + StructureAlloc<E, A>,
@@ -727,6 +3611,55 @@ where
     }
 }
 
+impl<S, E, A> PartialEq<[u8]> for SeaString<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: ByteEncoding,
+    A: Allocator,
+{
+    fn eq(&self, other: &[u8]) -> bool {
+        self.as_raw_bytes() == other
+    }
+}
+
+impl<'a, S, E, A> PartialEq<&'a [u8]> for SeaString<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: ByteEncoding,
+    A: Allocator,
+{
+    fn eq(&self, other: &&'a [u8]) -> bool {
+        self.as_raw_bytes() == *other
+    }
+}
+
+/**
+Compares the raw bytes of the string with the UTF-8 bytes of `other`.
+
+See the implementation for `SeStr`.
+*/
+impl<S, E, A> PartialEq<str> for SeaString<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: ByteEncoding + AsciiCompatible,
+    A: Allocator,
+{
+    fn eq(&self, other: &str) -> bool {
+        self.as_raw_bytes() == other.as_bytes()
+    }
+}
+
+impl<'a, S, E, A> PartialEq<&'a str> for SeaString<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: ByteEncoding + AsciiCompatible,
+    A: Allocator,
+{
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_raw_bytes() == other.as_bytes()
+    }
+}
+
 impl<S, E, T, B> PartialEq<SeaString<T, E, B>> for SeStr<S, E>
 where
     S: Structure<E>,
@@ -786,3 +3719,323 @@ where
         self.as_units().cmp(other.as_units())
     }
 }
+
+impl<'a, S, E, A> TryFrom<&'a str> for SeaString<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: Encoding,
+    A: Allocator,
+    UnitIter<CheckedUnicode, str::Chars<'a>>: TranscodeTo<E>,
+{
+    type Error = Box<StdError>;
+
+    fn try_from(s: &'a str) -> Result<Self, Self::Error> {
+        SeaString::from_str(s)
+    }
+}
+
+/**
+An iterator over the characters of a string, with replacement of invalid units.
+
+See `SeStr::chars_lossy`.
+*/
+pub struct CharsLossy<'a, S, E>
+where
+    S: StructureIter<'a, E>,
+    E: Encoding,
+    UnitIter<E, S::Iter>: TranscodeTo<CheckedUnicode>,
+{
+    iter: <UnitIter<E, S::Iter> as TranscodeTo<CheckedUnicode>>::Iter,
+    _marker: PhantomData<(&'a (), S, E)>,
+}
+
+impl<'a, S, E> Iterator for CharsLossy<'a, S, E>
+where
+    S: StructureIter<'a, E>,
+    E: Encoding,
+    UnitIter<E, S::Iter>: TranscodeTo<CheckedUnicode>,
+{
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|r| r.unwrap_or('\u{fffd}'))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/**
+An iterator over the units of a string.
+
+This is whichever iterator the string's structure provides; see `SeStr::units`.
+*/
+pub type Units<'a, S, E> = <S as StructureIter<'a, E>>::Iter;
+
+/**
+An iterator over the units of an owned string.
+
+The string's allocation is freed when the iterator is dropped, whether or not it was exhausted.
+
+See `SeaString::into_iter`.
+*/
+pub struct IntoUnits<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: Encoding,
+    A: Allocator,
+{
+    // Points into `_string`'s allocation, which is never mutated or moved while the iterator exists.
+    ptr: *const E::Unit,
+    front: usize,
+    back: usize,
+    _string: SeaString<S, E, A>,
+}
+
+/*
+`ptr` only ever points into `_string`, so the iterator can cross threads whenever the string itself can.
+*/
+unsafe impl<S, E, A> Send for IntoUnits<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: Encoding,
+    A: Allocator,
+    SeaString<S, E, A>: Send,
+{}
+
+unsafe impl<S, E, A> Sync for IntoUnits<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: Encoding,
+    A: Allocator,
+    SeaString<S, E, A>: Sync,
+{}
+
+impl<S, E, A> IntoUnits<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: Encoding,
+    A: Allocator,
+{
+    /**
+    Returns the units which have not yet been yielded.
+    */
+    pub fn as_units(&self) -> &[E::Unit] {
+        unsafe { slice::from_raw_parts(self.ptr.offset(self.front as isize), self.back - self.front) }
+    }
+}
+
+impl<S, E, A> Iterator for IntoUnits<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: Encoding,
+    A: Allocator,
+{
+    type Item = E::Unit;
+
+    fn next(&mut self) -> Option<E::Unit> {
+        let unit = self.as_units().first().cloned();
+        if unit.is_some() {
+            self.front += 1;
+        }
+        unit
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<S, E, A> DoubleEndedIterator for IntoUnits<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: Encoding,
+    A: Allocator,
+{
+    fn next_back(&mut self) -> Option<E::Unit> {
+        let unit = self.as_units().last().cloned();
+        if unit.is_some() {
+            self.back -= 1;
+        }
+        unit
+    }
+}
+
+impl<S, E, A> ExactSizeIterator for IntoUnits<S, E, A>
+where
+    S: Structure<E> + StructureAlloc<E, A>,
+    E: Encoding,
+    A: Allocator,
+{}
+
+/**
+An iterator over the characters of a string.
+
+See `SeStr::chars`.
+*/
+pub struct Chars<'a, E> where E: 'a + Encoding {
+    iter: CharIndices<'a, E>,
+}
+
+impl<'a, E> Iterator for Chars<'a, E> where E: 'a + DecodeUnits {
+    type Item = Result<char, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(_, r)| r)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/**
+An iterator over the characters of a string, and their offsets.
+
+See `SeStr::char_indices`.
+*/
+pub struct CharIndices<'a, E> where E: 'a + Encoding {
+    units: &'a [E::Unit],
+    at: usize,
+}
+
+impl<'a, E> Iterator for CharIndices<'a, E> where E: 'a + DecodeUnits {
+    type Item = (usize, Result<char, DecodeError>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let at = self.at;
+        E::decode_next(self.units, &mut self.at).map(|r| (at, r))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let rest = self.units.len() - self.at;
+        ((rest + E::MAX_UNITS_PER_CHAR - 1) / E::MAX_UNITS_PER_CHAR, Some(rest))
+    }
+}
+
+/**
+A sequence of units to look for in a string, such as with `SeStr::starts_with` or `SeStr::find`.
+
+This is implemented for single units, for borrowed strings of the same encoding, whatever their structure, and for slices of units.  Matching is always performed on raw units; no transcoding is done.
+*/
+pub trait UnitPattern<E> where E: Encoding {
+    /**
+    Returns the units to match.
+    */
+    fn pattern_units(&self) -> &[E::Unit];
+
+    /**
+    Returns the offset of the first match within `haystack`.
+
+    An empty pattern matches at offset `0`.  The default implementation compares the pattern against every position in turn.
+    */
+    fn find_in(&self, haystack: &[E::Unit]) -> Option<usize> {
+        let needle = self.pattern_units();
+        if needle.is_empty() {
+            return Some(0);
+        }
+        haystack.windows(needle.len()).position(|window| window == needle)
+    }
+
+    /**
+    Returns the offset of the last match within `haystack`.
+
+    An empty pattern matches at the end of `haystack`.  The default implementation compares the pattern against every position in turn, starting from the end.
+    */
+    fn rfind_in(&self, haystack: &[E::Unit]) -> Option<usize> {
+        let needle = self.pattern_units();
+        if needle.is_empty() {
+            return Some(haystack.len());
+        }
+        haystack.windows(needle.len()).rposition(|window| window == needle)
+    }
+}
+
+/*
+This can't be written as an implementation for `E::Unit`, as the compiler would consider that it might overlap the implementations for references below.  Going through `U` lets it see that a reference is never a `Unit`.
+*/
+impl<E, U> UnitPattern<E> for U where E: Encoding<Unit=U>, U: Unit {
+    fn pattern_units(&self) -> &[U] {
+        slice::from_ref(self)
+    }
+
+    fn find_in(&self, haystack: &[U]) -> Option<usize> {
+        if mem::size_of::<U>() == 1 {
+            // Single byte units are compared by value, so the C runtime's `memchr` can do the search.  It is typically vectorised, and much faster than comparing one unit at a time.
+            if haystack.is_empty() {
+                return None;
+            }
+            unsafe {
+                let byte = *(self as *const U as *const u8);
+                let base = haystack.as_ptr() as *const ::libc::c_void;
+                let at = ::libc::memchr(base, byte as ::libc::c_int, haystack.len());
+                if at.is_null() {
+                    None
+                } else {
+                    Some(at as usize - base as usize)
+                }
+            }
+        } else {
+            haystack.iter().position(|unit| unit == self)
+        }
+    }
+
+    fn rfind_in(&self, haystack: &[U]) -> Option<usize> {
+        haystack.iter().rposition(|unit| unit == self)
+    }
+}
+
+impl<'a, S, E> UnitPattern<E> for &'a SeStr<S, E> where S: Structure<E>, E: Encoding {
+    fn pattern_units(&self) -> &[E::Unit] {
+        self.as_units()
+    }
+}
+
+impl<'a, E> UnitPattern<E> for &'a [E::Unit] where E: Encoding {
+    fn pattern_units(&self) -> &[E::Unit] {
+        self
+    }
+}
+
+/**
+An iterator over the sub-strings of a string, separated by a unit.
+
+See `SeStr::split_unit`.
+*/
+pub struct SplitUnit<'a, S, E> where E: Encoding {
+    rest: Option<&'a [E::Unit]>,
+    sep: E::Unit,
+    _marker: PhantomData<S>,
+}
+
+impl<'a, S, E> Iterator for SplitUnit<'a, S, E> where E: 'a + Encoding {
+    type Item = &'a SeStr<Slice, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = match self.rest {
+            Some(rest) => rest,
+            None => return None,
+        };
+
+        let sep = self.sep;
+        match rest.iter().position(|&u| u == sep) {
+            Some(at) => {
+                self.rest = Some(&rest[at + 1..]);
+                Some(SeStr::new(&rest[..at]))
+            },
+            None => {
+                self.rest = None;
+                Some(SeStr::new(rest))
+            },
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.rest {
+            Some(rest) => (1, Some(rest.len() + 1)),
+            None => (0, Some(0)),
+        }
+    }
+}
@@ -0,0 +1,102 @@
+/*!
+`proptest` strategies for generating strings.
+
+These are intended for property-testing code built on top of this crate.  This module is only available with the `proptest` feature enabled.
+*/
+use std::fmt::Debug;
+use proptest::prelude::*;
+use proptest::sample::Index;
+use alloc::Allocator;
+use encoding::{Encoding, Unit, Utf8Unit, Utf16Unit};
+use sea::SeaString;
+use structure::{StructureAlloc, ZeroTerm};
+
+/**
+Generates sequences of units which are valid UTF-8.
+*/
+pub fn valid_utf8_units() -> impl Strategy<Value=Vec<Utf8Unit>> {
+    any::<String>().prop_map(|s| s.bytes().map(Utf8Unit).collect())
+}
+
+/**
+Generates sequences of units which are *not* valid UTF-8.
+
+Each sequence is valid UTF-8, except for a single stray byte which can never appear in UTF-8, inserted at an arbitrary unit offset.
+*/
+pub fn invalid_utf8_units() -> impl Strategy<Value=Vec<Utf8Unit>> {
+    (valid_utf8_units(), any::<Index>(), 0xf8u8..=0xff)
+        .prop_map(|(mut units, at, bad)| {
+            let at = at.index(units.len() + 1);
+            units.insert(at, Utf8Unit(bad));
+            units
+        })
+}
+
+/**
+Generates sequences of units which are valid UTF-16.
+*/
+pub fn valid_utf16_units() -> impl Strategy<Value=Vec<Utf16Unit>> {
+    any::<String>().prop_map(|s| s.encode_utf16().map(Utf16Unit).collect())
+}
+
+/**
+Generates sequences of units which are *not* valid UTF-16.
+
+Each sequence is valid UTF-16, except for a single unpaired low surrogate, inserted at an arbitrary *code point* boundary.
+*/
+pub fn invalid_utf16_units() -> impl Strategy<Value=Vec<Utf16Unit>> {
+    (any::<String>(), any::<Index>(), 0xdc00u16..=0xdfff)
+        .prop_map(|(s, at, bad)| {
+            let at = at.index(s.chars().count() + 1);
+            let mut units: Vec<_> = s.chars().take(at).collect::<String>()
+                .encode_utf16().map(Utf16Unit).collect();
+            units.push(Utf16Unit(bad));
+            units.extend(s.chars().skip(at).collect::<String>().encode_utf16().map(Utf16Unit));
+            units
+        })
+}
+
+/**
+Adapts a strategy for sequences of units such that it is suitable for zero-terminated strings.
+
+Zero units are removed, as they cannot appear in the interior of a zero-terminated string.
+*/
+pub fn zero_term_units<U, T>(units: T) -> impl Strategy<Value=Vec<U>>
+where
+    U: Unit + Debug,
+    T: Strategy<Value=Vec<U>>,
+{
+    units.prop_map(|units| units.into_iter().filter(|u| !u.is_zero()).collect())
+}
+
+/**
+Generates owned strings from a strategy for sequences of units.
+
+If `S` is `ZeroTerm`, `units` should be wrapped with `zero_term_units`; see also `zero_term_sea_string`.
+*/
+pub fn sea_string<S, E, A, T>(units: T) -> impl Strategy<Value=SeaString<S, E, A>>
+where
+    S: StructureAlloc<E, A>,
+    E: Encoding,
+    A: Allocator,
+    E::Unit: Debug,
+    T: Strategy<Value=Vec<E::Unit>>,
+{
+    units.prop_map(|units| SeaString::new(&units).expect("could not allocate SeaString"))
+}
+
+/**
+Generates owned zero-terminated strings from a strategy for sequences of units.
+
+Zero units are removed from the generated sequences.
+*/
+pub fn zero_term_sea_string<E, A, T>(units: T) -> impl Strategy<Value=SeaString<ZeroTerm, E, A>>
+where
+    ZeroTerm: StructureAlloc<E, A>,
+    E: Encoding,
+    A: Allocator,
+    E::Unit: Debug,
+    T: Strategy<Value=Vec<E::Unit>>,
+{
+    sea_string(zero_term_units(units))
+}
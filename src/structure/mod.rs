@@ -1,6 +1,7 @@
 /*!
 Structure types and traits.
 */
+use std::alloc::{dealloc, Layout};
 use std::marker::PhantomData;
 use std::mem;
 use std::ptr;
@@ -67,6 +68,8 @@ pub trait Structure<E>: Sized where E: Encoding {
 
     This should only return `None` if the provided foreign pointer is *invalid*.  In cases where the null pointer is a valid string (usually semantically equivalent to the empty string), it should return `Some` and *a valid pointer*.
 
+    Pointers which are not suitably aligned for `E::Unit` are invalid, and should result in `None`.  Creating a misaligned reference is undefined behaviour, even if it is never read through.
+
     Implementations *may* perform basic sanity-checks within this method, but should avoid any non-*O*(1) work.  It is the caller's responsibility to ensure any non-null pointer provided is valid.
 
     # Motivation
@@ -94,6 +97,25 @@ pub trait Structure<E>: Sized where E: Encoding {
     */
     fn slice_units_mut(ptr: &mut Self::RefTarget) -> &mut [E::Unit];
 
+    /**
+    Given a valid immutably borrowed pointer, determines whether the string has no contents.
+
+    The default implementation calls `slice_units`.  Structures which do not implement `KnownLength` should override it if they can tell that a string is empty without traversing it.
+    */
+    fn is_empty(ptr: &Self::RefTarget) -> bool {
+        Self::slice_units(ptr).is_empty()
+    }
+
+    /**
+    Given a valid immutably borrowed pointer, returns a slice over the contents of the string *including* its zero terminator, if it has one.
+
+    This lets code which is generic over structures take advantage of the terminator where there is one.  The default returns `None`; structures which implement `ZeroTerminated` should override it.
+    */
+    fn slice_units_terminated(ptr: &Self::RefTarget) -> Option<&[E::Unit]> {
+        let _ = ptr;
+        None
+    }
+
     /**
     Given a pointer to an owned string, derives an immutably borrowed pointer.
 
@@ -147,6 +169,122 @@ pub trait StructureAlloc<E, A>: Structure<E> where E: Encoding, A: Allocator {
     // TODO: what about failing on invalid contents?
     fn alloc_owned(units: &[E::Unit]) -> Result<Self::Owned, A::AllocError>;
 
+    /**
+    Allocate a string containing the contents of `head` followed by the contents of `tail`, and return an owned pointer.
+
+    This is used to append to existing strings.  The default implementation assembles the combined contents in a temporary buffer, then uses `alloc_owned`; implementations should override this to copy both parts directly into the new allocation.
+
+    # Failure
+
+    May fail if any of the underlying allocations fail.
+    */
+    fn alloc_owned_concat(head: &[E::Unit], tail: &[E::Unit]) -> Result<Self::Owned, A::AllocError> {
+        let mut units = Vec::with_capacity(head.len().saturating_add(tail.len()));
+        units.extend_from_slice(head);
+        units.extend_from_slice(tail);
+        Self::alloc_owned(&units)
+    }
+
+    /**
+    Allocate a string containing `count` copies of `unit`, and return an owned pointer.
+
+    The default implementation checks that the contents can be allocated at all, then uses `alloc_owned_from_iter`; implementations should override this to fill the new allocation directly.
+
+    # Failure
+
+    May fail if the size of the contents overflows, or if any of the underlying allocations fail.
+    */
+    fn alloc_owned_repeat(unit: E::Unit, count: usize) -> Result<Self::Owned, A::AllocError> {
+        count.checked_mul(mem::size_of::<E::Unit>())
+            .ok_or_else(A::AllocError::overflow)?;
+        Self::alloc_owned_from_iter(count, ::std::iter::repeat(unit))
+    }
+
+    /**
+    Allocate a string containing the first `len` units produced by `units`, and return an owned pointer.
+
+    This allows a string to be built from a computed sequence of units, such as the output of a transcoder, without first collecting them into a temporary buffer.  If `units` ends early, the string contains only the units it produced.  The default implementation collects the units into a temporary buffer, then uses `alloc_owned`; implementations should override this to fill a single allocation of `len` units directly.
+
+    # Failure
+
+    May fail if any of the underlying allocations fail, or if the units are incompatible with the structure.
+    */
+    fn alloc_owned_from_iter<I>(len: usize, units: I) -> Result<Self::Owned, A::AllocError>
+    where I: Iterator<Item=E::Unit> {
+        let units: Vec<_> = units.take(len).collect();
+        Self::alloc_owned(&units)
+    }
+
+    /**
+    Allocate a string of `len` units, have `fill` write its contents in place, and return an owned pointer.
+
+    `fill` is given the string's `len` units, all zero.  If it returns `false`, the allocation is released and this returns `None`.  The default implementation fills a temporary buffer, then uses `alloc_owned`; implementations should override this to hand `fill` the new allocation directly.
+
+    # Failure
+
+    May fail if any of the underlying allocations fail, or if the units written are incompatible with the structure.
+    */
+    fn alloc_owned_fill<F>(len: usize, fill: F) -> Result<Option<Self::Owned>, A::AllocError>
+    where F: FnOnce(&mut [E::Unit]) -> bool {
+        let mut units = vec![E::Unit::zero(); len];
+        if !fill(&mut units) {
+            return Ok(None);
+        }
+        Self::alloc_owned(&units).map(Some)
+    }
+
+    /**
+    Returns the number of bytes requested from the allocator to hold `owned`, including any terminator.
+
+    This does not include any overhead added by the allocator itself; see `Allocator::allocated_bytes`.  The default implementation assumes the allocation holds exactly the string's contents.
+    */
+    fn owned_bytes(owned: &Self::Owned) -> usize {
+        Self::slice_units(Self::borrow_from_owned(owned)).len().saturating_mul(mem::size_of::<E::Unit>())
+    }
+
+    /**
+    Shortens a string to its first `len` units, which must not be more than its current length.
+
+    If this needs a new allocation, `owned` is replaced, and the old allocation is returned for the caller to free.  The default implementation always copies the remaining units into a new allocation.  Structures which can be shortened in place should override this.
+
+    # Failure
+
+    This method will fail if a new allocation is needed and cannot be made, in which case `owned` is left unchanged.
+    */
+    fn truncate_owned(owned: &mut Self::Owned, len: usize) -> Result<Option<Self::Owned>, A::AllocError> {
+        let mut new = Self::alloc_owned(&Self::slice_units(Self::borrow_from_owned(owned))[..len])?;
+        mem::swap(owned, &mut new);
+        Ok(Some(new))
+    }
+
+    /**
+    Appends `tail` to the end of a string.
+
+    If this needs a new allocation, `owned` is replaced, and the old allocation is returned for the caller to free.  The default implementation always makes a new allocation using `alloc_owned_concat`.  Structures which can keep spare capacity should override this to append in place where there is room, and to leave room for later appends where there is not, so that repeated appends take amortised linear time.
+
+    # Failure
+
+    This method will fail if a new allocation is needed and cannot be made, in which case `owned` is left unchanged.
+    */
+    fn push_owned(owned: &mut Self::Owned, tail: &[E::Unit]) -> Result<Option<Self::Owned>, A::AllocError> {
+        let mut new = Self::alloc_owned_concat(Self::slice_units(Self::borrow_from_owned(owned)), tail)?;
+        mem::swap(owned, &mut new);
+        Ok(Some(new))
+    }
+
+    /**
+    Moves a string whose buffer was adopted from a `Box` or `Vec` into memory allocated by `A`, so that it can be handed to foreign code.
+
+    If this needs a new allocation, `owned` is replaced, and the old allocation is returned for the caller to free.  The default implementation does nothing, which is correct for structures that never adopt buffers.
+
+    # Failure
+
+    This method will fail if a new allocation is needed and cannot be made, in which case `owned` is left unchanged.
+    */
+    fn rehome_owned(_owned: &mut Self::Owned) -> Result<Option<Self::Owned>, A::AllocError> {
+        Ok(None)
+    }
+
     /**
     Deallocate a string.
     */
@@ -222,7 +360,8 @@ This is the structure used by various forms of "C" string.  This should *not* be
 pub enum ZeroTerm {}
 
 impl<E> Structure<E> for ZeroTerm where E: Encoding {
-    type Owned = *mut ();
+    // Pointer, and the capacity in units of a buffer adopted from a `Vec`, or zero if the buffer belongs to the string's allocator.
+    type Owned = (*mut (), usize);
     type RefTarget = E::Unit;
 
     type FfiPtr = *const E::FfiUnit;
@@ -231,7 +370,7 @@ impl<E> Structure<E> for ZeroTerm where E: Encoding {
     fn debug_prefix() -> &'static str { "Z" }
 
     unsafe fn borrow_from_ffi_ptr<'a>(ptr: Self::FfiPtr) -> Option<&'a Self::RefTarget> {
-        if ptr.is_null () {
+        if ptr.is_null () || !is_unit_aligned::<E>(ptr as usize) {
             None
         } else {
             Some(mem::transmute::<Self::FfiPtr, &Self::RefTarget>(ptr))
@@ -239,7 +378,7 @@ impl<E> Structure<E> for ZeroTerm where E: Encoding {
     }
 
     unsafe fn borrow_from_ffi_ptr_mut<'a>(ptr: Self::FfiMutPtr) -> Option<&'a mut Self::RefTarget> {
-        if ptr.is_null () {
+        if ptr.is_null () || !is_unit_aligned::<E>(ptr as usize) {
             None
         } else {
             Some(mem::transmute::<Self::FfiPtr, &mut Self::RefTarget>(ptr))
@@ -274,15 +413,24 @@ impl<E> Structure<E> for ZeroTerm where E: Encoding {
         }
     }
 
+    fn is_empty(ptr: &Self::RefTarget) -> bool {
+        // The borrowed pointer *is* the first unit.
+        ptr.is_zero()
+    }
+
+    fn slice_units_terminated(ptr: &Self::RefTarget) -> Option<&[E::Unit]> {
+        Some(<Self as ZeroTerminated<E>>::slice_units_with_term(ptr))
+    }
+
     fn borrow_from_owned<'a>(owned: &Self::Owned) -> &Self::RefTarget {
         unsafe {
-            &*((*owned) as *mut E::Unit as *const E::Unit)
+            &*(owned.0 as *mut E::Unit as *const E::Unit)
         }
     }
 
     fn borrow_from_owned_mut<'a>(owned: &mut Self::Owned) -> &mut Self::RefTarget {
         unsafe {
-            &mut *((*owned) as *mut E::Unit)
+            &mut *(owned.0 as *mut E::Unit)
         }
     }
 
@@ -301,12 +449,23 @@ impl<E> Structure<E> for ZeroTerm where E: Encoding {
 
 impl<E, A> StructureAlloc<E, A> for ZeroTerm where E: Encoding, A: Allocator<Pointer=*mut ()> {
     fn alloc_owned(units: &[E::Unit]) -> Result<Self::Owned, A::AllocError> {
+        <Self as StructureAlloc<E, A>>::alloc_owned_concat(units, &[])
+    }
+
+    fn alloc_owned_concat(head: &[E::Unit], tail: &[E::Unit]) -> Result<Self::Owned, A::AllocError> {
         unsafe {
-            // TODO: check for earlier NUL; fail if it isn't at the end.
-            let add_term = !(units.len() > 0 && units[units.len()-1].is_zero());
+            let content_u = head.len().checked_add(tail.len())
+                .ok_or_else(A::AllocError::overflow)?;
+
+            // A trailing zero can serve as the terminator; a zero anywhere else would truncate the string.
+            let add_term = match head.iter().chain(tail).position(Unit::is_zero) {
+                Some(at) if at + 1 == content_u => false,
+                Some(at) => return Err(A::AllocError::interior_zero(at)),
+                None => true,
+            };
 
             // +1 for the terminator.
-            let total_u = units.len().checked_add(if add_term {1} else {0})
+            let total_u = content_u.checked_add(if add_term {1} else {0})
                 .ok_or_else(A::AllocError::overflow)?;
             let unit_b = mem::size_of::<E::Unit>();
             let total_b = total_u.checked_mul(unit_b)
@@ -316,17 +475,141 @@ impl<E, A> StructureAlloc<E, A> for ZeroTerm where E: Encoding, A: Allocator<Poi
             {
                 let s = slice::from_raw_parts_mut(ptr as *mut E::Unit, total_u);
 
-                s[..units.len()].copy_from_slice(units);
+                s[..head.len()].copy_from_slice(head);
+                s[head.len()..content_u].copy_from_slice(tail);
                 s[total_u-1] = E::Unit::zero();
             }
 
-            Ok(ptr)
+            Ok((ptr, 0))
         }
     }
 
-    fn free_owned(ptr: &mut Self::Owned) {
+    fn alloc_owned_repeat(unit: E::Unit, count: usize) -> Result<Self::Owned, A::AllocError> {
+        // As with `alloc_owned`, a single zero is taken as the terminator, but any more would truncate the string.
+        let count = match (unit.is_zero(), count) {
+            (true, 0) | (true, 1) => 0,
+            (true, _) => return Err(A::AllocError::interior_zero(0)),
+            (false, count) => count,
+        };
+
         unsafe {
-            A::free(*ptr, mem::align_of::<E::Unit>());
+            // +1 for the terminator.
+            let total_u = count.checked_add(1)
+                .ok_or_else(A::AllocError::overflow)?;
+            let total_b = total_u.checked_mul(mem::size_of::<E::Unit>())
+                .ok_or_else(A::AllocError::overflow)?;
+
+            let ptr = A::alloc_bytes(total_b, mem::align_of::<E::Unit>())?;
+            {
+                let s = slice::from_raw_parts_mut(ptr as *mut E::Unit, total_u);
+                for u in &mut s[..count] {
+                    *u = unit;
+                }
+                s[count] = E::Unit::zero();
+            }
+
+            Ok((ptr, 0))
+        }
+    }
+
+    fn alloc_owned_from_iter<I>(len: usize, units: I) -> Result<Self::Owned, A::AllocError>
+    where I: Iterator<Item=E::Unit> {
+        unsafe {
+            // +1 for the terminator.
+            let total_u = len.checked_add(1)
+                .ok_or_else(A::AllocError::overflow)?;
+            let total_b = total_u.checked_mul(mem::size_of::<E::Unit>())
+                .ok_or_else(A::AllocError::overflow)?;
+
+            let ptr = A::alloc_bytes(total_b, mem::align_of::<E::Unit>())?;
+            // `take` guarantees at most `len` units, so writing through the pointer cannot overrun, and avoids a bounds check per unit.
+            let dst = ptr as *mut E::Unit;
+            let mut at = 0;
+            for unit in units.take(len) {
+                if unit.is_zero() {
+                    // As with `alloc_owned`, a trailing zero can serve as the terminator.
+                    if at + 1 == len {
+                        break;
+                    }
+                    A::free(ptr, mem::align_of::<E::Unit>());
+                    return Err(A::AllocError::interior_zero(at));
+                }
+                *dst.offset(at as isize) = unit;
+                at += 1;
+            }
+            *dst.offset(at as isize) = E::Unit::zero();
+
+            Ok((ptr, 0))
+        }
+    }
+
+    fn alloc_owned_fill<F>(len: usize, fill: F) -> Result<Option<Self::Owned>, A::AllocError>
+    where F: FnOnce(&mut [E::Unit]) -> bool {
+        unsafe {
+            // +1 for the terminator.
+            let total_u = len.checked_add(1)
+                .ok_or_else(A::AllocError::overflow)?;
+            let total_b = total_u.checked_mul(mem::size_of::<E::Unit>())
+                .ok_or_else(A::AllocError::overflow)?;
+
+            let ptr = A::alloc_bytes(total_b, mem::align_of::<E::Unit>())?;
+            // Zeroing also writes the terminator.
+            ptr::write_bytes(ptr as *mut E::Unit, 0, total_u);
+            let s = slice::from_raw_parts_mut(ptr as *mut E::Unit, len);
+            if !fill(s) {
+                A::free(ptr, mem::align_of::<E::Unit>());
+                return Ok(None);
+            }
+
+            // As with `alloc_owned`, a trailing zero can serve as the terminator.
+            match s.iter().position(Unit::is_zero) {
+                Some(at) if at + 1 < len => {
+                    A::free(ptr, mem::align_of::<E::Unit>());
+                    Err(A::AllocError::interior_zero(at))
+                },
+                _ => Ok(Some((ptr, 0))),
+            }
+        }
+    }
+
+    fn owned_bytes(owned: &Self::Owned) -> usize {
+        if owned.1 != 0 {
+            return owned.1.saturating_mul(mem::size_of::<E::Unit>());
+        }
+
+        // +1 for the terminator.
+        let units = <Self as Structure<E>>::slice_units(<Self as Structure<E>>::borrow_from_owned(owned));
+        units.len().saturating_add(1).saturating_mul(mem::size_of::<E::Unit>())
+    }
+
+    fn truncate_owned(owned: &mut Self::Owned, len: usize) -> Result<Option<Self::Owned>, A::AllocError> {
+        let units = <Self as Structure<E>>::slice_units_mut(<Self as Structure<E>>::borrow_from_owned_mut(owned));
+        assert!(len <= units.len());
+        unsafe {
+            // Overwrite the first unit being removed, or the existing terminator.
+            *units.as_mut_ptr().offset(len as isize) = E::Unit::zero();
+        }
+        Ok(None)
+    }
+
+    fn rehome_owned(owned: &mut Self::Owned) -> Result<Option<Self::Owned>, A::AllocError> {
+        if owned.1 == 0 {
+            return Ok(None);
+        }
+
+        let units = <Self as Structure<E>>::slice_units(<Self as Structure<E>>::borrow_from_owned(owned));
+        let mut new = <Self as StructureAlloc<E, A>>::alloc_owned(units)?;
+        mem::swap(owned, &mut new);
+        Ok(Some(new))
+    }
+
+    fn free_owned(&mut (ptr, cap): &mut Self::Owned) {
+        unsafe {
+            if cap != 0 {
+                free_adopted::<E>(ptr, cap);
+            } else {
+                A::free(ptr, mem::align_of::<E::Unit>());
+            }
         }
     }
 }
@@ -372,6 +655,72 @@ impl<'a, E> Iterator for ZeroTermIter<'a, E> where E: Encoding {
             }
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Looking any further than the next unit would mean scanning the whole string.
+        unsafe {
+            if (*self.ptr).is_zero() {
+                (0, Some(0))
+            } else {
+                (1, None)
+            }
+        }
+    }
+}
+
+impl ZeroTerm {
+    /**
+    Returns an iterator over the units of a zero-terminated string which may not be suitably aligned for `E::Unit`.
+
+    This is intended for reading strings out of packed or byte-oriented buffers, which `SeStr::from_ptr` will reject.  To get a usable string, collect the result into a `SeaString`.
+
+    # Safety
+
+    `ptr` must be non-null, and point to a zero-terminated sequence of units which remains valid for `'a`.
+    */
+    pub unsafe fn iter_unaligned<'a, E>(ptr: *const E::FfiUnit) -> ZeroTermUnalignedIter<'a, E>
+    where E: Encoding {
+        ZeroTermUnalignedIter {
+            ptr: ptr as *const E::Unit,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/**
+An iterator over the units of a possibly misaligned zero-terminated string.
+
+See `ZeroTerm::iter_unaligned`.
+*/
+pub struct ZeroTermUnalignedIter<'a, E> where E: Encoding {
+    ptr: *const E::Unit,
+    _marker: PhantomData<&'a E::Unit>,
+}
+
+impl<'a, E> Iterator for ZeroTermUnalignedIter<'a, E> where E: Encoding {
+    type Item = E::Unit;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            let unit = ptr::read_unaligned(self.ptr);
+            if unit.is_zero() {
+                None
+            } else {
+                self.ptr = self.ptr.offset(1);
+                Some(unit)
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        unsafe {
+            if ptr::read_unaligned(self.ptr).is_zero() {
+                (0, Some(0))
+            } else {
+                (1, None)
+            }
+        }
+    }
 }
 
 unsafe impl<E> OwnershipTransfer<E> for ZeroTerm where E: Encoding {
@@ -381,13 +730,15 @@ unsafe impl<E> OwnershipTransfer<E> for ZeroTerm where E: Encoding {
         if ptr.is_null() {
             None
         } else {
-            Some(ptr as *mut ())
+            Some((ptr as *mut (), 0))
         }
     }
 
     unsafe fn into_ffi_ptr(ptr: &mut Self::Owned) -> Self::OwnedFfiPtr {
-        let r = (*ptr) as *mut E::FfiUnit;
-        *ptr = ptr::null_mut();
+        // Adopted buffers must already have been moved by `rehome_owned`.
+        debug_assert_eq!(ptr.1, 0);
+        let r = ptr.0 as *mut E::FfiUnit;
+        *ptr = (ptr::null_mut(), 0);
         r
     }
 }
@@ -475,12 +826,13 @@ impl<E> ZeroTerminated<E> for ZeroTerm where E: Encoding {
 /**
 Strings represented by a pair consisting of a pointer to the first unit, and the number of units stored in a pointer-sized unsigned integer.
 
-This is similar to the representation used by Rust for slices.
+This is similar to the representation used by Rust for slices.  Owned strings additionally track their capacity, much like `Vec`, so that they can be grown in place; this is not part of the foreign representation.
 */
 pub enum Slice {}
 
 impl<E> Structure<E> for Slice where E: Encoding {
-    type Owned = (*mut (), usize);
+    // Pointer, length, and capacity, in units, and whether the buffer was adopted from a `Box` rather than allocated by the string's allocator.
+    type Owned = (*mut (), usize, usize, bool);
     type RefTarget = [E::Unit];
 
     type FfiPtr = (*const E::FfiUnit, usize);
@@ -490,7 +842,7 @@ impl<E> Structure<E> for Slice where E: Encoding {
 
     unsafe fn borrow_from_ffi_ptr<'a>(ptr: Self::FfiPtr) -> Option<&'a Self::RefTarget> {
         let (ptr, len) = ptr;
-        if ptr.is_null() {
+        if ptr.is_null() || !is_unit_aligned::<E>(ptr as usize) {
             None
         } else {
             Some(::std::slice::from_raw_parts(ptr as *const E::Unit, len))
@@ -499,7 +851,7 @@ impl<E> Structure<E> for Slice where E: Encoding {
 
     unsafe fn borrow_from_ffi_ptr_mut<'a>(ptr: Self::FfiMutPtr) -> Option<&'a mut Self::RefTarget> {
         let (ptr, len) = ptr;
-        if ptr.is_null() {
+        if ptr.is_null() || !is_unit_aligned::<E>(ptr as usize) {
             None
         } else {
             Some(::std::slice::from_raw_parts_mut(ptr as *mut E::Unit, len))
@@ -537,25 +889,100 @@ impl<E> Structure<E> for Slice where E: Encoding {
 
 impl<E, A> StructureAlloc<E, A> for Slice where E: Encoding, A: Allocator<Pointer=*mut ()> {
     fn alloc_owned(units: &[E::Unit]) -> Result<Self::Owned, A::AllocError> {
+        <Self as StructureAlloc<E, A>>::alloc_owned_concat(units, &[])
+    }
+
+    fn alloc_owned_concat(head: &[E::Unit], tail: &[E::Unit]) -> Result<Self::Owned, A::AllocError> {
+        let total_u = head.len().checked_add(tail.len())
+            .ok_or_else(A::AllocError::overflow)?;
+        Slice::alloc_owned_concat_cap::<E, A>(head, tail, total_u)
+    }
+
+    fn alloc_owned_repeat(unit: E::Unit, count: usize) -> Result<Self::Owned, A::AllocError> {
+        let (ptr, _, cap, _) = Slice::alloc_owned_concat_cap::<E, A>(&[], &[], count)?;
         unsafe {
-            let total_u = units.len();
-            let unit_b = mem::size_of::<E::Unit>();
-            let total_b = total_u.checked_mul(unit_b)
-                .ok_or_else(A::AllocError::overflow)?;
+            for u in slice::from_raw_parts_mut(ptr as *mut E::Unit, count) {
+                *u = unit;
+            }
+        }
+        Ok((ptr, count, cap, false))
+    }
 
-            let ptr = A::alloc_bytes(total_b, mem::align_of::<E::Unit>())?;
-            {
+    fn alloc_owned_from_iter<I>(len: usize, units: I) -> Result<Self::Owned, A::AllocError>
+    where I: Iterator<Item=E::Unit> {
+        let (ptr, _, cap, _) = Slice::alloc_owned_concat_cap::<E, A>(&[], &[], len)?;
+        let mut written = 0;
+        unsafe {
+            for (dst, unit) in slice::from_raw_parts_mut(ptr as *mut E::Unit, len).iter_mut().zip(units) {
+                *dst = unit;
+                written += 1;
+            }
+        }
+        Ok((ptr, written, cap, false))
+    }
+
+    fn alloc_owned_fill<F>(len: usize, fill: F) -> Result<Option<Self::Owned>, A::AllocError>
+    where F: FnOnce(&mut [E::Unit]) -> bool {
+        let (ptr, _, cap, _) = Slice::alloc_owned_concat_cap::<E, A>(&[], &[], len)?;
+        unsafe {
+            ptr::write_bytes(ptr as *mut E::Unit, 0, len);
+            if !fill(slice::from_raw_parts_mut(ptr as *mut E::Unit, len)) {
+                A::free(ptr, mem::align_of::<E::Unit>());
+                return Ok(None);
+            }
+        }
+        Ok(Some((ptr, len, cap, false)))
+    }
+
+    fn owned_bytes(&(_, _, cap, _): &Self::Owned) -> usize {
+        cap.saturating_mul(mem::size_of::<E::Unit>())
+    }
+
+    fn truncate_owned(&mut (_, ref mut owned_len, _, _): &mut Self::Owned, len: usize) -> Result<Option<Self::Owned>, A::AllocError> {
+        // The allocator doesn't need to know the length to free the buffer, so the excess simply becomes spare capacity.
+        assert!(len <= *owned_len);
+        *owned_len = len;
+        Ok(None)
+    }
+
+    fn push_owned(owned: &mut Self::Owned, tail: &[E::Unit]) -> Result<Option<Self::Owned>, A::AllocError> {
+        let (ptr, len, cap, _) = *owned;
+        let total_u = len.checked_add(tail.len())
+            .ok_or_else(A::AllocError::overflow)?;
+
+        if total_u <= cap {
+            unsafe {
                 let s = slice::from_raw_parts_mut(ptr as *mut E::Unit, total_u);
-                s.copy_from_slice(units);
+                s[len..].copy_from_slice(tail);
             }
+            owned.1 = total_u;
+            return Ok(None);
+        }
+
+        // Grow geometrically, so that repeated appends take amortised linear time.
+        let new_cap = ::std::cmp::max(total_u, cap.saturating_mul(2));
+        let mut new = Slice::alloc_owned_concat_cap::<E, A>(<Slice as Structure<E>>::borrow_from_owned(owned), tail, new_cap)?;
+        mem::swap(owned, &mut new);
+        Ok(Some(new))
+    }
 
-            Ok((ptr as *mut (), total_u))
+    fn rehome_owned(owned: &mut Self::Owned) -> Result<Option<Self::Owned>, A::AllocError> {
+        if !owned.3 {
+            return Ok(None);
         }
+
+        let mut new = <Self as StructureAlloc<E, A>>::alloc_owned(<Self as Structure<E>>::borrow_from_owned(owned))?;
+        mem::swap(owned, &mut new);
+        Ok(Some(new))
     }
 
-    fn free_owned(&mut (ptr, _): &mut Self::Owned) {
+    fn free_owned(&mut (ptr, _, cap, adopted): &mut Self::Owned) {
         unsafe {
-            A::free(ptr, mem::align_of::<E::Unit>());
+            if adopted {
+                free_adopted::<E>(ptr, cap);
+            } else {
+                A::free(ptr, mem::align_of::<E::Unit>());
+            }
         }
     }
 }
@@ -566,8 +993,100 @@ impl<E> StructureDefault<E> for Slice where E: Encoding {
     }
 }
 
+impl<'a, E> StructureIter<'a, E> for Slice where E: Encoding {
+    type Iter = ::std::iter::Cloned<slice::Iter<'a, E::Unit>>;
+
+    fn iter(ptr: &'a Self::RefTarget) -> Self::Iter {
+        ptr.iter().cloned()
+    }
+}
+
+impl Slice {
+    /**
+    Allocates a string containing `head` followed by `tail`, with room for `cap` units in total.
+
+    `cap` must be at least the combined length of `head` and `tail`.
+    */
+    pub(crate) fn alloc_owned_concat_cap<E, A>(head: &[E::Unit], tail: &[E::Unit], cap: usize) -> Result<(*mut (), usize, usize, bool), A::AllocError>
+    where E: Encoding, A: Allocator<Pointer=*mut ()> {
+        let total_u = head.len() + tail.len();
+        assert!(total_u <= cap);
+        unsafe {
+            let unit_b = mem::size_of::<E::Unit>();
+            let cap_b = cap.checked_mul(unit_b)
+                .ok_or_else(A::AllocError::overflow)?;
+
+            let ptr = A::alloc_bytes(cap_b, mem::align_of::<E::Unit>())?;
+            {
+                let s = slice::from_raw_parts_mut(ptr as *mut E::Unit, total_u);
+                s[..head.len()].copy_from_slice(head);
+                s[head.len()..].copy_from_slice(tail);
+            }
+
+            Ok((ptr as *mut (), total_u, cap, false))
+        }
+    }
+
+    /**
+    Returns an iterator over the units of a string which may not be suitably aligned for `E::Unit`.
+
+    This is intended for reading strings out of packed or byte-oriented buffers, which `SeStr::from_ptr` will reject.  To get a usable string, collect the result into a `SeaString`.
+
+    # Safety
+
+    `ptr` must be non-null, and point to `len` units which remain valid for `'a`.
+    */
+    pub unsafe fn iter_unaligned<'a, E>(ptr: *const E::FfiUnit, len: usize) -> SliceUnalignedIter<'a, E>
+    where E: Encoding {
+        SliceUnalignedIter {
+            ptr: ptr as *const E::Unit,
+            len: len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/**
+An iterator over the units of a possibly misaligned string.
+
+See `Slice::iter_unaligned`.
+*/
+pub struct SliceUnalignedIter<'a, E> where E: Encoding {
+    ptr: *const E::Unit,
+    len: usize,
+    _marker: PhantomData<&'a E::Unit>,
+}
+
+impl<'a, E> Iterator for SliceUnalignedIter<'a, E> where E: Encoding {
+    type Item = E::Unit;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        unsafe {
+            let unit = ptr::read_unaligned(self.ptr);
+            self.ptr = self.ptr.offset(1);
+            self.len -= 1;
+            Some(unit)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, E> ExactSizeIterator for SliceUnalignedIter<'a, E> where E: Encoding {}
+
 impl KnownLength for Slice {}
 
+/*
+The length of a slice is stored out-of-band, and there is no terminator, so no change to the contents can alter it.
+*/
+unsafe impl MutationSafe for Slice {}
+
 unsafe impl<E> OwnershipTransfer<E> for Slice where E: Encoding {
     type OwnedFfiPtr = (*mut E::FfiUnit, usize);
 
@@ -575,13 +1094,31 @@ unsafe impl<E> OwnershipTransfer<E> for Slice where E: Encoding {
         if ptr.is_null() {
             None
         } else {
-            Some((ptr as *mut (), len))
+            Some((ptr as *mut (), len, len, false))
         }
     }
 
     unsafe fn into_ffi_ptr(ptr: &mut Self::Owned) -> Self::OwnedFfiPtr {
-        let (tptr, tlen) = *ptr;
-        *ptr = (ptr::null_mut(), 0);
+        // Any spare capacity is lost, but the allocator doesn't need it to free the buffer.  Adopted buffers must already have been moved by `rehome_owned`.
+        let (tptr, tlen, _, adopted) = *ptr;
+        debug_assert!(!adopted);
+        *ptr = (ptr::null_mut(), 0, 0, false);
         (tptr as *mut E::FfiUnit, tlen)
     }
 }
+
+/**
+Frees a buffer of `cap` units which was adopted from a `Box` or `Vec`, and so belongs to the global heap rather than to the string's allocator.
+*/
+unsafe fn free_adopted<E>(ptr: *mut (), cap: usize) where E: Encoding {
+    // The buffer was allocated with this layout in the first place, so it cannot be invalid.
+    let layout = Layout::array::<E::Unit>(cap).expect("adopted buffer has an invalid layout");
+    dealloc(ptr as *mut u8, layout);
+}
+
+/**
+Determines whether an address is suitably aligned to point to units of the given encoding.
+*/
+fn is_unit_aligned<E>(addr: usize) -> bool where E: Encoding {
+    addr % mem::align_of::<E::Unit>() == 0
+}
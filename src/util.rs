@@ -1,4 +1,5 @@
 use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::rc::Rc;
 
 pub trait Utf8EncodeExt: Sized + Iterator<Item=char> {
@@ -46,6 +47,13 @@ impl<It> Iterator for Utf8EncodeIter<It> where It: Iterator<Item=char> {
         self.off += 1;
         Some(cu)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every code point produces between one and four units.
+        let buffered = (self.len - self.off) as usize;
+        let (lo, hi) = self.iter.size_hint();
+        (lo.saturating_add(buffered), hi.and_then(|hi| hi.checked_mul(4)).and_then(|hi| hi.checked_add(buffered)))
+    }
 }
 
 pub trait TrapErrExt: Sized + Iterator {
@@ -93,6 +101,60 @@ where
         *self.trap = trapped;
         None
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // An error can end the iteration at any point.
+        match self.iter {
+            Some(ref iter) => (0, iter.size_hint().1),
+            None => (0, Some(0)),
+        }
+    }
+}
+
+pub trait ReplaceErrExt: Sized + Iterator {
+    type Value;
+    fn replace_err(self, replacement: Self::Value) -> ReplaceErrIter<Self, Self::Value>;
+}
+
+impl<It, T, E> ReplaceErrExt for It where It: Iterator<Item=Result<T, E>>, T: Copy {
+    type Value = T;
+
+    fn replace_err(self, replacement: Self::Value) -> ReplaceErrIter<Self, Self::Value> {
+        ReplaceErrIter {
+            iter: self,
+            replacement: replacement,
+        }
+    }
+}
+
+/**
+Substitutes a fixed value for every error.
+
+This is only useful on iterators which resume after an error, such as those implementing `Recoverable`; anything else would substitute the value once and then end.
+*/
+pub struct ReplaceErrIter<It, T> {
+    iter: It,
+    replacement: T,
+}
+
+impl<It, T, E> Iterator for ReplaceErrIter<It, T>
+where
+    It: Iterator<Item=Result<T, E>>,
+    T: Copy,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.iter.next() {
+            Some(Ok(v)) => Some(v),
+            Some(Err(_)) => Some(self.replacement),
+            None => None,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
 }
 
 pub trait LiftErrExt: Sized + Iterator {
@@ -113,7 +175,7 @@ impl<It, T, E> LiftErrExt for It where It: Iterator<Item=Result<T, E>> {
         Over: Iterator<Item=Result<U, F>>,
         Self::Trap: Into<F>,
     {
-        let trap = Rc::new(RefCell::new(None));
+        let trap = Rc::new(RefCell::new(VecDeque::new()));
         let middle = LiftTrapErrIter {
             iter: self,
             trap: trap.clone(),
@@ -121,14 +183,22 @@ impl<It, T, E> LiftErrExt for It where It: Iterator<Item=Result<T, E>> {
         let over = wrap(middle);
         LiftErrIter {
             iter: Some(over),
+            held: None,
             trap: trap,
         }
     }
 }
 
-pub struct LiftErrIter<It, Err> {
+/**
+Passes on errors from a lower layer of a transcoder, alongside those of the layer over it.
+
+Each error taken out of the lower layer by `LiftTrapErrIter` is emitted in the position of the lower units it stood for.  The upper layer never sees the error, and carries on with whatever the lower layer produces next; this means a lower layer which resumes after an error is not cut short by being lifted.
+*/
+pub struct LiftErrIter<It, Err> where It: Iterator {
     iter: Option<It>,
-    trap: Rc<RefCell<Option<Err>>>,
+    // Taken from `iter` while lifting the errors which came before it.
+    held: Option<It::Item>,
+    trap: Rc<RefCell<VecDeque<Err>>>,
 }
 
 impl<It, Err, LiftErr, T> Iterator for LiftErrIter<It, LiftErr>
@@ -139,23 +209,52 @@ where
     type Item = Result<T, Err>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.trap.borrow_mut().pop_front() {
+            return Some(Err(err.into()));
+        }
+
+        if let Some(next) = self.held.take() {
+            return Some(next);
+        }
+
         let next = match self.iter.as_mut() {
             Some(iter) => iter.next(),
             None => return None,
         };
 
-        if let Some(err) = self.trap.borrow_mut().take() {
+        if next.is_none() {
             self.iter = None;
-            return Some(Err(err.into()));
         }
 
-        next
+        let lifted = self.trap.borrow_mut().pop_front();
+        match lifted {
+            Some(err) => {
+                self.held = next;
+                Some(Err(err.into()))
+            },
+            None => next,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let buffered = self.trap.borrow().len() + if self.held.is_some() { 1 } else { 0 };
+        // A lifted error may take the place of the end of `iter`.
+        match self.iter {
+            Some(ref iter) => {
+                let (lo, hi) = iter.size_hint();
+                (lo.saturating_add(buffered), hi.and_then(|hi| hi.checked_add(buffered + 1)))
+            },
+            None => (buffered, Some(buffered)),
+        }
     }
 }
 
+/**
+Hands the successful units of a lower transcoder layer to the layer over it, setting errors aside for `LiftErrIter`.
+*/
 pub struct LiftTrapErrIter<It, Err> {
     iter: It,
-    trap: Rc<RefCell<Option<Err>>>,
+    trap: Rc<RefCell<VecDeque<Err>>>,
 }
 
 impl<It, Err, T> Iterator for LiftTrapErrIter<It, Err>
@@ -165,34 +264,177 @@ where
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.iter.next() {
-            Some(Ok(v)) => Some(v),
-            Some(Err(err)) => {
-                *self.trap.borrow_mut() = Some(err);
-                None
-            },
-            None => None,
+        loop {
+            match self.iter.next() {
+                Some(Ok(v)) => return Some(v),
+                Some(Err(err)) => self.trap.borrow_mut().push_back(err),
+                None => return None,
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Any of the remaining items may be an error, which is set aside.
+        (0, self.iter.size_hint().1)
+    }
+}
+
+/**
+Implemented by transcoding iterators which know how far through their source they are.
+*/
+pub trait SourceOffset {
+    /**
+    Returns the source offset of the character which produces the next item.
+    */
+    fn source_offset(&self) -> usize;
+}
+
+/**
+Implemented by errors whose offset can be rewritten.
+*/
+pub trait MapOffset: Sized {
+    fn map_offset<F>(self, f: F) -> Self where F: FnOnce(usize) -> usize;
+}
+
+/**
+How many items `OffsetLog` remembers.  Later stages only ever report errors at one of the last couple of items they consumed.
+*/
+const OFFSET_LOG_LEN: usize = 4;
+
+/**
+Records the source offsets of the most recent items produced by the first stage of a layered transcoder.
+
+This lets errors from a later stage, which can only count items of the intermediate encoding, be reported as offsets into the original source.
+*/
+pub struct OffsetLog {
+    len: usize,
+    offsets: [usize; OFFSET_LOG_LEN],
+}
+
+impl OffsetLog {
+    pub fn new() -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(OffsetLog {
+            len: 0,
+            offsets: [0; OFFSET_LOG_LEN],
+        }))
+    }
+
+    fn push(&mut self, offset: usize) {
+        self.offsets[self.len % OFFSET_LOG_LEN] = offset;
+        self.len += 1;
+    }
+
+    /**
+    Returns the source offset of intermediate item `at`, if it is still remembered.
+    */
+    fn get(&self, at: usize) -> Option<usize> {
+        if at < self.len && self.len - at <= OFFSET_LOG_LEN {
+            Some(self.offsets[at % OFFSET_LOG_LEN])
+        } else {
+            None
+        }
+    }
+}
+
+/**
+Logs the source offset of each successful item of `iter`.
+*/
+pub struct TrackOffsetIter<It> {
+    iter: It,
+    log: Rc<RefCell<OffsetLog>>,
+}
+
+impl<It> TrackOffsetIter<It> {
+    pub fn new(iter: It, log: Rc<RefCell<OffsetLog>>) -> Self {
+        TrackOffsetIter {
+            iter: iter,
+            log: log,
         }
     }
 }
 
-pub trait Unsigned: Sized {
-    type Unsigned;
-    fn unsigned(self) -> Self::Unsigned;
+impl<It, T, E> Iterator for TrackOffsetIter<It>
+where
+    It: Iterator<Item=Result<T, E>> + SourceOffset,
+{
+    type Item = Result<T, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.iter.source_offset();
+        let next = self.iter.next();
+        if let Some(Ok(_)) = next {
+            self.log.borrow_mut().push(offset);
+        }
+        next
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/**
+Rewrites the offsets of errors from `iter` using an `OffsetLog`.
+*/
+pub struct MapOffsetIter<It> {
+    iter: It,
+    log: Rc<RefCell<OffsetLog>>,
 }
 
-impl Unsigned for u16 {
-    type Unsigned = u16;
-    fn unsigned(self) -> Self::Unsigned {
-        self
+impl<It> MapOffsetIter<It> {
+    pub fn new(iter: It, log: Rc<RefCell<OffsetLog>>) -> Self {
+        MapOffsetIter {
+            iter: iter,
+            log: log,
+        }
     }
 }
 
-impl Unsigned for i32 {
-    type Unsigned = u32;
-    fn unsigned(self) -> Self::Unsigned {
-        self as Self::Unsigned
+impl<It, T, E> Iterator for MapOffsetIter<It>
+where
+    It: Iterator<Item=Result<T, E>>,
+    E: MapOffset,
+{
+    type Item = Result<T, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let log = &self.log;
+        self.iter.next().map(|r| r.map_err(|err| err.map_offset(|at| log.borrow().get(at).unwrap_or(at))))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
     }
 }
 
 pub fn id<T>(v: T) -> T { v }
+
+/**
+Writes the UTF-8 encoding of `cp` to `dst`, returning the number of bytes written.
+
+# Safety
+
+`cp` must be a valid code point, and `dst` must have room for its encoding.
+*/
+#[inline]
+pub unsafe fn write_utf8(dst: *mut u8, cp: u32) -> usize {
+    if cp < 0x80 {
+        *dst = cp as u8;
+        1
+    } else if cp < 0x800 {
+        *dst = 0xc0 | (cp >> 6) as u8;
+        *dst.offset(1) = 0x80 | (cp & 0x3f) as u8;
+        2
+    } else if cp < 0x10000 {
+        *dst = 0xe0 | (cp >> 12) as u8;
+        *dst.offset(1) = 0x80 | ((cp >> 6) & 0x3f) as u8;
+        *dst.offset(2) = 0x80 | (cp & 0x3f) as u8;
+        3
+    } else {
+        *dst = 0xf0 | (cp >> 18) as u8;
+        *dst.offset(1) = 0x80 | ((cp >> 12) & 0x3f) as u8;
+        *dst.offset(2) = 0x80 | ((cp >> 6) & 0x3f) as u8;
+        *dst.offset(3) = 0x80 | (cp & 0x3f) as u8;
+        4
+    }
+}
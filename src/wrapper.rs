@@ -8,7 +8,7 @@ use std::ops::{Deref, DerefMut, Index, IndexMut, RangeFull};
 use libc::{c_char};
 use alloc::{AllocError, Malloc};
 use encoding::{MbUnit, MultiByte};
-use sea::{SeStr, SeaString};
+use sea::{SeStr, SeaString, TranscodeTarget};
 use structure::{ZeroTerm, ZeroTermIter};
 
 macro_rules! nyi {
@@ -33,7 +33,7 @@ This type *may* be used in FFI signatures and types, but we nonetheless recommen
 
 See also: `ZMbCString`.
 */
-#[repr(C)]
+#[repr(transparent)]
 pub struct ZMbStr(ZMbStrInner);
 
 impl ZMbStr {
@@ -73,6 +73,32 @@ impl ZMbStr {
         SeStr::from_ptr_mut(ptr).map(Into::into)
     }
 
+    /**
+    Determines whether the foreign string at `ptr` consists of exactly the units in `expected`, without borrowing it.
+
+    At most `expected.len() + 1` units are read.  If `ptr` is null, returns `false`.
+
+    # Safety
+
+    The caller asserts that `ptr` is either null, or points to a valid zero-terminated string.
+    */
+    pub unsafe fn ptr_eq_units(ptr: *const c_char, expected: &[MbUnit]) -> bool {
+        ZMbStrInner::ptr_eq_units(ptr, expected)
+    }
+
+    /**
+    Determines whether the foreign string at `ptr` begins with the units in `expected`, without borrowing it.
+
+    At most `expected.len()` units are read.  If `ptr` is null, returns `false`.
+
+    # Safety
+
+    The caller asserts that `ptr` is either null, or points to a valid zero-terminated string.
+    */
+    pub unsafe fn ptr_starts_with(ptr: *const c_char, expected: &[MbUnit]) -> bool {
+        ZMbStrInner::ptr_starts_with(ptr, expected)
+    }
+
     /**
     Returns the units comprising this string as a contiguous slice.  This *does not* include the terminating zero.
 
@@ -95,6 +121,26 @@ impl ZMbStr {
         self.0.as_units_with_term()
     }
 
+    /**
+    Returns the length of this string, in units.  This *does not* include the terminating zero.
+
+    # Efficiency
+
+    Note that this method will require a complete traversal of the underlying memory in order to compute the string's length.  You should avoid calling this method repeatedly.
+    */
+    pub fn unit_len(&self) -> usize {
+        self.0.unit_len()
+    }
+
+    /**
+    Determines whether this string has no contents.
+
+    Unlike `unit_len`, this only inspects the first unit.
+    */
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     /**
     Returns the units comprising this string as a contiguous, mutable slice.  This *does not* include the terminating zero.
 
@@ -145,7 +191,7 @@ impl ZMbStr {
     This conversion will fail if the string contains any units which cannot be translated into Unicode.
     */
     pub fn into_string(&self) -> Result<String, Box<StdError>> {
-        self.0.into_string()
+        self.0.into_string_bulk()
     }
 }
 
@@ -216,7 +262,7 @@ This type *may* be used in FFI signatures and types, but we nonetheless recommen
 
 See also: `ZMbCString`.
 */
-#[repr(C)]
+#[repr(transparent)]
 pub struct ZMbCString(ZMbCStringInner);
 
 impl ZMbCString {
@@ -229,7 +275,6 @@ impl ZMbCString {
 
     Construction can also fail if the string contains zero units anywhere *other* than at the end.
     */
-    // TODO: what about interior zeroes?
     pub fn new(units: &[MbUnit]) -> Result<Self, AllocError> {
         ZMbCStringInner::new(units).map(Into::into)
     }
@@ -401,3 +446,13 @@ impl Ord for ZMbCString {
         self.as_units().cmp(other.as_units())
     }
 }
+
+impl TranscodeTarget for ZMbCString {
+    type Structure = ZeroTerm;
+    type Encoding = MultiByte;
+    type Allocator = Malloc;
+
+    fn from_sea_string(s: ZMbCStringInner) -> Self {
+        s.into()
+    }
+}
@@ -0,0 +1,98 @@
+#![cfg(feature="alloc-stats")]
+
+extern crate strffi;
+
+mod common;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::alloc::stats::{self, AllocCounts};
+use strffi::encoding::{Utf8, Utf8Unit, Utf16, Utf16Le, Utf16Unit};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+use common::utf8;
+
+/*
+The hook and its counts are process-wide, so everything is checked from a single test.
+*/
+#[test]
+fn test_counting_hook() {
+    assert!(stats::set_stats_hook(Some(stats::counting_hook)).is_none());
+    stats::reset_counts();
+
+    let mut s = SeaString::<ZeroTerm, Utf8, Malloc>::new(&utf8(b"gar")).expect(here!());
+    assert_eq!(stats::counts(), AllocCounts { allocs: 1, frees: 0, alloc_bytes: 4, free_bytes: 0 });
+
+    s.push_units(&utf8(b"\xc3\xa7on")).expect(here!());
+    assert_eq!(stats::counts(), AllocCounts { allocs: 2, frees: 1, alloc_bytes: 12, free_bytes: 4 });
+    assert_eq!(stats::counts().live_bytes(), s.allocated_bytes());
+
+    // Truncating in place only reports the bytes given up, not a new allocation.
+    let mut t = s.clone();
+    let before = stats::counts();
+    unsafe { t.truncate_unsafe(3) };
+    let after = stats::counts();
+    assert_eq!((after.allocs, after.frees), (before.allocs, before.frees));
+    assert_eq!(after.free_bytes, before.free_bytes + 4);
+    drop(t);
+    assert_eq!(stats::counts().live_bytes(), s.allocated_bytes());
+
+    // Handing the string to foreign code and taking it back is balanced.
+    let ptr = s.into_ptr();
+    assert_eq!(stats::counts().live(), 0);
+    let s = unsafe { SeaString::<ZeroTerm, Utf8, Malloc>::from_ptr(ptr) }.expect(here!());
+    assert_eq!(stats::counts().live_bytes(), 8);
+
+    let t = s.clone();
+    drop(s);
+    drop(t);
+    let counts = stats::counts();
+    assert_eq!(counts.live(), 0);
+    assert_eq!(counts.live_bytes(), 0);
+    assert_eq!(counts.allocs, 5);
+
+    // Collecting an iterator of known length allocates once, and is reported like any other allocation.
+    let s: SeaString<ZeroTerm, Utf8, Malloc> = utf8(b"garcon").into_iter().collect();
+    assert_eq!(stats::counts().live_bytes(), 7);
+    drop(s);
+    assert_eq!(stats::counts().live(), 0);
+
+    // Owned iterators free the string exactly once, whether or not they finish.
+    let mut iter = SeaString::<ZeroTerm, Utf8, Malloc>::new(&utf8(b"garcon")).expect(here!()).into_iter();
+    assert_eq!(iter.next(), Some(Utf8Unit(b'g')));
+    assert_eq!(stats::counts().live(), 1);
+    drop(iter);
+    assert_eq!(stats::counts().live(), 0);
+
+    let iter = SeaString::<ZeroTerm, Utf8, Malloc>::new(&utf8(b"garcon")).expect(here!()).into_iter();
+    assert_eq!(iter.count(), 6);
+    let counts = stats::counts();
+    assert_eq!(counts.live(), 0);
+    assert_eq!(counts.allocs, 8);
+
+    // Borrowing a string as a transparent encoding allocates nothing; converting it does.
+    let s = SeaString::<ZeroTerm, Utf8, Malloc>::new(&utf8(b"garcon")).expect(here!());
+    let units: Vec<_> = "garçon".encode_utf16().map(Utf16Unit).collect();
+    let before = stats::counts();
+    {
+        let cow = s.transcode_to_cow::<Utf8>().expect(here!());
+        assert!(cow.is_borrowed());
+        let cow = SeStr::<Slice, Utf16>::new(&units).transcode_to_cow::<Utf16Le>().expect(here!());
+        assert_eq!(cow.is_borrowed(), cfg!(target_endian="little"));
+    }
+    let after = stats::counts();
+    if cfg!(target_endian="little") {
+        assert_eq!(after, before);
+    }
+    {
+        let cow = s.transcode_to_cow::<Utf16>().expect(here!());
+        assert!(!cow.is_borrowed());
+        assert_eq!(stats::counts().allocs, after.allocs + 1);
+    }
+    drop(s);
+
+    assert!(stats::set_stats_hook(None).is_some());
+    let _ = SeaString::<ZeroTerm, Utf8, Malloc>::new(&utf8(b"garcon")).expect(here!());
+    assert_eq!(stats::counts().allocs, 10);
+}
@@ -0,0 +1,48 @@
+extern crate strffi;
+
+mod common;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use std::mem;
+use strffi::alloc::{Allocator, Global, Malloc, Rust};
+use strffi::encoding::{Utf8, Wide, WUnit};
+use strffi::sea::SeaString;
+use strffi::structure::{Slice, ZeroTerm};
+use common::utf8;
+
+#[test]
+fn test_allocated_bytes_malloc() {
+    let units = utf8(b"gar\xc3\xa7on");
+
+    // Byte-aligned allocations need no padding, so only the terminator differs.
+    let s = SeaString::<ZeroTerm, Utf8, Malloc>::new(&units).expect(here!());
+    assert_eq!(s.allocated_bytes(), 8);
+    let s = SeaString::<Slice, Utf8, Malloc>::new(&units).expect(here!());
+    assert_eq!(s.allocated_bytes(), 7);
+
+    let units: Vec<_> = "garçon".chars().map(|c| WUnit(c as u32 as _)).collect();
+    let s = SeaString::<ZeroTerm, Wide, Malloc>::new(&units).expect(here!());
+    assert_eq!(s.allocated_bytes(), 7 * mem::size_of::<WUnit>());
+}
+
+#[test]
+fn test_allocated_bytes_headers() {
+    let units = utf8(b"garcon");
+
+    let s = SeaString::<ZeroTerm, Utf8, Global>::new(&units).expect(here!());
+    assert_eq!(s.allocated_bytes(), Global::allocated_bytes(7, 1));
+    assert!(s.allocated_bytes() > 7);
+
+    let s = SeaString::<Slice, Utf8, Rust>::new(&units).expect(here!());
+    assert_eq!(s.allocated_bytes(), Rust::allocated_bytes(6, 1));
+    assert!(s.allocated_bytes() > 6);
+}
+
+#[test]
+fn test_allocated_bytes_push() {
+    let mut s = SeaString::<ZeroTerm, Utf8, Malloc>::new(&utf8(b"gar")).expect(here!());
+    assert_eq!(s.allocated_bytes(), 4);
+    s.push_units(&utf8(b"\xc3\xa7on")).expect(here!());
+    assert_eq!(s.allocated_bytes(), 8);
+}
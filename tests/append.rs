@@ -0,0 +1,58 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{Utf16Le, MultiByte, MbUnit, Wide};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+
+fn mb(s: &[u8]) -> Vec<MbUnit> {
+    s.iter().map(|&b| MbUnit(b as _)).collect()
+}
+
+#[test]
+fn test_push_units_zero_term() {
+    let mut s = SeaString::<ZeroTerm, MultiByte, Malloc>::new(&mb(b"ab")).expect(here!());
+    s.push_units(&mb(b"cd")).expect(here!());
+    assert_eq!(s.as_units(), &mb(b"abcd")[..]);
+    assert_eq!(s.as_units_with_term(), &mb(b"abcd\0")[..]);
+
+    s.push_units(&[]).expect(here!());
+    assert_eq!(s.as_units_with_term(), &mb(b"abcd\0")[..]);
+
+    let mut s = SeaString::<ZeroTerm, MultiByte, Malloc>::new(&[]).expect(here!());
+    s.push_units(&mb(b"x")).expect(here!());
+    assert_eq!(s.as_units_with_term(), &mb(b"x\0")[..]);
+}
+
+#[test]
+fn test_push_units_slice() {
+    let mut s = SeaString::<Slice, MultiByte, Malloc>::new(&mb(b"ab")).expect(here!());
+    s.push_units(&mb(b"c")).expect(here!());
+    assert_eq!(s.as_units(), &mb(b"abc")[..]);
+}
+
+#[test]
+fn test_push_str() {
+    let mut s = SeaString::<ZeroTerm, Wide, Malloc>::from_str("gar").expect(here!());
+    s.push_str("çon").expect(here!());
+    assert_eq!(s.into_string().expect(here!()), "garçon");
+
+    let mut s = SeaString::<Slice, Utf16Le, Malloc>::from_str("").expect(here!());
+    s.push_str("😀").expect(here!());
+    s.push_str("!").expect(here!());
+    assert_eq!(s.into_string().expect(here!()), "😀!");
+}
+
+#[test]
+fn test_add() {
+    let tail = mb(b"cd");
+    let s = SeaString::<ZeroTerm, MultiByte, Malloc>::new(&mb(b"ab")).expect(here!());
+    let s = s + SeStr::<Slice, MultiByte>::new(&tail);
+    assert_eq!(s.as_units_with_term(), &mb(b"abcd\0")[..]);
+
+    let other = SeaString::<ZeroTerm, MultiByte, Malloc>::new(&mb(b"ef")).expect(here!());
+    let s = s + &*other;
+    assert_eq!(s.as_units(), &mb(b"abcdef")[..]);
+}
@@ -0,0 +1,83 @@
+extern crate strffi;
+
+mod common;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::{Allocator, AllocError, Arena};
+use strffi::encoding::{Utf8, Wide, WUnit};
+use strffi::sea::SeaString;
+use strffi::structure::{Slice, ZeroTerm};
+use common::utf8;
+
+#[test]
+fn test_arena_no_scope() {
+    assert!(!Arena::is_active());
+    assert_eq!(Arena::alloc_bytes(4, 1), Err(AllocError::NoArena));
+    assert!(SeaString::<Slice, Utf8, Arena>::new(&utf8("abc")).is_err());
+}
+
+#[test]
+fn test_arena_scope() {
+    unsafe {
+        Arena::scope_with_chunk_size(64, || {
+            assert!(Arena::is_active());
+
+            let strs: Vec<_> = (0..1000)
+                .map(|i| SeaString::<Slice, Utf8, Arena>::new(&utf8(&i.to_string())).expect(here!()))
+                .collect();
+
+            for (i, s) in strs.iter().enumerate() {
+                assert_eq!(s.as_units(), &utf8(&i.to_string())[..]);
+            }
+
+            // Larger than a single chunk.
+            let big = utf8(&"x".repeat(1000));
+            let s = SeaString::<Slice, Utf8, Arena>::new(&big).expect(here!());
+            assert_eq!(s.as_units(), &big[..]);
+
+            let wide: Vec<_> = (0x61..0x7b).map(WUnit).collect();
+            let s = SeaString::<ZeroTerm, Wide, Arena>::new(&wide).expect(here!());
+            assert_eq!(s.as_units(), &wide[..]);
+        });
+    }
+    assert!(!Arena::is_active());
+}
+
+#[test]
+fn test_arena_align() {
+    unsafe {
+        Arena::scope(|| {
+            for &align in &[1, 2, 4, 8, 16, 32, 64] {
+                let _ = Arena::alloc_bytes(1, 1).expect(here!());
+                let ptr = Arena::alloc_bytes(3, align).expect(here!());
+                assert_eq!(ptr as usize % align, 0);
+            }
+            assert_eq!(Arena::alloc_bytes(4, 3), Err(AllocError::CannotAlign));
+        });
+    }
+}
+
+#[test]
+fn test_arena_nested() {
+    unsafe {
+        Arena::scope(|| {
+            let outer = SeaString::<Slice, Utf8, Arena>::new(&utf8("outer")).expect(here!());
+            Arena::scope(|| {
+                let inner = SeaString::<Slice, Utf8, Arena>::new(&utf8("inner")).expect(here!());
+                assert_eq!(inner.as_units(), &utf8("inner")[..]);
+            });
+            assert!(Arena::is_active());
+            assert_eq!(outer.as_units(), &utf8("outer")[..]);
+        });
+    }
+}
+
+#[test]
+fn test_arena_panic() {
+    let r = ::std::panic::catch_unwind(|| unsafe {
+        Arena::scope(|| panic!("oops"))
+    });
+    assert!(r.is_err());
+    assert!(!Arena::is_active());
+}
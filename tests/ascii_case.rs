@@ -0,0 +1,71 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{Raw8, Raw8Unit, Utf8, Utf8Unit};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+
+const HEADER: &'static [u8] = b"Content-Length\0";
+
+#[test]
+fn test_eq_ignore_ascii_case() {
+    let header = unsafe { SeStr::<ZeroTerm, Utf8>::from_ptr(HEADER.as_ptr()) }.expect(here!());
+
+    let lower: Vec<_> = "content-length".bytes().map(Utf8Unit).collect();
+    let upper: Vec<_> = "CONTENT-LENGTH".bytes().map(Utf8Unit).collect();
+    let other: Vec<_> = "content-type".bytes().map(Utf8Unit).collect();
+    assert!(header.eq_ignore_ascii_case(SeStr::<Slice, Utf8>::new(&lower)));
+    assert!(header.eq_ignore_ascii_case(SeStr::<Slice, Utf8>::new(&upper)));
+    assert!(!header.eq_ignore_ascii_case(SeStr::<Slice, Utf8>::new(&other)));
+    assert!(!header.eq_ignore_ascii_case(SeStr::<Slice, Utf8>::new(&lower[..7])));
+}
+
+#[test]
+fn test_eq_ignore_ascii_case_non_ascii() {
+    // "É" and "é" differ only outside the ASCII range, and must not be folded.
+    let a: Vec<_> = "É".bytes().map(Utf8Unit).collect();
+    let b: Vec<_> = "é".bytes().map(Utf8Unit).collect();
+    assert!(!SeStr::<Slice, Utf8>::new(&a).eq_ignore_ascii_case(SeStr::<Slice, Utf8>::new(&b)));
+
+    // 0xc1 and 0xe1 differ by 0x20, like an ASCII case pair.
+    let a = [Raw8Unit(0xc1)];
+    let b = [Raw8Unit(0xe1)];
+    assert!(!SeStr::<Slice, Raw8>::new(&a).eq_ignore_ascii_case(SeStr::<Slice, Raw8>::new(&b)));
+
+    // '@' and '`' also differ by 0x20.
+    let a = [Raw8Unit(b'@')];
+    let b = [Raw8Unit(b'`')];
+    assert!(!SeStr::<Slice, Raw8>::new(&a).eq_ignore_ascii_case(SeStr::<Slice, Raw8>::new(&b)));
+}
+
+#[test]
+fn test_to_ascii_case() {
+    let header = unsafe { SeStr::<ZeroTerm, Utf8>::from_ptr(HEADER.as_ptr()) }.expect(here!());
+
+    let lower: SeaString<ZeroTerm, Utf8, Malloc> = header.to_ascii_lowercase().expect(here!());
+    let upper: SeaString<ZeroTerm, Utf8, Malloc> = header.to_ascii_uppercase().expect(here!());
+    assert_eq!(lower.to_byte_vec(), b"content-length");
+    assert_eq!(upper.to_byte_vec(), b"CONTENT-LENGTH");
+
+    let s = SeaString::<ZeroTerm, Utf8, Malloc>::from_bytes_copied("Grüße".as_bytes()).expect(here!());
+    let upper: SeaString<ZeroTerm, Utf8, Malloc> = s.to_ascii_uppercase().expect(here!());
+    assert_eq!(upper.to_byte_vec(), "GRüßE".as_bytes());
+}
+
+#[test]
+fn test_make_ascii_lowercase() {
+    let mut units: Vec<_> = "X-Forwarded-For: Ärger".bytes().map(Utf8Unit).collect();
+    SeStr::<Slice, Utf8>::new_mut(&mut units).make_ascii_lowercase();
+    let expected: Vec<_> = "x-forwarded-for: Ärger".bytes().map(Utf8Unit).collect();
+    assert_eq!(units, expected);
+}
+
+#[test]
+fn test_make_ascii_uppercase() {
+    let mut units: Vec<_> = b"--verbose\xff".iter().map(|&b| Raw8Unit(b)).collect();
+    SeStr::<Slice, Raw8>::new_mut(&mut units).make_ascii_uppercase();
+    let expected: Vec<_> = b"--VERBOSE\xff".iter().map(|&b| Raw8Unit(b)).collect();
+    assert_eq!(units, expected);
+}
@@ -0,0 +1,145 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::encoding::{Bom, CheckedUnicode, Endianness, Raw8, Raw8Unit, Utf16, Utf16Unit, Utf32, Utf32Unit};
+use strffi::encoding::conv::utf16::Utf16ToUniError;
+use strffi::sea::SeStr;
+use strffi::structure::Slice;
+
+fn utf16(s: &str) -> Vec<Utf16Unit> {
+    s.encode_utf16().map(Utf16Unit).collect()
+}
+
+fn utf32(s: &str) -> Vec<Utf32Unit> {
+    s.chars().map(|c| Utf32Unit(c as u32)).collect()
+}
+
+fn raw8(bytes: &[u8]) -> Vec<Raw8Unit> {
+    bytes.iter().cloned().map(Raw8Unit).collect()
+}
+
+#[test]
+fn test_utf16() {
+    let native = Endianness::native();
+
+    let us = utf16("\u{feff}abc");
+    let s = SeStr::<Slice, Utf16>::new(&us);
+    assert_eq!(s.detect_bom(), Some(native));
+    assert_eq!(s.strip_bom().as_units(), &us[1..]);
+    // The stripped string borrows from the original.
+    assert_eq!(s.strip_bom().as_units().as_ptr(), us[1..].as_ptr());
+
+    let us = vec![Utf16Unit(0xfffe), Utf16Unit(0x6100)];
+    let s = SeStr::<Slice, Utf16>::new(&us);
+    assert_eq!(s.detect_bom(), Some(native.swap()));
+    assert_eq!(s.strip_bom().as_units(), &us[1..]);
+
+    let us = utf16("\u{feff}");
+    let s = SeStr::<Slice, Utf16>::new(&us);
+    assert_eq!(s.detect_bom(), Some(native));
+    assert!(s.strip_bom().as_units().is_empty());
+
+    let us = utf16("abc\u{feff}");
+    let s = SeStr::<Slice, Utf16>::new(&us);
+    assert_eq!(s.detect_bom(), None);
+    assert_eq!(s.strip_bom().as_units(), &us[..]);
+
+    let s = SeStr::<Slice, Utf16>::new(&[]);
+    assert_eq!(s.detect_bom(), None);
+    assert!(s.strip_bom().as_units().is_empty());
+}
+
+#[test]
+fn test_utf32() {
+    let native = Endianness::native();
+
+    let us = utf32("\u{feff}abc");
+    let s = SeStr::<Slice, Utf32>::new(&us);
+    assert_eq!(s.detect_bom(), Some(native));
+    assert_eq!(s.strip_bom().as_units(), &us[1..]);
+
+    let us = vec![Utf32Unit(0xfffe0000)];
+    let s = SeStr::<Slice, Utf32>::new(&us);
+    assert_eq!(s.detect_bom(), Some(native.swap()));
+    assert!(s.strip_bom().as_units().is_empty());
+
+    let us = utf32("abc");
+    let s = SeStr::<Slice, Utf32>::new(&us);
+    assert_eq!(s.detect_bom(), None);
+    assert_eq!(s.strip_bom().as_units(), &us[..]);
+}
+
+#[test]
+fn test_raw8() {
+    let cases: &[(&[u8], Option<Bom>)] = &[
+        (b"\xef\xbb\xbfabc", Some(Bom::Utf8)),
+        (b"\xff\xfea\x00", Some(Bom::Utf16(Endianness::Little))),
+        (b"\xfe\xff\x00a", Some(Bom::Utf16(Endianness::Big))),
+        (b"\xff\xfe\x00\x00a\x00\x00\x00", Some(Bom::Utf32(Endianness::Little))),
+        (b"\x00\x00\xfe\xff\x00\x00\x00a", Some(Bom::Utf32(Endianness::Big))),
+        (b"\xef\xbb\xbf", Some(Bom::Utf8)),
+        (b"\xef\xbb", None),
+        (b"\xff", None),
+        (b"abc", None),
+        (b"", None),
+    ];
+
+    for &(bytes, bom) in cases {
+        let us = raw8(bytes);
+        let s = SeStr::<Slice, Raw8>::new(&us);
+        assert_eq!(s.detect_bom(), bom, "{:?}", bytes);
+        let len = bom.map(|bom| bom.len()).unwrap_or(0);
+        assert_eq!(s.strip_bom().as_units(), &us[len..], "{:?}", bytes);
+    }
+}
+
+#[test]
+fn test_raw8_utf16le() {
+    // A UTF-16LE buffer, as it might be read from a file or the clipboard.
+    let mut bytes = vec![];
+    for cu in "\u{feff}h\u{e9}llo".encode_utf16() {
+        bytes.push(cu as u8);
+        bytes.push((cu >> 8) as u8);
+    }
+
+    let s = SeStr::<Slice, Raw8>::from_bytes(&bytes);
+    assert_eq!(s.detect_bom(), Some(Bom::Utf16(Endianness::Little)));
+    assert_eq!(s.strip_bom().as_raw_bytes(), &bytes[2..]);
+
+    if let Some(s) = s.strip_bom().as_utf16le() {
+        assert_eq!(s.into_string().expect(here!()), "h\u{e9}llo");
+    }
+}
+
+#[test]
+fn test_skip_bom() {
+    let us = utf16("\u{feff}abc");
+    let s = SeStr::<Slice, Utf16>::new(&us);
+    let r: String = s.transcode_to_iter::<CheckedUnicode>().collect::<Result<_, _>>().expect(here!());
+    assert_eq!(r, "\u{feff}abc");
+    let r: String = s.transcode_to_iter::<CheckedUnicode>().skip_bom().collect::<Result<_, _>>().expect(here!());
+    assert_eq!(r, "abc");
+
+    // Only the first character is treated as a byte order mark.
+    let us = utf16("a\u{feff}");
+    let s = SeStr::<Slice, Utf16>::new(&us);
+    let r: String = s.transcode_to_iter::<CheckedUnicode>().skip_bom().collect::<Result<_, _>>().expect(here!());
+    assert_eq!(r, "a\u{feff}");
+
+    let us = utf16("\u{feff}");
+    let s = SeStr::<Slice, Utf16>::new(&us);
+    assert_eq!(s.transcode_to_iter::<CheckedUnicode>().skip_bom().next(), None);
+
+    // Error offsets still count the byte order mark.
+    let mut us = utf16("\u{feff}a");
+    us.push(Utf16Unit(0xdc00));
+    let s = SeStr::<Slice, Utf16>::new(&us);
+    let r: Result<String, _> = s.transcode_to_iter::<CheckedUnicode>().skip_bom().collect();
+    assert_eq!(r, Err(Utf16ToUniError::InvalidAt(2)));
+
+    let us = utf32("\u{feff}abc");
+    let s = SeStr::<Slice, Utf32>::new(&us);
+    let r: String = s.transcode_to_iter::<CheckedUnicode>().skip_bom().collect::<Result<_, _>>().expect(here!());
+    assert_eq!(r, "abc");
+}
@@ -0,0 +1,80 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use std::rc::Rc;
+use std::sync::Arc;
+use strffi::alloc::{Global, Malloc, Rust};
+use strffi::encoding::{Utf16, Utf16Unit};
+use strffi::sea::SeaString;
+use strffi::structure::{Slice, ZeroTerm};
+
+fn utf16(s: &str) -> Vec<Utf16Unit> {
+    s.encode_utf16().map(Utf16Unit).collect()
+}
+
+#[test]
+fn test_boxed_units() {
+    let boxed = utf16("garçon").into_boxed_slice();
+    let s = SeaString::<Slice, Utf16, Rust>::from_boxed_units(boxed.clone()).expect(here!());
+    assert_eq!(s.as_units(), &boxed[..]);
+    assert_eq!(s.into_boxed_units(), boxed);
+
+    let empty = SeaString::<Slice, Utf16, Rust>::from_boxed_units(Vec::new().into_boxed_slice()).expect(here!());
+    assert!(empty.into_boxed_units().is_empty());
+}
+
+#[test]
+fn test_boxed_units_handover() {
+    // The buffer is adopted, and handed back, without being copied.
+    let boxed = utf16("garçon").into_boxed_slice();
+    let ptr = boxed.as_ptr();
+    let s = SeaString::<Slice, Utf16, Rust>::from_boxed_units(boxed).expect(here!());
+    assert_eq!(s.as_units().as_ptr(), ptr);
+    let boxed = s.into_boxed_units();
+    assert_eq!(boxed.as_ptr(), ptr);
+    assert_eq!(&boxed[..], &utf16("garçon")[..]);
+
+    let s = SeaString::<Slice, Utf16, Global>::from_boxed_units(boxed).expect(here!());
+    assert_eq!(s.as_units().as_ptr(), ptr);
+    let boxed = s.into_boxed_units();
+    assert_eq!(boxed.as_ptr(), ptr);
+
+    // An adopted buffer can be dropped, shrunk, or outgrown like any other.
+    let s = SeaString::<Slice, Utf16, Global>::from_boxed_units(boxed).expect(here!());
+    drop(s);
+
+    let mut s = SeaString::<Slice, Utf16, Rust>::from_boxed_units(utf16("garçon").into_boxed_slice()).expect(here!());
+    s.truncate(3);
+    assert_eq!(&s.into_boxed_units()[..], &utf16("gar")[..]);
+
+    let mut s = SeaString::<Slice, Utf16, Rust>::from_boxed_units(utf16("gar").into_boxed_slice()).expect(here!());
+    s.push_str("çon").expect(here!());
+    assert_eq!(&s.into_boxed_units()[..], &utf16("garçon")[..]);
+
+    // Handing an adopted buffer to foreign code moves it into memory the allocator can free.
+    let s = SeaString::<Slice, Utf16, Global>::from_boxed_units(utf16("garçon").into_boxed_slice()).expect(here!());
+    let (ptr, len) = s.into_raw_parts();
+    let s = unsafe { SeaString::<Slice, Utf16, Global>::from_raw_parts(ptr, len) };
+    assert_eq!(s.as_units(), &utf16("garçon")[..]);
+    drop(s);
+
+    // A string which allocated its own buffer has to be copied out.
+    let s = SeaString::<Slice, Utf16, Rust>::new(&utf16("garçon")).expect(here!());
+    let ptr = s.as_units().as_ptr();
+    let boxed = s.into_boxed_units();
+    assert!(boxed.as_ptr() != ptr);
+    assert_eq!(&boxed[..], &utf16("garçon")[..]);
+}
+
+#[test]
+fn test_shared_units() {
+    let units = utf16("garçon");
+    let rc: Rc<[Utf16Unit]> = units.clone().into();
+    let arc: Arc<[Utf16Unit]> = units.clone().into();
+
+    let s = SeaString::<Slice, Utf16, Malloc>::from_rc_units(&rc).expect(here!());
+    assert_eq!(s.as_units(), &units[..]);
+    let s = SeaString::<ZeroTerm, Utf16, Malloc>::from_arc_units(&arc).expect(here!());
+    assert_eq!(s.as_units(), &units[..]);
+}
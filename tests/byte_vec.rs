@@ -0,0 +1,82 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use std::convert::TryFrom;
+use strffi::alloc::{Malloc, Rust};
+use strffi::encoding::{Raw8, Utf8, Utf8Unit};
+use strffi::sea::{FromBytesError, SeaString};
+use strffi::structure::ZeroTerm;
+
+#[test]
+fn test_try_from_vec() {
+    let s = SeaString::<ZeroTerm, Utf8, Rust>::try_from(b"gar\xc3\xa7on".to_vec()).expect(here!());
+    assert_eq!(s.validate_utf8().expect(here!()).as_str(), "garçon");
+    assert_eq!(s.as_units_with_term().last(), Some(&Utf8Unit(0)));
+
+    // A trailing zero becomes the terminator.
+    let s = SeaString::<ZeroTerm, Raw8, Rust>::try_from(b"abc\0".to_vec()).expect(here!());
+    assert_eq!(s.as_units().len(), 3);
+    assert_eq!(s.as_units_with_term().len(), 4);
+}
+
+#[test]
+fn test_try_from_vec_interior_zero() {
+    let err = SeaString::<ZeroTerm, Utf8, Rust>::try_from(b"ab\0c".to_vec()).err().expect(here!());
+    assert_eq!(err, FromBytesError::InteriorZeroAt(2));
+
+    let err = SeaString::<ZeroTerm, Raw8, Rust>::try_from(b"\0\0".to_vec()).err().expect(here!());
+    assert_eq!(err, FromBytesError::InteriorZeroAt(0));
+}
+
+#[test]
+fn test_vec_handover() {
+    // The vector's buffer is adopted, and handed back, without being copied.
+    let mut bytes = b"abc".to_vec();
+    bytes.reserve(8);
+    let ptr = bytes.as_ptr();
+    let cap = bytes.capacity();
+    let s = SeaString::<ZeroTerm, Utf8, Rust>::try_from(bytes).expect(here!());
+    assert_eq!(s.as_units().as_ptr() as *const u8, ptr);
+    assert_eq!(s.as_units_with_term(), &[Utf8Unit(b'a'), Utf8Unit(b'b'), Utf8Unit(b'c'), Utf8Unit(0)]);
+    let bytes: Vec<u8> = s.into();
+    assert_eq!(bytes.as_ptr(), ptr);
+    assert_eq!(bytes.capacity(), cap);
+    assert_eq!(bytes, b"abc");
+
+    // An existing terminator is used as-is.
+    let bytes = b"abc\0".to_vec();
+    let ptr = bytes.as_ptr();
+    let s = SeaString::<ZeroTerm, Raw8, Rust>::try_from(bytes).expect(here!());
+    assert_eq!(s.as_units().as_ptr() as *const u8, ptr);
+    drop(s);
+
+    // Handing an adopted buffer to foreign code moves it into memory the allocator can free.
+    let s = SeaString::<ZeroTerm, Utf8, Rust>::try_from(b"abc".to_vec()).expect(here!());
+    let ptr = s.into_ptr();
+    let s = unsafe { SeaString::<ZeroTerm, Utf8, Rust>::from_ptr(ptr) }.expect(here!());
+    assert_eq!(s.to_byte_vec(), b"abc");
+    drop(s);
+
+    // A string which allocated its own buffer has to be copied out.
+    let s = SeaString::<ZeroTerm, Raw8, Rust>::from_bytes_copied(b"abc").expect(here!());
+    let ptr = s.as_units().as_ptr() as *const u8;
+    let bytes: Vec<u8> = s.into();
+    assert!(bytes.as_ptr() != ptr);
+    assert_eq!(bytes, b"abc");
+}
+
+#[test]
+fn test_into_vec() {
+    let s = SeaString::<ZeroTerm, Raw8, Rust>::try_from(b"\xff\x01".to_vec()).expect(here!());
+    let bytes: Vec<u8> = s.into();
+    assert_eq!(bytes, b"\xff\x01");
+}
+
+#[test]
+fn test_malloc_copied() {
+    let s = SeaString::<ZeroTerm, Utf8, Malloc>::from_bytes_copied(b"abc").expect(here!());
+    assert_eq!(s.to_byte_vec(), b"abc");
+    let err = SeaString::<ZeroTerm, Utf8, Malloc>::from_bytes_copied(b"a\0b").err().expect(here!());
+    assert_eq!(err, FromBytesError::InteriorZeroAt(1));
+}
@@ -0,0 +1,76 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::{ZMbStr, ZMbCString};
+use strffi::alloc::Malloc;
+use strffi::encoding::{MultiByte, MbUnit, Raw8, Utf8, Utf8Unit};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+
+#[test]
+fn test_from_bytes() {
+    let bytes = b"g\xaar\xe7on";
+    let s = SeStr::<Slice, MultiByte>::from_bytes(bytes);
+    assert_eq!(s.as_units(), &[MbUnit(0x67), MbUnit(0xaau8 as _), MbUnit(0x72), MbUnit(0xe7u8 as _), MbUnit(0x6f), MbUnit(0x6e)][..]);
+    assert_eq!(s.as_raw_bytes().as_ptr(), bytes.as_ptr());
+    assert_eq!(s.as_raw_bytes(), &bytes[..]);
+}
+
+#[test]
+fn test_eq_bytes() {
+    let s = SeStr::<Slice, Utf8>::from_bytes(b"abc");
+    assert!(*s == b"abc"[..]);
+    assert!(*s == &b"abc"[..]);
+    assert!(*s != b"abd"[..]);
+    assert!(*s != b"ab"[..]);
+
+    let units = [Utf8Unit(0x61), Utf8Unit(0x62), Utf8Unit(0x63), Utf8Unit(0)];
+    let z = unsafe { SeStr::<ZeroTerm, Utf8>::from_ptr(units.as_ptr() as *const _) }.expect(here!());
+    assert!(*z == &b"abc"[..]);
+
+    let raw = SeStr::<Slice, Raw8>::from_bytes(b"\x00\xff");
+    assert!(*raw == b"\x00\xff"[..]);
+}
+
+#[test]
+fn test_seastring_eq_bytes() {
+    let s = SeaString::<Slice, Utf8, Malloc>::new(SeStr::<Slice, Utf8>::from_bytes(b"hello").as_units()).expect(here!());
+    assert!(s == b"hello"[..]);
+    assert!(s == &b"hello"[..]);
+
+    let zmb = ZMbCString::from(SeaString::<ZeroTerm, MultiByte, Malloc>::new(SeStr::<Slice, MultiByte>::from_bytes(b"abc").as_units()).expect(here!()));
+    let zmbstr: &ZMbStr = &zmb;
+    assert_eq!(zmbstr.as_raw_bytes(), b"abc");
+}
+
+#[test]
+fn test_as_raw_bytes_mut() {
+    let mut mb = SeaString::<Slice, MultiByte, Malloc>::new(SeStr::<Slice, MultiByte>::from_bytes(b"garcon").as_units()).expect(here!());
+    mb.as_raw_bytes_mut()[4] = b'\xe7';
+    assert_eq!(mb.as_raw_bytes(), b"garc\xe7n");
+
+    let mut s = SeaString::<Slice, Utf8, Malloc>::new(SeStr::<Slice, Utf8>::from_bytes(b"hello").as_units()).expect(here!());
+    s.as_raw_bytes_mut().copy_from_slice(b"HELLO");
+    assert!(s == b"HELLO"[..]);
+
+    let mut raw = SeaString::<Slice, Raw8, Malloc>::new(SeStr::<Slice, Raw8>::from_bytes(b"\x00\x01").as_units()).expect(here!());
+    raw.as_raw_bytes_mut()[1] = 0xff;
+    assert_eq!(raw.as_raw_bytes(), b"\x00\xff");
+}
+
+#[test]
+fn test_as_bytes() {
+    let bytes = b"g\xaar\xe7on";
+    let s = SeStr::<Slice, MultiByte>::from_bytes(bytes);
+    assert_eq!(s.as_bytes().as_ptr(), bytes.as_ptr());
+    assert_eq!(s.as_bytes(), s.as_raw_bytes());
+
+    let units = [Utf8Unit(0x61), Utf8Unit(0x62), Utf8Unit(0x63), Utf8Unit(0)];
+    let z = unsafe { SeStr::<ZeroTerm, Utf8>::from_ptr(units.as_ptr() as *const _) }.expect(here!());
+    assert_eq!(z.as_bytes(), b"abc");
+
+    let mut s = SeaString::<Slice, Utf8, Malloc>::new(SeStr::<Slice, Utf8>::from_bytes(b"hello").as_units()).expect(here!());
+    s.as_bytes_mut()[0] = b'j';
+    assert_eq!(s.as_bytes(), b"jello");
+}
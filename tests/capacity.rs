@@ -0,0 +1,95 @@
+extern crate strffi;
+
+mod common;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::{Malloc, Rust};
+use strffi::encoding::{Utf8, Utf8Unit, Utf16, Utf16Unit};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+use common::utf8;
+
+#[test]
+fn test_with_capacity() {
+    let mut s = SeaString::<Slice, Utf8, Malloc>::with_capacity(8).expect(here!());
+    assert_eq!(s.capacity(), 8);
+    assert_eq!(s.as_units(), &[][..]);
+    assert_eq!(s.allocated_bytes(), 8);
+
+    let ptr = s.as_units().as_ptr();
+    for &b in b"garcon" {
+        s.push_unit(Utf8Unit(b)).expect(here!());
+    }
+    assert_eq!(s.as_units(), &utf8(b"garcon")[..]);
+    assert_eq!(s.as_units().as_ptr(), ptr);
+    assert_eq!(s.capacity(), 8);
+
+    s.push_units(&utf8(b"s!!")).expect(here!());
+    assert_eq!(s.as_units(), &utf8(b"garcons!!")[..]);
+    assert_eq!(s.capacity(), 16);
+}
+
+#[test]
+fn test_reserve() {
+    let mut s = SeaString::<Slice, Utf16, Rust>::new(&[Utf16Unit(0x67)]).expect(here!());
+    assert_eq!(s.capacity(), 1);
+
+    s.reserve(0).expect(here!());
+    assert_eq!(s.capacity(), 1);
+
+    s.reserve(10).expect(here!());
+    assert_eq!(s.capacity(), 11);
+    assert_eq!(s.as_units(), &[Utf16Unit(0x67)][..]);
+
+    let ptr = s.as_units().as_ptr();
+    for _ in 0..10 {
+        s.push_unit(Utf16Unit(0x61)).expect(here!());
+    }
+    assert_eq!(s.as_units().as_ptr(), ptr);
+    assert_eq!(s.as_units().len(), 11);
+
+    s.reserve(1).expect(here!());
+    assert_eq!(s.capacity(), 22);
+
+    assert!(s.reserve(usize::max_value()).is_err());
+    assert_eq!(s.as_units().len(), 11);
+}
+
+#[test]
+fn test_push_growth() {
+    let mut s = SeaString::<Slice, Utf8, Malloc>::new(&[]).expect(here!());
+    let mut reallocs = 0;
+    let mut cap = s.capacity();
+    for i in 0..10000 {
+        s.push_unit(Utf8Unit(b'a' + (i % 26) as u8)).expect(here!());
+        if s.capacity() != cap {
+            reallocs += 1;
+            cap = s.capacity();
+        }
+    }
+    assert_eq!(s.as_units().len(), 10000);
+    assert!(reallocs <= 15, "{} reallocations", reallocs);
+}
+
+#[test]
+fn test_capacity_into_ptr() {
+    let mut s = SeaString::<Slice, Utf8, Malloc>::with_capacity(16).expect(here!());
+    s.push_units(&utf8(b"garcon")).expect(here!());
+
+    // The foreign representation only carries the length.
+    let (ptr, len) = s.into_ptr();
+    assert_eq!(len, 6);
+    let s = unsafe { SeaString::<Slice, Utf8, Malloc>::from_ptr((ptr, len)) }.expect(here!());
+    assert_eq!(s.capacity(), 6);
+    assert_eq!(&*s, SeStr::<Slice, Utf8>::from_bytes(b"garcon"));
+}
+
+#[test]
+fn test_zero_term_push_unit() {
+    let mut s = SeaString::<ZeroTerm, Utf8, Malloc>::new(&utf8(b"gar")).expect(here!());
+    s.push_unit(Utf8Unit(b'c')).expect(here!());
+    assert_eq!(s.as_units(), &utf8(b"garc")[..]);
+    assert!(s.push_unit(Utf8Unit(0)).is_ok());
+    assert_eq!(s.as_units(), &utf8(b"garc")[..]);
+}
@@ -0,0 +1,35 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::encoding::{DecodeError, Utf16Le, Utf16LeUnit, JniMtf8, Mtf8Unit};
+use strffi::sea::SeStr;
+use strffi::structure::{Slice, ZeroTerm};
+
+#[test]
+fn test_chars() {
+    let units: Vec<_> = "gªrçon😀".encode_utf16().map(Utf16LeUnit::new).collect();
+    let s = SeStr::<Slice, Utf16Le>::new(&units);
+    let chars: Result<String, _> = s.chars().collect();
+    assert_eq!(chars.expect(here!()), "gªrçon😀");
+}
+
+#[test]
+fn test_chars_error() {
+    let units = [Utf16LeUnit::new(0x67), Utf16LeUnit::new(0xDC00), Utf16LeUnit::new(0x6E)];
+    let s = SeStr::<Slice, Utf16Le>::new(&units);
+    let chars: Vec<_> = s.chars().collect();
+    // Decoding resumes after the unpaired surrogate.
+    assert_eq!(chars, vec![Ok('g'), Err(DecodeError::InvalidAt(1)), Ok('n')]);
+}
+
+#[test]
+fn test_chars_lossy() {
+    let units = [Mtf8Unit(b'a'), Mtf8Unit(0xFF), Mtf8Unit(b'b'), Mtf8Unit(0)];
+    let s = unsafe { SeStr::<ZeroTerm, JniMtf8>::from_ptr(units.as_ptr() as *const _).expect(here!()) };
+    let mut out = String::new();
+    for c in s.chars_lossy() {
+        out.push(c);
+    }
+    assert_eq!(out, "a\u{fffd}");
+}
@@ -0,0 +1,119 @@
+extern crate strffi;
+
+mod common;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{CheckedUtf8, Utf8};
+use strffi::encoding::conv::utf8::Utf8ValidationError;
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+use common::utf8;
+
+#[test]
+fn test_validate() {
+    let units = utf8(b"g\xc2\xaar\xc3\xa7on\xf0\x9f\x98\x80\0");
+    let zstr = unsafe { SeStr::<ZeroTerm, Utf8>::from_ptr(units.as_ptr() as *const _).expect(here!()) };
+    let checked = zstr.validate_utf8().expect(here!());
+    assert_eq!(checked.as_ptr() as usize, zstr.as_ptr() as usize);
+    assert_eq!(checked.as_str(), "gªrçon😀");
+    assert_eq!(String::from(checked), "gªrçon😀");
+    assert_eq!(checked.into_string().expect(here!()), "gªrçon😀");
+    assert_eq!(checked.as_utf8(), zstr);
+}
+
+#[test]
+fn test_validate_rejects_truncated() {
+    let units = utf8(b"ab\xe2\x82");
+    let r = SeStr::<Slice, Utf8>::new(&units).validate_utf8();
+    assert_eq!(r.err(), Some(Utf8ValidationError::Incomplete));
+
+    let units = utf8(b"ab\xe2\x82z");
+    let r = SeStr::<Slice, Utf8>::new(&units).validate_utf8();
+    assert_eq!(r.err(), Some(Utf8ValidationError::InvalidAt(2)));
+}
+
+#[test]
+fn test_validate_rejects_overlong() {
+    // Overlong encodings of '/' and NUL.
+    let units = utf8(b"a\xc0\xaf");
+    let r = SeStr::<Slice, Utf8>::new(&units).validate_utf8();
+    assert_eq!(r.err(), Some(Utf8ValidationError::InvalidAt(1)));
+
+    let units = utf8(b"\xe0\x80\x80");
+    let r = SeStr::<Slice, Utf8>::new(&units).validate_utf8();
+    assert_eq!(r.err(), Some(Utf8ValidationError::InvalidAt(0)));
+}
+
+#[test]
+fn test_validate_rejects_surrogate() {
+    let units = utf8(b"\xed\xa0\x80");
+    let r = SeStr::<Slice, Utf8>::new(&units).validate_utf8();
+    assert_eq!(r.err(), Some(Utf8ValidationError::InvalidAt(0)));
+}
+
+#[test]
+fn test_from_str() {
+    let s = SeaString::<ZeroTerm, CheckedUtf8, Malloc>::from_str("gªrçon😀").expect(here!());
+    assert_eq!(s.as_str(), "gªrçon😀");
+    let units: Vec<_> = s.as_units().iter().map(|u| u.get()).collect();
+    assert_eq!(units, "gªrçon😀".as_bytes());
+
+    let u8s: SeaString<Slice, Utf8, Malloc> = s.transcode_to().expect(here!());
+    assert_eq!(u8s.as_units(), &utf8("gªrçon😀".as_bytes())[..]);
+}
+
+#[test]
+fn test_to_str() {
+    let units = utf8(b"g\xc2\xaar\xc3\xa7on\0");
+    let zstr = unsafe { SeStr::<ZeroTerm, Utf8>::from_ptr(units.as_ptr() as *const _).expect(here!()) };
+    let s = zstr.to_str().expect(here!());
+    assert_eq!(s, "gªrçon");
+    assert_eq!(s.as_ptr() as usize, units.as_ptr() as usize);
+
+    let units = utf8(b"ab\xffcd");
+    let err = SeStr::<Slice, Utf8>::new(&units).to_str().unwrap_err();
+    assert_eq!(err.valid_up_to(), 2);
+}
+
+#[test]
+fn test_is_valid_utf8() {
+    let cases: &[(&[u8], bool)] = &[
+        (b"", true),
+        (b"plain ascii", true),
+        ("gªrçon, Съешь, 😀".as_bytes(), true),
+        (b"\xc3", false),
+        (b"\xc0\xaf", false),
+        (b"\xed\xa0\x80", false),
+        (b"\xf4\x90\x80\x80", false),
+        (b"abc\xff", false),
+    ];
+    for &(bytes, valid) in cases {
+        let units = utf8(bytes);
+        let s = SeStr::<Slice, Utf8>::new(&units);
+        assert_eq!(s.is_valid_utf8(), valid, "{:?}", bytes);
+        assert_eq!(s.is_valid_utf8(), s.validate_utf8().is_ok(), "{:?}", bytes);
+        assert_eq!(s.is_valid_utf8(), s.into_string().is_ok(), "{:?}", bytes);
+    }
+
+    let zstr = SeaString::<ZeroTerm, Utf8, Malloc>::new(&utf8("naïve".as_bytes())).expect(here!());
+    assert!(zstr.is_valid_utf8());
+}
+
+#[test]
+fn test_sliced_mid_sequence() {
+    let s = SeaString::<Slice, CheckedUtf8, Malloc>::from_str("gar\u{e7}").expect(here!());
+    // Slicing by units can cut the final character in half.
+    let cut = SeStr::<Slice, CheckedUtf8>::new(&s.as_units()[..4]);
+    assert!(cut.validate_encoding().is_err());
+    assert!(s.validate_encoding().is_ok());
+}
+
+#[test]
+#[should_panic]
+fn test_sliced_mid_sequence_as_str() {
+    let s = SeaString::<Slice, CheckedUtf8, Malloc>::from_str("gar\u{e7}").expect(here!());
+    let cut = SeStr::<Slice, CheckedUtf8>::new(&s.as_units()[..4]);
+    let _ = cut.as_str();
+}
@@ -0,0 +1,105 @@
+#![cfg(windows)]
+#[macro_use] extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{Wide, WUnit};
+use strffi::encoding::conv::win_cp::{CodePage, CpToUniError, CpToWcError, WcToCpError, cp_to_wide_bulk, wide_to_cp_bulk};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+
+declare_code_page! {
+    /// Windows-1252.
+    pub enum Cp1252;
+    unit Cp1252Unit;
+    prefix "Cp1252";
+    code_page 1252;
+}
+
+declare_code_page! {
+    /// Shift JIS.
+    pub enum Cp932;
+    unit Cp932Unit;
+    prefix "Cp932";
+    code_page 932;
+}
+
+fn units<U, F>(bytes: &[u8], f: F) -> Vec<U> where F: Fn(u8) -> U {
+    bytes.iter().cloned().map(f).collect()
+}
+
+#[test]
+fn test_single_byte() {
+    assert_eq!(Cp1252::code_page(), 1252);
+
+    let us = units(b"gar\xe7on\x80", Cp1252Unit);
+    let s = SeStr::<Slice, Cp1252>::new(&us);
+    assert_eq!(s.into_string().expect(here!()), "garçon€");
+
+    let cs = SeaString::<ZeroTerm, Cp1252, Malloc>::from_str("garçon€").expect(here!());
+    assert_eq!(cs.as_units(), &us[..]);
+    assert_eq!(format!("{:?}", cs), r#"ZCp1252"gar\xe7on\x80""#);
+}
+
+#[test]
+fn test_double_byte() {
+    // "日本" in Shift JIS.
+    let us = units(b"a\x93\xfa\x96\x7b", Cp932Unit);
+    let s = SeStr::<Slice, Cp932>::new(&us);
+    assert_eq!(s.into_string().expect(here!()), "a日本");
+
+    let ws: SeaString<Slice, Wide, Malloc> = s.transcode_to().expect(here!());
+    let expected: Vec<_> = "a日本".encode_utf16().map(WUnit).collect();
+    assert_eq!(ws.as_units(), &expected[..]);
+
+    let cs: SeaString<Slice, Cp932, Malloc> = ws.transcode_to().expect(here!());
+    assert_eq!(cs.as_units(), &us[..]);
+}
+
+#[test]
+fn test_invalid() {
+    // A lead byte with nothing after it.
+    let us = units(b"a\x93", Cp932Unit);
+    let r: Result<String, _> = SeStr::<Slice, Cp932>::new(&us).transcode_to_iter::<strffi::encoding::CheckedUnicode>().collect();
+    assert_eq!(r, Err(CpToUniError::Incomplete));
+
+    // A lead byte followed by an invalid trail byte.
+    let us = units(b"ab\x81\x20", Cp932Unit);
+    let r: Result<String, _> = SeStr::<Slice, Cp932>::new(&us).transcode_to_iter::<strffi::encoding::CheckedUnicode>().collect();
+    assert_eq!(r, Err(CpToUniError::InvalidAt(2)));
+}
+
+#[test]
+fn test_bulk() {
+    let bytes = b"a\x93\xfa\x96\x7b";
+    let expected: Vec<_> = "a日本".encode_utf16().map(WUnit).collect();
+    assert_eq!(cp_to_wide_bulk(932, bytes).expect(here!()), expected);
+    assert_eq!(wide_to_cp_bulk(932, &expected).expect(here!()), &bytes[..]);
+    assert_eq!(cp_to_wide_bulk(932, b"").expect(here!()), vec![]);
+    assert_eq!(wide_to_cp_bulk(932, &[]).expect(here!()), vec![]);
+
+    let us = units(bytes, Cp932Unit);
+    let s = SeStr::<Slice, Cp932>::new(&us);
+    assert_eq!(s.into_string_bulk().expect(here!()), "a日本");
+
+    let ws: SeaString<ZeroTerm, Wide, Malloc> = s.transcode_to_wide_bulk().expect(here!());
+    assert_eq!(ws.as_units(), &expected[..]);
+
+    let cs: SeaString<Slice, Cp932, Malloc> = ws.transcode_to_code_page_bulk().expect(here!());
+    assert_eq!(cs.as_units(), &us[..]);
+}
+
+#[test]
+fn test_bulk_invalid() {
+    // The bulk conversion falls back to the per-character one to find the offset.
+    assert_eq!(cp_to_wide_bulk(932, b"ab\x81\x20"), Err(CpToWcError::InvalidAt(2)));
+    assert_eq!(cp_to_wide_bulk(932, b"a\x93"), Err(CpToWcError::Incomplete));
+
+    // "日" has no representation in Windows-1252.
+    let ws: Vec<_> = "ab日".encode_utf16().map(WUnit).collect();
+    assert_eq!(wide_to_cp_bulk(1252, &ws), Err(WcToCpError::InvalidAt(2)));
+
+    let ws = SeStr::<Slice, Wide>::new(&ws);
+    assert!(ws.transcode_to_code_page_bulk::<Slice, Cp1252, Malloc>().is_err());
+}
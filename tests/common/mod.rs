@@ -0,0 +1,11 @@
+/*!
+Helpers shared between the integration tests.
+*/
+use strffi::encoding::Utf8Unit;
+
+/**
+Converts bytes, or the bytes of a `str`, into `Utf8` units.
+*/
+pub fn utf8<B>(bytes: &B) -> Vec<Utf8Unit> where B: AsRef<[u8]> + ?Sized {
+    bytes.as_ref().iter().map(|&b| Utf8Unit(b)).collect()
+}
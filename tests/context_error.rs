@@ -0,0 +1,46 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use std::error::Error;
+use strffi::alloc::Malloc;
+use strffi::encoding::{JniMtf8, Utf8, Utf8Unit};
+use strffi::sea::{context_limit, set_context_limit, SeStr, SeaString, DEFAULT_CONTEXT_LIMIT};
+use strffi::structure::{Slice, ZeroTerm};
+
+/*
+The limit is global, so everything which depends on it is checked in a single test.
+*/
+#[test]
+fn test_context_error() {
+    assert_eq!(context_limit(), DEFAULT_CONTEXT_LIMIT);
+
+    let mut units: Vec<_> = b"ab\xffcd".iter().map(|&b| Utf8Unit(b)).collect();
+    let s = SeStr::<Slice, Utf8>::new(&units);
+
+    let err = s.into_string_ctx().unwrap_err();
+    assert_eq!(err.snapshot(), r#"SUtf8"ab\xffcd""#);
+    assert_eq!(err.to_string(), r#"invalid unit at offset 2 in SUtf8"ab\xffcd""#);
+    assert!(err.cause().is_some());
+    assert_eq!(err.into_inner().to_string(), "invalid unit at offset 2");
+
+    let err = s.transcode_to_ctx::<ZeroTerm, JniMtf8, Malloc>().unwrap_err();
+    assert!(err.to_string().ends_with(r#" in SUtf8"ab\xffcd""#), "{}", err);
+
+    // Long strings are capped.
+    units.extend(vec![Utf8Unit(b'z'); 100]);
+    let s = SeaString::<ZeroTerm, Utf8, Malloc>::new(&units).expect(here!());
+    set_context_limit(4);
+    let err = s.into_string_ctx().unwrap_err();
+    assert_eq!(err.snapshot(), r#"ZUtf8"ab\xffc"... (105 units)"#);
+    assert!(err.to_string().ends_with(r#"... (105 units)"#), "{}", err);
+
+    set_context_limit(0);
+    let err = s.into_string_ctx().unwrap_err();
+    assert_eq!(err.snapshot(), r#"ZUtf8""... (105 units)"#);
+    set_context_limit(DEFAULT_CONTEXT_LIMIT);
+
+    // Successful conversions are unaffected.
+    let ok = SeStr::<Slice, Utf8>::new(&units[..2]);
+    assert_eq!(ok.into_string_ctx().expect(here!()), "ab");
+}
@@ -0,0 +1,42 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{Utf8, Utf8Unit};
+use strffi::sea::{debug_limit, set_debug_limit, SeStr, SeaString, DEFAULT_DEBUG_LIMIT};
+use strffi::structure::{Slice, ZeroTerm};
+
+/*
+The limit is global, so everything which depends on it is checked in a single test.
+*/
+#[test]
+fn test_debug_limit() {
+    assert_eq!(debug_limit(), Some(DEFAULT_DEBUG_LIMIT));
+
+    let units = vec![Utf8Unit(b'a'); DEFAULT_DEBUG_LIMIT + 5];
+    let s = SeStr::<Slice, Utf8>::new(&units);
+    let expected = format!("SUtf8\"{}\"... ({} units)", "a".repeat(DEFAULT_DEBUG_LIMIT), DEFAULT_DEBUG_LIMIT + 5);
+    assert_eq!(format!("{:?}", s), expected);
+
+    let s = SeStr::<Slice, Utf8>::new(&units[..DEFAULT_DEBUG_LIMIT]);
+    assert_eq!(format!("{:?}", s), format!("SUtf8\"{}\"", "a".repeat(DEFAULT_DEBUG_LIMIT)));
+
+    set_debug_limit(Some(3));
+    assert_eq!(debug_limit(), Some(3));
+    let seas = SeaString::<ZeroTerm, Utf8, Malloc>::new(&units[..4]).expect(here!());
+    assert_eq!(format!("{:?}", seas), "ZUtf8C\"aaa\"... (4 units)");
+    assert_eq!(format!("{:?}", &*seas), "ZUtf8\"aaa\"... (4 units)");
+    assert_eq!(format!("{:?}", SeStr::<Slice, Utf8>::new(&units[..3])), "SUtf8\"aaa\"");
+
+    set_debug_limit(Some(0));
+    assert_eq!(format!("{:?}", SeStr::<Slice, Utf8>::new(&units[..2])), "SUtf8\"\"... (2 units)");
+    assert_eq!(format!("{:?}", SeStr::<Slice, Utf8>::new(&[])), "SUtf8\"\"");
+
+    set_debug_limit(None);
+    assert_eq!(debug_limit(), None);
+    let s = SeStr::<Slice, Utf8>::new(&units);
+    assert_eq!(format!("{:?}", s), format!("SUtf8\"{}\"", "a".repeat(DEFAULT_DEBUG_LIMIT + 5)));
+
+    set_debug_limit(Some(DEFAULT_DEBUG_LIMIT));
+}
@@ -0,0 +1,67 @@
+#[macro_use] extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{Encoding, Unit};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::ZeroTerm;
+
+declare_encoding! {
+    /**
+    A toy 7-bit encoding.
+    */
+    pub enum Gsm7;
+    unit Gsm7Unit(u8);
+    prefix "Gsm7";
+}
+
+declare_encoding! {
+    enum Wide3;
+    unit Wide3Unit(u16);
+    prefix "Wide3";
+    max_units_per_char 3;
+    locale_independent;
+}
+
+declare_encoding! {
+    enum Fixed8;
+    unit Fixed8Unit(u8);
+    prefix "Fixed8";
+    locale_independent;
+}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn test_declared_locale_independent() {
+    assert_send_sync::<SeaString<ZeroTerm, Wide3, Malloc>>();
+    assert_send_sync::<SeaString<ZeroTerm, Fixed8, Malloc>>();
+    assert_eq!(Fixed8::MAX_UNITS_PER_CHAR, 1);
+}
+
+#[test]
+fn test_declared_encoding() {
+    assert_eq!(Gsm7::debug_prefix(), "Gsm7");
+    assert_eq!(Gsm7::MAX_UNITS_PER_CHAR, 1);
+    assert_eq!(Wide3::MAX_UNITS_PER_CHAR, 3);
+    assert_eq!(Gsm7Unit::BITS, 8);
+    assert_eq!(Gsm7Unit::from_raw(0x1b), Some(Gsm7Unit(0x1b)));
+    assert_eq!(Gsm7Unit::from_raw(0x100), None);
+    assert!(Gsm7Unit::zero().is_zero());
+}
+
+#[test]
+fn test_declared_encoding_strings() {
+    let units = [Gsm7Unit(b'h'), Gsm7Unit(b'i'), Gsm7Unit(0x1b)];
+    let s = SeaString::<ZeroTerm, Gsm7, Malloc>::new(&units).expect(here!());
+    assert_eq!(s.as_units(), &units[..]);
+    assert_eq!(format!("{:?}", s), r#"ZGsm7C"hi\x1b""#);
+    assert_eq!(format!("{:?}", units[0]), "'h'");
+
+    let empty: &SeStr<ZeroTerm, Gsm7> = Default::default();
+    assert_eq!(empty.as_units(), &[][..]);
+
+    let s = SeaString::<ZeroTerm, Wide3, Malloc>::new(&[Wide3Unit(0x41), Wide3Unit(0x263a)]).expect(here!());
+    assert_eq!(format!("{:?}", s), r#"ZWide3C"A\u263a""#);
+}
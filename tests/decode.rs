@@ -0,0 +1,62 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::encoding::{DecodeError, CheckedUnicode, Utf8, Utf16Be, Utf16BeUnit, Utf32, Utf32Unit};
+use strffi::sea::SeStr;
+use strffi::structure::{Slice, ZeroTerm};
+
+#[test]
+fn test_malformed_utf8_c_string() {
+    const WORD: &'static [u8] = b"a\xffb\xc3\xa7\xe2\x82c\x80\xf0\x9f\x98\x80\xe2\x82\0";
+    let s = unsafe { SeStr::<ZeroTerm, Utf8>::from_ptr(WORD.as_ptr()) }.expect(here!());
+    let chars: Vec<_> = s.char_indices().collect();
+    assert_eq!(chars, vec![
+        (0, Ok('a')),
+        (1, Err(DecodeError::InvalidAt(1))),
+        (2, Ok('b')),
+        (3, Ok('ç')),
+        (5, Err(DecodeError::InvalidAt(5))),
+        (7, Ok('c')),
+        (8, Err(DecodeError::InvalidAt(8))),
+        (9, Ok('😀')),
+        (13, Err(DecodeError::IncompleteAt(13))),
+    ]);
+}
+
+#[test]
+fn test_embedded_zero() {
+    let s = SeStr::<Slice, Utf8>::from_bytes(b"a\0b");
+    let chars: Vec<_> = s.char_indices().map(|(_, r)| r.expect(here!())).collect();
+    assert_eq!(chars, vec!['a', '\0', 'b']);
+}
+
+#[test]
+fn test_utf16_unpaired() {
+    let units: Vec<_> = [0x61, 0xd83d, 0x62, 0xdc00, 0xd83d, 0xde00, 0xd83d].iter().map(|&u| Utf16BeUnit::new(u)).collect();
+    let chars: Vec<_> = SeStr::<Slice, Utf16Be>::new(&units).char_indices().collect();
+    assert_eq!(chars, vec![
+        (0, Ok('a')),
+        (1, Err(DecodeError::InvalidAt(1))),
+        (2, Ok('b')),
+        (3, Err(DecodeError::InvalidAt(3))),
+        (4, Ok('😀')),
+        (6, Err(DecodeError::IncompleteAt(6))),
+    ]);
+}
+
+#[test]
+fn test_utf32_and_unicode() {
+    let units = [Utf32Unit(0x61), Utf32Unit(0xd800), Utf32Unit(0x110000), Utf32Unit(0x1f600)];
+    let chars: Vec<_> = SeStr::<Slice, Utf32>::new(&units).char_indices().collect();
+    assert_eq!(chars, vec![
+        (0, Ok('a')),
+        (1, Err(DecodeError::InvalidAt(1))),
+        (2, Err(DecodeError::InvalidAt(2))),
+        (3, Ok('😀')),
+    ]);
+
+    let units = ['x', 'y'];
+    let chars: Vec<_> = SeStr::<Slice, CheckedUnicode>::new(&units).char_indices().collect();
+    assert_eq!(chars, vec![(0, Ok('x')), (1, Ok('y'))]);
+}
@@ -0,0 +1,134 @@
+#[macro_use] extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use std::fmt::Write;
+use strffi::alloc::Malloc;
+use strffi::encoding::{EncodeChar, EncodeError, Encoding, Unit, CheckedUnicode, JniMtf8, Mtf8Unit,
+    Utf8, Utf8Unit, Utf16, Utf16Unit, Utf16Be, Utf16BeUnit, Utf32, Utf32Unit};
+use strffi::sea::SeaString;
+use strffi::structure::{Slice, ZeroTerm};
+
+const CHARS: &'static [char] = &['g', '\0', 'ç', '€', '\u{10302}'];
+
+#[test]
+fn test_encode_utf8() {
+    let mut buf = [Utf8Unit(0); 4];
+    for &c in CHARS {
+        let mut expected = [0u8; 4];
+        let expected: Vec<_> = c.encode_utf8(&mut expected).bytes().map(Utf8Unit).collect();
+        assert_eq!(Utf8::encode(c, &mut buf).expect(here!()), &expected[..]);
+    }
+}
+
+#[test]
+fn test_encode_utf16() {
+    let mut buf = [Utf16Unit(0); 2];
+    assert_eq!(Utf16::encode('€', &mut buf).expect(here!()), &[Utf16Unit(0x20ac)][..]);
+    assert_eq!(Utf16::encode('\u{10302}', &mut buf).expect(here!()), &[Utf16Unit(0xd800), Utf16Unit(0xdf02)][..]);
+
+    let mut buf = [Utf16BeUnit::new(0); 2];
+    assert_eq!(Utf16Be::encode('\u{10302}', &mut buf).expect(here!()), &[Utf16BeUnit::new(0xd800), Utf16BeUnit::new(0xdf02)][..]);
+}
+
+#[test]
+fn test_encode_utf32() {
+    let mut buf = [Utf32Unit(0); 1];
+    for &c in CHARS {
+        assert_eq!(Utf32::encode(c, &mut buf).expect(here!()), &[Utf32Unit(c as u32)][..]);
+    }
+
+    let mut buf = ['\0'];
+    assert_eq!(CheckedUnicode::encode('€', &mut buf).expect(here!()), &['€'][..]);
+}
+
+#[test]
+fn test_encode_jni_mtf8() {
+    let mut buf = [Mtf8Unit(0); 6];
+    assert_eq!(JniMtf8::encode('\0', &mut buf).expect(here!()), &[Mtf8Unit(0xc0), Mtf8Unit(0x80)][..]);
+    assert_eq!(JniMtf8::encode('\u{10302}', &mut buf).expect(here!()),
+        &[0xed, 0xa0, 0x80, 0xed, 0xbc, 0x82].iter().map(|&b| Mtf8Unit(b)).collect::<Vec<_>>()[..]);
+}
+
+#[test]
+fn test_encode_max_units() {
+    fn check<E>() where E: EncodeChar {
+        let mut buf = vec![<E::Unit as Unit>::zero(); E::MAX_UNITS_PER_CHAR];
+        for &c in CHARS {
+            assert!(E::encode(c, &mut buf).is_ok(), "{} {:?}", E::debug_prefix(), c);
+        }
+    }
+
+    check::<Utf8>();
+    check::<JniMtf8>();
+    check::<Utf16>();
+    check::<Utf32>();
+    check::<CheckedUnicode>();
+}
+
+#[test]
+fn test_encode_buffer_too_small() {
+    let mut buf = [Utf8Unit(0); 2];
+    assert_eq!(Utf8::encode('€', &mut buf), Err(EncodeError::BufferTooSmall('€')));
+    assert_eq!(Utf8::encode('ç', &mut buf), Ok(&[Utf8Unit(0xc3), Utf8Unit(0xa7)][..]));
+
+    let mut buf = [Utf16Unit(0); 1];
+    assert_eq!(Utf16::encode('\u{10302}', &mut buf), Err(EncodeError::BufferTooSmall('\u{10302}')));
+
+    let mut buf: [char; 0] = [];
+    assert_eq!(CheckedUnicode::encode('g', &mut buf), Err(EncodeError::BufferTooSmall('g')));
+}
+
+#[test]
+fn test_push_char() {
+    let mut s = SeaString::<ZeroTerm, Utf16, Malloc>::new(&[]).expect(here!());
+    for c in "garçon€".chars() {
+        s.push_char(c).expect(here!());
+    }
+    let expected: Vec<_> = "garçon€".encode_utf16().map(Utf16Unit).collect();
+    assert_eq!(s.as_units(), &expected[..]);
+}
+
+#[test]
+fn test_fmt_write() {
+    let mut s = SeaString::<Slice, Utf8, Malloc>::new(&[]).expect(here!());
+    write!(s, "{}-{:03}", "garçon", 7).expect(here!());
+    s.write_char('€').expect(here!());
+    let expected: Vec<_> = "garçon-007€".bytes().map(Utf8Unit).collect();
+    assert_eq!(s.as_units(), &expected[..]);
+}
+
+declare_encoding! {
+    enum Spelled;
+    unit SpelledUnit(u8);
+    prefix "Spelled";
+    max_units_per_char 12;
+}
+
+/**
+Spells out each character's code point in hex, between brackets.
+*/
+impl EncodeChar for Spelled {
+    fn encode(c: char, buf: &mut [SpelledUnit]) -> Result<&[SpelledUnit], EncodeError> {
+        let spelled = format!("<U+{:06X}>", c as u32);
+        if buf.len() < spelled.len() {
+            return Err(EncodeError::BufferTooSmall(c));
+        }
+        for (u, b) in buf.iter_mut().zip(spelled.bytes()) {
+            *u = SpelledUnit(b);
+        }
+        Ok(&buf[..spelled.len()])
+    }
+}
+
+#[test]
+fn test_push_char_long_encoding() {
+    // Longer than any encoding in the crate needs.
+    assert!(Spelled::MAX_UNITS_PER_CHAR > 8);
+
+    let mut s = SeaString::<Slice, Spelled, Malloc>::new(&[]).expect(here!());
+    s.push_char('a').expect(here!());
+    write!(s, "{}", '\u{1f600}').expect(here!());
+    let spelled: Vec<u8> = s.as_units().iter().map(|u| u.0).collect();
+    assert_eq!(spelled, b"<U+000061><U+01F600>");
+}
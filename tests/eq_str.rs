@@ -0,0 +1,46 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::ZWCString;
+use strffi::alloc::Malloc;
+use strffi::encoding::{JniMtf8, Utf8, Utf8Unit, Utf16};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+
+#[test]
+fn test_eq_str_bytes() {
+    let s = SeStr::<Slice, Utf8>::from_bytes("gªrçon".as_bytes());
+    assert!(*s == *"gªrçon");
+    assert!(*s == "gªrçon");
+    assert!(*s != "garcon");
+    assert!(*s != "gªrço");
+
+    let z = SeaString::<ZeroTerm, Utf8, Malloc>::new(&[Utf8Unit(b'o'), Utf8Unit(b'k')]).expect(here!());
+    assert!(z == "ok");
+    assert!(z == *"ok");
+    assert!(z != "ok\0");
+
+    // Comparison is byte-wise: Modified UTF-8 encodes U+0000 differently.
+    let m = SeaString::<Slice, JniMtf8, Malloc>::from_str("a\0b").expect(here!());
+    assert!(m != "a\0b");
+    assert!(m.eq_str("a\0b"));
+}
+
+#[test]
+fn test_eq_str_decoded() {
+    let w = ZWCString::from_str("gªrçon😀").expect(here!());
+    assert!(w.eq_str("gªrçon😀"));
+    assert!(!w.eq_str("gªrçon"));
+    assert!(!w.eq_str("gªrçon😀!"));
+    assert!(!w.eq_str("garçon😀"));
+
+    let u = SeaString::<Slice, Utf16, Malloc>::from_str("").expect(here!());
+    assert!(u.eq_str(""));
+    assert!(!u.eq_str("a"));
+
+    // Invalid units never compare equal.
+    let bad = SeStr::<Slice, Utf8>::from_bytes(b"ab\xff");
+    assert!(!bad.eq_str("ab"));
+    assert!(!bad.eq_str("ab\u{fffd}"));
+}
@@ -0,0 +1,64 @@
+extern crate strffi;
+
+mod common;
+
+use strffi::encoding::{CheckedUnicode, EscapeStyle, Utf8, Utf16, Utf16Unit, Utf32, Utf32Unit};
+use strffi::sea::SeStr;
+use strffi::structure::Slice;
+use common::utf8;
+
+fn utf32(s: &str) -> Vec<Utf32Unit> {
+    s.chars().map(|c| Utf32Unit(c as u32)).collect()
+}
+
+#[test]
+fn test_escape_rust() {
+    let units = utf8(b"q\"b\\\0\t\xff7\xf0\x9f\x98\x80");
+    let s = SeStr::<Slice, Utf8>::new(&units);
+    assert_eq!(s.escape_units_as(EscapeStyle::RustLike), r#""q\"b\\\0\t\xff7\xf0\x9f\x98\x80""#);
+
+    let units = utf32("a\0😀");
+    let s = SeStr::<Slice, Utf32>::new(&units);
+    assert_eq!(s.escape_units_as(EscapeStyle::RustLike), r#""a\0\u{1f600}""#);
+}
+
+#[test]
+fn test_escape_c() {
+    // Octal escapes are always three digits, so a following digit is unambiguous.
+    let units = utf8(b"q\"b\\\0\t\xff7\xf0\x9f\x98\x80");
+    let s = SeStr::<Slice, Utf8>::new(&units);
+    assert_eq!(s.escape_units_as(EscapeStyle::CLike), r#""q\"b\\\000\t\3777\360\237\230\200""#);
+
+    // Hex escapes absorb following hex digits, so the literal is split.
+    let units = utf32("\0a😀g☺b");
+    let s = SeStr::<Slice, Utf32>::new(&units);
+    assert_eq!(s.escape_units_as(EscapeStyle::CLike), r#""\x0""a\x1f600g\x263a""b""#);
+}
+
+#[test]
+fn test_escape_json() {
+    let units = utf8(b"q\"b\\\0\t\xff");
+    let s = SeStr::<Slice, Utf8>::new(&units);
+    assert_eq!(s.escape_units_as(EscapeStyle::Json), r#""q\"b\\\u0000\t\u00ff""#);
+
+    let units = utf32("a😀");
+    let s = SeStr::<Slice, Utf32>::new(&units);
+    assert_eq!(s.escape_units_as(EscapeStyle::Json), r#""a\ud83d\ude00""#);
+
+    let units = [Utf32Unit(0x110000)];
+    let s = SeStr::<Slice, Utf32>::new(&units);
+    assert_eq!(s.escape_units_as(EscapeStyle::Json), r#""\ufffd""#);
+
+    // Lone surrogates are passed through as-is.
+    let units = [Utf16Unit(0xd800), Utf16Unit(b'x' as u16)];
+    let s = SeStr::<Slice, Utf16>::new(&units);
+    assert_eq!(s.escape_units_as(EscapeStyle::Json), r#""\ud800x""#);
+}
+
+#[test]
+fn test_escape_empty() {
+    let s = SeStr::<Slice, CheckedUnicode>::new(&[]);
+    assert_eq!(s.escape_units_as(EscapeStyle::RustLike), r#""""#);
+    assert_eq!(s.escape_units_as(EscapeStyle::CLike), r#""""#);
+    assert_eq!(s.escape_units_as(EscapeStyle::Json), r#""""#);
+}
@@ -0,0 +1,45 @@
+extern crate libc;
+extern crate strffi;
+
+use libc::size_t;
+use strffi::ffi::MbConvResult;
+
+#[test]
+fn test_conv_result_consumed() {
+    assert_eq!(MbConvResult::from_raw(0), MbConvResult::Consumed(0));
+    assert_eq!(MbConvResult::from_raw(1), MbConvResult::Consumed(1));
+    assert_eq!(MbConvResult::from_raw(strffi::ffi::MB_LEN_MAX as size_t),
+        MbConvResult::Consumed(strffi::ffi::MB_LEN_MAX));
+}
+
+#[test]
+fn test_conv_result_errors() {
+    assert_eq!(MbConvResult::from_raw(size_t::max_value()), MbConvResult::Illegal);
+    assert_eq!(MbConvResult::from_raw(size_t::max_value() - 1), MbConvResult::Incomplete);
+    assert_eq!(MbConvResult::from_raw(size_t::max_value() - 2), MbConvResult::StoredOnly);
+    assert_eq!(MbConvResult::from_raw(size_t::max_value() - 3),
+        MbConvResult::Consumed(size_t::max_value() as usize - 3));
+}
+
+#[cfg(target_pointer_width="32")]
+#[test]
+fn test_conv_result_32() {
+    assert_eq!(MbConvResult::from_raw(0xffff_ffff), MbConvResult::Illegal);
+    assert_eq!(MbConvResult::from_raw(0xffff_fffe), MbConvResult::Incomplete);
+    assert_eq!(MbConvResult::from_raw(0xffff_fffd), MbConvResult::StoredOnly);
+    assert_eq!(MbConvResult::from_raw(0xffff_fffc), MbConvResult::Consumed(0xffff_fffc));
+    assert_eq!(MbConvResult::from_raw(0x7fff_ffff), MbConvResult::Consumed(0x7fff_ffff));
+}
+
+#[cfg(target_pointer_width="64")]
+#[test]
+fn test_conv_result_64() {
+    assert_eq!(MbConvResult::from_raw(0xffff_ffff_ffff_ffff), MbConvResult::Illegal);
+    assert_eq!(MbConvResult::from_raw(0xffff_ffff_ffff_fffe), MbConvResult::Incomplete);
+    assert_eq!(MbConvResult::from_raw(0xffff_ffff_ffff_fffd), MbConvResult::StoredOnly);
+    assert_eq!(MbConvResult::from_raw(0xffff_ffff_ffff_fffc), MbConvResult::Consumed(0xffff_ffff_ffff_fffc));
+
+    // A 32-bit error value is a (nonsensical) count on 64-bit targets.
+    assert_eq!(MbConvResult::from_raw(0xffff_ffff), MbConvResult::Consumed(0xffff_ffff));
+    assert_eq!(MbConvResult::from_raw(0xffff_fffe), MbConvResult::Consumed(0xffff_fffe));
+}
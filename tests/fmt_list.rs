@@ -0,0 +1,54 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::ZWCString;
+use strffi::alloc::Malloc;
+use strffi::encoding::{Utf8, Utf8Unit, WUnit};
+use strffi::fmt::list;
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+
+fn wide(s: &str) -> ZWCString {
+    let units: Vec<_> = s.chars().map(|c| WUnit(c as u32 as _)).collect();
+    ZWCString::new(&units).expect(here!())
+}
+
+fn utf8(bytes: &[u8]) -> SeaString<ZeroTerm, Utf8, Malloc> {
+    SeaString::<ZeroTerm, Utf8, Malloc>::from_bytes_copied(bytes).expect(here!())
+}
+
+#[test]
+fn test_display() {
+    let strs = vec![wide("garçon"), wide("abc"), wide("")];
+    assert_eq!(format!("{}", list(&strs)), "garçon, abc, ");
+    assert_eq!(format!("{}", list(&strs).separator("\n")), "garçon\nabc\n");
+
+    let empty: Vec<ZWCString> = vec![];
+    assert_eq!(format!("{}", list(&empty)), "");
+}
+
+#[test]
+fn test_display_lossy() {
+    let strs = vec![utf8(b"gar\xe7on"), utf8(b"ok")];
+    assert_eq!(format!("{}", list(&strs).separator("|")), "gar\u{fffd}on|ok");
+}
+
+#[test]
+fn test_display_truncated() {
+    let strs = vec![wide("a"), wide("b"), wide("c"), wide("d")];
+    assert_eq!(format!("{}", list(&strs).max_items(Some(2))), "a, b, ... (2 more)");
+    assert_eq!(format!("{}", list(&strs).max_items(Some(4))), "a, b, c, d");
+    assert_eq!(format!("{}", list(&strs).max_items(Some(0)).separator("\n")), "... (4 more)");
+}
+
+#[test]
+fn test_debug() {
+    let strs = vec![utf8(b"abc"), utf8(b"gar\xc3\xa7on")];
+    assert_eq!(format!("{:?}", list(&strs)), r#"ZUtf8["abc", "gar\xc3\xa7on"]"#);
+    assert_eq!(format!("{:?}", list(&strs).max_items(Some(1))), r#"ZUtf8["abc", ... (1 more)]"#);
+
+    let units: Vec<_> = b"xy".iter().map(|&b| Utf8Unit(b)).collect();
+    let borrowed = vec![SeStr::<Slice, Utf8>::new(&units); 3];
+    assert_eq!(format!("{:?}", list(borrowed.iter().cloned()).max_items(Some(0))), "SUtf8[... (3 more)]");
+}
@@ -0,0 +1,37 @@
+extern crate strffi;
+
+mod common;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::{Malloc, Rust};
+use strffi::encoding::Utf8;
+use strffi::sea::{Foreign, SeStr, SeaString};
+use strffi::structure::ZeroTerm;
+use common::utf8;
+
+fn untrusted_len(s: Foreign<ZeroTerm, Utf8>) -> usize {
+    s.as_units().len()
+}
+
+#[test]
+fn test_from_ptr_tagged() {
+    let owned = SeaString::<ZeroTerm, Utf8, Malloc>::new(&utf8(b"gar\xc3\xa7on")).expect(here!());
+
+    let s = unsafe { SeStr::<ZeroTerm, Utf8>::from_ptr_tagged(owned.as_ptr()) }.expect(here!());
+    assert_eq!(untrusted_len(s), 7);
+    assert_eq!(s.as_units(), owned.as_units());
+    assert_eq!(format!("{:?}", s), format!("Foreign({:?})", &*owned));
+
+    let copied: SeaString<ZeroTerm, Utf8, Rust> = s.to_owned_by().expect(here!());
+    assert_eq!(copied.as_units(), owned.as_units());
+
+    let checked: &SeStr<ZeroTerm, Utf8> = s.assume_checked();
+    assert_eq!(checked.as_ptr(), owned.as_ptr());
+}
+
+#[test]
+fn test_from_ptr_tagged_null() {
+    let s = unsafe { SeStr::<ZeroTerm, Utf8>::from_ptr_tagged(::std::ptr::null()) };
+    assert!(s.is_none());
+}
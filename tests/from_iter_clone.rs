@@ -0,0 +1,117 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{Utf16, Utf16Unit};
+use strffi::sea::SeaString;
+use strffi::structure::{Slice, ZeroTerm};
+
+fn units(s: &str) -> Vec<Utf16Unit> {
+    s.encode_utf16().map(Utf16Unit).collect()
+}
+
+/**
+An iterator which claims to know exactly how many units it has left, but gets it wrong.
+*/
+struct Liar<I> {
+    iter: I,
+    claim: usize,
+}
+
+impl<I> Iterator for Liar<I> where I: Iterator {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        self.claim = self.claim.saturating_sub(1);
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.claim, Some(self.claim))
+    }
+}
+
+#[test]
+fn test_collect() {
+    let text = units("h\u{e9}llo \u{1f600}");
+
+    // Exact size hint.
+    let s: SeaString<ZeroTerm, Utf16, Malloc> = text.iter().cloned().collect();
+    assert_eq!(s.as_units(), &text[..]);
+    let s: SeaString<Slice, Utf16, Malloc> = text.iter().cloned().collect();
+    assert_eq!(s.as_units(), &text[..]);
+
+    // Inexact size hint.
+    let s: SeaString<ZeroTerm, Utf16, Malloc> = text.iter().cloned().filter(|_| true).collect();
+    assert_eq!(s.as_units(), &text[..]);
+    let s: SeaString<Slice, Utf16, Malloc> = text.iter().cloned().filter(|_| true).collect();
+    assert_eq!(s.as_units(), &text[..]);
+
+    // Empty.
+    let s: SeaString<ZeroTerm, Utf16, Malloc> = None.into_iter().collect();
+    assert!(s.is_empty());
+    let s: SeaString<Slice, Utf16, Malloc> = None.into_iter().collect();
+    assert!(s.is_empty());
+
+    // A trailing zero is taken as the terminator.
+    let mut with_zero = text.clone();
+    with_zero.push(Utf16Unit(0));
+    let s: SeaString<ZeroTerm, Utf16, Malloc> = with_zero.iter().cloned().collect();
+    assert_eq!(s.as_units(), &text[..]);
+}
+
+#[test]
+fn test_collect_wrong_hint() {
+    let text = units("a longer piece of text");
+
+    for &claim in &[0, 1, 5, text.len() - 1, text.len() + 1, text.len() + 100] {
+        let s: SeaString<ZeroTerm, Utf16, Malloc> = Liar { iter: text.iter().cloned(), claim: claim }.collect();
+        assert_eq!(s.as_units(), &text[..], "claim: {}", claim);
+        let s: SeaString<Slice, Utf16, Malloc> = Liar { iter: text.iter().cloned(), claim: claim }.collect();
+        assert_eq!(s.as_units(), &text[..], "claim: {}", claim);
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_collect_interior_zero() {
+    let text = units("a\0b");
+    let _: SeaString<ZeroTerm, Utf16, Malloc> = text.iter().cloned().collect();
+}
+
+#[test]
+#[should_panic]
+fn test_collect_early_zero_wrong_hint() {
+    // The zero lands where the hint says the string ends, but more units follow it.
+    let text = units("ab\0cd");
+    let _: SeaString<ZeroTerm, Utf16, Malloc> = Liar { iter: text.iter().cloned(), claim: 3 }.collect();
+}
+
+#[test]
+fn test_collect_interior_zero_slice() {
+    let text = units("a\0b");
+    let s: SeaString<Slice, Utf16, Malloc> = text.iter().cloned().collect();
+    assert_eq!(s.as_units(), &text[..]);
+}
+
+#[test]
+fn test_clone() {
+    let text = units("h\u{e9}llo \u{1f600}");
+
+    let s = SeaString::<ZeroTerm, Utf16, Malloc>::new(&text).expect(here!());
+    let t = s.clone();
+    assert_eq!(s, t);
+    assert_eq!(t.as_units(), &text[..]);
+    assert!(s.as_ptr() != t.as_ptr());
+
+    let s = SeaString::<Slice, Utf16, Malloc>::new(&text).expect(here!());
+    let t = s.clone();
+    assert_eq!(s, t);
+    assert_eq!(t.as_units(), &text[..]);
+
+    let s = SeaString::<ZeroTerm, Utf16, Malloc>::new(&[]).expect(here!());
+    assert!(s.clone().is_empty());
+    let s = SeaString::<Slice, Utf16, Malloc>::new(&[]).expect(here!());
+    assert!(s.clone().is_empty());
+}
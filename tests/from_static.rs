@@ -0,0 +1,47 @@
+extern crate strffi;
+
+use strffi::encoding::{Utf8, Utf8Unit, Utf16, Utf16Unit, Utf32, Utf32Unit};
+use strffi::sea::SeStr;
+use strffi::structure::Slice;
+
+const HELLO: &'static SeStr<Slice, Utf16> = SeStr::from_static(&[Utf16Unit(0x68), Utf16Unit(0x69)]);
+
+static TABLE: [&'static SeStr<Slice, Utf8>; 3] = [
+    SeStr::from_static(&[]),
+    SeStr::from_static(&[Utf8Unit(b'a')]),
+    SeStr::from_static(&[Utf8Unit(b'b'), Utf8Unit(0), Utf8Unit(b'c')]),
+];
+
+#[test]
+fn test_from_static() {
+    assert_eq!(HELLO.as_units(), &[Utf16Unit(0x68), Utf16Unit(0x69)]);
+    assert_eq!(TABLE[0].as_units(), &[]);
+    assert_eq!(TABLE[1].as_units(), &[Utf8Unit(b'a')]);
+    assert_eq!(TABLE[2].as_units(), &[Utf8Unit(b'b'), Utf8Unit(0), Utf8Unit(b'c')]);
+    assert_eq!(TABLE[2].into_string().unwrap(), "b\0c");
+}
+
+/**
+Checks that re-borrowing a slice as a `SeStr` keeps both its address and its length.
+*/
+#[test]
+fn test_same_units() {
+    static UNITS: [Utf32Unit; 4] = [Utf32Unit(1), Utf32Unit(2), Utf32Unit(3), Utf32Unit(4)];
+
+    for start in 0..UNITS.len() + 1 {
+        for end in start..UNITS.len() + 1 {
+            let units = &UNITS[start..end];
+            for s in &[SeStr::<Slice, Utf32>::new(units), SeStr::<Slice, Utf32>::from_static(units)] {
+                assert_eq!(s.as_units().as_ptr(), units.as_ptr());
+                assert_eq!(s.as_units().len(), units.len());
+                assert_eq!(s.as_units(), units);
+            }
+        }
+    }
+
+    let mut units = [Utf32Unit(5), Utf32Unit(6)];
+    let ptr = units.as_ptr();
+    let s = SeStr::<Slice, Utf32>::new_mut(&mut units);
+    assert_eq!(s.as_units().as_ptr(), ptr);
+    assert_eq!(s.as_units(), &[Utf32Unit(5), Utf32Unit(6)]);
+}
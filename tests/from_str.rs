@@ -0,0 +1,51 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use std::convert::TryFrom;
+use strffi::ZWCString;
+use strffi::alloc::{AllocError, Malloc, Rust};
+use strffi::encoding::{JniMtf8, Utf16, Utf16Unit, Utf32, Wide};
+use strffi::sea::SeaString;
+use strffi::structure::{Slice, ZeroTerm};
+
+const WORD: &'static str = "gªrçon😀";
+
+#[test]
+fn test_from_str_round_trip() {
+    let w = ZWCString::from_str(WORD).expect(here!());
+    assert_eq!(w.into_string().expect(here!()), WORD);
+
+    let u16s = SeaString::<ZeroTerm, Utf16, Malloc>::from_str(WORD).expect(here!());
+    let expected: Vec<_> = WORD.encode_utf16().map(Utf16Unit).collect();
+    assert_eq!(u16s.as_units(), &expected[..]);
+    assert_eq!(u16s.into_string().expect(here!()), WORD);
+
+    let u32s = SeaString::<Slice, Utf32, Rust>::from_str(WORD).expect(here!());
+    assert_eq!(u32s.into_string().expect(here!()), WORD);
+
+    let mtf8 = SeaString::<ZeroTerm, JniMtf8, Malloc>::from_str(WORD).expect(here!());
+    assert_eq!(mtf8.into_string().expect(here!()), WORD);
+}
+
+#[test]
+fn test_try_from_str() {
+    let w = SeaString::<ZeroTerm, Wide, Malloc>::try_from(WORD).expect(here!());
+    assert_eq!(w, ZWCString::from_str(WORD).expect(here!()));
+
+    let u16s = SeaString::<Slice, Utf16, Malloc>::try_from(WORD).expect(here!());
+    assert_eq!(u16s.into_string().expect(here!()), WORD);
+}
+
+#[test]
+fn test_from_str_interior_zero() {
+    let err = SeaString::<ZeroTerm, Utf16, Malloc>::from_str("gar\0çon").err().expect(here!());
+    assert_eq!(err.downcast_ref::<AllocError>(), Some(&AllocError::InteriorZeroAt(3)));
+
+    let err = SeaString::<ZeroTerm, Wide, Malloc>::try_from("gar\0çon").err().expect(here!());
+    assert_eq!(err.downcast_ref::<AllocError>(), Some(&AllocError::InteriorZeroAt(3)));
+
+    // Slices have no terminator, so zeroes are just content.
+    let s = SeaString::<Slice, Utf16, Malloc>::from_str("gar\0çon").expect(here!());
+    assert_eq!(s.into_string().expect(here!()), "gar\0çon");
+}
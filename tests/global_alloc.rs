@@ -0,0 +1,33 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Global;
+use strffi::encoding::{Utf8, Utf8Unit, Wide, WUnit};
+use strffi::sea::SeaString;
+use strffi::structure::{Slice, ZeroTerm};
+
+#[test]
+fn test_global_slice() {
+    let units: Vec<_> = b"gar\xc3\xa7on".iter().map(|&b| Utf8Unit(b)).collect();
+    let s = SeaString::<Slice, Utf8, Global>::new(&units).expect(here!());
+    assert_eq!(s.as_units(), &units[..]);
+
+    let t = s.clone();
+    drop(s);
+    assert_eq!(t.as_units(), &units[..]);
+}
+
+#[test]
+fn test_global_empty() {
+    let s = SeaString::<Slice, Utf8, Global>::new(&[]).expect(here!());
+    assert_eq!(s.as_units(), &[][..]);
+}
+
+#[test]
+fn test_global_zero_term() {
+    let units: Vec<_> = (0x61..0x7b).map(|u| WUnit(u)).collect();
+    let s = SeaString::<ZeroTerm, Wide, Global>::new(&units).expect(here!());
+    assert_eq!(s.as_units(), &units[..]);
+    assert_eq!(s.as_units_with_term().last(), Some(&WUnit(0)));
+}
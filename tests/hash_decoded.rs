@@ -0,0 +1,66 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use strffi::alloc::Malloc;
+use strffi::encoding::{Utf8, Utf8Unit, Utf16Le, Utf32Be};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+
+const WORD: &'static str = "gªrçon😀";
+
+fn hash<T: Hash + ?Sized>(v: &T) -> u64 {
+    let mut state = DefaultHasher::new();
+    v.hash(&mut state);
+    state.finish()
+}
+
+fn hash_str(s: &str) -> u64 {
+    let mut state = DefaultHasher::new();
+    for c in s.chars() { c.hash(&mut state); }
+    state.write_u8(0xff);
+    state.finish()
+}
+
+macro_rules! hash_decoded {
+    ($s:expr) => {
+        {
+            let mut state = DefaultHasher::new();
+            $s.hash_decoded(&mut state);
+            state.finish()
+        }
+    };
+}
+
+#[test]
+fn test_hash_decoded() {
+    let u8s = SeaString::<ZeroTerm, Utf8, Malloc>::from_str(WORD).expect(here!());
+    let u16s = SeaString::<Slice, Utf16Le, Malloc>::from_str(WORD).expect(here!());
+    let u32s = SeaString::<ZeroTerm, Utf32Be, Malloc>::from_str(WORD).expect(here!());
+
+    assert_eq!(hash_decoded!(u8s), hash_str(WORD));
+    assert_eq!(hash_decoded!(u16s), hash_str(WORD));
+    assert_eq!(hash_decoded!(u32s), hash_str(WORD));
+    assert!(hash_decoded!(u8s) != hash_str("garcon"));
+
+    // Hashing is prefix-free.
+    let empty = SeaString::<Slice, Utf8, Malloc>::from_str("").expect(here!());
+    assert_eq!(hash_decoded!(empty), hash_str(""));
+}
+
+#[test]
+fn test_hash_units() {
+    let units: Vec<_> = WORD.bytes().map(Utf8Unit).collect();
+    let zstr = SeaString::<ZeroTerm, Utf8, Malloc>::new(&units).expect(here!());
+    let sstr = SeStr::<Slice, Utf8>::new(&units);
+    assert_eq!(hash(&*zstr), hash(sstr));
+}
+
+#[test]
+fn test_hash_decoded_lossy() {
+    let units = vec![Utf8Unit(b'a'), Utf8Unit(0xff)];
+    let sstr = SeStr::<Slice, Utf8>::new(&units);
+    assert_eq!(hash_decoded!(sstr), hash_str("a\u{fffd}"));
+}
@@ -0,0 +1,141 @@
+#![cfg(all(feature="iconv", target_os="linux"))]
+extern crate libc;
+#[macro_use] extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use std::ffi::CStr;
+use strffi::alloc::Malloc;
+use strffi::encoding::{CheckedUnicode, Raw8Unit};
+use strffi::encoding::conv::iconv::{IconvCharset, IconvToUniError, UniToIconvError};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+
+declare_iconv_encoding! {
+    pub enum ShiftJis;
+    unit SjisUnit;
+    prefix "Sjis";
+    iconv_name "SHIFT_JIS";
+}
+
+declare_iconv_encoding! {
+    pub enum EucKr;
+    unit EucKrUnit;
+    prefix "EucKr";
+    iconv_name "EUC-KR";
+}
+
+declare_iconv_encoding! {
+    pub enum Bogus;
+    unit BogusUnit;
+    prefix "Bogus";
+    iconv_name "NOT-A-REAL-CHARSET";
+}
+
+fn sjis(bytes: &[u8]) -> Vec<SjisUnit> {
+    bytes.iter().map(|&b| SjisUnit(b)).collect()
+}
+
+fn current_locale() -> String {
+    unsafe {
+        let r = libc::setlocale(libc::LC_ALL, ::std::ptr::null());
+        CStr::from_ptr(r).to_string_lossy().into_owned()
+    }
+}
+
+#[test]
+fn test_iconv_decode() {
+    let locale = current_locale();
+
+    let units = sjis(b"\x83\x65\x83\x58\x83\x67 ok");
+    let s = SeStr::<Slice, ShiftJis>::new(&units);
+    assert_eq!(s.into_string().expect(here!()), "テスト ok");
+
+    let units = [EucKrUnit(0xbe), EucKrUnit(0xc8), EucKrUnit(0xb3), EucKrUnit(0xe7)];
+    assert_eq!(SeStr::<Slice, EucKr>::new(&units).into_string().expect(here!()), "안녕");
+
+    // Embedded zeroes are just characters.
+    let units = sjis(b"a\0\x83\x65");
+    assert_eq!(SeStr::<Slice, ShiftJis>::new(&units).into_string().expect(here!()), "a\0テ");
+
+    // Longer than a single chunk, with characters straddling the chunk boundaries.
+    let units = sjis(&b"a\x83\x65".repeat(100));
+    assert_eq!(SeStr::<Slice, ShiftJis>::new(&units).into_string().expect(here!()), "aテ".repeat(100));
+
+    assert_eq!(current_locale(), locale);
+}
+
+#[test]
+fn test_iconv_decode_errors() {
+    let units = sjis(b"ab\x83\x65\x83");
+    let r: Vec<_> = SeStr::<Slice, ShiftJis>::new(&units).transcode_to_iter::<CheckedUnicode>().collect();
+    assert_eq!(r, vec![Ok('a'), Ok('b'), Ok('テ'), Err(IconvToUniError::Incomplete)]);
+
+    let units = sjis(b"ab\x83\x65\xff\xffcd");
+    let r: Vec<_> = SeStr::<Slice, ShiftJis>::new(&units).transcode_to_iter::<CheckedUnicode>().collect();
+    assert_eq!(r, vec![Ok('a'), Ok('b'), Ok('テ'), Err(IconvToUniError::InvalidAt(4))]);
+
+    let units = [BogusUnit(b'a')];
+    let r: Vec<_> = SeStr::<Slice, Bogus>::new(&units).transcode_to_iter::<CheckedUnicode>().collect();
+    assert_eq!(r, vec![Err(IconvToUniError::UnsupportedEncoding)]);
+
+    // Dropping a transcoder part-way through closes its descriptor.
+    let units = sjis(&b"a\x83\x65".repeat(100));
+    let mut chars = SeStr::<Slice, ShiftJis>::new(&units).transcode_to_iter::<CheckedUnicode>();
+    assert_eq!(chars.next(), Some(Ok('a')));
+    drop(chars);
+}
+
+#[test]
+fn test_iconv_encode() {
+    let locale = current_locale();
+
+    let s = SeaString::<ZeroTerm, ShiftJis, Malloc>::from_str("テスト ok").expect(here!());
+    assert_eq!(s.as_units(), &sjis(b"\x83\x65\x83\x58\x83\x67 ok")[..]);
+
+    let r = SeaString::<Slice, ShiftJis, Malloc>::from_str("テ😀");
+    let err = r.err().expect(here!());
+    assert_eq!(err.downcast_ref::<UniToIconvError>(), Some(&UniToIconvError::InvalidAt(1)));
+
+    let r = SeaString::<Slice, Bogus, Malloc>::from_str("a");
+    let err = r.err().expect(here!());
+    assert_eq!(err.downcast_ref::<UniToIconvError>(), Some(&UniToIconvError::UnsupportedEncoding));
+
+    assert_eq!(current_locale(), locale);
+}
+
+#[test]
+fn test_iconv_runtime_charset() {
+    fn raw(bytes: &[u8]) -> Vec<Raw8Unit> {
+        bytes.iter().map(|&b| Raw8Unit(b)).collect()
+    }
+
+    // The same bytes, picked apart by whichever character set is chosen at the time.
+    let units = raw(b"\xbe\xc8");
+    let s: Result<String, _> = IconvCharset("EUC-KR").decode(units.iter().cloned()).collect();
+    assert_eq!(s.expect(here!()), "안");
+    let s: Result<String, _> = IconvCharset("ISO-8859-1").decode(units.iter().cloned()).collect();
+    assert_eq!(s.expect(here!()), "\u{be}\u{c8}");
+
+    let r: Vec<_> = IconvCharset("SHIFT_JIS").decode(raw(b"a\x83")).collect();
+    assert_eq!(r, vec![Ok('a'), Err(IconvToUniError::Incomplete)]);
+
+    let r: Vec<_> = IconvCharset("NOT-A-REAL-CHARSET").decode(raw(b"a")).collect();
+    assert_eq!(r, vec![Err(IconvToUniError::UnsupportedEncoding)]);
+
+    let r: Result<Vec<_>, _> = IconvCharset("SHIFT_JIS").encode("テスト ok".chars()).collect();
+    assert_eq!(r.expect(here!()), raw(b"\x83\x65\x83\x58\x83\x67 ok"));
+
+    let r: Vec<_> = IconvCharset("SHIFT_JIS").encode("a😀".chars()).collect();
+    assert_eq!(r, vec![Ok(Raw8Unit(b'a')), Err(UniToIconvError::InvalidAt(1))]);
+}
+
+#[test]
+fn test_iconv_encode_size_hint() {
+    // Encoding stops at the first unrepresentable character, so the lower bound can't count the rest.
+    let mut iter = IconvCharset("SHIFT_JIS").encode("😀abcdef".chars());
+    let (lo, _) = iter.size_hint();
+    assert!(lo <= 1, "lo: {}", lo);
+    assert_eq!(iter.next(), Some(Err(UniToIconvError::InvalidAt(0))));
+    assert_eq!(iter.next(), None);
+}
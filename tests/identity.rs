@@ -0,0 +1,56 @@
+#[macro_use] extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::ZWCString;
+use strffi::alloc::{Allocator, Malloc};
+use strffi::encoding::{Encoding, TranscodeTo, UnitIter, Utf8, Utf8Unit, Wide, WUnit};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, Structure, StructureAlloc, StructureIter, ZeroTerm};
+
+fn to_wide<'a, S, E, T, A>(s: &'a SeStr<S, E>) -> SeaString<T, Wide, A>
+where
+    S: StructureIter<'a, E>,
+    E: Encoding,
+    T: Structure<Wide> + StructureAlloc<Wide, A>,
+    A: Allocator,
+    UnitIter<E, S::Iter>: TranscodeTo<Wide>,
+{
+    s.transcode_to().expect(here!())
+}
+
+#[test]
+fn test_identity_generic() {
+    let z = ZWCString::from_str("gªrçon").expect(here!());
+    let w: SeaString<Slice, Wide, Malloc> = to_wide(&*z);
+    assert_eq!(w.as_units(), z.as_units());
+    assert_eq!(w.into_string().expect(here!()), "gªrçon");
+}
+
+#[test]
+fn test_identity_preserves_invalid() {
+    let s = SeStr::<Slice, Utf8>::from_bytes(b"a\xffb");
+    let z = s.transcode_to::<ZeroTerm, Utf8, Malloc>().expect(here!());
+    assert_eq!(z.as_units(), &[Utf8Unit(b'a'), Utf8Unit(0xff), Utf8Unit(b'b')]);
+
+    let units: Vec<_> = s.transcode_to_iter::<Utf8>().collect();
+    assert_eq!(units.len(), 3);
+    assert!(units.iter().all(|r| r.is_ok()));
+
+    let w = [WUnit(0x61), WUnit(0xdc00)];
+    let w = SeStr::<Slice, Wide>::new(&w);
+    assert_eq!(w.transcode_to_iter::<Wide>().size_hint(), (2, Some(2)));
+}
+
+declare_encoding! {
+    pub enum Gsm7;
+    unit Gsm7Unit(u8);
+    prefix "Gsm7";
+}
+
+#[test]
+fn test_identity_declared() {
+    let s = SeaString::<ZeroTerm, Gsm7, Malloc>::new(&[Gsm7Unit(0x48), Gsm7Unit(0x69)]).expect(here!());
+    let t = s.transcode_to::<Slice, Gsm7, Malloc>().expect(here!());
+    assert_eq!(t.as_units(), s.as_units());
+}
@@ -0,0 +1,71 @@
+extern crate strffi;
+
+mod common;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use std::convert::TryFrom;
+use strffi::ZMbCString;
+use strffi::alloc::{AllocError, Malloc};
+use strffi::encoding::{CheckedUnicode, MbUnit, Utf8};
+use strffi::sea::SeaString;
+use strffi::structure::{Slice, ZeroTerm};
+use common::utf8;
+
+#[test]
+fn test_interior_zero_rejected() {
+    let r = SeaString::<ZeroTerm, Utf8, Malloc>::new(&utf8(b"a\0b\0"));
+    assert_eq!(r.err(), Some(AllocError::InteriorZeroAt(1)));
+
+    let r = SeaString::<ZeroTerm, Utf8, Malloc>::new(&utf8(b"\0\0"));
+    assert_eq!(r.err(), Some(AllocError::InteriorZeroAt(0)));
+
+    let r = SeaString::<ZeroTerm, Utf8, Malloc>::new(&utf8(b"a\0b"));
+    assert_eq!(r.err(), Some(AllocError::InteriorZeroAt(1)));
+
+    let r = ZMbCString::new(&[MbUnit(0x61), MbUnit(0), MbUnit(0x62), MbUnit(0)]);
+    assert_eq!(r.err(), Some(AllocError::InteriorZeroAt(1)));
+}
+
+#[test]
+fn test_trailing_zero_accepted() {
+    let s = SeaString::<ZeroTerm, Utf8, Malloc>::new(&utf8(b"ab\0")).expect(here!());
+    assert_eq!(s.as_units(), &utf8(b"ab")[..]);
+
+    let s = SeaString::<ZeroTerm, Utf8, Malloc>::new(&utf8(b"\0")).expect(here!());
+    assert_eq!(s.as_units(), &[][..]);
+
+    // Zeroes are just content in a slice.
+    let s = SeaString::<Slice, Utf8, Malloc>::new(&utf8(b"a\0b\0")).expect(here!());
+    assert_eq!(s.as_units(), &utf8(b"a\0b\0")[..]);
+}
+
+#[test]
+fn test_push_interior_zero_rejected() {
+    let mut s = SeaString::<ZeroTerm, Utf8, Malloc>::new(&utf8(b"ab")).expect(here!());
+    assert_eq!(s.push_units(&utf8(b"c\0d")), Err(AllocError::InteriorZeroAt(3)));
+    assert_eq!(s.as_units(), &utf8(b"ab")[..]);
+
+    s.push_units(&utf8(b"c\0")).expect(here!());
+    assert_eq!(s.as_units(), &utf8(b"abc")[..]);
+
+    assert_eq!(format!("{}", AllocError::InteriorZeroAt(3)), "interior zero at offset 3");
+}
+
+#[test]
+fn test_try_from_units() {
+    let units = utf8(b"ab");
+    let s = SeaString::<ZeroTerm, Utf8, Malloc>::try_from(&units[..]).expect(here!());
+    assert_eq!(s.as_units(), &units[..]);
+
+    let units = utf8(b"a\0b");
+    let r = SeaString::<ZeroTerm, Utf8, Malloc>::try_from(&units[..]);
+    assert_eq!(r.err(), Some(AllocError::InteriorZeroAt(1)));
+
+    let s = SeaString::<Slice, Utf8, Malloc>::try_from(&units[..]).expect(here!());
+    assert_eq!(s.as_units(), &units[..]);
+
+    let chars = ['g', 'ª', '\0'];
+    let s: SeaString<ZeroTerm, CheckedUnicode, Malloc> = TryFrom::try_from(&chars[..]).expect(here!());
+    assert_eq!(s.as_units(), &chars[..2]);
+}
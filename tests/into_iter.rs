@@ -0,0 +1,59 @@
+extern crate strffi;
+
+mod common;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::{Malloc, Rust};
+use strffi::encoding::{Utf8, Utf8Unit, Utf16, Utf16Unit};
+use strffi::sea::SeaString;
+use strffi::structure::{Slice, ZeroTerm};
+use common::utf8;
+
+#[test]
+fn test_into_iter_owned() {
+    let s = SeaString::<ZeroTerm, Utf8, Malloc>::new(&utf8(b"garcon")).expect(here!());
+    let units: Vec<_> = s.into_iter().collect();
+    assert_eq!(units, utf8(b"garcon"));
+
+    let s = SeaString::<Slice, Utf8, Rust>::new(&utf8(b"garcon")).expect(here!());
+    let mut n = 0;
+    for (u, &b) in s.into_iter().zip(b"garcon") {
+        assert_eq!(u, Utf8Unit(b));
+        n += 1;
+    }
+    assert_eq!(n, 6);
+
+    let s = SeaString::<ZeroTerm, Utf8, Malloc>::new(&[]).expect(here!());
+    assert_eq!(s.into_iter().next(), None);
+}
+
+#[test]
+fn test_into_iter_partial() {
+    let s = SeaString::<ZeroTerm, Utf16, Malloc>::from_str("garçon").expect(here!());
+    let mut iter = s.into_iter();
+    assert_eq!(iter.len(), 6);
+    assert_eq!(iter.next(), Some(Utf16Unit(0x67)));
+    assert_eq!(iter.next_back(), Some(Utf16Unit(0x6e)));
+    assert_eq!(iter.len(), 4);
+    assert_eq!(iter.as_units(), &"arço".encode_utf16().map(Utf16Unit).collect::<Vec<_>>()[..]);
+    drop(iter);
+
+    let s = SeaString::<Slice, Utf8, Malloc>::new(&utf8(b"garcon")).expect(here!());
+    let mut iter = s.into_iter();
+    assert_eq!(iter.nth(2), Some(Utf8Unit(b'r')));
+    let rest: Vec<_> = iter.rev().collect();
+    assert_eq!(rest, utf8(b"noc"));
+}
+
+#[test]
+fn test_into_iter_borrowed() {
+    let s = SeaString::<ZeroTerm, Utf8, Malloc>::new(&utf8(b"garcon")).expect(here!());
+    let mut units = vec![];
+    for u in &s {
+        units.push(u);
+    }
+    assert_eq!(units, utf8(b"garcon"));
+    assert_eq!((&s).into_iter().count(), 6);
+    assert_eq!(s.as_units(), &utf8(b"garcon")[..]);
+}
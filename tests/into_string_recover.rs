@@ -0,0 +1,34 @@
+extern crate strffi;
+
+mod common;
+
+use strffi::encoding::{Utf8, Utf32, Utf32Unit};
+use strffi::sea::SeStr;
+use strffi::structure::Slice;
+use common::utf8;
+
+#[test]
+fn test_utf8() {
+    let units = utf8(b"gar\xc3\xa7on");
+    let s = SeStr::<Slice, Utf8>::new(&units);
+    assert_eq!(s.into_string_recover(), ("garçon".to_owned(), vec![]));
+
+    let units = utf8(b"a\xffb\xc3c\xed\xa0\x80d");
+    let s = SeStr::<Slice, Utf8>::new(&units);
+    assert_eq!(s.into_string_recover(), ("a\u{fffd}b\u{fffd}c\u{fffd}\u{fffd}\u{fffd}d".to_owned(), vec![1, 3, 5, 6, 7]));
+
+    // A truncated sequence at the end has no offset of its own.
+    let units = utf8(b"ab\xe2\x82");
+    let s = SeStr::<Slice, Utf8>::new(&units);
+    assert_eq!(s.into_string_recover(), ("ab\u{fffd}".to_owned(), vec![4]));
+
+    let s = SeStr::<Slice, Utf8>::new(&[]);
+    assert_eq!(s.into_string_recover(), (String::new(), vec![]));
+}
+
+#[test]
+fn test_utf32() {
+    let units = [Utf32Unit(0x61), Utf32Unit(0xd800), Utf32Unit(0x62), Utf32Unit(0x110000)];
+    let s = SeStr::<Slice, Utf32>::new(&units);
+    assert_eq!(s.into_string_recover(), ("a\u{fffd}b\u{fffd}".to_owned(), vec![1, 3]));
+}
@@ -0,0 +1,103 @@
+extern crate strffi;
+
+mod common;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::encoding::{CheckedUnicode, Utf8, Utf16, Utf16Unit, Utf32, Utf32Unit, Wide16, W16Unit};
+use strffi::sea::SeStr;
+use strffi::structure::{Slice, ZeroTerm};
+use common::utf8;
+
+const UTF8: &'static [&'static [u8]] = &[
+    b"",
+    b"g\xc2\xaar\xc3\xa7on\xf0\x9f\x98\x80",
+    b"a\0b",
+    b"ab\xff",
+    b"ab\x80cd",
+    b"a\xc0\x80",
+    b"a\xed\xa0\x80",
+    b"a\xf4\x90\x80\x80",
+    b"a\xe2\x82z",
+    b"a\xe2\x82",
+    b"a\xf0\x9f\x98",
+    b"a\xffb\xc3c\xed\xa0\x80d",
+];
+
+#[test]
+fn test_utf8() {
+    for bytes in UTF8 {
+        let units = utf8(bytes);
+        let s = SeStr::<Slice, Utf8>::new(&units);
+
+        // The `from_utf8` shortcut must agree with transcoding.
+        let generic = s.transcode_to_iter::<CheckedUnicode>().all(|r| r.is_ok());
+        assert_eq!(s.is_valid(), generic, "{:?}", s);
+        assert_eq!(s.is_valid(), s.into_string().is_ok(), "{:?}", s);
+        assert_eq!(s.is_valid(), s.is_valid_utf8(), "{:?}", s);
+
+        assert_eq!(s.validation_errors(), s.into_string_recover().1, "{:?}", s);
+        assert_eq!(s.validation_errors().is_empty(), s.is_valid(), "{:?}", s);
+    }
+
+    let units = utf8(b"a\xffb\xc3c\xed\xa0\x80d");
+    assert_eq!(SeStr::<Slice, Utf8>::new(&units).validation_errors(), vec![1, 3, 5, 6, 7]);
+
+    // A truncated sequence at the end is reported at the length of the string.
+    let units = utf8(b"ab\xe2\x82");
+    assert_eq!(SeStr::<Slice, Utf8>::new(&units).validation_errors(), vec![4]);
+}
+
+#[test]
+fn test_utf16() {
+    let valid: Vec<_> = "gar\u{e7}on \u{1f600}".encode_utf16().map(Utf16Unit).collect();
+    let s = SeStr::<Slice, Utf16>::new(&valid);
+    assert!(s.is_valid());
+
+    assert_eq!(s.validation_errors(), vec![]);
+
+    let invalid = [Utf16Unit(0x61), Utf16Unit(0xdc00), Utf16Unit(0x62), Utf16Unit(0xd800)];
+    assert!(!SeStr::<Slice, Utf16>::new(&invalid).is_valid());
+    assert!(!SeStr::<Slice, Utf16>::new(&invalid[3..]).is_valid());
+    assert!(SeStr::<Slice, Utf16>::new(&invalid[..1]).is_valid());
+
+    // A trailing high surrogate is reported at the length of the string.
+    let s = SeStr::<Slice, Utf16>::new(&invalid);
+    assert_eq!(s.validation_errors(), vec![1, 4]);
+    assert_eq!(s.validation_errors(), s.into_string_recover().1);
+
+    // Decoding resumes after each unpaired surrogate, including with the unit after a high surrogate.
+    let invalid = [Utf16Unit(0xdc00), Utf16Unit(0x62), Utf16Unit(0xd800), Utf16Unit(0x63), Utf16Unit(0xd83d), Utf16Unit(0xde00)];
+    let s = SeStr::<Slice, Utf16>::new(&invalid);
+    assert_eq!(s.validation_errors(), vec![0, 2]);
+    assert_eq!(s.into_string_recover(), ("\u{fffd}b\u{fffd}c\u{1f600}".to_owned(), vec![0, 2]));
+
+    let invalid = [W16Unit(0x61), W16Unit(0xdc00), W16Unit(0x62), W16Unit(0xd800), W16Unit(0x63)];
+    assert_eq!(SeStr::<Slice, Wide16>::new(&invalid).validation_errors(), vec![1, 3]);
+}
+
+#[test]
+fn test_utf32() {
+    let valid: Vec<_> = "gar\u{e7}on \u{1f600}".chars().map(|c| Utf32Unit(c as u32)).collect();
+    let s = SeStr::<Slice, Utf32>::new(&valid);
+    assert!(s.is_valid());
+    assert_eq!(s.validation_errors(), vec![]);
+
+    let invalid = [Utf32Unit(0x61), Utf32Unit(0xd800), Utf32Unit(0x110000)];
+    let s = SeStr::<Slice, Utf32>::new(&invalid);
+    assert!(!s.is_valid());
+    assert_eq!(s.validation_errors(), vec![1, 2]);
+}
+
+#[test]
+fn test_zero_term() {
+    let units = utf8(b"gar\xc3\xa7on\0");
+    let s = unsafe { SeStr::<ZeroTerm, Utf8>::from_ptr(units.as_ptr() as *const _) }.expect(here!());
+    assert!(s.is_valid());
+    assert_eq!(s.validation_errors(), vec![]);
+
+    let units = utf8(b"gar\xe7on\0");
+    let s = unsafe { SeStr::<ZeroTerm, Utf8>::from_ptr(units.as_ptr() as *const _) }.expect(here!());
+    assert!(!s.is_valid());
+    assert_eq!(s.validation_errors(), vec![3]);
+}
@@ -0,0 +1,93 @@
+extern crate strffi;
+
+mod common;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{CheckedUnicode, JniMtf8, Mtf8Unit, Utf8, Utf8Unit, UnitIter, TranscodeTo};
+use strffi::encoding::conv::jni_mtf8::{Mtf8ToUniError, Utf8ToMtf8Error};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::ZeroTerm;
+use common::utf8;
+
+const WORD: &'static str = "a\u{0}\u{e7}\u{20ac}\u{1f600}z";
+const WORD_MTF8: &'static [u8] = b"a\xc0\x80\xc3\xa7\xe2\x82\xac\xed\xa0\xbd\xed\xb8\x80z";
+
+fn mtf8(bytes: &[u8]) -> Vec<Mtf8Unit> {
+    bytes.iter().map(|&b| Mtf8Unit(b)).collect()
+}
+
+fn decode(bytes: &[u8]) -> Result<String, Mtf8ToUniError> {
+    let units = mtf8(bytes);
+    TranscodeTo::<CheckedUnicode>::transcode(UnitIter::<JniMtf8, _>::new(units.into_iter()))
+        .collect()
+}
+
+#[test]
+fn test_mtf8_round_trip() {
+    let zjstr = SeaString::<ZeroTerm, JniMtf8, Malloc>::from_str(WORD).expect(here!());
+    assert_eq!(zjstr.as_units(), &mtf8(WORD_MTF8)[..]);
+    assert_eq!(zjstr.into_string().expect(here!()), WORD);
+}
+
+#[test]
+fn test_mtf8_to_utf8() {
+    let zjstr = SeaString::<ZeroTerm, JniMtf8, Malloc>::new(&mtf8(WORD_MTF8)).expect(here!());
+    let units: Result<Vec<Utf8Unit>, _> = zjstr.transcode_to_iter::<Utf8>().collect();
+    assert_eq!(units.expect(here!()), utf8(WORD.as_bytes()));
+}
+
+#[test]
+fn test_utf8_to_mtf8() {
+    let units = utf8(WORD.as_bytes());
+    let r: Result<Vec<Mtf8Unit>, _> =
+        TranscodeTo::<JniMtf8>::transcode(UnitIter::<Utf8, _>::new(units.into_iter()))
+            .collect();
+    assert_eq!(r.expect(here!()), mtf8(WORD_MTF8));
+
+    let units = utf8(b"ab\xed\xa0\xbdz");
+    let r: Result<Vec<Mtf8Unit>, _> =
+        TranscodeTo::<JniMtf8>::transcode(UnitIter::<Utf8, _>::new(units.into_iter()))
+            .collect();
+    assert_eq!(r, Err(Utf8ToMtf8Error::InvalidAt(2)));
+}
+
+#[test]
+fn test_mtf8_invalid() {
+    // Overlong forms other than `C0 80`.
+    assert_eq!(decode(b"ab\xc0\x81"), Err(Mtf8ToUniError::InvalidAt(2)));
+    assert_eq!(decode(b"ab\xe0\x80\x80"), Err(Mtf8ToUniError::InvalidAt(2)));
+    // Four-unit sequences are not used.
+    assert_eq!(decode(b"ab\xf0\x9f\x98\x80"), Err(Mtf8ToUniError::InvalidAt(2)));
+    // Unpaired surrogates.
+    assert_eq!(decode(b"ab\xed\xb8\x80"), Err(Mtf8ToUniError::InvalidAt(2)));
+    assert_eq!(decode(b"ab\xed\xa0\xbdz"), Err(Mtf8ToUniError::InvalidAt(2)));
+    // Stray continuation unit.
+    assert_eq!(decode(b"\xc3\xa7\x80"), Err(Mtf8ToUniError::InvalidAt(2)));
+    // Truncated.
+    assert_eq!(decode(b"ab\xed\xa0\xbd"), Err(Mtf8ToUniError::Incomplete));
+    assert_eq!(decode(b"ab\xc3"), Err(Mtf8ToUniError::Incomplete));
+}
+
+#[test]
+fn test_mtf8_from_ptr() {
+    let mut bytes = WORD_MTF8.to_vec();
+    bytes.push(0);
+    let zjstr = unsafe { SeStr::<ZeroTerm, JniMtf8>::from_ptr(bytes.as_ptr() as *const _).expect(here!()) };
+    let chars: Result<Vec<char>, _> = zjstr.transcode_to_iter::<CheckedUnicode>().collect();
+    assert_eq!(chars.expect(here!()), WORD.chars().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_mtf8_supplementary() {
+    // Supplementary characters are always six units, never four.
+    let zjstr = SeaString::<ZeroTerm, JniMtf8, Malloc>::from_str("\u{10000}\u{10ffff}").expect(here!());
+    assert_eq!(zjstr.as_units(), &mtf8(b"\xed\xa0\x80\xed\xb0\x80\xed\xaf\xbf\xed\xbf\xbf")[..]);
+    assert_eq!(zjstr.into_string().expect(here!()), "\u{10000}\u{10ffff}");
+
+    // Embedded zeroes never produce a zero unit.
+    let zjstr = SeaString::<ZeroTerm, JniMtf8, Malloc>::from_str("\u{0}\u{0}").expect(here!());
+    assert_eq!(zjstr.as_units(), &mtf8(b"\xc0\x80\xc0\x80")[..]);
+    assert_eq!(format!("{:?}", zjstr), r#"ZJniC"\xc0\x80\xc0\x80""#);
+}
@@ -5,10 +5,14 @@ extern crate strffi;
 macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
 
 use strffi::{ZMbStr, ZMbCString, ZWCString, ZWStr};
+use strffi::alloc::Malloc;
+use strffi::encoding::{Char16, C16Unit, Char32, C32Unit, MultiByte};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
 
 fn set_utf8() {
     unsafe {
-        let r = libc::setlocale(libc::LC_ALL, b"C.UTF-8".as_ptr() as *const _);
+        let r = libc::setlocale(libc::LC_ALL, b"C.UTF-8\0".as_ptr() as *const _);
         assert!(!r.is_null());
     }
 }
@@ -52,3 +56,209 @@ fn test_garcon() {
         assert_eq!(&zwcstr, zwstr);
     }
 }
+
+#[test]
+fn test_char16_round_trip() {
+    const WORD: &'static str = "gªrçon😀";
+    const WORD_C16: &'static [u16] = &[0x67, 0xAA, 0x72, 0xE7, 0x6F, 0x6E, 0xD83D, 0xDE00];
+
+    set_utf8();
+
+    let zmbcstr = ZMbCString::from_str(WORD).expect(here!());
+    let c16s: SeaString<ZeroTerm, Char16, Malloc> = zmbcstr.transcode_to().expect(here!());
+    let expected: Vec<_> = WORD_C16.iter().map(|&u| C16Unit(u)).collect();
+    assert_eq!(c16s.as_units(), &expected[..]);
+
+    let mbs: SeaString<ZeroTerm, MultiByte, Malloc> = c16s.transcode_to().expect(here!());
+    assert_eq!(mbs.as_units(), zmbcstr.as_units());
+    assert_eq!(mbs.into_string().expect(here!()), WORD);
+}
+
+#[test]
+fn test_char32_round_trip() {
+    const WORD: &'static str = "gªrçon😀";
+
+    set_utf8();
+
+    let zmbcstr = ZMbCString::from_str(WORD).expect(here!());
+    let c32s: SeaString<ZeroTerm, Char32, Malloc> = zmbcstr.transcode_to().expect(here!());
+    let expected: Vec<_> = WORD.chars().map(|c| C32Unit(c as u32)).collect();
+    assert_eq!(c32s.as_units(), &expected[..]);
+
+    let mbs: SeaString<ZeroTerm, MultiByte, Malloc> = c32s.transcode_to().expect(here!());
+    assert_eq!(mbs.as_units(), zmbcstr.as_units());
+}
+
+#[test]
+fn test_char16_unpaired_surrogate() {
+    set_utf8();
+
+    let units = [C16Unit(0x67), C16Unit(0xD83D)];
+    let r: Result<SeaString<ZeroTerm, MultiByte, Malloc>, _> = SeStr::<Slice, Char16>::new(&units).transcode_to();
+    assert!(r.is_err());
+}
+
+#[cfg(feature="stdc-utf")]
+#[test]
+fn test_char16_as_utf16() {
+    set_utf8();
+
+    let zmbcstr = ZMbCString::from_str("gªrçon😀").expect(here!());
+    let c16s: SeaString<ZeroTerm, Char16, Malloc> = zmbcstr.transcode_to().expect(here!());
+    let c32s: SeaString<ZeroTerm, Char32, Malloc> = zmbcstr.transcode_to().expect(here!());
+    let utf16: Vec<u16> = c16s.as_utf16().as_units().iter().map(|u| u.0).collect();
+    let utf32: Vec<u32> = c32s.as_utf32().as_units().iter().map(|u| u.0).collect();
+    assert_eq!(utf16, "gªrçon😀".encode_utf16().collect::<Vec<_>>());
+    assert_eq!(utf32, "gªrçon😀".chars().map(|c| c as u32).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_wide_to_mb_slice() {
+    const WORD: &'static str = "gªrçon";
+    const WORD_MB: &'static [u8] = b"g\xc2\xaar\xc3\xa7on";
+
+    set_utf8();
+
+    let zwcstr = ZWCString::from_str(WORD).expect(here!());
+    let mbs: SeaString<Slice, MultiByte, Malloc> = zwcstr.transcode_to().expect(here!());
+    let bytes: Vec<u8> = mbs.as_units().iter().map(|u| u.0 as u8).collect();
+    assert_eq!(&bytes[..], WORD_MB);
+}
+
+#[test]
+fn test_mb_from_str_round_trip() {
+    const WORD: &'static str = "gªrçon😀";
+
+    set_utf8();
+
+    let mbs = SeaString::<ZeroTerm, MultiByte, Malloc>::from_str(WORD).expect(here!());
+    assert_eq!(mbs.as_units().len(), WORD.len());
+    assert_eq!(mbs.into_string().expect(here!()), WORD);
+}
+
+#[test]
+fn test_mb_error_offset() {
+    use strffi::encoding::conv::mb_x_wc::MbsToUniError;
+
+    set_utf8();
+
+    let err_at = |bytes: &[u8]| {
+        let err = SeStr::<Slice, MultiByte>::from_bytes(bytes).into_string().err().expect(here!());
+        *err.downcast_ref::<MbsToUniError>().expect(here!())
+    };
+
+    // Rejected by `mbrtowc` itself.
+    assert_eq!(err_at(b"g\xc2\xaar\xc3\xa7\xffon"), MbsToUniError::InvalidAt(6));
+
+    // glibc decodes this to U+110000, which is only rejected once it is a wide character; the offset must still be in bytes, not wide units.
+    assert_eq!(err_at(b"g\xc2\xaar\xc3\xa7\xf4\x90\x80\x80on"), MbsToUniError::InvalidAt(6));
+    assert_eq!(err_at(b"\xf0\x9f\x98\x80\xf0\x9f\x98\x80\xf4\x90\x80\x80"), MbsToUniError::InvalidAt(8));
+}
+
+#[test]
+fn test_mb_stream() {
+    use strffi::encoding::{CheckedUnicode, StreamTranscoder, TranscodeError, MbUnit, Wide, WUnit};
+
+    const WORD: &'static str = "gªrçon";
+    const WORD_MB: &'static [u8] = b"g\xc2\xaar\xc3\xa7on";
+
+    set_utf8();
+
+    let mb: Vec<MbUnit> = WORD_MB.iter().map(|&b| MbUnit(b as _)).collect();
+    for i in 0..mb.len() + 1 {
+        let mut tc = StreamTranscoder::<MultiByte, CheckedUnicode>::new();
+        let mut out = vec![];
+        tc.feed(&mb[..i], &mut out).expect(here!());
+        tc.feed(&mb[i..], &mut out).expect(here!());
+        tc.finish(&mut out).expect(here!());
+        assert_eq!(out.into_iter().collect::<String>(), WORD);
+    }
+
+    // Split inside "ç".
+    let mut tc = StreamTranscoder::<MultiByte, Wide>::new();
+    let mut out = vec![];
+    tc.feed(&mb[..5], &mut out).expect(here!());
+    assert_eq!(tc.pending(), 1);
+    tc.feed(&mb[5..], &mut out).expect(here!());
+    tc.finish(&mut out).expect(here!());
+    let expected: Vec<WUnit> = WORD.chars().map(|c| WUnit(c as _)).collect();
+    assert_eq!(out, expected);
+
+    tc.feed(&mb[..5], &mut out).expect(here!());
+    assert_eq!(tc.finish(&mut out), Err(TranscodeError::Incomplete));
+}
+
+#[test]
+fn test_transcode_into_mb_buffer() {
+    use strffi::encoding::MbUnit;
+    use strffi::sea::TranscodeInto;
+
+    set_utf8();
+
+    let s = ZWCString::from_str("gªrçon").expect(here!());
+    let mut out = [MbUnit(0x7f); 6];
+    assert_eq!(s.transcode_into_slice_z::<MultiByte>(&mut out).expect(here!()),
+        TranscodeInto { units_written: 4, truncated: true });
+    let bytes: Vec<u8> = out[..5].iter().map(|u| u.0 as u8).collect();
+    assert_eq!(&bytes[..], b"g\xc2\xaar\0");
+}
+
+#[test]
+fn test_mb_transcode_to_lossy() {
+    use strffi::encoding::{CheckedUnicode, MbUnit};
+
+    set_utf8();
+
+    // The multi-byte decoder is layered over wide units, and resumes wherever the lower layer does.
+    let units: Vec<_> = b"a\xffb\xc3c\xe2\x82".iter().map(|&b| MbUnit(b as _)).collect();
+    let s = SeStr::<Slice, MultiByte>::new(&units);
+    let t = s.transcode_to_lossy::<Slice, CheckedUnicode, Malloc>('?').expect(here!());
+    assert_eq!(t.as_units(), &['a', '?', 'b', '?', 'c', '?']);
+    assert_eq!(s.into_string_recover(), ("a\u{fffd}b\u{fffd}c\u{fffd}".to_owned(), vec![1, 3, 7]));
+
+    let units = ['a', '\u{e7}', 'b'];
+    let s = SeStr::<Slice, CheckedUnicode>::new(&units);
+    let t = s.transcode_to_lossy::<Slice, MultiByte, Malloc>(MbUnit(b'?' as _)).expect(here!());
+    let bytes: Vec<u8> = t.as_units().iter().map(|u| u.0 as u8).collect();
+    assert_eq!(&bytes[..], "a\u{e7}b".as_bytes());
+}
+
+#[test]
+fn test_mb_wc_recover() {
+    use strffi::encoding::{MbUnit, Wide, WUnit};
+
+    set_utf8();
+
+    let wide = |s: &str| -> Vec<WUnit> { s.chars().map(|c| WUnit(c as _)).collect() };
+
+    // Conversion between multibyte and wide units resumes after each error.  Units read ahead while looking for the end of an invalid sequence are not lost.
+    let units: Vec<_> = b"a\xffb\xc3c\xe2\x82".iter().map(|&b| MbUnit(b as _)).collect();
+    let s = SeStr::<Slice, MultiByte>::new(&units);
+    let t = s.transcode_to_lossy::<Slice, Wide, Malloc>(WUnit('?' as _)).expect(here!());
+    assert_eq!(t.as_units(), &wide("a?b?c?")[..]);
+
+    let units = [WUnit('a' as _), WUnit(0xdc00), WUnit('b' as _), WUnit(0xd800)];
+    let s = SeStr::<Slice, Wide>::new(&units);
+    let t = s.transcode_to_lossy::<Slice, MultiByte, Malloc>(MbUnit(b'?' as _)).expect(here!());
+    let bytes: Vec<u8> = t.as_units().iter().map(|u| u.0 as u8).collect();
+    assert_eq!(&bytes[..], b"a?b?");
+}
+
+#[test]
+fn test_wc_to_mb_resync() {
+    use strffi::encoding::{MbUnit, Wide, WUnit};
+    use strffi::encoding::conv::mb_x_wc::WcsToMbError;
+
+    set_utf8();
+
+    // A bad unit in the middle of the string produces one error, and the rest of the string survives.
+    let mut units: Vec<WUnit> = "gar".chars().map(|c| WUnit(c as _)).collect();
+    units.push(WUnit(0xdc00));
+    units.extend("çon".chars().map(|c| WUnit(c as _)));
+    let s = SeStr::<Slice, Wide>::new(&units);
+    let r: Vec<_> = s.transcode_to_iter::<MultiByte>().collect();
+    let mut expected: Vec<Result<MbUnit, WcsToMbError>> = b"gar".iter().map(|&b| Ok(MbUnit(b as _))).collect();
+    expected.push(Err(WcsToMbError::InvalidAt(3)));
+    expected.extend(b"\xc3\xa7on".iter().map(|&b| Ok(MbUnit(b as _))));
+    assert_eq!(r, expected);
+}
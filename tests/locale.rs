@@ -0,0 +1,60 @@
+/*!
+Checks that multibyte strings can be converted under an explicitly chosen locale.
+
+The C runtime locale is process-wide, so this gets a test binary to itself, and does everything from a single test.
+*/
+#![cfg(target_os="linux")]
+extern crate libc;
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::ZMbCString;
+use strffi::encoding::MbUnit;
+use strffi::locale::{Locale, LocaleError};
+
+fn set_locale(name: &str) -> bool {
+    let name = format!("{}\0", name);
+    unsafe { !libc::setlocale(libc::LC_ALL, name.as_ptr() as *const _).is_null() }
+}
+
+fn mb(bytes: &[u8]) -> Vec<MbUnit> {
+    bytes.iter().map(|&b| MbUnit(b as _)).collect()
+}
+
+#[test]
+fn test_locale() {
+    assert_eq!(Locale::new("no_such_locale.XYZ").err(), Some(LocaleError::Unavailable));
+    assert_eq!(Locale::new("C\0UTF-8").err(), Some(LocaleError::Unavailable));
+
+    let s = ZMbCString::new(&mb(b"gar\xc3\xa7on")).expect(here!());
+    let utf8 = Locale::new("C.UTF-8").expect(here!());
+
+    // Under the C locale, the string is not valid, unless the conversion is told otherwise.
+    assert!(set_locale("C"));
+    assert!(s.into_string().is_err());
+    assert_eq!(s.into_string_in_locale(&utf8).expect(here!()), "gar\u{e7}on");
+
+    // The thread's locale is put back afterward.
+    assert!(s.into_string().is_err());
+
+    // ... even if the conversion panics.
+    let r = ::std::panic::catch_unwind(|| utf8.with(|| panic!("oops")));
+    assert!(r.is_err());
+    assert!(s.into_string().is_err());
+
+    // A captured locale is unaffected by later changes.
+    assert!(set_locale("C.UTF-8"));
+    let captured = Locale::current().expect(here!());
+    assert!(set_locale("C"));
+    assert_eq!(s.into_string_in_locale(&captured).expect(here!()), "gar\u{e7}on");
+
+    let c = Locale::current().expect(here!());
+    assert!(set_locale("C.UTF-8"));
+    assert!(s.into_string_in_locale(&c).is_err());
+    assert_eq!(s.into_string().expect(here!()), "gar\u{e7}on");
+
+    // Locales nest.
+    utf8.with(|| c.with(|| assert!(s.into_string().is_err())));
+    c.with(|| utf8.with(|| assert_eq!(s.into_string().expect(here!()), "gar\u{e7}on")));
+}
@@ -0,0 +1,38 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use std::slice;
+use strffi::alloc::{Allocator, AllocError, Malloc};
+
+fn check_align(bytes: usize, align: usize) {
+    let ptr = Malloc::alloc_bytes(bytes, align).expect(here!());
+    assert_eq!(ptr as usize % align, 0, "{} bytes at align {}", bytes, align);
+    unsafe {
+        let s = slice::from_raw_parts_mut(ptr as *mut u8, bytes);
+        assert!(s.iter().all(|&b| b == 0));
+        for b in s.iter_mut() {
+            *b = 0xff;
+        }
+        Malloc::free(ptr, align);
+    }
+}
+
+#[test]
+fn test_malloc_align() {
+    for &align in &[1, 2, 4, 8, 16, 32, 64] {
+        for &bytes in &[0, 1, 7, 64, 1000] {
+            check_align(bytes, align);
+        }
+    }
+}
+
+#[test]
+fn test_malloc_align_not_power_of_two() {
+    assert_eq!(Malloc::alloc_bytes(16, 48), Err(AllocError::CannotAlign));
+}
+
+#[test]
+fn test_malloc_align_overflow() {
+    assert_eq!(Malloc::alloc_bytes(usize::max_value() - 8, 64), Err(AllocError::SizeOverflow));
+}
@@ -0,0 +1,34 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{Encoding, CheckedUnicode, MultiByte, Utf8, Utf8Unit, Utf16, Utf16Le, Utf32, Wide16, Wide32};
+use strffi::ffi::MB_LEN_MAX;
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::Slice;
+
+#[test]
+fn test_max_units_per_char() {
+    assert_eq!(<Utf8 as Encoding>::MAX_UNITS_PER_CHAR, 4);
+    assert_eq!(<Utf16 as Encoding>::MAX_UNITS_PER_CHAR, 2);
+    assert_eq!(<Wide16 as Encoding>::MAX_UNITS_PER_CHAR, 2);
+    assert_eq!(<Utf32 as Encoding>::MAX_UNITS_PER_CHAR, 1);
+    assert_eq!(<Wide32 as Encoding>::MAX_UNITS_PER_CHAR, 1);
+    assert_eq!(<CheckedUnicode as Encoding>::MAX_UNITS_PER_CHAR, 1);
+    assert_eq!(<MultiByte as Encoding>::MAX_UNITS_PER_CHAR, MB_LEN_MAX);
+}
+
+#[test]
+fn test_max_transcoded_len() {
+    let units: Vec<_> = "a😀".bytes().map(Utf8Unit).collect();
+    let s = SeStr::<Slice, Utf8>::new(&units);
+    assert_eq!(s.max_transcoded_len::<Utf16>(), Some(10));
+    assert_eq!(s.max_transcoded_len::<Utf32>(), Some(5));
+
+    let t = SeaString::<Slice, Utf16Le, Malloc>::from_str("a😀").expect(here!());
+    assert!(t.as_units().len() <= s.max_transcoded_len::<Utf16Le>().expect(here!()));
+
+    let empty = SeStr::<Slice, Utf8>::new(&[]);
+    assert_eq!(empty.max_transcoded_len::<MultiByte>(), Some(0));
+}
@@ -0,0 +1,58 @@
+/*!
+Checks that the ASCII fast path in multibyte decoding gives the same results as `mbrtowc`.
+
+The C runtime locale is process-wide, so this gets a test binary to itself, and does everything from a single test.
+*/
+#![cfg(target_os="linux")]
+extern crate libc;
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::encoding::{MultiByte, MbUnit, Wide, WUnit};
+use strffi::encoding::conv::mb_x_wc::MbsToWcError;
+use strffi::ffi::mb_is_ascii_transparent;
+use strffi::sea::SeStr;
+use strffi::structure::Slice;
+
+fn set_locale(name: &str) -> bool {
+    let name = format!("{}\0", name);
+    unsafe { !libc::setlocale(libc::LC_ALL, name.as_ptr() as *const _).is_null() }
+}
+
+fn to_wide(bytes: &[u8]) -> Vec<Result<WUnit, MbsToWcError>> {
+    let units: Vec<_> = bytes.iter().map(|&b| MbUnit(b as _)).collect();
+    SeStr::<Slice, MultiByte>::new(&units).transcode_to_iter::<Wide>().collect()
+}
+
+fn wide(s: &str) -> Vec<Result<WUnit, MbsToWcError>> {
+    s.chars().map(|c| Ok(WUnit(c as _))).collect()
+}
+
+#[test]
+fn test_mb_ascii() {
+    assert!(set_locale("C.UTF-8"));
+    assert!(mb_is_ascii_transparent());
+
+    assert_eq!(to_wide(b"plain ascii\0text"), wide("plain ascii\0text"));
+    assert_eq!(to_wide(b"g\xc2\xaar\xc3\xa7on!"), wide("gªrçon!"));
+
+    // Errors, and recovery from them, are unaffected.
+    let mut expected = wide("a");
+    expected.push(Err(MbsToWcError::InvalidAt(1)));
+    expected.extend(wide("b"));
+    expected.push(Err(MbsToWcError::InvalidAt(3)));
+    expected.extend(wide("c"));
+    assert_eq!(to_wide(b"a\xffb\xc3c"), expected);
+
+    // In a shift-based encoding, ASCII units after a shift sequence are *not* ASCII characters.
+    let iso2022 = ["ja_JP.ISO-2022-JP", "ja_JP.iso2022jp", "ko_KR.ISO-2022-KR"];
+    if iso2022.iter().any(|name| set_locale(name)) {
+        assert!(!mb_is_ascii_transparent());
+        // "日本" in ISO-2022-JP.
+        let r: Result<String, _> = SeStr::<Slice, MultiByte>::from_bytes(b"a\x1b$BF|K\\\x1b(Bb").into_string();
+        assert_eq!(r.expect(here!()), "a日本b");
+    }
+
+    assert!(set_locale("C.UTF-8"));
+}
@@ -0,0 +1,104 @@
+/*!
+Checks that the bulk `mbsrtowcs` and `wcsrtombs` conversions give the same results as the transcoding iterators.
+
+The C runtime locale is process-wide, so this gets a test binary to itself, and does everything from a single test.
+*/
+#![cfg(target_os="linux")]
+extern crate libc;
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{MultiByte, Wide, WUnit};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+
+fn set_locale(name: &str) -> bool {
+    let name = format!("{}\0", name);
+    unsafe { !libc::setlocale(libc::LC_ALL, name.as_ptr() as *const _).is_null() }
+}
+
+fn zmb(bytes: &[u8]) -> SeaString<ZeroTerm, MultiByte, Malloc> {
+    SeaString::new(SeStr::<Slice, MultiByte>::from_bytes(bytes).as_units()).expect(here!())
+}
+
+fn zw(units: &[u32]) -> SeaString<ZeroTerm, Wide, Malloc> {
+    let units: Vec<_> = units.iter().map(|&u| WUnit(u as _)).collect();
+    SeaString::new(&units).expect(here!())
+}
+
+fn check_mb(bytes: &[u8]) {
+    let s = zmb(bytes);
+
+    let bulk = s.into_string_bulk().map_err(|e| e.to_string());
+    let iter = s.into_string().map_err(|e| e.to_string());
+    assert_eq!(bulk, iter, "{:?}", bytes);
+
+    // `transcode_to` takes the bulk path for zero-terminated strings, but a slice has to go through the iterators.
+    let unterminated = SeStr::<Slice, MultiByte>::new(s.as_units());
+
+    let bulk: Result<SeaString<ZeroTerm, Wide, Malloc>, _> = s.transcode_to_wide_bulk();
+    let iter: Result<SeaString<ZeroTerm, Wide, Malloc>, _> = unterminated.transcode_to();
+    assert_eq!(bulk.map_err(|e| e.to_string()), iter.map_err(|e| e.to_string()), "{:?}", bytes);
+
+    let bulk: Result<SeaString<Slice, Wide, Malloc>, _> = s.transcode_to();
+    let iter: Result<SeaString<Slice, Wide, Malloc>, _> = unterminated.transcode_to();
+    assert_eq!(bulk.map_err(|e| e.to_string()), iter.map_err(|e| e.to_string()), "{:?}", bytes);
+}
+
+fn check_wide(units: &[u32]) {
+    let s = zw(units);
+    let bulk: Result<SeaString<ZeroTerm, MultiByte, Malloc>, _> = s.transcode_to_multibyte_bulk();
+    let iter: Result<SeaString<ZeroTerm, MultiByte, Malloc>, _> = s.transcode_to();
+    assert_eq!(bulk.map_err(|e| e.to_string()), iter.map_err(|e| e.to_string()), "{:?}", units);
+}
+
+#[test]
+fn test_mb_bulk() {
+    assert!(set_locale("C.UTF-8"));
+
+    let mbs: &[&[u8]] = &[
+        b"",
+        b"plain ascii",
+        b"g\xc2\xaar\xc3\xa7on",
+        "日本語 and \u{1f600}".as_bytes(),
+        // Invalid and truncated sequences, which must fall back to report exact offsets.
+        b"ab\xffcd",
+        b"\xc3(",
+        b"abc\xe6\x97",
+    ];
+    for bytes in mbs {
+        check_mb(bytes);
+    }
+
+    let s = zmb("gªrçon 日本".as_bytes());
+    assert_eq!(s.into_string_bulk().expect(here!()), "gªrçon 日本");
+    assert!(zmb(b"ab\xffcd").into_string_bulk().is_err());
+
+    let wides: &[&[u32]] = &[
+        &[],
+        &[0x61, 0x62],
+        &[0x67, 0xaa, 0x72, 0xe7, 0x6f, 0x6e, 0x65e5, 0x1f600],
+        // Not a character, so it cannot be encoded.
+        &[0x61, 0xdc00, 0x62],
+    ];
+    for units in wides {
+        check_wide(units);
+    }
+
+    let s = zw(&[0x65e5, 0x61]);
+    let r: SeaString<ZeroTerm, MultiByte, Malloc> = s.transcode_to_multibyte_bulk().expect(here!());
+    assert_eq!(r.as_units(), &SeStr::<Slice, MultiByte>::from_bytes("日a".as_bytes()).as_units()[..]);
+
+    // A large string, so that the output buffers are well exercised.
+    let big = "garçon, naïve, 日本語, \u{1f600}; ".repeat(4096);
+    let s = zmb(big.as_bytes());
+    assert_eq!(s.into_string_bulk().expect(here!()), big);
+    let ws: Vec<u32> = big.chars().map(|c| c as u32).collect();
+    let r: SeaString<Slice, Wide, Malloc> = s.transcode_to().expect(here!());
+    assert_eq!(r.as_units(), zw(&ws).as_units());
+    let r: SeaString<ZeroTerm, MultiByte, Malloc> = zw(&ws).transcode_to_multibyte_bulk().expect(here!());
+    assert_eq!(r.as_units(), &SeStr::<Slice, MultiByte>::from_bytes(big.as_bytes()).as_units()[..]);
+
+}
@@ -0,0 +1,66 @@
+/*!
+Checks that multibyte conversion leaves the shift state clean at the end of the input.
+
+The C runtime locale is process-wide, so this gets a test binary to itself, and does everything from a single test.
+*/
+#![cfg(target_os="linux")]
+extern crate libc;
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::encoding::{MultiByte, MbUnit, Wide, WUnit};
+use strffi::encoding::conv::mb_x_wc::MbsToWcError;
+use strffi::sea::SeStr;
+use strffi::structure::Slice;
+
+fn set_locale(name: &str) -> bool {
+    let name = format!("{}\0", name);
+    unsafe { !libc::setlocale(libc::LC_ALL, name.as_ptr() as *const _).is_null() }
+}
+
+fn mb(bytes: &[u8]) -> Vec<MbUnit> {
+    bytes.iter().map(|&b| MbUnit(b as _)).collect()
+}
+
+fn wide(s: &str) -> Vec<WUnit> {
+    s.chars().map(|c| WUnit(c as _)).collect()
+}
+
+#[test]
+fn test_mb_shift_state() {
+    assert!(set_locale("C.UTF-8"));
+
+    // An incomplete sequence at the end is reported once, after which the iterator stays finished.
+    let units = mb(b"a\xe6\x97");
+    let s = SeStr::<Slice, MultiByte>::new(&units);
+    let mut it = s.transcode_to_iter::<Wide>();
+    assert_eq!(it.next(), Some(Ok(WUnit('a' as _))));
+    assert_eq!(it.next(), Some(Err(MbsToWcError::Incomplete)));
+    assert_eq!(it.next(), None);
+    assert_eq!(it.next(), None);
+
+    // Nothing is carried over into the next conversion.
+    let units = mb(b"\xa5b");
+    let r: Result<Vec<_>, _> = SeStr::<Slice, MultiByte>::new(&units).transcode_to_iter::<Wide>().collect();
+    assert_eq!(r, Err(MbsToWcError::InvalidAt(0)));
+    let r: Result<String, _> = SeStr::<Slice, MultiByte>::from_bytes(b"\xe6\x97\xa5").into_string();
+    assert_eq!(r.expect(here!()), "日");
+
+    let iso2022 = ["ja_JP.ISO-2022-JP", "ja_JP.iso2022jp"];
+    if iso2022.iter().any(|name| set_locale(name)) {
+        // Encoding must shift back to ASCII before the end.
+        let ws = wide("a日");
+        let r: Result<Vec<_>, _> = SeStr::<Slice, Wide>::new(&ws).transcode_to_iter::<MultiByte>().collect();
+        let bytes: Vec<u8> = r.expect(here!()).into_iter().map(|u| u.0 as u8).collect();
+        assert_eq!(bytes, b"a\x1b$BF|\x1b(B");
+
+        // Ending in the middle of a double-byte character must not leave the next conversion shifted.
+        let r: Result<String, _> = SeStr::<Slice, MultiByte>::from_bytes(b"\x1b$BF").into_string();
+        assert!(r.is_err());
+        let r: Result<String, _> = SeStr::<Slice, MultiByte>::from_bytes(b"ab").into_string();
+        assert_eq!(r.expect(here!()), "ab");
+    }
+
+    assert!(set_locale("C.UTF-8"));
+}
@@ -0,0 +1,69 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{Utf16, Utf16Unit, Utf32, Utf32Unit};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+
+const WORD: &'static [u16] = &[0x68, 0xe9, 0x6c, 0x6c, 0x6f, 0x00];
+
+/**
+Returns a buffer containing `WORD`, starting at an odd address, along with the offset of the first unit.
+*/
+fn misaligned_word() -> (Vec<u16>, usize) {
+    let mut buf = vec![0u16; WORD.len() + 1];
+    {
+        let bytes = unsafe {
+            ::std::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut u8, buf.len() * 2)
+        };
+        for (i, &u) in WORD.iter().enumerate() {
+            let b: [u8; 2] = unsafe { ::std::mem::transmute(u) };
+            bytes[1 + i*2] = b[0];
+            bytes[2 + i*2] = b[1];
+        }
+    }
+    (buf, 1)
+}
+
+#[test]
+fn test_from_ptr_misaligned_slice() {
+    let (buf, off) = misaligned_word();
+    let ptr = unsafe { (buf.as_ptr() as *const u8).offset(off as isize) as *const u16 };
+    assert_eq!(ptr as usize % 2, 1);
+
+    let r = unsafe { SeStr::<Slice, Utf16>::from_ptr((ptr, WORD.len() - 1)) };
+    assert!(r.is_none());
+
+    let units: Vec<_> = unsafe { Slice::iter_unaligned::<Utf16>(ptr, WORD.len() - 1) }.collect();
+    let expected: Vec<_> = WORD[..WORD.len() - 1].iter().map(|&u| Utf16Unit(u)).collect();
+    assert_eq!(units, expected);
+}
+
+#[test]
+fn test_from_ptr_misaligned_zero_term() {
+    let (buf, off) = misaligned_word();
+    let ptr = unsafe { (buf.as_ptr() as *const u8).offset(off as isize) as *const u16 };
+
+    let r = unsafe { SeStr::<ZeroTerm, Utf16>::from_ptr(ptr) };
+    assert!(r.is_none());
+
+    let owned: SeaString<ZeroTerm, Utf16, Malloc> = unsafe { ZeroTerm::iter_unaligned::<Utf16>(ptr) }.collect();
+    let expected: Vec<_> = WORD[..WORD.len() - 1].iter().map(|&u| Utf16Unit(u)).collect();
+    assert_eq!(owned.as_units(), &expected[..]);
+}
+
+#[test]
+fn test_from_ptr_aligned() {
+    let r = unsafe { SeStr::<ZeroTerm, Utf16>::from_ptr(WORD.as_ptr()) };
+    assert_eq!(r.expect(here!()).as_units().len(), WORD.len() - 1);
+
+    let words: &[u32] = &[1, 2, 3];
+    for off in 1..4 {
+        let ptr = unsafe { (words.as_ptr() as *const u8).offset(off) as *const u32 };
+        assert!(unsafe { SeStr::<Slice, Utf32>::from_ptr((ptr, 1)) }.is_none());
+    }
+    let s = unsafe { SeStr::<Slice, Utf32>::from_ptr((words.as_ptr(), 3)).expect(here!()) };
+    assert_eq!(s.as_units(), &[Utf32Unit(1), Utf32Unit(2), Utf32Unit(3)][..]);
+}
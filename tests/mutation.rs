@@ -0,0 +1,20 @@
+extern crate strffi;
+
+use strffi::encoding::{Utf8, Utf8Unit};
+use strffi::sea::SeStr;
+use strffi::structure::Slice;
+
+#[test]
+fn test_slice_as_units_mut() {
+    let mut units: Vec<_> = b"garcon".iter().map(|&b| Utf8Unit(b)).collect();
+    {
+        let s = SeStr::<Slice, Utf8>::new_mut(&mut units);
+        s.as_units_mut()[0] = Utf8Unit(b'G');
+
+        // Zero units are just content in a slice.
+        s.as_units_mut()[3] = Utf8Unit(0);
+        assert_eq!(s.as_units().len(), 6);
+    }
+    let bytes: Vec<_> = units.iter().map(|u| u.0).collect();
+    assert_eq!(bytes, b"Gar\0on");
+}
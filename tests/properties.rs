@@ -0,0 +1,75 @@
+#![cfg(feature="proptest")]
+#[macro_use] extern crate proptest;
+extern crate strffi;
+
+use proptest::prelude::*;
+use strffi::alloc::{Global, Malloc};
+use strffi::encoding::{Utf8, Utf16, Wide, WinUnicode};
+use strffi::sea::SeaString;
+use strffi::strategy::*;
+use strffi::structure::{Slice, ZeroTerm};
+
+proptest! {
+    #[test]
+    fn prop_valid_utf8_is_valid(units in valid_utf8_units()) {
+        let bytes: Vec<u8> = units.iter().map(|u| u.0).collect();
+        prop_assert!(String::from_utf8(bytes).is_ok());
+    }
+
+    #[test]
+    fn prop_invalid_utf8_is_invalid(units in invalid_utf8_units()) {
+        let bytes: Vec<u8> = units.iter().map(|u| u.0).collect();
+        prop_assert!(String::from_utf8(bytes).is_err());
+    }
+
+    #[test]
+    fn prop_valid_utf16_is_valid(units in valid_utf16_units()) {
+        let words: Vec<u16> = units.iter().map(|u| u.0).collect();
+        prop_assert!(String::from_utf16(&words).is_ok());
+    }
+
+    #[test]
+    fn prop_invalid_utf16_is_invalid(units in invalid_utf16_units()) {
+        let words: Vec<u16> = units.iter().map(|u| u.0).collect();
+        prop_assert!(String::from_utf16(&words).is_err());
+    }
+
+    #[test]
+    fn prop_zero_term_has_no_interior_zeroes(s in zero_term_sea_string::<Utf8, Global, _>(invalid_utf8_units())) {
+        prop_assert!(s.as_units().iter().all(|u| u.0 != 0));
+        prop_assert_eq!(s.as_units_with_term().last().map(|u| u.0), Some(0));
+    }
+
+    #[test]
+    fn prop_slice_ptr_round_trip(s in sea_string::<Slice, Utf16, Global, _>(invalid_utf16_units())) {
+        let expected = s.clone();
+        let ptr = s.into_ptr();
+        let s = unsafe { SeaString::<Slice, Utf16, Global>::from_ptr(ptr).unwrap() };
+        prop_assert_eq!(s, expected);
+    }
+
+    #[test]
+    fn prop_zero_term_ptr_round_trip(s in zero_term_sea_string::<Utf8, Malloc, _>(valid_utf8_units())) {
+        let expected = s.clone();
+        let ptr = s.into_ptr();
+        let s = unsafe { SeaString::<ZeroTerm, Utf8, Malloc>::from_ptr(ptr).unwrap() };
+        prop_assert_eq!(s, expected);
+    }
+
+    #[test]
+    fn prop_debug_is_stable(s in sea_string::<Slice, Utf8, Global, _>(invalid_utf8_units())) {
+        let first = format!("{:?}", s);
+        prop_assert_eq!(&first, &format!("{:?}", s));
+        prop_assert_eq!(&first, &format!("{:?}", s.clone()));
+        prop_assert_eq!(&first[first.len()-1..], "\"");
+    }
+
+    #[test]
+    fn prop_wide_round_trip(s in "[^\u{0}]*") {
+        let zw = SeaString::<ZeroTerm, Wide, Global>::from_str(&s).unwrap();
+        let zww: SeaString<ZeroTerm, WinUnicode, Global> = zw.transcode_to().unwrap();
+        let zw2: SeaString<ZeroTerm, Wide, Global> = zww.transcode_to().unwrap();
+        prop_assert_eq!(&zw, &zw2);
+        prop_assert_eq!(zw2.into_string().unwrap(), s);
+    }
+}
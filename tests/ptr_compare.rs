@@ -0,0 +1,70 @@
+extern crate strffi;
+
+use std::ptr;
+use strffi::{ZMbStr, ZWStr};
+use strffi::encoding::{MbUnit, WUnit};
+
+fn mb(bytes: &[u8]) -> Vec<MbUnit> {
+    bytes.iter().map(|&b| MbUnit(b as _)).collect()
+}
+
+fn wide(s: &str) -> Vec<WUnit> {
+    s.chars().map(|c| WUnit(c as _)).collect()
+}
+
+#[test]
+fn test_mb_ptr_eq_units() {
+    let foreign = b"garcon\0";
+    let ptr = foreign.as_ptr() as *const _;
+    unsafe {
+        assert!(ZMbStr::ptr_eq_units(ptr, &mb(b"garcon")));
+        assert!(!ZMbStr::ptr_eq_units(ptr, &mb(b"gar")));
+        assert!(!ZMbStr::ptr_eq_units(ptr, &mb(b"garcons")));
+        assert!(!ZMbStr::ptr_eq_units(ptr, &mb(b"garcon\0")));
+        assert!(!ZMbStr::ptr_eq_units(ptr, &mb(b"")));
+        assert!(ZMbStr::ptr_eq_units(b"\0".as_ptr() as *const _, &mb(b"")));
+        assert!(!ZMbStr::ptr_eq_units(ptr::null(), &mb(b"")));
+        assert!(!ZMbStr::ptr_eq_units(ptr::null(), &mb(b"garcon")));
+    }
+}
+
+#[test]
+fn test_mb_ptr_starts_with() {
+    let foreign = b"garcon\0";
+    let ptr = foreign.as_ptr() as *const _;
+    unsafe {
+        assert!(ZMbStr::ptr_starts_with(ptr, &mb(b"gar")));
+        assert!(ZMbStr::ptr_starts_with(ptr, &mb(b"garcon")));
+        assert!(ZMbStr::ptr_starts_with(ptr, &mb(b"")));
+        assert!(!ZMbStr::ptr_starts_with(ptr, &mb(b"garcons")));
+        assert!(!ZMbStr::ptr_starts_with(ptr, &mb(b"garcon\0")));
+        assert!(!ZMbStr::ptr_starts_with(ptr, &mb(b"gaz")));
+        assert!(!ZMbStr::ptr_starts_with(ptr::null(), &mb(b"")));
+    }
+}
+
+#[test]
+fn test_bounded_reads() {
+    // Nothing past the first difference, or the unit after `expected`, may be read.
+    let foreign = b"ab";
+    let ptr = foreign.as_ptr() as *const _;
+    unsafe {
+        assert!(!ZMbStr::ptr_eq_units(ptr, &mb(b"x")));
+        assert!(!ZMbStr::ptr_eq_units(ptr, &mb(b"a")));
+        assert!(ZMbStr::ptr_starts_with(ptr, &mb(b"ab")));
+    }
+}
+
+#[test]
+fn test_wide_ptr() {
+    let mut foreign = wide("gar\u{e7}on");
+    foreign.push(WUnit(0));
+    let ptr = foreign.as_ptr() as *const _;
+    unsafe {
+        assert!(ZWStr::ptr_eq_units(ptr, &wide("gar\u{e7}on")));
+        assert!(!ZWStr::ptr_eq_units(ptr, &wide("gar\u{e7}o")));
+        assert!(ZWStr::ptr_starts_with(ptr, &wide("gar\u{e7}")));
+        assert!(!ZWStr::ptr_starts_with(ptr, &wide("garcon")));
+        assert!(!ZWStr::ptr_eq_units(ptr::null(), &wide("")));
+    }
+}
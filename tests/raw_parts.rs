@@ -0,0 +1,45 @@
+extern crate libc;
+extern crate strffi;
+
+mod common;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::{Malloc, Rust};
+use strffi::encoding::{Utf8, Utf8Unit, Utf16};
+use strffi::sea::SeaString;
+use strffi::structure::Slice;
+use common::utf8;
+
+#[test]
+fn test_raw_parts_round_trip() {
+    let s = SeaString::<Slice, Utf8, Malloc>::new(&utf8(b"garcon")).expect(here!());
+    let units = s.as_units().as_ptr();
+    let (ptr, len) = s.into_raw_parts();
+    assert_eq!(ptr as *const Utf8Unit, units);
+    assert_eq!(len, 6);
+
+    let s = unsafe { SeaString::<Slice, Utf8, Malloc>::from_raw_parts(ptr, len) };
+    assert_eq!(s.as_units(), &utf8(b"garcon")[..]);
+
+    let s = SeaString::<Slice, Utf16, Rust>::from_str("gªrçon").expect(here!());
+    let (ptr, len) = s.into_raw_parts();
+    let s = unsafe { SeaString::<Slice, Utf16, Rust>::from_raw_parts(ptr, len) };
+    assert_eq!(s.into_string().expect(here!()), "gªrçon");
+}
+
+#[test]
+fn test_raw_parts_foreign_free() {
+    // `Malloc` strings can be freed by foreign code with `free`.
+    let mut s = SeaString::<Slice, Utf8, Malloc>::with_capacity(32).expect(here!());
+    s.push_units(&utf8(b"garcon")).expect(here!());
+    let (ptr, len) = s.into_raw_parts();
+    assert_eq!(len, 6);
+    unsafe { libc::free(ptr as *mut _) };
+}
+
+#[test]
+#[should_panic]
+fn test_raw_parts_null() {
+    let _ = unsafe { SeaString::<Slice, Utf8, Malloc>::from_raw_parts(std::ptr::null_mut(), 0) };
+}
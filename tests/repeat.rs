@@ -0,0 +1,56 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::{AllocError, Malloc, Rust};
+use strffi::encoding::{Utf8, Utf8Unit, Utf16, Utf16Unit, Utf32, Utf32Unit};
+use strffi::sea::SeaString;
+use strffi::structure::{Slice, ZeroTerm};
+
+const SPACE: Utf8Unit = Utf8Unit(b' ');
+
+#[test]
+fn test_repeat() {
+    let s = SeaString::<ZeroTerm, Utf8, Malloc>::repeat(SPACE, 4).expect(here!());
+    assert_eq!(s.as_units_with_term(), &[SPACE, SPACE, SPACE, SPACE, Utf8Unit(0)][..]);
+
+    let s = SeaString::<Slice, Utf16, Rust>::repeat(Utf16Unit(0x2d), 3).expect(here!());
+    assert_eq!(s.into_string().expect(here!()), "---");
+
+    let s = SeaString::<Slice, Utf8, Malloc>::repeat(SPACE, 5).expect(here!());
+    assert_eq!(s.as_units(), &[SPACE; 5][..]);
+    assert_eq!(s.capacity(), 5);
+}
+
+#[test]
+fn test_repeat_empty() {
+    let s = SeaString::<ZeroTerm, Utf8, Malloc>::repeat(SPACE, 0).expect(here!());
+    assert_eq!(s.as_units(), &[][..]);
+    assert_eq!(s.as_units_with_term(), &[Utf8Unit(0)][..]);
+
+    let s = SeaString::<Slice, Utf8, Malloc>::repeat(SPACE, 0).expect(here!());
+    assert_eq!(s.as_units(), &[][..]);
+}
+
+#[test]
+fn test_repeat_zero() {
+    let s = SeaString::<Slice, Utf8, Malloc>::repeat(Utf8Unit(0), 2).expect(here!());
+    assert_eq!(s.as_units(), &[Utf8Unit(0), Utf8Unit(0)][..]);
+
+    assert_eq!(SeaString::<ZeroTerm, Utf8, Malloc>::repeat(Utf8Unit(0), 2).err(), Some(AllocError::InteriorZeroAt(0)));
+
+    // A single zero is just the terminator.
+    let s = SeaString::<ZeroTerm, Utf8, Malloc>::repeat(Utf8Unit(0), 1).expect(here!());
+    assert!(s.is_empty());
+
+    // Refused before anything is allocated, however many there are.
+    assert_eq!(SeaString::<ZeroTerm, Utf8, Malloc>::repeat(Utf8Unit(0), usize::max_value()).err(), Some(AllocError::InteriorZeroAt(0)));
+}
+
+#[test]
+fn test_repeat_overflow() {
+    let huge = usize::max_value() / 2;
+    assert_eq!(SeaString::<ZeroTerm, Utf32, Malloc>::repeat(Utf32Unit(0x20), huge).err(), Some(AllocError::SizeOverflow));
+    assert_eq!(SeaString::<Slice, Utf32, Rust>::repeat(Utf32Unit(0x20), huge).err(), Some(AllocError::SizeOverflow));
+    assert_eq!(SeaString::<ZeroTerm, Utf8, Malloc>::repeat(SPACE, usize::max_value()).err(), Some(AllocError::SizeOverflow));
+}
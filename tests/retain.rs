@@ -0,0 +1,54 @@
+extern crate strffi;
+
+mod common;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::{Global, Malloc, Rust};
+use strffi::encoding::{Utf8, Utf8Unit, Wide, WUnit};
+use strffi::sea::SeaString;
+use strffi::structure::Slice;
+use common::utf8;
+
+#[test]
+fn test_retain() {
+    let mut s = SeaString::<Slice, Utf8, Malloc>::new(&utf8(b"g-a-r-c-o-n")).expect(here!());
+    s.retain(|u| u != Utf8Unit(b'-'));
+    assert_eq!(s.as_units(), &utf8(b"garcon")[..]);
+
+    // Nothing to remove.
+    s.retain(|_| true);
+    assert_eq!(s.as_units(), &utf8(b"garcon")[..]);
+
+    // The shortened string must still behave normally.
+    let t = s.clone();
+    s.push_units(&utf8(b"s")).expect(here!());
+    assert_eq!(s.as_units(), &utf8(b"garcons")[..]);
+    assert_eq!(t.as_units(), &utf8(b"garcon")[..]);
+}
+
+#[test]
+fn test_retain_all_removed() {
+    let mut s = SeaString::<Slice, Utf8, Rust>::new(&utf8(b"garcon")).expect(here!());
+    s.retain(|_| false);
+    assert_eq!(s.as_units(), &[][..]);
+    s.retain(|_| false);
+    assert_eq!(s.as_units(), &[][..]);
+
+    let units: Vec<_> = "garçon".chars().map(|c| WUnit(c as u32 as _)).collect();
+    let mut s = SeaString::<Slice, Wide, Global>::new(&units).expect(here!());
+    s.retain(|_| false);
+    assert_eq!(s.as_units(), &[][..]);
+    s.push_units(&units).expect(here!());
+    assert_eq!(s.as_units(), &units[..]);
+}
+
+#[test]
+fn test_replace_unit() {
+    let mut s = SeaString::<Slice, Utf8, Malloc>::new(&utf8(b"g\0r\0on")).expect(here!());
+    s.replace_unit(Utf8Unit(0), Utf8Unit(b'?'));
+    assert_eq!(s.as_units(), &utf8(b"g?r?on")[..]);
+
+    s.replace_unit(Utf8Unit(b'x'), Utf8Unit(b'y'));
+    assert_eq!(s.as_units(), &utf8(b"g?r?on")[..]);
+}
@@ -0,0 +1,213 @@
+/*!
+Checks that transcoding from one encoding to another and back again reproduces the original units, for every pair of encodings with conversions in both directions.
+*/
+extern crate libc;
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use std::fmt::Debug;
+use std::iter::Cloned;
+use std::slice;
+use std::str::Chars;
+use std::vec;
+use strffi::encoding::{Encoding, TranscodeTo, UnitIter, CheckedUnicode, CheckedUtf8, JniMtf8,
+    Utf8, Utf8Unit, Utf16, Utf16Be, Utf16Le, Utf16Unit, Utf32, Utf32Be, Utf32Le, Utf32Unit,
+    Wide16, Wide32, WinUnicode, Wtf8};
+
+/**
+Strings covering the boundaries between sequence lengths in each encoding, the edges of the surrogate range, the largest code point, and embedded zeroes.
+*/
+const SAMPLES: &'static [&'static str] = &[
+    "",
+    "a",
+    "\0",
+    "a\0b",
+    "\u{7f}\u{80}",
+    "\u{7ff}\u{800}",
+    "\u{d7ff}\u{e000}",
+    "\u{fffd}\u{ffff}",
+    "\u{10000}",
+    "\u{10ffff}",
+    "gªrçon 😀\0tail",
+];
+
+/**
+Encodes `s` in `E`.
+*/
+fn units_of<E>(s: &str) -> Vec<E::Unit>
+where
+    E: Encoding,
+    for<'a> UnitIter<CheckedUnicode, Chars<'a>>: TranscodeTo<E>,
+{
+    UnitIter::<CheckedUnicode, _>::new(s.chars()).transcode()
+        .map(|r| r.expect(here!()))
+        .collect()
+}
+
+/**
+Returns the offset of the first unit at which `a` and `b` differ, if any.
+*/
+fn first_difference<U>(a: &[U], b: &[U]) -> Option<usize> where U: PartialEq {
+    match a.iter().zip(b).position(|(a, b)| a != b) {
+        Some(at) => Some(at),
+        None if a.len() != b.len() => Some(a.len().min(b.len())),
+        None => None,
+    }
+}
+
+/**
+Transcodes `units` from `E` to `F` and back again, and panics if the result differs from `units`.
+*/
+fn assert_roundtrip<E, F>(units: &[E::Unit])
+where
+    E: Encoding,
+    F: Encoding,
+    E::Unit: Debug,
+    for<'a> UnitIter<E, Cloned<slice::Iter<'a, E::Unit>>>: TranscodeTo<F>,
+    UnitIter<F, vec::IntoIter<F::Unit>>: TranscodeTo<E>,
+{
+    let (e, f) = (E::debug_prefix(), F::debug_prefix());
+
+    let there: Vec<F::Unit> = UnitIter::<E, _>::new(units.iter().cloned()).transcode()
+        .collect::<Result<_, _>>()
+        .unwrap_or_else(|err| panic!("{} -> {} failed for {:?}: {}", e, f, units, err));
+
+    let back: Vec<E::Unit> = UnitIter::<F, _>::new(there.into_iter()).transcode()
+        .collect::<Result<_, _>>()
+        .unwrap_or_else(|err| panic!("{} -> {} -> {} failed for {:?}: {}", e, f, e, units, err));
+
+    if let Some(at) = first_difference(units, &back) {
+        panic!("{} -> {} -> {} differs at offset {}: expected {:?}, got {:?}",
+            e, f, e, at, &units[at..], &back[at..]);
+    }
+}
+
+/*
+Each pair is checked in both directions, starting from the same text.
+*/
+macro_rules! check_pairs {
+    ($($a:ident <-> $b:ident),* $(,)*) => {
+        for s in SAMPLES {
+            $(
+                assert_roundtrip::<$a, $b>(&units_of::<$a>(s));
+                assert_roundtrip::<$b, $a>(&units_of::<$b>(s));
+            )*
+        }
+    };
+}
+
+#[test]
+fn test_roundtrip_utf8() {
+    check_pairs! {
+        CheckedUtf8 <-> CheckedUnicode,
+        Utf8 <-> CheckedUnicode,
+        Utf8 <-> Utf16,
+        Utf8 <-> JniMtf8,
+        JniMtf8 <-> CheckedUnicode,
+        Wtf8 <-> CheckedUnicode,
+        Wtf8 <-> Utf16,
+        Wtf8 <-> Wide16,
+        Wtf8 <-> WinUnicode,
+    }
+}
+
+#[test]
+fn test_roundtrip_utf16() {
+    check_pairs! {
+        Utf16 <-> CheckedUnicode,
+        Utf16Le <-> CheckedUnicode,
+        Utf16Be <-> CheckedUnicode,
+        Utf16 <-> Utf16Le,
+        Utf16 <-> Utf16Be,
+        WinUnicode <-> CheckedUnicode,
+    }
+}
+
+#[test]
+fn test_roundtrip_utf32() {
+    check_pairs! {
+        Utf32 <-> CheckedUnicode,
+        Utf32Le <-> CheckedUnicode,
+        Utf32Be <-> CheckedUnicode,
+        Utf32 <-> Utf32Le,
+        Utf32 <-> Utf32Be,
+    }
+}
+
+#[test]
+fn test_roundtrip_wide() {
+    check_pairs! {
+        Wide16 <-> CheckedUnicode,
+        Wide32 <-> CheckedUnicode,
+        Wide16 <-> WinUnicode,
+        Wide32 <-> WinUnicode,
+    }
+}
+
+#[test]
+fn test_roundtrip_identity() {
+    check_pairs! {
+        Utf8 <-> Utf8,
+        CheckedUnicode <-> CheckedUnicode,
+    }
+
+    // Identity conversions don't care whether the units are valid.
+    assert_roundtrip::<Utf8, Utf8>(&[Utf8Unit(b'a'), Utf8Unit(0xff), Utf8Unit(0xc0)]);
+}
+
+#[test]
+fn test_roundtrip_byte_order_invalid() {
+    // Byte-swapping conversions don't validate, so they also preserve invalid units.
+    let units = [Utf16Unit(0x61), Utf16Unit(0xdc00), Utf16Unit(0xd800)];
+    assert_roundtrip::<Utf16, Utf16Le>(&units);
+    assert_roundtrip::<Utf16, Utf16Be>(&units);
+
+    let units = [Utf32Unit(0x61), Utf32Unit(0xd800), Utf32Unit(0x110000)];
+    assert_roundtrip::<Utf32, Utf32Le>(&units);
+    assert_roundtrip::<Utf32, Utf32Be>(&units);
+}
+
+#[test]
+fn test_roundtrip_wtf8_unpaired() {
+    // WTF-8 exists to preserve unpaired surrogates.
+    let units = [Utf16Unit(0x61), Utf16Unit(0xdc00), Utf16Unit(0xd800), Utf16Unit(0xd83d), Utf16Unit(0xde00), Utf16Unit(0xd800)];
+    assert_roundtrip::<Utf16, Wtf8>(&units);
+}
+
+#[test]
+fn test_first_difference() {
+    assert_eq!(first_difference(&[1, 2, 3], &[1, 2, 3]), None);
+    assert_eq!(first_difference(&[1, 2, 3], &[1, 5, 3]), Some(1));
+    assert_eq!(first_difference(&[1, 2, 3], &[1, 2]), Some(2));
+    assert_eq!(first_difference::<u8>(&[], &[]), None);
+}
+
+#[cfg(target_os="linux")]
+mod multibyte {
+    use libc;
+    use strffi::encoding::{CheckedUnicode, Char16, Char32, MultiByte, Wide};
+    use super::{SAMPLES, assert_roundtrip, units_of};
+
+    /*
+    The multi-byte conversions depend on the C runtime locale, which is process-wide, so these are all checked from a single test.
+    */
+    #[test]
+    fn test_roundtrip_multibyte() {
+        unsafe {
+            let r = libc::setlocale(libc::LC_ALL, b"C.UTF-8\0".as_ptr() as *const _);
+            assert!(!r.is_null());
+        }
+
+        check_pairs! {
+            MultiByte <-> CheckedUnicode,
+            MultiByte <-> Wide,
+        }
+
+        // There is no direct conversion from `CheckedUnicode` to `Char16` or `Char32`, so these only start from `MultiByte`.
+        for s in SAMPLES {
+            assert_roundtrip::<MultiByte, Char16>(&units_of::<MultiByte>(s));
+            assert_roundtrip::<MultiByte, Char32>(&units_of::<MultiByte>(s));
+        }
+    }
+}
@@ -0,0 +1,207 @@
+extern crate strffi;
+
+mod common;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::encoding::{MbUnit, MultiByte, Utf8, Utf8Unit, Utf16, Utf16Unit};
+use strffi::sea::SeStr;
+use strffi::structure::{Slice, ZeroTerm};
+use common::utf8;
+
+fn utf8_z(s: &str) -> Vec<Utf8Unit> {
+    s.bytes().chain(Some(0)).map(Utf8Unit).collect()
+}
+
+#[test]
+fn test_starts_ends_with() {
+    let hay = utf8("key=value");
+    let hay = SeStr::<Slice, Utf8>::new(&hay);
+
+    let key = utf8_z("key");
+    let key = unsafe { SeStr::<ZeroTerm, Utf8>::from_ptr(key.as_ptr() as *const _).expect(here!()) };
+    let value = utf8("value");
+    let value = SeStr::<Slice, Utf8>::new(&value);
+
+    assert!(hay.starts_with(key));
+    assert!(!hay.starts_with(value));
+    assert!(hay.ends_with(value));
+    assert!(!hay.ends_with(key));
+    assert!(hay.starts_with(hay));
+    assert!(hay.ends_with(hay));
+}
+
+#[test]
+fn test_find() {
+    let hay = utf8("key=value=");
+    let hay = SeStr::<Slice, Utf8>::new(&hay);
+    let eq = utf8("=");
+    let missing = utf8("==");
+
+    assert_eq!(hay.find(SeStr::<Slice, Utf8>::new(&eq)), Some(3));
+    assert!(hay.contains(SeStr::<Slice, Utf8>::new(&eq)));
+    assert_eq!(hay.find(SeStr::<Slice, Utf8>::new(&missing)), None);
+    assert!(!hay.contains(SeStr::<Slice, Utf8>::new(&missing)));
+
+    let aaa = utf8("aaa");
+    let aa = utf8("aa");
+    assert_eq!(SeStr::<Slice, Utf8>::new(&aaa).find(SeStr::<Slice, Utf8>::new(&aa)), Some(0));
+    assert_eq!(SeStr::<Slice, Utf8>::new(&aaa[1..]).find(SeStr::<Slice, Utf8>::new(&aa)), Some(0));
+}
+
+#[test]
+fn test_empty_needle() {
+    let hay = utf8("abc");
+    let hay = SeStr::<Slice, Utf8>::new(&hay);
+    let empty = SeStr::<Slice, Utf8>::new(&[]);
+
+    assert!(hay.starts_with(empty));
+    assert!(hay.ends_with(empty));
+    assert!(hay.contains(empty));
+    assert_eq!(hay.find(empty), Some(0));
+    assert_eq!(empty.find(empty), Some(0));
+}
+
+#[test]
+fn test_needle_longer_than_haystack() {
+    let hay = utf8("ab");
+    let hay = SeStr::<Slice, Utf8>::new(&hay);
+    let needle = utf8("abc");
+    let needle = SeStr::<Slice, Utf8>::new(&needle);
+
+    assert!(!hay.starts_with(needle));
+    assert!(!hay.ends_with(needle));
+    assert!(!hay.contains(needle));
+    assert_eq!(hay.find(needle), None);
+}
+
+#[test]
+fn test_unit_slice_pattern() {
+    let hay = utf8("file:///tmp");
+    let hay = SeStr::<Slice, Utf8>::new(&hay);
+
+    assert!(hay.starts_with(&utf8("file://")[..]));
+    assert!(!hay.starts_with(&utf8("http://")[..]));
+    assert!(hay.ends_with(&utf8("/tmp")[..]));
+    assert!(!hay.ends_with(&utf8("/var")[..]));
+}
+
+#[test]
+fn test_strip_prefix_suffix() {
+    let hay = utf8("file:///tmp");
+    let hay = SeStr::<Slice, Utf8>::new(&hay);
+
+    assert_eq!(hay.strip_prefix(&utf8("file://")[..]).expect(here!()).as_units(), &utf8("/tmp")[..]);
+    assert_eq!(hay.strip_prefix(&utf8("http://")[..]), None);
+    assert_eq!(hay.strip_suffix(&utf8("/tmp")[..]).expect(here!()).as_units(), &utf8("file://")[..]);
+    assert_eq!(hay.strip_suffix(&utf8("/var")[..]), None);
+
+    let scheme = utf8_z("file://");
+    let scheme = unsafe { SeStr::<ZeroTerm, Utf8>::from_ptr(scheme.as_ptr() as *const _).expect(here!()) };
+    assert_eq!(hay.strip_prefix(scheme).expect(here!()).as_units(), &utf8("/tmp")[..]);
+
+    // Empty patterns always match, and strip nothing.
+    assert_eq!(hay.strip_prefix(&[][..]).expect(here!()).as_units(), hay.as_units());
+    assert_eq!(hay.strip_suffix(&[][..]).expect(here!()).as_units(), hay.as_units());
+
+    // An exact match leaves nothing.
+    assert!(hay.strip_prefix(hay).expect(here!()).is_empty());
+    assert!(hay.strip_suffix(hay).expect(here!()).is_empty());
+
+    // A pattern longer than the string never matches.
+    let long = utf8("file:///tmp/");
+    assert_eq!(hay.strip_prefix(&long[..]), None);
+    assert_eq!(hay.strip_suffix(&long[..]), None);
+}
+
+#[test]
+fn test_strip_prefix_terminated() {
+    let hay = utf8_z("file:///tmp");
+    let hay = unsafe { SeStr::<ZeroTerm, Utf8>::from_ptr(hay.as_ptr() as *const _).expect(here!()) };
+
+    let rest = hay.strip_prefix_terminated(&utf8("file://")[..]).expect(here!());
+    assert_eq!(rest.as_units(), &utf8("/tmp")[..]);
+    assert_eq!(rest.as_units_with_term(), &utf8_z("/tmp")[..]);
+    assert_eq!(hay.strip_prefix_terminated(&utf8("http://")[..]), None);
+
+    assert_eq!(hay.strip_prefix_terminated(&[][..]).expect(here!()).as_units(), hay.as_units());
+
+    let rest = hay.strip_prefix_terminated(hay.as_slice()).expect(here!());
+    assert!(rest.is_empty());
+    assert_eq!(rest.as_units_with_term(), &[Utf8Unit(0)][..]);
+
+    assert_eq!(hay.strip_prefix_terminated(&utf8("file:///tmp/")[..]), None);
+
+    // The terminator can't be matched.
+    assert_eq!(hay.strip_prefix_terminated(&utf8_z("file:///tmp")[..]), None);
+}
+
+#[test]
+fn test_find_unit() {
+    let hay = utf8("KEY=VALUE=");
+    let hay = SeStr::<Slice, Utf8>::new(&hay);
+
+    assert_eq!(hay.find(Utf8Unit(b'=')), Some(3));
+    assert_eq!(hay.rfind(Utf8Unit(b'=')), Some(9));
+    assert!(hay.contains(Utf8Unit(b'V')));
+    assert_eq!(hay.find(Utf8Unit(b'x')), None);
+    assert_eq!(hay.rfind(Utf8Unit(b'x')), None);
+    assert!(!hay.contains(Utf8Unit(b'x')));
+
+    let at = hay.find(Utf8Unit(b'=')).expect(here!());
+    assert_eq!(&hay.as_units()[..at], &utf8("KEY")[..]);
+    assert_eq!(&hay.as_units()[at + 1..], &utf8("VALUE=")[..]);
+
+    let empty = SeStr::<Slice, Utf8>::new(&[]);
+    assert_eq!(empty.find(Utf8Unit(b'=')), None);
+    assert_eq!(empty.rfind(Utf8Unit(b'=')), None);
+
+    // Wider units don't go through `memchr`.
+    let hay: Vec<_> = "KEY=VALUE=".encode_utf16().map(Utf16Unit).collect();
+    let hay = SeStr::<Slice, Utf16>::new(&hay);
+    assert_eq!(hay.find(Utf16Unit(0x3d)), Some(3));
+    assert_eq!(hay.rfind(Utf16Unit(0x3d)), Some(9));
+    assert_eq!(hay.find(Utf16Unit(0x13d)), None);
+}
+
+#[test]
+fn test_find_unit_long() {
+    let mut hay = vec![Utf8Unit(b'a'); 100000];
+    hay[70000] = Utf8Unit(0xff);
+    hay[80000] = Utf8Unit(0xff);
+    let hay = SeStr::<Slice, Utf8>::new(&hay);
+    assert_eq!(hay.find(Utf8Unit(0xff)), Some(70000));
+    assert_eq!(hay.rfind(Utf8Unit(0xff)), Some(80000));
+    assert_eq!(hay.find(Utf8Unit(0)), None);
+
+    let hay: Vec<_> = (0..1000u32).map(|i| MbUnit((i % 100) as u8 as _)).collect();
+    let hay = SeStr::<Slice, MultiByte>::new(&hay);
+    assert_eq!(hay.find(MbUnit(99u8 as _)), Some(99));
+    assert_eq!(hay.rfind(MbUnit(99u8 as _)), Some(999));
+}
+
+#[test]
+fn test_rfind() {
+    let aaa = utf8("aaa");
+    let aaa = SeStr::<Slice, Utf8>::new(&aaa);
+    let aa = utf8("aa");
+
+    // Overlapping matches.
+    assert_eq!(aaa.find(&aa[..]), Some(0));
+    assert_eq!(aaa.rfind(&aa[..]), Some(1));
+    assert_eq!(aaa.rfind(SeStr::<Slice, Utf8>::new(&aa)), Some(1));
+
+    let hay = utf8("a=b=c");
+    let hay = SeStr::<Slice, Utf8>::new(&hay);
+    assert_eq!(hay.find(&utf8("=")[..]), Some(1));
+    assert_eq!(hay.rfind(&utf8("=")[..]), Some(3));
+    assert_eq!(hay.rfind(&utf8("==")[..]), None);
+    assert_eq!(hay.rfind(hay), Some(0));
+    assert_eq!(hay.rfind(&utf8("a=b=c=")[..]), None);
+
+    // An empty needle is found at either end.
+    assert_eq!(hay.find(&[][..]), Some(0));
+    assert_eq!(hay.rfind(&[][..]), Some(5));
+    let empty = SeStr::<Slice, Utf8>::new(&[]);
+    assert_eq!(empty.rfind(empty), Some(0));
+}
@@ -0,0 +1,174 @@
+extern crate strffi;
+
+use strffi::encoding::{TranscodeTo, Unit, UnitIter, CheckedUnicode, Encoding, JniMtf8, Mtf8Unit, Utf8, Utf8Unit, Utf16, Utf16Unit, Utf32, Utf32Le, Utf32LeUnit, Utf32Unit, Wide, WUnit, Wtf8, Wtf8Unit};
+
+const SAMPLES: &'static [&'static str] = &[
+    "",
+    "garcon",
+    "gªrçon😀",
+    "\u{0}\u{7f}\u{80}\u{7ff}\u{800}\u{ffff}\u{10000}\u{10ffff}",
+    "日本語のテキスト with ASCII 🎉 mixed in",
+];
+
+/**
+Checks that `size_hint` brackets the number of remaining items at every step of iteration.
+*/
+fn check_bounds<It>(mut iter: It) where It: Iterator {
+    let mut items = vec![];
+    let mut hints = vec![iter.size_hint()];
+    while let Some(item) = iter.next() {
+        items.push(item);
+        hints.push(iter.size_hint());
+    }
+    let len = items.len();
+    for (i, &(lo, hi)) in hints.iter().enumerate() {
+        let rest = len - i;
+        assert!(lo <= rest, "lower bound {} > {} remaining", lo, rest);
+        assert!(hi.map_or(true, |hi| rest <= hi), "upper bound {:?} < {} remaining", hi, rest);
+    }
+}
+
+fn transcode<E, F>(units: Vec<E::Unit>) -> <UnitIter<E, ::std::vec::IntoIter<E::Unit>> as TranscodeTo<F>>::Iter
+where
+    E: Encoding,
+    F: Encoding,
+    UnitIter<E, ::std::vec::IntoIter<E::Unit>>: TranscodeTo<F>,
+{
+    UnitIter::<E, _>::new(units.into_iter()).transcode()
+}
+
+#[test]
+fn test_size_hint_bounds() {
+    for s in SAMPLES {
+        let chars: Vec<char> = s.chars().collect();
+        let utf8: Vec<Utf8Unit> = s.bytes().map(Utf8Unit).collect();
+        let utf16: Vec<Utf16Unit> = s.encode_utf16().map(Utf16Unit).collect();
+        let utf32: Vec<Utf32Unit> = s.chars().map(|c| Utf32Unit(c as u32)).collect();
+        let wide: Vec<WUnit> = transcode::<CheckedUnicode, Wide>(chars.clone()).map(Result::unwrap).collect();
+        let mtf8: Vec<Mtf8Unit> = transcode::<CheckedUnicode, JniMtf8>(chars.clone()).map(Result::unwrap).collect();
+
+        check_bounds(transcode::<CheckedUnicode, Utf8>(chars.clone()));
+        check_bounds(transcode::<CheckedUnicode, Utf16>(chars.clone()));
+        check_bounds(transcode::<CheckedUnicode, Utf32>(chars.clone()));
+        check_bounds(transcode::<CheckedUnicode, Wide>(chars.clone()));
+        check_bounds(transcode::<CheckedUnicode, JniMtf8>(chars.clone()));
+        check_bounds(transcode::<Utf8, CheckedUnicode>(utf8.clone()));
+        check_bounds(transcode::<Utf8, Utf16>(utf8.clone()));
+        check_bounds(transcode::<Utf8, JniMtf8>(utf8.clone()));
+        check_bounds(transcode::<Utf16, CheckedUnicode>(utf16.clone()));
+        check_bounds(transcode::<Utf16, Utf8>(utf16.clone()));
+        check_bounds(transcode::<Utf32, CheckedUnicode>(utf32.clone()));
+        check_bounds(transcode::<Utf32, Utf32Le>(utf32.clone()));
+        check_bounds(transcode::<Wide, CheckedUnicode>(wide.clone()));
+        check_bounds(transcode::<JniMtf8, CheckedUnicode>(mtf8.clone()));
+        check_bounds(transcode::<JniMtf8, Utf8>(mtf8.clone()));
+        check_bounds(transcode::<CheckedUnicode, Wtf8>(chars.clone()));
+        check_bounds(transcode::<Utf16, Wtf8>(utf16.clone()));
+        check_bounds(transcode::<Wtf8, CheckedUnicode>(s.bytes().map(Wtf8Unit).collect()));
+        check_bounds(transcode::<Wtf8, Utf16>(s.bytes().map(Wtf8Unit).collect()));
+    }
+}
+
+#[test]
+fn test_size_hint_bounds_invalid() {
+    // Decoders which stop at the first error must not promise more than one item.
+    let utf32le = |raw: &[u32]| -> Vec<Utf32LeUnit> { raw.iter().map(|&v| Utf32LeUnit::from_raw(v).unwrap()).collect() };
+    check_bounds(transcode::<Utf32Le, CheckedUnicode>(utf32le(&[0xd800, 0x61, 0x62, 0x63])));
+    check_bounds(transcode::<Utf32Le, CheckedUnicode>(utf32le(&[0x61, 0x110000, 0x62])));
+
+    let utf16 = |raw: &[u16]| -> Vec<Utf16Unit> { raw.iter().map(|&v| Utf16Unit(v)).collect() };
+    check_bounds(transcode::<Utf16, CheckedUnicode>(utf16(&[0xdc00, 0x61, 0x62, 0x63])));
+    check_bounds(transcode::<Utf16, Utf8>(utf16(&[0xdc00, 0x61, 0x62, 0x63])));
+    check_bounds(transcode::<Utf16, Utf8>(utf16(&[0xe9, 0xd800, 0x62, 0x63])));
+
+    let wide: Vec<WUnit> = transcode::<CheckedUnicode, Wide>("abcdef".chars().collect()).map(Result::unwrap).collect();
+    let mut bad_wide = wide.clone();
+    bad_wide[0] = WUnit::from_raw(0xdc00).unwrap();
+    check_bounds(transcode::<Wide, CheckedUnicode>(bad_wide));
+
+    let mtf8 = |raw: &[u8]| -> Vec<Mtf8Unit> { raw.iter().map(|&v| Mtf8Unit(v)).collect() };
+    check_bounds(transcode::<JniMtf8, CheckedUnicode>(mtf8(b"\xff\x61\x62\x63\x64\x65\x66")));
+    check_bounds(transcode::<JniMtf8, Utf8>(mtf8(b"\xff\x61\x62\x63\x64\x65\x66")));
+    check_bounds(transcode::<Utf8, JniMtf8>(b"\xffabcdef".iter().map(|&b| Utf8Unit(b)).collect()));
+
+    let wtf8: Vec<Wtf8Unit> = b"\xffabcdefgh".iter().map(|&b| Wtf8Unit(b)).collect();
+    check_bounds(transcode::<Wtf8, CheckedUnicode>(wtf8.clone()));
+    check_bounds(transcode::<Wtf8, Utf16>(wtf8));
+}
+
+#[test]
+fn test_trap_err_size_hint() {
+    use strffi::alloc::Malloc;
+    use strffi::sea::{SeStr, SeaString};
+    use strffi::structure::{Slice, ZeroTerm};
+
+    // Trapping an error ends the iteration early, so a transcode which fails partway must still succeed in reporting the error, rather than tripping over a lower bound it can't meet.
+    let units: Vec<Utf16Unit> = [0x61, 0x62, 0xdc00, 0x63].iter().map(|&v| Utf16Unit(v)).collect();
+    let r: Result<SeaString<ZeroTerm, Utf8, Malloc>, _> = SeStr::<Slice, Utf16>::new(&units).transcode_to();
+    assert!(r.is_err());
+}
+
+#[test]
+fn test_size_hint_nonzero() {
+    // A non-trivial lower bound is what lets `collect` allocate up front.
+    let s = "gªrçon😀".repeat(100);
+    let utf8: Vec<Utf8Unit> = s.bytes().map(Utf8Unit).collect();
+    let utf16: Vec<Utf16Unit> = s.encode_utf16().map(Utf16Unit).collect();
+
+    let (lo, hi) = transcode::<Utf8, Utf16>(utf8.clone()).size_hint();
+    assert!(lo >= utf8.len() / 4);
+    assert_eq!(hi, Some(utf8.len()));
+
+    // This one stops at the first unpaired surrogate, so it can't promise more than one item.
+    let (lo, hi) = transcode::<Utf16, Utf8>(utf16.clone()).size_hint();
+    assert_eq!(lo, 1);
+    assert_eq!(hi, Some(utf16.len() * 3));
+
+    let (lo, hi) = transcode::<CheckedUnicode, Utf16>(s.chars().collect()).size_hint();
+    assert_eq!(lo, s.chars().count());
+    assert_eq!(hi, Some(s.chars().count() * 2));
+}
+
+#[test]
+fn test_exact_size() {
+    let utf32: Vec<Utf32Unit> = "gªrçon😀".chars().map(|c| Utf32Unit(c as u32)).collect();
+    let mut iter = transcode::<Utf32, Utf32Le>(utf32.clone());
+    assert_eq!(iter.len(), utf32.len());
+    iter.next();
+    assert_eq!(iter.len(), utf32.len() - 1);
+
+    let iter = transcode::<CheckedUnicode, Utf32>("gªrçon😀".chars().collect());
+    assert_eq!(iter.len(), 7);
+}
+
+#[test]
+fn test_zero_term_size_hint() {
+    use strffi::alloc::Malloc;
+    use strffi::sea::SeaString;
+    use strffi::structure::ZeroTerm;
+
+    let s = SeaString::<ZeroTerm, Utf16, Malloc>::from_str("gªrçon😀").unwrap();
+    check_bounds(s.units());
+    assert_eq!(s.units().size_hint(), (1, None));
+
+    let empty = SeaString::<ZeroTerm, Utf16, Malloc>::from_str("").unwrap();
+    assert_eq!(empty.units().size_hint(), (0, Some(0)));
+}
+
+#[test]
+fn test_min_transcoded_len() {
+    use strffi::sea::SeStr;
+    use strffi::structure::Slice;
+
+    for s in SAMPLES {
+        let utf8: Vec<Utf8Unit> = s.bytes().map(Utf8Unit).collect();
+        let utf16: Vec<Utf16Unit> = s.encode_utf16().map(Utf16Unit).collect();
+        let chars = s.chars().count();
+        assert!(SeStr::<Slice, Utf8>::new(&utf8).min_transcoded_len() <= chars);
+        assert!(SeStr::<Slice, Utf16>::new(&utf16).min_transcoded_len() <= chars);
+    }
+    let utf8: Vec<Utf8Unit> = "garcon".bytes().map(Utf8Unit).collect();
+    assert_eq!(SeStr::<Slice, Utf8>::new(&utf8).min_transcoded_len(), 2);
+    let utf32: Vec<Utf32Unit> = "garcon".chars().map(|c| Utf32Unit(c as u32)).collect();
+    assert_eq!(SeStr::<Slice, Utf32>::new(&utf32).min_transcoded_len(), 6);
+}
@@ -0,0 +1,39 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{Wide, WUnit};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+
+fn split(s: &str, sep: char) -> Vec<String> {
+    let s = SeaString::<ZeroTerm, Wide, Malloc>::from_str(s).expect(here!());
+    let sep = WUnit(sep as _);
+    s.split_unit(sep).map(|part| part.into_string().expect(here!())).collect()
+}
+
+#[test]
+fn test_split_unit() {
+    assert_eq!(split("a;bc;d", ';'), vec!["a", "bc", "d"]);
+    assert_eq!(split("abc", ';'), vec!["abc"]);
+    assert_eq!(split("", ';'), vec![""]);
+}
+
+#[test]
+fn test_split_unit_empty_parts() {
+    assert_eq!(split(";a;;b;", ';'), vec!["", "a", "", "b", ""]);
+    assert_eq!(split(";", ';'), vec!["", ""]);
+}
+
+#[test]
+fn test_split_unit_borrows() {
+    let units: Vec<_> = "x:y".chars().map(|c| WUnit(c as _)).collect();
+    let parts: Vec<&SeStr<Slice, Wide>> = {
+        let s = SeStr::<Slice, Wide>::new(&units);
+        s.split_unit(WUnit(':' as _)).collect()
+    };
+    assert_eq!(parts.len(), 2);
+    assert_eq!(parts[0].as_units().as_ptr(), units.as_ptr());
+    assert_eq!(parts[1].as_units().as_ptr(), units[2..].as_ptr());
+}
@@ -0,0 +1,162 @@
+#[macro_use] extern crate strffi;
+
+mod common;
+
+use strffi::encoding::{EncodeChar, EncodeError, Encoding, StreamTranscoder, TranscodeError, Unit, Utf8, Utf8Unit, Utf16, Utf16Unit, CheckedUnicode};
+use common::utf8;
+
+const WORD: &'static str = "gªrçon";
+
+fn utf16(s: &str) -> Vec<Utf16Unit> {
+    s.encode_utf16().map(Utf16Unit).collect()
+}
+
+#[test]
+fn test_stream_split_char() {
+    let units = utf8(WORD.as_bytes());
+    // Split inside "ª", which is `c2 aa`.
+    let (head, tail) = units.split_at(2);
+
+    let mut tc = StreamTranscoder::<Utf8, Utf16>::new();
+    let mut out = vec![];
+    tc.feed(head, &mut out).unwrap();
+    assert_eq!(out, utf16("g"));
+    assert_eq!(tc.pending(), 1);
+    tc.feed(tail, &mut out).unwrap();
+    assert_eq!(tc.pending(), 0);
+    tc.finish(&mut out).unwrap();
+    assert_eq!(out, utf16(WORD));
+}
+
+#[test]
+fn test_stream_every_split() {
+    let s = "gªrçon😀日本";
+    let units = utf8(s.as_bytes());
+    for i in 0..units.len() + 1 {
+        for j in i..units.len() + 1 {
+            let mut tc = StreamTranscoder::<Utf8, CheckedUnicode>::new();
+            let mut out = vec![];
+            tc.feed(&units[..i], &mut out).unwrap();
+            tc.feed(&units[i..j], &mut out).unwrap();
+            tc.feed(&units[j..], &mut out).unwrap();
+            tc.finish(&mut out).unwrap();
+            assert_eq!(out, s.chars().collect::<Vec<_>>(), "split at {} and {}", i, j);
+        }
+    }
+}
+
+#[test]
+fn test_stream_one_unit_at_a_time() {
+    let mut tc = StreamTranscoder::<Utf8, Utf16>::new();
+    let mut out = vec![];
+    for unit in utf8("gªrçon😀".as_bytes()) {
+        tc.feed(&[unit], &mut out).unwrap();
+    }
+    tc.finish(&mut out).unwrap();
+    assert_eq!(out, utf16("gªrçon😀"));
+}
+
+#[test]
+fn test_stream_errors() {
+    let mut tc = StreamTranscoder::<Utf8, Utf16>::new();
+    let mut out = vec![];
+
+    // Only an incomplete sequence at the very end is an error.
+    tc.feed(&utf8(b"g\xc2"), &mut out).unwrap();
+    assert_eq!(tc.finish(&mut out), Err(TranscodeError::Incomplete));
+
+    // `finish` starts a new stream.
+    out.clear();
+    tc.feed(&utf8(b"ok"), &mut out).unwrap();
+    tc.finish(&mut out).unwrap();
+    assert_eq!(out, utf16("ok"));
+
+    // Offsets count from the start of the stream.
+    tc.feed(&utf8(b"gar\xc3"), &mut out).unwrap();
+    assert_eq!(tc.feed(&utf8(b"\xa7o\xffn"), &mut out), Err(TranscodeError::InvalidAt(6)));
+    assert_eq!(tc.pending(), 0);
+
+    // A sequence broken across chunks is reported where it starts.
+    tc.feed(&utf8(b"ab\xe2\x82"), &mut out).unwrap();
+    assert_eq!(tc.feed(&utf8(b"x"), &mut out), Err(TranscodeError::InvalidAt(2)));
+}
+
+#[test]
+fn test_stream_pending_tail() {
+    let mut tc = StreamTranscoder::<Utf8, Utf16>::new();
+    let mut out = vec![];
+    tc.feed(&utf8(b"g\xc2"), &mut out).unwrap();
+    assert_eq!(tc.pending(), 1);
+
+    // Finishing "ª" leaves only the start of "ç" held back, not the whole chunk.
+    let mut chunk = utf8(b"\xaa");
+    chunk.extend(utf8("r".repeat(100).as_bytes()));
+    chunk.push(Utf8Unit(0xc3));
+    tc.feed(&chunk, &mut out).unwrap();
+    assert_eq!(tc.pending(), 1);
+    tc.feed(&utf8(b"\xa7on"), &mut out).unwrap();
+    tc.finish(&mut out).unwrap();
+    assert_eq!(out, utf16(&format!("gª{}çon", "r".repeat(100))));
+}
+
+declare_encoding! {
+    enum Spelled;
+    unit SpelledUnit(u8);
+    prefix "Spelled";
+    max_units_per_char 12;
+}
+
+/**
+Spells out each character's code point in hex, between brackets.
+*/
+impl EncodeChar for Spelled {
+    fn encode(c: char, buf: &mut [SpelledUnit]) -> Result<&[SpelledUnit], EncodeError> {
+        let spelled = format!("<U+{:06X}>", c as u32);
+        if buf.len() < spelled.len() {
+            return Err(EncodeError::BufferTooSmall(c));
+        }
+        for (u, b) in buf.iter_mut().zip(spelled.bytes()) {
+            *u = SpelledUnit(b);
+        }
+        Ok(&buf[..spelled.len()])
+    }
+}
+
+declare_encoding! {
+    enum Understated;
+    unit UnderstatedUnit(u8);
+    prefix "Understated";
+    max_units_per_char 2;
+}
+
+/**
+Claims every character fits in two units, but needs three.
+*/
+impl EncodeChar for Understated {
+    fn encode(c: char, buf: &mut [UnderstatedUnit]) -> Result<&[UnderstatedUnit], EncodeError> {
+        if buf.len() < 3 {
+            return Err(EncodeError::BufferTooSmall(c));
+        }
+        for u in &mut buf[..3] {
+            *u = UnderstatedUnit::zero();
+        }
+        Ok(&buf[..3])
+    }
+}
+
+#[test]
+fn test_stream_long_encodings() {
+    assert!(Spelled::MAX_UNITS_PER_CHAR > 8);
+    let mut tc = StreamTranscoder::<Utf8, Spelled>::new();
+    let mut out = vec![];
+    tc.feed(&utf8("a😀".as_bytes()), &mut out).unwrap();
+    tc.finish(&mut out).unwrap();
+    let spelled: Vec<u8> = out.iter().map(|u| u.0).collect();
+    assert_eq!(spelled, b"<U+000061><U+01F600>");
+
+    // An encoding which gets its own limit wrong is an error, not a panic.
+    let mut tc = StreamTranscoder::<Utf8, Understated>::new();
+    let mut out = vec![];
+    assert_eq!(tc.feed(&utf8(b"ab"), &mut out), Err(TranscodeError::OutOfBufferAt(0)));
+    assert!(out.is_empty());
+}
@@ -0,0 +1,131 @@
+extern crate libc;
+#[macro_use] extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use strffi::alloc::{Allocator, AllocError};
+use strffi::encoding::{Utf16, Utf16Unit, Wide, WUnit};
+use strffi::encoding::conv::WcToUniError;
+use strffi::encoding::conv::utf16::Utf16ToUniError;
+use strffi::sea::SeaString;
+use strffi::structure::ZeroTerm;
+
+/*
+Each test gets its own allocator, so that tests running in parallel don't disturb one another's counts.
+*/
+macro_rules! counting_allocator {
+    ($name:ident, $live:ident) => {
+        static $live: AtomicUsize = ATOMIC_USIZE_INIT;
+
+        enum $name {}
+
+        impl Allocator for $name {
+            type AllocError = AllocError;
+            type Pointer = *mut ();
+
+            fn alloc_bytes(bytes: usize, _align: usize) -> Result<*mut (), AllocError> {
+                let ptr = unsafe { libc::calloc(bytes, 1) };
+                if ptr.is_null() {
+                    Err(AllocError::Failed)
+                } else {
+                    $live.fetch_add(1, Ordering::SeqCst);
+                    Ok(ptr as *mut ())
+                }
+            }
+
+            unsafe fn free(ptr: *mut (), _align: usize) {
+                if !ptr.is_null() {
+                    $live.fetch_sub(1, Ordering::SeqCst);
+                    libc::free(ptr as *mut libc::c_void);
+                }
+            }
+
+            fn debug_prefix() -> &'static str { "Count" }
+        }
+    };
+}
+
+counting_allocator! { CountValid, LIVE_VALID }
+counting_allocator! { CountInvalid, LIVE_INVALID }
+counting_allocator! { CountUtf16Valid, LIVE_UTF16_VALID }
+counting_allocator! { CountUtf16Invalid, LIVE_UTF16_INVALID }
+
+// "gªrçon"
+const VALID: &'static [u32] = &[0x67, 0xAA, 0x72, 0xE7, 0x6F, 0x6E];
+
+// A lone surrogate where `wchar_t` holds UTF-16, or a code point beyond Unicode where it holds UTF-32.
+const INVALID: &'static [u32] = if wide_is_utf16!() { &[0x67, 0xDC00, 0x72] } else { &[0x67, 0x110000, 0x72] };
+
+fn wide(units: &[u32]) -> Vec<WUnit> {
+    units.iter().map(|&u| WUnit(u as _)).collect()
+}
+
+#[test]
+fn test_take_validated_accepts_valid() {
+    let units = wide(VALID);
+    let ptr = SeaString::<ZeroTerm, Wide, CountValid>::new(&units).expect(here!()).into_ptr();
+    assert_eq!(LIVE_VALID.load(Ordering::SeqCst), 1);
+
+    {
+        let s = unsafe {
+            SeaString::<ZeroTerm, Wide, CountValid>::from_ptr_take_validated(ptr)
+                .expect(here!())
+                .expect(here!())
+        };
+        assert_eq!(s.as_units(), &units[..]);
+        assert_eq!(LIVE_VALID.load(Ordering::SeqCst), 1);
+    }
+
+    assert_eq!(LIVE_VALID.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn test_take_validated_rejects_and_frees_invalid() {
+    let units = wide(INVALID);
+    let ptr = SeaString::<ZeroTerm, Wide, CountInvalid>::new(&units).expect(here!()).into_ptr();
+    assert_eq!(LIVE_INVALID.load(Ordering::SeqCst), 1);
+
+    let r = unsafe { SeaString::<ZeroTerm, Wide, CountInvalid>::from_ptr_take_validated(ptr) };
+    assert_eq!(r.err(), Some(WcToUniError::InvalidAt(1)));
+    assert_eq!(LIVE_INVALID.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn test_take_validated_null() {
+    let r = unsafe {
+        SeaString::<ZeroTerm, Wide, CountValid>::from_ptr_take_validated(::std::ptr::null_mut())
+    };
+    assert!(r.expect(here!()).is_none());
+}
+
+#[test]
+fn test_take_validated_utf16() {
+    let units: Vec<_> = "g\u{aa}r\u{e7}on \u{1f600}".encode_utf16().map(Utf16Unit).collect();
+    let ptr = SeaString::<ZeroTerm, Utf16, CountUtf16Valid>::new(&units).expect(here!()).into_ptr();
+    assert_eq!(LIVE_UTF16_VALID.load(Ordering::SeqCst), 1);
+
+    {
+        let s = unsafe {
+            SeaString::<ZeroTerm, Utf16, CountUtf16Valid>::from_ptr_take_validated(ptr)
+                .expect(here!())
+                .expect(here!())
+        };
+        assert_eq!(s.as_units(), &units[..]);
+        assert_eq!(LIVE_UTF16_VALID.load(Ordering::SeqCst), 1);
+    }
+
+    assert_eq!(LIVE_UTF16_VALID.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn test_take_validated_utf16_rejects_and_frees_invalid() {
+    // A high surrogate with no low surrogate after it.
+    let units = [Utf16Unit(0x67), Utf16Unit(0xD83D), Utf16Unit(0x72)];
+    let ptr = SeaString::<ZeroTerm, Utf16, CountUtf16Invalid>::new(&units).expect(here!()).into_ptr();
+    assert_eq!(LIVE_UTF16_INVALID.load(Ordering::SeqCst), 1);
+
+    let r = unsafe { SeaString::<ZeroTerm, Utf16, CountUtf16Invalid>::from_ptr_take_validated(ptr) };
+    assert_eq!(r.err(), Some(Utf16ToUniError::InvalidAt(1)));
+    assert_eq!(LIVE_UTF16_INVALID.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,188 @@
+#![cfg(feature="mb-test-codec")]
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::{ZMbStr, ZMbCString, ZWCString};
+use strffi::alloc::Malloc;
+use strffi::encoding::{Char16, C16Unit, Char32, C32Unit, MultiByte};
+use strffi::ffi::test_codec::{TestCodec, test_codec, with_test_codec};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+
+const WORD: &'static str = "gªrçon";
+
+fn check_garcon(word_mb: &[u8]) {
+    let zmbstr = unsafe { ZMbStr::from_ptr(word_mb.as_ptr() as *const _).expect(here!()) };
+    let zwcstr = ZWCString::from_str(WORD).expect(here!());
+    {
+        let rstr = zmbstr.into_string().expect(here!());
+        assert_eq!(&rstr, WORD);
+    }
+    {
+        let zwcstr2: ZWCString = zmbstr.transcode_to().expect(here!());
+        assert_eq!(&zwcstr2, &zwcstr);
+    }
+    {
+        let zmbcstr: ZMbCString = zwcstr.transcode_to().expect(here!()).into();
+        assert_eq!(&zmbcstr, zmbstr);
+        let rstr = zmbcstr.into_string().expect(here!());
+        assert_eq!(&rstr, WORD);
+    }
+    {
+        let zmbcstr = ZMbCString::from_str(WORD).expect(here!());
+        assert_eq!(&zmbcstr, zmbstr);
+    }
+}
+
+#[test]
+fn test_garcon_utf8() {
+    with_test_codec(TestCodec::Utf8, || check_garcon(b"g\xc2\xaar\xc3\xa7on\0"));
+}
+
+#[test]
+fn test_garcon_1252() {
+    with_test_codec(TestCodec::Windows1252, || check_garcon(b"g\xaar\xe7on\0"));
+}
+
+#[test]
+fn test_1252_high() {
+    with_test_codec(TestCodec::Windows1252, || {
+        let zmbstr = unsafe { ZMbStr::from_ptr(b"\x80\x9f\0".as_ptr() as *const _).expect(here!()) };
+        assert_eq!(zmbstr.into_string().expect(here!()), "€Ÿ");
+
+        // 0x81 is undefined in code page 1252.
+        let zmbstr = unsafe { ZMbStr::from_ptr(b"a\x81\0".as_ptr() as *const _).expect(here!()) };
+        assert!(zmbstr.into_string().is_err());
+
+        assert!(ZMbCString::from_str("😀").is_err());
+    });
+}
+
+#[test]
+fn test_utf8_invalid() {
+    with_test_codec(TestCodec::Utf8, || {
+        let zmbstr = unsafe { ZMbStr::from_ptr(b"a\xc0\x80\0".as_ptr() as *const _).expect(here!()) };
+        assert!(zmbstr.into_string().is_err());
+
+        let zmbstr = unsafe { ZMbStr::from_ptr(b"a\xe2\x82\0".as_ptr() as *const _).expect(here!()) };
+        assert!(zmbstr.into_string().is_err());
+    });
+}
+
+#[test]
+fn test_char16_surrogates() {
+    const WORD: &'static str = "a😀b";
+    const WORD_MB: &'static [u8] = b"a\xf0\x9f\x98\x80b\0";
+
+    with_test_codec(TestCodec::Utf8, || {
+        let zmbstr = unsafe { ZMbStr::from_ptr(WORD_MB.as_ptr() as *const _).expect(here!()) };
+        let expected: Vec<_> = WORD.encode_utf16().map(C16Unit).collect();
+
+        let c16: SeaString<Slice, Char16, Malloc> = zmbstr.transcode_to().expect(here!());
+        assert_eq!(c16.as_units(), &expected[..]);
+
+        let mb: SeaString<ZeroTerm, MultiByte, Malloc> = c16.transcode_to().expect(here!());
+        assert_eq!(mb.as_units(), zmbstr.as_units());
+
+        let lone = [C16Unit(0x61), C16Unit(0xd83d)];
+        let r: Result<SeaString<ZeroTerm, MultiByte, Malloc>, _> = SeStr::<Slice, Char16>::new(&lone).transcode_to();
+        assert!(r.is_err());
+    });
+}
+
+#[test]
+fn test_char32() {
+    const WORD: &'static str = "a😀b";
+    const WORD_MB: &'static [u8] = b"a\xf0\x9f\x98\x80b\0";
+
+    with_test_codec(TestCodec::Utf8, || {
+        let zmbstr = unsafe { ZMbStr::from_ptr(WORD_MB.as_ptr() as *const _).expect(here!()) };
+        let expected: Vec<_> = WORD.chars().map(|c| C32Unit(c as u32)).collect();
+
+        let c32: SeaString<Slice, Char32, Malloc> = zmbstr.transcode_to().expect(here!());
+        assert_eq!(c32.as_units(), &expected[..]);
+
+        let mb: SeaString<ZeroTerm, MultiByte, Malloc> = c32.transcode_to().expect(here!());
+        assert_eq!(mb.as_units(), zmbstr.as_units());
+    });
+}
+
+#[test]
+fn test_codec_restored() {
+    assert_eq!(test_codec(), None);
+    with_test_codec(TestCodec::Utf8, || {
+        assert_eq!(test_codec(), Some(TestCodec::Utf8));
+        with_test_codec(TestCodec::Windows1252, || {
+            assert_eq!(test_codec(), Some(TestCodec::Windows1252));
+        });
+        assert_eq!(test_codec(), Some(TestCodec::Utf8));
+    });
+    assert_eq!(test_codec(), None);
+}
+
+#[test]
+fn test_validate_encoding() {
+    use strffi::encoding::EncodingError;
+
+    with_test_codec(TestCodec::Utf8, || {
+        let mb = |bytes| SeStr::<Slice, MultiByte>::from_bytes(bytes).validate_encoding();
+        assert_eq!(mb(b"g\xc2\xaar\xc3\xa7on"), Ok(()));
+        assert_eq!(mb(b"a\0b"), Ok(()));
+        assert_eq!(mb(b"ab\xffcd"), Err(EncodingError::new(2, Some(1))));
+        assert_eq!(mb(b"ab\xe2\x82"), Err(EncodingError::new(2, None)));
+    });
+}
+
+#[test]
+fn test_wide_to_mb_slice() {
+    let check = |word_mb: &[u8]| {
+        let zwcstr = ZWCString::from_str(WORD).expect(here!());
+        let mbs: SeaString<Slice, MultiByte, Malloc> = zwcstr.transcode_to().expect(here!());
+        let bytes: Vec<u8> = mbs.as_units().iter().map(|u| u.0 as u8).collect();
+        assert_eq!(&bytes[..], word_mb);
+    };
+    with_test_codec(TestCodec::Utf8, || check(b"g\xc2\xaar\xc3\xa7on"));
+    with_test_codec(TestCodec::Windows1252, || check(b"g\xaar\xe7on"));
+}
+
+#[test]
+fn test_mb_error_offset() {
+    use strffi::encoding::conv::mb_x_wc::MbsToUniError;
+
+    let err_at = |bytes: &[u8]| {
+        let err = SeStr::<Slice, MultiByte>::from_bytes(bytes).into_string().err().expect(here!());
+        *err.downcast_ref::<MbsToUniError>().expect(here!())
+    };
+    with_test_codec(TestCodec::Utf8, || {
+        assert_eq!(err_at(b"g\xc2\xaar\xc3\xa7\xffon"), MbsToUniError::InvalidAt(6));
+        assert_eq!(err_at(b"\xf0\x9f\x98\x80\xed\xa0\x80"), MbsToUniError::InvalidAt(4));
+    });
+    with_test_codec(TestCodec::Windows1252, || {
+        assert_eq!(err_at(b"g\xaar\xe7\x81on"), MbsToUniError::InvalidAt(4));
+    });
+}
+
+#[test]
+fn test_bulk() {
+    use strffi::encoding::conv::mb_x_wc::MbsToUniError;
+
+    let check = |word_mb: &[u8]| {
+        let zmbstr = SeaString::<ZeroTerm, MultiByte, Malloc>::new(SeStr::<Slice, MultiByte>::from_bytes(word_mb).as_units()).expect(here!());
+        assert_eq!(zmbstr.into_string_bulk().expect(here!()), WORD);
+        let zwcstr = ZWCString::from_str(WORD).expect(here!());
+        let zwcstr2: ZWCString = zmbstr.transcode_to_wide_bulk().expect(here!());
+        assert_eq!(&zwcstr2, &zwcstr);
+        let zmbstr2: SeaString<ZeroTerm, MultiByte, Malloc> = zwcstr.transcode_to_multibyte_bulk().expect(here!());
+        assert_eq!(zmbstr2.as_units(), zmbstr.as_units());
+    };
+    with_test_codec(TestCodec::Utf8, || check(b"g\xc2\xaar\xc3\xa7on"));
+    with_test_codec(TestCodec::Windows1252, || check(b"g\xaar\xe7on"));
+
+    // Errors are found by falling back to the iterators.
+    with_test_codec(TestCodec::Utf8, || {
+        let zmbstr = SeaString::<ZeroTerm, MultiByte, Malloc>::new(SeStr::<Slice, MultiByte>::from_bytes(b"g\xc2\xaar\xc3\xa7\xffon").as_units()).expect(here!());
+        let err = zmbstr.into_string_bulk().err().expect(here!());
+        assert_eq!(*err.downcast_ref::<MbsToUniError>().expect(here!()), MbsToUniError::InvalidAt(6));
+    });
+}
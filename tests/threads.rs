@@ -0,0 +1,57 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use std::sync::Arc;
+use std::thread;
+use strffi::alloc::{Global, Malloc};
+use strffi::encoding::{Raw8, Utf8, Utf16, Utf32};
+use strffi::sea::{IntoUnits, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn test_send_sync() {
+    assert_send_sync::<SeaString<Slice, Utf16, Malloc>>();
+    assert_send_sync::<SeaString<ZeroTerm, Utf16, Malloc>>();
+    assert_send_sync::<SeaString<Slice, Utf8, Global>>();
+    assert_send_sync::<SeaString<ZeroTerm, Utf32, Global>>();
+    assert_send_sync::<SeaString<Slice, Raw8, Malloc>>();
+
+    assert_send_sync::<IntoUnits<Slice, Utf16, Malloc>>();
+    assert_send_sync::<IntoUnits<ZeroTerm, Utf8, Global>>();
+}
+
+#[test]
+fn test_move_to_thread() {
+    let s = SeaString::<Slice, Utf16, Malloc>::from_str("h\u{e9}llo \u{1f600}").expect(here!());
+
+    let r = thread::spawn(move || s.into_string().expect(here!()))
+        .join().expect(here!());
+    assert_eq!(r, "h\u{e9}llo \u{1f600}");
+}
+
+#[test]
+fn test_move_iter_to_thread() {
+    let mut iter = SeaString::<ZeroTerm, Utf8, Malloc>::from_str("abc").expect(here!()).into_iter();
+    assert!(iter.next().is_some());
+
+    let rest: Vec<_> = thread::spawn(move || iter.collect())
+        .join().expect(here!());
+    assert_eq!(rest.len(), 2);
+}
+
+#[test]
+fn test_share_between_threads() {
+    let s = SeaString::<ZeroTerm, Utf8, Global>::from_str("abc").expect(here!());
+    let s = Arc::new(s);
+
+    let threads: Vec<_> = (0..4).map(|_| {
+        let s = s.clone();
+        thread::spawn(move || s.as_units().len())
+    }).collect();
+    for t in threads {
+        assert_eq!(t.join().expect(here!()), 3);
+    }
+}
@@ -0,0 +1,122 @@
+/*!
+Checks that `transcode_to` allocates its result exactly once, without a temporary buffer.
+
+The Rust heap is only used for temporaries here, so a global allocator counts those, while the destination uses a counting `Allocator` of its own.  Both counts are process-wide, so everything is checked from a single test.
+*/
+extern crate libc;
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use strffi::alloc::{Allocator, AllocError};
+use strffi::encoding::{Utf8, Utf8Unit, Utf16, Utf16Le, Utf16Unit};
+use strffi::sea::SeStr;
+use strffi::structure::{Slice, ZeroTerm};
+
+struct CountingGlobal;
+
+static HEAP_ALLOCS: AtomicUsize = ATOMIC_USIZE_INIT;
+
+unsafe impl GlobalAlloc for CountingGlobal {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        HEAP_ALLOCS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingGlobal = CountingGlobal;
+
+static DEST_ALLOCS: AtomicUsize = ATOMIC_USIZE_INIT;
+static DEST_LIVE: AtomicUsize = ATOMIC_USIZE_INIT;
+
+enum Counting {}
+
+impl Allocator for Counting {
+    type AllocError = AllocError;
+    type Pointer = *mut ();
+
+    fn alloc_bytes(bytes: usize, _align: usize) -> Result<*mut (), AllocError> {
+        let ptr = unsafe { libc::calloc(bytes, 1) };
+        if ptr.is_null() {
+            Err(AllocError::Failed)
+        } else {
+            DEST_ALLOCS.fetch_add(1, Ordering::SeqCst);
+            DEST_LIVE.fetch_add(1, Ordering::SeqCst);
+            Ok(ptr as *mut ())
+        }
+    }
+
+    unsafe fn free(ptr: *mut (), _align: usize) {
+        if !ptr.is_null() {
+            DEST_LIVE.fetch_sub(1, Ordering::SeqCst);
+            libc::free(ptr as *mut libc::c_void);
+        }
+    }
+
+    fn debug_prefix() -> &'static str { "Count" }
+}
+
+/**
+Runs `f`, and returns how many allocations it made from the Rust heap and from `Counting`.
+*/
+fn count<F, R>(f: F) -> (R, usize, usize) where F: FnOnce() -> R {
+    let heap = HEAP_ALLOCS.load(Ordering::SeqCst);
+    let dest = DEST_ALLOCS.load(Ordering::SeqCst);
+    let r = f();
+    (r, HEAP_ALLOCS.load(Ordering::SeqCst) - heap, DEST_ALLOCS.load(Ordering::SeqCst) - dest)
+}
+
+fn check_transcode_to() {
+    let units: Vec<_> = "gªrçon 😀".bytes().map(Utf8Unit).collect();
+    let s = SeStr::<Slice, Utf8>::new(&units);
+
+    // The transcoder can't predict its length, so this counts first.
+    // `here!()` allocates, so results are unwrapped outside of `count`.
+    let (t, heap, dest) = count(|| s.transcode_to::<ZeroTerm, Utf16, Counting>());
+    let t = t.expect(here!());
+    assert_eq!((heap, dest), (0, 1));
+    assert_eq!(t.into_string().expect(here!()), "gªrçon 😀");
+
+    let (t, heap, dest) = count(|| s.transcode_to::<Slice, Utf16, Counting>());
+    let t = t.expect(here!());
+    assert_eq!((heap, dest), (0, 1));
+    assert_eq!(t.as_units().len(), 9);
+    assert_eq!(t.capacity(), 9);
+
+    // Byte-swapping knows its length exactly, so there's only one pass.
+    let (u, heap, dest) = count(|| t.transcode_to::<ZeroTerm, Utf16Le, Counting>());
+    let u = u.expect(here!());
+    assert_eq!((heap, dest), (0, 1));
+    assert_eq!(u.as_units().len(), 9);
+
+    // Failures are found before anything is allocated.
+    let bad = SeStr::<Slice, Utf8>::new(&[Utf8Unit(b'a'), Utf8Unit(0xff)]);
+    let (r, _, dest) = count(|| bad.transcode_to::<Slice, Utf16, Counting>().map(|_| ()));
+    assert!(r.is_err());
+    assert_eq!(dest, 0);
+
+    // An interior zero only turns up while filling; the allocation must not leak.
+    let z = SeStr::<Slice, Utf8>::new(&[Utf8Unit(b'a'), Utf8Unit(0), Utf8Unit(b'b')]);
+    let (r, _, dest) = count(|| z.transcode_to::<ZeroTerm, Utf16, Counting>().map(|_| ()));
+    assert!(r.is_err());
+    assert_eq!(dest, 1);
+
+    // ... whereas a trailing zero is just the terminator.
+    let z = SeStr::<Slice, Utf16>::new(&[Utf16Unit(0x61), Utf16Unit(0)]);
+    let t = z.transcode_to::<ZeroTerm, Utf8, Counting>().expect(here!());
+    assert_eq!(t.as_units(), &[Utf8Unit(b'a')]);
+}
+
+#[test]
+fn test_transcode_to_single_alloc() {
+    check_transcode_to();
+    // Every string has been dropped by now, including the one which failed part-way through.
+    assert_eq!(DEST_LIVE.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,87 @@
+extern crate strffi;
+
+mod common;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{Utf8, Utf8Unit, Utf16, Utf16Be, Utf16Le, Utf16Unit, Wide16, W16Unit, WinUnicode, WwUnit};
+use strffi::sea::{SeaCow, SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+use common::utf8;
+
+#[test]
+fn test_identity() {
+    let us = utf8("garçon");
+    let s = SeStr::<Slice, Utf8>::new(&us);
+    let cow = s.transcode_to_cow::<Utf8>().expect(here!());
+    assert!(cow.is_borrowed());
+    assert_eq!(cow.as_units().as_ptr(), us.as_ptr());
+    assert_eq!(&*cow, s);
+
+    // Zero-terminated strings are borrowed without their terminator.
+    let z = SeaString::<ZeroTerm, Utf8, Malloc>::new(&us).expect(here!());
+    let cow = z.transcode_to_cow::<Utf8>().expect(here!());
+    assert!(cow.is_borrowed());
+    assert_eq!(cow.as_units(), &us[..]);
+}
+
+#[test]
+fn test_transparent_pairs() {
+    let ws = vec![W16Unit(0x67), W16Unit(0xe7)];
+    let s = SeStr::<Slice, Wide16>::new(&ws);
+    let cow = s.transcode_to_cow::<WinUnicode>().expect(here!());
+    assert!(cow.is_borrowed());
+    assert_eq!(cow.as_units(), &[WwUnit(0x67), WwUnit(0xe7)]);
+
+    let us: Vec<_> = "garçon".encode_utf16().map(Utf16Unit).collect();
+    let s = SeStr::<Slice, Utf16>::new(&us);
+    let le = s.transcode_to_cow::<Utf16Le>().expect(here!());
+    let be = s.transcode_to_cow::<Utf16Be>().expect(here!());
+    assert_eq!(le.is_borrowed(), cfg!(target_endian="little"));
+    assert_eq!(be.is_borrowed(), cfg!(target_endian="big"));
+
+    // Either way, the contents are the same as `transcode_to` would produce.
+    let le_owned: SeaString<Slice, Utf16Le, Malloc> = s.transcode_to().expect(here!());
+    let be_owned: SeaString<Slice, Utf16Be, Malloc> = s.transcode_to().expect(here!());
+    assert_eq!(&*le, &*le_owned);
+    assert_eq!(&*be, &*be_owned);
+}
+
+#[test]
+fn test_owned() {
+    let us = utf8("garçon");
+    let s = SeStr::<Slice, Utf8>::new(&us);
+    let cow = s.transcode_to_cow::<Utf16>().expect(here!());
+    assert!(!cow.is_borrowed());
+    let expected: Vec<_> = "garçon".encode_utf16().map(Utf16Unit).collect();
+    assert_eq!(cow.as_units(), &expected[..]);
+
+    let us = vec![Utf8Unit(b'a'), Utf8Unit(0xff)];
+    assert!(SeStr::<Slice, Utf8>::new(&us).transcode_to_cow::<Utf16>().is_err());
+}
+
+#[test]
+fn test_into_owned() {
+    let us = utf8("abc");
+    let s = SeStr::<Slice, Utf8>::new(&us);
+
+    let owned = s.transcode_to_cow::<Utf8>().expect(here!()).into_owned().expect(here!());
+    assert_eq!(owned.as_units(), &us[..]);
+    assert!(owned.as_units().as_ptr() != us.as_ptr());
+
+    let owned = s.transcode_to_cow::<Utf16>().expect(here!()).into_owned().expect(here!());
+    assert_eq!(owned.as_units(), &[Utf16Unit(0x61), Utf16Unit(0x62), Utf16Unit(0x63)]);
+}
+
+#[test]
+fn test_debug() {
+    let us = utf8("ab");
+    let s = SeStr::<Slice, Utf8>::new(&us);
+    let borrowed = s.transcode_to_cow::<Utf8>().expect(here!());
+    assert_eq!(format!("{:?}", borrowed), format!("Borrowed({:?})", s));
+
+    let owned: SeaCow<Slice, Utf16> = s.transcode_to_cow().expect(here!());
+    let expected: SeaString<Slice, Utf16, Malloc> = s.transcode_to().expect(here!());
+    assert_eq!(format!("{:?}", owned), format!("Owned({:?})", expected));
+}
@@ -0,0 +1,138 @@
+extern crate strffi;
+
+mod common;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::encoding::{CheckedUnicode, Utf8, Utf8Unit, Utf16, Utf16Unit};
+use strffi::sea::SeStr;
+use strffi::structure::Slice;
+use common::utf8;
+
+#[test]
+fn test_transcode_into() {
+    let mut out = Vec::new();
+
+    let units = utf8("gªrçon😀".as_bytes());
+    {
+        let r = SeStr::<Slice, Utf8>::new(&units).transcode_into::<Utf16>(&mut out).expect(here!());
+        let expected: Vec<_> = "gªrçon😀".encode_utf16().map(Utf16Unit).collect();
+        assert_eq!(r, &expected[..]);
+    }
+    let cap = out.capacity();
+
+    // Reusing the buffer replaces its contents, without reallocating.
+    let units = utf8(b"abc");
+    {
+        let r = SeStr::<Slice, Utf8>::new(&units).transcode_into::<Utf16>(&mut out).expect(here!());
+        assert_eq!(r, &[Utf16Unit(0x61), Utf16Unit(0x62), Utf16Unit(0x63)][..]);
+    }
+    assert_eq!(out.capacity(), cap);
+
+    let units = utf8(b"");
+    assert_eq!(SeStr::<Slice, Utf8>::new(&units).transcode_into::<Utf16>(&mut out).expect(here!()), &[][..]);
+}
+
+#[test]
+fn test_transcode_into_invalid() {
+    let mut out = vec!['x'];
+    let units = utf8(b"ab\xffc");
+    assert!(SeStr::<Slice, Utf8>::new(&units).transcode_into::<CheckedUnicode>(&mut out).is_err());
+    assert!(out.is_empty());
+}
+
+#[test]
+fn test_transcode_into_slice() {
+    use strffi::sea::TranscodeInto;
+
+    let units = utf8("gªrçon😀".as_bytes());
+    let s = SeStr::<Slice, Utf8>::new(&units);
+    let expected: Vec<_> = "gªrçon😀".encode_utf16().map(Utf16Unit).collect();
+    assert_eq!(expected.len(), 8);
+
+    // Exactly fits.
+    let mut out = [Utf16Unit(0xffff); 8];
+    assert_eq!(s.transcode_into_slice::<Utf16>(&mut out).expect(here!()),
+        TranscodeInto { units_written: 8, truncated: false });
+    assert_eq!(&out[..], &expected[..]);
+
+    // One unit too small: the surrogate pair doesn't fit, so neither half is written.
+    let mut out = [Utf16Unit(0xffff); 7];
+    assert_eq!(s.transcode_into_slice::<Utf16>(&mut out).expect(here!()),
+        TranscodeInto { units_written: 6, truncated: true });
+    assert_eq!(&out[..6], &expected[..6]);
+
+    // Into UTF-8, cutting "ç" in half.
+    let units16 = expected.clone();
+    let s16 = SeStr::<Slice, Utf16>::new(&units16);
+    let mut out = [Utf8Unit(0); 5];
+    assert_eq!(s16.transcode_into_slice::<Utf8>(&mut out).expect(here!()),
+        TranscodeInto { units_written: 4, truncated: true });
+    assert_eq!(&out[..4], &utf8("gªr".as_bytes())[..]);
+
+    // A character which takes two source units and four output units, cut after its third.
+    let mut out = [Utf8Unit(0); 11];
+    assert_eq!(s16.transcode_into_slice::<Utf8>(&mut out).expect(here!()),
+        TranscodeInto { units_written: 8, truncated: true });
+    assert_eq!(&out[..8], &utf8("gªrçon".as_bytes())[..]);
+
+    // Long strings are cut just as cleanly.
+    let long = utf8("ç".repeat(10000).as_bytes());
+    let mut out = vec![Utf16Unit(0); 5000];
+    assert_eq!(SeStr::<Slice, Utf8>::new(&long).transcode_into_slice::<Utf16>(&mut out).expect(here!()),
+        TranscodeInto { units_written: 5000, truncated: true });
+    let mut out = vec![Utf8Unit(0); 4999];
+    assert_eq!(SeStr::<Slice, Utf8>::new(&long).transcode_into_slice::<Utf8>(&mut out).expect(here!()),
+        TranscodeInto { units_written: 4998, truncated: true });
+
+    // Empty buffer.
+    let mut out: [Utf16Unit; 0] = [];
+    assert_eq!(s.transcode_into_slice::<Utf16>(&mut out).expect(here!()),
+        TranscodeInto { units_written: 0, truncated: true });
+    let empty = utf8(b"");
+    assert_eq!(SeStr::<Slice, Utf8>::new(&empty).transcode_into_slice::<Utf16>(&mut out).expect(here!()),
+        TranscodeInto { units_written: 0, truncated: false });
+
+    // Errors are only reported in the part which fits.
+    let bad = utf8(b"ab\xffc");
+    let mut out = [Utf16Unit(0); 2];
+    assert_eq!(SeStr::<Slice, Utf8>::new(&bad).transcode_into_slice::<Utf16>(&mut out).expect(here!()),
+        TranscodeInto { units_written: 2, truncated: true });
+    let mut out = [Utf16Unit(0); 3];
+    assert!(SeStr::<Slice, Utf8>::new(&bad).transcode_into_slice::<Utf16>(&mut out).is_err());
+}
+
+#[test]
+fn test_transcode_into_slice_z() {
+    use strffi::sea::TranscodeInto;
+
+    let units = utf8(b"garcon");
+    let s = SeStr::<Slice, Utf8>::new(&units);
+
+    let mut out = [Utf16Unit(0xffff); 7];
+    assert_eq!(s.transcode_into_slice_z::<Utf16>(&mut out).expect(here!()),
+        TranscodeInto { units_written: 6, truncated: false });
+    assert_eq!(out[6], Utf16Unit(0));
+
+    let mut out = [Utf16Unit(0xffff); 6];
+    assert_eq!(s.transcode_into_slice_z::<Utf16>(&mut out).expect(here!()),
+        TranscodeInto { units_written: 5, truncated: true });
+    assert_eq!(out[5], Utf16Unit(0));
+
+    let mut out = [Utf16Unit(0xffff); 1];
+    assert_eq!(s.transcode_into_slice_z::<Utf16>(&mut out).expect(here!()),
+        TranscodeInto { units_written: 0, truncated: true });
+    assert_eq!(out[0], Utf16Unit(0));
+
+    let zero = utf8(b"ga\0rcon");
+    let mut out = [Utf16Unit(0xffff); 16];
+    assert!(SeStr::<Slice, Utf8>::new(&zero).transcode_into_slice_z::<Utf16>(&mut out).is_err());
+}
+
+#[test]
+#[should_panic]
+fn test_transcode_into_slice_z_empty() {
+    let units = utf8(b"garcon");
+    let mut out: [Utf16Unit; 0] = [];
+    let _ = SeStr::<Slice, Utf8>::new(&units).transcode_into_slice_z::<Utf16>(&mut out);
+}
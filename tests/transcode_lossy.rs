@@ -0,0 +1,47 @@
+extern crate strffi;
+
+mod common;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::{AllocError, Malloc};
+use strffi::encoding::{CheckedUnicode, Utf8, Utf16, Utf16Unit, Utf32, Utf32Unit};
+use strffi::sea::SeStr;
+use strffi::structure::{Slice, ZeroTerm};
+use common::utf8;
+
+fn utf16(s: &str) -> Vec<Utf16Unit> {
+    s.encode_utf16().map(Utf16Unit).collect()
+}
+
+#[test]
+fn test_transcode_to_lossy_recoverable() {
+    // UTF-8 decoding resumes after each invalid sequence.
+    let units = utf8(b"a\xffb\xc3");
+    let s = SeStr::<Slice, Utf8>::new(&units);
+    let t = s.transcode_to_lossy::<ZeroTerm, Utf16, Malloc>(Utf16Unit(b'?' as u16)).expect(here!());
+    assert_eq!(t.as_units(), &utf16("a?b?")[..]);
+
+    let units = [Utf32Unit(0x61), Utf32Unit(0xd800), Utf32Unit(0x110000), Utf32Unit(0x62)];
+    let s = SeStr::<Slice, Utf32>::new(&units);
+    let t = s.transcode_to_lossy::<Slice, CheckedUnicode, Malloc>('?').expect(here!());
+    assert_eq!(t.as_units(), &['a', '?', '?', 'b']);
+
+    // Valid strings are unaffected.
+    let units = utf8("gªrçon".as_bytes());
+    let s = SeStr::<Slice, Utf8>::new(&units);
+    let t = s.transcode_to_lossy::<Slice, Utf16, Malloc>(Utf16Unit(b'?' as u16)).expect(here!());
+    assert_eq!(t.as_units(), &utf16("gªrçon")[..]);
+}
+
+#[test]
+fn test_transcode_to_lossy_zero() {
+    let units = utf8(b"a\xffb");
+    let s = SeStr::<Slice, Utf8>::new(&units);
+    let r = s.transcode_to_lossy::<ZeroTerm, Utf16, Malloc>(Utf16Unit(0));
+    assert_eq!(r.err(), Some(AllocError::InteriorZeroAt(1)));
+
+    // ... but zeroes are fine in a slice.
+    let t = s.transcode_to_lossy::<Slice, Utf16, Malloc>(Utf16Unit(0)).expect(here!());
+    assert_eq!(t.as_units(), &[Utf16Unit(0x61), Utf16Unit(0), Utf16Unit(0x62)]);
+}
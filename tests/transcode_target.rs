@@ -0,0 +1,51 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::{Error, ZMbStr, ZMbCString, ZWCString, ZWStr};
+use strffi::alloc::Malloc;
+use strffi::encoding::{JniMtf8, Utf8Unit};
+use strffi::sea::SeaString;
+use strffi::structure::ZeroTerm;
+
+// Plain ASCII, so that these work under the default "C" locale.
+const WORD_MB: &'static [u8] = b"garcon\0";
+#[cfg(windows)]
+const WORD_W: &'static [u16] = &[0x67, 0x61, 0x72, 0x63, 0x6F, 0x6E, 0x00];
+#[cfg(not(windows))]
+const WORD_W: &'static [u32] = &[0x67, 0x61, 0x72, 0x63, 0x6F, 0x6E, 0x00];
+
+fn mb_to_wide(zmbstr: &ZMbStr) -> Result<ZWCString, Error> {
+    let zwcstr: ZWCString = zmbstr.transcode()?;
+    Ok(zwcstr)
+}
+
+#[test]
+fn test_transcode_infers_alias() {
+    let zmbstr = unsafe { ZMbStr::from_ptr(WORD_MB.as_ptr() as *const _).expect(here!()) };
+    let zwstr = unsafe { ZWStr::from_ptr(WORD_W.as_ptr() as *const _).expect(here!()) };
+    assert_eq!(&mb_to_wide(zmbstr).expect(here!()), zwstr);
+}
+
+#[test]
+fn test_transcode_infers_wrapper() {
+    let zmbstr = unsafe { ZMbStr::from_ptr(WORD_MB.as_ptr() as *const _).expect(here!()) };
+    let zwstr = unsafe { ZWStr::from_ptr(WORD_W.as_ptr() as *const _).expect(here!()) };
+    let zmbcstr: ZMbCString = zwstr.transcode().expect(here!());
+    assert_eq!(&zmbcstr, zmbstr);
+}
+
+#[test]
+fn test_to_wide_z() {
+    let zmbstr = unsafe { ZMbStr::from_ptr(WORD_MB.as_ptr() as *const _).expect(here!()) };
+    let zwstr = unsafe { ZWStr::from_ptr(WORD_W.as_ptr() as *const _).expect(here!()) };
+    assert_eq!(&zmbstr.to_wide_z().expect(here!()), zwstr);
+}
+
+#[test]
+fn test_to_utf8_z() {
+    let zjstr = SeaString::<ZeroTerm, JniMtf8, Malloc>::from_str("gar\u{e7}on").expect(here!());
+    let zu8str = zjstr.to_utf8_z().expect(here!());
+    let expected: Vec<_> = "gar\u{e7}on".bytes().map(Utf8Unit).collect();
+    assert_eq!(zu8str.as_units(), &expected[..]);
+}
@@ -0,0 +1,62 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{Utf8, Utf16, Utf16Unit};
+use strffi::sea::{ReplacementPolicy, SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+
+type Utf16String = SeaString<ZeroTerm, Utf16, Malloc>;
+
+/// An invalid byte in the middle, and a truncated sequence at the end.
+const MALFORMED: &'static [u8] = b"ga\xffr\xc3";
+
+fn source() -> &'static SeStr<Slice, Utf8> {
+    SeStr::from_bytes(MALFORMED)
+}
+
+fn utf16(s: &str) -> Vec<Utf16Unit> {
+    s.encode_utf16().map(Utf16Unit).collect()
+}
+
+fn offsets(issues: &[strffi::sea::TranscodeIssue]) -> Vec<Option<usize>> {
+    issues.iter().map(|issue| issue.at()).collect()
+}
+
+#[test]
+fn test_transcode_with_unicode() {
+    let (s, issues) = source().transcode_with::<ZeroTerm, Utf16, Malloc>(ReplacementPolicy::Unicode).expect(here!());
+    assert_eq!(s.as_units(), &utf16("ga\u{fffd}r\u{fffd}")[..]);
+    assert_eq!(offsets(&issues), vec![Some(2), None]);
+}
+
+#[test]
+fn test_transcode_with_char() {
+    let (s, issues): (Utf16String, _) = source().transcode_with(ReplacementPolicy::Char('?')).expect(here!());
+    assert_eq!(s.as_units(), &utf16("ga?r?")[..]);
+    assert_eq!(offsets(&issues), vec![Some(2), None]);
+    assert!(!issues[0].to_string().is_empty());
+}
+
+#[test]
+fn test_transcode_with_skip() {
+    let (s, issues): (Utf16String, _) = source().transcode_with(ReplacementPolicy::Skip).expect(here!());
+    assert_eq!(s.as_units(), &utf16("gar")[..]);
+    assert_eq!(offsets(&issues), vec![Some(2), None]);
+}
+
+#[test]
+fn test_transcode_with_stop() {
+    let (s, issues): (Utf16String, _) = source().transcode_with(ReplacementPolicy::StopAtError).expect(here!());
+    assert_eq!(s.as_units(), &utf16("ga")[..]);
+    assert_eq!(offsets(&issues), vec![Some(2)]);
+}
+
+#[test]
+fn test_transcode_with_valid() {
+    let src = SeStr::<Slice, Utf8>::from_bytes("gªrçon😀".as_bytes());
+    let (s, issues) = src.transcode_with::<Slice, Utf16, Malloc>(ReplacementPolicy::Unicode).expect(here!());
+    assert!(issues.is_empty());
+    assert_eq!(s.into_string().expect(here!()), "gªrçon😀");
+}
@@ -0,0 +1,42 @@
+extern crate strffi;
+
+use strffi::encoding::{MultiByte, MbUnit};
+use strffi::sea::SeStr;
+use strffi::structure::Slice;
+
+fn mb(s: &[u8]) -> Vec<MbUnit> {
+    s.iter().map(|&b| MbUnit(b as _)).collect()
+}
+
+const SP: MbUnit = MbUnit(b' ' as _);
+
+#[test]
+fn test_trim() {
+    let units = mb(b"  a b  ");
+    let s = SeStr::<Slice, MultiByte>::new(&units);
+    assert_eq!(s.trim_matches(SP).as_units(), &mb(b"a b")[..]);
+    assert_eq!(s.trim_start_matches(SP).as_units(), &mb(b"a b  ")[..]);
+    assert_eq!(s.trim_end_matches(SP).as_units(), &mb(b"  a b")[..]);
+}
+
+#[test]
+fn test_trim_all_matching() {
+    let units = mb(b"   ");
+    let s = SeStr::<Slice, MultiByte>::new(&units);
+    assert!(s.trim_matches(SP).as_units().is_empty());
+    assert!(s.trim_start_matches(SP).as_units().is_empty());
+    assert!(s.trim_end_matches(SP).as_units().is_empty());
+}
+
+#[test]
+fn test_trim_no_match() {
+    let units = mb(b"a b");
+    let s = SeStr::<Slice, MultiByte>::new(&units);
+    assert_eq!(s.trim_matches(SP), s);
+    assert_eq!(s.trim_start_matches(SP), s);
+    assert_eq!(s.trim_end_matches(SP), s);
+    assert_eq!(s.trim_matches(SP).as_units().as_ptr(), units.as_ptr());
+
+    let s = SeStr::<Slice, MultiByte>::new(&[]);
+    assert!(s.trim_matches(SP).as_units().is_empty());
+}
@@ -0,0 +1,57 @@
+extern crate strffi;
+
+mod common;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::{Malloc, Rust};
+use strffi::encoding::{Utf8, Utf16};
+use strffi::sea::SeaString;
+use strffi::structure::{Slice, ZeroTerm};
+use common::utf8;
+
+#[test]
+fn test_truncate_slice() {
+    let mut s = SeaString::<Slice, Utf8, Malloc>::new(&utf8(b"garcon")).expect(here!());
+    let ptr = s.as_units().as_ptr();
+    s.truncate(3);
+    assert_eq!(s.as_units(), &utf8(b"gar")[..]);
+    assert_eq!(s.as_units().as_ptr(), ptr);
+
+    s.truncate(10);
+    assert_eq!(s.as_units(), &utf8(b"gar")[..]);
+
+    // The removed units become spare capacity.
+    s.push_units(&utf8(b"den")).expect(here!());
+    assert_eq!(s.as_units(), &utf8(b"garden")[..]);
+    assert_eq!(s.as_units().as_ptr(), ptr);
+
+    s.truncate(0);
+    assert_eq!(s.as_units(), &[][..]);
+}
+
+#[test]
+fn test_truncate_zero_term() {
+    let mut s = SeaString::<ZeroTerm, Utf8, Malloc>::new(&utf8(b"garcon")).expect(here!());
+    unsafe { s.truncate_unsafe(3) };
+    assert_eq!(s.as_units(), &utf8(b"gar")[..]);
+    assert_eq!(s.as_units_with_term(), &utf8(b"gar\0")[..]);
+
+    unsafe { s.truncate_unsafe(3) };
+    unsafe { s.truncate_unsafe(4) };
+    assert_eq!(s.as_units_with_term(), &utf8(b"gar\0")[..]);
+
+    unsafe { s.truncate_unsafe(0) };
+    assert_eq!(s.as_units_with_term(), &utf8(b"\0")[..]);
+    assert_eq!(s, SeaString::<ZeroTerm, Utf8, Malloc>::new(&[]).expect(here!()));
+
+    s.push_units(&utf8(b"ok")).expect(here!());
+    assert_eq!(s.as_units(), &utf8(b"ok")[..]);
+}
+
+#[test]
+fn test_truncate_units() {
+    let mut s = SeaString::<Slice, Utf16, Rust>::from_str("gªrçon").expect(here!());
+    s.truncate(4);
+    assert_eq!(s.into_string().expect(here!()), "gªrç");
+}
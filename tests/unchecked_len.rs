@@ -0,0 +1,39 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{Utf16, Utf16Unit, Utf8, Utf8Unit};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+
+#[test]
+fn test_from_ptr_unchecked_len() {
+    let units: Vec<_> = b"gar\xc3\xa7on".iter().map(|&b| Utf8Unit(b)).collect();
+    let owned = SeaString::<ZeroTerm, Utf8, Malloc>::new(&units).expect(here!());
+
+    let s: &SeStr<Slice, Utf8> = unsafe {
+        SeStr::<ZeroTerm, Utf8>::from_ptr_unchecked_len(owned.as_ptr(), units.len())
+    }.expect(here!());
+    assert_eq!(s.as_units(), &units[..]);
+    assert_eq!(s.as_ptr().0 as usize, owned.as_ptr() as usize);
+    assert_eq!(&*s, &*owned.as_slice());
+}
+
+#[test]
+fn test_from_ptr_unchecked_len_empty() {
+    let units = [Utf16Unit(0)];
+    let s = unsafe { SeStr::<ZeroTerm, Utf16>::from_ptr_unchecked_len(units.as_ptr() as *const u16, 0) };
+    assert_eq!(s.expect(here!()).as_units(), &[][..]);
+}
+
+#[test]
+fn test_from_ptr_unchecked_len_invalid() {
+    let s = unsafe { SeStr::<ZeroTerm, Utf8>::from_ptr_unchecked_len(::std::ptr::null(), 0) };
+    assert!(s.is_none());
+
+    let buf = [0u16; 4];
+    let odd = (buf.as_ptr() as usize + 1) as *const u16;
+    let s = unsafe { SeStr::<ZeroTerm, Utf16>::from_ptr_unchecked_len(odd, 0) };
+    assert!(s.is_none());
+}
@@ -0,0 +1,123 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use std::cell::Cell;
+use std::fmt;
+use strffi::ZMbCString;
+use strffi::alloc::Malloc;
+use strffi::encoding::{Encoding, MbUnit, Unit, UnitDebug, Utf16, Utf16Unit};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+
+thread_local! {
+    static ZERO_CHECKS: Cell<usize> = Cell::new(0);
+}
+
+fn zero_checks() -> usize {
+    ZERO_CHECKS.with(|n| n.replace(0))
+}
+
+/**
+An encoding whose units count how many times they are checked for being zero, so that we can tell how much of a zero-terminated string was traversed.
+*/
+enum Counted {}
+
+impl Encoding for Counted {
+    type Unit = CountedUnit;
+    type FfiUnit = u8;
+    const MAX_UNITS_PER_CHAR: usize = 1;
+
+    fn debug_prefix() -> &'static str { "Cnt" }
+
+    fn static_zeroes() -> &'static [CountedUnit] {
+        const ZEROES: &'static [CountedUnit] = &[CountedUnit(0), CountedUnit(0)];
+        ZEROES
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(C)]
+struct CountedUnit(u8);
+
+impl Unit for CountedUnit {
+    fn zero() -> Self { CountedUnit(0) }
+
+    fn is_zero(&self) -> bool {
+        ZERO_CHECKS.with(|n| n.set(n.get() + 1));
+        self.0 == 0
+    }
+
+    const BITS: u32 = 8;
+
+    fn to_raw(&self) -> u32 { self.0 as u32 }
+
+    fn from_raw(v: u32) -> Option<Self> {
+        if v <= 0xff { Some(CountedUnit(v as u8)) } else { None }
+    }
+}
+
+impl UnitDebug for CountedUnit {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "\\x{:02x}", self.0)
+    }
+}
+
+#[test]
+fn test_zero_term_is_empty() {
+    const LEN: usize = 1 << 20;
+    let mut units = vec![CountedUnit(b'a'); LEN];
+    units.push(CountedUnit(0));
+    let s = unsafe { SeStr::<ZeroTerm, Counted>::from_ptr(units.as_ptr() as *const u8) }.expect(here!());
+
+    zero_checks();
+    assert!(!s.is_empty());
+    assert_eq!(zero_checks(), 1);
+
+    assert_eq!(s.unit_len(), LEN);
+    assert_eq!(zero_checks(), LEN + 1);
+
+    let units = [CountedUnit(0), CountedUnit(b'a')];
+    let s = unsafe { SeStr::<ZeroTerm, Counted>::from_ptr(units.as_ptr() as *const u8) }.expect(here!());
+    assert!(s.is_empty());
+    assert_eq!(s.unit_len(), 0);
+}
+
+#[test]
+fn test_slice() {
+    let units = [Utf16Unit(0x61), Utf16Unit(0), Utf16Unit(0x62)];
+    let s = SeStr::<Slice, Utf16>::new(&units);
+    assert_eq!(s.len(), 3);
+    assert_eq!(s.unit_len(), 3);
+    assert!(!s.is_empty());
+
+    let s = SeStr::<Slice, Utf16>::new(&[]);
+    assert_eq!(s.len(), 0);
+    assert!(s.is_empty());
+
+    let s = SeaString::<Slice, Utf16, Malloc>::new(&units).expect(here!());
+    assert_eq!(s.len(), 3);
+    assert!(!s.is_empty());
+}
+
+#[test]
+fn test_zero_term() {
+    let s = SeaString::<ZeroTerm, Utf16, Malloc>::new(&[Utf16Unit(0x61), Utf16Unit(0x62)]).expect(here!());
+    assert_eq!(s.unit_len(), 2);
+    assert!(!s.is_empty());
+
+    let s = SeaString::<ZeroTerm, Utf16, Malloc>::new(&[]).expect(here!());
+    assert_eq!(s.unit_len(), 0);
+    assert!(s.is_empty());
+}
+
+#[test]
+fn test_zmb() {
+    let s = ZMbCString::new(&[MbUnit(b'h' as _), MbUnit(b'i' as _)]).expect(here!());
+    assert_eq!(s.unit_len(), 2);
+    assert!(!s.is_empty());
+
+    let s = ZMbCString::default();
+    assert_eq!(s.unit_len(), 0);
+    assert!(s.is_empty());
+}
@@ -0,0 +1,39 @@
+extern crate strffi;
+
+use strffi::encoding::{Unit, CheckedUtf8, Encoding, MbUnit, Utf8Unit, Utf16Unit, Utf16BeUnit, Utf32Unit, W16Unit, W32Unit};
+
+#[test]
+fn test_bits() {
+    assert_eq!(<MbUnit as Unit>::BITS, 8);
+    assert_eq!(<Utf8Unit as Unit>::BITS, 8);
+    assert_eq!(<Utf16Unit as Unit>::BITS, 16);
+    assert_eq!(<Utf16BeUnit as Unit>::BITS, 16);
+    assert_eq!(<W16Unit as Unit>::BITS, 16);
+    assert_eq!(<W32Unit as Unit>::BITS, 32);
+    assert_eq!(<Utf32Unit as Unit>::BITS, 32);
+    assert_eq!(<char as Unit>::BITS, 21);
+}
+
+#[test]
+fn test_round_trip() {
+    assert_eq!(MbUnit(-1i8 as _).to_raw(), 0xff);
+    assert_eq!(MbUnit::from_raw(0xff), Some(MbUnit(-1i8 as _)));
+    assert_eq!(MbUnit::from_raw(0x100), None);
+
+    assert_eq!(Utf16BeUnit::new(0xd83d).to_raw(), 0xd83d);
+    assert_eq!(Utf16BeUnit::from_raw(0xd83d), Some(Utf16BeUnit::new(0xd83d)));
+    assert_eq!(Utf16Unit::from_raw(0x10000), None);
+
+    assert_eq!(W32Unit::from_raw(0xffff_ffff), Some(W32Unit(0xffff_ffffu32 as _)));
+
+    assert_eq!('😀'.to_raw(), 0x1f600);
+    assert_eq!(char::from_raw(0x1f600), Some('😀'));
+    assert_eq!(char::from_raw(0xd800), None);
+    assert_eq!(char::from_raw(0x110000), None);
+}
+
+#[test]
+fn test_checked_from_raw() {
+    // Checked units can only be produced by validation.
+    assert_eq!(<CheckedUtf8 as Encoding>::Unit::from_raw(b'a' as u32), None);
+}
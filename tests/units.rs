@@ -0,0 +1,29 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::encoding::{Utf8, Utf8Unit};
+use strffi::sea::{SeStr, Units};
+use strffi::structure::{Slice, ZeroTerm};
+
+#[test]
+fn test_units_zero_term() {
+    let buf: Vec<_> = b"garcon\0ignored\0".iter().map(|&b| Utf8Unit(b)).collect();
+    let s = unsafe { SeStr::<ZeroTerm, Utf8>::from_ptr(buf.as_ptr() as *const u8) }.expect(here!());
+
+    let units: Units<ZeroTerm, Utf8> = s.units();
+    assert_eq!(units.collect::<Vec<_>>(), &buf[..6]);
+
+    // Early exit.
+    assert_eq!(s.units().position(|u| u == Utf8Unit(b'c')), Some(3));
+    assert_eq!(s.units().position(|u| u == Utf8Unit(b'i')), None);
+}
+
+#[test]
+fn test_units_slice() {
+    let buf: Vec<_> = b"gar\0con".iter().map(|&b| Utf8Unit(b)).collect();
+    let s = SeStr::<Slice, Utf8>::new(&buf);
+    let units: Units<Slice, Utf8> = s.units();
+    assert_eq!(units.len(), 7);
+    assert_eq!(s.units().collect::<Vec<_>>(), buf);
+}
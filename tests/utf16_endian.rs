@@ -0,0 +1,96 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{Raw8Unit, Utf16, Utf16Unit, Utf16Le, Utf16LeUnit, Utf16Be, Utf16BeUnit};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::Slice;
+
+// "héllo", as little-endian and big-endian bytes.
+const HELLO_LE: &'static [u8] = &[0x68, 0x00, 0xE9, 0x00, 0x6C, 0x00, 0x6C, 0x00, 0x6F, 0x00];
+const HELLO_BE: &'static [u8] = &[0x00, 0x68, 0x00, 0xE9, 0x00, 0x6C, 0x00, 0x6C, 0x00, 0x6F];
+
+/*
+Copies bytes into a `u16`-aligned buffer, so the reinterpretation can't fail due to alignment.
+*/
+fn aligned(bytes: &[u8]) -> Vec<u16> {
+    let mut buf = vec![0u16; (bytes.len() + 1) / 2];
+    unsafe {
+        ::std::ptr::copy_nonoverlapping(bytes.as_ptr(), buf.as_mut_ptr() as *mut u8, bytes.len());
+    }
+    buf
+}
+
+fn raw8(buf: &[u16], len: usize) -> &[Raw8Unit] {
+    assert!(len <= buf.len() * 2);
+    unsafe { ::std::slice::from_raw_parts(buf.as_ptr() as *const Raw8Unit, len) }
+}
+
+#[test]
+fn test_decode_le() {
+    let buf = aligned(HELLO_LE);
+    let bytes = SeStr::<Slice, _>::new(raw8(&buf, HELLO_LE.len()));
+    let s = bytes.as_utf16le().expect(here!());
+    assert_eq!(s.as_units().len(), 5);
+    assert_eq!(s.into_string().expect(here!()), "héllo");
+}
+
+#[test]
+fn test_decode_be() {
+    let buf = aligned(HELLO_BE);
+    let bytes = SeStr::<Slice, _>::new(raw8(&buf, HELLO_BE.len()));
+    let s = bytes.as_utf16be().expect(here!());
+    assert_eq!(s.into_string().expect(here!()), "héllo");
+}
+
+#[test]
+fn test_reinterpret_rejects_odd_length() {
+    let buf = aligned(HELLO_LE);
+    let bytes = SeStr::<Slice, _>::new(raw8(&buf, HELLO_LE.len() - 1));
+    assert!(bytes.as_utf16le().is_none());
+    assert!(bytes.as_utf16be().is_none());
+}
+
+#[test]
+fn test_reinterpret_rejects_misaligned() {
+    let buf = aligned(&[0; 12]);
+    let bytes = SeStr::<Slice, _>::new(&raw8(&buf, 12)[1..11]);
+    assert!(bytes.as_utf16le().is_none());
+}
+
+#[test]
+fn test_swap_to_native() {
+    let buf = aligned(HELLO_LE);
+    let bytes = SeStr::<Slice, _>::new(raw8(&buf, HELLO_LE.len()));
+    let le = bytes.as_utf16le().expect(here!());
+    let native: SeaString<Slice, Utf16, Malloc> = le.transcode_to().expect(here!());
+    let expected: Vec<_> = "héllo".encode_utf16().map(Utf16Unit).collect();
+    assert_eq!(native.as_units(), &expected[..]);
+
+    let be: SeaString<Slice, Utf16Be, Malloc> = native.transcode_to().expect(here!());
+    let be_units: Vec<_> = be.as_units().iter().map(|u| u.get()).collect();
+    assert_eq!(be_units, "héllo".encode_utf16().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_encode_le() {
+    let s: SeaString<Slice, Utf16Le, Malloc> = SeaString::from_str("a😀").expect(here!());
+    let storage: Vec<_> = s.as_units().iter().map(|u| u.0).collect();
+    assert_eq!(storage, vec![0x0061u16.to_le(), 0xD83Du16.to_le(), 0xDE00u16.to_le()]);
+}
+
+#[test]
+fn test_decode_unpaired_surrogate() {
+    let units = [Utf16BeUnit::new(0x68), Utf16BeUnit::new(0xDC00)];
+    let r = SeStr::<Slice, Utf16Be>::new(&units).into_string();
+    assert!(r.is_err());
+}
+
+#[test]
+fn test_unit_debug_shows_logical_value() {
+    let units = [Utf16LeUnit::new(0x68), Utf16LeUnit::new(0xE9)];
+    assert_eq!(format!("{:?}", SeStr::<Slice, Utf16Le>::new(&units)), "SUtf16Le\"h\\u00e9\"");
+    let units = [Utf16BeUnit::new(0x68), Utf16BeUnit::new(0xE9)];
+    assert_eq!(format!("{:?}", SeStr::<Slice, Utf16Be>::new(&units)), "SUtf16Be\"h\\u00e9\"");
+}
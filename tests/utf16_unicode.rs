@@ -0,0 +1,44 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{TranscodeTo, UnitIter, CheckedUnicode, Utf8, Utf16, Utf16Unit};
+use strffi::encoding::conv::utf16::Utf16ToUniError;
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+
+const WORD: &'static str = "gªrçon😀!";
+
+fn utf16(s: &str) -> Vec<Utf16Unit> {
+    s.encode_utf16().map(Utf16Unit).collect()
+}
+
+fn decode(units: &[u16]) -> Result<String, Utf16ToUniError> {
+    let units: Vec<_> = units.iter().map(|&u| Utf16Unit(u)).collect();
+    TranscodeTo::<CheckedUnicode>::transcode(UnitIter::<Utf16, _>::new(units.into_iter())).collect()
+}
+
+#[test]
+fn test_round_trip() {
+    let units = utf16(WORD);
+    let sstr = SeStr::<Slice, Utf16>::new(&units);
+    assert_eq!(sstr.into_string().expect(here!()), WORD);
+
+    let zcstr = SeaString::<ZeroTerm, Utf16, Malloc>::from_str(WORD).expect(here!());
+    assert_eq!(zcstr.as_units(), &units[..]);
+    assert_eq!(zcstr.into_string().expect(here!()), WORD);
+
+    let u8s = SeaString::<Slice, Utf8, Malloc>::from_str(WORD).expect(here!());
+    let chars: Vec<char> = u8s.chars_lossy().collect();
+    let zcstr: SeaString<ZeroTerm, Utf16, Malloc> = SeStr::<Slice, CheckedUnicode>::new(&chars[..]).transcode_to().expect(here!());
+    assert_eq!(zcstr.as_units(), &units[..]);
+}
+
+#[test]
+fn test_unpaired_surrogates() {
+    assert_eq!(decode(&[0x61, 0xdc00, 0x62]), Err(Utf16ToUniError::InvalidAt(1)));
+    assert_eq!(decode(&[0x61, 0xd83d, 0x62]), Err(Utf16ToUniError::InvalidAt(1)));
+    assert_eq!(decode(&[0xd83d, 0xde00, 0xd83d]), Err(Utf16ToUniError::Incomplete));
+    assert_eq!(decode(&[0xd83d, 0xde00, 0x61, 0xdfff]), Err(Utf16ToUniError::InvalidAt(3)));
+}
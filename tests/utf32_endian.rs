@@ -0,0 +1,63 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{TranscodeTo, UnitIter, CheckedUnicode, Utf32, Utf32Unit,
+    Utf32Le, Utf32LeUnit, Utf32Be, Utf32BeUnit};
+use strffi::encoding::conv::utf32::Utf32ToUniError;
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::Slice;
+
+#[test]
+fn test_decode_le_and_be() {
+    let le: Vec<_> = "gªrçon😀".chars().map(|c| Utf32LeUnit::new(c as u32)).collect();
+    let be: Vec<_> = "gªrçon😀".chars().map(|c| Utf32BeUnit::new(c as u32)).collect();
+    assert_eq!(SeStr::<Slice, Utf32Le>::new(&le).into_string().expect(here!()), "gªrçon😀");
+    assert_eq!(SeStr::<Slice, Utf32Be>::new(&be).into_string().expect(here!()), "gªrçon😀");
+}
+
+#[test]
+fn test_decode_storage_order() {
+    // "é" as raw little-endian bytes.
+    let bytes = [0xE9u8, 0x00, 0x00, 0x00];
+    let unit = Utf32LeUnit(unsafe { ::std::mem::transmute::<[u8; 4], u32>(bytes) });
+    assert_eq!(unit.get(), 0xE9);
+    assert_eq!(SeStr::<Slice, Utf32Le>::new(&[unit]).into_string().expect(here!()), "é");
+}
+
+#[test]
+fn test_decode_out_of_range() {
+    let units = [Utf32LeUnit::new(0x67), Utf32LeUnit::new(0x6E), Utf32LeUnit::new(0x110000), Utf32LeUnit::new(0x67)];
+    let mut iter = TranscodeTo::<CheckedUnicode>::transcode(UnitIter::<Utf32Le, _>::new(units.iter().cloned()));
+    assert_eq!(iter.next(), Some(Ok('g')));
+    assert_eq!(iter.next(), Some(Ok('n')));
+    assert_eq!(iter.next(), Some(Err(Utf32ToUniError::InvalidAt(2))));
+    assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_decode_surrogate() {
+    let units = [Utf32BeUnit::new(0x67), Utf32BeUnit::new(0xD800)];
+    let mut iter = TranscodeTo::<CheckedUnicode>::transcode(UnitIter::<Utf32Be, _>::new(units.iter().cloned()));
+    assert_eq!(iter.next(), Some(Ok('g')));
+    assert_eq!(iter.next(), Some(Err(Utf32ToUniError::InvalidAt(1))));
+    assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_swap_to_native() {
+    // Invalid code points survive a swap untouched.
+    let units = [Utf32BeUnit::new(0x67), Utf32BeUnit::new(0x110000)];
+    let native: SeaString<Slice, Utf32, Malloc> = SeStr::<Slice, Utf32Be>::new(&units).transcode_to().expect(here!());
+    assert_eq!(native.as_units(), &[Utf32Unit(0x67), Utf32Unit(0x110000)]);
+
+    let le: SeaString<Slice, Utf32Le, Malloc> = native.transcode_to().expect(here!());
+    assert_eq!(le.as_units(), &[Utf32LeUnit::new(0x67), Utf32LeUnit::new(0x110000)]);
+}
+
+#[test]
+fn test_unit_debug_shows_logical_value() {
+    let units = [Utf32BeUnit::new(0x68), Utf32BeUnit::new(0x1F600)];
+    assert_eq!(format!("{:?}", SeStr::<Slice, Utf32Be>::new(&units)), "SUtf32Be\"h\\U0001f600\"");
+}
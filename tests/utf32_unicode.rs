@@ -0,0 +1,45 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{TranscodeTo, UnitIter, CheckedUnicode, Utf32, Utf32Unit};
+use strffi::encoding::conv::utf32::Utf32ToUniError;
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+
+const WORD: &'static str = "gªrçon😀!";
+
+fn utf32(s: &str) -> Vec<Utf32Unit> {
+    s.chars().map(|c| Utf32Unit(c as u32)).collect()
+}
+
+fn decode(units: &[u32]) -> Vec<Result<char, Utf32ToUniError>> {
+    let units: Vec<_> = units.iter().map(|&u| Utf32Unit(u)).collect();
+    TranscodeTo::<CheckedUnicode>::transcode(UnitIter::<Utf32, _>::new(units.into_iter())).collect()
+}
+
+#[test]
+fn test_round_trip() {
+    let units = utf32(WORD);
+    let sstr = SeStr::<Slice, Utf32>::new(&units);
+    assert_eq!(sstr.into_string().expect(here!()), WORD);
+
+    let zcstr = SeaString::<ZeroTerm, Utf32, Malloc>::from_str(WORD).expect(here!());
+    assert_eq!(zcstr.as_units(), &units[..]);
+    assert_eq!(zcstr.into_string().expect(here!()), WORD);
+}
+
+#[test]
+fn test_invalid() {
+    use Utf32ToUniError::InvalidAt;
+
+    assert_eq!(decode(&[0x61, 0xd800, 0x62]), vec![Ok('a'), Err(InvalidAt(1)), Ok('b')]);
+    assert_eq!(decode(&[0x110000, 0x10ffff]), vec![Err(InvalidAt(0)), Ok('\u{10ffff}')]);
+    assert_eq!(decode(&[0xdfff, 0xffff_ffff]), vec![Err(InvalidAt(0)), Err(InvalidAt(1))]);
+
+    let units = [Utf32Unit(0x61), Utf32Unit(0xd800)];
+    assert!(SeStr::<Slice, Utf32>::new(&units).into_string().is_err());
+    let lossy: String = SeStr::<Slice, Utf32>::new(&units).chars_lossy().collect();
+    assert_eq!(lossy, "a\u{fffd}");
+}
@@ -0,0 +1,74 @@
+extern crate strffi;
+
+mod common;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{TranscodeTo, UnitIter, CheckedUnicode, Utf8, Utf8Unit};
+use strffi::encoding::conv::utf8::Utf8ToUniError;
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+use common::utf8;
+
+const WORD: &'static str = "gªrçon😀";
+
+fn decode(bytes: &[u8]) -> Vec<Result<char, Utf8ToUniError>> {
+    TranscodeTo::<CheckedUnicode>::transcode(UnitIter::<Utf8, _>::new(utf8(bytes).into_iter())).collect()
+}
+
+#[test]
+fn test_round_trip() {
+    let units = utf8(b"g\xc2\xaar\xc3\xa7on\xf0\x9f\x98\x80\0");
+    let zstr = unsafe { SeStr::<ZeroTerm, Utf8>::from_ptr(units.as_ptr() as *const _).expect(here!()) };
+    assert_eq!(zstr.into_string().expect(here!()), WORD);
+
+    let zcstr = SeaString::<ZeroTerm, Utf8, Malloc>::from_str(WORD).expect(here!());
+    assert_eq!(zcstr.as_units(), &units[..units.len()-1]);
+    assert_eq!(&zcstr, zstr);
+
+    let scstr = SeaString::<Slice, Utf8, Malloc>::from_str("\u{7f}\u{80}\u{7ff}\u{800}\u{ffff}\u{10000}\u{10ffff}").expect(here!());
+    assert_eq!(scstr.into_string().expect(here!()), "\u{7f}\u{80}\u{7ff}\u{800}\u{ffff}\u{10000}\u{10ffff}");
+}
+
+#[test]
+fn test_invalid() {
+    use Utf8ToUniError::*;
+
+    // Stray continuation unit.
+    assert_eq!(decode(b"a\x80b"), vec![Ok('a'), Err(InvalidAt(1)), Ok('b')]);
+    // Overlong encodings.
+    assert_eq!(decode(b"\xc0\xaf"), vec![Err(InvalidAt(0)), Err(InvalidAt(1))]);
+    assert_eq!(decode(b"\xe0\x80\xaf"), vec![Err(InvalidAt(0)), Err(InvalidAt(1)), Err(InvalidAt(2))]);
+    // Encoded surrogate.
+    assert_eq!(decode(b"\xed\xa0\x80"), vec![Err(InvalidAt(0)), Err(InvalidAt(1)), Err(InvalidAt(2))]);
+    // Beyond U+10FFFF.
+    assert_eq!(decode(b"\xf4\x90\x80\x80")[0], Err(InvalidAt(0)));
+    assert_eq!(decode(b"\xf5"), vec![Err(InvalidAt(0))]);
+    // Truncated sequences.
+    assert_eq!(decode(b"a\xf0\x9f\x98"), vec![Ok('a'), Err(Incomplete)]);
+    assert_eq!(decode(b"\xe2\x82"), vec![Err(Incomplete)]);
+}
+
+#[test]
+fn test_recovery() {
+    use Utf8ToUniError::*;
+
+    // A truncated sequence doesn't swallow the unit which interrupted it.
+    assert_eq!(decode(b"\xf0\x9f\x98a\xc3\xa7\xff\xf0\x9f\x98\x80"),
+        vec![Err(InvalidAt(0)), Ok('a'), Ok('ç'), Err(InvalidAt(6)), Ok('😀')]);
+
+    let units = utf8(b"g\xffr\xc3on");
+    let sstr = SeStr::<Slice, Utf8>::new(&units);
+    assert!(sstr.into_string().is_err());
+    let lossy: String = sstr.transcode_to_iter::<CheckedUnicode>()
+        .map(|r| r.unwrap_or('\u{fffd}'))
+        .collect();
+    assert_eq!(lossy, "g\u{fffd}r\u{fffd}on");
+}
+
+#[test]
+fn test_encode() {
+    let units: Result<Vec<Utf8Unit>, _> = TranscodeTo::<Utf8>::transcode(UnitIter::<CheckedUnicode, _>::new(WORD.chars())).collect();
+    assert_eq!(units.expect(here!()), utf8(WORD.as_bytes()));
+}
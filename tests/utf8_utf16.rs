@@ -0,0 +1,88 @@
+extern crate strffi;
+
+mod common;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{TranscodeTo, UnitIter, CheckedUnicode, Utf8, Utf8Unit, Utf16, Utf16Unit};
+use strffi::encoding::conv::utf8::Utf8Utf16Error;
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+use common::utf8;
+
+const SAMPLES: &'static [&'static str] = &[
+    "",
+    "garcon",
+    "gªrçon😀",
+    "\u{0}\u{7f}\u{80}\u{7ff}\u{800}\u{ffff}\u{10000}\u{10ffff}",
+    "日本語のテキスト with ASCII 🎉 mixed in",
+];
+
+fn utf16(units: &[u16]) -> Vec<Utf16Unit> {
+    units.iter().map(|&u| Utf16Unit(u)).collect()
+}
+
+fn direct_8_to_16(units: &[Utf8Unit]) -> Vec<Result<Utf16Unit, Utf8Utf16Error>> {
+    TranscodeTo::<Utf16>::transcode(UnitIter::<Utf8, _>::new(units.iter().cloned())).collect()
+}
+
+fn direct_16_to_8(units: &[Utf16Unit]) -> Vec<Result<Utf8Unit, Utf8Utf16Error>> {
+    TranscodeTo::<Utf8>::transcode(UnitIter::<Utf16, _>::new(units.iter().cloned())).collect()
+}
+
+#[test]
+fn test_equivalence() {
+    for &sample in SAMPLES {
+        let u8s = utf8(sample.as_bytes());
+        let u16s: Vec<_> = sample.encode_utf16().map(Utf16Unit).collect();
+
+        // The composed path, via `CheckedUnicode`.
+        let composed_16: Vec<_> = SeStr::<Slice, Utf8>::new(&u8s).transcode_to_iter::<CheckedUnicode>()
+            .map(|r| r.expect(here!()))
+            .collect::<String>()
+            .encode_utf16().map(Utf16Unit)
+            .collect();
+        let composed_8: Vec<_> = SeStr::<Slice, Utf16>::new(&u16s).into_string().expect(here!())
+            .bytes().map(Utf8Unit)
+            .collect();
+
+        let direct_16: Result<Vec<_>, _> = direct_8_to_16(&u8s).into_iter().collect();
+        let direct_8: Result<Vec<_>, _> = direct_16_to_8(&u16s).into_iter().collect();
+        assert_eq!(direct_16.expect(here!()), composed_16, "{:?}", sample);
+        assert_eq!(direct_8.expect(here!()), composed_8, "{:?}", sample);
+    }
+}
+
+#[test]
+fn test_transcode_to() {
+    for &sample in SAMPLES {
+        let s8 = SeaString::<Slice, Utf8, Malloc>::from_str(sample).expect(here!());
+        let s16: SeaString<Slice, Utf16, Malloc> = s8.transcode_to().expect(here!());
+        assert_eq!(s16.into_string().expect(here!()), sample);
+        let back: SeaString<Slice, Utf8, Malloc> = s16.transcode_to().expect(here!());
+        assert_eq!(back.as_units(), s8.as_units());
+    }
+
+    let s8 = SeaString::<Slice, Utf8, Malloc>::from_str("gªrçon😀").expect(here!());
+    let s16: SeaString<ZeroTerm, Utf16, Malloc> = s8.transcode_to().expect(here!());
+    assert_eq!(s16.into_string().expect(here!()), "gªrçon😀");
+}
+
+#[test]
+fn test_utf8_errors() {
+    use Utf8Utf16Error::*;
+
+    assert_eq!(direct_8_to_16(&utf8(b"a\xffb")), vec![Ok(Utf16Unit(0x61)), Err(InvalidAt(1)), Ok(Utf16Unit(0x62))]);
+    assert_eq!(direct_8_to_16(&utf8(b"ab\xf0\x9f\x98a")), vec![Ok(Utf16Unit(0x61)), Ok(Utf16Unit(0x62)), Err(InvalidAt(2)), Ok(Utf16Unit(0x61))]);
+    assert_eq!(direct_8_to_16(&utf8(b"\xf0\x9f\x98\x80\xe2\x82")), vec![Ok(Utf16Unit(0xd83d)), Ok(Utf16Unit(0xde00)), Err(Incomplete)]);
+}
+
+#[test]
+fn test_utf16_errors() {
+    use Utf8Utf16Error::*;
+
+    assert_eq!(direct_16_to_8(&utf16(&[0x61, 0xdc00, 0x62])), vec![Ok(Utf8Unit(0x61)), Err(InvalidAt(1))]);
+    assert_eq!(direct_16_to_8(&utf16(&[0xe7, 0xd83d, 0x62])), vec![Ok(Utf8Unit(0xc3)), Ok(Utf8Unit(0xa7)), Err(InvalidAt(1))]);
+    assert_eq!(direct_16_to_8(&utf16(&[0x61, 0xd83d])), vec![Ok(Utf8Unit(0x61)), Err(Incomplete)]);
+}
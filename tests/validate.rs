@@ -0,0 +1,88 @@
+extern crate strffi;
+
+use strffi::encoding::{EncodingError, Raw8, Utf8, Utf16, Utf16Unit, Utf16Be, Utf16BeUnit, Utf32, Utf32Unit, Wide16, W16Unit};
+use strffi::sea::SeStr;
+use strffi::structure::Slice;
+
+fn utf8(bytes: &[u8]) -> Result<(), EncodingError> {
+    SeStr::<Slice, Utf8>::from_bytes(bytes).validate_encoding()
+}
+
+fn utf16(units: &[u16]) -> Result<(), EncodingError> {
+    let units: Vec<_> = units.iter().map(|&u| Utf16Unit(u)).collect();
+    SeStr::<Slice, Utf16>::new(&units).validate_encoding()
+}
+
+fn utf32(units: &[u32]) -> Result<(), EncodingError> {
+    let units: Vec<_> = units.iter().map(|&u| Utf32Unit(u)).collect();
+    SeStr::<Slice, Utf32>::new(&units).validate_encoding()
+}
+
+#[test]
+fn test_utf8_corpus() {
+    let corpus: &[(&[u8], Result<(), EncodingError>)] = &[
+        (b"", Ok(())),
+        (b"g\xc2\xaar\xc3\xa7on\xf0\x9f\x98\x80", Ok(())),
+        (b"a\0b", Ok(())),
+        (b"ab\xff", Err(EncodingError::new(2, Some(1)))),
+        (b"ab\x80cd", Err(EncodingError::new(2, Some(1)))),
+        (b"a\xc0\x80", Err(EncodingError::new(1, Some(1)))),
+        (b"a\xed\xa0\x80", Err(EncodingError::new(1, Some(1)))),
+        (b"a\xf4\x90\x80\x80", Err(EncodingError::new(1, Some(1)))),
+        (b"a\xe2\x82z", Err(EncodingError::new(1, Some(2)))),
+        (b"a\xe2\x82", Err(EncodingError::new(1, None))),
+        (b"a\xf0\x9f\x98", Err(EncodingError::new(1, None))),
+    ];
+
+    for &(bytes, expected) in corpus {
+        assert_eq!(utf8(bytes), expected, "{:?}", bytes);
+    }
+}
+
+#[test]
+fn test_utf16_corpus() {
+    let corpus: &[(&[u16], Result<(), EncodingError>)] = &[
+        (&[], Ok(())),
+        (&[0x61, 0xd83d, 0xde00, 0x62], Ok(())),
+        (&[0x61, 0xde00, 0x62], Err(EncodingError::new(1, Some(1)))),
+        (&[0x61, 0x62, 0xd83d, 0x62], Err(EncodingError::new(2, Some(1)))),
+        (&[0x61, 0xd83d, 0xd83d, 0xde00], Err(EncodingError::new(1, Some(1)))),
+        (&[0x61, 0xd83d], Err(EncodingError::new(1, None))),
+    ];
+
+    for &(units, expected) in corpus {
+        assert_eq!(utf16(units), expected, "{:?}", units);
+    }
+}
+
+#[test]
+fn test_utf32_corpus() {
+    let corpus: &[(&[u32], Result<(), EncodingError>)] = &[
+        (&[], Ok(())),
+        (&[0x61, 0x1f600, 0x10ffff], Ok(())),
+        (&[0x61, 0xd800], Err(EncodingError::new(1, Some(1)))),
+        (&[0x61, 0x62, 0x110000], Err(EncodingError::new(2, Some(1)))),
+        (&[0xffffffff], Err(EncodingError::new(0, Some(1)))),
+    ];
+
+    for &(units, expected) in corpus {
+        assert_eq!(utf32(units), expected, "{:?}", units);
+    }
+}
+
+#[test]
+fn test_other_encodings() {
+    let be = [Utf16BeUnit::new(0x61), Utf16BeUnit::new(0xdc00)];
+    assert_eq!(SeStr::<Slice, Utf16Be>::new(&be).validate_encoding(), Err(EncodingError::new(1, Some(1))));
+
+    let w16 = [W16Unit(0xd83d), W16Unit(0xde00)];
+    assert_eq!(SeStr::<Slice, Wide16>::new(&w16).validate_encoding(), Ok(()));
+
+    assert_eq!(SeStr::<Slice, Raw8>::from_bytes(b"\xff\xfe").validate_encoding(), Ok(()));
+}
+
+#[test]
+fn test_error_display() {
+    assert_eq!(EncodingError::new(3, Some(2)).to_string(), "invalid sequence of 2 units at offset 3");
+    assert_eq!(EncodingError::new(3, None).to_string(), "incomplete sequence at offset 3");
+}
@@ -0,0 +1,140 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{TranscodeTo, UnitIter, CheckedUnicode, Wide16, W16Unit, Wide32, W32Unit, WinUnicode, WwUnit};
+use strffi::encoding::conv::WcToUniError;
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::Slice;
+
+const WORD: &'static str = "gªrçon😀";
+
+#[test]
+fn test_wide16_round_trip() {
+    let s = SeaString::<Slice, Wide16, Malloc>::from_str(WORD).expect(here!());
+    let expected: Vec<_> = WORD.encode_utf16().map(W16Unit).collect();
+    assert_eq!(s.as_units(), &expected[..]);
+    assert_eq!(s.into_string().expect(here!()), WORD);
+}
+
+#[test]
+fn test_wide32_round_trip() {
+    let s = SeaString::<Slice, Wide32, Malloc>::from_str(WORD).expect(here!());
+    let expected: Vec<_> = WORD.chars().map(|c| W32Unit(c as u32 as _)).collect();
+    assert_eq!(s.as_units(), &expected[..]);
+    assert_eq!(s.into_string().expect(here!()), WORD);
+}
+
+#[test]
+fn test_wide16_invalid() {
+    let units = [W16Unit(0x67), W16Unit(0xDC00)];
+    let mut iter = TranscodeTo::<CheckedUnicode>::transcode(UnitIter::<Wide16, _>::new(units.iter().cloned()));
+    assert_eq!(iter.next(), Some(Ok('g')));
+    assert_eq!(iter.next(), Some(Err(WcToUniError::InvalidAt(1))));
+    assert_eq!(iter.next(), None);
+
+    let units = [W16Unit(0x67), W16Unit(0xD83D)];
+    let mut iter = TranscodeTo::<CheckedUnicode>::transcode(UnitIter::<Wide16, _>::new(units.iter().cloned()));
+    assert_eq!(iter.next(), Some(Ok('g')));
+    assert_eq!(iter.next(), Some(Err(WcToUniError::Incomplete)));
+    assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_wide16_surrogate_bounds() {
+    let decode = |units: &[u16]| -> Vec<Result<char, WcToUniError>> {
+        let units: Vec<_> = units.iter().map(|&u| W16Unit(u)).collect();
+        TranscodeTo::<CheckedUnicode>::transcode(UnitIter::<Wide16, _>::new(units.into_iter())).collect()
+    };
+
+    // Both ends of the high and low surrogate ranges pair up.
+    assert_eq!(decode(&[0xD800, 0xDC00]), vec![Ok('\u{10000}')]);
+    assert_eq!(decode(&[0xD800, 0xDFFF]), vec![Ok('\u{103FF}')]);
+    assert_eq!(decode(&[0xDBFF, 0xDC00]), vec![Ok('\u{10FC00}')]);
+    assert_eq!(decode(&[0xDBFF, 0xDFFF]), vec![Ok('\u{10FFFF}')]);
+
+    // The units either side of the surrogates are ordinary characters.
+    assert_eq!(decode(&[0xD7FF, 0xE000]), vec![Ok('\u{D7FF}'), Ok('\u{E000}')]);
+
+    // A high surrogate followed by anything other than a low surrogate is reported at the high surrogate, and decoding resumes with the unit after it.
+    assert_eq!(decode(&[0xD83D, 0xDE00, 0x61, 0xDBFF, 0x62]),
+        vec![Ok('\u{1F600}'), Ok('a'), Err(WcToUniError::InvalidAt(3)), Ok('b')]);
+    assert_eq!(decode(&[0x61, 0xDBFF, 0xDBFF, 0xDC00]),
+        vec![Ok('a'), Err(WcToUniError::InvalidAt(1)), Ok('\u{10FC00}')]);
+    assert_eq!(decode(&[0x61, 0xD800, 0xE000]),
+        vec![Ok('a'), Err(WcToUniError::InvalidAt(1)), Ok('\u{E000}')]);
+
+    // A low surrogate can never start a character.
+    assert_eq!(decode(&[0xD83D, 0xDE00, 0xDFFF]),
+        vec![Ok('\u{1F600}'), Err(WcToUniError::InvalidAt(2))]);
+    assert_eq!(decode(&[0xDBFF]), vec![Err(WcToUniError::Incomplete)]);
+
+    // `WinUnicode` decodes through the same path on its way to `Wide32`.
+    let ww: Vec<_> = [0x61, 0xDBFF, 0xDFFF].iter().map(|&u| WwUnit(u)).collect();
+    let w32: SeaString<Slice, Wide32, Malloc> = SeStr::<Slice, WinUnicode>::new(&ww).transcode_to().expect(here!());
+    assert_eq!(w32.as_units(), &[W32Unit(0x61), W32Unit(0x10FFFF)]);
+    let ww: Vec<_> = [0x61, 0xDBFF, 0x62].iter().map(|&u| WwUnit(u)).collect();
+    let r: Result<Vec<_>, _> = TranscodeTo::<Wide32>::transcode(UnitIter::<WinUnicode, _>::new(ww.into_iter())).collect();
+    assert_eq!(r, Err(WcToUniError::InvalidAt(1)));
+}
+
+#[test]
+fn test_wide32_invalid() {
+    let units = [W32Unit(0x67), W32Unit(0x110000)];
+    let mut iter = TranscodeTo::<CheckedUnicode>::transcode(UnitIter::<Wide32, _>::new(units.iter().cloned()));
+    assert_eq!(iter.next(), Some(Ok('g')));
+    assert_eq!(iter.next(), Some(Err(WcToUniError::InvalidAt(1))));
+    assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_error_offsets() {
+    use strffi::encoding::conv::ErrorOffset;
+
+    let wide16_err = |units: &[u16]| {
+        let units: Vec<_> = units.iter().map(|&u| W16Unit(u)).collect();
+        let err = SeStr::<Slice, Wide16>::new(&units).into_string().err().expect(here!());
+        err.downcast_ref::<WcToUniError>().expect(here!()).offset()
+    };
+    let wide32_err = |units: &[u32]| {
+        let units: Vec<_> = units.iter().map(|&u| W32Unit(u as _)).collect();
+        let err = SeStr::<Slice, Wide32>::new(&units).into_string().err().expect(here!());
+        err.downcast_ref::<WcToUniError>().expect(here!()).offset()
+    };
+
+    // Offsets count units, so each pair before the error counts twice.
+    assert_eq!(wide16_err(&[0xD83D, 0xDE00, 0xD83D, 0xDE00, 0xDC00]), Some(4));
+    assert_eq!(wide16_err(&[0x61, 0xD83D, 0xDE00, 0xD83D, 0x62]), Some(3));
+    assert_eq!(wide16_err(&[0xD83D, 0xDE00, 0xD83D, 0xD83D, 0xDE00]), Some(2));
+    assert_eq!(wide16_err(&[0xD83D, 0xDE00, 0xD83D]), None);
+
+    assert_eq!(wide32_err(&[0x1F600, 0x61, 0xD800]), Some(2));
+    assert_eq!(wide32_err(&[0x1F600, 0xDFFF, 0x61]), Some(1));
+    assert_eq!(wide32_err(&[0x61, 0x62, 0x63, 0x110000]), Some(3));
+}
+
+#[test]
+fn test_win_unicode() {
+    let ww: Vec<_> = WORD.encode_utf16().map(WwUnit).collect();
+    let ww = SeStr::<Slice, WinUnicode>::new(&ww);
+
+    let w16: SeaString<Slice, Wide16, Malloc> = ww.transcode_to().expect(here!());
+    let w32: SeaString<Slice, Wide32, Malloc> = ww.transcode_to().expect(here!());
+    assert_eq!(w16.into_string().expect(here!()), WORD);
+    assert_eq!(w32.into_string().expect(here!()), WORD);
+
+    let ww16: SeaString<Slice, WinUnicode, Malloc> = w16.transcode_to().expect(here!());
+    let ww32: SeaString<Slice, WinUnicode, Malloc> = w32.transcode_to().expect(here!());
+    assert_eq!(&*ww16, ww);
+    assert_eq!(&*ww32, ww);
+}
+
+#[test]
+fn test_debug_prefix() {
+    let w16 = [W16Unit(0x61)];
+    let w32 = [W32Unit(0x61)];
+    let (p16, p32) = if cfg!(windows) { ("W", "W32") } else { ("W16", "W") };
+    assert_eq!(format!("{:?}", SeStr::<Slice, Wide16>::new(&w16)), format!("S{}\"a\"", p16));
+    assert_eq!(format!("{:?}", SeStr::<Slice, Wide32>::new(&w32)), format!("S{}\"a\"", p32));
+}
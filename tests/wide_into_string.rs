@@ -0,0 +1,126 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{CheckedUnicode, Encoding, TranscodeTo, UnitIter, Utf16, Utf16Unit, Utf32, Utf32Unit, Wide16, W16Unit, Wide32, W32Unit};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+
+const TEXT: &'static [&'static str] = &[
+    "",
+    "a",
+    "gar\u{e7}on",
+    "\u{7f}\u{80}\u{7ff}\u{800}\u{d7ff}\u{e000}\u{ffff}\u{10000}\u{10ffff}",
+    "Съешь же ещё этих мягких французских булок",
+    "\u{1f600}",
+    "a\u{1f600}b\u{1f600}",
+];
+
+const UTF16: &'static [&'static [u16]] = &[
+    &[0xd800],
+    &[0xdc00],
+    &[0x61, 0xd800],
+    &[0x61, 0xdc00, 0x62],
+    &[0xd800, 0xd800, 0xdc00],
+    &[0xd83d, 0xde00, 0xde00],
+    &[0x61, 0xd83d, 0x62],
+    &[0xdbff, 0xdfff, 0xdfff, 0xdbff],
+];
+
+const UTF32: &'static [&'static [u32]] = &[
+    &[0xd800],
+    &[0x61, 0xdfff],
+    &[0x61, 0x110000, 0x62],
+    &[0x10ffff, 0xffff_ffff],
+    &[0x62, 0xdc00, 0xd800],
+];
+
+/**
+Checks that `into_string` agrees with collecting the transcoder one character at a time, including on the error offset.
+*/
+fn check<E>(units: &[E::Unit]) -> Option<String>
+where
+    E: Encoding,
+    for<'a> UnitIter<E, ::std::iter::Cloned<::std::slice::Iter<'a, E::Unit>>>: TranscodeTo<CheckedUnicode>,
+{
+    let s = SeStr::<Slice, E>::new(units);
+    let generic = s.transcode_to_iter::<CheckedUnicode>().collect::<Result<String, _>>();
+    match (s.into_string(), generic) {
+        (Ok(fast), Ok(generic)) => {
+            assert_eq!(fast, generic, "{:?}", s);
+            // The string was measured before it was allocated.
+            assert_eq!(fast.capacity(), fast.len(), "{:?}", s);
+            Some(fast)
+        },
+        (Err(fast), Err(generic)) => {
+            assert_eq!(fast.to_string(), generic.to_string(), "{:?}", s);
+            None
+        },
+        (fast, generic) => panic!("{:?}: {:?} != {:?}", s, fast, generic),
+    }
+}
+
+#[test]
+fn test_utf16() {
+    for text in TEXT {
+        let units: Vec<_> = text.encode_utf16().map(Utf16Unit).collect();
+        assert_eq!(check::<Utf16>(&units).as_ref().map(|s| &**s), Some(*text));
+        let units: Vec<_> = text.encode_utf16().map(W16Unit).collect();
+        assert_eq!(check::<Wide16>(&units).as_ref().map(|s| &**s), Some(*text));
+    }
+
+    for invalid in UTF16 {
+        let units: Vec<_> = invalid.iter().cloned().map(Utf16Unit).collect();
+        assert_eq!(check::<Utf16>(&units), None);
+        let units: Vec<_> = invalid.iter().cloned().map(W16Unit).collect();
+        assert_eq!(check::<Wide16>(&units), None);
+    }
+}
+
+#[test]
+fn test_utf32() {
+    for text in TEXT {
+        let units: Vec<_> = text.chars().map(|c| Utf32Unit(c as u32)).collect();
+        assert_eq!(check::<Utf32>(&units).as_ref().map(|s| &**s), Some(*text));
+        let units: Vec<_> = text.chars().map(|c| W32Unit(c as _)).collect();
+        assert_eq!(check::<Wide32>(&units).as_ref().map(|s| &**s), Some(*text));
+    }
+
+    for invalid in UTF32 {
+        let units: Vec<_> = invalid.iter().cloned().map(Utf32Unit).collect();
+        assert_eq!(check::<Utf32>(&units), None);
+        let units: Vec<_> = invalid.iter().map(|&u| W32Unit(u as _)).collect();
+        assert_eq!(check::<Wide32>(&units), None);
+    }
+}
+
+#[test]
+fn test_long() {
+    // Long enough to span several measuring chunks.
+    let text: String = (0..20000).map(|i| match i % 5 {
+        0 => 'a',
+        1 => '\u{e9}',
+        2 => '\u{20ac}',
+        _ => '\u{1f600}',
+    }).collect();
+    let units: Vec<_> = text.encode_utf16().map(Utf16Unit).collect();
+    assert_eq!(check::<Utf16>(&units).as_ref().map(|s| &**s), Some(&*text));
+    let units: Vec<_> = text.chars().map(|c| Utf32Unit(c as u32)).collect();
+    assert_eq!(check::<Utf32>(&units).as_ref().map(|s| &**s), Some(&*text));
+
+    let mut units: Vec<_> = text.encode_utf16().map(Utf16Unit).collect();
+    units[20000] = Utf16Unit(0xdc00);
+    assert_eq!(check::<Utf16>(&units), None);
+    let mut units: Vec<_> = text.chars().map(|c| Utf32Unit(c as u32)).collect();
+    units[10000] = Utf32Unit(0xd800);
+    assert_eq!(check::<Utf32>(&units), None);
+}
+
+#[test]
+fn test_zero_term() {
+    let s = SeaString::<ZeroTerm, Utf16, Malloc>::from_str("h\u{e9}llo \u{1f600}").expect(here!());
+    assert_eq!(s.into_string().expect(here!()), "h\u{e9}llo \u{1f600}");
+    let s = SeaString::<ZeroTerm, Utf32, Malloc>::from_str("h\u{e9}llo \u{1f600}").expect(here!());
+    assert_eq!(s.into_string().expect(here!()), "h\u{e9}llo \u{1f600}");
+}
@@ -0,0 +1,47 @@
+extern crate libc;
+#[macro_use] extern crate strffi;
+
+use std::mem;
+use strffi::encoding::{Wide, Wide16, Wide32, WUnit};
+use strffi::sea::SeStr;
+use strffi::structure::Slice;
+
+const WORD: &'static str = "gªrçon😀";
+
+#[test]
+fn test_wide_width() {
+    assert_eq!(Wide16::WIDTH, 16);
+    assert_eq!(Wide32::WIDTH, 32);
+    assert_eq!(Wide::WIDTH as usize, mem::size_of::<WUnit>() * 8);
+    assert_eq!(Wide::WIDTH as usize, mem::size_of::<libc::wchar_t>() * 8);
+    assert!(wide_is_utf16!() != wide_is_utf32!());
+    assert_eq!(wide_is_utf16!(), cfg!(windows));
+}
+
+#[cfg(windows)]
+#[test]
+fn test_wide_as_utf16() {
+    use strffi::encoding::{Utf16, Utf16Unit};
+
+    let units: Vec<_> = WORD.encode_utf16().map(WUnit).collect();
+    let w = SeStr::<Slice, Wide>::new(&units);
+    let u = w.as_utf16();
+    let expected: Vec<_> = WORD.encode_utf16().map(Utf16Unit).collect();
+    assert_eq!(u.as_units(), &expected[..]);
+    assert_eq!(u.as_wide(), w);
+    let _: &SeStr<Slice, Utf16> = u;
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_wide_as_utf32() {
+    use strffi::encoding::{Utf32, Utf32Unit};
+
+    let units: Vec<_> = WORD.chars().map(|c| WUnit(c as u32 as _)).collect();
+    let w = SeStr::<Slice, Wide>::new(&units);
+    let u = w.as_utf32();
+    let expected: Vec<_> = WORD.chars().map(|c| Utf32Unit(c as u32)).collect();
+    assert_eq!(u.as_units(), &expected[..]);
+    assert_eq!(u.as_wide(), w);
+    let _: &SeStr<Slice, Utf32> = u;
+}
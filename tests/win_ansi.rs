@@ -0,0 +1,68 @@
+#![cfg(windows)]
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::encoding::{Wide, WUnit, WinAnsi, AnsiUnit};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+use strffi::alloc::Malloc;
+
+extern "system" {
+    fn GetACP() -> u32;
+}
+
+const WORD: &'static str = "gªrçon€";
+const WORD_ANSI: &'static [u8] = b"g\xaar\xe7on\x80\0";
+
+/**
+Returns `true` if the ANSI code page is Windows-1252, which the tests know the contents of.
+*/
+fn is_1252() -> bool {
+    match unsafe { GetACP() } {
+        1252 => true,
+        cp => {
+            println!("skipping test: ANSI code page is {}", cp);
+            false
+        },
+    }
+}
+
+#[test]
+fn test_ansi_round_trip() {
+    if !is_1252() { return; }
+
+    let zastr = unsafe { SeStr::<ZeroTerm, WinAnsi>::from_ptr(WORD_ANSI.as_ptr() as *const _).expect(here!()) };
+    assert_eq!(zastr.into_string().expect(here!()), WORD);
+
+    let zacstr = SeaString::<ZeroTerm, WinAnsi, Malloc>::from_str(WORD).expect(here!());
+    assert_eq!(&zacstr, zastr);
+}
+
+#[test]
+fn test_ansi_wide() {
+    if !is_1252() { return; }
+
+    let zastr = unsafe { SeStr::<ZeroTerm, WinAnsi>::from_ptr(WORD_ANSI.as_ptr() as *const _).expect(here!()) };
+    let zwstr: SeaString<ZeroTerm, Wide, Malloc> = zastr.transcode_to().expect(here!());
+    let expected: Vec<_> = WORD.encode_utf16().map(WUnit).collect();
+    assert_eq!(zwstr.as_units(), &expected[..]);
+
+    let sastr: SeaString<Slice, WinAnsi, Malloc> = zwstr.transcode_to().expect(here!());
+    assert_eq!(sastr.as_units(), &zastr.as_units()[..]);
+}
+
+#[test]
+fn test_ansi_unrepresentable() {
+    if !is_1252() { return; }
+
+    assert!(SeaString::<ZeroTerm, WinAnsi, Malloc>::from_str("\u{2603}").is_err());
+    // This must not be replaced with the "best fit" `a`.
+    assert!(SeaString::<ZeroTerm, WinAnsi, Malloc>::from_str("\u{101}").is_err());
+}
+
+#[test]
+fn test_debug() {
+    let units = [AnsiUnit(b'a'), AnsiUnit(0xe7)];
+    assert_eq!(format!("{:?}", SeStr::<Slice, WinAnsi>::new(&units)), r#"SWa"a\xe7""#);
+}
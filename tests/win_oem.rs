@@ -0,0 +1,82 @@
+#![cfg(windows)]
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::encoding::{TranscodeTo, UnitIter, Wide, WUnit, WinOem, OemUnit};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+use strffi::alloc::Malloc;
+
+extern "system" {
+    fn GetOEMCP() -> u32;
+}
+
+// "┌─┐│" in code pages 437 and 850, which agree on the box-drawing characters.
+const BOX_OEM: &'static [u8] = b"\xda\xc4\xbf\xb3\0";
+const BOX: &'static str = "\u{250c}\u{2500}\u{2510}\u{2502}";
+
+/**
+Returns `true` if the OEM code page is one the tests know the contents of.
+*/
+fn known_oem_cp() -> bool {
+    match unsafe { GetOEMCP() } {
+        437 | 850 => true,
+        cp => {
+            println!("skipping test: OEM code page is {}", cp);
+            false
+        },
+    }
+}
+
+#[test]
+fn test_oem_to_unicode() {
+    if !known_oem_cp() { return; }
+
+    let zostr = unsafe { SeStr::<ZeroTerm, WinOem>::from_ptr(BOX_OEM.as_ptr() as *const _).expect(here!()) };
+    assert_eq!(zostr.into_string().expect(here!()), BOX);
+}
+
+#[test]
+fn test_oem_to_wide() {
+    if !known_oem_cp() { return; }
+
+    let zostr = unsafe { SeStr::<ZeroTerm, WinOem>::from_ptr(BOX_OEM.as_ptr() as *const _).expect(here!()) };
+    let zwstr: SeaString<ZeroTerm, Wide, Malloc> = zostr.transcode_to().expect(here!());
+    let expected: Vec<_> = BOX.encode_utf16().map(WUnit).collect();
+    assert_eq!(zwstr.as_units(), &expected[..]);
+}
+
+#[test]
+fn test_unicode_to_oem() {
+    if !known_oem_cp() { return; }
+
+    let zostr = SeaString::<ZeroTerm, WinOem, Malloc>::from_str(BOX).expect(here!());
+    let expected: Vec<_> = BOX_OEM[..BOX_OEM.len()-1].iter().map(|&u| OemUnit(u)).collect();
+    assert_eq!(zostr.as_units(), &expected[..]);
+
+    let units: Vec<_> = "gar\u{e7}on".encode_utf16().map(WUnit).collect();
+    let zwstr = SeStr::<Slice, Wide>::new(&units);
+    let sostr: SeaString<Slice, WinOem, Malloc> = zwstr.transcode_to().expect(here!());
+    assert_eq!(sostr.as_units(), &[OemUnit(b'g'), OemUnit(b'a'), OemUnit(b'r'), OemUnit(0x87), OemUnit(b'o'), OemUnit(b'n')][..]);
+}
+
+#[test]
+fn test_unrepresentable() {
+    if !known_oem_cp() { return; }
+
+    // Neither code page has a snowman, and it must not be replaced with a "best fit" character.
+    assert!(SeaString::<ZeroTerm, WinOem, Malloc>::from_str("a\u{2603}").is_err());
+
+    // Lone surrogates are rejected, rather than replaced.
+    let units = vec![WUnit(b'a' as u16), WUnit(0xd83d), WUnit(b'b' as u16)];
+    let r: Result<Vec<OemUnit>, _> = TranscodeTo::<WinOem>::transcode(UnitIter::<Wide, _>::new(units.into_iter())).collect();
+    assert!(r.is_err());
+}
+
+#[test]
+fn test_debug() {
+    let units = [OemUnit(b'a'), OemUnit(0xc4)];
+    let s = SeStr::<Slice, WinOem>::new(&units);
+    assert_eq!(format!("{:?}", s), r#"SWo"a\xc4""#);
+}
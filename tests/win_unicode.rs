@@ -0,0 +1,62 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::encoding::{Wide, WUnit, WinUnicode, WwUnit};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::ZeroTerm;
+use strffi::alloc::Malloc;
+
+// "g😀n"
+const WORD_WW: &'static [u16] = &[0x67, 0xD83D, 0xDE00, 0x6E, 0x00];
+#[cfg(windows)]
+const WORD_W: &'static [u16] = &[0x67, 0xD83D, 0xDE00, 0x6E, 0x00];
+#[cfg(not(windows))]
+const WORD_W: &'static [u32] = &[0x67, 0x1F600, 0x6E, 0x00];
+
+#[test]
+fn test_wide_to_win_unicode() {
+    let zwstr = unsafe { SeStr::<ZeroTerm, Wide>::from_ptr(WORD_W.as_ptr() as *const _).expect(here!()) };
+    let zwwstr: SeaString<ZeroTerm, WinUnicode, Malloc> = zwstr.transcode_to().expect(here!());
+    let expected: Vec<_> = WORD_WW[..WORD_WW.len()-1].iter().map(|&u| WwUnit(u)).collect();
+    assert_eq!(zwwstr.as_units(), &expected[..]);
+}
+
+#[test]
+fn test_win_unicode_to_wide() {
+    let zwwstr = unsafe { SeStr::<ZeroTerm, WinUnicode>::from_ptr(WORD_WW.as_ptr()).expect(here!()) };
+    let zwstr: SeaString<ZeroTerm, Wide, Malloc> = zwwstr.transcode_to().expect(here!());
+    let expected: Vec<_> = WORD_W[..WORD_W.len()-1].iter().map(|&u| WUnit(u as _)).collect();
+    assert_eq!(zwstr.as_units(), &expected[..]);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_win_unicode_to_wide_invalid() {
+    let lone: &[u16] = &[0x67, 0xDE00, 0x00];
+    let zwwstr = unsafe { SeStr::<ZeroTerm, WinUnicode>::from_ptr(lone.as_ptr()).expect(here!()) };
+    let r: Result<SeaString<ZeroTerm, Wide, Malloc>, _> = zwwstr.transcode_to();
+    assert!(r.is_err());
+}
+
+#[cfg(windows)]
+#[test]
+fn test_reinterpret() {
+    let zwstr = unsafe { SeStr::<ZeroTerm, Wide>::from_ptr(WORD_W.as_ptr()).expect(here!()) };
+    let zwwstr = zwstr.as_win_unicode();
+    assert_eq!(zwwstr.as_ptr(), zwstr.as_ptr());
+    assert_eq!(zwwstr.as_wide(), zwstr);
+}
+
+#[test]
+fn test_win_unicode_to_unicode() {
+    let zwwstr = unsafe { SeStr::<ZeroTerm, WinUnicode>::from_ptr(WORD_WW.as_ptr()).expect(here!()) };
+    assert_eq!(zwwstr.into_string().expect(here!()), "g\u{1f600}n");
+
+    let zwwcstr = SeaString::<ZeroTerm, WinUnicode, Malloc>::from_str("g\u{1f600}n").expect(here!());
+    assert_eq!(&zwwcstr, zwwstr);
+
+    let lone: &[u16] = &[0x67, 0xDE00, 0x00];
+    let zwwstr = unsafe { SeStr::<ZeroTerm, WinUnicode>::from_ptr(lone.as_ptr()).expect(here!()) };
+    assert!(zwwstr.into_string().is_err());
+}
@@ -5,6 +5,10 @@ extern crate strffi;
 macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
 
 use strffi::{ZMbStr, ZMbCString, ZWCString, ZWStr};
+use strffi::alloc::Malloc;
+use strffi::encoding::MultiByte;
+use strffi::sea::SeaString;
+use strffi::structure::Slice;
 
 fn set_1252() {
     unsafe {
@@ -52,3 +56,16 @@ fn test_garcon() {
         assert_eq!(&zwcstr, zwstr);
     }
 }
+
+#[test]
+fn test_wide_to_mb_slice() {
+    const WORD: &'static str = "gªrçon";
+    const WORD_MB: &'static [u8] = b"g\xaar\xe7on";
+
+    set_1252();
+
+    let zwcstr = ZWCString::from_str(WORD).expect(here!());
+    let mbs: SeaString<Slice, MultiByte, Malloc> = zwcstr.transcode_to().expect(here!());
+    let bytes: Vec<u8> = mbs.as_units().iter().map(|u| u.0 as u8).collect();
+    assert_eq!(&bytes[..], WORD_MB);
+}
@@ -0,0 +1,76 @@
+extern crate strffi;
+
+macro_rules! here { () => { &format!(concat!(file!(), ":{:?}"), line!()) } }
+
+use strffi::alloc::Malloc;
+use strffi::encoding::{CheckedUnicode, Utf16, Utf16Unit, Wide16, W16Unit, Wtf8, Wtf8Unit};
+use strffi::encoding::conv::wtf8::{Wtf8ToUniError, Wtf8ToUtf16Error};
+use strffi::sea::{SeStr, SeaString};
+use strffi::structure::{Slice, ZeroTerm};
+
+fn wtf8(bytes: &[u8]) -> Vec<Wtf8Unit> {
+    bytes.iter().map(|&b| Wtf8Unit(b)).collect()
+}
+
+#[test]
+fn test_lone_surrogate_round_trip() {
+    let units = [Utf16Unit(b'a' as u16), Utf16Unit(0xd800), Utf16Unit(b'b' as u16)];
+    let s = SeStr::<Slice, Utf16>::new(&units);
+    assert!(s.into_string().is_err());
+
+    let w: SeaString<ZeroTerm, Wtf8, Malloc> = s.transcode_to().expect(here!());
+    assert_eq!(w.as_raw_bytes(), b"a\xed\xa0\x80b");
+
+    let back: SeaString<Slice, Utf16, Malloc> = w.transcode_to().expect(here!());
+    assert_eq!(back.as_units(), &units[..]);
+}
+
+#[test]
+fn test_encode() {
+    let check = |units: &[u16], expected: &[u8]| {
+        let units: Vec<_> = units.iter().cloned().map(W16Unit).collect();
+        let w: SeaString<Slice, Wtf8, Malloc> = SeStr::<Slice, Wide16>::new(&units).transcode_to().expect(here!());
+        assert_eq!(w.as_raw_bytes(), expected, "{:x?}", units);
+    };
+
+    // Pairs are encoded as the character they represent.
+    check(&[0xd83d, 0xde00], "😀".as_bytes());
+    // A high surrogate at the end, or followed by anything but a low surrogate, is encoded on its own.
+    check(&[0xd83d], b"\xed\xa0\xbd");
+    check(&[0xd83d, 0x61], b"\xed\xa0\xbda");
+    check(&[0xd83d, 0xd83d, 0xde00], b"\xed\xa0\xbd\xf0\x9f\x98\x80");
+    // As is a low surrogate on its own.
+    check(&[0xde00, 0xd83d], b"\xed\xb8\x80\xed\xa0\xbd");
+    check(&[], b"");
+}
+
+#[test]
+fn test_decode_invalid() {
+    let to_utf16 = |bytes: &[u8]| -> Result<Vec<Utf16Unit>, Wtf8ToUtf16Error> {
+        let units = wtf8(bytes);
+        SeStr::<Slice, Wtf8>::new(&units).transcode_to_iter::<Utf16>().collect()
+    };
+
+    // An encoded surrogate pair should have been a single supplementary character.
+    assert_eq!(to_utf16(b"a\xed\xa0\xbd\xed\xb8\x80"), Err(Wtf8ToUtf16Error::InvalidAt(4)));
+    // But a low surrogate followed by a high surrogate is fine.
+    assert_eq!(to_utf16(b"\xed\xb8\x80\xed\xa0\xbd"), Ok(vec![Utf16Unit(0xde00), Utf16Unit(0xd83d)]));
+    assert_eq!(to_utf16(b"a\xff"), Err(Wtf8ToUtf16Error::InvalidAt(1)));
+    assert_eq!(to_utf16(b"a\xc0\x80"), Err(Wtf8ToUtf16Error::InvalidAt(1)));
+    assert_eq!(to_utf16(b"a\xed\xa0"), Err(Wtf8ToUtf16Error::Incomplete));
+}
+
+#[test]
+fn test_unicode() {
+    let units = wtf8("gªrçon 😀".as_bytes());
+    let s = SeStr::<Slice, Wtf8>::new(&units);
+    assert_eq!(s.into_string().expect(here!()), "gªrçon 😀");
+
+    let w = SeaString::<Slice, Wtf8, Malloc>::from_str("gªrçon 😀").expect(here!());
+    assert_eq!(w.as_raw_bytes(), "gªrçon 😀".as_bytes());
+
+    // Unpaired surrogates cannot be turned into characters.
+    let units = wtf8(b"ab\xed\xa0\x80");
+    let r: Result<String, _> = SeStr::<Slice, Wtf8>::new(&units).transcode_to_iter::<CheckedUnicode>().collect();
+    assert_eq!(r, Err(Wtf8ToUniError::InvalidAt(2)));
+}